@@ -1,14 +1,17 @@
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use rand::Rng;
 use secrecy::SecretString;
 
 use crate::callback::CallbackServer;
+use crate::endpoints::Endpoints;
 use crate::error::{AuthError, Result};
-use crate::types::MinecraftProfile;
+use crate::microsoft::DevicePoll;
+use crate::types::{AccountKind, DeviceCode, MinecraftProfile};
 use crate::{microsoft, minecraft, xbox};
 
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+const SLOW_DOWN_STEP: Duration = Duration::from_secs(5);
 
 /// High-level orchestrator for the full Microsoft → Minecraft authentication flow.
 ///
@@ -29,6 +32,7 @@ pub struct MicrosoftAuth {
     http: reqwest::Client,
     timeout: Duration,
     port: Option<u16>,
+    endpoints: Endpoints,
 }
 
 impl MicrosoftAuth {
@@ -42,6 +46,7 @@ impl MicrosoftAuth {
             http: reqwest::Client::new(),
             timeout: DEFAULT_TIMEOUT,
             port: None,
+            endpoints: Endpoints::default(),
         }
     }
 
@@ -63,6 +68,12 @@ impl MicrosoftAuth {
         self
     }
 
+    /// Override the service URLs (e.g. to go through a proxy or a mock server).
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
     /// Run the full authentication flow:
     ///
     /// 1. Start a local callback server
@@ -102,17 +113,78 @@ impl MicrosoftAuth {
 
         // 6. Exchange auth code for Microsoft tokens.
         log::info!("exchanging authorization code for Microsoft tokens");
-        let ms_tokens = microsoft::exchange_code(&self.http, &self.client_id, &code, &redirect_uri).await?;
+        let ms_tokens = microsoft::exchange_code_at(
+            &self.http,
+            &self.endpoints.token,
+            &self.client_id,
+            &code,
+            &redirect_uri,
+        )
+        .await?;
 
         // 7. Complete the rest of the chain.
         self.exchange_chain(&ms_tokens.access_token, ms_tokens.refresh_token)
             .await
     }
 
+    /// Start a device-code login, for devices without a usable browser or when the
+    /// launcher prefers not to run a callback server.
+    ///
+    /// Show the returned [`DeviceCode::message`] (or `user_code` and
+    /// `verification_uri`) to the user, then call [`MicrosoftAuth::poll_token`].
+    ///
+    /// ```no_run
+    /// use emerald_auth::MicrosoftAuth;
+    ///
+    /// # async fn example() -> emerald_auth::Result<()> {
+    /// let auth = MicrosoftAuth::new("your-azure-client-id");
+    /// let code = auth.begin_device_code().await?;
+    /// println!("Go to {} and enter {}", code.verification_uri, code.user_code);
+    /// let profile = auth.poll_token(&code).await?;
+    /// println!("Logged in as {}", profile.username);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn begin_device_code(&self) -> Result<DeviceCode> {
+        log::info!("requesting Microsoft device code");
+        microsoft::request_device_code_at(&self.http, &self.endpoints.device_code, &self.client_id).await
+    }
+
+    /// Poll until the user completes the device-code login, then run the rest of the
+    /// chain: Xbox Live → XSTS → Minecraft → profile.
+    ///
+    /// Gives up with [`AuthError::Timeout`] once the code expires or the configured
+    /// timeout elapses, whichever comes first.
+    pub async fn poll_token(&self, code: &DeviceCode) -> Result<MinecraftProfile> {
+        let limit = Duration::from_secs(code.expires_in).min(self.timeout);
+        let deadline = tokio::time::Instant::now() + limit;
+        let mut interval = Duration::from_secs(code.interval);
+
+        let ms_tokens = loop {
+            match microsoft::poll_device_code_at(&self.http, &self.endpoints.token, &self.client_id, code).await? {
+                DevicePoll::Authorized(tokens) => break tokens,
+                DevicePoll::Pending => {}
+                DevicePoll::SlowDown => interval += SLOW_DOWN_STEP,
+            }
+            if tokio::time::Instant::now() + interval >= deadline {
+                return Err(AuthError::Timeout(limit));
+            }
+            tokio::time::sleep(interval).await;
+        };
+
+        log::info!("device code authorized");
+        self.exchange_chain(&ms_tokens.access_token, ms_tokens.refresh_token)
+            .await
+    }
+
     /// Re-authenticate using a previously obtained refresh token, without opening the browser.
+    ///
+    /// Persist [`MinecraftProfile::refresh_token`] after every login or refresh;
+    /// Microsoft rotates refresh tokens, so the newest one must be stored.
     pub async fn refresh(&self, refresh_token: &SecretString) -> Result<MinecraftProfile> {
         log::info!("refreshing Microsoft tokens");
-        let ms_tokens = microsoft::refresh_tokens(&self.http, &self.client_id, refresh_token).await?;
+        let ms_tokens =
+            microsoft::refresh_tokens_at(&self.http, &self.endpoints.token, &self.client_id, refresh_token).await?;
 
         self.exchange_chain(&ms_tokens.access_token, ms_tokens.refresh_token)
             .await
@@ -126,26 +198,33 @@ impl MicrosoftAuth {
     ) -> Result<MinecraftProfile> {
         // Xbox Live
         log::info!("authenticating with Xbox Live");
-        let xbox = xbox::authenticate_xbox_live(&self.http, ms_access_token).await?;
+        let xbox = xbox::authenticate_xbox_live_at(&self.http, &self.endpoints.xbox_live, ms_access_token).await?;
 
         // XSTS
         log::info!("obtaining XSTS token");
-        let xsts = xbox::authenticate_xsts(&self.http, &xbox.token).await?;
+        let xsts = xbox::authenticate_xsts_at(&self.http, &self.endpoints.xsts, &xbox.token).await?;
 
         // Minecraft
         log::info!("authenticating with Minecraft services");
-        let mc = minecraft::authenticate_minecraft(&self.http, &xsts.token, &xsts.user_hash).await?;
+        let mc = minecraft::authenticate_minecraft_at(
+            &self.http,
+            &self.endpoints.minecraft_login,
+            &xsts.token,
+            &xsts.user_hash,
+        )
+        .await?;
+        let expires_at = SystemTime::now() + Duration::from_secs(mc.expires_in);
 
         // Ownership check
         log::info!("verifying game ownership");
-        let owns = minecraft::check_ownership(&self.http, &mc.access_token).await?;
+        let owns = minecraft::check_ownership_at(&self.http, &self.endpoints.entitlements, &mc.access_token).await?;
         if !owns {
             return Err(AuthError::NoGameOwnership);
         }
 
         // Profile
         log::info!("fetching Minecraft profile");
-        let profile_resp = minecraft::fetch_profile(&self.http, &mc.access_token).await?;
+        let profile_resp = minecraft::fetch_profile_at(&self.http, &self.endpoints.profile, &mc.access_token).await?;
 
         let skin = profile_resp.active_skin();
         let cape = profile_resp.active_cape();
@@ -156,7 +235,9 @@ impl MicrosoftAuth {
             skin,
             cape,
             access_token: mc.access_token,
+            expires_at: Some(expires_at),
             refresh_token: ms_refresh_token,
+            kind: AccountKind::Microsoft,
         })
     }
 }
@@ -0,0 +1,50 @@
+use crate::{microsoft, minecraft, xbox};
+
+/// The service URLs used by each step of the authentication chain.
+///
+/// [`Endpoints::default`] points at the real Microsoft, Xbox Live and Minecraft
+/// services. Override individual URLs to route requests through a proxy or a
+/// mock server in tests.
+///
+/// # Example
+///
+/// ```
+/// use emerald_auth::Endpoints;
+///
+/// let endpoints = Endpoints {
+///     xsts: "http://127.0.0.1:8080/xsts/authorize".into(),
+///     ..Endpoints::default()
+/// };
+/// assert!(endpoints.token.starts_with("https://login.microsoftonline.com/"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    /// Microsoft OAuth2 device authorization endpoint.
+    pub device_code: String,
+    /// Microsoft OAuth2 token endpoint (code exchange, refresh, device polling).
+    pub token: String,
+    /// Xbox Live user authentication endpoint.
+    pub xbox_live: String,
+    /// XSTS authorization endpoint.
+    pub xsts: String,
+    /// Minecraft Services `login_with_xbox` endpoint.
+    pub minecraft_login: String,
+    /// Minecraft Services entitlements endpoint.
+    pub entitlements: String,
+    /// Minecraft Services profile endpoint.
+    pub profile: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            device_code: microsoft::DEVICE_CODE_URL.to_owned(),
+            token: microsoft::TOKEN_URL.to_owned(),
+            xbox_live: xbox::XBOX_LIVE_AUTH_URL.to_owned(),
+            xsts: xbox::XSTS_AUTH_URL.to_owned(),
+            minecraft_login: minecraft::MC_AUTH_URL.to_owned(),
+            entitlements: minecraft::MC_ENTITLEMENTS_URL.to_owned(),
+            profile: minecraft::MC_PROFILE_URL.to_owned(),
+        }
+    }
+}
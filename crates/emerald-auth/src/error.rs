@@ -41,6 +41,21 @@ pub enum AuthError {
 
     #[error("failed to open browser: {0}")]
     BrowserOpen(String),
+
+    #[error("invalid username {0:?}: must be 3-16 characters of A-Z, a-z, 0-9 or _")]
+    InvalidUsername(String),
+
+    #[error("authentication server error: {error} - {message}")]
+    AuthServer { error: String, message: String },
+
+    #[error("no Minecraft profile found for {0:?}")]
+    NotFound(String),
+
+    #[error("session for {0:?} has expired and has no refresh token")]
+    NoRefreshToken(String),
+
+    #[error("rate limited by Mojang{}", .0.map(|d| format!(", retry after {d:?}")).unwrap_or_default())]
+    RateLimited(Option<Duration>),
 }
 
 pub type Result<T> = std::result::Result<T, AuthError>;
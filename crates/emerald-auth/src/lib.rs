@@ -31,6 +31,61 @@
 //! # }
 //! ```
 //!
+//! ## Sessions
+//!
+//! A [`Session`] keeps the access token together with its expiry and refresh
+//! token, and renews itself shortly before the token lapses:
+//!
+//! ```no_run
+//! use emerald_auth::{MicrosoftAuth, Session};
+//!
+//! # async fn example(mut session: Session) -> emerald_auth::Result<()> {
+//! let auth = MicrosoftAuth::new("your-azure-client-id");
+//! let session = session.ensure_valid(&auth).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Device-code login
+//!
+//! ```no_run
+//! use emerald_auth::MicrosoftAuth;
+//!
+//! # async fn example() -> emerald_auth::Result<()> {
+//! let auth = MicrosoftAuth::new("your-azure-client-id");
+//! let code = auth.begin_device_code().await?;
+//! println!("{}", code.message);
+//! let profile = auth.poll_token(&code).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Offline profiles
+//!
+//! ```
+//! let profile = emerald_auth::offline_profile("Notch").unwrap();
+//! assert_eq!(profile.uuid, "b50ad385829d3141a2167e7d7539ba7f");
+//! assert_eq!(profile.user_type(), "legacy");
+//! ```
+//!
+//! ## Custom auth servers
+//!
+//! Accounts on Ely.by or a self-hosted Yggdrasil server sign in with a password
+//! instead, and the game is launched through authlib-injector:
+//!
+//! ```no_run
+//! use emerald_auth::{AuthServer, yggdrasil};
+//! use secrecy::SecretString;
+//!
+//! # async fn example(password: SecretString) -> emerald_auth::Result<()> {
+//! let server = AuthServer::custom("https://authserver.ely.by/api/authlib-injector");
+//! let api_url = server.api_url().unwrap();
+//! let profile = yggdrasil::authenticate(&reqwest::Client::new(), api_url, "steve@example.com", &password).await?;
+//! assert_eq!(profile.user_type(), "mojang");
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! ## Lower-level API
 //!
 //! Each step of the authentication chain is exposed as a public function
@@ -39,15 +94,26 @@
 
 pub mod callback;
 pub mod client;
+pub mod endpoints;
 pub mod error;
 pub mod microsoft;
 pub mod minecraft;
+pub mod mojang;
+pub mod offline;
+pub mod profile;
+pub mod session;
 pub mod types;
 pub mod xbox;
+pub mod yggdrasil;
 
 pub use client::MicrosoftAuth;
+pub use endpoints::Endpoints;
 pub use error::{AuthError, Result};
+pub use offline::offline_profile;
+pub use profile::{SkinCache, SkinInfo, fetch_skin};
+pub use session::Session;
+pub use yggdrasil::AuthServer;
 pub use types::{
-    Cape, MinecraftProfile, MinecraftToken, MicrosoftTokens, Skin, SkinVariant, XboxLiveToken,
-    XstsToken,
+    AccountKind, Cape, DeviceCode, MinecraftProfile, MinecraftToken, MicrosoftTokens, Skin,
+    SkinVariant, XboxLiveToken, XstsToken,
 };
@@ -1,11 +1,24 @@
 use secrecy::{ExposeSecret, SecretString};
 
 use crate::error::{AuthError, Result};
-use crate::types::MicrosoftTokens;
+use crate::types::{DeviceCode, MicrosoftTokens};
 
 const AUTH_URL: &str = "https://login.microsoftonline.com/consumers/oauth2/v2.0/authorize";
-const TOKEN_URL: &str = "https://login.microsoftonline.com/consumers/oauth2/v2.0/token";
+pub(crate) const DEVICE_CODE_URL: &str = "https://login.microsoftonline.com/consumers/oauth2/v2.0/devicecode";
+pub(crate) const TOKEN_URL: &str = "https://login.microsoftonline.com/consumers/oauth2/v2.0/token";
 const SCOPE: &str = "XboxLive.signin offline_access";
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Outcome of a single device-code token poll.
+#[derive(Debug, Clone)]
+pub enum DevicePoll {
+    /// The user has not finished signing in yet; poll again after the interval.
+    Pending,
+    /// Microsoft asked us to poll less often; add 5 seconds to the interval.
+    SlowDown,
+    /// The user signed in and the tokens were issued.
+    Authorized(MicrosoftTokens),
+}
 
 /// Build the Microsoft OAuth2 authorization URL that the user should visit.
 pub fn build_auth_url(client_id: &str, redirect_uri: &str, state: &str) -> String {
@@ -22,6 +35,17 @@ pub async fn exchange_code(
     client_id: &str,
     auth_code: &str,
     redirect_uri: &str,
+) -> Result<MicrosoftTokens> {
+    exchange_code_at(client, TOKEN_URL, client_id, auth_code, redirect_uri).await
+}
+
+/// Like [`exchange_code`], against a custom token endpoint.
+pub async fn exchange_code_at(
+    client: &reqwest::Client,
+    token_url: &str,
+    client_id: &str,
+    auth_code: &str,
+    redirect_uri: &str,
 ) -> Result<MicrosoftTokens> {
     let params = [
         ("client_id", client_id),
@@ -31,7 +55,7 @@ pub async fn exchange_code(
         ("scope", SCOPE),
     ];
 
-    let resp = client.post(TOKEN_URL).form(&params).send().await?;
+    let resp = client.post(token_url).form(&params).send().await?;
     parse_token_response(resp).await
 }
 
@@ -40,6 +64,16 @@ pub async fn refresh_tokens(
     client: &reqwest::Client,
     client_id: &str,
     refresh_token: &SecretString,
+) -> Result<MicrosoftTokens> {
+    refresh_tokens_at(client, TOKEN_URL, client_id, refresh_token).await
+}
+
+/// Like [`refresh_tokens`], against a custom token endpoint.
+pub async fn refresh_tokens_at(
+    client: &reqwest::Client,
+    token_url: &str,
+    client_id: &str,
+    refresh_token: &SecretString,
 ) -> Result<MicrosoftTokens> {
     let params = [
         ("client_id", client_id.to_owned()),
@@ -48,23 +82,95 @@ pub async fn refresh_tokens(
         ("scope", SCOPE.to_owned()),
     ];
 
-    let resp = client.post(TOKEN_URL).form(&params).send().await?;
+    let resp = client.post(token_url).form(&params).send().await?;
     parse_token_response(resp).await
 }
 
-async fn parse_token_response(resp: reqwest::Response) -> Result<MicrosoftTokens> {
+/// Start a device-code login.
+///
+/// Show [`DeviceCode::user_code`] and [`DeviceCode::verification_uri`] to the user,
+/// then call [`poll_device_code`] every [`DeviceCode::interval`] seconds.
+pub async fn request_device_code(client: &reqwest::Client, client_id: &str) -> Result<DeviceCode> {
+    request_device_code_at(client, DEVICE_CODE_URL, client_id).await
+}
+
+/// Like [`request_device_code`], against a custom device authorization endpoint.
+pub async fn request_device_code_at(
+    client: &reqwest::Client,
+    device_code_url: &str,
+    client_id: &str,
+) -> Result<DeviceCode> {
+    let params = [("client_id", client_id), ("scope", SCOPE)];
+
+    let resp = client.post(device_code_url).form(&params).send().await?;
     let body: serde_json::Value = resp.json().await?;
+    check_oauth_error(&body)?;
 
-    if let Some(error) = body.get("error") {
-        return Err(AuthError::OAuth {
+    let field = |name: &str| {
+        body[name]
+            .as_str()
+            .map(str::to_owned)
+            .ok_or_else(|| AuthError::MissingParam(name.to_owned()))
+    };
+
+    Ok(DeviceCode {
+        device_code: SecretString::from(field("device_code")?),
+        user_code: field("user_code")?,
+        verification_uri: field("verification_uri")?,
+        message: field("message").unwrap_or_default(),
+        expires_in: body["expires_in"].as_u64().unwrap_or(900),
+        interval: body["interval"].as_u64().unwrap_or(5),
+    })
+}
+
+/// Poll the token endpoint once for a pending device-code login.
+pub async fn poll_device_code(
+    client: &reqwest::Client,
+    client_id: &str,
+    device_code: &DeviceCode,
+) -> Result<DevicePoll> {
+    poll_device_code_at(client, TOKEN_URL, client_id, device_code).await
+}
+
+/// Like [`poll_device_code`], against a custom token endpoint.
+pub async fn poll_device_code_at(
+    client: &reqwest::Client,
+    token_url: &str,
+    client_id: &str,
+    device_code: &DeviceCode,
+) -> Result<DevicePoll> {
+    let params = [
+        ("client_id", client_id),
+        ("device_code", device_code.device_code.expose_secret()),
+        ("grant_type", DEVICE_CODE_GRANT),
+    ];
+
+    let resp = client.post(token_url).form(&params).send().await?;
+    match parse_token_response(resp).await {
+        Ok(tokens) => Ok(DevicePoll::Authorized(tokens)),
+        Err(AuthError::OAuth { error, .. }) if error == "authorization_pending" => Ok(DevicePoll::Pending),
+        Err(AuthError::OAuth { error, .. }) if error == "slow_down" => Ok(DevicePoll::SlowDown),
+        Err(e) => Err(e),
+    }
+}
+
+fn check_oauth_error(body: &serde_json::Value) -> Result<()> {
+    match body.get("error") {
+        Some(error) => Err(AuthError::OAuth {
             error: error.as_str().unwrap_or("unknown").to_owned(),
             description: body
                 .get("error_description")
                 .and_then(|v| v.as_str())
                 .unwrap_or("")
                 .to_owned(),
-        });
+        }),
+        None => Ok(()),
     }
+}
+
+async fn parse_token_response(resp: reqwest::Response) -> Result<MicrosoftTokens> {
+    let body: serde_json::Value = resp.json().await?;
+    check_oauth_error(&body)?;
 
     let access_token = body["access_token"]
         .as_str()
@@ -4,20 +4,30 @@ use serde::Deserialize;
 use crate::error::{AuthError, Result};
 use crate::types::{Cape, MinecraftToken, Skin, SkinVariant};
 
-const MC_AUTH_URL: &str = "https://api.minecraftservices.com/authentication/login_with_xbox";
-const MC_ENTITLEMENTS_URL: &str = "https://api.minecraftservices.com/entitlements/mcstore";
-const MC_PROFILE_URL: &str = "https://api.minecraftservices.com/minecraft/profile";
+pub(crate) const MC_AUTH_URL: &str = "https://api.minecraftservices.com/authentication/login_with_xbox";
+pub(crate) const MC_ENTITLEMENTS_URL: &str = "https://api.minecraftservices.com/entitlements/mcstore";
+pub(crate) const MC_PROFILE_URL: &str = "https://api.minecraftservices.com/minecraft/profile";
 
 /// Exchange an XSTS token for a Minecraft access token.
 pub async fn authenticate_minecraft(
     client: &reqwest::Client,
     xsts_token: &SecretString,
     user_hash: &str,
+) -> Result<MinecraftToken> {
+    authenticate_minecraft_at(client, MC_AUTH_URL, xsts_token, user_hash).await
+}
+
+/// Like [`authenticate_minecraft`], against a custom endpoint.
+pub async fn authenticate_minecraft_at(
+    client: &reqwest::Client,
+    url: &str,
+    xsts_token: &SecretString,
+    user_hash: &str,
 ) -> Result<MinecraftToken> {
     let identity_token = format!("XBL3.0 x={};{}", user_hash, xsts_token.expose_secret());
 
     let resp = client
-        .post(MC_AUTH_URL)
+        .post(url)
         .json(&serde_json::json!({ "identityToken": identity_token }))
         .send()
         .await?;
@@ -42,8 +52,17 @@ pub async fn authenticate_minecraft(
 
 /// Check whether the authenticated account owns Minecraft.
 pub async fn check_ownership(client: &reqwest::Client, minecraft_token: &SecretString) -> Result<bool> {
+    check_ownership_at(client, MC_ENTITLEMENTS_URL, minecraft_token).await
+}
+
+/// Like [`check_ownership`], against a custom endpoint.
+pub async fn check_ownership_at(
+    client: &reqwest::Client,
+    url: &str,
+    minecraft_token: &SecretString,
+) -> Result<bool> {
     let resp = client
-        .get(MC_ENTITLEMENTS_URL)
+        .get(url)
         .bearer_auth(minecraft_token.expose_secret())
         .send()
         .await?;
@@ -63,9 +82,18 @@ pub async fn check_ownership(client: &reqwest::Client, minecraft_token: &SecretS
 pub async fn fetch_profile(
     client: &reqwest::Client,
     minecraft_token: &SecretString,
+) -> Result<ProfileResponse> {
+    fetch_profile_at(client, MC_PROFILE_URL, minecraft_token).await
+}
+
+/// Like [`fetch_profile`], against a custom endpoint.
+pub async fn fetch_profile_at(
+    client: &reqwest::Client,
+    url: &str,
+    minecraft_token: &SecretString,
 ) -> Result<ProfileResponse> {
     let resp = client
-        .get(MC_PROFILE_URL)
+        .get(url)
         .bearer_auth(minecraft_token.expose_secret())
         .send()
         .await?;
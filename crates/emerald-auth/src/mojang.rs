@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::error::{AuthError, Result};
+
+pub(crate) const USERNAME_LOOKUP_URL: &str = "https://api.mojang.com/users/profiles/minecraft";
+pub(crate) const UUID_LOOKUP_URL: &str = "https://api.minecraftservices.com/minecraft/profile/lookup";
+
+/// A player's current name and UUID.
+#[derive(Debug, Deserialize)]
+struct NameAndId {
+    id: String,
+    name: String,
+}
+
+/// Resolve a username to the UUID (without dashes) of the account that currently owns it.
+///
+/// Names are matched case-insensitively. Fails with [`AuthError::NotFound`] when no
+/// account has the name, and [`AuthError::RateLimited`] when Mojang throttles the
+/// lookups (roughly 600 per 10 minutes per IP).
+pub async fn uuid_for_username(client: &reqwest::Client, username: &str) -> Result<String> {
+    uuid_for_username_at(client, USERNAME_LOOKUP_URL, username).await
+}
+
+/// Like [`uuid_for_username`], against a custom endpoint.
+pub async fn uuid_for_username_at(client: &reqwest::Client, url: &str, username: &str) -> Result<String> {
+    Ok(lookup(client, url, username).await?.id)
+}
+
+/// Resolve a UUID (with or without dashes) to the player's current username.
+///
+/// Fails with [`AuthError::NotFound`] for unknown UUIDs and offline-mode UUIDs.
+pub async fn username_for_uuid(client: &reqwest::Client, uuid: &str) -> Result<String> {
+    username_for_uuid_at(client, UUID_LOOKUP_URL, uuid).await
+}
+
+/// Like [`username_for_uuid`], against a custom endpoint.
+pub async fn username_for_uuid_at(client: &reqwest::Client, url: &str, uuid: &str) -> Result<String> {
+    Ok(lookup(client, url, &uuid.replace('-', "")).await?.name)
+}
+
+async fn lookup(client: &reqwest::Client, url: &str, key: &str) -> Result<NameAndId> {
+    let resp = client.get(format!("{}/{key}", url.trim_end_matches('/'))).send().await?;
+
+    // Older endpoints answer misses with 204 and an empty body, newer ones with 404.
+    match resp.status() {
+        reqwest::StatusCode::NO_CONTENT | reqwest::StatusCode::NOT_FOUND => {
+            return Err(AuthError::NotFound(key.to_owned()));
+        }
+        reqwest::StatusCode::TOO_MANY_REQUESTS => {
+            let retry_after = resp
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|s| s.parse::<u64>().ok())
+                .map(Duration::from_secs);
+            return Err(AuthError::RateLimited(retry_after));
+        }
+        status if !status.is_success() => {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(AuthError::Minecraft(format!("profile lookup failed ({status}): {text}")));
+        }
+        _ => {}
+    }
+
+    let body = resp.bytes().await?;
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Err(AuthError::NotFound(key.to_owned()));
+    }
+    Ok(serde_json::from_slice(&body)?)
+}
@@ -0,0 +1,51 @@
+use md5::{Digest, Md5};
+use secrecy::SecretString;
+
+use crate::error::{AuthError, Result};
+use crate::types::{AccountKind, MinecraftProfile};
+
+/// Placeholder access token passed to the game for offline profiles.
+const OFFLINE_ACCESS_TOKEN: &str = "0";
+
+/// Create an offline profile for LAN and singleplayer play without a Microsoft login.
+///
+/// The UUID is derived from the username exactly as a vanilla server in offline
+/// mode does, so the same name always maps to the same player data.
+///
+/// ```
+/// let profile = emerald_auth::offline_profile("Notch").unwrap();
+/// assert_eq!(profile.username, "Notch");
+/// assert!(emerald_auth::offline_profile("no spaces allowed").is_err());
+/// ```
+pub fn offline_profile(username: &str) -> Result<MinecraftProfile> {
+    if !is_valid_username(username) {
+        return Err(AuthError::InvalidUsername(username.to_owned()));
+    }
+
+    Ok(MinecraftProfile {
+        uuid: offline_uuid(username),
+        username: username.to_owned(),
+        skin: None,
+        cape: None,
+        access_token: SecretString::from(OFFLINE_ACCESS_TOKEN.to_owned()),
+        expires_at: None,
+        refresh_token: None,
+        kind: AccountKind::Offline,
+    })
+}
+
+/// The offline-mode UUID for a username (without dashes).
+///
+/// This is a version-3 (name-based, MD5) UUID of `OfflinePlayer:<username>`,
+/// matching Java's `UUID.nameUUIDFromBytes`.
+pub fn offline_uuid(username: &str) -> String {
+    let mut hash: [u8; 16] = Md5::digest(format!("OfflinePlayer:{username}").as_bytes()).into();
+    hash[6] = (hash[6] & 0x0f) | 0x30;
+    hash[8] = (hash[8] & 0x3f) | 0x80;
+    hash.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Whether `username` follows Minecraft's rules: 3–16 characters of `[A-Za-z0-9_]`.
+pub fn is_valid_username(username: &str) -> bool {
+    (3..=16).contains(&username.len()) && username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
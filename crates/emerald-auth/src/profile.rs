@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::Deserialize;
+
+use crate::error::{AuthError, Result};
+use crate::types::SkinVariant;
+
+pub(crate) const SESSION_PROFILE_URL: &str = "https://sessionserver.mojang.com/session/minecraft/profile";
+
+/// How long [`fetch_skin`] reuses a looked-up skin. The session server rate limits
+/// repeated lookups of the same profile, and skins rarely change within minutes.
+pub const DEFAULT_SKIN_TTL: Duration = Duration::from_secs(300);
+
+/// The textures a player currently wears, as any client sees them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkinInfo {
+    /// Minecraft UUID (without dashes).
+    pub uuid: String,
+    pub username: String,
+    /// `None` when the player uses a default skin.
+    pub skin_url: Option<String>,
+    pub cape_url: Option<String>,
+    /// Arm width of the skin; default skins count as classic.
+    pub model: SkinVariant,
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionProfile {
+    id: String,
+    name: String,
+    #[serde(default)]
+    properties: Vec<SessionProperty>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionProperty {
+    name: String,
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TexturesPayload {
+    profile_id: String,
+    profile_name: String,
+    #[serde(default)]
+    textures: Textures,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+struct Textures {
+    skin: Option<Texture>,
+    cape: Option<Texture>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Texture {
+    url: String,
+    #[serde(default)]
+    metadata: HashMap<String, String>,
+}
+
+/// Decode the base64 `textures` property the session server returns for a profile.
+///
+/// ```
+/// // {"profileId":"853c80ef3c3749fdaa49938b674adae6","profileName":"jeb_","textures":{}}
+/// let value = "eyJwcm9maWxlSWQiOiI4NTNjODBlZjNjMzc0OWZkYWE0OTkzOGI2NzRhZGFlNiIsInByb2ZpbGVOYW1lIjoiamViXyIsInRleHR1cmVzIjp7fX0=";
+/// let skin = emerald_auth::profile::decode_textures(value).unwrap();
+/// assert_eq!(skin.username, "jeb_");
+/// assert!(skin.skin_url.is_none());
+/// ```
+pub fn decode_textures(value: &str) -> Result<SkinInfo> {
+    let bytes = STANDARD
+        .decode(value.trim())
+        .map_err(|e| AuthError::Minecraft(format!("invalid textures property: {e}")))?;
+    let payload: TexturesPayload = serde_json::from_slice(&bytes)?;
+    let model = match payload.textures.skin.as_ref().and_then(|skin| skin.metadata.get("model")) {
+        Some(model) if model.eq_ignore_ascii_case("slim") => SkinVariant::Slim,
+        _ => SkinVariant::Classic,
+    };
+    Ok(SkinInfo {
+        uuid: payload.profile_id,
+        username: payload.profile_name,
+        skin_url: payload.textures.skin.map(|skin| skin.url),
+        cape_url: payload.textures.cape.map(|cape| cape.url),
+        model,
+    })
+}
+
+/// Look up the skin and cape of the player with `uuid` (with or without dashes),
+/// reusing results from the last [`DEFAULT_SKIN_TTL`].
+///
+/// This is a public endpoint; no access token is needed.
+pub async fn fetch_skin(client: &reqwest::Client, uuid: &str) -> Result<SkinInfo> {
+    static CACHE: OnceLock<SkinCache> = OnceLock::new();
+    CACHE.get_or_init(|| SkinCache::new(DEFAULT_SKIN_TTL)).fetch(client, uuid).await
+}
+
+/// Like [`fetch_skin`], against a custom endpoint and without caching.
+pub async fn fetch_skin_at(client: &reqwest::Client, url: &str, uuid: &str) -> Result<SkinInfo> {
+    let uuid = uuid.replace('-', "");
+    let resp = client.get(format!("{}/{uuid}", url.trim_end_matches('/'))).send().await?;
+
+    // The session server answers unknown profiles with 204 No Content.
+    if resp.status() == reqwest::StatusCode::NO_CONTENT || resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(AuthError::NotFound(uuid));
+    }
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        return Err(AuthError::Minecraft(format!("skin fetch failed ({status}): {text}")));
+    }
+
+    let profile: SessionProfile = resp.json().await?;
+    match profile.properties.iter().find(|p| p.name == "textures") {
+        Some(textures) => decode_textures(&textures.value),
+        None => Ok(SkinInfo {
+            uuid: profile.id,
+            username: profile.name,
+            skin_url: None,
+            cape_url: None,
+            model: SkinVariant::Classic,
+        }),
+    }
+}
+
+/// Caches skin lookups per UUID for a fixed time.
+///
+/// [`fetch_skin`] uses a process-wide cache; create your own to pick another TTL or
+/// endpoint.
+#[derive(Debug)]
+pub struct SkinCache {
+    ttl: Duration,
+    url: String,
+    entries: Mutex<HashMap<String, (Instant, SkinInfo)>>,
+}
+
+impl SkinCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            url: SESSION_PROFILE_URL.to_owned(),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Query another session server (e.g. a mock server in tests).
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+
+    /// The cached skin for `uuid` if it is younger than the TTL, otherwise a fresh one.
+    pub async fn fetch(&self, client: &reqwest::Client, uuid: &str) -> Result<SkinInfo> {
+        let key = uuid.replace('-', "").to_ascii_lowercase();
+        let cached = self
+            .entries
+            .lock()
+            .unwrap()
+            .get(&key)
+            .filter(|(fetched, _)| fetched.elapsed() < self.ttl)
+            .map(|(_, skin)| skin.clone());
+        if let Some(skin) = cached {
+            return Ok(skin);
+        }
+
+        let skin = fetch_skin_at(client, &self.url, &key).await?;
+        self.entries.lock().unwrap().insert(key, (Instant::now(), skin.clone()));
+        Ok(skin)
+    }
+
+    /// Forget the cached skin for `uuid`, e.g. after the player changed it.
+    pub fn invalidate(&self, uuid: &str) {
+        self.entries.lock().unwrap().remove(&uuid.replace('-', "").to_ascii_lowercase());
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::{Duration, SystemTime};
+
+use secrecy::SecretString;
+
+use crate::client::MicrosoftAuth;
+use crate::error::{AuthError, Result};
+use crate::types::{AccountKind, MinecraftProfile};
+
+/// How long before expiry [`Session::ensure_valid`] already refreshes the access
+/// token, so it does not lapse while the game is starting or joining a server.
+pub const REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// A signed-in player together with the tokens needed to keep them signed in.
+///
+/// Hold on to one of these instead of a bare access token and call
+/// [`Session::ensure_valid`] before handing it to the game.
+///
+/// ```no_run
+/// use emerald_auth::{MicrosoftAuth, Session};
+///
+/// # async fn example() -> emerald_auth::Result<()> {
+/// let auth = MicrosoftAuth::new("your-azure-client-id");
+/// let mut session = Session::from(auth.authenticate().await?);
+/// // ...later, right before launching:
+/// let session = session.ensure_valid(&auth).await?;
+/// println!("Launching as {}", session.username);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// In-game username.
+    pub username: String,
+    /// Minecraft UUID (without dashes).
+    pub uuid: String,
+    /// Minecraft access token passed to the game.
+    pub access_token: SecretString,
+    /// When `access_token` stops being accepted; `None` never expires (offline sessions).
+    pub expires_at: Option<SystemTime>,
+    /// Microsoft refresh token used to renew `access_token`.
+    pub refresh_token: Option<SecretString>,
+    /// Whether this is a Microsoft account, an offline profile or a custom server account.
+    pub kind: AccountKind,
+}
+
+impl Session {
+    /// Whether the access token has already expired.
+    pub fn is_expired(&self) -> bool {
+        self.expires_within(Duration::ZERO)
+    }
+
+    /// Whether the access token expires within `margin` from now, or already has.
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        self.expires_at.is_some_and(|expires_at| SystemTime::now() + margin >= expires_at)
+    }
+
+    /// The `${user_type}` launch argument for this session; see
+    /// [`MinecraftProfile::user_type`].
+    pub fn user_type(&self) -> &'static str {
+        match self.kind {
+            AccountKind::Microsoft => "msa",
+            AccountKind::Offline => "legacy",
+            AccountKind::Custom => "mojang",
+        }
+    }
+
+    /// Make sure the access token is good for at least [`REFRESH_MARGIN`], refreshing
+    /// it through `auth` when it is not.
+    ///
+    /// A session that is still fresh is returned without any request. Refreshing
+    /// replaces the tokens, expiry and player name in place; as with
+    /// [`MicrosoftAuth::refresh`], persist the new `refresh_token` afterwards.
+    /// Fails with [`AuthError::NoRefreshToken`] if the token needs renewing and there
+    /// is no refresh token to do it with.
+    pub async fn ensure_valid(&mut self, auth: &MicrosoftAuth) -> Result<&Session> {
+        if !self.expires_within(REFRESH_MARGIN) {
+            return Ok(self);
+        }
+
+        let Some(refresh_token) = &self.refresh_token else {
+            return Err(AuthError::NoRefreshToken(self.username.clone()));
+        };
+        log::info!("access token for {} is about to expire, refreshing", self.username);
+        let profile = auth.refresh(refresh_token).await?;
+        // Keep the old refresh token if Microsoft did not rotate it.
+        let refresh_token = profile.refresh_token.clone().or_else(|| self.refresh_token.take());
+        *self = Session {
+            refresh_token,
+            ..Session::from(profile)
+        };
+        Ok(self)
+    }
+}
+
+impl From<MinecraftProfile> for Session {
+    fn from(profile: MinecraftProfile) -> Self {
+        Self {
+            username: profile.username,
+            uuid: profile.uuid,
+            access_token: profile.access_token,
+            expires_at: profile.expires_at,
+            refresh_token: profile.refresh_token,
+            kind: profile.kind,
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::SystemTime;
+
 use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
 
@@ -17,8 +19,38 @@ pub struct MinecraftProfile {
     pub cape: Option<Cape>,
     /// Minecraft access token for API calls.
     pub access_token: SecretString,
+    /// When `access_token` stops being accepted; `None` for offline profiles.
+    pub expires_at: Option<SystemTime>,
     /// Microsoft refresh token for re-authentication without browser.
     pub refresh_token: Option<SecretString>,
+    /// Whether this is a Microsoft account or an offline profile.
+    pub kind: AccountKind,
+}
+
+impl MinecraftProfile {
+    /// The `${user_type}` launch argument for this profile: `msa`, `legacy`, or
+    /// `mojang` for custom servers, as authlib-injector expects.
+    pub fn user_type(&self) -> &'static str {
+        match self.kind {
+            AccountKind::Microsoft => "msa",
+            AccountKind::Offline => "legacy",
+            AccountKind::Custom => "mojang",
+        }
+    }
+}
+
+/// Where a [`MinecraftProfile`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountKind {
+    /// Authenticated through Microsoft; the access token is valid for online play.
+    Microsoft,
+    /// Created locally by [`crate::offline::offline_profile`]; only usable in
+    /// offline mode, singleplayer and LAN.
+    Offline,
+    /// Signed in to a custom Yggdrasil server with [`crate::yggdrasil::authenticate`];
+    /// the game needs authlib-injector to use it.
+    Custom,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,7 +60,7 @@ pub struct Skin {
     pub variant: SkinVariant,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum SkinVariant {
     Classic,
@@ -50,6 +82,25 @@ pub struct MicrosoftTokens {
     pub expires_in: u64,
 }
 
+/// A pending device-code login, returned by [`crate::microsoft::request_device_code`].
+///
+/// The user visits `verification_uri` on any device and enters `user_code`.
+#[derive(Debug, Clone)]
+pub struct DeviceCode {
+    /// Opaque code used to poll for the tokens.
+    pub device_code: SecretString,
+    /// Short code the user types in on the verification page.
+    pub user_code: String,
+    /// Page where the user enters the code, usually <https://www.microsoft.com/link>.
+    pub verification_uri: String,
+    /// Human-readable instructions from Microsoft.
+    pub message: String,
+    /// Seconds until the code expires.
+    pub expires_in: u64,
+    /// Minimum seconds between polls.
+    pub interval: u64,
+}
+
 /// Xbox Live authentication result.
 #[derive(Debug, Clone)]
 pub struct XboxLiveToken {
@@ -4,13 +4,22 @@ use serde_json::json;
 use crate::error::{AuthError, Result};
 use crate::types::{XboxLiveToken, XstsToken};
 
-const XBOX_LIVE_AUTH_URL: &str = "https://user.auth.xboxlive.com/user/authenticate";
-const XSTS_AUTH_URL: &str = "https://xsts.auth.xboxlive.com/xsts/authorize";
+pub(crate) const XBOX_LIVE_AUTH_URL: &str = "https://user.auth.xboxlive.com/user/authenticate";
+pub(crate) const XSTS_AUTH_URL: &str = "https://xsts.auth.xboxlive.com/xsts/authorize";
 
 /// Authenticate with Xbox Live using a Microsoft access token.
 pub async fn authenticate_xbox_live(
     client: &reqwest::Client,
     microsoft_token: &SecretString,
+) -> Result<XboxLiveToken> {
+    authenticate_xbox_live_at(client, XBOX_LIVE_AUTH_URL, microsoft_token).await
+}
+
+/// Like [`authenticate_xbox_live`], against a custom endpoint.
+pub async fn authenticate_xbox_live_at(
+    client: &reqwest::Client,
+    url: &str,
+    microsoft_token: &SecretString,
 ) -> Result<XboxLiveToken> {
     let body = json!({
         "Properties": {
@@ -23,7 +32,7 @@ pub async fn authenticate_xbox_live(
     });
 
     let resp = client
-        .post(XBOX_LIVE_AUTH_URL)
+        .post(url)
         .header("Content-Type", "application/json")
         .header("Accept", "application/json")
         .json(&body)
@@ -55,6 +64,15 @@ pub async fn authenticate_xbox_live(
 pub async fn authenticate_xsts(
     client: &reqwest::Client,
     xbox_token: &SecretString,
+) -> Result<XstsToken> {
+    authenticate_xsts_at(client, XSTS_AUTH_URL, xbox_token).await
+}
+
+/// Like [`authenticate_xsts`], against a custom endpoint.
+pub async fn authenticate_xsts_at(
+    client: &reqwest::Client,
+    url: &str,
+    xbox_token: &SecretString,
 ) -> Result<XstsToken> {
     let body = json!({
         "Properties": {
@@ -66,7 +84,7 @@ pub async fn authenticate_xsts(
     });
 
     let resp = client
-        .post(XSTS_AUTH_URL)
+        .post(url)
         .header("Content-Type", "application/json")
         .header("Accept", "application/json")
         .json(&body)
@@ -0,0 +1,117 @@
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::error::{AuthError, Result};
+use crate::types::{AccountKind, MinecraftProfile};
+
+/// Which service a player signs in with.
+///
+/// Self-hosted servers and services such as Ely.by run their own Yggdrasil API
+/// (the protocol Mojang's legacy accounts used). The game only talks to those when
+/// launched with [authlib-injector](https://github.com/yushijinhun/authlib-injector)
+/// as a Java agent pointed at the server's API root.
+///
+/// ```
+/// use emerald_auth::AuthServer;
+///
+/// let ely = AuthServer::custom("https://authserver.ely.by/api/authlib-injector/");
+/// assert_eq!(ely.api_url(), Some("https://authserver.ely.by/api/authlib-injector"));
+/// assert_eq!(AuthServer::default().api_url(), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum AuthServer {
+    /// Microsoft accounts through [`MicrosoftAuth`](crate::MicrosoftAuth); the game
+    /// needs nothing extra.
+    #[default]
+    Mojang,
+    /// A Yggdrasil server, by its authlib-injector API root. Sign in with
+    /// [`authenticate`].
+    Custom(String),
+}
+
+impl AuthServer {
+    /// A custom server at `api_url`, without any trailing `/`.
+    pub fn custom(api_url: impl Into<String>) -> Self {
+        let api_url = api_url.into();
+        Self::Custom(api_url.trim_end_matches('/').to_owned())
+    }
+
+    /// The API root to hand authlib-injector, `None` for [`AuthServer::Mojang`].
+    pub fn api_url(&self) -> Option<&str> {
+        match self {
+            Self::Mojang => None,
+            Self::Custom(api_url) => Some(api_url),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthenticateResponse {
+    access_token: String,
+    selected_profile: Option<SelectedProfile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SelectedProfile {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_message: String,
+}
+
+/// Sign in to the Yggdrasil server at `api_url` with a username (usually an email
+/// address) and password, through its `/authserver/authenticate` endpoint.
+///
+/// The returned profile is an [`AccountKind::Custom`] account whose access token
+/// only the same server accepts, so launch the game with authlib-injector pointed at
+/// `api_url`. Yggdrasil tokens carry no expiry and come without a refresh token.
+/// Rejected credentials fail with [`AuthError::AuthServer`], and an account without
+/// a game profile with [`AuthError::NoGameOwnership`].
+pub async fn authenticate(client: &reqwest::Client, api_url: &str, username: &str, password: &SecretString) -> Result<MinecraftProfile> {
+    let url = format!("{}/authserver/authenticate", api_url.trim_end_matches('/'));
+    let resp = client
+        .post(url)
+        .json(&serde_json::json!({
+            "agent": { "name": "Minecraft", "version": 1 },
+            "username": username,
+            "password": password.expose_secret(),
+            "requestUser": true,
+        }))
+        .send()
+        .await?;
+
+    let status = resp.status();
+    let body = resp.bytes().await?;
+    if !status.is_success() {
+        return Err(match serde_json::from_slice::<ErrorResponse>(&body) {
+            Ok(error) => AuthError::AuthServer {
+                error: error.error,
+                message: error.error_message,
+            },
+            Err(_) => AuthError::AuthServer {
+                error: status.to_string(),
+                message: String::from_utf8_lossy(&body).into_owned(),
+            },
+        });
+    }
+
+    let data: AuthenticateResponse = serde_json::from_slice(&body)?;
+    let profile = data.selected_profile.ok_or(AuthError::NoGameOwnership)?;
+    Ok(MinecraftProfile {
+        uuid: profile.id.replace('-', ""),
+        username: profile.name,
+        skin: None,
+        cape: None,
+        access_token: SecretString::from(data.access_token),
+        expires_at: None,
+        refresh_token: None,
+        kind: AccountKind::Custom,
+    })
+}
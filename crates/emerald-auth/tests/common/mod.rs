@@ -0,0 +1,52 @@
+//! Helpers shared by the integration tests that talk to a mock HTTP server.
+
+// Each test file compiles its own copy of this module and uses only part of it.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use emerald_auth::Endpoints;
+use serde_json::json;
+use test_support::{Response, serve};
+
+/// A recorded request: path and body.
+pub type Requests = Arc<Mutex<Vec<(String, String)>>>;
+
+/// Helper: serve canned JSON responses per path. Each path answers with its
+/// responses in order and repeats the last one once they run out.
+pub fn mock_server(routes: Vec<(&str, Vec<(u16, serde_json::Value)>)>) -> (String, Requests) {
+    let routes: HashMap<String, Vec<(u16, serde_json::Value)>> = routes.into_iter().map(|(path, responses)| (path.to_owned(), responses)).collect();
+    let routes = Mutex::new(routes);
+    let requests: Requests = Arc::new(Mutex::new(Vec::new()));
+
+    let recorded = requests.clone();
+    let server = serve(move |request| {
+        recorded.lock().unwrap().push((request.path.clone(), request.body_text()));
+        let mut routes = routes.lock().unwrap();
+        let (status, payload) = match routes.get_mut(&request.path) {
+            Some(responses) if responses.len() > 1 => responses.remove(0),
+            Some(responses) => responses[0].clone(),
+            None => (404, json!({})),
+        };
+        json_response(status, payload.to_string())
+    });
+
+    (server.base_url().to_owned(), requests)
+}
+
+pub fn json_response(status: u16, body: impl Into<Vec<u8>>) -> Response {
+    Response::status(status).with_header("Content-Type", "application/json").with_body(body)
+}
+
+pub fn endpoints(base: &str) -> Endpoints {
+    Endpoints {
+        device_code: format!("{base}/devicecode"),
+        token: format!("{base}/token"),
+        xbox_live: format!("{base}/xbl"),
+        xsts: format!("{base}/xsts"),
+        minecraft_login: format!("{base}/mc/login"),
+        entitlements: format!("{base}/mc/entitlements"),
+        profile: format!("{base}/mc/profile"),
+    }
+}
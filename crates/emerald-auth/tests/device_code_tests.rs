@@ -0,0 +1,282 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{endpoints, mock_server};
+use emerald_auth::{AuthError, DeviceCode, MicrosoftAuth, microsoft, minecraft, xbox};
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::json;
+
+fn device_code_response() -> serde_json::Value {
+    json!({
+        "device_code": "device-123",
+        "user_code": "ABCD-EFGH",
+        "verification_uri": "https://www.microsoft.com/link",
+        "expires_in": 900,
+        "interval": 0,
+        "message": "To sign in, use a web browser to open the page https://www.microsoft.com/link and enter the code ABCD-EFGH to authenticate."
+    })
+}
+
+fn token_response(access: &str, refresh: &str) -> serde_json::Value {
+    json!({
+        "token_type": "Bearer",
+        "access_token": access,
+        "refresh_token": refresh,
+        "expires_in": 3600
+    })
+}
+
+fn xbox_response(token: &str) -> serde_json::Value {
+    json!({
+        "Token": token,
+        "DisplayClaims": { "xui": [{ "uhs": "user-hash-1" }] }
+    })
+}
+
+/// Routes for the Xbox Live → XSTS → Minecraft → profile part of the chain.
+fn chain_routes() -> Vec<(&'static str, Vec<(u16, serde_json::Value)>)> {
+    vec![
+        ("/xbl", vec![(200, xbox_response("xbl-token"))]),
+        ("/xsts", vec![(200, xbox_response("xsts-token"))]),
+        ("/mc/login", vec![(200, json!({ "access_token": "mc-token", "expires_in": 86400 }))]),
+        ("/mc/entitlements", vec![(200, json!({ "items": [{ "name": "game_minecraft" }] }))]),
+        (
+            "/mc/profile",
+            vec![(200, json!({ "id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch" }))],
+        ),
+    ]
+}
+
+#[tokio::test]
+async fn request_device_code_parses_response() {
+    let (base, requests) = mock_server(vec![("/devicecode", vec![(200, device_code_response())])]);
+    let http = reqwest::Client::new();
+
+    let code = microsoft::request_device_code_at(&http, &format!("{base}/devicecode"), "client-1")
+        .await
+        .unwrap();
+    assert_eq!(code.device_code.expose_secret(), "device-123");
+    assert_eq!(code.user_code, "ABCD-EFGH");
+    assert_eq!(code.verification_uri, "https://www.microsoft.com/link");
+    assert_eq!(code.expires_in, 900);
+    assert_eq!(code.interval, 0);
+
+    let body = &requests.lock().unwrap()[0].1;
+    assert!(body.contains("client_id=client-1"));
+    assert!(body.contains("scope=XboxLive.signin+offline_access"));
+}
+
+#[tokio::test]
+async fn poll_device_code_reports_pending_then_tokens() {
+    let (base, requests) = mock_server(vec![(
+        "/token",
+        vec![
+            (400, json!({ "error": "authorization_pending", "error_description": "waiting" })),
+            (400, json!({ "error": "slow_down" })),
+            (200, token_response("ms-access", "ms-refresh")),
+        ],
+    )])
+    .await;
+    let http = reqwest::Client::new();
+    let token_url = format!("{base}/token");
+    let code = DeviceCode {
+        device_code: SecretString::from("device-123".to_owned()),
+        user_code: "ABCD-EFGH".into(),
+        verification_uri: "https://www.microsoft.com/link".into(),
+        message: String::new(),
+        expires_in: 900,
+        interval: 0,
+    };
+
+    let first = microsoft::poll_device_code_at(&http, &token_url, "c", &code).await.unwrap();
+    assert!(matches!(first, microsoft::DevicePoll::Pending));
+    let second = microsoft::poll_device_code_at(&http, &token_url, "c", &code).await.unwrap();
+    assert!(matches!(second, microsoft::DevicePoll::SlowDown));
+    let third = microsoft::poll_device_code_at(&http, &token_url, "c", &code).await.unwrap();
+    match third {
+        microsoft::DevicePoll::Authorized(tokens) => {
+            assert_eq!(tokens.access_token.expose_secret(), "ms-access");
+            assert_eq!(tokens.refresh_token.unwrap().expose_secret(), "ms-refresh");
+        }
+        other => panic!("expected tokens, got {other:?}"),
+    }
+
+    let requests = requests.lock().unwrap();
+    let token_body = &requests.iter().find(|(path, _)| path == "/token").unwrap().1;
+    assert!(token_body.contains("grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Adevice_code"));
+    assert!(token_body.contains("device_code=device-123"));
+}
+
+#[tokio::test]
+async fn poll_device_code_surfaces_declined_login() {
+    let (base, _) = mock_server(vec![(
+        "/token",
+        vec![(400, json!({ "error": "authorization_declined", "error_description": "User declined" }))],
+    )])
+    .await;
+    let code = DeviceCode {
+        device_code: SecretString::from("device-123".to_owned()),
+        user_code: String::new(),
+        verification_uri: String::new(),
+        message: String::new(),
+        expires_in: 900,
+        interval: 0,
+    };
+
+    let err = microsoft::poll_device_code_at(&reqwest::Client::new(), &format!("{base}/token"), "c", &code)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AuthError::OAuth { ref error, .. } if error == "authorization_declined"));
+}
+
+#[tokio::test]
+async fn xbox_live_exchange() {
+    let (base, requests) = mock_server(vec![("/xbl", vec![(200, xbox_response("xbl-token"))])]);
+    let token = SecretString::from("ms-access".to_owned());
+
+    let xbl = xbox::authenticate_xbox_live_at(&reqwest::Client::new(), &format!("{base}/xbl"), &token)
+        .await
+        .unwrap();
+    assert_eq!(xbl.token.expose_secret(), "xbl-token");
+    assert_eq!(xbl.user_hash, "user-hash-1");
+
+    let body: serde_json::Value = serde_json::from_str(&requests.lock().unwrap()[0].1).unwrap();
+    assert_eq!(body["Properties"]["RpsTicket"], "d=ms-access");
+}
+
+#[tokio::test]
+async fn xsts_exchange() {
+    let (base, _) = mock_server(vec![("/xsts", vec![(200, xbox_response("xsts-token"))])]);
+    let token = SecretString::from("xbl-token".to_owned());
+
+    let xsts = xbox::authenticate_xsts_at(&reqwest::Client::new(), &format!("{base}/xsts"), &token)
+        .await
+        .unwrap();
+    assert_eq!(xsts.token.expose_secret(), "xsts-token");
+    assert_eq!(xsts.user_hash, "user-hash-1");
+}
+
+#[tokio::test]
+async fn xsts_reports_missing_xbox_account() {
+    let (base, _) = mock_server(vec![(
+        "/xsts",
+        vec![(
+            401,
+            json!({ "Identity": "0", "XErr": 2148916233u64, "Message": "", "Redirect": "https://start.ui.xboxlive.com/CreateAccount" }),
+        )],
+    )])
+    .await;
+    let token = SecretString::from("xbl-token".to_owned());
+
+    let err = xbox::authenticate_xsts_at(&reqwest::Client::new(), &format!("{base}/xsts"), &token)
+        .await
+        .unwrap_err();
+    match err {
+        AuthError::Xsts { xerr, message } => {
+            assert_eq!(xerr, 2148916233);
+            assert!(message.contains("does not have an Xbox account"));
+        }
+        other => panic!("expected an XSTS error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn minecraft_login_ownership_and_profile() {
+    let (base, requests) = mock_server(chain_routes());
+    let http = reqwest::Client::new();
+    let xsts = SecretString::from("xsts-token".to_owned());
+
+    let mc = minecraft::authenticate_minecraft_at(&http, &format!("{base}/mc/login"), &xsts, "user-hash-1")
+        .await
+        .unwrap();
+    assert_eq!(mc.access_token.expose_secret(), "mc-token");
+    assert_eq!(mc.expires_in, 86400);
+
+    let owns = minecraft::check_ownership_at(&http, &format!("{base}/mc/entitlements"), &mc.access_token)
+        .await
+        .unwrap();
+    assert!(owns);
+
+    let profile = minecraft::fetch_profile_at(&http, &format!("{base}/mc/profile"), &mc.access_token)
+        .await
+        .unwrap();
+    assert_eq!(profile.name, "Notch");
+
+    let body: serde_json::Value = serde_json::from_str(&requests.lock().unwrap()[0].1).unwrap();
+    assert_eq!(body["identityToken"], "XBL3.0 x=user-hash-1;xsts-token");
+}
+
+#[tokio::test]
+async fn device_code_flow_end_to_end() {
+    let mut routes = chain_routes();
+    routes.push(("/devicecode", vec![(200, device_code_response())]));
+    routes.push((
+        "/token",
+        vec![
+            (400, json!({ "error": "authorization_pending" })),
+            (400, json!({ "error": "authorization_pending" })),
+            (200, token_response("ms-access", "ms-refresh")),
+        ],
+    ));
+    let (base, _) = mock_server(routes);
+
+    let auth = MicrosoftAuth::new("client-1").with_endpoints(endpoints(&base));
+    let code = auth.begin_device_code().await.unwrap();
+    assert_eq!(code.user_code, "ABCD-EFGH");
+
+    let profile = auth.poll_token(&code).await.unwrap();
+    assert_eq!(profile.username, "Notch");
+    assert_eq!(profile.uuid, "069a79f444e94726a5befca90e38aaf5");
+    assert_eq!(profile.access_token.expose_secret(), "mc-token");
+    assert_eq!(profile.refresh_token.unwrap().expose_secret(), "ms-refresh");
+}
+
+#[tokio::test]
+async fn device_code_flow_times_out() {
+    let (base, _) = mock_server(vec![("/token", vec![(400, json!({ "error": "authorization_pending" }))])]);
+    let code = DeviceCode {
+        device_code: SecretString::from("device-123".to_owned()),
+        user_code: String::new(),
+        verification_uri: String::new(),
+        message: String::new(),
+        expires_in: 900,
+        interval: 1,
+    };
+
+    let auth = MicrosoftAuth::new("client-1")
+        .with_endpoints(endpoints(&base))
+        .with_timeout(Duration::from_secs(1));
+    let err = auth.poll_token(&code).await.unwrap_err();
+    assert!(matches!(err, AuthError::Timeout(_)));
+}
+
+#[tokio::test]
+async fn refresh_runs_full_chain() {
+    let mut routes = chain_routes();
+    routes.push(("/token", vec![(200, token_response("ms-access-2", "ms-refresh-2"))]));
+    let (base, requests) = mock_server(routes);
+
+    let auth = MicrosoftAuth::new("client-1").with_endpoints(endpoints(&base));
+    let profile = auth.refresh(&SecretString::from("ms-refresh-1".to_owned())).await.unwrap();
+    assert_eq!(profile.username, "Notch");
+    assert_eq!(profile.refresh_token.unwrap().expose_secret(), "ms-refresh-2");
+
+    let requests = requests.lock().unwrap();
+    let token_body = &requests.iter().find(|(path, _)| path == "/token").unwrap().1;
+    assert!(token_body.contains("grant_type=refresh_token"));
+    assert!(token_body.contains("refresh_token=ms-refresh-1"));
+}
+
+#[tokio::test]
+async fn chain_rejects_accounts_without_the_game() {
+    let mut routes = chain_routes();
+    routes.retain(|(path, _)| *path != "/mc/entitlements");
+    routes.push(("/mc/entitlements", vec![(200, json!({ "items": [] }))]));
+    routes.push(("/token", vec![(200, token_response("ms-access", "ms-refresh"))]));
+    let (base, _) = mock_server(routes);
+
+    let auth = MicrosoftAuth::new("client-1").with_endpoints(endpoints(&base));
+    let err = auth.refresh(&SecretString::from("ms-refresh".to_owned())).await.unwrap_err();
+    assert!(matches!(err, AuthError::NoGameOwnership));
+}
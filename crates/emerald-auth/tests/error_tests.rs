@@ -101,3 +101,12 @@ fn json_error_converts_to_auth_error() {
     let auth_err: AuthError = json_err.into();
     assert!(matches!(auth_err, AuthError::Decode(_)));
 }
+
+#[test]
+fn error_display_invalid_username() {
+    let err = AuthError::InvalidUsername("a b".into());
+    assert_eq!(
+        err.to_string(),
+        "invalid username \"a b\": must be 3-16 characters of A-Z, a-z, 0-9 or _"
+    );
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use common::json_response;
+use emerald_auth::AuthError;
+use emerald_auth::mojang::{username_for_uuid_at, uuid_for_username_at};
+use test_support::{Response, serve};
+
+/// Helper: serve a fixed `(status, extra header, body)` per path; unknown paths get a 404.
+fn mock_server(routes: Vec<(&str, u16, Option<(&'static str, &'static str)>, &'static str)>) -> String {
+    let routes: HashMap<String, (u16, Option<(&str, &str)>, &str)> = routes
+        .into_iter()
+        .map(|(path, status, header, body)| (path.to_owned(), (status, header, body)))
+        .collect();
+
+    let server = serve(move |request| match routes.get(&request.path) {
+        Some(&(status, header, body)) => {
+            let response = json_response(status, body);
+            match header {
+                Some((name, value)) => response.with_header(name, value),
+                None => response,
+            }
+        }
+        None => Response::not_found(),
+    });
+
+    server.base_url().to_owned()
+}
+
+const NOTCH: &str = r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch"}"#;
+
+#[tokio::test]
+async fn resolves_username_to_uuid() {
+    let base = mock_server(vec![("/users/profiles/minecraft/notch", 200, None, NOTCH)]);
+    let uuid = uuid_for_username_at(&reqwest::Client::new(), &format!("{base}/users/profiles/minecraft"), "notch")
+        .await
+        .unwrap();
+    assert_eq!(uuid, "069a79f444e94726a5befca90e38aaf5");
+}
+
+#[tokio::test]
+async fn resolves_uuid_to_username() {
+    let base = mock_server(vec![("/lookup/069a79f444e94726a5befca90e38aaf5", 200, None, NOTCH)]);
+    let name = username_for_uuid_at(&reqwest::Client::new(), &format!("{base}/lookup"), "069a79f4-44e9-4726-a5be-fca90e38aaf5")
+        .await
+        .unwrap();
+    assert_eq!(name, "Notch");
+}
+
+#[tokio::test]
+async fn missing_username_is_not_found() {
+    let base = mock_server(vec![("/users/profiles/minecraft/nobody_here_42", 204, None, "")]);
+    let client = reqwest::Client::new();
+
+    let err = uuid_for_username_at(&client, &format!("{base}/users/profiles/minecraft"), "nobody_here_42")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AuthError::NotFound(ref name) if name == "nobody_here_42"));
+
+    // Newer endpoints answer with a 404 instead of an empty 204.
+    let err = uuid_for_username_at(&client, &format!("{base}/users/profiles/minecraft"), "someone_else")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AuthError::NotFound(_)));
+}
+
+#[tokio::test]
+async fn rate_limit_reports_retry_after() {
+    let base = mock_server(vec![("/users/profiles/minecraft/notch", 429, Some(("Retry-After", "30")), "")]);
+    let err = uuid_for_username_at(&reqwest::Client::new(), &format!("{base}/users/profiles/minecraft"), "notch")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AuthError::RateLimited(Some(d)) if d == Duration::from_secs(30)));
+    assert_eq!(err.to_string(), "rate limited by Mojang, retry after 30s");
+}
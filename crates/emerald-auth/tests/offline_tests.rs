@@ -0,0 +1,52 @@
+use emerald_auth::offline::{is_valid_username, offline_uuid};
+use emerald_auth::{AccountKind, AuthError, offline_profile};
+use secrecy::ExposeSecret;
+
+#[test]
+fn offline_uuid_matches_known_values() {
+    assert_eq!(offline_uuid("Notch"), "b50ad385829d3141a2167e7d7539ba7f");
+    assert_eq!(offline_uuid("jeb_"), "a762f5604fce3236812ab80efff0b62b");
+    assert_eq!(offline_uuid("Steve"), "5627dd98e6be3c21b8a8e92344183641");
+}
+
+#[test]
+fn offline_uuid_is_version_3() {
+    let uuid = offline_uuid("Notch");
+    assert_eq!(&uuid[12..13], "3");
+    assert!(matches!(&uuid[16..17], "8" | "9" | "a" | "b"));
+}
+
+#[test]
+fn offline_uuid_is_case_sensitive() {
+    assert_ne!(offline_uuid("notch"), offline_uuid("Notch"));
+}
+
+#[test]
+fn offline_profile_fills_launch_fields() {
+    let profile = offline_profile("Notch").unwrap();
+    assert_eq!(profile.username, "Notch");
+    assert_eq!(profile.uuid, "b50ad385829d3141a2167e7d7539ba7f");
+    assert_eq!(profile.access_token.expose_secret(), "0");
+    assert!(profile.refresh_token.is_none());
+    assert_eq!(profile.kind, AccountKind::Offline);
+    assert_eq!(profile.user_type(), "legacy");
+}
+
+#[test]
+fn username_validation() {
+    assert!(is_valid_username("abc"));
+    assert!(is_valid_username("Player_123"));
+    assert!(is_valid_username("sixteen_chars_ok"));
+
+    assert!(!is_valid_username("ab"));
+    assert!(!is_valid_username("seventeen_chars_x"));
+    assert!(!is_valid_username("has space"));
+    assert!(!is_valid_username("dash-name"));
+    assert!(!is_valid_username("ünïcode"));
+}
+
+#[test]
+fn offline_profile_rejects_invalid_usernames() {
+    let err = offline_profile("no").unwrap_err();
+    assert!(matches!(err, AuthError::InvalidUsername(ref name) if name == "no"));
+}
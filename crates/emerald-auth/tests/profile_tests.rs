@@ -0,0 +1,100 @@
+mod common;
+
+use std::time::Duration;
+
+use common::json_response;
+
+use emerald_auth::profile::{decode_textures, fetch_skin_at};
+use emerald_auth::types::SkinVariant;
+use emerald_auth::{AuthError, SkinCache};
+use serde_json::json;
+use test_support::{MockServer, serve};
+
+/// Captured `textures` property of a profile with a slim skin and a cape.
+const NOTCH_TEXTURES: &str = "eyJ0aW1lc3RhbXAiOiAxNzE1MDAwMDAwMDAwLCAicHJvZmlsZUlkIjogIjA2OWE3OWY0NDRlOTQ3MjZhNWJlZmNhOTBlMzhhYWY1IiwgInByb2ZpbGVOYW1lIjogIk5vdGNoIiwgInRleHR1cmVzIjogeyJTS0lOIjogeyJ1cmwiOiAiaHR0cDovL3RleHR1cmVzLm1pbmVjcmFmdC5uZXQvdGV4dHVyZS8yOTIwMDlhNDkyNWI1OGYwMmM3N2RhZGMzZWNlZjA3ZWE0Yzc0NzJmNjRlMGZkYzMyY2U1NTIyNDg5MzYyNjgwIiwgIm1ldGFkYXRhIjogeyJtb2RlbCI6ICJzbGltIn19LCAiQ0FQRSI6IHsidXJsIjogImh0dHA6Ly90ZXh0dXJlcy5taW5lY3JhZnQubmV0L3RleHR1cmUvMjM0MGMwZTAzZGQyNGExMWIxNWE4YjMzYzJhN2U5ZTMyYWJiMjA1MWIyNDgxZDBiYTdkZWZkNjM1Y2E3YTkzMyJ9fX0=";
+
+/// Captured `textures` property of a profile with a classic skin and no cape.
+const JEB_TEXTURES: &str = "eyJ0aW1lc3RhbXAiOiAxNzE1MDAwMDAwMDAwLCAicHJvZmlsZUlkIjogIjg1M2M4MGVmM2MzNzQ5ZmRhYTQ5OTM4YjY3NGFkYWU2IiwgInByb2ZpbGVOYW1lIjogImplYl8iLCAidGV4dHVyZXMiOiB7IlNLSU4iOiB7InVybCI6ICJodHRwOi8vdGV4dHVyZXMubWluZWNyYWZ0Lm5ldC90ZXh0dXJlLzdmZDliYTQyYTdjODFlZWVhMjJmMTUyNDI3MWFlODVhOGUwNDVjZTBhZjVhNmFlMTZjNjQwNmFlOTE3ZTY4YjUifX19";
+
+/// Path of the session server's profile lookups on the mock server.
+const PROFILE_PATH: &str = "/session/minecraft/profile";
+
+/// Helper: answer every request with the session profile for `textures`.
+fn session_server(textures: &'static str) -> MockServer {
+    serve(move |_| {
+        let body = json!({
+            "id": "069a79f444e94726a5befca90e38aaf5",
+            "name": "Notch",
+            "properties": [{"name": "textures", "value": textures}]
+        });
+        json_response(200, body.to_string())
+    })
+}
+
+#[test]
+fn decodes_skin_and_cape() {
+    let skin = decode_textures(NOTCH_TEXTURES).unwrap();
+    assert_eq!(skin.uuid, "069a79f444e94726a5befca90e38aaf5");
+    assert_eq!(skin.username, "Notch");
+    assert_eq!(
+        skin.skin_url.as_deref(),
+        Some("http://textures.minecraft.net/texture/292009a4925b58f02c77dadc3ecef07ea4c7472f64e0fdc32ce5522489362680")
+    );
+    assert_eq!(
+        skin.cape_url.as_deref(),
+        Some("http://textures.minecraft.net/texture/2340c0e03dd24a11b15a8b33c2a7e9e32abb2051b2481d0ba7defd635ca7a933")
+    );
+    assert_eq!(skin.model, SkinVariant::Slim);
+}
+
+#[test]
+fn decodes_profile_without_cape() {
+    let skin = decode_textures(JEB_TEXTURES).unwrap();
+    assert_eq!(skin.username, "jeb_");
+    assert!(skin.skin_url.is_some());
+    assert!(skin.cape_url.is_none());
+    assert_eq!(skin.model, SkinVariant::Classic);
+}
+
+#[test]
+fn rejects_invalid_base64() {
+    let err = decode_textures("not base64!").unwrap_err();
+    assert!(matches!(err, AuthError::Minecraft(_)));
+}
+
+#[tokio::test]
+async fn fetch_skin_at_decodes_response() {
+    let server = session_server(JEB_TEXTURES);
+    let skin = fetch_skin_at(&reqwest::Client::new(), &server.url(PROFILE_PATH), "853c80ef-3c37-49fd-aa49-938b674adae6")
+        .await
+        .unwrap();
+    assert_eq!(skin.username, "jeb_");
+    assert!(skin.cape_url.is_none());
+}
+
+#[tokio::test]
+async fn cache_reuses_skin_within_ttl() {
+    let server = session_server(NOTCH_TEXTURES);
+    let client = reqwest::Client::new();
+    let cache = SkinCache::new(Duration::from_secs(60)).with_url(server.url(PROFILE_PATH));
+
+    let first = cache.fetch(&client, "069a79f4-44e9-4726-a5be-fca90e38aaf5").await.unwrap();
+    let second = cache.fetch(&client, "069a79f444e94726a5befca90e38aaf5").await.unwrap();
+    assert_eq!(first, second);
+    assert_eq!(server.hits(), 1);
+
+    cache.invalidate("069a79f444e94726a5befca90e38aaf5");
+    cache.fetch(&client, "069a79f444e94726a5befca90e38aaf5").await.unwrap();
+    assert_eq!(server.hits(), 2);
+}
+
+#[tokio::test]
+async fn cache_refetches_after_ttl() {
+    let server = session_server(NOTCH_TEXTURES);
+    let client = reqwest::Client::new();
+    let cache = SkinCache::new(Duration::ZERO).with_url(server.url(PROFILE_PATH));
+
+    cache.fetch(&client, "069a79f444e94726a5befca90e38aaf5").await.unwrap();
+    cache.fetch(&client, "069a79f444e94726a5befca90e38aaf5").await.unwrap();
+    assert_eq!(server.hits(), 2);
+}
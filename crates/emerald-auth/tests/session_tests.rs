@@ -0,0 +1,110 @@
+mod common;
+
+use std::time::{Duration, SystemTime};
+
+use common::{endpoints, mock_server};
+use emerald_auth::session::REFRESH_MARGIN;
+use emerald_auth::{AccountKind, AuthError, MicrosoftAuth, Session};
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::json;
+
+fn session(expires_at: SystemTime) -> Session {
+    Session {
+        username: "Notch".into(),
+        uuid: "069a79f444e94726a5befca90e38aaf5".into(),
+        access_token: SecretString::from("old-mc-token".to_owned()),
+        expires_at: Some(expires_at),
+        refresh_token: Some(SecretString::from("old-refresh".to_owned())),
+        kind: AccountKind::Microsoft,
+    }
+}
+
+fn refresh_routes() -> Vec<(&'static str, Vec<(u16, serde_json::Value)>)> {
+    let xbox = |token: &str| json!({ "Token": token, "DisplayClaims": { "xui": [{ "uhs": "user-hash-1" }] } });
+    vec![
+        (
+            "/token",
+            vec![(
+                200,
+                json!({ "token_type": "Bearer", "access_token": "ms-access", "refresh_token": "new-refresh", "expires_in": 3600 }),
+            )],
+        ),
+        ("/xbl", vec![(200, xbox("xbl-token"))]),
+        ("/xsts", vec![(200, xbox("xsts-token"))]),
+        ("/mc/login", vec![(200, json!({ "access_token": "new-mc-token", "expires_in": 86400 }))]),
+        ("/mc/entitlements", vec![(200, json!({ "items": [{ "name": "game_minecraft" }] }))]),
+        (
+            "/mc/profile",
+            vec![(200, json!({ "id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch" }))],
+        ),
+    ]
+}
+
+#[test]
+fn expiry_checks() {
+    let now = SystemTime::now();
+    assert!(session(now - Duration::from_secs(1)).is_expired());
+    let fresh = session(now + Duration::from_secs(3600));
+    assert!(!fresh.is_expired());
+    assert!(!fresh.expires_within(Duration::from_secs(60)));
+    assert!(fresh.expires_within(Duration::from_secs(7200)));
+
+    let offline = Session::from(emerald_auth::offline_profile("Notch").unwrap());
+    assert!(!offline.is_expired());
+    assert_eq!(offline.user_type(), "legacy");
+}
+
+#[tokio::test]
+async fn expired_session_is_refreshed() {
+    let (base, requests) = mock_server(refresh_routes());
+    let auth = MicrosoftAuth::new("client-1").with_endpoints(endpoints(&base));
+    let mut session = session(SystemTime::now() - Duration::from_secs(60));
+
+    let refreshed = session.ensure_valid(&auth).await.unwrap();
+    assert_eq!(refreshed.access_token.expose_secret(), "new-mc-token");
+    assert!(!refreshed.is_expired());
+    assert!(!refreshed.expires_within(REFRESH_MARGIN));
+    assert_eq!(session.refresh_token.as_ref().unwrap().expose_secret(), "new-refresh");
+    assert_eq!(session.username, "Notch");
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests[0].0, "/token");
+    assert!(requests[0].1.contains("refresh_token=old-refresh"));
+    assert_eq!(requests.len(), 6);
+}
+
+#[tokio::test]
+async fn session_close_to_expiry_is_refreshed() {
+    let (base, requests) = mock_server(refresh_routes());
+    let auth = MicrosoftAuth::new("client-1").with_endpoints(endpoints(&base));
+    let mut session = session(SystemTime::now() + REFRESH_MARGIN / 2);
+
+    session.ensure_valid(&auth).await.unwrap();
+    assert_eq!(session.access_token.expose_secret(), "new-mc-token");
+    assert!(!requests.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn fresh_session_is_not_refreshed() {
+    let (base, requests) = mock_server(refresh_routes());
+    let auth = MicrosoftAuth::new("client-1").with_endpoints(endpoints(&base));
+    let mut session = session(SystemTime::now() + Duration::from_secs(3600));
+
+    let unchanged = session.ensure_valid(&auth).await.unwrap();
+    assert_eq!(unchanged.access_token.expose_secret(), "old-mc-token");
+    assert!(requests.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn expired_session_without_refresh_token_fails() {
+    let (base, requests) = mock_server(refresh_routes());
+    let auth = MicrosoftAuth::new("client-1").with_endpoints(endpoints(&base));
+    let mut session = Session {
+        refresh_token: None,
+        ..session(SystemTime::now() - Duration::from_secs(60))
+    };
+
+    let err = session.ensure_valid(&auth).await.unwrap_err();
+    assert!(matches!(err, AuthError::NoRefreshToken(name) if name == "Notch"));
+    assert!(requests.lock().unwrap().is_empty());
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::mock_server;
+use emerald_auth::{AccountKind, AuthError, AuthServer, Session, yggdrasil};
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::json;
+
+fn authenticate_response() -> serde_json::Value {
+    json!({
+        "accessToken": "eyJhbGciOiJIUzI1NiJ9.custom-token",
+        "clientToken": "e3a8c5b0f1d84f6e9c2a7b4d1e0f3a6c",
+        "availableProfiles": [{"id": "ffc8fdc95824509e8a57c99b940fb996", "name": "ErickSkrauch"}],
+        "selectedProfile": {"id": "ffc8fdc9-5824-509e-8a57-c99b940fb996", "name": "ErickSkrauch"},
+        "user": {"id": "ffc8fdc95824509e8a57c99b940fb996", "properties": [{"name": "preferredLanguage", "value": "en"}]}
+    })
+}
+
+#[test]
+fn custom_server_trims_api_url() {
+    let server = AuthServer::custom("https://authserver.ely.by/api/authlib-injector/");
+    assert_eq!(server, AuthServer::Custom("https://authserver.ely.by/api/authlib-injector".into()));
+    assert_eq!(server.api_url(), Some("https://authserver.ely.by/api/authlib-injector"));
+    assert_eq!(AuthServer::default(), AuthServer::Mojang);
+    assert_eq!(AuthServer::Mojang.api_url(), None);
+}
+
+#[tokio::test]
+async fn authenticate_parses_custom_response() {
+    let (base, requests) = mock_server(vec![("/api/authserver/authenticate", vec![(200, authenticate_response())])]);
+    let password = SecretString::from("hunter2".to_owned());
+
+    let profile = yggdrasil::authenticate(&reqwest::Client::new(), &format!("{base}/api/"), "erick@skrauch.net", &password)
+        .await
+        .unwrap();
+    assert_eq!(profile.username, "ErickSkrauch");
+    assert_eq!(profile.uuid, "ffc8fdc95824509e8a57c99b940fb996");
+    assert_eq!(profile.access_token.expose_secret(), "eyJhbGciOiJIUzI1NiJ9.custom-token");
+    assert_eq!(profile.kind, AccountKind::Custom);
+    assert_eq!(profile.user_type(), "mojang");
+    assert!(profile.refresh_token.is_none());
+    assert!(!Session::from(profile).is_expired());
+
+    let requests = requests.lock().unwrap();
+    let body: serde_json::Value = serde_json::from_str(&requests[0].1).unwrap();
+    assert_eq!(body["agent"], json!({"name": "Minecraft", "version": 1}));
+    assert_eq!(body["username"], "erick@skrauch.net");
+    assert_eq!(body["password"], "hunter2");
+}
+
+#[tokio::test]
+async fn authenticate_reports_server_error() {
+    let (base, _) = mock_server(vec![(
+        "/authserver/authenticate",
+        vec![(
+            401,
+            json!({"error": "ForbiddenOperationException", "errorMessage": "Invalid credentials. Invalid username or password."}),
+        )],
+    )])
+    .await;
+    let password = SecretString::from("wrong".to_owned());
+
+    let err = yggdrasil::authenticate(&reqwest::Client::new(), &base, "erick@skrauch.net", &password)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, AuthError::AuthServer { ref error, ref message }
+            if error == "ForbiddenOperationException" && message.starts_with("Invalid credentials")),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn authenticate_without_profile_is_not_owned() {
+    let mut response = authenticate_response();
+    response.as_object_mut().unwrap().remove("selectedProfile");
+    response["availableProfiles"] = json!([]);
+    let (base, _) = mock_server(vec![("/authserver/authenticate", vec![(200, response)])]);
+    let password = SecretString::from("hunter2".to_owned());
+
+    let err = yggdrasil::authenticate(&reqwest::Client::new(), &base, "erick@skrauch.net", &password)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AuthError::NoGameOwnership));
+}
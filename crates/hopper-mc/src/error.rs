@@ -37,6 +37,39 @@ pub enum ContentError {
 
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
+
+    #[error("dependency conflict: {}", join_conflicts(.0))]
+    DependencyConflict(Vec<DependencyConflict>),
+}
+
+/// Two dependents require different versions of the same project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyConflict {
+    pub project_id: String,
+    /// Version already selected for the install set.
+    pub chosen_version: String,
+    /// Version pinned by `required_by`.
+    pub requested_version: String,
+    /// Version id of the dependent that asked for `requested_version`.
+    pub required_by: String,
+}
+
+impl std::fmt::Display for DependencyConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} requires {} {} but {} was already chosen",
+            self.required_by, self.project_id, self.requested_version, self.chosen_version
+        )
+    }
+}
+
+fn join_conflicts(conflicts: &[DependencyConflict]) -> String {
+    conflicts
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 /// Convenience alias used by trait and dispatch APIs.
@@ -50,7 +50,7 @@ pub mod platform;
 pub mod platforms;
 pub mod provider;
 
-pub use error::{ContentError, Result};
+pub use error::{ContentError, DependencyConflict, Result};
 pub use model::{
     Author, ContentBase, DatapackItem, Dependency, DependencyKind, License, Links, ModItem,
     PackItem, ProjectVersion, ResourcePackItem, ShaderPackItem, SideSupport, VersionFile,
@@ -58,7 +58,7 @@ pub use model::{
 };
 pub use modpack::{
     extract_overrides, parse_curseforge_pack, parse_modpack, parse_mrpack, ModpackFile,
-    ModpackFileEnv, ModpackManifest, ModpackSource,
+    ModpackFileEnv, ModpackManifest, ModpackSource, Mrpack, MrpackOverride,
 };
 pub use platform::{ContentType, Platform, SearchFilters, Sort};
 pub use platforms::{
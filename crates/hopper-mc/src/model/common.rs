@@ -125,6 +125,18 @@ pub struct ProjectVersion {
     pub platform: Platform,
 }
 
+impl ProjectVersion {
+    /// The file marked primary, or the first file when none is marked.
+    pub fn primary_file(&self) -> Option<&VersionFile> {
+        self.files.iter().find(|f| f.primary).or_else(|| self.files.first())
+    }
+
+    /// Dependencies that must be installed alongside this version.
+    pub fn required_dependencies(&self) -> impl Iterator<Item = &Dependency> {
+        self.dependencies.iter().filter(|d| d.kind == DependencyKind::Required)
+    }
+}
+
 /// Release channel of a version.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum VersionType {
@@ -149,6 +161,18 @@ pub struct VersionFile {
     pub hashes: HashMap<String, String>,
 }
 
+impl VersionFile {
+    /// Hex SHA-1 digest, if the platform reported one.
+    pub fn sha1(&self) -> Option<&str> {
+        self.hashes.get("sha1").map(String::as_str)
+    }
+
+    /// Hex SHA-512 digest, if the platform reported one (Modrinth always does).
+    pub fn sha512(&self) -> Option<&str> {
+        self.hashes.get("sha512").map(String::as_str)
+    }
+}
+
 /// How a mod interacts with the client/server side.
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SideSupport {
@@ -11,7 +11,7 @@ mod overrides;
 
 pub use curseforge_pack::parse_curseforge_pack;
 pub use manifest::{ModpackFile, ModpackFileEnv, ModpackManifest, ModpackSource};
-pub use mrpack::parse_mrpack;
+pub use mrpack::{parse_mrpack, Mrpack, MrpackOverride};
 pub use overrides::extract_overrides;
 
 use std::io::{Read, Seek};
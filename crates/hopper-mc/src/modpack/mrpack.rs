@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::io::{Read, Seek};
+use std::path::{Component, Path, PathBuf};
 
 use serde::Deserialize;
 
@@ -13,13 +14,10 @@ use crate::error::ContentError;
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct MrIndex {
-    #[allow(dead_code)]
     format_version: u32,
-    #[allow(dead_code)]
     game: String,
     version_id: String,
     name: String,
-    #[allow(dead_code)]
     summary: Option<String>,
     files: Vec<MrFile>,
     dependencies: HashMap<String, String>,
@@ -62,68 +60,445 @@ fn extract_loader(deps: &HashMap<String, String>) -> Option<(String, String)> {
     None
 }
 
+// ---------------------------------------------------------------------------
+// Validation
+// ---------------------------------------------------------------------------
+
+/// The only `formatVersion` defined by the `.mrpack` specification.
+const SUPPORTED_FORMAT_VERSION: u32 = 1;
+
+const OVERRIDES_PREFIX: &str = "overrides/";
+const CLIENT_OVERRIDES_PREFIX: &str = "client-overrides/";
+const SERVER_OVERRIDES_PREFIX: &str = "server-overrides/";
+
+/// Check that `path` stays inside the directory it is joined onto.
+///
+/// Rejects absolute paths, drive prefixes, `..` components, and
+/// backslash-separated paths (which Windows would treat as separators).
+fn safe_relative_path(path: &str) -> Option<PathBuf> {
+    if path.is_empty() || path.contains('\\') {
+        return None;
+    }
+    let mut out = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!out.as_os_str().is_empty()).then_some(out)
+}
+
+fn file_env(env: Option<MrEnv>) -> Option<ModpackFileEnv> {
+    env.map(|e| match (e.client.as_str(), e.server.as_str()) {
+        ("required", "unsupported") | ("optional", "unsupported") => ModpackFileEnv::Client,
+        ("unsupported", "required") | ("unsupported", "optional") => ModpackFileEnv::Server,
+        _ => ModpackFileEnv::Both,
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
 
+/// A file shipped inside an `.mrpack` under one of the overrides folders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MrpackOverride {
+    /// Entry name inside the archive (e.g. `overrides/config/sodium.json`).
+    pub archive_path: String,
+    /// Destination relative to the instance, with the overrides prefix
+    /// stripped (e.g. `config/sodium.json`).
+    pub path: PathBuf,
+    /// Which side the override applies to: `overrides/` is
+    /// [`ModpackFileEnv::Both`], `client-overrides/` and `server-overrides/`
+    /// are side-specific.
+    pub env: ModpackFileEnv,
+}
+
+/// An opened and validated Modrinth `.mrpack` archive.
+///
+/// [`Mrpack::open`] reads `modrinth.index.json`, checks the format version,
+/// game, name and `minecraft` dependency, and rejects any file or override
+/// path that would escape the instance directory (zip-slip). The archive is
+/// kept open so overrides can be extracted afterwards.
+///
+/// ```no_run
+/// use hopper_mc::Mrpack;
+///
+/// # fn example() -> hopper_mc::Result<()> {
+/// let mut pack = Mrpack::open(std::fs::File::open("Fabulously.Optimized.mrpack")?)?;
+/// println!("{} for Minecraft {}", pack.name(), pack.minecraft_version());
+/// for file in pack.files() {
+///     println!("download {} -> {}", file.download_urls[0], file.path);
+/// }
+/// pack.extract_overrides("instances/fo".as_ref(), true)?;
+/// # Ok(()) }
+/// ```
+pub struct Mrpack<R> {
+    archive: zip::ZipArchive<R>,
+    manifest: ModpackManifest,
+    summary: Option<String>,
+    dependencies: HashMap<String, String>,
+    overrides: Vec<MrpackOverride>,
+}
+
+impl<R: Read + Seek> Mrpack<R> {
+    /// Open an `.mrpack` archive and validate its index.
+    ///
+    /// Fails with [`ContentError::InvalidArchive`] when the index is missing
+    /// or malformed, or when any path in it (or any overrides entry) is
+    /// unsafe.
+    pub fn open(reader: R) -> crate::Result<Self> {
+        let mut archive = zip::ZipArchive::new(reader)
+            .map_err(|e| ContentError::InvalidArchive(format!("failed to open zip: {e}")))?;
+
+        let index: MrIndex = {
+            let index_file = archive
+                .by_name("modrinth.index.json")
+                .map_err(|_| ContentError::InvalidArchive("missing modrinth.index.json".into()))?;
+            serde_json::from_reader(index_file).map_err(|e| {
+                ContentError::InvalidArchive(format!("invalid modrinth.index.json: {e}"))
+            })?
+        };
+
+        if index.format_version != SUPPORTED_FORMAT_VERSION {
+            return Err(ContentError::InvalidArchive(format!(
+                "unsupported mrpack formatVersion {}",
+                index.format_version
+            )));
+        }
+        if index.game != "minecraft" {
+            return Err(ContentError::InvalidArchive(format!(
+                "unsupported mrpack game {:?}",
+                index.game
+            )));
+        }
+        if index.name.trim().is_empty() {
+            return Err(ContentError::InvalidArchive("mrpack has no name".into()));
+        }
+
+        let minecraft_version = index
+            .dependencies
+            .get("minecraft")
+            .cloned()
+            .ok_or_else(|| {
+                ContentError::InvalidArchive("missing minecraft version in dependencies".into())
+            })?;
+
+        let (loader, loader_version) = extract_loader(&index.dependencies).unwrap_or_else(|| {
+            // Fallback: vanilla pack with no loader
+            ("vanilla".to_string(), String::new())
+        });
+
+        let mut files = Vec::with_capacity(index.files.len());
+        for f in index.files {
+            if safe_relative_path(&f.path).is_none() {
+                return Err(ContentError::InvalidArchive(format!(
+                    "file path escapes the instance directory: {}",
+                    f.path
+                )));
+            }
+            files.push(ModpackFile {
+                path: f.path,
+                download_urls: f.downloads,
+                size: f.file_size,
+                hashes: f.hashes,
+                required: true,
+                env: file_env(f.env),
+                project_id: None,
+                file_id: None,
+            });
+        }
+
+        let mut overrides = Vec::new();
+        for name in archive.file_names() {
+            let (relative, env) = if let Some(r) = name.strip_prefix(OVERRIDES_PREFIX) {
+                (r, ModpackFileEnv::Both)
+            } else if let Some(r) = name.strip_prefix(CLIENT_OVERRIDES_PREFIX) {
+                (r, ModpackFileEnv::Client)
+            } else if let Some(r) = name.strip_prefix(SERVER_OVERRIDES_PREFIX) {
+                (r, ModpackFileEnv::Server)
+            } else {
+                continue;
+            };
+            // Directory entries carry no content.
+            if relative.is_empty() || name.ends_with('/') {
+                continue;
+            }
+            let path = safe_relative_path(relative).ok_or_else(|| {
+                ContentError::InvalidArchive(format!(
+                    "override path escapes the instance directory: {name}"
+                ))
+            })?;
+            overrides.push(MrpackOverride {
+                archive_path: name.to_string(),
+                path,
+                env,
+            });
+        }
+        overrides.sort_by(|a, b| a.archive_path.cmp(&b.archive_path));
+
+        let manifest = ModpackManifest {
+            name: index.name,
+            version: Some(index.version_id),
+            author: None,
+            minecraft_version,
+            loader,
+            loader_version,
+            files,
+            source: ModpackSource::Modrinth,
+        };
+
+        Ok(Self {
+            archive,
+            manifest,
+            summary: index.summary,
+            dependencies: index.dependencies,
+            overrides,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    pub fn summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+
+    pub fn minecraft_version(&self) -> &str {
+        &self.manifest.minecraft_version
+    }
+
+    /// Raw `dependencies` map from the index (e.g. `minecraft`,
+    /// `fabric-loader`).
+    pub fn dependencies(&self) -> &HashMap<String, String> {
+        &self.dependencies
+    }
+
+    /// Files to download into the instance. Every path has been checked to
+    /// stay inside the instance directory.
+    pub fn files(&self) -> &[ModpackFile] {
+        &self.manifest.files
+    }
+
+    /// Override files bundled in the archive, sorted by archive path.
+    pub fn overrides(&self) -> &[MrpackOverride] {
+        &self.overrides
+    }
+
+    pub fn manifest(&self) -> &ModpackManifest {
+        &self.manifest
+    }
+
+    pub fn into_manifest(self) -> ModpackManifest {
+        self.manifest
+    }
+
+    /// Copy the override files into `dest`.
+    ///
+    /// `overrides/` is always extracted; `client-overrides/` is added when
+    /// `client` is `true`, `server-overrides/` otherwise. Side-specific
+    /// files are written after the common ones so they win on conflicts.
+    /// Returns the number of files written.
+    pub fn extract_overrides(&mut self, dest: &Path, client: bool) -> crate::Result<u64> {
+        let side = if client {
+            ModpackFileEnv::Client
+        } else {
+            ModpackFileEnv::Server
+        };
+        let selected: Vec<MrpackOverride> = self
+            .overrides
+            .iter()
+            .filter(|o| o.env == ModpackFileEnv::Both)
+            .chain(self.overrides.iter().filter(|o| o.env == side))
+            .cloned()
+            .collect();
+
+        let mut count = 0u64;
+        for entry in selected {
+            let mut file = self.archive.by_name(&entry.archive_path).map_err(|e| {
+                ContentError::InvalidArchive(format!("bad zip entry {}: {e}", entry.archive_path))
+            })?;
+            let target = dest.join(&entry.path);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut out = std::fs::File::create(&target)?;
+            std::io::copy(&mut file, &mut out)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
 /// Parse a Modrinth `.mrpack` archive and return a unified [`ModpackManifest`].
 ///
 /// The reader must point to a valid ZIP archive containing `modrinth.index.json`.
+/// This is [`Mrpack::open`] without keeping the archive around.
 pub fn parse_mrpack<R: Read + Seek>(reader: R) -> crate::Result<ModpackManifest> {
-    let mut archive = zip::ZipArchive::new(reader)
-        .map_err(|e| ContentError::InvalidArchive(format!("failed to open zip: {e}")))?;
-
-    let index_file = archive
-        .by_name("modrinth.index.json")
-        .map_err(|_| ContentError::InvalidArchive("missing modrinth.index.json".into()))?;
-
-    let index: MrIndex = serde_json::from_reader(index_file)
-        .map_err(|e| ContentError::InvalidArchive(format!("invalid modrinth.index.json: {e}")))?;
-
-    let minecraft_version = index
-        .dependencies
-        .get("minecraft")
-        .cloned()
-        .ok_or_else(|| {
-            ContentError::InvalidArchive("missing minecraft version in dependencies".into())
-        })?;
-
-    let (loader, loader_version) = extract_loader(&index.dependencies).unwrap_or_else(|| {
-        // Fallback: vanilla pack with no loader
-        ("vanilla".to_string(), String::new())
-    });
-
-    let files = index
-        .files
-        .into_iter()
-        .map(|f| ModpackFile {
-            path: f.path,
-            download_urls: f.downloads,
-            size: f.file_size,
-            hashes: f.hashes,
-            required: true,
-            env: f.env.map(|e| match (e.client.as_str(), e.server.as_str()) {
-                ("required", "unsupported") | ("optional", "unsupported") => {
-                    ModpackFileEnv::Client
-                }
-                ("unsupported", "required") | ("unsupported", "optional") => {
-                    ModpackFileEnv::Server
-                }
-                _ => ModpackFileEnv::Both,
-            }),
-            project_id: None,
-            file_id: None,
-        })
-        .collect();
-
-    Ok(ModpackManifest {
-        name: index.name,
-        version: Some(index.version_id),
-        author: None,
-        minecraft_version,
-        loader,
-        loader_version,
-        files,
-        source: ModpackSource::Modrinth,
-    })
+    Mrpack::open(reader).map(Mrpack::into_manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::write::SimpleFileOptions;
+
+    use super::*;
+
+    const INDEX: &str = r#"{
+        "formatVersion": 1,
+        "game": "minecraft",
+        "versionId": "1.0.0",
+        "name": "Test Pack",
+        "summary": "A tiny pack",
+        "files": [
+            {
+                "path": "mods/sodium.jar",
+                "hashes": { "sha1": "aa", "sha512": "bb" },
+                "env": { "client": "required", "server": "unsupported" },
+                "downloads": ["https://cdn.modrinth.com/data/AANobbMI/versions/1/sodium.jar"],
+                "fileSize": 1024
+            },
+            {
+                "path": "mods/lithium.jar",
+                "hashes": { "sha1": "cc" },
+                "downloads": ["https://cdn.modrinth.com/data/gvQqBUqZ/versions/1/lithium.jar"]
+            }
+        ],
+        "dependencies": { "minecraft": "1.20.1", "fabric-loader": "0.15.11" }
+    }"#;
+
+    fn build_pack(index: &str, extra: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+        zip.start_file("modrinth.index.json", options).unwrap();
+        zip.write_all(index.as_bytes()).unwrap();
+        for (name, contents) in extra {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        let mut cursor = zip.finish().unwrap();
+        cursor.set_position(0);
+        cursor
+    }
+
+    fn expect_invalid(result: crate::Result<Mrpack<Cursor<Vec<u8>>>>, needle: &str) {
+        match result {
+            Err(ContentError::InvalidArchive(msg)) => {
+                assert!(msg.contains(needle), "unexpected message: {msg}")
+            }
+            Err(other) => panic!("expected InvalidArchive, got {other:?}"),
+            Ok(_) => panic!("expected InvalidArchive, got a parsed pack"),
+        }
+    }
+
+    #[test]
+    fn reads_index_files_and_overrides() {
+        let archive = build_pack(
+            INDEX,
+            &[
+                ("overrides/config/sodium.json", "{}"),
+                ("overrides/options.txt", "fov:1.0"),
+                ("client-overrides/options.txt", "fov:0.5"),
+                ("server-overrides/server.properties", "motd=hi"),
+                ("README.md", "not an override"),
+            ],
+        );
+        let mut pack = Mrpack::open(archive).unwrap();
+
+        assert_eq!(pack.name(), "Test Pack");
+        assert_eq!(pack.summary(), Some("A tiny pack"));
+        assert_eq!(pack.minecraft_version(), "1.20.1");
+        assert_eq!(pack.manifest().loader, "fabric");
+        assert_eq!(pack.manifest().loader_version, "0.15.11");
+        assert_eq!(pack.dependencies().get("fabric-loader").map(String::as_str), Some("0.15.11"));
+
+        let files = pack.files();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "mods/sodium.jar");
+        assert_eq!(files[0].env, Some(ModpackFileEnv::Client));
+        assert_eq!(files[0].hashes.get("sha512").map(String::as_str), Some("bb"));
+        assert_eq!(files[0].size, Some(1024));
+        assert_eq!(files[1].env, None);
+
+        let overrides: Vec<(&str, ModpackFileEnv)> = pack
+            .overrides()
+            .iter()
+            .map(|o| (o.archive_path.as_str(), o.env))
+            .collect();
+        assert_eq!(
+            overrides,
+            vec![
+                ("client-overrides/options.txt", ModpackFileEnv::Client),
+                ("overrides/config/sodium.json", ModpackFileEnv::Both),
+                ("overrides/options.txt", ModpackFileEnv::Both),
+                ("server-overrides/server.properties", ModpackFileEnv::Server),
+            ]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("instance");
+        let written = pack.extract_overrides(&dest, true).unwrap();
+        assert_eq!(written, 3);
+        assert_eq!(std::fs::read_to_string(dest.join("options.txt")).unwrap(), "fov:0.5");
+        assert!(dest.join("config/sodium.json").is_file());
+        assert!(!dest.join("server.properties").exists());
+    }
+
+    #[test]
+    fn rejects_file_paths_that_escape_the_instance() {
+        for path in ["../../.bashrc", "/etc/passwd", "mods/../../evil.jar", "mods\\\\..\\\\evil.jar"] {
+            let index = INDEX.replace("mods/lithium.jar", path);
+            expect_invalid(Mrpack::open(build_pack(&index, &[])), "escapes");
+        }
+    }
+
+    #[test]
+    fn rejects_override_paths_that_escape_the_instance() {
+        let archive = build_pack(INDEX, &[("overrides/../../evil.sh", "rm -rf ~")]);
+        expect_invalid(Mrpack::open(archive), "escapes");
+    }
+
+    #[test]
+    fn validates_index_header() {
+        expect_invalid(
+            Mrpack::open(build_pack(&INDEX.replace(r#""formatVersion": 1"#, r#""formatVersion": 2"#), &[])),
+            "formatVersion",
+        );
+        expect_invalid(
+            Mrpack::open(build_pack(&INDEX.replace(r#""game": "minecraft""#, r#""game": "terraria""#), &[])),
+            "game",
+        );
+        expect_invalid(
+            Mrpack::open(build_pack(&INDEX.replace(r#""name": "Test Pack""#, r#""name": " ""#), &[])),
+            "name",
+        );
+        expect_invalid(
+            Mrpack::open(build_pack(&INDEX.replace(r#""minecraft": "1.20.1", "#, ""), &[])),
+            "minecraft",
+        );
+    }
+
+    #[test]
+    fn missing_index_is_invalid() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("manifest.json", SimpleFileOptions::default()).unwrap();
+        let mut cursor = zip.finish().unwrap();
+        cursor.set_position(0);
+        expect_invalid(Mrpack::open(cursor), "modrinth.index.json");
+    }
+
+    #[test]
+    fn safe_paths() {
+        assert_eq!(safe_relative_path("mods/a.jar"), Some(PathBuf::from("mods/a.jar")));
+        assert_eq!(safe_relative_path("./config/a.toml"), Some(PathBuf::from("config/a.toml")));
+        assert_eq!(safe_relative_path(""), None);
+        assert_eq!(safe_relative_path("."), None);
+        assert_eq!(safe_relative_path("../a"), None);
+        assert_eq!(safe_relative_path("/a"), None);
+    }
 }
@@ -198,7 +198,8 @@ pub(crate) async fn get_mod_files(
     Ok(body.data)
 }
 
-/// Fetch a single file by mod ID and file ID.
+/// Fetch a single file by mod ID and file ID. A 404 maps to
+/// [`ContentError::NotFound`].
 pub(crate) async fn get_file(
     client: &reqwest::Client,
     api_key: Option<&str>,
@@ -212,6 +213,11 @@ pub(crate) async fn get_file(
         .send()
         .await?;
 
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Err(ContentError::NotFound(format!(
+            "curseforge file {mod_id}/{file_id}"
+        )));
+    }
     handle_common_status(&resp)?;
     let resp = resp.error_for_status()?;
     let body: Envelope<CfFile> = resp.json().await?;
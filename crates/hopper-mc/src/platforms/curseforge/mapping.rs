@@ -261,6 +261,37 @@ pub(crate) fn world_from(m: CfMod) -> WorldItem {
 // Version / file / dependency mappings
 // ---------------------------------------------------------------------------
 
+/// CDN host that serves CurseForge files, addressed by file id and name.
+const CDN_BASE_URL: &str = "https://mediafilez.forgecdn.net/files";
+
+/// Rebuild the CDN URL for a file from its id and file name.
+///
+/// CurseForge stores files under `/files/<id / 1000>/<id % 1000>/<name>`,
+/// e.g. file `4567089` named `sodium.jar` lives at
+/// `/files/4567/89/sodium.jar`. Returns `None` when `file_name` is empty.
+pub(crate) fn cdn_download_url(file_id: u64, file_name: &str) -> Option<String> {
+    if file_name.is_empty() {
+        return None;
+    }
+    let mut url = url::Url::parse(CDN_BASE_URL).ok()?;
+    url.path_segments_mut()
+        .ok()?
+        .push(&(file_id / 1000).to_string())
+        .push(&(file_id % 1000).to_string())
+        .push(file_name);
+    Some(url.into())
+}
+
+/// The download URL for a file: the API's `downloadUrl` when present,
+/// otherwise one reconstructed from the file id — CurseForge nulls
+/// `downloadUrl` for projects that opt out of third-party distribution.
+pub(crate) fn file_download_url(f: &CfFile) -> Option<String> {
+    f.download_url
+        .clone()
+        .filter(|u| !u.is_empty())
+        .or_else(|| cdn_download_url(f.id, &f.file_name))
+}
+
 /// Map a CurseForge file to a [`ProjectVersion`].
 pub(crate) fn version_from_cf(f: CfFile, project_id: &str) -> ProjectVersion {
     // Filter game_versions to only include actual MC versions (not loader names).
@@ -294,7 +325,7 @@ pub(crate) fn version_from_cf(f: CfFile, project_id: &str) -> ProjectVersion {
         game_versions: mc_versions,
         loaders,
         files: vec![VersionFile {
-            url: f.download_url,
+            url: file_download_url(&f),
             filename: f.file_name,
             size: f.file_length.unwrap_or(0),
             primary: !f.is_server_pack.unwrap_or(false),
@@ -425,6 +456,65 @@ mod tests {
         assert_eq!(collect_game_versions(&idx), vec!["1.20.1", "1.19.2"]);
     }
 
+    const FILE_FIXTURE: &str = r#"{
+        "id": 4567089,
+        "modId": 394468,
+        "displayName": "Sodium 0.5.3",
+        "fileName": "sodium-fabric-mc1.20.1-0.5.3.jar",
+        "fileDate": "2023-09-20T12:00:00Z",
+        "fileLength": 905912,
+        "releaseType": 1,
+        "downloadUrl": "https://edge.forgecdn.net/files/4567/89/sodium-fabric-mc1.20.1-0.5.3.jar",
+        "gameVersions": ["1.20.1", "Fabric"],
+        "dependencies": [{ "modId": 306612, "relationType": 3 }]
+    }"#;
+
+    const RESTRICTED_FILE_FIXTURE: &str = r#"{
+        "id": 4801002,
+        "modId": 238222,
+        "displayName": "JEI 15.2.0.27",
+        "fileName": "jei-1.20.1-forge 15.2.0.27.jar",
+        "fileLength": 1201524,
+        "releaseType": 2,
+        "downloadUrl": null,
+        "gameVersions": ["1.20.1", "Forge"]
+    }"#;
+
+    #[test]
+    fn file_with_download_url_keeps_it() {
+        let file: CfFile = serde_json::from_str(FILE_FIXTURE).unwrap();
+        let version = version_from_cf(file, "394468");
+        assert_eq!(version.id, "4567089");
+        assert_eq!(version.game_versions, vec!["1.20.1"]);
+        assert_eq!(version.loaders, vec!["fabric"]);
+        assert_eq!(
+            version.files[0].url.as_deref(),
+            Some("https://edge.forgecdn.net/files/4567/89/sodium-fabric-mc1.20.1-0.5.3.jar")
+        );
+        assert_eq!(version.required_dependencies().count(), 1);
+    }
+
+    #[test]
+    fn file_without_download_url_is_reconstructed() {
+        let file: CfFile = serde_json::from_str(RESTRICTED_FILE_FIXTURE).unwrap();
+        assert!(file.download_url.is_none());
+        let version = version_from_cf(file, "238222");
+        assert_eq!(version.version_type, VersionType::Beta);
+        assert_eq!(
+            version.files[0].url.as_deref(),
+            Some("https://mediafilez.forgecdn.net/files/4801/2/jei-1.20.1-forge%2015.2.0.27.jar")
+        );
+    }
+
+    #[test]
+    fn cdn_url_needs_a_file_name() {
+        assert_eq!(
+            cdn_download_url(1000, "a.jar").as_deref(),
+            Some("https://mediafilez.forgecdn.net/files/1/0/a.jar")
+        );
+        assert_eq!(cdn_download_url(1000, ""), None);
+    }
+
     fn dummy_index(game_version: &str) -> FileIndex {
         FileIndex {
             game_version: game_version.to_string(),
@@ -51,7 +51,7 @@ use std::sync::OnceLock;
 
 use secrecy::{ExposeSecret, SecretString};
 
-use crate::error::Result;
+use crate::error::{ContentError, Result};
 use crate::model::{
     DatapackItem, ModItem, PackItem, ProjectVersion, ResourcePackItem, ShaderPackItem, WorldItem,
 };
@@ -165,14 +165,49 @@ impl CurseForgeProvider {
         self.api_key.as_ref().map(|s| s.expose_secret())
     }
 
+    /// Fetch a mod by its numeric CurseForge id.
+    ///
+    /// Unlike [`ModProvider::get_mod`], a missing project is an error
+    /// ([`ContentError::NotFound`]) rather than `Ok(None)`.
+    pub async fn fetch_mod(&self, mod_id: u64) -> Result<ModItem> {
+        api::get_mod_by_id(&self.client, self.exposed_key(), mod_id)
+            .await?
+            .map(mapping::mod_from)
+            .ok_or_else(|| ContentError::NotFound(format!("curseforge mod {mod_id}")))
+    }
+
+    /// Fetch a single file of a mod.
+    ///
+    /// The returned version's file always carries a download URL when the
+    /// file has a name: files whose project disallows third-party
+    /// distribution come back from the API with `downloadUrl: null`, and
+    /// the URL is rebuilt from the file id instead.
+    ///
+    /// ```no_run
+    /// use hopper_mc::CurseForgeProvider;
+    /// use secrecy::SecretString;
+    ///
+    /// # async fn example(key: SecretString) -> Result<(), hopper_mc::ContentError> {
+    /// let cf = CurseForgeProvider::new_with_secret_key(Some(key));
+    /// let jei = cf.fetch_mod(238222).await?;
+    /// let file = cf.fetch_file(238222, 4801002).await?;
+    /// println!("{} -> {:?}", jei.base.name, file.files[0].url);
+    /// # Ok(()) }
+    /// ```
+    pub async fn fetch_file(&self, mod_id: u64, file_id: u64) -> Result<ProjectVersion> {
+        let file = api::get_file(&self.client, self.exposed_key(), mod_id, file_id).await?;
+        Ok(mapping::version_from_cf(file, &mod_id.to_string()))
+    }
+
     /// Resolve download URLs for CurseForge modpack files.
     ///
     /// Iterates `files` that have `project_id` and `file_id` set but empty
     /// `download_urls`, calls the CurseForge API to fetch each file's
     /// metadata, and populates `download_urls`, `size`, and `path`.
     ///
-    /// Files with restricted distribution (`download_url: null`) are left
-    /// with empty `download_urls`; the caller should handle this gracefully.
+    /// Files with restricted distribution (`download_url: null`) get a URL
+    /// reconstructed from their file id; only files that could not be
+    /// looked up at all are left with empty `download_urls`.
     ///
     /// Uses up to 8 concurrent API requests via a semaphore.
     pub async fn resolve_pack_files(
@@ -220,7 +255,7 @@ impl CurseForgeProvider {
             match result {
                 Ok(cf_file) => {
                     let entry = &mut files[idx];
-                    if let Some(url) = cf_file.download_url {
+                    if let Some(url) = mapping::file_download_url(&cf_file) {
                         entry.download_urls.push(url);
                     }
                     entry.size = cf_file.file_length;
@@ -4,6 +4,7 @@ pub mod atlauncher;
 pub mod curseforge;
 pub mod ftb;
 pub mod modrinth;
+mod retry;
 pub mod technic;
 mod user_agent;
 
@@ -10,6 +10,7 @@ use crate::platform::{ContentType, SearchFilters, Sort};
 
 use super::dto::{MrVersion, Project, SearchResponse};
 use super::mapping;
+use crate::platforms::retry;
 
 pub(crate) const BASE_URL: &str = "https://api.modrinth.com/v2";
 
@@ -54,6 +55,43 @@ pub(crate) async fn search(
     Ok(body)
 }
 
+/// Build a free-form `/search` request. `facets` is an already encoded
+/// facet array, or `None` to search every project type.
+pub(crate) fn faceted_search_request(
+    client: &reqwest::Client,
+    base_url: &str,
+    query: &str,
+    facets: Option<&str>,
+    limit: u32,
+    offset: u32,
+) -> reqwest::RequestBuilder {
+    let mut req = client.get(format!("{base_url}/search")).query(&[
+        ("query", query),
+        ("limit", &limit.clamp(1, 100).to_string()),
+        ("offset", &offset.to_string()),
+    ]);
+    if let Some(facets) = facets {
+        req = req.query(&[("facets", facets)]);
+    }
+    req
+}
+
+/// Run a free-form `/search` query, backing off when rate limited.
+pub(crate) async fn faceted_search(
+    client: &reqwest::Client,
+    query: &str,
+    facets: Option<&str>,
+    limit: u32,
+    offset: u32,
+) -> Result<SearchResponse> {
+    let req = faceted_search_request(client, BASE_URL, query, facets, limit, offset);
+    let resp = retry::send_with_backoff(req).await?;
+    handle_common_status(&resp)?;
+    let resp = resp.error_for_status()?;
+    let body: SearchResponse = resp.json().await?;
+    Ok(body)
+}
+
 /// Fetch a single project by id or slug. Returns `Ok(None)` on 404.
 pub(crate) async fn get_project(
     client: &reqwest::Client,
@@ -88,6 +126,29 @@ pub(crate) async fn get_project_versions(
     Ok(body)
 }
 
+/// List the versions of a project compatible with any of `game_versions` and
+/// any of `loaders`. Empty slices leave that dimension unfiltered. Returns
+/// `Ok(None)` when the project does not exist.
+pub(crate) async fn get_project_versions_filtered(
+    client: &reqwest::Client,
+    id_or_slug: &str,
+    game_versions: &[&str],
+    loaders: &[&str],
+) -> Result<Option<Vec<MrVersion>>> {
+    let resp = client
+        .get(format!("{BASE_URL}/project/{id_or_slug}/version"))
+        .query(&mapping::version_filter_query(game_versions, loaders))
+        .send()
+        .await?;
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    handle_common_status(&resp)?;
+    let resp = resp.error_for_status()?;
+    let body: Vec<MrVersion> = resp.json().await?;
+    Ok(Some(body))
+}
+
 /// Fetch a single version by id. Returns `Ok(None)` on 404.
 pub(crate) async fn get_version_by_id(
     client: &reqwest::Client,
@@ -106,17 +167,31 @@ pub(crate) async fn get_version_by_id(
     Ok(Some(body))
 }
 
+/// Look up the version that published the file with the given hex digest.
+/// `algorithm` is `"sha1"` or `"sha512"`. Returns `Ok(None)` when Modrinth
+/// does not know the file.
+pub(crate) async fn get_version_from_hash(
+    client: &reqwest::Client,
+    hash: &str,
+    algorithm: &str,
+) -> Result<Option<MrVersion>> {
+    let resp = client
+        .get(format!("{BASE_URL}/version_file/{hash}"))
+        .query(&[("algorithm", algorithm)])
+        .send()
+        .await?;
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    handle_common_status(&resp)?;
+    let resp = resp.error_for_status()?;
+    let body: MrVersion = resp.json().await?;
+    Ok(Some(body))
+}
+
 fn handle_common_status(resp: &reqwest::Response) -> Result<()> {
     match resp.status() {
-        StatusCode::TOO_MANY_REQUESTS => {
-            let retry_after = resp
-                .headers()
-                .get(reqwest::header::RETRY_AFTER)
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| s.parse::<u64>().ok())
-                .map(std::time::Duration::from_secs);
-            Err(ContentError::RateLimited(retry_after))
-        }
+        StatusCode::TOO_MANY_REQUESTS => Err(ContentError::RateLimited(retry::retry_after(resp))),
         StatusCode::BAD_REQUEST => Err(ContentError::BadRequest(format!(
             "modrinth rejected request: {}",
             resp.status()
@@ -9,7 +9,10 @@ use crate::model::{
 };
 use crate::platform::{ContentType, Platform, SearchFilters, Sort};
 
-use super::dto::{GalleryItem, MrDependency, MrVersion, MrVersionFile, Project, SearchHit};
+use super::dto::{
+    GalleryItem, MrDependency, MrVersion, MrVersionFile, Project, SearchHit, SearchResponse,
+};
+use super::search::{SearchResult, SearchResults};
 
 /// Modrinth `project_type` values. We primarily reach these via facet filters
 /// but they also appear on responses.
@@ -218,6 +221,27 @@ fn base_from_project(p: &Project) -> ContentBase {
 // Per-kind conversions
 // ---------------------------------------------------------------------------
 
+pub(crate) fn search_results_from(r: SearchResponse) -> SearchResults {
+    SearchResults {
+        hits: r
+            .hits
+            .into_iter()
+            .map(|hit| SearchResult {
+                project_id: hit.project_id,
+                slug: hit.slug,
+                title: hit.title,
+                description: hit.description,
+                project_type: hit.project_type,
+                downloads: hit.downloads,
+                icon_url: hit.icon_url,
+            })
+            .collect(),
+        offset: r.offset,
+        limit: r.limit,
+        total_hits: r.total_hits,
+    }
+}
+
 pub(crate) fn mod_from_hit(hit: SearchHit) -> ModItem {
     let client = side(hit.client_side.as_deref());
     let server = side(hit.server_side.as_deref());
@@ -395,6 +419,27 @@ fn dependency_from_mr(d: &MrDependency) -> Dependency {
     }
 }
 
+/// Build the `game_versions` / `loaders` query parameters for
+/// `GET /v2/project/{id}/version`. Modrinth expects each as a JSON array of
+/// strings; a dimension with no values is omitted entirely.
+pub(crate) fn version_filter_query(
+    game_versions: &[&str],
+    loaders: &[&str],
+) -> Vec<(&'static str, String)> {
+    let mut query = Vec::new();
+    if !loaders.is_empty() {
+        query.push(("loaders", json_string_array(loaders)));
+    }
+    if !game_versions.is_empty() {
+        query.push(("game_versions", json_string_array(game_versions)));
+    }
+    query
+}
+
+fn json_string_array(values: &[&str]) -> String {
+    serde_json::to_string(values).expect("string slices always serialize")
+}
+
 /// Loader names known to appear in Modrinth's `categories` array.
 ///
 /// Keeping a small allowlist rather than a full taxonomy — new entries are a
@@ -486,6 +531,94 @@ mod tests {
         assert!(facets.contains("\"categories:fabric\""));
     }
 
+    #[test]
+    fn version_filter_query_encodes_json_arrays() {
+        let query = version_filter_query(&["1.20.1", "1.20.2"], &["fabric"]);
+        assert_eq!(
+            query,
+            vec![
+                ("loaders", "[\"fabric\"]".to_string()),
+                ("game_versions", "[\"1.20.1\",\"1.20.2\"]".to_string()),
+            ]
+        );
+        assert!(version_filter_query(&[], &[]).is_empty());
+        assert_eq!(version_filter_query(&["1.21.4"], &[]).len(), 1);
+    }
+
+    const VERSIONS_FIXTURE: &str = r#"[
+        {
+            "id": "tFw0iWAk",
+            "project_id": "AANobbMI",
+            "author_id": "TEZXhE2U",
+            "name": "Sodium 0.5.3 for Fabric 1.20.1",
+            "version_number": "mc1.20.1-0.5.3",
+            "changelog": "Fixes a crash on startup.",
+            "date_published": "2023-09-20T22:34:40.185754Z",
+            "downloads": 123456,
+            "version_type": "release",
+            "game_versions": ["1.20.1"],
+            "loaders": ["fabric", "quilt"],
+            "featured": true,
+            "files": [
+                {
+                    "url": "https://cdn.modrinth.com/data/AANobbMI/versions/tFw0iWAk/sodium-fabric-mc1.20.1-0.5.3.jar",
+                    "filename": "sodium-fabric-mc1.20.1-0.5.3.jar",
+                    "primary": true,
+                    "size": 950236,
+                    "hashes": {
+                        "sha1": "0a4a0d2ad1b2d0f7a6c0b6b7e2f2b9cbe4d2e1f0",
+                        "sha512": "f3c5a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0"
+                    }
+                }
+            ],
+            "dependencies": [
+                {"version_id": null, "project_id": "P7dR8mSH", "file_name": null, "dependency_type": "required"},
+                {"version_id": "abc123", "project_id": "gvQqBUqZ", "file_name": null, "dependency_type": "optional"},
+                {"version_id": null, "project_id": "YL57xq9U", "file_name": null, "dependency_type": "incompatible"}
+            ]
+        },
+        {
+            "id": "b4hTi3mo",
+            "project_id": "AANobbMI",
+            "name": "Sodium 0.5.4-beta.1",
+            "version_number": "mc1.20.1-0.5.4-beta.1",
+            "date_published": "2023-10-01T12:00:00Z",
+            "version_type": "beta",
+            "game_versions": ["1.20.1"],
+            "loaders": ["fabric"],
+            "files": []
+        }
+    ]"#;
+
+    #[test]
+    fn versions_fixture_maps_files_and_dependencies() {
+        let versions: Vec<MrVersion> = serde_json::from_str(VERSIONS_FIXTURE).unwrap();
+        let versions: Vec<ProjectVersion> = versions.into_iter().map(version_from_mr).collect();
+        assert_eq!(versions.len(), 2);
+
+        let release = &versions[0];
+        assert_eq!(release.version_type, VersionType::Release);
+        assert_eq!(release.loaders, vec!["fabric", "quilt"]);
+        let file = release.primary_file().unwrap();
+        assert_eq!(file.filename, "sodium-fabric-mc1.20.1-0.5.3.jar");
+        assert!(file.url.as_deref().unwrap().starts_with("https://cdn.modrinth.com/"));
+        assert_eq!(file.sha512().map(str::len), Some(128));
+        assert_eq!(file.sha1(), Some("0a4a0d2ad1b2d0f7a6c0b6b7e2f2b9cbe4d2e1f0"));
+
+        assert_eq!(release.dependencies.len(), 3);
+        let required: Vec<_> = release.required_dependencies().collect();
+        assert_eq!(required.len(), 1);
+        assert_eq!(required[0].project_id.as_deref(), Some("P7dR8mSH"));
+        assert_eq!(release.dependencies[1].kind, DependencyKind::Optional);
+        assert_eq!(release.dependencies[1].version_id.as_deref(), Some("abc123"));
+        assert_eq!(release.dependencies[2].kind, DependencyKind::Incompatible);
+
+        let beta = &versions[1];
+        assert_eq!(beta.version_type, VersionType::Beta);
+        assert!(beta.primary_file().is_none());
+        assert!(beta.dependencies.is_empty());
+    }
+
     #[test]
     fn loader_filter_keeps_only_known_loaders() {
         let cats = vec![
@@ -3,10 +3,32 @@
 //! Uses the Labrinth v2 HTTP API at `https://api.modrinth.com/v2`. Modrinth
 //! asks that clients identify themselves with a descriptive `User-Agent`
 //! (`<name>/<version>`) — we set one at construction time.
+//!
+//! ## User-Agent policy
+//!
+//! Modrinth's API terms require a uniquely identifying User-Agent, ideally
+//! with contact information, e.g. `my-launcher/1.2.3 (contact@example.com)`.
+//! Requests with generic agents (such as a bare `reqwest` default) may be
+//! rate limited or blocked outright. The crate default (`hopper-mc/<version>`)
+//! is only a fallback — launchers should call
+//! [`ModrinthProvider::with_user_agent`] with their own identity.
 
 mod api;
 mod dto;
 mod mapping;
+mod resolve;
+mod search;
+mod updates;
+
+pub use resolve::{resolve_dependencies, resolve_with, Resolution, VersionSource};
+pub use search::{search, Facet, SearchResult, SearchResults};
+pub use updates::{
+    check_updates, check_updates_with, InstalledMod, ModUpdate, UpdateSource, UpdateStatus,
+};
+
+/// `.mrpack` archives are Modrinth's modpack format; the parser lives in
+/// [`crate::modpack`] alongside the CurseForge one.
+pub use crate::modpack::{Mrpack, MrpackOverride};
 
 use std::sync::OnceLock;
 
@@ -60,6 +82,58 @@ impl ModrinthProvider {
     }
 }
 
+impl ModrinthProvider {
+    /// List the versions of a project (by id or slug) that support any of
+    /// `game_versions` and any of `loaders`, most recent first.
+    ///
+    /// Filtering happens server-side through the `game_versions` and
+    /// `loaders` query parameters; pass an empty slice to skip a filter.
+    /// Returns [`ContentError::NotFound`] when the project does not exist.
+    ///
+    /// ```no_run
+    /// use hopper_mc::ModrinthProvider;
+    ///
+    /// # async fn example() -> hopper_mc::Result<()> {
+    /// let modrinth = ModrinthProvider::with_user_agent("my-launcher/1.0.0");
+    /// let versions = modrinth.fetch_versions("sodium", &["1.20.1"], &["fabric"]).await?;
+    /// if let Some(file) = versions.first().and_then(|v| v.primary_file()) {
+    ///     println!("{} sha512={:?}", file.filename, file.sha512());
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn fetch_versions(
+        &self,
+        id_or_slug: &str,
+        game_versions: &[&str],
+        loaders: &[&str],
+    ) -> Result<Vec<ProjectVersion>> {
+        let versions =
+            api::get_project_versions_filtered(&self.client, id_or_slug, game_versions, loaders)
+                .await?
+                .ok_or_else(|| ContentError::NotFound(format!("modrinth project {id_or_slug}")))?;
+        Ok(versions.into_iter().map(mapping::version_from_mr).collect())
+    }
+
+    /// Find the version that published a file, given the file's hex SHA-1
+    /// (40 characters) or SHA-512 (128 characters) digest.
+    ///
+    /// Returns `Ok(None)` when the file is not hosted on Modrinth, and
+    /// [`ContentError::BadRequest`] when `hash` is neither digest length.
+    pub async fn version_from_hash(&self, hash: &str) -> Result<Option<ProjectVersion>> {
+        let algorithm = match hash.len() {
+            40 => "sha1",
+            128 => "sha512",
+            len => {
+                return Err(ContentError::BadRequest(format!(
+                    "expected a sha1 or sha512 hex digest, got {len} characters"
+                )))
+            }
+        };
+        let v = api::get_version_from_hash(&self.client, hash, algorithm).await?;
+        Ok(v.map(mapping::version_from_mr))
+    }
+}
+
 impl Default for ModrinthProvider {
     fn default() -> Self {
         Self::new()
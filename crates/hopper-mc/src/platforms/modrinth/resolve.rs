@@ -0,0 +1,375 @@
+//! Transitive resolution of required Modrinth dependencies.
+//!
+//! The walk is written against the small [`VersionSource`] trait rather than
+//! the HTTP client directly, so the graph logic can be exercised with an
+//! in-memory source.
+
+use std::collections::HashMap;
+
+use crate::error::{ContentError, DependencyConflict, Result};
+use crate::model::{Dependency, ProjectVersion, VersionType};
+use crate::provider::VersionProvider;
+
+use super::ModrinthProvider;
+
+/// Where [`resolve_with`] looks up versions.
+pub trait VersionSource: Send + Sync {
+    /// Fetch a single version by id.
+    fn version(&self, version_id: &str) -> impl std::future::Future<Output = Result<Option<ProjectVersion>>> + Send;
+
+    /// Versions of `project_id` that support `game_version` and `loader`.
+    fn compatible_versions(
+        &self,
+        project_id: &str,
+        game_version: &str,
+        loader: &str,
+    ) -> impl std::future::Future<Output = Result<Vec<ProjectVersion>>> + Send;
+}
+
+impl VersionSource for ModrinthProvider {
+    async fn version(&self, version_id: &str) -> Result<Option<ProjectVersion>> {
+        self.get_version(version_id).await
+    }
+
+    async fn compatible_versions(&self, project_id: &str, game_version: &str, loader: &str) -> Result<Vec<ProjectVersion>> {
+        self.fetch_versions(project_id, &[game_version], &[loader]).await
+    }
+}
+
+/// The outcome of [`resolve_dependencies`].
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    /// Every version to install, one per project, starting with the root and
+    /// otherwise in the order they were discovered.
+    pub versions: Vec<ProjectVersion>,
+    /// Dependency cycles that were found and broken, each as the project ids
+    /// along the cycle with the first id repeated at the end.
+    pub cycles: Vec<Vec<String>>,
+}
+
+/// Resolve the required dependencies of `root` against Modrinth, using the
+/// shared provider.
+///
+/// See [`resolve_with`] for the resolution rules.
+pub async fn resolve_dependencies(root: &ProjectVersion, game_version: &str, loader: &str) -> Result<Resolution> {
+    resolve_with(ModrinthProvider::shared(), root, game_version, loader).await
+}
+
+/// Walk the `required` dependencies of `root` transitively and return a
+/// deduplicated install set.
+///
+/// - A dependency pinned to a `version_id` uses exactly that version.
+/// - An unpinned dependency uses the newest release compatible with
+///   `game_version` and `loader` (falling back to the newest beta/alpha).
+/// - Each project is installed once. When two dependents pin different
+///   versions of the same project — or one pins a version other than the one
+///   already chosen — the walk finishes and then fails with
+///   [`ContentError::DependencyConflict`] listing every conflict.
+/// - Cycles (`A → B → A`) are broken and reported in [`Resolution::cycles`].
+pub async fn resolve_with<S: VersionSource>(source: &S, root: &ProjectVersion, game_version: &str, loader: &str) -> Result<Resolution> {
+    struct Frame {
+        project_id: String,
+        deps: Vec<Dependency>,
+        next: usize,
+    }
+
+    let mut chosen: HashMap<String, usize> = HashMap::new();
+    let mut versions: Vec<ProjectVersion> = vec![root.clone()];
+    chosen.insert(root.project_id.clone(), 0);
+
+    let mut conflicts: Vec<DependencyConflict> = Vec::new();
+    let mut cycles: Vec<Vec<String>> = Vec::new();
+    let mut stack = vec![Frame {
+        project_id: root.project_id.clone(),
+        deps: root.required_dependencies().cloned().collect(),
+        next: 0,
+    }];
+
+    while let Some(frame) = stack.last_mut() {
+        let Some(dep) = frame.deps.get(frame.next).cloned() else {
+            stack.pop();
+            continue;
+        };
+        frame.next += 1;
+        let required_by = versions[chosen[&frame.project_id]].id.clone();
+
+        // Work out which project this dependency points at, fetching the
+        // pinned version up front when only its id is known.
+        let (project_id, mut pinned) = match (dep.project_id, dep.version_id.as_deref()) {
+            (Some(project_id), _) => (project_id, None),
+            (None, Some(version_id)) => {
+                let version = fetch_pinned(source, version_id).await?;
+                (version.project_id.clone(), Some(version))
+            }
+            (None, None) => continue,
+        };
+
+        if let Some(&index) = chosen.get(&project_id) {
+            let existing = &versions[index];
+            if let Some(wanted) = dep.version_id.as_deref().filter(|v| *v != existing.id) {
+                conflicts.push(DependencyConflict {
+                    project_id: project_id.clone(),
+                    chosen_version: existing.id.clone(),
+                    requested_version: wanted.to_owned(),
+                    required_by,
+                });
+            }
+            if let Some(start) = stack.iter().position(|f| f.project_id == project_id) {
+                let mut cycle: Vec<String> = stack[start..].iter().map(|f| f.project_id.clone()).collect();
+                cycle.push(project_id);
+                cycles.push(cycle);
+            }
+            continue;
+        }
+
+        let version = match (pinned.take(), dep.version_id.as_deref()) {
+            (Some(version), _) => version,
+            (None, Some(version_id)) => fetch_pinned(source, version_id).await?,
+            (None, None) => pick_compatible(source, &project_id, game_version, loader).await?,
+        };
+
+        chosen.insert(project_id.clone(), versions.len());
+        stack.push(Frame {
+            project_id,
+            deps: version.required_dependencies().cloned().collect(),
+            next: 0,
+        });
+        versions.push(version);
+    }
+
+    if !conflicts.is_empty() {
+        return Err(ContentError::DependencyConflict(conflicts));
+    }
+    Ok(Resolution { versions, cycles })
+}
+
+async fn fetch_pinned<S: VersionSource>(source: &S, version_id: &str) -> Result<ProjectVersion> {
+    source
+        .version(version_id)
+        .await?
+        .ok_or_else(|| ContentError::NotFound(format!("modrinth version {version_id}")))
+}
+
+async fn pick_compatible<S: VersionSource>(source: &S, project_id: &str, game_version: &str, loader: &str) -> Result<ProjectVersion> {
+    let mut candidates: Vec<ProjectVersion> = source
+        .compatible_versions(project_id, game_version, loader)
+        .await?
+        .into_iter()
+        .filter(|v| v.game_versions.iter().any(|g| g == game_version) && v.loaders.iter().any(|l| l == loader))
+        .collect();
+    if candidates.is_empty() {
+        return Err(ContentError::NotFound(format!(
+            "no version of {project_id} for Minecraft {game_version} with {loader}"
+        )));
+    }
+
+    candidates.sort_by(|a, b| b.date_published.cmp(&a.date_published));
+    let index = candidates.iter().position(|v| v.version_type == VersionType::Release).unwrap_or(0);
+    Ok(candidates.swap_remove(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::model::DependencyKind;
+    use crate::platform::Platform;
+
+    /// In-memory dependency graph keyed by version id.
+    #[derive(Default)]
+    struct Graph {
+        versions: HashMap<String, ProjectVersion>,
+    }
+
+    impl Graph {
+        fn add(mut self, version: ProjectVersion) -> Self {
+            self.versions.insert(version.id.clone(), version);
+            self
+        }
+    }
+
+    impl VersionSource for Graph {
+        async fn version(&self, version_id: &str) -> Result<Option<ProjectVersion>> {
+            Ok(self.versions.get(version_id).cloned())
+        }
+
+        async fn compatible_versions(&self, project_id: &str, _game_version: &str, _loader: &str) -> Result<Vec<ProjectVersion>> {
+            Ok(self.versions.values().filter(|v| v.project_id == project_id).cloned().collect())
+        }
+    }
+
+    fn version(id: &str, project: &str, day: u32, deps: Vec<Dependency>) -> ProjectVersion {
+        ProjectVersion {
+            id: id.into(),
+            project_id: project.into(),
+            name: id.into(),
+            version_number: id.into(),
+            changelog: None,
+            date_published: Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap(),
+            downloads: 0,
+            version_type: VersionType::Release,
+            game_versions: vec!["1.20.1".into()],
+            loaders: vec!["fabric".into()],
+            files: Vec::new(),
+            dependencies: deps,
+            featured: false,
+            platform: Platform::Modrinth,
+        }
+    }
+
+    fn requires(project: &str) -> Dependency {
+        Dependency {
+            project_id: Some(project.into()),
+            version_id: None,
+            kind: DependencyKind::Required,
+        }
+    }
+
+    fn pinned(project: &str, version: &str) -> Dependency {
+        Dependency {
+            project_id: Some(project.into()),
+            version_id: Some(version.into()),
+            kind: DependencyKind::Required,
+        }
+    }
+
+    fn ids(resolution: &Resolution) -> Vec<&str> {
+        resolution.versions.iter().map(|v| v.id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn diamond_dependency_is_installed_once() {
+        // root -> a -> c, root -> b -> c
+        let root = version("root-1", "root", 1, vec![requires("a"), requires("b")]);
+        let graph = Graph::default()
+            .add(version("a-1", "a", 1, vec![requires("c")]))
+            .add(version("b-1", "b", 1, vec![requires("c")]))
+            .add(version("c-1", "c", 1, vec![]))
+            .add(version("c-2", "c", 2, vec![]));
+
+        let resolution = resolve_with(&graph, &root, "1.20.1", "fabric").await.unwrap();
+        assert_eq!(ids(&resolution), vec!["root-1", "a-1", "c-2", "b-1"]);
+        assert!(resolution.cycles.is_empty());
+    }
+
+    #[tokio::test]
+    async fn optional_and_incompatible_dependencies_are_skipped() {
+        let root = version(
+            "root-1",
+            "root",
+            1,
+            vec![
+                Dependency {
+                    project_id: Some("opt".into()),
+                    version_id: None,
+                    kind: DependencyKind::Optional,
+                },
+                Dependency {
+                    project_id: Some("bad".into()),
+                    version_id: None,
+                    kind: DependencyKind::Incompatible,
+                },
+            ],
+        );
+        let graph = Graph::default().add(version("opt-1", "opt", 1, vec![]));
+
+        let resolution = resolve_with(&graph, &root, "1.20.1", "fabric").await.unwrap();
+        assert_eq!(ids(&resolution), vec!["root-1"]);
+    }
+
+    #[tokio::test]
+    async fn cycles_are_detected_and_broken() {
+        // root -> x -> y -> x
+        let root = version("root-1", "root", 1, vec![requires("x")]);
+        let graph = Graph::default()
+            .add(version("x-1", "x", 1, vec![requires("y")]))
+            .add(version("y-1", "y", 1, vec![requires("x")]));
+
+        let resolution = resolve_with(&graph, &root, "1.20.1", "fabric").await.unwrap();
+        assert_eq!(ids(&resolution), vec!["root-1", "x-1", "y-1"]);
+        assert_eq!(resolution.cycles, vec![vec!["x", "y", "x"]]);
+    }
+
+    #[tokio::test]
+    async fn pinned_versions_are_honoured() {
+        let root = version("root-1", "root", 1, vec![pinned("lib", "lib-1")]);
+        let graph = Graph::default()
+            .add(version("lib-1", "lib", 1, vec![]))
+            .add(version("lib-2", "lib", 2, vec![]));
+
+        let resolution = resolve_with(&graph, &root, "1.20.1", "fabric").await.unwrap();
+        assert_eq!(ids(&resolution), vec!["root-1", "lib-1"]);
+    }
+
+    #[tokio::test]
+    async fn version_only_dependencies_resolve_their_project() {
+        let root = version(
+            "root-1",
+            "root",
+            1,
+            vec![Dependency {
+                project_id: None,
+                version_id: Some("lib-1".into()),
+                kind: DependencyKind::Required,
+            }],
+        );
+        let graph = Graph::default().add(version("lib-1", "lib", 1, vec![requires("root")]));
+
+        let resolution = resolve_with(&graph, &root, "1.20.1", "fabric").await.unwrap();
+        assert_eq!(ids(&resolution), vec!["root-1", "lib-1"]);
+        assert_eq!(resolution.cycles, vec![vec!["root", "lib", "root"]]);
+    }
+
+    #[tokio::test]
+    async fn conflicting_pins_are_reported() {
+        let root = version("root-1", "root", 1, vec![requires("a"), requires("b")]);
+        let graph = Graph::default()
+            .add(version("a-1", "a", 1, vec![pinned("lib", "lib-1")]))
+            .add(version("b-1", "b", 1, vec![pinned("lib", "lib-2")]))
+            .add(version("lib-1", "lib", 1, vec![]))
+            .add(version("lib-2", "lib", 2, vec![]));
+
+        let err = resolve_with(&graph, &root, "1.20.1", "fabric").await.unwrap_err();
+        match err {
+            ContentError::DependencyConflict(conflicts) => {
+                assert_eq!(
+                    conflicts,
+                    vec![DependencyConflict {
+                        project_id: "lib".into(),
+                        chosen_version: "lib-1".into(),
+                        requested_version: "lib-2".into(),
+                        required_by: "b-1".into(),
+                    }]
+                );
+            }
+            other => panic!("expected a dependency conflict, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn picks_newest_compatible_release() {
+        let root = version("root-1", "root", 1, vec![requires("lib")]);
+        let mut forge_only = version("lib-forge", "lib", 9, vec![]);
+        forge_only.loaders = vec!["forge".into()];
+        let mut beta = version("lib-beta", "lib", 8, vec![]);
+        beta.version_type = VersionType::Beta;
+        let mut old_game = version("lib-old", "lib", 7, vec![]);
+        old_game.game_versions = vec!["1.19.2".into()];
+        let graph = Graph::default()
+            .add(forge_only)
+            .add(beta)
+            .add(old_game)
+            .add(version("lib-release", "lib", 3, vec![]));
+
+        let resolution = resolve_with(&graph, &root, "1.20.1", "fabric").await.unwrap();
+        assert_eq!(ids(&resolution), vec!["root-1", "lib-release"]);
+    }
+
+    #[tokio::test]
+    async fn missing_compatible_version_is_an_error() {
+        let root = version("root-1", "root", 1, vec![requires("ghost")]);
+        let err = resolve_with(&Graph::default(), &root, "1.20.1", "fabric").await.unwrap_err();
+        assert!(matches!(err, ContentError::NotFound(msg) if msg.contains("ghost")));
+    }
+}
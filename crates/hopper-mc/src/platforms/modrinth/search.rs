@@ -0,0 +1,224 @@
+//! Free-form Modrinth search with explicit facets.
+//!
+//! The `find_*` trait methods always pin one content type and speak the
+//! crate-wide [`SearchFilters`](crate::SearchFilters). This is the thinner
+//! entry point for a launcher's own search box: any mix of project types,
+//! loaders and game versions, paged by raw `limit`/`offset`.
+
+use crate::error::Result;
+
+use super::{ModrinthProvider, api, mapping};
+
+/// One facet constraint on a [`search`].
+///
+/// Facets of the same kind are OR-ed and different kinds AND-ed, so
+/// `[Loader("fabric"), Loader("quilt"), GameVersion("1.21.4")]` matches
+/// projects for 1.21.4 that support Fabric or Quilt.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Facet {
+    /// A Modrinth project type: `mod`, `modpack`, `resourcepack`, `shader`
+    /// or `datapack`.
+    ProjectType(String),
+    /// A loader slug such as `fabric` or `neoforge`.
+    Loader(String),
+    /// A game version such as `1.20.1`.
+    GameVersion(String),
+}
+
+impl Facet {
+    /// The facet as Modrinth spells it, e.g. `categories:fabric`. Loaders are
+    /// categories as far as the search index is concerned.
+    pub fn as_facet(&self) -> String {
+        match self {
+            Self::ProjectType(t) => format!("project_type:{t}"),
+            Self::Loader(l) => format!("categories:{l}"),
+            Self::GameVersion(v) => format!("versions:{v}"),
+        }
+    }
+
+    fn group(&self) -> u8 {
+        match self {
+            Self::ProjectType(_) => 0,
+            Self::Loader(_) => 1,
+            Self::GameVersion(_) => 2,
+        }
+    }
+}
+
+/// One page of [`search`] results.
+#[derive(Debug, Clone)]
+pub struct SearchResults {
+    pub hits: Vec<SearchResult>,
+    pub offset: u32,
+    pub limit: u32,
+    /// Matches across all pages.
+    pub total_hits: u32,
+}
+
+/// A project matching a [`search`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    pub project_id: String,
+    pub slug: String,
+    pub title: String,
+    pub description: String,
+    pub project_type: Option<String>,
+    pub downloads: u64,
+    pub icon_url: Option<String>,
+}
+
+/// Search Modrinth using the shared provider.
+///
+/// See [`ModrinthProvider::search`].
+pub async fn search(query: &str, facets: &[Facet], limit: u32, offset: u32) -> Result<SearchResults> {
+    ModrinthProvider::shared().search(query, facets, limit, offset).await
+}
+
+impl ModrinthProvider {
+    /// Search Modrinth for `query`, narrowed by `facets`.
+    ///
+    /// `limit` is clamped to Modrinth's `1..=100`; `offset` counts results,
+    /// not pages. No facets means every project type. Requests that are
+    /// rate limited are retried with backoff before failing with
+    /// [`ContentError::RateLimited`](crate::ContentError::RateLimited).
+    ///
+    /// ```no_run
+    /// use hopper_mc::platforms::modrinth::{search, Facet};
+    ///
+    /// # async fn example() -> hopper_mc::Result<()> {
+    /// let facets = [Facet::Loader("fabric".into()), Facet::GameVersion("1.21.4".into())];
+    /// for hit in search("minimap", &facets, 20, 0).await?.hits {
+    ///     println!("{} ({} downloads)", hit.title, hit.downloads);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn search(&self, query: &str, facets: &[Facet], limit: u32, offset: u32) -> Result<SearchResults> {
+        let facets = encode_facets(facets);
+        let r = api::faceted_search(&self.client, query, facets.as_deref(), limit, offset).await?;
+        Ok(mapping::search_results_from(r))
+    }
+}
+
+/// The `facets` query parameter for `facets`, or `None` when there are none.
+/// Groups keep the order their kind first appears in.
+pub(crate) fn encode_facets(facets: &[Facet]) -> Option<String> {
+    let mut groups: Vec<(u8, Vec<String>)> = Vec::new();
+    for facet in facets {
+        match groups.iter_mut().find(|(group, _)| *group == facet.group()) {
+            Some((_, values)) => values.push(facet.as_facet()),
+            None => groups.push((facet.group(), vec![facet.as_facet()])),
+        }
+    }
+    if groups.is_empty() {
+        return None;
+    }
+    let groups: Vec<Vec<String>> = groups.into_iter().map(|(_, values)| values).collect();
+    Some(serde_json::to_string(&groups).expect("string arrays always serialize"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::platforms::modrinth::dto::SearchResponse;
+
+    const SEARCH_FIXTURE: &str = r#"{
+        "hits": [
+            {
+                "project_id": "AANobbMI",
+                "project_type": "mod",
+                "slug": "sodium",
+                "author": "jellysquid3",
+                "title": "Sodium",
+                "description": "The fastest rendering optimization mod for Minecraft.",
+                "categories": ["optimization", "fabric", "quilt"],
+                "versions": ["1.20.1", "1.21.4"],
+                "downloads": 70123456,
+                "follows": 25000,
+                "icon_url": "https://cdn.modrinth.com/data/AANobbMI/icon.png",
+                "date_created": "2021-01-03T00:53:34.185936Z",
+                "date_modified": "2024-12-03T18:47:23.720578Z",
+                "client_side": "required",
+                "server_side": "unsupported",
+                "license": "LGPL-3.0-only"
+            },
+            {
+                "project_id": "gvQqBUqZ",
+                "project_type": "mod",
+                "slug": "lithium",
+                "title": "Lithium",
+                "description": "No-compromises game logic optimization mod.",
+                "downloads": 40000000,
+                "date_created": "2021-01-03T00:56:52.292581Z",
+                "date_modified": "2024-12-04T10:00:00.000000Z"
+            }
+        ],
+        "offset": 20,
+        "limit": 2,
+        "total_hits": 118
+    }"#;
+
+    fn query_of(req: reqwest::RequestBuilder) -> HashMap<String, String> {
+        let req = req.build().unwrap();
+        req.url().query_pairs().into_owned().collect()
+    }
+
+    #[test]
+    fn basic_query_sends_no_facets() {
+        let req = api::faceted_search_request(
+            &reqwest::Client::new(),
+            "https://api.modrinth.com/v2",
+            "sodium",
+            encode_facets(&[]).as_deref(),
+            500,
+            20,
+        );
+        let query = query_of(req);
+        assert_eq!(query["query"], "sodium");
+        assert_eq!(query["limit"], "100");
+        assert_eq!(query["offset"], "20");
+        assert!(!query.contains_key("facets"));
+
+        let body: SearchResponse = serde_json::from_str(SEARCH_FIXTURE).unwrap();
+        let results = mapping::search_results_from(body);
+        assert_eq!((results.offset, results.limit, results.total_hits), (20, 2, 118));
+        assert_eq!(results.hits.len(), 2);
+        assert_eq!(results.hits[0].slug, "sodium");
+        assert_eq!(results.hits[0].title, "Sodium");
+        assert_eq!(results.hits[0].downloads, 70123456);
+        assert_eq!(
+            results.hits[0].icon_url.as_deref(),
+            Some("https://cdn.modrinth.com/data/AANobbMI/icon.png")
+        );
+        assert_eq!(results.hits[1].icon_url, None);
+    }
+
+    #[test]
+    fn faceted_query_groups_facets_by_kind() {
+        let facets = [
+            Facet::Loader("fabric".into()),
+            Facet::GameVersion("1.21.4".into()),
+            Facet::Loader("quilt".into()),
+            Facet::ProjectType("mod".into()),
+        ];
+        assert_eq!(
+            encode_facets(&facets).as_deref(),
+            Some(r#"[["categories:fabric","categories:quilt"],["versions:1.21.4"],["project_type:mod"]]"#)
+        );
+
+        let req = api::faceted_search_request(
+            &reqwest::Client::new(),
+            "https://api.modrinth.com/v2",
+            "map",
+            encode_facets(&facets).as_deref(),
+            10,
+            0,
+        );
+        let url = req.build().unwrap().url().to_string();
+        assert!(
+            url.contains("facets=%5B%5B%22categories%3Afabric%22%2C%22categories%3Aquilt%22%5D"),
+            "{url}"
+        );
+    }
+}
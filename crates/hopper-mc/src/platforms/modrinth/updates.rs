@@ -0,0 +1,284 @@
+//! Update checks for mods that are already installed.
+//!
+//! Installed jars are matched to Modrinth by file hash, which works no matter
+//! how the file was renamed and needs no project id stored alongside it. Like
+//! [`super::resolve`], the logic is written against a small trait so it can be
+//! exercised with an in-memory source.
+
+use crate::error::Result;
+use crate::model::{ProjectVersion, VersionType};
+
+use super::{ModrinthProvider, VersionSource};
+
+/// Where [`check_updates_with`] looks up installed files and their projects.
+pub trait UpdateSource: VersionSource {
+    /// The version that published the file with this hex SHA-1 or SHA-512
+    /// digest, or `None` when the file is unknown.
+    fn version_from_hash(&self, hash: &str) -> impl std::future::Future<Output = Result<Option<ProjectVersion>>> + Send;
+}
+
+impl UpdateSource for ModrinthProvider {
+    async fn version_from_hash(&self, hash: &str) -> Result<Option<ProjectVersion>> {
+        ModrinthProvider::version_from_hash(self, hash).await
+    }
+}
+
+/// A mod file on disk, identified by its content hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledMod {
+    /// File name on disk, carried through to the matching [`ModUpdate`].
+    pub filename: String,
+    /// Hex SHA-1 or SHA-512 digest of the file.
+    pub hash: String,
+}
+
+impl InstalledMod {
+    pub fn new(filename: impl Into<String>, hash: impl Into<String>) -> Self {
+        Self {
+            filename: filename.into(),
+            hash: hash.into(),
+        }
+    }
+}
+
+/// What [`check_updates`] found for one installed mod.
+#[derive(Debug, Clone)]
+pub struct ModUpdate {
+    pub filename: String,
+    pub status: UpdateStatus,
+}
+
+#[derive(Debug, Clone)]
+pub enum UpdateStatus {
+    /// The installed version is the newest one for the game version and loader.
+    UpToDate { current: ProjectVersion },
+    /// A newer version is available.
+    Outdated { current: ProjectVersion, latest: ProjectVersion },
+    /// The project has no version at all for the game version and loader.
+    NoCompatibleVersion { current: ProjectVersion },
+    /// The file's hash is not known to Modrinth, e.g. a locally built jar or
+    /// one downloaded from elsewhere.
+    UnknownSource,
+}
+
+impl ModUpdate {
+    /// The newer version to install, if there is one.
+    pub fn latest(&self) -> Option<&ProjectVersion> {
+        match &self.status {
+            UpdateStatus::Outdated { latest, .. } => Some(latest),
+            _ => None,
+        }
+    }
+}
+
+/// Check `installed` for updates on Modrinth, using the shared provider.
+///
+/// See [`check_updates_with`] for the rules.
+pub async fn check_updates(installed: &[InstalledMod], game_version: &str, loader: &str) -> Result<Vec<ModUpdate>> {
+    check_updates_with(ModrinthProvider::shared(), installed, game_version, loader).await
+}
+
+/// Check each of `installed` for a newer version compatible with
+/// `game_version` and `loader`, returning one [`ModUpdate`] per input in the
+/// same order.
+///
+/// - The installed version is found by file hash; files Modrinth does not
+///   know are reported as [`UpdateStatus::UnknownSource`] rather than failing
+///   the whole check.
+/// - An update is a compatible version published after the installed one
+///   that is at least as stable: a release is only offered releases, a beta
+///   releases and betas, an alpha anything.
+/// - Network and API errors abort the check.
+pub async fn check_updates_with<S: UpdateSource>(source: &S, installed: &[InstalledMod], game_version: &str, loader: &str) -> Result<Vec<ModUpdate>> {
+    let mut updates = Vec::with_capacity(installed.len());
+    for file in installed {
+        let status = match source.version_from_hash(&file.hash).await? {
+            None => UpdateStatus::UnknownSource,
+            Some(current) => status_of(source, current, game_version, loader).await?,
+        };
+        updates.push(ModUpdate {
+            filename: file.filename.clone(),
+            status,
+        });
+    }
+    Ok(updates)
+}
+
+async fn status_of<S: UpdateSource>(source: &S, current: ProjectVersion, game_version: &str, loader: &str) -> Result<UpdateStatus> {
+    let candidates: Vec<ProjectVersion> = source
+        .compatible_versions(&current.project_id, game_version, loader)
+        .await?
+        .into_iter()
+        .filter(|v| v.game_versions.iter().any(|g| g == game_version) && v.loaders.iter().any(|l| l == loader))
+        .collect();
+    if candidates.is_empty() {
+        return Ok(UpdateStatus::NoCompatibleVersion { current });
+    }
+
+    let newest = candidates
+        .into_iter()
+        .filter(|v| stability(v.version_type) <= stability(current.version_type))
+        .filter(|v| v.id != current.id && v.date_published > current.date_published)
+        .max_by(|a, b| a.date_published.cmp(&b.date_published));
+    Ok(match newest {
+        Some(latest) => UpdateStatus::Outdated { current, latest },
+        None => UpdateStatus::UpToDate { current },
+    })
+}
+
+/// Lower is more stable.
+fn stability(version_type: VersionType) -> u8 {
+    match version_type {
+        VersionType::Release => 0,
+        VersionType::Beta => 1,
+        VersionType::Alpha => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::model::VersionFile;
+    use crate::platform::Platform;
+
+    /// In-memory Modrinth keyed by version id, with each version publishing
+    /// one file whose sha1 is `"sha1-<version id>"`.
+    #[derive(Default)]
+    struct Catalog {
+        versions: HashMap<String, ProjectVersion>,
+    }
+
+    impl Catalog {
+        fn add(mut self, version: ProjectVersion) -> Self {
+            self.versions.insert(version.id.clone(), version);
+            self
+        }
+    }
+
+    impl VersionSource for Catalog {
+        async fn version(&self, version_id: &str) -> Result<Option<ProjectVersion>> {
+            Ok(self.versions.get(version_id).cloned())
+        }
+
+        async fn compatible_versions(&self, project_id: &str, _game_version: &str, _loader: &str) -> Result<Vec<ProjectVersion>> {
+            Ok(self.versions.values().filter(|v| v.project_id == project_id).cloned().collect())
+        }
+    }
+
+    impl UpdateSource for Catalog {
+        async fn version_from_hash(&self, hash: &str) -> Result<Option<ProjectVersion>> {
+            Ok(self.versions.values().find(|v| v.files.iter().any(|f| f.sha1() == Some(hash))).cloned())
+        }
+    }
+
+    fn version(id: &str, project: &str, day: u32) -> ProjectVersion {
+        ProjectVersion {
+            id: id.into(),
+            project_id: project.into(),
+            name: id.into(),
+            version_number: id.into(),
+            changelog: None,
+            date_published: Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap(),
+            downloads: 0,
+            version_type: VersionType::Release,
+            game_versions: vec!["1.20.1".into()],
+            loaders: vec!["fabric".into()],
+            files: vec![VersionFile {
+                url: Some(format!("https://cdn.modrinth.com/{id}.jar")),
+                filename: format!("{id}.jar"),
+                size: 0,
+                primary: true,
+                hashes: HashMap::from([("sha1".to_string(), format!("sha1-{id}"))]),
+            }],
+            dependencies: Vec::new(),
+            featured: false,
+            platform: Platform::Modrinth,
+        }
+    }
+
+    fn installed(version_id: &str) -> InstalledMod {
+        InstalledMod::new(format!("{version_id}.jar"), format!("sha1-{version_id}"))
+    }
+
+    fn catalog() -> Catalog {
+        let mut beta = version("sodium-3-beta", "sodium", 4);
+        beta.version_type = VersionType::Beta;
+        let mut forge = version("sodium-4-forge", "sodium", 5);
+        forge.loaders = vec!["forge".into()];
+        let mut old_game = version("lithium-1", "lithium", 1);
+        old_game.game_versions = vec!["1.19.2".into()];
+        Catalog::default()
+            .add(version("sodium-1", "sodium", 1))
+            .add(version("sodium-2", "sodium", 2))
+            .add(beta)
+            .add(forge)
+            .add(version("fabric-api-1", "fabric-api", 3))
+            .add(old_game)
+    }
+
+    #[tokio::test]
+    async fn outdated_mod_offers_newest_compatible_release() {
+        let updates = check_updates_with(&catalog(), &[installed("sodium-1")], "1.20.1", "fabric")
+            .await
+            .unwrap();
+
+        assert_eq!(updates[0].filename, "sodium-1.jar");
+        match &updates[0].status {
+            UpdateStatus::Outdated { current, latest } => {
+                assert_eq!(current.id, "sodium-1");
+                assert_eq!(latest.id, "sodium-2");
+            }
+            other => panic!("expected an update, got {other:?}"),
+        }
+        assert_eq!(updates[0].latest().map(|v| v.id.as_str()), Some("sodium-2"));
+    }
+
+    #[tokio::test]
+    async fn latest_mod_is_up_to_date() {
+        let updates = check_updates_with(&catalog(), &[installed("fabric-api-1")], "1.20.1", "fabric")
+            .await
+            .unwrap();
+
+        match &updates[0].status {
+            UpdateStatus::UpToDate { current } => assert_eq!(current.id, "fabric-api-1"),
+            other => panic!("expected up to date, got {other:?}"),
+        }
+        assert!(updates[0].latest().is_none());
+    }
+
+    #[tokio::test]
+    async fn beta_installs_are_offered_betas() {
+        let mut catalog = catalog();
+        let mut old_beta = version("sodium-0-beta", "sodium", 1);
+        old_beta.version_type = VersionType::Beta;
+        catalog = catalog.add(old_beta);
+
+        let updates = check_updates_with(&catalog, &[installed("sodium-0-beta")], "1.20.1", "fabric")
+            .await
+            .unwrap();
+        assert_eq!(updates[0].latest().map(|v| v.id.as_str()), Some("sodium-3-beta"));
+    }
+
+    #[tokio::test]
+    async fn unknown_file_is_reported_not_an_error() {
+        let files = [InstalledMod::new("homemade.jar", "sha1-nowhere"), installed("sodium-2")];
+        let updates = check_updates_with(&catalog(), &files, "1.20.1", "fabric").await.unwrap();
+
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].filename, "homemade.jar");
+        assert!(matches!(updates[0].status, UpdateStatus::UnknownSource));
+        assert!(matches!(updates[1].status, UpdateStatus::UpToDate { .. }));
+    }
+
+    #[tokio::test]
+    async fn project_without_compatible_version() {
+        let updates = check_updates_with(&catalog(), &[installed("lithium-1")], "1.20.1", "fabric")
+            .await
+            .unwrap();
+        assert!(matches!(updates[0].status, UpdateStatus::NoCompatibleVersion { .. }));
+    }
+}
@@ -0,0 +1,81 @@
+//! Shared backoff for requests a platform answers with `429 Too Many Requests`.
+//!
+//! Platforms that publish a `Retry-After` header get exactly that long to
+//! cool down (capped at [`MAX_DELAY`]); otherwise the delay doubles from one
+//! second per attempt. After [`MAX_ATTEMPTS`] the 429 response is handed back
+//! to the caller, whose status handling turns it into
+//! [`ContentError::RateLimited`](crate::ContentError::RateLimited).
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+use crate::error::Result;
+
+/// Total attempts per request, the first one included.
+pub(crate) const MAX_ATTEMPTS: u32 = 3;
+
+/// The longest we wait between attempts, whatever `Retry-After` asks for.
+pub(crate) const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Send `req`, retrying while the server rate limits it.
+///
+/// Requests whose body cannot be cloned (streams) are sent once.
+pub(crate) async fn send_with_backoff(req: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+    let mut attempt = 1;
+    loop {
+        let retry = req.try_clone().filter(|_| attempt < MAX_ATTEMPTS);
+        let Some(next) = retry else {
+            return Ok(req.send().await?);
+        };
+        let resp = next.send().await?;
+        if resp.status() != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(resp);
+        }
+
+        let delay = backoff_delay(attempt, retry_after(&resp));
+        log::warn!(
+            "rate limited by {}, retrying in {:?} (attempt {}/{})",
+            resp.url().host_str().unwrap_or("server"),
+            delay,
+            attempt + 1,
+            MAX_ATTEMPTS
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// The `Retry-After` header in seconds, if the server sent one.
+pub(crate) fn retry_after(resp: &reqwest::Response) -> Option<Duration> {
+    resp.headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// How long to wait after the `attempt`-th (1-based) request was rate limited.
+fn backoff_delay(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    let delay = retry_after.unwrap_or_else(|| Duration::from_secs(1u64 << (attempt - 1).min(5)));
+    delay.min(MAX_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_prefers_retry_after_and_caps_it() {
+        assert_eq!(backoff_delay(1, Some(Duration::from_secs(7))), Duration::from_secs(7));
+        assert_eq!(backoff_delay(1, Some(Duration::from_secs(600))), MAX_DELAY);
+    }
+
+    #[test]
+    fn backoff_doubles_without_retry_after() {
+        assert_eq!(backoff_delay(1, None), Duration::from_secs(1));
+        assert_eq!(backoff_delay(2, None), Duration::from_secs(2));
+        assert_eq!(backoff_delay(3, None), Duration::from_secs(4));
+        assert_eq!(backoff_delay(40, None), MAX_DELAY);
+    }
+}
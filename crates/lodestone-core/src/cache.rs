@@ -0,0 +1,198 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+
+use crate::instance::{Instance, RemovalReport, file_id};
+use crate::paths;
+
+/// Trim [`paths::cache_dir`] to `max_bytes`, see [`prune_dir`].
+pub fn prune(max_bytes: u64, max_age: Duration, instances: &[Instance]) -> Result<RemovalReport> {
+    prune_dir(&paths::cache_dir()?, max_bytes, max_age, instances)
+}
+
+/// Delete cache files unused for longer than `max_age`, then the least recently used
+/// ones until the cache holds at most `max_bytes`.
+///
+/// A file counts as used when it was last read or written, whichever is later. Files
+/// that one of the `instances` currently holding [`Instance::lock`] links to, by a
+/// symlink or a hard link, are never deleted; they still count toward `max_bytes`, so
+/// the cache can end up over budget. Directories left empty are removed too, and the
+/// report lists the deleted files only.
+pub fn prune_dir(cache_dir: &Path, max_bytes: u64, max_age: Duration, instances: &[Instance]) -> Result<RemovalReport> {
+    let mut report = RemovalReport::default();
+    if !cache_dir.exists() {
+        return Ok(report);
+    }
+    let cache_dir = cache_dir.canonicalize()?;
+    let in_use = InUse::collect(&cache_dir, instances.iter().filter(|instance| instance.is_locked()))?;
+
+    let mut entries = Vec::new();
+    let mut total = 0;
+    for (path, metadata) in files_below(&cache_dir)? {
+        total += metadata.len();
+        if !in_use.contains(&path, &metadata) {
+            entries.push((last_used(&metadata), metadata.len(), path));
+        }
+    }
+    // Oldest first, so both passes delete from the front.
+    entries.sort();
+
+    let now = SystemTime::now();
+    for (used, size, path) in entries {
+        let expired = now.duration_since(used).is_ok_and(|age| age > max_age);
+        if !expired && total <= max_bytes {
+            break;
+        }
+        std::fs::remove_file(&path).with_context(|| format!("Failed to prune {}", path.display()))?;
+        total -= size;
+        report.total_bytes += size;
+        report.paths.push(path);
+    }
+    remove_empty_dirs(&cache_dir)?;
+    Ok(report)
+}
+
+/// Cache files that a locked instance links to.
+#[derive(Default)]
+struct InUse {
+    /// Symlink targets inside the cache; a directory covers everything below it.
+    targets: Vec<PathBuf>,
+    /// Files the instances hard-link.
+    file_ids: HashSet<(u64, u64)>,
+}
+
+impl InUse {
+    fn collect<'a>(cache_dir: &Path, instances: impl Iterator<Item = &'a Instance>) -> Result<Self> {
+        let mut in_use = Self::default();
+        for instance in instances {
+            let mut pending = vec![instance.root().to_path_buf()];
+            while let Some(path) = pending.pop() {
+                let Ok(metadata) = std::fs::symlink_metadata(&path) else { continue };
+                if metadata.is_symlink() {
+                    in_use
+                        .targets
+                        .extend(path.canonicalize().ok().filter(|target| target.starts_with(cache_dir)));
+                } else if metadata.is_dir() {
+                    for entry in std::fs::read_dir(&path)? {
+                        pending.push(entry?.path());
+                    }
+                } else if let Some(id) = file_id(&metadata) {
+                    in_use.file_ids.insert(id);
+                }
+            }
+        }
+        Ok(in_use)
+    }
+
+    fn contains(&self, path: &Path, metadata: &std::fs::Metadata) -> bool {
+        self.targets.iter().any(|target| path.starts_with(target)) || file_id(metadata).is_some_and(|id| self.file_ids.contains(&id))
+    }
+}
+
+/// Every regular file below `dir`, without following symlinks.
+fn files_below(dir: &Path) -> Result<Vec<(PathBuf, std::fs::Metadata)>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let metadata = std::fs::symlink_metadata(entry.path())?;
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else if metadata.is_file() {
+                files.push((entry.path(), metadata));
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn last_used(metadata: &std::fs::Metadata) -> SystemTime {
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    metadata.accessed().map_or(modified, |accessed| accessed.max(modified))
+}
+
+/// Remove the directories below `dir` that hold no files, keeping `dir` itself.
+fn remove_empty_dirs(dir: &Path) -> Result<bool> {
+    let mut empty = true;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if std::fs::symlink_metadata(&path)?.is_dir() && remove_empty_dirs(&path)? {
+            std::fs::remove_dir(&path)?;
+        } else {
+            empty = false;
+        }
+    }
+    Ok(empty)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs::FileTimes;
+
+    /// A file of `size` bytes last used `age` ago.
+    fn cache_file(dir: &Path, name: &str, size: usize, age: Duration) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, vec![0u8; size]).unwrap();
+        let at = SystemTime::now() - age;
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_times(FileTimes::new().set_accessed(at).set_modified(at)).unwrap();
+        path
+    }
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn prunes_expired_then_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().canonicalize().unwrap();
+        let ancient = cache_file(&cache, "meta/ancient.json", 100, 48 * HOUR);
+        let old = cache_file(&cache, "libraries/old.jar", 400, 3 * HOUR);
+        let recent = cache_file(&cache, "libraries/recent.jar", 300, 2 * HOUR);
+        let fresh = cache_file(&cache, "fresh.json", 200, Duration::ZERO);
+
+        let report = prune_dir(&cache, 600, 24 * HOUR, &[]).unwrap();
+        assert_eq!(report.paths, vec![ancient.clone(), old.clone()]);
+        assert_eq!(report.total_bytes, 500);
+        assert!(!ancient.exists() && !old.exists());
+        assert!(recent.exists() && fresh.exists());
+        assert!(!cache.join("meta").exists());
+
+        let report = prune_dir(&cache, 600, 24 * HOUR, &[]).unwrap();
+        assert_eq!(report, RemovalReport::default());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_files_linked_from_locked_instances() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let cache = root.join("cache");
+        let shared = cache_file(&cache, "libraries/shared.jar", 100, 48 * HOUR);
+        let linked = cache_file(&cache, "assets/linked.json", 100, 48 * HOUR);
+        let loose = cache_file(&cache, "loose.json", 100, 48 * HOUR);
+
+        let locked = Instance::create(root.join("locked")).unwrap();
+        std::os::unix::fs::symlink(cache.join("libraries"), locked.root().join("shared-libraries")).unwrap();
+        std::fs::hard_link(&linked, locked.assets_dir().join("linked.json")).unwrap();
+        let _lock = locked.lock().unwrap();
+        let unlocked = Instance::create(root.join("unlocked")).unwrap();
+        std::os::unix::fs::symlink(&loose, unlocked.root().join("loose.json")).unwrap();
+
+        let report = prune_dir(&cache, 0, 24 * HOUR, &[locked, unlocked]).unwrap();
+        assert_eq!(report.paths, vec![loose]);
+        assert_eq!(report.total_bytes, 100);
+        assert!(shared.exists() && linked.exists());
+    }
+
+    #[test]
+    fn missing_cache_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = prune_dir(&dir.path().join("nothing"), 0, Duration::ZERO, &[]).unwrap();
+        assert_eq!(report, RemovalReport::default());
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use hopper_mc::ModrinthProvider;
+use minecraft_modloaders::download::{HashAlgo, file_hash_blocking};
+use serde::Serialize;
+
+use crate::instance::{Instance, LoaderType};
+use crate::lock::LOCK_FILE;
+use crate::metadata::METADATA_FILE;
+use crate::profile::Profile;
+use crate::utils::{ZipOptions, zip_dir};
+
+/// The index every `.mrpack` carries at its root.
+pub const MRPACK_INDEX_FILE: &str = "modrinth.index.json";
+
+/// Top-level instance entries that never go into `overrides/`: downloaded game files,
+/// worlds, logs, backups, the instance lock and the instance metadata, whose UUID must
+/// not be shared by everyone who imports the pack. Mods are listed in the index or
+/// added one by one.
+const NOT_EXPORTED: [&str; 11] = [
+    "assets",
+    "backups",
+    "crash-reports",
+    METADATA_FILE,
+    LOCK_FILE,
+    "libraries",
+    "logs",
+    "mods",
+    "natives",
+    "saves",
+    "versions",
+];
+
+/// Describes the modpack written by [`Instance::export_mrpack`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MrpackMetadata {
+    pub name: String,
+    /// The pack's own version, e.g. `1.0.0`.
+    pub version_id: String,
+    pub summary: Option<String>,
+    pub minecraft_version: String,
+    pub loader: LoaderType,
+    /// Required for every loader except vanilla.
+    pub loader_version: Option<String>,
+}
+
+impl MrpackMetadata {
+    /// Name the pack after `profile` and take its game and loader versions.
+    pub fn from_profile(profile: &Profile, version_id: impl Into<String>) -> Self {
+        Self {
+            name: profile.name.clone(),
+            version_id: version_id.into(),
+            summary: None,
+            minecraft_version: profile.minecraft_version.clone(),
+            loader: profile.loader.clone(),
+            loader_version: profile.loader_version.clone(),
+        }
+    }
+
+    pub fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// The index `dependencies`: `minecraft` plus the loader under its mrpack key.
+    fn dependencies(&self) -> Result<BTreeMap<&'static str, String>> {
+        let mut dependencies = BTreeMap::from([("minecraft", self.minecraft_version.clone())]);
+        let key = match self.loader {
+            LoaderType::Vanilla => return Ok(dependencies),
+            LoaderType::Fabric => "fabric-loader",
+            LoaderType::Quilt => "quilt-loader",
+            LoaderType::Forge => "forge",
+            LoaderType::Neoforge => "neoforge",
+        };
+        let Some(version) = &self.loader_version else {
+            bail!("Cannot export a {} pack without a loader version", self.loader);
+        };
+        dependencies.insert(key, version.clone());
+        Ok(dependencies)
+    }
+}
+
+/// Where a mod jar can be downloaded from, as found by a [`ModLookup`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MrpackDownload {
+    pub url: String,
+    pub sha1: String,
+    pub sha512: String,
+    pub size: u64,
+}
+
+/// Finds public downloads for mod jars so the pack can link them instead of bundling them.
+pub trait ModLookup {
+    /// The download for the file with this hex SHA-1, or `None` when it is not hosted.
+    fn find_download(&self, sha1: &str) -> impl Future<Output = Result<Option<MrpackDownload>>> + Send;
+}
+
+impl ModLookup for ModrinthProvider {
+    async fn find_download(&self, sha1: &str) -> Result<Option<MrpackDownload>> {
+        let Some(version) = self.version_from_hash(sha1).await? else {
+            return Ok(None);
+        };
+        Ok(version.files.into_iter().find(|file| file.sha1() == Some(sha1)).and_then(|file| {
+            Some(MrpackDownload {
+                sha512: file.sha512()?.to_string(),
+                url: file.url?,
+                sha1: sha1.to_string(),
+                size: file.size,
+            })
+        }))
+    }
+}
+
+/// What [`Instance::export_mrpack`] wrote.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MrpackExport {
+    pub path: PathBuf,
+    /// Mod files listed in the index with a download link.
+    pub linked: Vec<String>,
+    /// Mod files no download was found for, bundled under `overrides/mods/`.
+    pub bundled: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MrpackIndex<'a> {
+    format_version: u32,
+    game: &'static str,
+    version_id: &'a str,
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<&'a str>,
+    files: Vec<MrpackIndexFile>,
+    dependencies: BTreeMap<&'static str, String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MrpackIndexFile {
+    path: String,
+    hashes: BTreeMap<&'static str, String>,
+    downloads: Vec<String>,
+    file_size: u64,
+}
+
+impl Instance {
+    /// Write the instance as a Modrinth modpack to `dest`, linking mods found on Modrinth.
+    ///
+    /// See [`Instance::export_mrpack_with`].
+    pub async fn export_mrpack(&self, dest: &Path, metadata: &MrpackMetadata) -> Result<MrpackExport> {
+        self.export_mrpack_with(dest, metadata, ModrinthProvider::shared()).await
+    }
+
+    /// Like [`Instance::export_mrpack`], finding mod downloads through `lookup`.
+    ///
+    /// Enabled mods are looked up by SHA-1; those without a download are bundled under
+    /// `overrides/mods/`. Everything else in the instance except game files, worlds,
+    /// logs and backups (configs, `options.txt`, resource packs, ...) goes into
+    /// `overrides/`. The zip stores no modification times, so exporting an unchanged
+    /// instance twice gives identical files.
+    pub async fn export_mrpack_with(&self, dest: &Path, metadata: &MrpackMetadata, lookup: &impl ModLookup) -> Result<MrpackExport> {
+        let dependencies = metadata.dependencies()?;
+        let mut export = MrpackExport {
+            path: dest.to_path_buf(),
+            ..MrpackExport::default()
+        };
+        let mut files = Vec::new();
+        let mut bundled_paths = Vec::new();
+        for file in self.list_mods()?.into_iter().filter(|file| file.enabled) {
+            let sha1 = file_hash_blocking(&file.path, HashAlgo::Sha1)?;
+            match lookup.find_download(&sha1).await? {
+                Some(download) => {
+                    files.push(MrpackIndexFile {
+                        path: format!("mods/{}", file.filename),
+                        hashes: BTreeMap::from([("sha1", download.sha1), ("sha512", download.sha512)]),
+                        downloads: vec![download.url],
+                        file_size: download.size,
+                    });
+                    export.linked.push(file.filename);
+                }
+                None => {
+                    bundled_paths.push(file.path);
+                    export.bundled.push(file.filename);
+                }
+            }
+        }
+        let index = MrpackIndex {
+            format_version: 1,
+            game: "minecraft",
+            version_id: &metadata.version_id,
+            name: &metadata.name,
+            summary: metadata.summary.as_deref(),
+            files,
+            dependencies,
+        };
+
+        let file_name = dest.file_name().and_then(|name| name.to_str()).context("Export path has no file name")?;
+        let staging = dest.with_file_name(format!("{}.staging", file_name));
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)?;
+        }
+        let result = self.stage_mrpack(&staging, &index, &bundled_paths, &[dest]).and_then(|()| {
+            zip_dir(
+                &staging,
+                dest,
+                ZipOptions {
+                    store_mtimes: false,
+                    ..ZipOptions::default()
+                },
+            )
+        });
+        let _ = std::fs::remove_dir_all(&staging);
+        result.with_context(|| format!("Failed to export {} to {}", self.root().display(), dest.display()))?;
+        Ok(export)
+    }
+
+    /// Lay out the pack contents in `staging`, leaving out `skip` should it live inside the instance.
+    fn stage_mrpack(&self, staging: &Path, index: &MrpackIndex, bundled: &[PathBuf], skip: &[&Path]) -> Result<()> {
+        let overrides = staging.join("overrides");
+        std::fs::create_dir_all(&overrides)?;
+        std::fs::write(staging.join(MRPACK_INDEX_FILE), serde_json::to_vec_pretty(index)?)?;
+
+        for entry in std::fs::read_dir(self.root())? {
+            let entry = entry?;
+            let path = entry.path();
+            let name = entry.file_name();
+            if NOT_EXPORTED.iter().any(|skipped| name == *skipped) || path == staging || skip.contains(&path.as_path()) {
+                continue;
+            }
+            copy_tree(&path, &overrides.join(&name))?;
+        }
+        if !bundled.is_empty() {
+            std::fs::create_dir_all(overrides.join("mods"))?;
+            for path in bundled {
+                std::fs::copy(path, overrides.join("mods").join(path.file_name().unwrap_or_default()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Copy a file or directory tree; symlinks are skipped rather than followed.
+fn copy_tree(src: &Path, dest: &Path) -> Result<()> {
+    let metadata = std::fs::symlink_metadata(src)?;
+    if metadata.is_dir() {
+        std::fs::create_dir_all(dest)?;
+        for entry in std::fs::read_dir(src)? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &dest.join(entry.file_name()))?;
+        }
+    } else if metadata.is_file() {
+        std::fs::copy(src, dest)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hopper_mc::Mrpack;
+    use std::collections::HashMap;
+
+    /// Knows the downloads of a fixed set of files, by SHA-1.
+    struct FakeLookup(HashMap<String, MrpackDownload>);
+
+    impl ModLookup for FakeLookup {
+        async fn find_download(&self, sha1: &str) -> Result<Option<MrpackDownload>> {
+            Ok(self.0.get(sha1).cloned())
+        }
+    }
+
+    fn fixture() -> (tempfile::TempDir, Instance, FakeLookup) {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path().join("pack")).unwrap();
+        std::fs::write(instance.mods_dir().join("sodium-0.5.8.jar"), b"sodium jar").unwrap();
+        std::fs::write(instance.mods_dir().join("homemade.jar"), b"my own mod").unwrap();
+        std::fs::write(instance.mods_dir().join("old.jar.disabled"), b"switched off").unwrap();
+        std::fs::create_dir_all(instance.root().join("config")).unwrap();
+        std::fs::write(instance.root().join("config").join("sodium-options.json"), b"{}").unwrap();
+        std::fs::write(instance.root().join("options.txt"), b"fov:90").unwrap();
+        std::fs::create_dir_all(instance.saves_dir().join("World")).unwrap();
+        std::fs::write(instance.saves_dir().join("World").join("level.dat"), b"level").unwrap();
+        std::fs::create_dir_all(instance.logs_dir()).unwrap();
+        std::fs::write(instance.logs_dir().join("latest.log"), b"log").unwrap();
+
+        let sha1 = file_hash_blocking(&instance.mods_dir().join("sodium-0.5.8.jar"), HashAlgo::Sha1).unwrap();
+        let lookup = FakeLookup(HashMap::from([(
+            sha1.clone(),
+            MrpackDownload {
+                url: "https://cdn.modrinth.com/data/AANobbMI/versions/sodium-0.5.8.jar".to_string(),
+                sha1,
+                sha512: "ab".repeat(64),
+                size: 10,
+            },
+        )]));
+        (dir, instance, lookup)
+    }
+
+    fn metadata() -> MrpackMetadata {
+        let profile = Profile::new("Cozy Pack", "1.20.1", LoaderType::Fabric).with_loader_version("0.15.11");
+        MrpackMetadata::from_profile(&profile, "1.0.0").with_summary("Just the essentials")
+    }
+
+    #[tokio::test]
+    async fn export_round_trips() {
+        let (dir, instance, lookup) = fixture();
+        let dest = dir.path().join("Cozy Pack.mrpack");
+
+        let export = instance.export_mrpack_with(&dest, &metadata(), &lookup).await.unwrap();
+        assert_eq!(export.linked, ["sodium-0.5.8.jar"]);
+        assert_eq!(export.bundled, ["homemade.jar"]);
+        assert!(!dir.path().join("Cozy Pack.mrpack.staging").exists());
+
+        let pack = Mrpack::open(std::fs::File::open(&dest).unwrap()).unwrap();
+        assert_eq!(pack.name(), "Cozy Pack");
+        assert_eq!(pack.summary(), Some("Just the essentials"));
+        assert_eq!(pack.minecraft_version(), "1.20.1");
+        assert_eq!(pack.dependencies().get("fabric-loader").map(String::as_str), Some("0.15.11"));
+        assert_eq!(pack.manifest().version.as_deref(), Some("1.0.0"));
+
+        let files = pack.files();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "mods/sodium-0.5.8.jar");
+        assert_eq!(
+            files[0].download_urls,
+            ["https://cdn.modrinth.com/data/AANobbMI/versions/sodium-0.5.8.jar"]
+        );
+        assert_eq!(files[0].size, Some(10));
+        assert_eq!(files[0].hashes.get("sha512"), Some(&"ab".repeat(64)));
+
+        let overrides: Vec<_> = pack.overrides().iter().map(|o| o.archive_path.as_str()).collect();
+        assert_eq!(
+            overrides,
+            [
+                "overrides/config/sodium-options.json",
+                "overrides/mods/homemade.jar",
+                "overrides/options.txt"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn export_is_reproducible() {
+        let (dir, instance, lookup) = fixture();
+        let first = dir.path().join("first.mrpack");
+        let second = dir.path().join("second.mrpack");
+        instance.export_mrpack_with(&first, &metadata(), &lookup).await.unwrap();
+        instance.export_mrpack_with(&second, &metadata(), &lookup).await.unwrap();
+        assert_eq!(std::fs::read(first).unwrap(), std::fs::read(second).unwrap());
+    }
+
+    #[tokio::test]
+    async fn export_into_the_instance_skips_itself() {
+        let (_dir, instance, lookup) = fixture();
+        let dest = instance.root().join("pack.mrpack");
+        instance.export_mrpack_with(&dest, &metadata(), &lookup).await.unwrap();
+        // A second export must not bundle the first
+        instance.export_mrpack_with(&dest, &metadata(), &lookup).await.unwrap();
+
+        let pack = Mrpack::open(std::fs::File::open(&dest).unwrap()).unwrap();
+        assert!(pack.overrides().iter().all(|o| !o.archive_path.ends_with(".mrpack")));
+    }
+
+    #[tokio::test]
+    async fn export_needs_a_loader_version() {
+        let (dir, instance, lookup) = fixture();
+        let metadata = MrpackMetadata::from_profile(&Profile::new("Forge Pack", "1.20.1", LoaderType::Forge), "1.0.0");
+        let dest = dir.path().join("forge.mrpack");
+        assert!(instance.export_mrpack_with(&dest, &metadata, &lookup).await.is_err());
+        assert!(!dest.exists());
+    }
+}
@@ -0,0 +1,278 @@
+//! Importing instances from other launchers.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::instance::LoaderType;
+use crate::profile::Profile;
+
+/// Name of the official launcher's profile list inside `.minecraft`.
+pub const VANILLA_PROFILES_FILE: &str = "launcher_profiles.json";
+
+/// Release (`1.20.1`, `1.21-pre1`), snapshot (`23w13a`) and old alpha/beta/classic ids.
+static VANILLA_VERSION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d+\.\d+(\.\d+)?(-(pre|rc)\d+| Pre-Release \d+)?|\d{2}w\d{2}[a-z]|[abc]\d+\.\d+.*|rd-\d+|inf-\d+)$").unwrap());
+
+#[derive(Debug, Deserialize)]
+struct LauncherProfilesFile {
+    #[serde(default)]
+    profiles: BTreeMap<String, LauncherProfile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LauncherProfile {
+    #[serde(default)]
+    name: String,
+    /// `custom`, `latest-release` or `latest-snapshot`.
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+    last_version_id: Option<String>,
+    game_dir: Option<PathBuf>,
+    java_args: Option<String>,
+    created: Option<DateTime<Utc>>,
+    last_used: Option<DateTime<Utc>>,
+}
+
+/// The official launcher's `.minecraft` directory for this OS: `%APPDATA%\.minecraft` on
+/// Windows, `~/Library/Application Support/minecraft` on macOS and `~/.minecraft` elsewhere.
+pub fn default_minecraft_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(|appdata| PathBuf::from(appdata).join(".minecraft"))
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library").join("Application Support").join("minecraft"))
+    } else {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".minecraft"))
+    }
+}
+
+/// Read the profiles of the official launcher from `minecraft_dir`, or from
+/// [`default_minecraft_dir`] when it is `None`.
+///
+/// Each profile's version id is mapped to a Minecraft version and loader; Fabric, Quilt,
+/// Forge and NeoForge installs are recognised by their version id. Profiles that cannot be
+/// mapped are skipped with a warning: the `latest-release`/`latest-snapshot` entries,
+/// which name no fixed version, and versions from other loaders or mods (e.g. OptiFine).
+/// Profiles are returned sorted by name; unnamed ones are named after their version.
+pub fn from_vanilla_launcher(minecraft_dir: Option<&Path>) -> Result<Vec<Profile>> {
+    let minecraft_dir = match minecraft_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => default_minecraft_dir().ok_or_else(|| anyhow!("Could not locate the .minecraft directory"))?,
+    };
+    let path = minecraft_dir.join(VANILLA_PROFILES_FILE);
+    let json = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let file: LauncherProfilesFile = serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let mut profiles = Vec::new();
+    for (key, launcher_profile) in file.profiles {
+        match import_profile(launcher_profile) {
+            Ok(profile) => profiles.push(profile),
+            Err(e) => log::warn!("Skipping launcher profile {}: {}", key, e),
+        }
+    }
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(profiles)
+}
+
+fn import_profile(launcher_profile: LauncherProfile) -> Result<Profile> {
+    if let Some(kind) = launcher_profile.kind.as_deref().filter(|kind| kind.starts_with("latest-")) {
+        return Err(anyhow!("'{}' profiles follow the newest version and have no fixed one", kind));
+    }
+    let version_id = launcher_profile.last_version_id.ok_or_else(|| anyhow!("No version selected"))?;
+    let (minecraft_version, loader, loader_version) = parse_version_id(&version_id).ok_or_else(|| anyhow!("Unsupported version '{}'", version_id))?;
+
+    let name = if launcher_profile.name.trim().is_empty() {
+        version_id.clone()
+    } else {
+        launcher_profile.name
+    };
+    let mut profile = Profile::new(name, minecraft_version, loader);
+    profile.loader_version = loader_version;
+    profile.jvm_args = launcher_profile
+        .java_args
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+    profile.game_dir = launcher_profile.game_dir;
+    if let Some(created) = launcher_profile.created {
+        profile.created_at = created;
+    }
+    // The launcher writes the epoch for profiles that were never played.
+    profile.last_used = launcher_profile.last_used.filter(|used| used.timestamp() > 0);
+    Ok(profile)
+}
+
+/// Split a launcher version id into Minecraft version, loader and loader version.
+fn parse_version_id(id: &str) -> Option<(String, LoaderType, Option<String>)> {
+    // fabric-loader-0.15.11-1.20.1, quilt-loader-0.26.0-1.20.1
+    for (prefix, loader) in [("fabric-loader-", LoaderType::Fabric), ("quilt-loader-", LoaderType::Quilt)] {
+        if let Some(rest) = id.strip_prefix(prefix) {
+            let (loader_version, minecraft_version) = rest.split_once('-')?;
+            return Some((minecraft_version.to_string(), loader, Some(loader_version.to_string())));
+        }
+    }
+
+    // neoforge-20.4.80 targets 1.20.4, neoforge-21.0.14-beta targets 1.21
+    if let Some(loader_version) = id.strip_prefix("neoforge-") {
+        let mut parts = loader_version.split('.');
+        let (major, minor) = (parts.next()?, parts.next()?);
+        let minecraft_version = if minor == "0" {
+            format!("1.{major}")
+        } else {
+            format!("1.{major}.{minor}")
+        };
+        return Some((minecraft_version, LoaderType::Neoforge, Some(loader_version.to_string())));
+    }
+
+    // 1.20.1-forge-47.2.0, 1.12.2-forge1.12.2-14.23.5.2847, 1.7.10-Forge10.13.4.1614-1.7.10
+    if let Some(index) = id.to_ascii_lowercase().find("-forge") {
+        let minecraft_version = &id[..index];
+        let rest = id[index + "-forge".len()..].trim_start_matches('-');
+        let rest = rest.strip_prefix(&format!("{minecraft_version}-")).unwrap_or(rest);
+        let loader_version = rest.strip_suffix(&format!("-{minecraft_version}")).unwrap_or(rest);
+        if minecraft_version.is_empty() || loader_version.is_empty() {
+            return None;
+        }
+        return Some((minecraft_version.to_string(), LoaderType::Forge, Some(loader_version.to_string())));
+    }
+
+    VANILLA_VERSION_RE.is_match(id).then(|| (id.to_string(), LoaderType::Vanilla, None))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LAUNCHER_PROFILES: &str = r#"{
+        "profiles": {
+            "5f1e0c0a0b6c4c2f9f0e8d7c6b5a4f3e": {
+                "created": "1970-01-02T00:00:00.000Z",
+                "icon": "Grass",
+                "lastUsed": "2024-05-01T12:00:00.000Z",
+                "lastVersionId": "latest-release",
+                "name": "",
+                "type": "latest-release"
+            },
+            "a1": {
+                "created": "2023-06-12T18:30:00.000Z",
+                "gameDir": "/home/steve/.minecraft/instances/survival",
+                "icon": "Furnace",
+                "javaArgs": "-Xmx4G -XX:+UnlockExperimentalVMOptions -XX:+UseG1GC",
+                "lastUsed": "2024-04-20T09:15:00.000Z",
+                "lastVersionId": "1.20.1",
+                "name": "Survival",
+                "type": "custom"
+            },
+            "b2": {
+                "created": "2024-01-05T10:00:00.000Z",
+                "lastUsed": "1970-01-01T00:00:00.000Z",
+                "lastVersionId": "fabric-loader-0.15.11-1.20.1",
+                "name": "Fabric",
+                "type": "custom"
+            },
+            "c3": {
+                "lastVersionId": "1.12.2-forge-14.23.5.2859",
+                "name": "Old Forge",
+                "type": "custom"
+            },
+            "d4": {
+                "lastVersionId": "neoforge-20.4.80",
+                "name": "NeoForge",
+                "type": "custom"
+            },
+            "e5": {
+                "lastVersionId": "1.20.1-OptiFine_HD_U_I6",
+                "name": "OptiFine",
+                "type": "custom"
+            },
+            "f6": {
+                "lastVersionId": "23w13a",
+                "type": "custom"
+            }
+        },
+        "settings": {"enableSnapshots": true},
+        "version": 3
+    }"#;
+
+    fn import(json: &str) -> Vec<Profile> {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(VANILLA_PROFILES_FILE), json).unwrap();
+        from_vanilla_launcher(Some(dir.path())).unwrap()
+    }
+
+    #[test]
+    fn imports_supported_profiles() {
+        let profiles = import(LAUNCHER_PROFILES);
+        let names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["23w13a", "Fabric", "NeoForge", "Old Forge", "Survival"]);
+
+        let survival = &profiles[4];
+        assert_eq!(survival.minecraft_version, "1.20.1");
+        assert_eq!(survival.loader, LoaderType::Vanilla);
+        assert_eq!(survival.loader_version, None);
+        assert_eq!(survival.game_dir, Some(PathBuf::from("/home/steve/.minecraft/instances/survival")));
+        assert_eq!(survival.jvm_args, vec!["-Xmx4G", "-XX:+UnlockExperimentalVMOptions", "-XX:+UseG1GC"]);
+        assert_eq!(survival.created_at.to_rfc3339(), "2023-06-12T18:30:00+00:00");
+        assert!(survival.last_used.is_some());
+    }
+
+    #[test]
+    fn maps_modded_version_ids() {
+        let profiles = import(LAUNCHER_PROFILES);
+        let find = |name: &str| profiles.iter().find(|p| p.name == name).unwrap();
+
+        let fabric = find("Fabric");
+        assert_eq!(fabric.loader, LoaderType::Fabric);
+        assert_eq!(fabric.minecraft_version, "1.20.1");
+        assert_eq!(fabric.loader_version.as_deref(), Some("0.15.11"));
+        assert_eq!(fabric.last_used, None);
+        assert!(fabric.game_dir.is_none());
+
+        let forge = find("Old Forge");
+        assert_eq!(forge.loader, LoaderType::Forge);
+        assert_eq!(forge.minecraft_version, "1.12.2");
+        assert_eq!(forge.loader_version.as_deref(), Some("14.23.5.2859"));
+
+        let neoforge = find("NeoForge");
+        assert_eq!(neoforge.loader, LoaderType::Neoforge);
+        assert_eq!(neoforge.minecraft_version, "1.20.4");
+    }
+
+    #[test]
+    fn parses_version_id_variants() {
+        let forge = |id: &str| parse_version_id(id).map(|(mc, loader, version)| (mc, loader, version.unwrap()));
+        assert_eq!(
+            forge("1.12.2-forge1.12.2-14.23.5.2847"),
+            Some(("1.12.2".into(), LoaderType::Forge, "14.23.5.2847".into()))
+        );
+        assert_eq!(
+            forge("1.7.10-Forge10.13.4.1614-1.7.10"),
+            Some(("1.7.10".into(), LoaderType::Forge, "10.13.4.1614".into()))
+        );
+        assert_eq!(
+            forge("quilt-loader-0.26.0-1.20.1"),
+            Some(("1.20.1".into(), LoaderType::Quilt, "0.26.0".into()))
+        );
+        assert_eq!(
+            forge("neoforge-21.0.14-beta"),
+            Some(("1.21".into(), LoaderType::Neoforge, "21.0.14-beta".into()))
+        );
+        assert!(parse_version_id("1.21-pre1").is_some());
+        assert!(parse_version_id("b1.7.3").is_some());
+        assert!(parse_version_id("Lunar Client").is_none());
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(from_vanilla_launcher(Some(dir.path())).is_err());
+    }
+}
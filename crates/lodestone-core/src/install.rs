@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use minecraft_modloaders::ModLoaderVersions;
+use minecraft_modloaders::download::{CancellationToken, HashAlgo, file_hash_blocking};
+use minecraft_modloaders::fabric::{FabricModLoader, FabricVersions};
+use minecraft_modloaders::install::InstallEvent;
+use minecraft_modloaders::quilt::{QuiltModLoader, QuiltVersions};
+use minecraft_modloaders::vanilla::VersionManifest;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::instance::{Instance, LoaderType};
+use crate::selector::VersionSelector;
+
+/// Where [`Instance::install_with`] fetches game metadata and assets from, for mirrors
+/// and tests, and how to stop it. Unset fields use Mojang's services.
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    pub manifest_url: Option<String>,
+    pub resources_url: Option<String>,
+    /// Cancelling it aborts the downloads and fails the install with
+    /// [`Cancelled`](minecraft_modloaders::download::Cancelled).
+    pub cancel: Option<CancellationToken>,
+}
+
+impl InstallOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_manifest_url(mut self, url: impl Into<String>) -> Self {
+        self.manifest_url = Some(url.into());
+        self
+    }
+
+    pub fn with_resources_url(mut self, url: impl Into<String>) -> Self {
+        self.resources_url = Some(url.into());
+        self
+    }
+
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+}
+
+/// What [`Instance::install`] put in place, for auditing an instance or diffing two.
+///
+/// Files are sorted by path, which is relative to the instance root, so manifests of
+/// the same version installed into different instances compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallManifest {
+    /// The concrete game version the selector resolved to.
+    pub minecraft_version: String,
+    pub loader: LoaderType,
+    /// The concrete loader version, None for Vanilla.
+    pub loader_version: Option<String>,
+    pub files: Vec<InstalledFile>,
+}
+
+impl InstallManifest {
+    /// The files that were actually written, leaving out those already in place.
+    pub fn written(&self) -> impl Iterator<Item = &InstalledFile> {
+        self.files.iter().filter(|file| !file.skipped)
+    }
+}
+
+/// One file of an [`InstallManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledFile {
+    /// Relative to the instance root.
+    pub path: PathBuf,
+    /// SHA-1 of the file as it is on disk after the install.
+    pub sha1: String,
+    pub size: u64,
+    /// Already present and intact, so it was not written again.
+    pub skipped: bool,
+}
+
+impl Instance {
+    /// Fully provision the instance for a game version and, optionally, a loader, in
+    /// one call: resolve the selectors, download the client jar, libraries and assets,
+    /// extract natives and write the version JSONs to `versions/` so the instance can
+    /// be launched offline.
+    ///
+    /// Running it again is cheap: files already present with the expected hash are
+    /// skipped and marked so in the returned [`InstallManifest`]. Progress is reported
+    /// through `events` as [`Installer::install`](minecraft_modloaders::install::Installer::install)
+    /// reports it. Fabric and Quilt are installed from the launch profile their meta
+    /// API publishes. Forge and NeoForge are not supported: they need their installer
+    /// run (see [`ProcessorRunner`](minecraft_modloaders::forge::ProcessorRunner)), and
+    /// fail before anything is fetched.
+    pub async fn install(
+        &self,
+        minecraft: &VersionSelector,
+        loader: Option<(LoaderType, VersionSelector)>,
+        events: Option<mpsc::Sender<InstallEvent>>,
+    ) -> Result<InstallManifest> {
+        self.install_with(minecraft, loader, events, &InstallOptions::new()).await
+    }
+
+    /// Like [`Instance::install`], fetching from the sources in `options`.
+    pub async fn install_with(
+        &self,
+        minecraft: &VersionSelector,
+        loader: Option<(LoaderType, VersionSelector)>,
+        events: Option<mpsc::Sender<InstallEvent>>,
+        options: &InstallOptions,
+    ) -> Result<InstallManifest> {
+        if let Some((loader @ (LoaderType::Forge | LoaderType::Neoforge), _)) = &loader {
+            bail!("Installing {} instances is not supported", loader.as_str());
+        }
+        let manifest = match &options.manifest_url {
+            Some(url) => VersionManifest::fetch_from(url).await?,
+            None => VersionManifest::fetch().await?,
+        };
+        let game = minecraft.resolve(&manifest, &[])?;
+        let version = manifest.fetch_detail(&game).await?;
+        let (loader, loader_version, profile) = match loader {
+            None | Some((LoaderType::Vanilla, _)) => (LoaderType::Vanilla, None, None),
+            Some((LoaderType::Fabric, selector)) => {
+                let loader_version = match selector {
+                    VersionSelector::Exact(exact) => exact,
+                    alias => alias.resolve(&manifest, &FabricModLoader::new().loader_versions_for_game(&game).await?)?,
+                };
+                let profile = FabricVersions::profile_json(&game, &loader_version).await?;
+                (LoaderType::Fabric, Some(loader_version), Some(profile))
+            }
+            Some((LoaderType::Quilt, selector)) => {
+                let loader_version = match selector {
+                    VersionSelector::Exact(exact) => exact,
+                    alias => alias.resolve(&manifest, &QuiltModLoader::new().loader_versions_for_game(&game).await?)?,
+                };
+                let profile = QuiltVersions::profile_json(&game, &loader_version).await?;
+                (LoaderType::Quilt, Some(loader_version), Some(profile))
+            }
+            Some((LoaderType::Forge | LoaderType::Neoforge, _)) => unreachable!("rejected before fetching the manifest"),
+        };
+        log::info!("Installing Minecraft {} ({}) into {}", game, loader.as_str(), self.root().display());
+
+        let (tx, mut rx) = mpsc::channel(64);
+        let collector = tokio::spawn(async move {
+            let mut placed = BTreeMap::new();
+            while let Some(event) = rx.recv().await {
+                if let InstallEvent::FileDownloaded { path, skipped, .. } = &event {
+                    placed.insert(path.clone(), *skipped);
+                }
+                if let Some(events) = &events {
+                    // A caller that stopped listening does not stop the install.
+                    let _ = events.send(event).await;
+                }
+            }
+            placed
+        });
+
+        let mut installer = self.installer(&version)?.with_natives_dir(self.natives_dir()).with_events(tx);
+        if let Some(profile) = &profile {
+            installer = installer.with_loader_profile(profile);
+        }
+        if let Some(url) = &options.resources_url {
+            installer = installer.with_resources_url(url);
+        }
+        if let Some(token) = &options.cancel {
+            installer = installer.with_cancellation(token.clone());
+        }
+        let result = installer.install().await;
+        drop(installer);
+        let mut placed = collector.await?;
+        result?;
+
+        let version_json = self.client_jar(&version.id)?.with_extension("json");
+        let written = write_if_changed(&version_json, &serde_json::to_string_pretty(&version)?)?;
+        placed.insert(version_json, !written);
+        if let Some(profile) = &profile {
+            // Next to the version it inherits from, as the vanilla launcher lays them out.
+            let profile_json = self.client_jar(&profile.id)?.with_extension("json");
+            let written = write_if_changed(&profile_json, &serde_json::to_string_pretty(profile)?)?;
+            placed.insert(profile_json, !written);
+        }
+
+        let root = self.root().to_path_buf();
+        let files = tokio::task::spawn_blocking(move || {
+            placed
+                .into_iter()
+                .map(|(path, skipped)| installed_file(&root, &path, skipped))
+                .collect::<Result<Vec<_>>>()
+        })
+        .await??;
+
+        let manifest = InstallManifest {
+            minecraft_version: game,
+            loader,
+            loader_version,
+            files,
+        };
+        log::info!(
+            "Installed {} files into {} ({} already in place)",
+            manifest.files.len(),
+            self.root().display(),
+            manifest.files.len() - manifest.written().count()
+        );
+        Ok(manifest)
+    }
+}
+
+/// Write `contents` to `path` unless it already holds exactly that; true if written.
+fn write_if_changed(path: &Path, contents: &str) -> Result<bool> {
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(true)
+}
+
+fn installed_file(root: &Path, path: &Path, skipped: bool) -> Result<InstalledFile> {
+    let size = std::fs::metadata(path)
+        .with_context(|| format!("Installed file {} is missing", path.display()))?
+        .len();
+    Ok(InstalledFile {
+        path: path.strip_prefix(root).unwrap_or(path).to_path_buf(),
+        sha1: file_hash_blocking(path, HashAlgo::Sha1)?,
+        size,
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn forge_is_rejected_before_fetching() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        // Nothing listens there, so any request would fail with a different error.
+        let options = InstallOptions::new().with_manifest_url("http://127.0.0.1:9/version_manifest_v2.json");
+
+        for loader in [LoaderType::Forge, LoaderType::Neoforge] {
+            let selector = VersionSelector::Exact("1.20.1".to_string());
+            let error = instance
+                .install_with(&selector, Some((loader.clone(), VersionSelector::LatestStableLoader)), None, &options)
+                .await
+                .unwrap_err();
+            assert_eq!(error.to_string(), format!("Installing {} instances is not supported", loader.as_str()));
+        }
+    }
+}
@@ -1,5 +1,17 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use chrono::Local;
+use minecraft_modloaders::download::DownloadSummary;
+use minecraft_modloaders::fabric::FabricModJson;
+use minecraft_modloaders::forge::ForgeModsToml;
+use minecraft_modloaders::install::{Installer, RepairReport, version_jar_path};
+use minecraft_modloaders::vanilla::VersionDetail;
 use serde::{Deserialize, Serialize};
 
+use crate::utils::path_util::PathUtil;
+use crate::utils::{ZipOptions, unzip, zip_dir};
+
 /// The type of mod loader for an instance.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -72,3 +84,919 @@ pub struct CreateInstanceParams {
     pub loader_version: Option<String>,
     pub java_version: Option<String>,
 }
+
+/// The suffix a mod jar gets while it is disabled, `sodium.jar` -> `sodium.jar.disabled`.
+pub const DISABLED_SUFFIX: &str = ".disabled";
+
+/// A mod jar in an instance's `mods/` directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModFile {
+    /// Full path to the jar, including the `.disabled` suffix when disabled.
+    pub path: PathBuf,
+    /// File name on disk, e.g. `sodium-0.5.8.jar.disabled`.
+    pub filename: String,
+    /// False when the jar carries the `.disabled` suffix.
+    pub enabled: bool,
+    /// Mod id from `fabric.mod.json` or `META-INF/mods.toml`, None when the jar has neither.
+    pub mod_id: Option<String>,
+    /// Mod version from the same metadata file.
+    pub version: Option<String>,
+}
+
+impl ModFile {
+    fn read(path: PathBuf) -> Option<Self> {
+        let filename = path.file_name()?.to_str()?.to_string();
+        let enabled = if filename.ends_with(".jar") {
+            true
+        } else if filename.ends_with(&format!(".jar{}", DISABLED_SUFFIX)) {
+            false
+        } else {
+            return None;
+        };
+
+        let (mod_id, version) = match FabricModJson::from_jar(&path) {
+            Ok(json) => (Some(json.id), Some(json.version)),
+            Err(_) => match ForgeModsToml::from_jar(&path).ok().and_then(|toml| toml.mods.into_iter().next()) {
+                Some(definition) => (Some(definition.mod_id), definition.version),
+                None => (None, None),
+            },
+        };
+
+        Some(Self {
+            path,
+            filename,
+            enabled,
+            mod_id,
+            version,
+        })
+    }
+}
+
+/// A problem found among the enabled mods of an instance by [`Instance::check_mods`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModConflict {
+    /// More than one jar declares the same mod id; the game refuses to start.
+    DuplicateId { mod_id: String, files: Vec<ModFile> },
+}
+
+impl std::fmt::Display for ModConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateId { mod_id, files } => {
+                let files = files
+                    .iter()
+                    .map(|file| match &file.version {
+                        Some(version) => format!("{} ({})", file.filename, version),
+                        None => file.filename.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "Mod \"{}\" is installed more than once: {}", mod_id, files.join(", "))
+            }
+        }
+    }
+}
+
+/// What [`Instance::delete`], [`Instance::clean_cache`] or [`crate::cache::prune`] removed,
+/// or would remove in a dry run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemovalReport {
+    /// The removed paths, files and directories alike; directories count with their contents.
+    pub paths: Vec<PathBuf>,
+    /// The combined size of `paths` in bytes.
+    pub total_bytes: u64,
+    /// Whether this was a dry run that left everything in place.
+    pub dry_run: bool,
+}
+
+/// Bytes an instance takes up on disk, from [`Instance::disk_usage`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    pub libraries: u64,
+    pub assets: u64,
+    pub mods: u64,
+    pub saves: u64,
+    pub logs: u64,
+    /// Everything else: natives, client jars, backups, configs and so on.
+    pub other: u64,
+}
+
+impl DiskUsage {
+    pub fn total(&self) -> u64 {
+        self.libraries + self.assets + self.mods + self.saves + self.logs + self.other
+    }
+
+    /// The counter for a top-level entry of the instance root.
+    fn category_mut(&mut self, top_level: &std::ffi::OsStr) -> &mut u64 {
+        match top_level.to_str() {
+            Some("libraries") => &mut self.libraries,
+            Some("assets") => &mut self.assets,
+            Some("mods") => &mut self.mods,
+            Some("saves") => &mut self.saves,
+            Some("logs") => &mut self.logs,
+            _ => &mut self.other,
+        }
+    }
+}
+
+/// The on-disk layout of an instance directory.
+///
+/// Every part of the launcher resolves instance paths through this type instead of
+/// joining them by hand:
+///
+/// ```text
+/// <root>/
+///   mods/
+///   saves/
+///   resourcepacks/
+///   shaderpacks/
+///   libraries/
+///   assets/
+///   natives/
+///   versions/<version>/<version>.jar
+///   backups/
+///   logs/
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instance {
+    root: PathBuf,
+}
+
+impl Instance {
+    /// Refer to an existing instance directory without touching the disk.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Create the instance directory skeleton under `root`, keeping anything already there.
+    pub fn create(root: impl Into<PathBuf>) -> Result<Self> {
+        let instance = Self::new(root);
+        for dir in [
+            instance.mods_dir(),
+            instance.saves_dir(),
+            instance.libraries_dir(),
+            instance.assets_dir(),
+            instance.natives_dir(),
+            instance.versions_dir(),
+        ] {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(instance)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn mods_dir(&self) -> PathBuf {
+        self.root.join("mods")
+    }
+
+    pub fn saves_dir(&self) -> PathBuf {
+        self.root.join("saves")
+    }
+
+    /// Not created with the instance; the game creates it when the pack screen is opened.
+    pub fn resource_packs_dir(&self) -> PathBuf {
+        self.root.join("resourcepacks")
+    }
+
+    /// Where Iris and OptiFine look for shader packs; created by them on first use.
+    pub fn shader_packs_dir(&self) -> PathBuf {
+        self.root.join("shaderpacks")
+    }
+
+    pub fn libraries_dir(&self) -> PathBuf {
+        self.root.join("libraries")
+    }
+
+    pub fn assets_dir(&self) -> PathBuf {
+        self.root.join("assets")
+    }
+
+    pub fn natives_dir(&self) -> PathBuf {
+        self.root.join("natives")
+    }
+
+    pub fn versions_dir(&self) -> PathBuf {
+        self.root.join("versions")
+    }
+
+    /// Where world backups are written; created on the first backup.
+    pub fn backups_dir(&self) -> PathBuf {
+        self.root.join("backups")
+    }
+
+    /// Where the game writes `latest.log` and its gzipped predecessors.
+    pub fn logs_dir(&self) -> PathBuf {
+        self.root.join("logs")
+    }
+
+    /// Where the client jar of `version` lives, `versions/<version>/<version>.jar`.
+    ///
+    /// An id that could escape the versions directory, such as `1.20.1/../x`, is an
+    /// error; see [`version_jar_path`].
+    pub fn client_jar(&self, version: &str) -> Result<PathBuf> {
+        version_jar_path(self.versions_dir(), version)
+    }
+
+    /// An [`Installer`] for `version` writing into this instance's libraries, assets
+    /// and client jar paths. Add events or a configured downloader before running it.
+    pub fn installer<'a>(&self, version: &'a VersionDetail) -> Result<Installer<'a>> {
+        Ok(Installer::new(version, self.libraries_dir(), self.assets_dir()).with_client_jar(self.client_jar(&version.id)?))
+    }
+
+    /// Re-check the client jar, libraries and asset objects of `version` against their
+    /// declared SHA-1s, reporting what is missing or corrupt without downloading anything.
+    ///
+    /// Use [`Instance::installer`] and [`Installer::verify`] directly for progress events.
+    pub async fn verify(&self, version: &VersionDetail) -> Result<RepairReport> {
+        self.installer(version)?.verify().await
+    }
+
+    /// Re-download only the files a [`Instance::verify`] of `version` reported.
+    pub async fn repair(&self, version: &VersionDetail, report: &RepairReport) -> Result<DownloadSummary> {
+        self.installer(version)?.repair(report).await
+    }
+
+    /// Every mod jar in `mods/`, enabled or not, sorted by file name.
+    ///
+    /// Metadata is read from `fabric.mod.json` or Forge's `META-INF/mods.toml`; jars
+    /// with neither, or that fail to open, are still listed without an id or version.
+    /// A missing `mods/` directory lists as empty.
+    pub fn list_mods(&self) -> Result<Vec<ModFile>> {
+        let entries = match std::fs::read_dir(self.mods_dir()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut mods = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            if let Some(file) = ModFile::read(entry.path()) {
+                mods.push(file);
+            }
+        }
+        mods.sort_by(|a, b| a.filename.cmp(&b.filename));
+        Ok(mods)
+    }
+
+    /// Look for enabled mods that will clash at startup, such as two versions of the same mod.
+    ///
+    /// This only reports problems; nothing in `mods/` is changed.
+    pub fn check_mods(&self) -> Result<Vec<ModConflict>> {
+        let mut by_id: Vec<(String, Vec<ModFile>)> = Vec::new();
+        for file in self.list_mods()? {
+            if !file.enabled {
+                continue;
+            }
+            let Some(mod_id) = file.mod_id.clone() else {
+                continue;
+            };
+            match by_id.iter_mut().find(|(id, _)| *id == mod_id) {
+                Some((_, files)) => files.push(file),
+                None => by_id.push((mod_id, vec![file])),
+            }
+        }
+
+        Ok(by_id
+            .into_iter()
+            .filter(|(_, files)| files.len() > 1)
+            .map(|(mod_id, files)| ModConflict::DuplicateId { mod_id, files })
+            .collect())
+    }
+
+    /// Zip `saves/<world_name>` into `backups/<world_name>-<timestamp>.zip`, returning the archive path.
+    ///
+    /// An existing archive with the same name is never overwritten; the new one gets a
+    /// ` (n)` suffix instead. File modification times are stored in the archive.
+    pub fn backup_world(&self, world_name: &str) -> Result<PathBuf> {
+        let world = self.saves_dir().join(world_file_name(world_name)?);
+        if !world.is_dir() {
+            bail!("World not found: {}", world.display());
+        }
+
+        std::fs::create_dir_all(self.backups_dir())?;
+        let mut archive = self
+            .backups_dir()
+            .join(format!("{}-{}.zip", world_name, Local::now().format("%Y-%m-%d_%H-%M-%S")));
+        // Reserve the name first; zip_dir then overwrites the empty placeholder
+        archive.unique_create()?;
+        zip_dir(&world, &archive, ZipOptions::default()).with_context(|| format!("Failed to back up world {}", world_name))?;
+        Ok(archive)
+    }
+
+    /// Unzip a backup made by [`Instance::backup_world`] into `saves/<target>`, returning the world path.
+    ///
+    /// The target world must not exist yet or be empty, so a restore never mixes two
+    /// worlds. Entries that would land outside the world directory (zip-slip) are refused.
+    pub fn restore_world(&self, archive: impl AsRef<Path>, target: &str) -> Result<PathBuf> {
+        let archive = archive.as_ref();
+        let world = self.saves_dir().join(world_file_name(target)?);
+        if world.exists() && std::fs::read_dir(&world)?.next().is_some() {
+            bail!("Cannot restore into {}: the world already exists", world.display());
+        }
+
+        unzip(archive, &world).with_context(|| format!("Failed to restore backup {}", archive.display()))?;
+        Ok(world)
+    }
+
+    /// Enable or disable the mod jar at `path` by adding or removing the `.disabled`
+    /// suffix, returning its new path. A jar already in the requested state is left as is.
+    pub fn set_mod_enabled(&self, path: impl AsRef<Path>, enabled: bool) -> Result<PathBuf> {
+        let path = path.as_ref();
+        let Some(filename) = path.file_name().and_then(|name| name.to_str()) else {
+            bail!("Invalid mod path: {}", path.display());
+        };
+        if !path.is_file() {
+            bail!("Mod file not found: {}", path.display());
+        }
+
+        let is_enabled = !filename.ends_with(DISABLED_SUFFIX);
+        if is_enabled == enabled {
+            return Ok(path.to_path_buf());
+        }
+
+        let target = if enabled {
+            path.with_file_name(&filename[..filename.len() - DISABLED_SUFFIX.len()])
+        } else {
+            path.with_file_name(format!("{}{}", filename, DISABLED_SUFFIX))
+        };
+        if target.exists() {
+            bail!("Cannot rename {} to {}: the target already exists", path.display(), target.display());
+        }
+        std::fs::rename(path, &target)?;
+        Ok(target)
+    }
+
+    /// Remove the whole instance directory, or with `dry_run` only report what would go.
+    ///
+    /// The report lists every top-level entry followed by the root itself. Symlinks are
+    /// removed without touching what they point to, so nothing outside the root is deleted.
+    pub fn delete(&self, dry_run: bool) -> Result<RemovalReport> {
+        let root = self.canonical_root()?;
+        let mut paths = dir_entries(&root)?;
+        let total_bytes = total_size(&paths)?;
+        paths.push(root.clone());
+
+        if !dry_run {
+            std::fs::remove_dir_all(&root).with_context(|| format!("Failed to delete {}", root.display()))?;
+        }
+        Ok(RemovalReport { paths, total_bytes, dry_run })
+    }
+
+    /// Empty `libraries/`, `assets/` and `natives/`, which are re-downloaded or
+    /// re-extracted on the next launch. Mods, worlds and settings are kept.
+    ///
+    /// A cache directory that resolves outside the instance root, such as a symlink
+    /// to a shared libraries folder, is refused rather than emptied.
+    pub fn clean_cache(&self) -> Result<RemovalReport> {
+        let root = self.canonical_root()?;
+        let mut paths = Vec::new();
+        for dir in [self.libraries_dir(), self.assets_dir(), self.natives_dir()] {
+            let dir = match dir.canonicalize() {
+                Ok(dir) => dir,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if dir == root || !dir.starts_with(&root) {
+                bail!("Refusing to clean {}: it is outside the instance at {}", dir.display(), root.display());
+            }
+            paths.extend(dir_entries(&dir)?);
+        }
+
+        let total_bytes = total_size(&paths)?;
+        for path in &paths {
+            remove_path(path)?;
+        }
+        Ok(RemovalReport {
+            paths,
+            total_bytes,
+            dry_run: false,
+        })
+    }
+
+    /// Bytes used below the instance root, split by the directory they live in.
+    ///
+    /// The tree is walked once without following symlinks, so a libraries folder linked
+    /// in from a shared cache counts as nothing here. Files hard-linked more than once
+    /// inside the instance are counted once. A missing root is an error.
+    pub fn disk_usage(&self) -> Result<DiskUsage> {
+        let mut usage = DiskUsage::default();
+        let mut seen = std::collections::HashSet::new();
+        let mut pending = Vec::new();
+        for entry in std::fs::read_dir(&self.root).with_context(|| format!("Instance directory not found: {}", self.root.display()))? {
+            let entry = entry?;
+            pending.push((entry.file_name(), entry.path()));
+        }
+
+        while let Some((top_level, path)) = pending.pop() {
+            let metadata = std::fs::symlink_metadata(&path)?;
+            if metadata.is_dir() {
+                for entry in std::fs::read_dir(&path)? {
+                    pending.push((top_level.clone(), entry?.path()));
+                }
+            } else if metadata.is_file() && file_id(&metadata).is_none_or(|id| seen.insert(id)) {
+                *usage.category_mut(&top_level) += metadata.len();
+            }
+        }
+        Ok(usage)
+    }
+
+    /// The resolved root, refusing paths such as `/` or a home folder that are not an
+    /// instance directory: it must have the `mods/` and `versions/` that
+    /// [`Instance::create`] makes.
+    fn canonical_root(&self) -> Result<PathBuf> {
+        let root = self
+            .root
+            .canonicalize()
+            .with_context(|| format!("Instance directory not found: {}", self.root.display()))?;
+        let is_instance = root.parent().is_some() && root.join("mods").is_dir() && root.join("versions").is_dir();
+        if !root.is_dir() || !is_instance {
+            bail!("Refusing to remove {}: not an instance directory", root.display());
+        }
+        Ok(root)
+    }
+}
+
+/// The entries directly inside `dir`, sorted by name.
+fn dir_entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+    Ok(paths)
+}
+
+fn total_size(paths: &[PathBuf]) -> Result<u64> {
+    paths.iter().try_fold(0, |total, path| Ok(total + path_size(path)?))
+}
+
+/// Size of `path` and everything below it, without following symlinks.
+fn path_size(path: &Path) -> Result<u64> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    total_size(&dir_entries(path)?)
+}
+
+/// Identifies a file with several hard links so it is only counted once.
+#[cfg(unix)]
+pub(crate) fn file_id(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn file_id(_metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Remove a file, symlink or directory tree; symlinks are never followed.
+fn remove_path(path: &Path) -> Result<()> {
+    if std::fs::symlink_metadata(path)?.is_dir() {
+        std::fs::remove_dir_all(path)?;
+    } else {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// `name` as a single path component, so it cannot point outside `saves/`.
+fn world_file_name(name: &str) -> Result<&str> {
+    if name.is_empty() || Path::new(name).file_name().and_then(|n| n.to_str()) != Some(name) {
+        bail!("Invalid world name: {:?}", name);
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use minecraft_modloaders::download::sha1_hex;
+    use std::fs::File;
+    use std::io::Write;
+    use std::time::SystemTime;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    fn write_jar(path: &Path, entry: &str, contents: &str) {
+        let mut zip = ZipWriter::new(std::fs::File::create(path).unwrap());
+        zip.start_file(entry, SimpleFileOptions::default()).unwrap();
+        zip.write_all(contents.as_bytes()).unwrap();
+        zip.finish().unwrap();
+    }
+
+    fn fixture_mods() -> (tempfile::TempDir, Instance) {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        write_jar(
+            &instance.mods_dir().join("sodium-0.5.8.jar"),
+            "fabric.mod.json",
+            r#"{"schemaVersion": 1, "id": "sodium", "version": "0.5.8"}"#,
+        );
+        write_jar(
+            &instance.mods_dir().join("jei-15.2.0.jar.disabled"),
+            "META-INF/mods.toml",
+            r#"
+modLoader = "javafml"
+loaderVersion = "[47,)"
+license = "MIT"
+
+[[mods]]
+modId = "jei"
+version = "15.2.0"
+displayName = "Just Enough Items"
+"#,
+        );
+        write_jar(&instance.mods_dir().join("library.jar"), "readme.txt", "no metadata here");
+        std::fs::write(instance.mods_dir().join("notes.txt"), b"not a mod").unwrap();
+        (dir, instance)
+    }
+
+    #[tokio::test]
+    async fn verify_reports_truncated_library() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        let jar = b"client jar";
+        let library = b"library";
+        let version: VersionDetail = serde_json::from_value(serde_json::json!({
+            "id": "1.20.1",
+            "type": "release",
+            "mainClass": "net.minecraft.client.main.Main",
+            "releaseTime": "2023-06-12T13:25:51+00:00",
+            "downloads": {"client": {"sha1": sha1_hex(jar), "size": jar.len(), "url": "http://127.0.0.1:9/client.jar"}},
+            "libraries": [{
+                "name": "com.example:lib:1.0",
+                "downloads": {"artifact": {
+                    "path": "com/example/lib/1.0/lib-1.0.jar",
+                    "sha1": sha1_hex(library),
+                    "size": library.len(),
+                    "url": "http://127.0.0.1:9/lib.jar"
+                }}
+            }]
+        }))
+        .unwrap();
+
+        let client_jar = instance.client_jar("1.20.1").unwrap();
+        std::fs::create_dir_all(client_jar.parent().unwrap()).unwrap();
+        std::fs::write(&client_jar, jar).unwrap();
+        let library_path = instance.libraries_dir().join("com/example/lib/1.0/lib-1.0.jar");
+        std::fs::create_dir_all(library_path.parent().unwrap()).unwrap();
+        std::fs::write(&library_path, &library[..3]).unwrap();
+
+        let report = instance.verify(&version).await.unwrap();
+        assert_eq!(report.checked, 2);
+        assert!(report.missing.is_empty());
+        let corrupt: Vec<&Path> = report.corrupt.iter().map(|job| job.dest.as_path()).collect();
+        assert_eq!(corrupt, vec![library_path.as_path()]);
+    }
+
+    #[test]
+    fn create_builds_the_skeleton() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path().join("My Pack")).unwrap();
+
+        assert_eq!(instance.root(), dir.path().join("My Pack"));
+        assert_eq!(instance.mods_dir(), dir.path().join("My Pack").join("mods"));
+        assert_eq!(instance.libraries_dir(), dir.path().join("My Pack").join("libraries"));
+        assert_eq!(instance.assets_dir(), dir.path().join("My Pack").join("assets"));
+        assert_eq!(instance.natives_dir(), dir.path().join("My Pack").join("natives"));
+        for sub in ["mods", "saves", "libraries", "assets", "natives", "versions"] {
+            assert!(dir.path().join("My Pack").join(sub).is_dir(), "{sub} was not created");
+        }
+
+        // Creating it again keeps existing content
+        std::fs::write(instance.mods_dir().join("sodium.jar"), b"jar").unwrap();
+        let again = Instance::create(instance.root()).unwrap();
+        assert!(again.mods_dir().join("sodium.jar").exists());
+    }
+
+    #[test]
+    fn client_jar_path() {
+        let instance = Instance::new("/instances/pack");
+        assert_eq!(
+            instance.client_jar("1.21.4").unwrap(),
+            PathBuf::from("/instances/pack/versions/1.21.4/1.21.4.jar")
+        );
+    }
+
+    #[test]
+    fn client_jar_rejects_unsafe_version_id() {
+        let instance = Instance::new("/instances/pack");
+        assert!(instance.client_jar("1.20.1/../fa:bric?").is_err());
+        assert!(instance.client_jar("..").is_err());
+        assert!(instance.client_jar("").is_err());
+        assert!(instance.client_jar("1.20.1-forge-47.2.0").is_ok());
+    }
+
+    #[test]
+    fn list_mods_reads_metadata() {
+        let (_dir, instance) = fixture_mods();
+        let mods = instance.list_mods().unwrap();
+
+        assert_eq!(mods.len(), 3);
+        assert_eq!(mods[0].filename, "jei-15.2.0.jar.disabled");
+        assert!(!mods[0].enabled);
+        assert_eq!(mods[0].mod_id.as_deref(), Some("jei"));
+        assert_eq!(mods[0].version.as_deref(), Some("15.2.0"));
+
+        assert_eq!(mods[1].filename, "library.jar");
+        assert!(mods[1].enabled);
+        assert_eq!(mods[1].mod_id, None);
+
+        assert_eq!(mods[2].filename, "sodium-0.5.8.jar");
+        assert!(mods[2].enabled);
+        assert_eq!(mods[2].mod_id.as_deref(), Some("sodium"));
+        assert_eq!(mods[2].version.as_deref(), Some("0.5.8"));
+        assert_eq!(mods[2].path, instance.mods_dir().join("sodium-0.5.8.jar"));
+    }
+
+    #[test]
+    fn list_mods_without_mods_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(Instance::new(dir.path()).list_mods().unwrap().is_empty());
+    }
+
+    #[test]
+    fn set_mod_enabled_renames() {
+        let (_dir, instance) = fixture_mods();
+        let sodium = instance.mods_dir().join("sodium-0.5.8.jar");
+
+        let disabled = instance.set_mod_enabled(&sodium, false).unwrap();
+        assert_eq!(disabled, instance.mods_dir().join("sodium-0.5.8.jar.disabled"));
+        assert!(disabled.exists() && !sodium.exists());
+
+        // Already disabled: nothing to do
+        assert_eq!(instance.set_mod_enabled(&disabled, false).unwrap(), disabled);
+
+        let enabled = instance.set_mod_enabled(&disabled, true).unwrap();
+        assert_eq!(enabled, sodium);
+        assert!(sodium.exists() && !disabled.exists());
+
+        let jei = instance
+            .set_mod_enabled(instance.mods_dir().join("jei-15.2.0.jar.disabled"), true)
+            .unwrap();
+        let listed = instance.list_mods().unwrap();
+        assert!(listed.iter().all(|m| m.enabled));
+        assert!(listed.iter().any(|m| m.path == jei && m.mod_id.as_deref() == Some("jei")));
+    }
+
+    #[test]
+    fn set_mod_enabled_refuses_to_overwrite() {
+        let (_dir, instance) = fixture_mods();
+        std::fs::write(instance.mods_dir().join("sodium-0.5.8.jar.disabled"), b"old copy").unwrap();
+        assert!(instance.set_mod_enabled(instance.mods_dir().join("sodium-0.5.8.jar"), false).is_err());
+        assert!(instance.set_mod_enabled(instance.mods_dir().join("missing.jar"), false).is_err());
+    }
+
+    #[test]
+    fn check_mods_reports_duplicate_ids() {
+        let (_dir, instance) = fixture_mods();
+        assert!(instance.check_mods().unwrap().is_empty());
+
+        write_jar(
+            &instance.mods_dir().join("sodium-0.5.3.jar"),
+            "fabric.mod.json",
+            r#"{"schemaVersion": 1, "id": "sodium", "version": "0.5.3"}"#,
+        );
+        // A disabled copy is not loaded, so it does not conflict
+        write_jar(
+            &instance.mods_dir().join("jei-15.1.0.jar"),
+            "META-INF/mods.toml",
+            "modLoader = \"javafml\"\nloaderVersion = \"[47,)\"\nlicense = \"MIT\"\n[[mods]]\nmodId = \"jei\"\nversion = \"15.1.0\"\n",
+        );
+
+        let conflicts = instance.check_mods().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        let ModConflict::DuplicateId { mod_id, files } = &conflicts[0];
+        assert_eq!(mod_id, "sodium");
+        let names: Vec<_> = files.iter().map(|file| file.filename.as_str()).collect();
+        assert_eq!(names, ["sodium-0.5.3.jar", "sodium-0.5.8.jar"]);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "Mod \"sodium\" is installed more than once: sodium-0.5.3.jar (0.5.3), sodium-0.5.8.jar (0.5.8)"
+        );
+
+        // Enabling the second JEI makes it a conflict too; the mods themselves are untouched
+        instance
+            .set_mod_enabled(instance.mods_dir().join("jei-15.2.0.jar.disabled"), true)
+            .unwrap();
+        assert_eq!(instance.check_mods().unwrap().len(), 2);
+        assert_eq!(instance.list_mods().unwrap().len(), 5);
+    }
+
+    fn fixture_world(instance: &Instance) -> PathBuf {
+        let world = instance.saves_dir().join("New World");
+        std::fs::create_dir_all(world.join("region")).unwrap();
+        std::fs::create_dir_all(world.join("datapacks")).unwrap();
+        std::fs::write(world.join("level.dat"), b"level data").unwrap();
+        std::fs::write(world.join("region").join("r.0.0.mca"), vec![7u8; 4096]).unwrap();
+        world
+    }
+
+    fn fixture_instance() -> (tempfile::TempDir, Instance) {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path().join("pack")).unwrap();
+        std::fs::write(instance.mods_dir().join("sodium.jar"), [0u8; 100]).unwrap();
+        std::fs::create_dir_all(instance.saves_dir().join("World")).unwrap();
+        std::fs::write(instance.saves_dir().join("World").join("level.dat"), [0u8; 50]).unwrap();
+        let asm = instance.libraries_dir().join("org/ow2/asm/asm/9.3");
+        std::fs::create_dir_all(&asm).unwrap();
+        std::fs::write(asm.join("asm-9.3.jar"), [0u8; 1000]).unwrap();
+        std::fs::create_dir_all(instance.assets_dir().join("indexes")).unwrap();
+        std::fs::write(instance.assets_dir().join("indexes").join("12.json"), [0u8; 10]).unwrap();
+        (dir, instance)
+    }
+
+    #[test]
+    fn delete_dry_run_reports_without_removing() {
+        let (_dir, instance) = fixture_instance();
+        let root = instance.root().canonicalize().unwrap();
+
+        let report = instance.delete(true).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.total_bytes, 1160);
+        assert_eq!(report.paths.last(), Some(&root));
+        assert!(report.paths.contains(&root.join("mods")));
+        assert!(report.paths.contains(&root.join("saves")));
+        assert!(instance.mods_dir().join("sodium.jar").exists());
+        assert!(instance.saves_dir().join("World").join("level.dat").exists());
+    }
+
+    #[test]
+    fn delete_removes_the_instance() {
+        let (dir, instance) = fixture_instance();
+        std::fs::write(dir.path().join("neighbour.txt"), b"keep me").unwrap();
+
+        let report = instance.delete(false).unwrap();
+        assert!(!report.dry_run);
+        assert_eq!(report.total_bytes, 1160);
+        assert!(!instance.root().exists());
+        assert!(dir.path().join("neighbour.txt").exists());
+        assert!(instance.delete(false).is_err());
+    }
+
+    #[test]
+    fn delete_refuses_a_plain_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let documents = dir.path().join("Documents");
+        std::fs::create_dir_all(documents.join("mods")).unwrap();
+        std::fs::write(documents.join("taxes.pdf"), b"keep me").unwrap();
+
+        let instance = Instance::new(&documents);
+        let error = instance.delete(false).unwrap_err();
+        assert!(error.to_string().contains("not an instance directory"), "{:#}", error);
+        assert!(instance.delete(true).is_err());
+        assert!(instance.clean_cache().is_err());
+        assert!(documents.join("taxes.pdf").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn delete_does_not_follow_symlinks() {
+        let (dir, instance) = fixture_instance();
+        let outside = dir.path().join("shared");
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("big.bin"), [0u8; 4096]).unwrap();
+        std::os::unix::fs::symlink(&outside, instance.root().join("shared-link")).unwrap();
+
+        let report = instance.delete(false).unwrap();
+        assert!(report.total_bytes < 4096);
+        assert!(!instance.root().exists());
+        assert!(outside.join("big.bin").exists());
+    }
+
+    #[test]
+    fn clean_cache_keeps_mods_and_worlds() {
+        let (_dir, instance) = fixture_instance();
+
+        let report = instance.clean_cache().unwrap();
+        assert_eq!(report.total_bytes, 1010);
+        assert!(instance.libraries_dir().is_dir());
+        assert_eq!(std::fs::read_dir(instance.libraries_dir()).unwrap().count(), 0);
+        assert_eq!(std::fs::read_dir(instance.assets_dir()).unwrap().count(), 0);
+        assert!(instance.mods_dir().join("sodium.jar").exists());
+        assert!(instance.saves_dir().join("World").join("level.dat").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn clean_cache_refuses_libraries_outside_the_root() {
+        let (dir, instance) = fixture_instance();
+        let shared = dir.path().join("shared-libraries");
+        std::fs::create_dir_all(&shared).unwrap();
+        std::fs::write(shared.join("lib.jar"), b"jar").unwrap();
+        std::fs::remove_dir_all(instance.libraries_dir()).unwrap();
+        std::os::unix::fs::symlink(&shared, instance.libraries_dir()).unwrap();
+
+        assert!(instance.clean_cache().is_err());
+        assert!(shared.join("lib.jar").exists());
+    }
+
+    #[test]
+    fn disk_usage_by_category() {
+        let (_dir, instance) = fixture_instance();
+        std::fs::create_dir_all(instance.logs_dir()).unwrap();
+        std::fs::write(instance.logs_dir().join("latest.log"), [0u8; 300]).unwrap();
+        std::fs::write(instance.root().join("options.txt"), [0u8; 20]).unwrap();
+        std::fs::write(instance.natives_dir().join("liblwjgl.so"), [0u8; 5]).unwrap();
+
+        let usage = instance.disk_usage().unwrap();
+        assert_eq!(
+            usage,
+            DiskUsage {
+                libraries: 1000,
+                assets: 10,
+                mods: 100,
+                saves: 50,
+                logs: 300,
+                other: 25,
+            }
+        );
+        assert_eq!(usage.total(), 1485);
+        assert!(Instance::new(instance.root().join("missing")).disk_usage().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn disk_usage_counts_links_once() {
+        let (dir, instance) = fixture_instance();
+        let shared = dir.path().join("shared");
+        std::fs::create_dir_all(&shared).unwrap();
+        std::fs::write(shared.join("big.bin"), [0u8; 4096]).unwrap();
+        std::os::unix::fs::symlink(&shared, instance.mods_dir().join("shared-link")).unwrap();
+        std::fs::hard_link(instance.mods_dir().join("sodium.jar"), instance.mods_dir().join("sodium-copy.jar")).unwrap();
+
+        let usage = instance.disk_usage().unwrap();
+        assert_eq!(usage.mods, 100);
+        assert_eq!(usage.total(), 1160);
+    }
+
+    #[test]
+    fn backup_and_restore_world() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        let world = fixture_world(&instance);
+        let modified = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        File::options()
+            .write(true)
+            .open(world.join("level.dat"))
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        let archive = instance.backup_world("New World").unwrap();
+        assert_eq!(archive.parent().unwrap(), instance.backups_dir());
+        let name = archive.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("New World-") && name.ends_with(".zip"), "{name}");
+
+        let restored = instance.restore_world(&archive, "New World (restored)").unwrap();
+        assert_eq!(restored, instance.saves_dir().join("New World (restored)"));
+        assert_eq!(std::fs::read(restored.join("level.dat")).unwrap(), b"level data");
+        assert_eq!(std::fs::read(restored.join("region").join("r.0.0.mca")).unwrap(), vec![7u8; 4096]);
+        assert!(restored.join("datapacks").is_dir());
+        assert_eq!(std::fs::metadata(restored.join("level.dat")).unwrap().modified().unwrap(), modified);
+
+        // Restoring over a world that has content is refused
+        assert!(instance.restore_world(&archive, "New World").is_err());
+    }
+
+    #[test]
+    fn backup_world_does_not_clobber() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        fixture_world(&instance);
+
+        let first = instance.backup_world("New World").unwrap();
+        let second = instance.backup_world("New World").unwrap();
+        let third = instance.backup_world("New World").unwrap();
+        assert!(first != second && second != third && first != third);
+        assert!(first.exists() && second.exists() && third.exists());
+
+        assert!(instance.backup_world("Missing World").is_err());
+        assert!(instance.backup_world("../New World").is_err());
+    }
+
+    #[test]
+    fn restore_world_refuses_zip_slip() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        let archive = dir.path().join("evil.zip");
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file("level.dat", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"level data").unwrap();
+        zip.start_file("../../evil.txt", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"evil").unwrap();
+        zip.finish().unwrap();
+
+        assert!(instance.restore_world(&archive, "Evil World").is_err());
+        assert!(!instance.saves_dir().join("Evil World").exists());
+        assert!(!dir.path().join("evil.txt").exists());
+        assert!(!instance.root().join("evil.txt").exists());
+    }
+}
@@ -3,8 +3,8 @@ use std::path::{Path, PathBuf};
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::{Row, SqlitePool};
 
-use crate::instance::{CreateInstanceParams, InstanceConfig, LoaderType};
-use crate::utils::path_util::PathUtil;
+use crate::instance::{CreateInstanceParams, Instance, InstanceConfig, LoaderType};
+use crate::utils::path_util::{DEFAULT_MAX_UNIQUE_ATTEMPTS, PathUtil};
 
 /// Manages Minecraft instances, accounts, and recent imports backed by SQLite.
 ///
@@ -144,11 +144,19 @@ impl InstanceManager {
     // -----------------------------------------------------------------------
 
     /// Create a new instance. Returns the full config with generated ID and path.
+    ///
+    /// The directory gets an [`instance.json`](crate::metadata::METADATA_FILE) with a
+    /// fresh UUID, from which [`Instance::id`] is derived.
     pub async fn create(&self, params: CreateInstanceParams) -> anyhow::Result<InstanceConfig> {
         let mut dir_path = self.instances_dir.join(&params.name);
         dir_path.clean()?;
-        dir_path.unique();
+        dir_path.unique_capped(DEFAULT_MAX_UNIQUE_ATTEMPTS)?;
         std::fs::create_dir_all(&dir_path)?;
+        Instance::new(&dir_path).init_metadata(
+            &params.minecraft_version,
+            params.loader.clone(),
+            params.loader_version.clone(),
+        )?;
 
         let created_at = chrono::Utc::now().to_rfc3339();
         let loader_str = params.loader.as_str();
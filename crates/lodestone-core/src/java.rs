@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use minecraft_modloaders::java::{self, component_for_major, required_java_major, runtime_component};
+use minecraft_modloaders::vanilla::VersionDetail;
+use serde::{Deserialize, Serialize};
+
+use crate::profile::Profile;
+
+/// Which Java runtime a profile launches with, see [`Profile::java_override`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JavaSelection {
+    /// The Mojang runtime the game version asks for.
+    #[default]
+    Auto,
+    /// The Mojang runtime that provides this Java major, e.g. 17.
+    BundledMajor(u8),
+    /// A `java` executable installed on the system.
+    Path(PathBuf),
+}
+
+/// The runtime a [`JavaSelection`] settled on for one game version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedJava {
+    /// Path to the `java` executable. Bundled runtimes may not be installed yet.
+    pub path: PathBuf,
+    /// The runtime's Java major.
+    pub major: u8,
+    /// The major the game version requires.
+    pub required_major: u8,
+    /// Set when `major` is not `required_major`. The launch may still work, so this is
+    /// for the user to see rather than a reason to refuse.
+    pub warning: Option<String>,
+}
+
+impl JavaSelection {
+    /// Work out the runtime to launch `version` with. Bundled runtimes are looked up in
+    /// `runtimes_dir`, where `minecraft_modloaders::java::install_runtime` puts them.
+    ///
+    /// A system install is run once to read its major version, so a path that is not a
+    /// working `java` is an error. A major that differs from what the version needs is
+    /// only a [`ResolvedJava::warning`].
+    pub async fn resolve(&self, version: &VersionDetail, runtimes_dir: &Path) -> Result<ResolvedJava> {
+        let required_major = required_java_major(version);
+        let (path, major) = match self {
+            Self::Auto => (java::java_executable(runtimes_dir.join(runtime_component(version))), required_major),
+            Self::BundledMajor(major) => {
+                let component = component_for_major(*major).ok_or_else(|| anyhow!("Mojang publishes no Java {} runtime", major))?;
+                (java::java_executable(runtimes_dir.join(component)), *major)
+            }
+            Self::Path(path) => {
+                let install = java::probe(path)
+                    .await
+                    .with_context(|| format!("Java at {} is not usable", path.display()))?;
+                (install.path, install.major)
+            }
+        };
+
+        let warning = major_mismatch(major, required_major, &version.id);
+        if let Some(warning) = &warning {
+            log::warn!("{}", warning);
+        }
+        Ok(ResolvedJava {
+            path,
+            major,
+            required_major,
+            warning,
+        })
+    }
+}
+
+fn major_mismatch(major: u8, required_major: u8, version_id: &str) -> Option<String> {
+    (major != required_major).then(|| {
+        format!(
+            "Minecraft {} requires Java {}, but Java {} is selected",
+            version_id, required_major, major
+        )
+    })
+}
+
+impl Profile {
+    /// The runtime this profile launches `version` with: its [`Profile::java_override`],
+    /// or [`JavaSelection::Auto`] when it has none.
+    pub async fn resolve_java(&self, version: &VersionDetail, runtimes_dir: &Path) -> Result<ResolvedJava> {
+        self.java_override.clone().unwrap_or_default().resolve(version, runtimes_dir).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instance::LoaderType;
+
+    fn version(major: u8) -> VersionDetail {
+        let json = format!(
+            r#"{{
+                "id": "1.20.6",
+                "type": "release",
+                "mainClass": "net.minecraft.client.main.Main",
+                "releaseTime": "2024-04-29T12:00:00+00:00",
+                "javaVersion": {{"component": "java-runtime-delta", "majorVersion": {}}}
+            }}"#,
+            major
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[tokio::test]
+    async fn auto_and_bundled_use_mojang_runtimes() {
+        let runtimes = Path::new("/data/runtimes");
+        let profile = Profile::new("Modern", "1.20.6", LoaderType::Vanilla);
+
+        let auto = profile.resolve_java(&version(21), runtimes).await.unwrap();
+        assert_eq!(auto.path, java::java_executable(runtimes.join("java-runtime-delta")));
+        assert_eq!((auto.major, auto.required_major, auto.warning), (21, 21, None));
+
+        let pinned = JavaSelection::BundledMajor(21).resolve(&version(21), runtimes).await.unwrap();
+        assert_eq!(pinned.path, auto.path);
+        assert_eq!(pinned.warning, None);
+
+        assert!(JavaSelection::BundledMajor(11).resolve(&version(21), runtimes).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn mismatched_major_warns_but_resolves() {
+        let runtimes = Path::new("/data/runtimes");
+        let profile = Profile::new("Modern", "1.20.6", LoaderType::Vanilla).with_java_override(JavaSelection::BundledMajor(17));
+
+        let resolved = profile.resolve_java(&version(21), runtimes).await.unwrap();
+        assert_eq!(resolved.path, java::java_executable(runtimes.join("java-runtime-gamma")));
+        assert_eq!((resolved.major, resolved.required_major), (17, 21));
+        assert_eq!(
+            resolved.warning.as_deref(),
+            Some("Minecraft 1.20.6 requires Java 21, but Java 17 is selected")
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn system_path_is_probed() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let fake_java = dir.path().join("java");
+        std::fs::write(
+            &fake_java,
+            "#!/bin/sh\necho '    java.version = 17.0.8' >&2\necho 'openjdk version \"17.0.8\" 2023-07-18' >&2\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&fake_java, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let selection = JavaSelection::Path(fake_java.clone());
+        let resolved = selection.resolve(&version(17), Path::new("/data/runtimes")).await.unwrap();
+        assert_eq!((resolved.path, resolved.major, resolved.warning), (fake_java.clone(), 17, None));
+
+        let resolved = selection.resolve(&version(21), Path::new("/data/runtimes")).await.unwrap();
+        assert!(resolved.warning.is_some());
+
+        assert!(
+            JavaSelection::Path(dir.path().join("missing"))
+                .resolve(&version(17), Path::new("/data/runtimes"))
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn selection_round_trips_through_profile_json() {
+        let profile =
+            Profile::new("Pinned", "1.20.1", LoaderType::Fabric).with_java_override(JavaSelection::Path("/usr/lib/jvm/java-17/bin/java".into()));
+        let json = serde_json::to_string(&profile).unwrap();
+        assert!(json.contains(r#""java_override":{"path":"/usr/lib/jvm/java-17/bin/java"}"#), "{json}");
+        assert_eq!(serde_json::from_str::<Profile>(&json).unwrap(), profile);
+
+        let legacy: Profile = serde_json::from_str(r#"{"name": "Old", "minecraft_version": "1.8.9", "loader": "vanilla", "loader_version": null, "game_dir": null, "created_at": "2024-01-01T00:00:00Z", "last_used": null}"#).unwrap();
+        assert_eq!(legacy.java_override, None);
+        assert_eq!(serde_json::from_str::<JavaSelection>(r#""auto""#).unwrap(), JavaSelection::Auto);
+        assert_eq!(
+            serde_json::from_str::<JavaSelection>(r#"{"bundled_major": 17}"#).unwrap(),
+            JavaSelection::BundledMajor(17)
+        );
+    }
+}
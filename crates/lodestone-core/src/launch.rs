@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use minecraft_modloaders::launch::LaunchArguments;
+use minecraft_modloaders::vanilla::{RuleContext, VersionDetail};
+
+use crate::instance::Instance;
+use crate::paths::join_cleaned;
+
+/// Per-launch choices that are not part of the instance itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchOptions {
+    /// Where the game runs, passed as `--gameDir`, instead of the instance root.
+    ///
+    /// The game keeps `saves/`, `config/`, `options.txt` and, for most loaders, reads
+    /// `mods/` relative to this directory, while libraries, assets and natives stay in
+    /// the instance. A relative path is taken below the instance root.
+    pub game_dir: Option<PathBuf>,
+}
+
+impl LaunchOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_game_dir(mut self, game_dir: impl Into<PathBuf>) -> Self {
+        self.game_dir = Some(game_dir.into());
+        self
+    }
+}
+
+impl Instance {
+    /// The directory the game runs in under `options`, creating it if it is missing.
+    ///
+    /// This is [`LaunchOptions::game_dir`] when set and the instance root otherwise. A
+    /// relative override is joined to the root one component at a time, each cleaned
+    /// with [`PathUtil::clean`](crate::utils::path_util::PathUtil::clean), so it cannot
+    /// climb out of the instance with `..`. An absolute override is used as given.
+    pub fn game_dir(&self, options: &LaunchOptions) -> Result<PathBuf> {
+        let dir = match &options.game_dir {
+            None => self.root().to_path_buf(),
+            Some(dir) if dir.is_absolute() => dir.clone(),
+            Some(dir) => join_cleaned(self.root(), &dir.to_string_lossy())?,
+        };
+        std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create game directory {}", dir.display()))?;
+        Ok(dir)
+    }
+
+    /// [`LaunchArguments`] for `version` laid out on this instance: the version's
+    /// libraries and client jar on the classpath, the instance's assets and natives, and
+    /// `${game_directory}` set to [`Instance::game_dir`].
+    ///
+    /// Natives are always taken from the instance, since they are extracted with its
+    /// libraries. Authentication, JVM settings and a loader profile are left for the
+    /// caller to add.
+    pub fn launch_arguments<'a>(&self, version: &'a VersionDetail, options: &LaunchOptions) -> Result<LaunchArguments<'a>> {
+        let game_dir = self.game_dir(options)?;
+        let mut classpath = version.libraries.classpath(self.libraries_dir(), &RuleContext::default());
+        classpath.push(self.client_jar(&version.id)?);
+
+        Ok(LaunchArguments::new(version)
+            .with_game_dir(game_dir)
+            .with_assets_dir(self.assets_dir())
+            .with_libraries_dir(self.libraries_dir())
+            .with_natives_dir(self.natives_dir())
+            .with_classpath(classpath))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn version() -> VersionDetail {
+        serde_json::from_str(
+            r#"{
+                "id": "1.20.4",
+                "type": "release",
+                "mainClass": "net.minecraft.client.main.Main",
+                "releaseTime": "2023-12-07T12:56:20+00:00",
+                "libraries": [],
+                "arguments": {
+                    "game": ["--gameDir", "${game_directory}", "--assetsDir", "${assets_root}"],
+                    "jvm": ["-Djava.library.path=${natives_directory}", "-cp", "${classpath}"]
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn arg_after<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+        args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(String::as_str)
+    }
+
+    #[test]
+    fn game_dir_defaults_to_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        let version = version();
+
+        let command = instance.launch_arguments(&version, &LaunchOptions::new()).unwrap().build();
+        assert_eq!(arg_after(&command.game_args, "--gameDir"), Some(dir.path().to_str().unwrap()));
+        let natives = format!("-Djava.library.path={}", instance.natives_dir().display());
+        assert!(command.jvm_args.contains(&natives), "{:?}", command.jvm_args);
+    }
+
+    #[test]
+    fn game_dir_override_is_substituted_and_created() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path().join("shared")).unwrap();
+        let version = version();
+
+        let saves = dir.path().join("alice");
+        let options = LaunchOptions::new().with_game_dir(&saves);
+        let command = instance.launch_arguments(&version, &options).unwrap().build();
+        assert_eq!(arg_after(&command.game_args, "--gameDir"), Some(saves.to_str().unwrap()));
+        assert_eq!(
+            arg_after(&command.game_args, "--assetsDir"),
+            Some(instance.assets_dir().to_str().unwrap())
+        );
+        let natives = format!("-Djava.library.path={}", instance.natives_dir().display());
+        assert!(command.jvm_args.contains(&natives), "{:?}", command.jvm_args);
+        assert!(saves.is_dir());
+
+        let relative = LaunchOptions::new().with_game_dir("profiles/Who?");
+        assert_eq!(instance.game_dir(&relative).unwrap(), instance.root().join("profiles").join("Who"));
+        assert!(instance.root().join("profiles").join("Who").is_dir());
+        assert!(instance.game_dir(&LaunchOptions::new().with_game_dir("../escape")).is_err());
+    }
+}
@@ -1,3 +1,18 @@
+pub mod cache;
+pub mod export;
+pub mod import;
+pub mod install;
 pub mod instance;
 pub mod instance_manager;
+pub mod java;
+pub mod launch;
+pub mod lock;
+pub mod metadata;
+pub mod mod_install;
+pub mod packs;
+pub mod paths;
+pub mod profile;
+pub mod selector;
 pub mod utils;
+pub mod watch;
+pub mod world;
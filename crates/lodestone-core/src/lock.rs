@@ -0,0 +1,140 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::instance::Instance;
+
+/// The lock file [`Instance::lock`] creates in the instance root.
+pub const LOCK_FILE: &str = "instance.lock";
+
+/// Proof that this process has an instance to itself, from [`Instance::lock`].
+///
+/// The lock is released when this is dropped, or by the operating system if the
+/// process dies first. The lock file itself stays behind for the next launch.
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+    _file: File,
+}
+
+impl InstanceLock {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Instance {
+    /// Claim the instance so it is not launched twice at once, by taking an exclusive
+    /// operating system lock on [`LOCK_FILE`] in its root, and write this process's id
+    /// into it.
+    ///
+    /// Fails while another lock on the instance is held, one taken earlier by this
+    /// process included. The lock is advisory: it only keeps out callers that also
+    /// take it.
+    pub fn lock(&self) -> Result<InstanceLock> {
+        let path = self.root().join(LOCK_FILE);
+        let mut file = open_lock_file(&path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => match holder(&path) {
+                Some(pid) => bail!("Instance {} is already in use by process {}", self.root().display(), pid),
+                None => bail!("Instance {} is already in use", self.root().display()),
+            },
+            Err(TryLockError::Error(e)) => return Err(e).with_context(|| format!("Failed to lock {}", path.display())),
+        }
+        // Only for people looking at the file; the lock itself is what counts.
+        file.set_len(0)
+            .and_then(|()| file.write_all(std::process::id().to_string().as_bytes()))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(InstanceLock { path, _file: file })
+    }
+
+    /// Whether a running process, this one included, holds [`Instance::lock`] on the
+    /// instance. A lock file left behind by a process that is gone does not count.
+    pub fn is_locked(&self) -> bool {
+        let path = self.root().join(LOCK_FILE);
+        if !path.exists() {
+            return false;
+        }
+        match open_lock_file(&path).map(|file| file.try_lock()) {
+            // Dropping the file releases the lock again.
+            Ok(Ok(())) => false,
+            Ok(Err(TryLockError::WouldBlock)) => true,
+            Ok(Err(TryLockError::Error(e))) | Err(e) => {
+                log::warn!("Failed to check {}: {:#}", path.display(), e);
+                false
+            }
+        }
+    }
+}
+
+fn open_lock_file(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)
+}
+
+/// The process id written into the lock file at `path`, if it can be read. Windows
+/// does not let other processes read a locked file.
+fn holder(path: &Path) -> Option<u32> {
+    let mut contents = String::new();
+    File::open(path).ok()?.read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn second_lock_fails_until_released() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+
+        let lock = instance.lock().unwrap();
+        assert_eq!(lock.path(), dir.path().join(LOCK_FILE));
+        assert_eq!(std::fs::read_to_string(lock.path()).unwrap(), std::process::id().to_string());
+        assert!(instance.lock().is_err());
+        assert!(instance.is_locked());
+
+        drop(lock);
+        assert!(!instance.is_locked());
+        assert!(instance.lock().is_ok());
+    }
+
+    #[test]
+    fn leftover_lock_file_is_not_held() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        // What a launcher that crashed leaves behind, or one that is still writing.
+        for contents in [(u32::MAX - 1).to_string(), String::new(), "not a pid".to_string()] {
+            std::fs::write(dir.path().join(LOCK_FILE), &contents).unwrap();
+            assert!(!instance.is_locked());
+
+            let lock = instance.lock().unwrap();
+            assert_eq!(std::fs::read_to_string(lock.path()).unwrap(), std::process::id().to_string());
+        }
+    }
+
+    #[test]
+    fn concurrent_locks_cannot_both_succeed() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+
+        let barrier = std::sync::Barrier::new(8);
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        instance.lock()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+        let error = results.iter().find_map(|result| result.as_ref().err()).unwrap();
+        assert!(error.to_string().contains("already in use"), "{:#}", error);
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use uuid::Uuid;
+
+use crate::instance::{Instance, LoaderType};
+
+/// Where an instance keeps its [`InstanceMetadata`], in its root.
+pub const METADATA_FILE: &str = "instance.json";
+
+/// What an instance is, stored in [`METADATA_FILE`] so it travels with the folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstanceMetadata {
+    /// Generated once when the instance is set up and never changed afterwards.
+    pub uuid: Uuid,
+    pub minecraft_version: String,
+    pub loader: LoaderType,
+    /// None for Vanilla.
+    pub loader_version: Option<String>,
+}
+
+impl Instance {
+    /// Record the game and loader versions in [`METADATA_FILE`], generating the
+    /// instance's UUID if it has none yet. An existing UUID is kept, so updating the
+    /// versions of an instance does not make it a different one.
+    ///
+    /// Fails without touching the file if it exists but cannot be read, rather than
+    /// giving the instance a new id.
+    pub fn init_metadata(
+        &self,
+        minecraft_version: impl Into<String>,
+        loader: LoaderType,
+        loader_version: Option<String>,
+    ) -> Result<InstanceMetadata> {
+        let uuid = match std::fs::metadata(self.metadata_path()) {
+            Ok(_) => self.metadata()?.uuid,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Uuid::new_v4(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read instance metadata at {}", self.metadata_path().display())),
+        };
+        let metadata = InstanceMetadata {
+            uuid,
+            minecraft_version: minecraft_version.into(),
+            loader,
+            loader_version,
+        };
+        std::fs::create_dir_all(self.root())?;
+        std::fs::write(self.metadata_path(), serde_json::to_string_pretty(&metadata)?)?;
+        Ok(metadata)
+    }
+
+    /// The instance's [`METADATA_FILE`], failing if it was never written with
+    /// [`Instance::init_metadata`].
+    pub fn metadata(&self) -> Result<InstanceMetadata> {
+        let path = self.metadata_path();
+        let contents = std::fs::read_to_string(&path).with_context(|| format!("No instance metadata at {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Invalid instance metadata in {}", path.display()))
+    }
+
+    /// A stable identifier for syncing and deduplicating instances across machines:
+    /// the SHA-1 of the stored UUID, game version, loader and loader version.
+    ///
+    /// It does not depend on where the instance lives, so it survives renaming or
+    /// moving the folder, while two instances set up separately never share one even
+    /// with the same versions. Changing the versions changes the id.
+    pub fn id(&self) -> Result<String> {
+        let metadata = self.metadata()?;
+        let mut hasher = Sha1::new();
+        for part in [
+            metadata.uuid.to_string().as_str(),
+            &metadata.minecraft_version,
+            metadata.loader.as_str(),
+            metadata.loader_version.as_deref().unwrap_or(""),
+        ] {
+            hasher.update(part.as_bytes());
+            // Separate the parts so that moving characters between them changes the hash.
+            hasher.update([0]);
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    fn metadata_path(&self) -> std::path::PathBuf {
+        self.root().join(METADATA_FILE)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn id_survives_renaming_the_instance() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path().join("Survival")).unwrap();
+        instance.init_metadata("1.20.4", LoaderType::Fabric, Some("0.15.11".to_string())).unwrap();
+        let id = instance.id().unwrap();
+        assert_eq!(id.len(), 40);
+        assert_eq!(instance.id().unwrap(), id);
+
+        std::fs::rename(dir.path().join("Survival"), dir.path().join("Survival (old)")).unwrap();
+        let renamed = Instance::new(dir.path().join("Survival (old)"));
+        assert_eq!(renamed.id().unwrap(), id);
+    }
+
+    #[test]
+    fn distinct_instances_get_distinct_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = Instance::create(dir.path().join("a")).unwrap();
+        let second = Instance::create(dir.path().join("b")).unwrap();
+        first.init_metadata("1.20.4", LoaderType::Vanilla, None).unwrap();
+        second.init_metadata("1.20.4", LoaderType::Vanilla, None).unwrap();
+        assert_ne!(first.id().unwrap(), second.id().unwrap());
+    }
+
+    #[test]
+    fn init_metadata_keeps_uuid() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        assert!(instance.id().is_err());
+
+        let before = instance.init_metadata("1.20.1", LoaderType::Vanilla, None).unwrap();
+        let vanilla_id = instance.id().unwrap();
+        let after = instance.init_metadata("1.20.1", LoaderType::Fabric, Some("0.15.11".to_string())).unwrap();
+        assert_eq!(before.uuid, after.uuid);
+        assert_ne!(instance.id().unwrap(), vanilla_id);
+        assert_eq!(instance.metadata().unwrap(), after);
+    }
+
+    #[test]
+    fn init_metadata_rejects_corrupt_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        std::fs::write(dir.path().join(METADATA_FILE), "{ not json").unwrap();
+
+        assert!(instance.init_metadata("1.20.1", LoaderType::Vanilla, None).is_err());
+        assert_eq!(std::fs::read_to_string(dir.path().join(METADATA_FILE)).unwrap(), "{ not json");
+    }
+}
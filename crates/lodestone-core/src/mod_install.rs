@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use minecraft_modloaders::download::{DownloadJob, Downloader};
+use serde::{Deserialize, Serialize};
+
+use crate::instance::Instance;
+
+/// A mod jar to put into an instance's `mods/` directory, e.g. one entry of a
+/// modpack's file list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModDownload {
+    pub url: String,
+    /// File name inside `mods/`, e.g. `sodium-fabric-0.5.8.jar`.
+    pub filename: String,
+    pub sha1: Option<String>,
+    /// Checked instead of `sha1` when both are set, as Modrinth publishes both.
+    pub sha512: Option<String>,
+    pub size: Option<u64>,
+}
+
+impl ModDownload {
+    pub fn new(url: impl Into<String>, filename: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            filename: filename.into(),
+            sha1: None,
+            sha512: None,
+            size: None,
+        }
+    }
+
+    pub fn with_sha1(mut self, sha1: impl Into<String>) -> Self {
+        self.sha1 = Some(sha1.into());
+        self
+    }
+
+    pub fn with_sha512(mut self, sha512: impl Into<String>) -> Self {
+        self.sha512 = Some(sha512.into());
+        self
+    }
+
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    fn job(&self, mods_dir: &Path) -> DownloadJob {
+        DownloadJob {
+            url: self.url.clone(),
+            dest: mods_dir.join(&self.filename),
+            sha1: self.sha1.clone(),
+            sha512: self.sha512.clone(),
+            size: self.size,
+        }
+    }
+}
+
+/// A mod [`Instance::install_mods`] could not install.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModInstallFailure {
+    pub entry: ModDownload,
+    /// The error with its causes, for showing to the user.
+    pub error: String,
+}
+
+/// What [`Instance::install_mods`] did with each entry, in the order they were given.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallManyReport {
+    pub installed: Vec<ModDownload>,
+    /// Entries whose jar was already in `mods/` with the expected hash.
+    pub skipped: Vec<ModDownload>,
+    pub failed: Vec<ModInstallFailure>,
+}
+
+impl InstallManyReport {
+    pub fn total(&self) -> usize {
+        self.installed.len() + self.skipped.len() + self.failed.len()
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+enum Outcome {
+    Installed,
+    Skipped,
+    Failed(String),
+}
+
+impl Instance {
+    /// Download `entries` into `mods/`, see [`Instance::install_mods_with`].
+    pub async fn install_mods(&self, entries: &[ModDownload]) -> InstallManyReport {
+        self.install_mods_with(entries, &Downloader::new()).await
+    }
+
+    /// Download `entries` into `mods/` concurrently through `downloader`, verifying each
+    /// jar against its hash.
+    ///
+    /// One entry failing does not stop the others; every entry ends up in exactly one
+    /// list of the report. Jars already present with the expected hash are skipped, so an
+    /// interrupted modpack install can simply be run again. File names that are not a
+    /// single path component fail without being downloaded.
+    pub async fn install_mods_with(&self, entries: &[ModDownload], downloader: &Downloader) -> InstallManyReport {
+        let mods_dir = self.mods_dir();
+        let mut outcomes: Vec<Option<Outcome>> = entries.iter().map(|_| None).collect();
+        let mut by_dest: HashMap<PathBuf, usize> = HashMap::new();
+        let mut jobs = Vec::new();
+        for (index, entry) in entries.iter().enumerate() {
+            if Path::new(&entry.filename).file_name().and_then(|name| name.to_str()) != Some(entry.filename.as_str()) {
+                outcomes[index] = Some(Outcome::Failed(format!("Invalid mod file name: {:?}", entry.filename)));
+                continue;
+            }
+            let job = entry.job(&mods_dir);
+            if by_dest.contains_key(&job.dest) {
+                outcomes[index] = Some(Outcome::Failed(format!("{} is listed more than once", entry.filename)));
+                continue;
+            }
+            by_dest.insert(job.dest.clone(), index);
+            jobs.push(job);
+        }
+
+        let summary = downloader.download(jobs).await;
+        let mut record = |job: &DownloadJob, outcome: Outcome| {
+            if let Some(&index) = by_dest.get(&job.dest) {
+                outcomes[index] = Some(outcome);
+            }
+        };
+        for job in &summary.succeeded {
+            record(job, Outcome::Installed);
+        }
+        for job in &summary.skipped {
+            record(job, Outcome::Skipped);
+        }
+        for failure in &summary.failed {
+            record(&failure.job, Outcome::Failed(format!("{:#}", failure.error)));
+        }
+
+        let mut report = InstallManyReport::default();
+        for (entry, outcome) in entries.iter().zip(outcomes) {
+            match outcome {
+                Some(Outcome::Installed) => report.installed.push(entry.clone()),
+                Some(Outcome::Skipped) => report.skipped.push(entry.clone()),
+                Some(Outcome::Failed(error)) => report.failed.push(ModInstallFailure { entry: entry.clone(), error }),
+                None => report.failed.push(ModInstallFailure {
+                    entry: entry.clone(),
+                    error: "Download did not report a result".to_string(),
+                }),
+            }
+        }
+        log::info!(
+            "Installed {} of {} mods into {} ({} already present, {} failed)",
+            report.installed.len() + report.skipped.len(),
+            report.total(),
+            mods_dir.display(),
+            report.skipped.len(),
+            report.failed.len()
+        );
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use minecraft_modloaders::download::sha1_hex;
+    use test_support::{MockServer, Response, serve};
+
+    /// Serves `/<name>.jar` as the bytes `<name>` and 404s everything under `/missing/`.
+    fn serve_jars() -> MockServer {
+        serve(
+            |request| match request.path.strip_suffix(".jar").filter(|_| !request.path.starts_with("/missing/")) {
+                Some(name) => Response::ok(name.trim_start_matches('/')),
+                None => Response::not_found(),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn reports_each_mod_without_aborting() {
+        let server = serve_jars();
+        let base = server.base_url();
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        std::fs::write(instance.mods_dir().join("present.jar"), b"present").unwrap();
+
+        let entries = vec![
+            ModDownload::new(format!("{}/sodium.jar", base), "sodium.jar").with_sha1(sha1_hex(b"sodium")),
+            ModDownload::new(format!("{}/missing/iris.jar", base), "iris.jar"),
+            ModDownload::new(format!("{}/present.jar", base), "present.jar").with_sha1(sha1_hex(b"present")),
+            ModDownload::new(format!("{}/lithium.jar", base), "lithium.jar").with_sha1(sha1_hex(b"not lithium")),
+            ModDownload::new(format!("{}/escape.jar", base), "../escape.jar"),
+        ];
+        let report = instance.install_mods(&entries).await;
+
+        let names = |entries: &[ModDownload]| entries.iter().map(|e| e.filename.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&report.installed), vec!["sodium.jar"]);
+        assert_eq!(names(&report.skipped), vec!["present.jar"]);
+        let failed: Vec<_> = report.failed.iter().map(|f| f.entry.filename.as_str()).collect();
+        assert_eq!(failed, vec!["iris.jar", "lithium.jar", "../escape.jar"]);
+        assert_eq!(report.total(), 5);
+        assert!(!report.is_success());
+
+        assert_eq!(std::fs::read(instance.mods_dir().join("sodium.jar")).unwrap(), b"sodium");
+        assert!(!instance.mods_dir().join("iris.jar").exists());
+        assert!(!instance.mods_dir().join("lithium.jar").exists());
+        assert!(!dir.path().join("escape.jar").exists());
+    }
+
+    #[tokio::test]
+    async fn duplicate_file_name_installs_first_entry() {
+        let server = serve_jars();
+        let base = server.base_url();
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+
+        let entries = vec![
+            ModDownload::new(format!("{}/sodium.jar", base), "sodium.jar").with_sha1(sha1_hex(b"sodium")),
+            ModDownload::new(format!("{}/other.jar", base), "sodium.jar"),
+        ];
+        let report = instance.install_mods(&entries).await;
+
+        assert_eq!(report.installed, vec![entries[0].clone()]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].entry, entries[1]);
+        assert!(report.failed[0].error.contains("listed more than once"), "{}", report.failed[0].error);
+        assert_eq!(std::fs::read(instance.mods_dir().join("sodium.jar")).unwrap(), b"sodium");
+    }
+}
@@ -0,0 +1,292 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::instance::Instance;
+
+/// A resource pack in `resourcepacks/`, from [`Instance::list_resource_packs`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourcePack {
+    pub path: PathBuf,
+    /// File or folder name, as the game lists it in `options.txt`.
+    pub filename: String,
+    /// `filename` without the `.zip` extension.
+    pub name: String,
+    /// A `.zip` rather than an unzipped folder.
+    pub zipped: bool,
+    /// `pack.description` from `pack.mcmeta` as plain text, formatting dropped.
+    pub description: Option<String>,
+    /// `pack.pack_format` from `pack.mcmeta`, which says which game versions the pack
+    /// was made for.
+    pub pack_format: Option<u32>,
+}
+
+/// A shader pack in `shaderpacks/`, from [`Instance::list_shader_packs`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShaderPack {
+    pub path: PathBuf,
+    /// File or folder name, as Iris and OptiFine store the selected pack.
+    pub filename: String,
+    /// `filename` without the `.zip` extension.
+    pub name: String,
+    /// A `.zip` rather than an unzipped folder.
+    pub zipped: bool,
+}
+
+#[derive(Deserialize)]
+struct PackMcmeta {
+    pack: PackSection,
+}
+
+#[derive(Deserialize)]
+struct PackSection {
+    pack_format: Option<u32>,
+    #[serde(default)]
+    description: serde_json::Value,
+}
+
+/// A pack found in a packs directory: a `.zip` file or a folder holding `marker`.
+struct PackEntry {
+    path: PathBuf,
+    filename: String,
+    name: String,
+    zipped: bool,
+}
+
+impl PackEntry {
+    fn read(path: PathBuf, is_dir: bool, marker: &str) -> Option<Self> {
+        let filename = path.file_name()?.to_str()?.to_string();
+        let (name, zipped) = if is_dir {
+            if !path.join(marker).exists() {
+                return None;
+            }
+            (filename.clone(), false)
+        } else {
+            let name = filename.strip_suffix(".zip")?;
+            (name.to_string(), true)
+        };
+        Some(Self {
+            path,
+            filename,
+            name,
+            zipped,
+        })
+    }
+}
+
+impl ResourcePack {
+    fn from_entry(entry: PackEntry) -> Self {
+        let (description, pack_format) = match read_pack_mcmeta(&entry.path, entry.zipped) {
+            Ok(mcmeta) => (text_component(&mcmeta.pack.description), mcmeta.pack.pack_format),
+            Err(e) => {
+                log::warn!("Could not read pack.mcmeta of {}: {:#}", entry.path.display(), e);
+                (None, None)
+            }
+        };
+        Self {
+            path: entry.path,
+            filename: entry.filename,
+            name: entry.name,
+            zipped: entry.zipped,
+            description,
+            pack_format,
+        }
+    }
+}
+
+fn read_pack_mcmeta(path: &Path, zipped: bool) -> Result<PackMcmeta> {
+    let contents = if zipped {
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+        let mut contents = String::new();
+        archive.by_name("pack.mcmeta")?.read_to_string(&mut contents)?;
+        contents
+    } else {
+        std::fs::read_to_string(path.join("pack.mcmeta"))?
+    };
+    // Some packs are saved with a byte order mark, which the game tolerates.
+    Ok(serde_json::from_str(contents.trim_start_matches('\u{feff}'))?)
+}
+
+/// The plain text of a chat component: a string, an object with `text` and `extra`,
+/// or a list of components. None when there is no text at all.
+fn text_component(value: &serde_json::Value) -> Option<String> {
+    fn collect(value: &serde_json::Value, out: &mut String) {
+        match value {
+            serde_json::Value::String(text) => out.push_str(text),
+            serde_json::Value::Array(parts) => parts.iter().for_each(|part| collect(part, out)),
+            serde_json::Value::Object(object) => {
+                if let Some(text) = object.get("text") {
+                    collect(text, out);
+                }
+                if let Some(extra) = object.get("extra") {
+                    collect(extra, out);
+                }
+            }
+            serde_json::Value::Number(number) => out.push_str(&number.to_string()),
+            serde_json::Value::Bool(_) | serde_json::Value::Null => {}
+        }
+    }
+
+    let mut text = String::new();
+    collect(value, &mut text);
+    (!text.is_empty()).then_some(text)
+}
+
+/// Every pack in `dir`, sorted by file name. A missing directory lists as empty.
+fn list_packs(dir: &Path, marker: &str) -> Result<Vec<PackEntry>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut packs = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if let Some(pack) = PackEntry::read(entry.path(), entry.file_type()?.is_dir(), marker) {
+            packs.push(pack);
+        }
+    }
+    packs.sort_by(|a, b| a.filename.cmp(&b.filename));
+    Ok(packs)
+}
+
+impl Instance {
+    /// Every resource pack in `resourcepacks/`, zipped or unzipped, sorted by file name.
+    ///
+    /// Folders count as packs when they hold a `pack.mcmeta`, as in game. Packs whose
+    /// `pack.mcmeta` is missing or unreadable are still listed without a description
+    /// or pack format. A missing `resourcepacks/` directory lists as empty.
+    pub fn list_resource_packs(&self) -> Result<Vec<ResourcePack>> {
+        Ok(list_packs(&self.resource_packs_dir(), "pack.mcmeta")?
+            .into_iter()
+            .map(ResourcePack::from_entry)
+            .collect())
+    }
+
+    /// Every shader pack in `shaderpacks/`, zipped or unzipped, sorted by file name.
+    ///
+    /// Folders count as packs when they hold a `shaders/` directory, which is what
+    /// Iris and OptiFine load. A missing `shaderpacks/` directory lists as empty.
+    pub fn list_shader_packs(&self) -> Result<Vec<ShaderPack>> {
+        Ok(list_packs(&self.shader_packs_dir(), "shaders")?
+            .into_iter()
+            .map(|entry| ShaderPack {
+                path: entry.path,
+                filename: entry.filename,
+                name: entry.name,
+                zipped: entry.zipped,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, contents) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn list_resource_packs_reads_zips_and_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        let packs = instance.resource_packs_dir();
+        std::fs::create_dir_all(&packs).unwrap();
+        write_zip(
+            &packs.join("Faithful 32x.zip"),
+            &[
+                ("pack.mcmeta", r#"{"pack": {"pack_format": 15, "description": "The Faithful 32x pack"}}"#),
+                ("assets/minecraft/textures/block/stone.png", ""),
+            ],
+        );
+        let folder = packs.join("My Pack");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(
+            folder.join("pack.mcmeta"),
+            "\u{feff}{\"pack\": {\"pack_format\": 4, \"description\": [{\"text\": \"Made by \", \"color\": \"gray\"}, {\"text\": \"me\", \"bold\": true}]}}",
+        )
+        .unwrap();
+        write_zip(&packs.join("broken.zip"), &[("readme.txt", "no mcmeta")]);
+        std::fs::create_dir_all(packs.join("not a pack")).unwrap();
+        std::fs::write(packs.join("notes.txt"), b"").unwrap();
+
+        let listed = instance.list_resource_packs().unwrap();
+        let names: Vec<&str> = listed.iter().map(|pack| pack.filename.as_str()).collect();
+        assert_eq!(names, ["Faithful 32x.zip", "My Pack", "broken.zip"]);
+
+        assert_eq!(listed[0].name, "Faithful 32x");
+        assert!(listed[0].zipped);
+        assert_eq!(listed[0].description.as_deref(), Some("The Faithful 32x pack"));
+        assert_eq!(listed[0].pack_format, Some(15));
+
+        assert!(!listed[1].zipped);
+        assert_eq!(listed[1].description.as_deref(), Some("Made by me"));
+        assert_eq!(listed[1].pack_format, Some(4));
+
+        assert_eq!(listed[2].description, None);
+        assert_eq!(listed[2].pack_format, None);
+    }
+
+    #[test]
+    fn list_shader_packs_reads_zips_and_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        let packs = instance.shader_packs_dir();
+        std::fs::create_dir_all(packs.join("BSL_v8.2.09").join("shaders")).unwrap();
+        std::fs::create_dir_all(packs.join("empty folder")).unwrap();
+        write_zip(&packs.join("ComplementaryReimagined_r5.1.zip"), &[("shaders/final.fsh", "")]);
+        std::fs::write(packs.join("ComplementaryReimagined_r5.1.zip.txt"), b"settings").unwrap();
+
+        let listed = instance.list_shader_packs().unwrap();
+        assert_eq!(
+            listed,
+            [
+                ShaderPack {
+                    path: packs.join("BSL_v8.2.09"),
+                    filename: "BSL_v8.2.09".to_string(),
+                    name: "BSL_v8.2.09".to_string(),
+                    zipped: false,
+                },
+                ShaderPack {
+                    path: packs.join("ComplementaryReimagined_r5.1.zip"),
+                    filename: "ComplementaryReimagined_r5.1.zip".to_string(),
+                    name: "ComplementaryReimagined_r5.1".to_string(),
+                    zipped: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn list_packs_without_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::new(dir.path());
+        assert!(instance.list_resource_packs().unwrap().is_empty());
+        assert!(instance.list_shader_packs().unwrap().is_empty());
+    }
+
+    #[test]
+    fn text_component_flattens_formatting() {
+        assert_eq!(text_component(&serde_json::json!("plain")).as_deref(), Some("plain"));
+        assert_eq!(
+            text_component(&serde_json::json!({"text": "a", "extra": [{"text": "b"}, "c"]})).as_deref(),
+            Some("abc")
+        );
+        assert_eq!(text_component(&serde_json::json!(null)), None);
+    }
+}
@@ -0,0 +1,139 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+
+use crate::utils::path_util::PathUtil;
+
+/// Environment variable that moves all launcher data, caches included, to another directory.
+pub const DATA_DIR_ENV: &str = "LODESTONE_DATA_DIR";
+
+/// The folder the launcher creates inside the platform's data and cache directories.
+const APP_DIR_NAME: &str = "lodestone";
+
+/// Where the launcher keeps data that must survive, such as instances, profiles and accounts.
+///
+/// `$LODESTONE_DATA_DIR` when set, otherwise `%APPDATA%\lodestone` on Windows,
+/// `~/Library/Application Support/lodestone` on macOS and `$XDG_DATA_HOME/lodestone`
+/// (default `~/.local/share/lodestone`) elsewhere.
+pub fn data_dir() -> Result<PathBuf> {
+    resolve_data_dir(std::env::var_os(DATA_DIR_ENV)).ok_or_else(|| anyhow!("Could not determine the data directory; set {}", DATA_DIR_ENV))
+}
+
+/// Where the launcher keeps data it can download again, such as version manifests and
+/// loader metadata. This is the directory to hand to the `fetch_cached` functions.
+///
+/// `$LODESTONE_DATA_DIR/cache` when the variable is set, otherwise `%LOCALAPPDATA%\lodestone`
+/// on Windows, `~/Library/Caches/lodestone` on macOS and `$XDG_CACHE_HOME/lodestone`
+/// (default `~/.cache/lodestone`) elsewhere.
+pub fn cache_dir() -> Result<PathBuf> {
+    resolve_cache_dir(std::env::var_os(DATA_DIR_ENV)).ok_or_else(|| anyhow!("Could not determine the cache directory; set {}", DATA_DIR_ENV))
+}
+
+/// `subpath` below [`data_dir`], e.g. `data_path("instances/My Pack")`.
+///
+/// `subpath` is split on `/` and each component is cleaned with [`PathUtil::clean`], so
+/// a caller-provided name cannot contain invalid characters or climb out with `..`.
+pub fn data_path(subpath: &str) -> Result<PathBuf> {
+    join_cleaned(&data_dir()?, subpath)
+}
+
+/// `subpath` below [`cache_dir`], cleaned like [`data_path`].
+pub fn cache_path(subpath: &str) -> Result<PathBuf> {
+    join_cleaned(&cache_dir()?, subpath)
+}
+
+fn resolve_data_dir(override_dir: Option<OsString>) -> Option<PathBuf> {
+    match non_empty(override_dir) {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => platform_data_dir().map(|dir| dir.join(APP_DIR_NAME)),
+    }
+}
+
+fn resolve_cache_dir(override_dir: Option<OsString>) -> Option<PathBuf> {
+    match non_empty(override_dir) {
+        Some(dir) => Some(PathBuf::from(dir).join("cache")),
+        None => platform_cache_dir().map(|dir| dir.join(APP_DIR_NAME)),
+    }
+}
+
+fn non_empty(value: Option<OsString>) -> Option<OsString> {
+    value.filter(|value| !value.is_empty())
+}
+
+fn platform_data_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home_dir().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        xdg_dir("XDG_DATA_HOME").or_else(|| home_dir().map(|home| home.join(".local").join("share")))
+    }
+}
+
+fn platform_cache_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home_dir().map(|home| home.join("Library").join("Caches"))
+    } else {
+        xdg_dir("XDG_CACHE_HOME").or_else(|| home_dir().map(|home| home.join(".cache")))
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    non_empty(std::env::var_os("HOME")).map(PathBuf::from)
+}
+
+/// The XDG base directory in `var`; the spec says relative values are to be ignored.
+fn xdg_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var).map(PathBuf::from).filter(|dir| dir.is_absolute())
+}
+
+pub(crate) fn join_cleaned(base: &Path, subpath: &str) -> Result<PathBuf> {
+    let mut path = base.to_path_buf();
+    for component in subpath.split(['/', '\\']).filter(|component| !component.is_empty()) {
+        let mut component = component.to_string();
+        component.clean()?;
+        path.push(component);
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn env_override_wins() {
+        let dir = OsString::from("/srv/lodestone");
+        assert_eq!(resolve_data_dir(Some(dir.clone())), Some(PathBuf::from("/srv/lodestone")));
+        assert_eq!(resolve_cache_dir(Some(dir)), Some(PathBuf::from("/srv/lodestone/cache")));
+    }
+
+    #[test]
+    fn empty_override_is_ignored() {
+        assert_eq!(resolve_data_dir(Some(OsString::new())), resolve_data_dir(None));
+        assert_eq!(resolve_cache_dir(Some(OsString::new())), resolve_cache_dir(None));
+    }
+
+    #[test]
+    fn platform_dirs_end_in_app_name() {
+        if let Some(dir) = resolve_data_dir(None) {
+            assert!(dir.ends_with(APP_DIR_NAME), "{}", dir.display());
+        }
+        if let Some(dir) = resolve_cache_dir(None) {
+            assert!(dir.ends_with(APP_DIR_NAME), "{}", dir.display());
+        }
+    }
+
+    #[test]
+    fn subpath_is_joined_and_cleaned() {
+        let base = Path::new("/data/lodestone");
+        assert_eq!(join_cleaned(base, "instances/My Pack").unwrap(), base.join("instances").join("My Pack"));
+        assert_eq!(join_cleaned(base, "meta//fabric/").unwrap(), base.join("meta").join("fabric"));
+        assert_eq!(join_cleaned(base, "skins/Who?").unwrap(), base.join("skins").join("Who"));
+        assert_eq!(join_cleaned(base, "").unwrap(), base);
+        assert!(join_cleaned(base, "../etc").is_err());
+    }
+}
@@ -0,0 +1,317 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::instance::LoaderType;
+use crate::java::JavaSelection;
+use crate::utils::path_util::PathUtil;
+
+/// A saved launch configuration: which game and loader to run, how, and where.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Display name, unique within a [`ProfileStore`].
+    pub name: String,
+    /// Minecraft version (e.g., "1.21.4"), or an alias such as "latest-release"
+    /// that is resolved at launch; see [`VersionSelector`](crate::selector::VersionSelector).
+    pub minecraft_version: String,
+    pub loader: LoaderType,
+    /// Mod loader version (e.g., "0.16.14") or alias such as "latest-stable", None for Vanilla.
+    pub loader_version: Option<String>,
+    /// Extra JVM arguments, e.g. `-Xmx4G`.
+    #[serde(default)]
+    pub jvm_args: Vec<String>,
+    /// Game directory. When unset, [`ProfileStore::game_dir`] derives one from the name.
+    pub game_dir: Option<PathBuf>,
+    /// The Java runtime to launch with instead of the one the game version asks for;
+    /// see [`Profile::resolve_java`].
+    #[serde(default)]
+    pub java_override: Option<JavaSelection>,
+    pub created_at: DateTime<Utc>,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+impl Profile {
+    pub fn new(name: impl Into<String>, minecraft_version: impl Into<String>, loader: LoaderType) -> Self {
+        Self {
+            name: name.into(),
+            minecraft_version: minecraft_version.into(),
+            loader,
+            loader_version: None,
+            jvm_args: Vec::new(),
+            game_dir: None,
+            java_override: None,
+            created_at: Utc::now(),
+            last_used: None,
+        }
+    }
+
+    pub fn with_loader_version(mut self, loader_version: impl Into<String>) -> Self {
+        self.loader_version = Some(loader_version.into());
+        self
+    }
+
+    pub fn with_jvm_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.jvm_args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_game_dir(mut self, game_dir: impl Into<PathBuf>) -> Self {
+        self.game_dir = Some(game_dir.into());
+        self
+    }
+
+    pub fn with_java_override(mut self, java: JavaSelection) -> Self {
+        self.java_override = Some(java);
+        self
+    }
+
+    /// The profile name sanitized for use as a file or folder name, so
+    /// `My/Cool:Pack` becomes `MyCoolPack`.
+    pub fn file_name(&self) -> Result<String> {
+        let mut name = self.name.clone();
+        name.clean()?;
+        Ok(name)
+    }
+}
+
+/// On-disk format of the profiles file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProfilesFile {
+    profiles: Vec<Profile>,
+}
+
+/// A list of [`Profile`]s persisted as a single JSON file.
+///
+/// Every mutating call writes the file back immediately, so the store never
+/// has unsaved changes.
+///
+/// # Example
+///
+/// ```no_run
+/// use lodestone_core::instance::LoaderType;
+/// use lodestone_core::profile::{Profile, ProfileStore};
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let mut store = ProfileStore::open("data/profiles.json", "data/profiles")?;
+/// store.create(Profile::new("Survival", "1.21.4", LoaderType::Fabric).with_loader_version("0.16.14"))?;
+/// store.touch("Survival")?;
+/// for profile in store.list() {
+///     println!("{} ({})", profile.name, profile.minecraft_version);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ProfileStore {
+    path: PathBuf,
+    profiles_dir: PathBuf,
+    profiles: Vec<Profile>,
+}
+
+impl ProfileStore {
+    /// Load the store from `path`, starting empty if the file does not exist yet.
+    ///
+    /// Profiles without an explicit game directory get one under `profiles_dir`.
+    pub fn open(path: impl Into<PathBuf>, profiles_dir: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let profiles = match std::fs::read_to_string(&path) {
+            Ok(json) => {
+                serde_json::from_str::<ProfilesFile>(&json)
+                    .with_context(|| format!("Failed to parse profiles file {}", path.display()))?
+                    .profiles
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read profiles file {}", path.display())),
+        };
+        Ok(Self {
+            path,
+            profiles_dir: profiles_dir.into(),
+            profiles,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All profiles, most recently used first. Profiles that were never used
+    /// come last, newest first.
+    pub fn list(&self) -> Vec<&Profile> {
+        let mut profiles: Vec<&Profile> = self.profiles.iter().collect();
+        profiles.sort_by(|a, b| b.last_used.cmp(&a.last_used).then(b.created_at.cmp(&a.created_at)));
+        profiles
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    /// Add a new profile. Fails if a profile with the same name, or the same
+    /// sanitized file name, already exists.
+    pub fn create(&mut self, profile: Profile) -> Result<&Profile> {
+        let file_name = profile.file_name()?;
+        for existing in &self.profiles {
+            if existing.name == profile.name || existing.file_name().is_ok_and(|f| f == file_name) {
+                return Err(anyhow!("A profile named '{}' already exists", existing.name));
+            }
+        }
+        self.profiles.push(profile);
+        self.save()?;
+        Ok(self.profiles.last().expect("profile was just pushed"))
+    }
+
+    /// Replace the profile named `name` with `profile`, which may carry a new name.
+    pub fn update(&mut self, name: &str, profile: Profile) -> Result<&Profile> {
+        let index = self.index_of(name)?;
+        if profile.name != name && self.get(&profile.name).is_some() {
+            return Err(anyhow!("A profile named '{}' already exists", profile.name));
+        }
+        profile.file_name()?;
+        self.profiles[index] = profile;
+        self.save()?;
+        Ok(&self.profiles[index])
+    }
+
+    /// Remove a profile and return it. Its game directory is left on disk.
+    pub fn delete(&mut self, name: &str) -> Result<Profile> {
+        let index = self.index_of(name)?;
+        let removed = self.profiles.remove(index);
+        self.save()?;
+        Ok(removed)
+    }
+
+    /// Mark a profile as used just now, moving it to the front of [`ProfileStore::list`].
+    pub fn touch(&mut self, name: &str) -> Result<()> {
+        let index = self.index_of(name)?;
+        self.profiles[index].last_used = Some(Utc::now());
+        self.save()
+    }
+
+    /// The directory the game runs in for `profile`: its explicit `game_dir`, or
+    /// `profiles_dir/<sanitized name>`.
+    pub fn game_dir(&self, profile: &Profile) -> Result<PathBuf> {
+        match &profile.game_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => Ok(self.profiles_dir.join(profile.file_name()?)),
+        }
+    }
+
+    /// Write the profiles to disk, replacing the file atomically.
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = ProfilesFile {
+            profiles: self.profiles.clone(),
+        };
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&file)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    fn index_of(&self, name: &str) -> Result<usize> {
+        self.profiles
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| anyhow!("No profile named '{}'", name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn store(dir: &Path) -> ProfileStore {
+        ProfileStore::open(dir.join("profiles.json"), dir.join("profiles")).unwrap()
+    }
+
+    #[test]
+    fn create_update_delete_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut profiles = store(dir.path());
+        assert!(profiles.list().is_empty());
+
+        profiles.create(Profile::new("Vanilla", "1.21.4", LoaderType::Vanilla)).unwrap();
+        profiles
+            .create(
+                Profile::new("Fabric Pack", "1.20.1", LoaderType::Fabric)
+                    .with_loader_version("0.16.14")
+                    .with_jvm_args(["-Xmx4G"]),
+            )
+            .unwrap();
+        assert!(profiles.create(Profile::new("Vanilla", "1.8.9", LoaderType::Vanilla)).is_err());
+
+        let reopened = store(dir.path());
+        assert_eq!(reopened.list().len(), 2);
+        let fabric = reopened.get("Fabric Pack").unwrap();
+        assert_eq!(fabric.loader_version.as_deref(), Some("0.16.14"));
+        assert_eq!(fabric.jvm_args, vec!["-Xmx4G"]);
+
+        let mut updated = fabric.clone();
+        updated.name = "Fabric 1.20".to_string();
+        updated.minecraft_version = "1.20.4".to_string();
+        profiles.update("Fabric Pack", updated).unwrap();
+        assert!(profiles.update("Missing", Profile::new("x", "1.0", LoaderType::Vanilla)).is_err());
+
+        let reopened = store(dir.path());
+        assert!(reopened.get("Fabric Pack").is_none());
+        assert_eq!(reopened.get("Fabric 1.20").unwrap().minecraft_version, "1.20.4");
+
+        let removed = profiles.delete("Vanilla").unwrap();
+        assert_eq!(removed.name, "Vanilla");
+        assert!(profiles.delete("Vanilla").is_err());
+
+        let reopened = store(dir.path());
+        let names: Vec<&str> = reopened.list().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Fabric 1.20"]);
+    }
+
+    #[test]
+    fn list_orders_by_last_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut profiles = store(dir.path());
+        for name in ["A", "B", "C"] {
+            profiles.create(Profile::new(name, "1.21.4", LoaderType::Vanilla)).unwrap();
+        }
+
+        profiles.touch("A").unwrap();
+        profiles.touch("C").unwrap();
+        let names: Vec<&str> = profiles.list().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["C", "A", "B"]);
+
+        let reopened = store(dir.path());
+        let names: Vec<&str> = reopened.list().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["C", "A", "B"]);
+        assert!(profiles.touch("Missing").is_err());
+    }
+
+    #[test]
+    fn profile_names_are_sanitized() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut profiles = store(dir.path());
+        let profile = profiles
+            .create(Profile::new("My/Cool:Pack", "1.21.4", LoaderType::Quilt))
+            .unwrap()
+            .clone();
+
+        assert_eq!(profile.file_name().unwrap(), "MyCoolPack");
+        assert_eq!(profiles.game_dir(&profile).unwrap(), dir.path().join("profiles").join("MyCoolPack"));
+
+        // A different name that sanitizes to the same folder is rejected
+        assert!(profiles.create(Profile::new("MyCool/Pack", "1.21.4", LoaderType::Quilt)).is_err());
+
+        // Names with nothing valid left cannot be stored
+        assert!(profiles.create(Profile::new("<>", "1.21.4", LoaderType::Vanilla)).is_err());
+
+        // An explicit game directory wins over the derived one
+        let custom = Profile::new("Custom", "1.21.4", LoaderType::Vanilla).with_game_dir("/games/custom");
+        assert_eq!(profiles.game_dir(&custom).unwrap(), PathBuf::from("/games/custom"));
+    }
+}
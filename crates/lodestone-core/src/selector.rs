@@ -0,0 +1,190 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use minecraft_modloaders::LoaderVersionInfo;
+use minecraft_modloaders::vanilla::VersionManifest;
+use serde::{Deserialize, Serialize};
+
+use crate::profile::Profile;
+
+/// A game or loader version as a profile stores it: either a concrete version or an
+/// alias resolved at launch time against freshly fetched version lists.
+///
+/// Selectors are written as plain strings, so `"1.21.4"`, `"latest-release"`,
+/// `"latest-snapshot"`, `"latest-stable"` and `"latest"` are all valid values of
+/// [`Profile::minecraft_version`] and [`Profile::loader_version`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum VersionSelector {
+    Exact(String),
+    /// The newest game release in the version manifest.
+    LatestRelease,
+    /// The newest game snapshot in the version manifest.
+    LatestSnapshot,
+    /// The newest loader build marked stable.
+    LatestStableLoader,
+    /// The newest loader build, stable or not.
+    LatestLoader,
+}
+
+impl VersionSelector {
+    /// The string this selector is stored as, e.g. `latest-release`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Exact(version) => version,
+            Self::LatestRelease => "latest-release",
+            Self::LatestSnapshot => "latest-snapshot",
+            Self::LatestStableLoader => "latest-stable",
+            Self::LatestLoader => "latest",
+        }
+    }
+
+    pub fn is_alias(&self) -> bool {
+        !matches!(self, Self::Exact(_))
+    }
+
+    /// The concrete version this selector stands for.
+    ///
+    /// Game aliases are looked up in `manifest`; loader aliases pick from
+    /// `loader_versions`, which is expected newest first and already narrowed to the
+    /// game version, as [`minecraft_modloaders::ModLoaderVersions::loader_versions_for_game`]
+    /// returns it. Exact versions are returned unchanged without checking either list.
+    pub fn resolve(&self, manifest: &VersionManifest, loader_versions: &[LoaderVersionInfo]) -> Result<String> {
+        let resolved = match self {
+            Self::Exact(version) => Some(version.clone()),
+            Self::LatestRelease => manifest.latest_release().map(|entry| entry.id.clone()),
+            Self::LatestSnapshot => manifest.latest_snapshot().map(|entry| entry.id.clone()),
+            Self::LatestStableLoader => loader_versions.iter().find(|v| v.stable).map(|v| v.version.clone()),
+            Self::LatestLoader => loader_versions.first().map(|v| v.version.clone()),
+        };
+        resolved.ok_or_else(|| anyhow!("No version matches {}", self))
+    }
+}
+
+impl From<String> for VersionSelector {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "latest-release" => Self::LatestRelease,
+            "latest-snapshot" => Self::LatestSnapshot,
+            "latest-stable" => Self::LatestStableLoader,
+            "latest" => Self::LatestLoader,
+            _ => Self::Exact(value),
+        }
+    }
+}
+
+impl From<&str> for VersionSelector {
+    fn from(value: &str) -> Self {
+        Self::from(value.to_string())
+    }
+}
+
+impl From<VersionSelector> for String {
+    fn from(value: VersionSelector) -> Self {
+        match value {
+            VersionSelector::Exact(version) => version,
+            alias => alias.as_str().to_string(),
+        }
+    }
+}
+
+impl FromStr for VersionSelector {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl fmt::Display for VersionSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Profile {
+    /// The profile's game version as a selector; aliases stay unresolved.
+    pub fn minecraft_selector(&self) -> VersionSelector {
+        VersionSelector::from(self.minecraft_version.as_str())
+    }
+
+    /// The profile's loader version as a selector, None for Vanilla.
+    pub fn loader_selector(&self) -> Option<VersionSelector> {
+        self.loader_version.as_deref().map(VersionSelector::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instance::LoaderType;
+
+    fn manifest() -> VersionManifest {
+        serde_json::from_str(
+            r#"{
+                "latest": {"release": "1.21.4", "snapshot": "25w02a"},
+                "versions": [
+                    {"id": "25w02a", "type": "snapshot", "url": "", "sha1": "", "time": "2025-01-08T12:00:00+00:00", "releaseTime": "2025-01-08T12:00:00+00:00"},
+                    {"id": "1.21.4", "type": "release", "url": "", "sha1": "", "time": "2024-12-03T10:12:57+00:00", "releaseTime": "2024-12-03T10:12:57+00:00"},
+                    {"id": "1.21.3", "type": "release", "url": "", "sha1": "", "time": "2024-10-23T12:28:15+00:00", "releaseTime": "2024-10-23T12:28:15+00:00"}
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn loader_versions() -> Vec<LoaderVersionInfo> {
+        [("0.16.10-beta.1", false), ("0.16.9", true), ("0.16.8", true)]
+            .into_iter()
+            .map(|(version, stable)| LoaderVersionInfo {
+                version: version.to_string(),
+                game_version: None,
+                stable,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resolves_every_variant() {
+        let manifest = manifest();
+        let loaders = loader_versions();
+        let resolve = |selector: VersionSelector| selector.resolve(&manifest, &loaders).unwrap();
+
+        assert_eq!(resolve(VersionSelector::Exact("1.20.1".to_string())), "1.20.1");
+        assert_eq!(resolve(VersionSelector::LatestRelease), "1.21.4");
+        assert_eq!(resolve(VersionSelector::LatestSnapshot), "25w02a");
+        assert_eq!(resolve(VersionSelector::LatestStableLoader), "0.16.9");
+        assert_eq!(resolve(VersionSelector::LatestLoader), "0.16.10-beta.1");
+    }
+
+    #[test]
+    fn loader_alias_without_candidates_is_an_error() {
+        let unstable = &loader_versions()[..1];
+        assert!(VersionSelector::LatestStableLoader.resolve(&manifest(), unstable).is_err());
+        assert!(VersionSelector::LatestLoader.resolve(&manifest(), &[]).is_err());
+    }
+
+    #[test]
+    fn profile_keeps_the_alias() {
+        let profile = Profile::new("Bleeding Edge", VersionSelector::LatestSnapshot, LoaderType::Fabric)
+            .with_loader_version(VersionSelector::LatestStableLoader);
+        assert_eq!(profile.minecraft_version, "latest-snapshot");
+        assert_eq!(profile.minecraft_selector(), VersionSelector::LatestSnapshot);
+        assert_eq!(profile.loader_selector(), Some(VersionSelector::LatestStableLoader));
+
+        let json = serde_json::to_string(&profile).unwrap();
+        assert!(json.contains(r#""minecraft_version":"latest-snapshot""#), "{json}");
+        assert!(json.contains(r#""loader_version":"latest-stable""#), "{json}");
+
+        let selectors: Vec<VersionSelector> = serde_json::from_str(r#"["1.21.4", "latest-release", "latest"]"#).unwrap();
+        assert_eq!(
+            selectors,
+            vec![
+                VersionSelector::Exact("1.21.4".to_string()),
+                VersionSelector::LatestRelease,
+                VersionSelector::LatestLoader
+            ]
+        );
+    }
+}
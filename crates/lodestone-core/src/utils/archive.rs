@@ -0,0 +1,324 @@
+use anyhow::{Context, Result, bail};
+use chrono::{Datelike, Local, TimeZone, Timelike};
+use std::fs::File;
+use std::io::{BufWriter, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Controls how [`zip_dir`] writes an archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZipOptions {
+    /// Deflate level from 1 (fastest) to 9 (smallest); `Some(0)` stores entries uncompressed
+    /// and `None` uses the deflate default.
+    pub compression_level: Option<i64>,
+    /// Store each file's modification time. Without it every entry gets the same fixed
+    /// time, so archives of the same content match even after the files were touched.
+    pub store_mtimes: bool,
+}
+
+impl Default for ZipOptions {
+    fn default() -> Self {
+        Self {
+            compression_level: None,
+            store_mtimes: true,
+        }
+    }
+}
+
+/// Zip everything below `src` into a new archive at `dest`, with entry names relative to `src`.
+///
+/// Entries are written depth-first in file name order with explicit directory entries,
+/// so zipping the same tree twice with the same options gives byte-identical archives.
+/// Symlinks are skipped. On failure the partial archive at `dest` is removed.
+pub fn zip_dir(src: &Path, dest: &Path, options: ZipOptions) -> Result<()> {
+    if !src.is_dir() {
+        bail!("Cannot zip {}: not a directory", src.display());
+    }
+    let file = File::create(dest).with_context(|| format!("Failed to create archive {}", dest.display()))?;
+    let result = write_zip(src, file, options);
+    if result.is_err() {
+        let _ = std::fs::remove_file(dest);
+    }
+    result
+}
+
+fn write_zip(src: &Path, file: File, options: ZipOptions) -> Result<()> {
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+    add_dir_to_zip(&mut zip, src, "", options)?;
+    zip.finish()?.flush()?;
+    Ok(())
+}
+
+/// Extract every entry of the zip at `archive` into `dest`, returning the written files.
+///
+/// See [`unzip_with`] for how entries are checked and what metadata is kept.
+pub fn unzip(archive: &Path, dest: &Path) -> Result<Vec<PathBuf>> {
+    unzip_with(archive, dest, |_| true)
+}
+
+/// Like [`unzip`], but only extracts entries whose path relative to `dest` passes `filter`.
+///
+/// Every entry is checked before anything is written: one that is absolute or climbs
+/// out of `dest` with `..` (zip-slip) fails the whole extraction, even if `filter`
+/// would have skipped it, and leaves `dest` untouched. Modification times are restored
+/// and on unix the permission bits too, without setuid, setgid or sticky bits.
+pub fn unzip_with(archive: &Path, dest: &Path, mut filter: impl FnMut(&Path) -> bool) -> Result<Vec<PathBuf>> {
+    let file = File::open(archive).with_context(|| format!("Failed to open archive {}", archive.display()))?;
+    let mut zip = ZipArchive::new(file).with_context(|| format!("Not a zip archive: {}", archive.display()))?;
+    for index in 0..zip.len() {
+        let entry = zip.by_index(index)?;
+        if entry.enclosed_name().is_none() {
+            bail!(
+                "Refusing to extract {} from {}: it escapes {}",
+                entry.name(),
+                archive.display(),
+                dest.display()
+            );
+        }
+    }
+
+    std::fs::create_dir_all(dest)?;
+    let mut written = Vec::new();
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index)?;
+        let Some(relative) = entry.enclosed_name() else {
+            continue;
+        };
+        if !filter(&relative) {
+            continue;
+        }
+        let path = dest.join(relative);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&path)?;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut out = File::create(&path)?;
+        std::io::copy(&mut entry, &mut out)?;
+        if let Some(modified) = entry.last_modified().and_then(system_time_from_zip) {
+            // Best effort, some filesystems do not allow setting times
+            let _ = out.set_modified(modified);
+        }
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode & 0o777))?;
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Add everything under `dir` to `zip`, with entry names relative to `dir` and prefixed by `prefix`.
+fn add_dir_to_zip<W: Write + Seek>(zip: &mut ZipWriter<W>, dir: &Path, prefix: &str, options: ZipOptions) -> Result<()> {
+    let mut entries = std::fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        let metadata = entry.metadata()?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .filter(|_| options.store_mtimes)
+            .and_then(zip_time_from_system)
+            .unwrap_or_default();
+        let mut file_options = SimpleFileOptions::default().last_modified_time(modified);
+        file_options = match options.compression_level {
+            Some(0) => file_options.compression_method(CompressionMethod::Stored),
+            level => file_options.compression_method(CompressionMethod::Deflated).compression_level(level),
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file_options = file_options.unix_permissions(metadata.permissions().mode() & 0o777);
+        }
+
+        if metadata.is_dir() {
+            zip.add_directory(format!("{}/", name), file_options)?;
+            add_dir_to_zip(zip, &entry.path(), &format!("{}/", name), options)?;
+        } else if metadata.is_file() {
+            zip.start_file(name, file_options)?;
+            std::io::copy(&mut File::open(entry.path())?, zip)?;
+        }
+    }
+    Ok(())
+}
+
+/// Zip stores local wall-clock times with two second precision and no zone.
+fn zip_time_from_system(time: SystemTime) -> Option<zip::DateTime> {
+    let local = chrono::DateTime::<Local>::from(time);
+    zip::DateTime::from_date_and_time(
+        u16::try_from(local.year()).ok()?,
+        local.month() as u8,
+        local.day() as u8,
+        local.hour() as u8,
+        local.minute() as u8,
+        local.second() as u8,
+    )
+    .ok()
+}
+
+fn system_time_from_zip(time: zip::DateTime) -> Option<SystemTime> {
+    let local = Local
+        .with_ymd_and_hms(
+            time.year().into(),
+            time.month().into(),
+            time.day().into(),
+            time.hour().into(),
+            time.minute().into(),
+            time.second().into(),
+        )
+        .earliest()?;
+    Some(local.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write_zip(path: &Path, entries: &[(&str, &str, u32)]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, contents, mode) in entries {
+            zip.start_file(*name, SimpleFileOptions::default().unix_permissions(*mode)).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn unzip_extracts_everything() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("pack.zip");
+        write_zip(
+            &archive,
+            &[
+                ("options.txt", "fov:90", 0o644),
+                ("config/sodium.json", "{}", 0o644),
+                ("run.sh", "#!/bin/sh", 0o755),
+            ],
+        );
+
+        let dest = dir.path().join("out");
+        let mut written = unzip(&archive, &dest).unwrap();
+        written.sort();
+        assert_eq!(written, [dest.join("config/sodium.json"), dest.join("options.txt"), dest.join("run.sh")]);
+        assert_eq!(std::fs::read(dest.join("options.txt")).unwrap(), b"fov:90");
+        assert_eq!(std::fs::read(dest.join("config/sodium.json")).unwrap(), b"{}");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |name: &str| std::fs::metadata(dest.join(name)).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode("run.sh"), 0o755);
+            assert_eq!(mode("options.txt"), 0o644);
+        }
+    }
+
+    #[test]
+    fn unzip_with_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("pack.zip");
+        write_zip(&archive, &[("overrides/mods/a.jar", "a", 0o644), ("modrinth.index.json", "{}", 0o644)]);
+
+        let dest = dir.path().join("out");
+        let written = unzip_with(&archive, &dest, |path| path.starts_with("overrides")).unwrap();
+        assert_eq!(written, [dest.join("overrides/mods/a.jar")]);
+        assert!(!dest.join("modrinth.index.json").exists());
+    }
+
+    #[test]
+    fn unzip_refuses_zip_slip() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("evil.zip");
+        write_zip(&archive, &[("good.txt", "fine", 0o644), ("../evil", "evil", 0o644)]);
+
+        let dest = dir.path().join("out");
+        assert!(unzip(&archive, &dest).is_err());
+        // Skipping the bad entry is no way around the check
+        assert!(unzip_with(&archive, &dest, |path| path == Path::new("good.txt")).is_err());
+        assert!(!dest.exists());
+        assert!(!dir.path().join("evil").exists());
+    }
+
+    #[test]
+    fn unzip_refuses_absolute_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("evil.zip");
+        write_zip(&archive, &[("/tmp/evil", "evil", 0o644)]);
+        assert!(unzip(&archive, &dir.path().join("out")).is_err());
+    }
+
+    fn fixture_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(src.join("region")).unwrap();
+        std::fs::create_dir_all(src.join("datapacks")).unwrap();
+        std::fs::write(src.join("level.dat"), "level data").unwrap();
+        std::fs::write(src.join("region").join("r.0.0.mca"), vec![7u8; 4096]).unwrap();
+        std::fs::write(src.join("region").join("r.-1.0.mca"), vec![3u8; 512]).unwrap();
+        dir
+    }
+
+    #[test]
+    fn zip_dir_writes_sorted_entries() {
+        let dir = fixture_dir();
+        let archive = dir.path().join("world.zip");
+        zip_dir(&dir.path().join("src"), &archive, ZipOptions::default()).unwrap();
+
+        let zip = ZipArchive::new(File::open(&archive).unwrap()).unwrap();
+        let names: Vec<_> = zip.file_names().collect();
+        assert_eq!(names, ["datapacks/", "level.dat", "region/", "region/r.-1.0.mca", "region/r.0.0.mca"]);
+
+        let out = dir.path().join("out");
+        unzip(&archive, &out).unwrap();
+        assert_eq!(std::fs::read(out.join("region").join("r.0.0.mca")).unwrap(), vec![7u8; 4096]);
+        assert!(out.join("datapacks").is_dir());
+    }
+
+    #[test]
+    fn zip_dir_is_deterministic() {
+        let dir = fixture_dir();
+        let src = dir.path().join("src");
+        let options = ZipOptions {
+            compression_level: Some(9),
+            store_mtimes: false,
+        };
+        zip_dir(&src, &dir.path().join("a.zip"), options).unwrap();
+        // Touching a file changes nothing when mtimes are not stored
+        File::options()
+            .write(true)
+            .open(src.join("level.dat"))
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000))
+            .unwrap();
+        zip_dir(&src, &dir.path().join("b.zip"), options).unwrap();
+
+        assert_eq!(
+            std::fs::read(dir.path().join("a.zip")).unwrap(),
+            std::fs::read(dir.path().join("b.zip")).unwrap()
+        );
+    }
+
+    #[test]
+    fn zip_dir_stored_is_larger() {
+        let dir = fixture_dir();
+        let src = dir.path().join("src");
+        let stored = ZipOptions {
+            compression_level: Some(0),
+            ..ZipOptions::default()
+        };
+        zip_dir(&src, &dir.path().join("stored.zip"), stored).unwrap();
+        zip_dir(&src, &dir.path().join("deflated.zip"), ZipOptions::default()).unwrap();
+
+        let size = |name: &str| std::fs::metadata(dir.path().join(name)).unwrap().len();
+        assert!(size("stored.zip") > 4096 + 512);
+        assert!(size("deflated.zip") < size("stored.zip"));
+        assert!(zip_dir(&src.join("level.dat"), &dir.path().join("bad.zip"), stored).is_err());
+    }
+}
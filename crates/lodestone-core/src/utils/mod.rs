@@ -1 +1,4 @@
+pub mod archive;
 pub mod path_util;
+
+pub use archive::{ZipOptions, unzip, unzip_with, zip_dir};
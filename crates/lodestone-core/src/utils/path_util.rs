@@ -1,9 +1,40 @@
 use anyhow::{Result, anyhow};
 use regex::Regex;
-use std::path::PathBuf;
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 
 static INVALID_FILENAME_CHAR_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"[*:"'\\/?<>|]"#).unwrap());
+static INVALID_FILENAME_RUN_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"[*:"'\\/?<>|]+"#).unwrap());
+
+/// Highest `name (n)` index [`PathUtil::unique`] will probe before giving up.
+pub const DEFAULT_MAX_UNIQUE_ATTEMPTS: usize = 10_000;
+
+/// Device names that Windows refuses to use as a file stem, regardless of extension.
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5",
+    "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Controls how [`PathUtil::clean_with_options`] treats invalid filename characters.
+#[derive(Debug, Clone, Copy)]
+pub struct CleanOptions<'a> {
+    /// Substituted for each invalid character. An empty string removes them outright.
+    pub replacement: &'a str,
+    /// Replace a run of adjacent invalid characters with a single `replacement`,
+    /// so `AC//DC` becomes `AC-DC` rather than `AC--DC`.
+    pub collapse_runs: bool,
+}
+
+impl Default for CleanOptions<'_> {
+    fn default() -> Self {
+        Self {
+            replacement: "",
+            collapse_runs: true,
+        }
+    }
+}
 
 pub trait PathUtil {
     /// Cleans or resets the internal state of the object and returns a reference to itself.
@@ -19,65 +50,232 @@ pub trait PathUtil {
     /// obj.clean();
     /// // The internal state of `obj` is now reset or cleaned.
     /// ```
-    fn clean(&mut self) -> Result<&Self>;
-    /// Ensures that all elements in the collection are unique by removing any duplicate entries.
+    fn clean(&mut self) -> Result<&Self> {
+        self.clean_with("")
+    }
+    /// Like [`PathUtil::clean`], but substitutes `replacement` for invalid characters instead of
+    /// deleting them, collapsing adjacent runs into a single replacement (`AC/DC` -> `AC-DC`).
+    fn clean_with(&mut self, replacement: &str) -> Result<&Self> {
+        self.clean_with_options(CleanOptions {
+            replacement,
+            collapse_runs: true,
+        })
+    }
+    /// Cleans the filename using the given [`CleanOptions`].
     ///
-    /// This method modifies the collection in-place, retaining only the first occurrence of
-    /// each element, and removes subsequent duplicates. The ordering of elements in the collection
-    /// is preserved.
+    /// Fails if the replacement itself contains invalid characters, or if nothing valid is left.
+    fn clean_with_options(&mut self, options: CleanOptions<'_>) -> Result<&Self>;
+    /// Makes the path unique by appending ` (1)`, ` (2)`, ... to the file stem until
+    /// nothing exists at it. A path that does not exist yet is left unchanged.
     ///
-    /// # Returns
-    /// A mutable reference to the collection (`&Self`) after duplicates have been removed.
+    /// Fails once the first [`DEFAULT_MAX_UNIQUE_ATTEMPTS`] indices are all taken, leaving
+    /// the path unchanged.
     ///
     /// # Example
-    /// ```rust
-    /// let mut collection = vec![1, 2, 2, 3, 4, 4, 5];
-    /// collection.unique();
-    /// assert_eq!(collection, vec![1, 2, 3, 4, 5]);
+    /// ```rust,ignore
+    /// let mut path = PathBuf::from("./Cargo.toml");
+    /// path.unique()?;
+    /// assert_eq!(path, PathBuf::from("./Cargo (1).toml"));
     /// ```
-    fn unique(&mut self) -> &Self;
+    fn unique(&mut self) -> Result<&Self> {
+        self.unique_capped(DEFAULT_MAX_UNIQUE_ATTEMPTS)
+    }
+    /// Like [`PathUtil::unique`], but returns an error once every index up to `max_attempts`
+    /// is taken instead of probing forever.
+    fn unique_capped(&mut self, max_attempts: usize) -> Result<&Self>;
+    /// Atomically creates a new file at the first free `name (n).ext` variant of this path.
+    ///
+    /// Unlike [`PathUtil::unique`], the existence check and the creation happen in a single
+    /// `create_new` call, so concurrent callers sharing a base path never receive the same file.
+    /// On success the path is updated to point at the created file.
+    fn unique_create(&mut self) -> Result<std::fs::File>;
+    /// Directory counterpart of [`PathUtil::unique_create`], creating the directory with `create_dir`.
+    fn unique_create_dir(&mut self) -> Result<&Self>;
 }
 
 impl PathUtil for PathBuf {
-    fn clean(&mut self) -> Result<&Self> {
+    fn clean_with_options(&mut self, options: CleanOptions<'_>) -> Result<&Self> {
         if let Some(filename) = self.file_name() {
-            let filename = filename.to_string_lossy();
-            if INVALID_FILENAME_CHAR_RE.is_match(filename.as_ref()) {
-                let clean_name = INVALID_FILENAME_CHAR_RE.replace_all(filename.as_ref(), "");
-                let clean_name = clean_name.trim();
-                if clean_name.is_empty() {
-                    return Err(anyhow!("Path did not contain any valid filename characters"));
-                }
+            let filename = filename.to_string_lossy().into_owned();
+            let clean_name = clean_filename_with(&filename, options)?;
+            if clean_name != filename {
                 *self = self.with_file_name(clean_name);
             }
         }
         Ok(self)
     }
 
-    fn unique(&mut self) -> &Self {
+    fn unique_capped(&mut self, max_attempts: usize) -> Result<&Self> {
         if !self.exists() {
-            return self;
+            return Ok(self);
+        }
+
+        let base = self.clone();
+        let taken = |index: usize| indexed_path(&base, index).exists();
+
+        // Double the probe until a free index turns up, then binary search back down, so a
+        // folder with thousands of collisions costs a few dozen stat calls instead of one per index.
+        let mut occupied = 0;
+        let mut free = 1;
+        while taken(free) {
+            if free >= max_attempts {
+                return Err(anyhow!("No free name for {} within {} attempts", base.display(), max_attempts));
+            }
+            occupied = free;
+            free = (free * 2).min(max_attempts);
+        }
+        while free - occupied > 1 {
+            let mid = occupied + (free - occupied) / 2;
+            if taken(mid) {
+                occupied = mid;
+            } else {
+                free = mid;
+            }
         }
 
-        let stem = self.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
-        let extension = self.extension().map(|e| e.to_string_lossy().into_owned());
-
-        let mut index = 1;
-        while self.exists() {
-            let new_name = match &extension {
-                Some(ext) => format!("{} ({}).{}", stem, index, ext),
-                None => format!("{} ({})", stem, index),
-            };
-            *self = self.with_file_name(new_name);
-            index += 1;
+        *self = indexed_path(&base, free);
+        Ok(self)
+    }
+
+    fn unique_create(&mut self) -> Result<std::fs::File> {
+        let base = self.clone();
+        let mut index = 0;
+        loop {
+            if index > DEFAULT_MAX_UNIQUE_ATTEMPTS {
+                return Err(anyhow!(
+                    "No free name for {} within {} attempts",
+                    base.display(),
+                    DEFAULT_MAX_UNIQUE_ATTEMPTS
+                ));
+            }
+            let candidate = indexed_path(&base, index);
+            match OpenOptions::new().write(true).create_new(true).open(&candidate) {
+                Ok(file) => {
+                    *self = candidate;
+                    return Ok(file);
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => index += 1,
+                Err(e) => return Err(e.into()),
+            }
         }
-        self
+    }
+
+    fn unique_create_dir(&mut self) -> Result<&Self> {
+        let base = self.clone();
+        let mut index = 0;
+        loop {
+            if index > DEFAULT_MAX_UNIQUE_ATTEMPTS {
+                return Err(anyhow!(
+                    "No free name for {} within {} attempts",
+                    base.display(),
+                    DEFAULT_MAX_UNIQUE_ATTEMPTS
+                ));
+            }
+            let candidate = indexed_path(&base, index);
+            match std::fs::create_dir(&candidate) {
+                Ok(()) => {
+                    *self = candidate;
+                    return Ok(self);
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => index += 1,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+/// Treats the string as a single filename component for [`PathUtil::clean`] (so `1.20.1/fabric`
+/// becomes `1.20.1fabric`), and as a path for the `unique*` family.
+impl PathUtil for String {
+    fn clean_with_options(&mut self, options: CleanOptions<'_>) -> Result<&Self> {
+        *self = clean_filename_with(self, options)?;
+        Ok(self)
+    }
+
+    fn unique_capped(&mut self, max_attempts: usize) -> Result<&Self> {
+        let mut path = PathBuf::from(self.as_str());
+        path.unique_capped(max_attempts)?;
+        *self = path.to_string_lossy().into_owned();
+        Ok(self)
+    }
+
+    fn unique_create(&mut self) -> Result<std::fs::File> {
+        let mut path = PathBuf::from(self.as_str());
+        let file = path.unique_create()?;
+        *self = path.to_string_lossy().into_owned();
+        Ok(file)
+    }
+
+    fn unique_create_dir(&mut self) -> Result<&Self> {
+        let mut path = PathBuf::from(self.as_str());
+        path.unique_create_dir()?;
+        *self = path.to_string_lossy().into_owned();
+        Ok(self)
+    }
+}
+
+/// Sanitizes a single filename component, e.g. a loader version used as a folder name.
+///
+/// Strips characters that are invalid on any major platform, trims trailing dots and spaces,
+/// and escapes Windows reserved device names. Fails if nothing valid is left.
+pub fn clean_filename(name: &str) -> Result<String> {
+    clean_filename_with(name, CleanOptions::default())
+}
+
+/// [`clean_filename`] with a custom replacement for invalid characters, see [`CleanOptions`].
+pub fn clean_filename_with(name: &str, options: CleanOptions<'_>) -> Result<String> {
+    if INVALID_FILENAME_CHAR_RE.is_match(options.replacement) {
+        return Err(anyhow!("Replacement '{}' contains invalid filename characters", options.replacement));
+    }
+
+    let mut clean_name = name.to_string();
+    if INVALID_FILENAME_CHAR_RE.is_match(&clean_name) {
+        let pattern = if options.collapse_runs {
+            &INVALID_FILENAME_RUN_RE
+        } else {
+            &INVALID_FILENAME_CHAR_RE
+        };
+        clean_name = pattern.replace_all(&clean_name, options.replacement).trim().to_string();
+    }
+    // Windows silently drops trailing dots and spaces, so strip them up front
+    // to keep the path pointing at the file that actually gets created.
+    let clean_name = clean_name.trim_end_matches(['.', ' ']);
+    if clean_name.is_empty() {
+        return Err(anyhow!("Path did not contain any valid filename characters"));
+    }
+    Ok(escape_reserved_name(clean_name))
+}
+
+/// Builds the `name (index).ext` sibling of `base`, or `base` itself for index `0`.
+fn indexed_path(base: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        return base.to_path_buf();
+    }
+    let stem = base.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let new_name = match base.extension() {
+        Some(ext) => format!("{} ({}).{}", stem, index, ext.to_string_lossy()),
+        None => format!("{} ({})", stem, index),
+    };
+    base.with_file_name(new_name)
+}
+
+/// Appends an underscore to the stem of a Windows reserved device name
+/// (`CON`, `nul.log`, `Com1`, ...) so the file can be created on every platform.
+fn escape_reserved_name(name: &str) -> String {
+    let (stem, rest) = match name.find('.') {
+        Some(index) => name.split_at(index),
+        None => (name, ""),
+    };
+    if WINDOWS_RESERVED_NAMES.iter().any(|reserved| stem.eq_ignore_ascii_case(reserved)) {
+        format!("{}_{}", stem, rest)
+    } else {
+        name.to_string()
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::utils::path_util::PathUtil;
+    use crate::utils::path_util::{CleanOptions, PathUtil, clean_filename};
 
     #[test]
     fn clean_path_name() {
@@ -94,18 +292,186 @@ mod test {
         assert_eq!(path.clean().unwrap(), &std::path::PathBuf::from("/some/filenamehere"));
     }
 
+    #[test]
+    fn clean_trailing_dots_and_spaces() {
+        let mut path = std::path::PathBuf::from("/some/file... ");
+        assert_eq!(path.clean().unwrap(), &std::path::PathBuf::from("/some/file"));
+
+        let mut path = std::path::PathBuf::from("/some/name .");
+        assert_eq!(path.clean().unwrap(), &std::path::PathBuf::from("/some/name"));
+
+        // Interior dots are part of a legitimate extension and must be kept
+        let mut path = std::path::PathBuf::from("/some/archive.tar.gz");
+        assert_eq!(path.clean().unwrap(), &std::path::PathBuf::from("/some/archive.tar.gz"));
+
+        // Nothing left after trimming is still an error
+        let mut path = std::path::PathBuf::from("/some/. .");
+        assert!(path.clean().is_err());
+    }
+
+    #[test]
+    fn clean_reserved_windows_names() {
+        // Bare device names get an underscore appended
+        let mut path = std::path::PathBuf::from("/some/CON");
+        assert_eq!(path.clean().unwrap(), &std::path::PathBuf::from("/some/CON_"));
+
+        // Device names followed by an extension are still reserved
+        let mut path = std::path::PathBuf::from("/some/nul.log");
+        assert_eq!(path.clean().unwrap(), &std::path::PathBuf::from("/some/nul_.log"));
+
+        // Matching is case-insensitive
+        let mut path = std::path::PathBuf::from("/some/Com1");
+        assert_eq!(path.clean().unwrap(), &std::path::PathBuf::from("/some/Com1_"));
+
+        // Names that merely start with a device name are left alone
+        let mut path = std::path::PathBuf::from("/some/console.txt");
+        assert_eq!(path.clean().unwrap(), &std::path::PathBuf::from("/some/console.txt"));
+        let mut path = std::path::PathBuf::from("/some/COM10");
+        assert_eq!(path.clean().unwrap(), &std::path::PathBuf::from("/some/COM10"));
+    }
+
     #[test]
     fn unique_path_name() {
         // Non-existent file stays unchanged
         let mut path = std::path::PathBuf::from("./some/nonexistent/file");
-        assert_eq!(path.unique(), &std::path::PathBuf::from("./some/nonexistent/file"));
+        assert_eq!(path.unique().unwrap(), &std::path::PathBuf::from("./some/nonexistent/file"));
 
         // Existing file gets (1) before extension
         let mut path = std::path::PathBuf::from("./Cargo.toml");
-        assert_eq!(path.unique(), &std::path::PathBuf::from("./Cargo (1).toml"));
+        assert_eq!(path.unique().unwrap(), &std::path::PathBuf::from("./Cargo (1).toml"));
 
         // Directory without extension (src/ exists in this crate)
         let mut path = std::path::PathBuf::from("./src");
-        assert_eq!(path.unique(), &std::path::PathBuf::from("./src (1)"));
+        assert_eq!(path.unique().unwrap(), &std::path::PathBuf::from("./src (1)"));
+    }
+
+    #[test]
+    fn clean_string_name() {
+        // Clean name should remain unchanged
+        let mut name = String::from("file");
+        assert_eq!(name.clean().unwrap(), "file");
+
+        // Separators are invalid characters when the whole string is one component
+        let mut name = String::from("1.20.1/fabric");
+        assert_eq!(name.clean().unwrap(), "1.20.1fabric");
+
+        let mut name = String::from("Super Duper <?>\"");
+        assert_eq!(name.clean().unwrap(), "Super Duper");
+
+        let mut name = String::from("file*name|here");
+        assert_eq!(name.clean().unwrap(), "filenamehere");
+
+        let mut name = String::from("con.txt");
+        assert_eq!(name.clean().unwrap(), "con_.txt");
+
+        let mut name = String::from("<>");
+        assert!(name.clean().is_err());
+
+        assert_eq!(clean_filename("My World ").unwrap(), "My World");
+    }
+
+    #[test]
+    fn unique_string_name() {
+        // Non-existent file stays unchanged
+        let mut name = String::from("./some/nonexistent/file");
+        assert_eq!(name.unique().unwrap(), "./some/nonexistent/file");
+
+        // Existing file gets (1) before extension
+        let mut name = String::from("./Cargo.toml");
+        assert_eq!(name.unique().unwrap(), "./Cargo (1).toml");
+
+        // Directory without extension (src/ exists in this crate)
+        let mut name = String::from("./src");
+        assert_eq!(name.unique().unwrap(), "./src (1)");
+    }
+
+    #[test]
+    fn clean_with_replacement() {
+        let mut path = std::path::PathBuf::from("/music/AC:DC Music");
+        assert_eq!(path.clean_with("_").unwrap(), &std::path::PathBuf::from("/music/AC_DC Music"));
+
+        // An empty replacement behaves exactly like clean()
+        let mut name = String::from("AC/DC Music");
+        assert_eq!(name.clean_with("").unwrap(), "ACDC Music");
+
+        // Runs collapse into a single replacement by default
+        let mut name = String::from("AC//DC");
+        assert_eq!(name.clean_with("-").unwrap(), "AC-DC");
+
+        // ...unless collapsing is turned off
+        let mut name = String::from("AC//DC");
+        let options = CleanOptions {
+            replacement: "-",
+            collapse_runs: false,
+        };
+        assert_eq!(name.clean_with_options(options).unwrap(), "AC--DC");
+
+        // A replacement that is itself invalid is rejected
+        let mut name = String::from("AC/DC");
+        assert!(name.clean_with("/").is_err());
+    }
+
+    #[test]
+    fn unique_capped_errors_when_exhausted() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("crash.txt"), "").unwrap();
+        for index in 1..=20 {
+            std::fs::write(dir.path().join(format!("crash ({}).txt", index)), "").unwrap();
+        }
+
+        let mut path = dir.path().join("crash.txt");
+        assert!(path.unique_capped(20).is_err());
+        assert_eq!(path, dir.path().join("crash.txt"));
+
+        // The probe still lands on the first free index when the cap allows it
+        let mut path = dir.path().join("crash.txt");
+        assert_eq!(path.unique_capped(100).unwrap(), &dir.path().join("crash (21).txt"));
+    }
+
+    #[test]
+    fn unique_create_is_race_free() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("mod.jar");
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let base = base.clone();
+                std::thread::spawn(move || {
+                    let mut path = base;
+                    path.unique_create().unwrap();
+                    path
+                })
+            })
+            .collect();
+        let mut created: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        created.sort();
+        created.dedup();
+
+        assert_eq!(created.len(), 8);
+        assert!(created.contains(&base));
+        assert!(created.contains(&dir.path().join("mod (7).jar")));
+    }
+
+    #[test]
+    fn unique_create_dir_is_race_free() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("World");
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let base = base.clone();
+                std::thread::spawn(move || {
+                    let mut path = base;
+                    path.unique_create_dir().unwrap();
+                    path
+                })
+            })
+            .collect();
+        let mut created: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        created.sort();
+        created.dedup();
+
+        assert_eq!(created.len(), 8);
+        assert!(created.iter().all(|p| p.is_dir()));
     }
 }
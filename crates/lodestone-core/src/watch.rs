@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_stream::Stream;
+
+use crate::instance::{DISABLED_SUFFIX, Instance, ModFile};
+
+/// How long `mods/` has to stay quiet before [`Instance::watch_mods`] re-scans it.
+pub const MODS_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// How `mods/` changed between two scans, reported by [`ModsWatcher`].
+///
+/// A jar is identified by its file name without the `.disabled` suffix, so enabling
+/// or disabling one shows up in `toggled` rather than as a removal and an addition.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ModsChanged {
+    pub added: Vec<ModFile>,
+    pub removed: Vec<ModFile>,
+    /// Jars that were enabled or disabled, in their new state.
+    pub toggled: Vec<ModFile>,
+}
+
+impl ModsChanged {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.toggled.is_empty()
+    }
+
+    /// The difference between two [`Instance::list_mods`] results. A jar replaced under
+    /// the same name with other metadata counts as removed and added again.
+    fn between(before: &[ModFile], after: &[ModFile]) -> Self {
+        let mut changes = Self::default();
+        let mut previous: HashMap<&str, &ModFile> = before.iter().map(|file| (mod_key(file), file)).collect();
+        for file in after {
+            match previous.remove(mod_key(file)) {
+                None => changes.added.push(file.clone()),
+                Some(old) if old.enabled != file.enabled => changes.toggled.push(file.clone()),
+                Some(old) if old != file => {
+                    changes.removed.push(old.clone());
+                    changes.added.push(file.clone());
+                }
+                Some(_) => {}
+            }
+        }
+        changes.removed.extend(previous.into_values().cloned());
+        changes.removed.sort_by(|a, b| a.filename.cmp(&b.filename));
+        changes
+    }
+}
+
+fn mod_key(file: &ModFile) -> &str {
+    file.filename.strip_suffix(DISABLED_SUFFIX).unwrap_or(&file.filename)
+}
+
+/// A stream of [`ModsChanged`] for one instance, from [`Instance::watch_mods`].
+///
+/// Dropping it stops watching the directory.
+pub struct ModsWatcher {
+    changes: mpsc::Receiver<ModsChanged>,
+    scanner: JoinHandle<()>,
+    _watcher: RecommendedWatcher,
+}
+
+impl Stream for ModsWatcher {
+    type Item = ModsChanged;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<ModsChanged>> {
+        self.changes.poll_recv(cx)
+    }
+}
+
+impl Drop for ModsWatcher {
+    fn drop(&mut self) {
+        self.scanner.abort();
+    }
+}
+
+impl Instance {
+    /// Watch `mods/` and report jars being added, removed, enabled or disabled.
+    ///
+    /// Bursts of filesystem events, such as a large jar being copied in, are collapsed
+    /// into one re-scan once the directory has been quiet for [`MODS_DEBOUNCE`]. Scans
+    /// that find nothing changed are not reported. `mods/` is created if it is missing.
+    /// Must be called from within a Tokio runtime.
+    pub fn watch_mods(&self) -> Result<ModsWatcher> {
+        self.watch_mods_with(MODS_DEBOUNCE)
+    }
+
+    /// Like [`Instance::watch_mods`], waiting `debounce` for the directory to settle.
+    pub fn watch_mods_with(&self, debounce: Duration) -> Result<ModsWatcher> {
+        let mods_dir = self.mods_dir();
+        std::fs::create_dir_all(&mods_dir)?;
+
+        let (raw_tx, mut raw_rx) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if event.is_ok() {
+                let _ = raw_tx.send(());
+            }
+        })?;
+        watcher
+            .watch(&mods_dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {}", mods_dir.display()))?;
+        // Scanned after the watch starts, so nothing changed in between goes unnoticed.
+        let mut known = self.list_mods()?;
+
+        let (tx, changes) = mpsc::channel(16);
+        let instance = self.clone();
+        let scanner = tokio::spawn(async move {
+            while raw_rx.recv().await.is_some() {
+                loop {
+                    match tokio::time::timeout(debounce, raw_rx.recv()).await {
+                        Ok(Some(())) => continue,
+                        Ok(None) => return,
+                        Err(_) => break,
+                    }
+                }
+
+                let scan = instance.clone();
+                let current = match tokio::task::spawn_blocking(move || scan.list_mods()).await {
+                    Ok(Ok(current)) => current,
+                    Ok(Err(e)) => {
+                        log::warn!("Failed to re-scan {}: {}", instance.mods_dir().display(), e);
+                        continue;
+                    }
+                    Err(_) => return,
+                };
+                let changed = ModsChanged::between(&known, &current);
+                known = current;
+                if !changed.is_empty() && tx.send(changed).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(ModsWatcher {
+            changes,
+            scanner,
+            _watcher: watcher,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio_stream::StreamExt;
+
+    async fn next_change(watcher: &mut ModsWatcher) -> ModsChanged {
+        tokio::time::timeout(Duration::from_secs(10), watcher.next())
+            .await
+            .expect("no change reported")
+            .expect("watcher ended")
+    }
+
+    fn filenames(files: &[ModFile]) -> Vec<&str> {
+        files.iter().map(|file| file.filename.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn reports_added_toggled_and_removed_jars() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        std::fs::write(instance.mods_dir().join("kept.jar"), b"jar").unwrap();
+        let mut watcher = instance.watch_mods_with(Duration::from_millis(100)).unwrap();
+
+        let jar = instance.mods_dir().join("sodium.jar");
+        std::fs::write(&jar, b"jar").unwrap();
+        let changed = next_change(&mut watcher).await;
+        assert_eq!(filenames(&changed.added), vec!["sodium.jar"]);
+        assert!(changed.removed.is_empty() && changed.toggled.is_empty());
+
+        let disabled = instance.set_mod_enabled(&jar, false).unwrap();
+        let changed = next_change(&mut watcher).await;
+        assert_eq!(filenames(&changed.toggled), vec!["sodium.jar.disabled"]);
+        assert!(!changed.toggled[0].enabled);
+        assert!(changed.added.is_empty() && changed.removed.is_empty());
+
+        std::fs::remove_file(&disabled).unwrap();
+        let changed = next_change(&mut watcher).await;
+        assert_eq!(filenames(&changed.removed), vec!["sodium.jar.disabled"]);
+        assert!(changed.added.is_empty() && changed.toggled.is_empty());
+    }
+
+    #[test]
+    fn diff_ignores_unchanged_jars() {
+        let file = |name: &str, enabled: bool| ModFile {
+            path: name.into(),
+            filename: name.to_string(),
+            enabled,
+            mod_id: None,
+            version: None,
+        };
+        let before = [file("a.jar", true), file("b.jar", true)];
+        assert!(ModsChanged::between(&before, &before).is_empty());
+
+        let after = [file("a.jar.disabled", false), file("c.jar", true)];
+        let changed = ModsChanged::between(&before, &after);
+        assert_eq!(filenames(&changed.added), vec!["c.jar"]);
+        assert_eq!(filenames(&changed.removed), vec!["b.jar"]);
+        assert_eq!(filenames(&changed.toggled), vec!["a.jar.disabled"]);
+    }
+}
@@ -0,0 +1,264 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+
+use crate::instance::Instance;
+
+/// The game mode a world was last saved in, from `GameType` in `level.dat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl GameMode {
+    fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::Survival),
+            1 => Some(Self::Creative),
+            2 => Some(Self::Adventure),
+            3 => Some(Self::Spectator),
+            _ => None,
+        }
+    }
+}
+
+/// A singleplayer world in `saves/`, from [`Instance::list_worlds`].
+///
+/// The metadata fields are None when `level.dat` could not be read or lacks them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct World {
+    /// Folder name under `saves/`, as taken by [`Instance::backup_world`].
+    pub folder: String,
+    pub path: PathBuf,
+    /// Display name, `LevelName`.
+    pub name: Option<String>,
+    pub game_mode: Option<GameMode>,
+    pub last_played: Option<DateTime<Utc>>,
+    pub hardcore: Option<bool>,
+    /// `WorldGenSettings.seed` from 1.16 on, `RandomSeed` before.
+    pub seed: Option<i64>,
+}
+
+impl World {
+    fn read(path: PathBuf) -> Option<Self> {
+        let folder = path.file_name()?.to_str()?.to_string();
+        let level_dat = path.join("level.dat");
+        if !level_dat.is_file() {
+            return None;
+        }
+
+        let mut world = Self {
+            folder,
+            path,
+            name: None,
+            game_mode: None,
+            last_played: None,
+            hardcore: None,
+            seed: None,
+        };
+        match read_level_dat(&level_dat) {
+            Ok(level) => {
+                let data = level.data;
+                world.name = data.level_name;
+                world.game_mode = data.game_type.and_then(GameMode::from_id);
+                world.last_played = data.last_played.and_then(DateTime::from_timestamp_millis);
+                world.hardcore = data.hardcore.map(|hardcore| hardcore != 0);
+                world.seed = data.world_gen_settings.and_then(|settings| settings.seed).or(data.random_seed);
+            }
+            Err(e) => log::warn!("Could not read {}: {:#}", level_dat.display(), e),
+        }
+        Some(world)
+    }
+}
+
+#[derive(Deserialize)]
+struct LevelDat {
+    #[serde(rename = "Data")]
+    data: LevelData,
+}
+
+#[derive(Deserialize)]
+struct LevelData {
+    #[serde(rename = "LevelName")]
+    level_name: Option<String>,
+    #[serde(rename = "GameType")]
+    game_type: Option<i32>,
+    #[serde(rename = "LastPlayed")]
+    last_played: Option<i64>,
+    hardcore: Option<i8>,
+    #[serde(rename = "RandomSeed")]
+    random_seed: Option<i64>,
+    #[serde(rename = "WorldGenSettings")]
+    world_gen_settings: Option<WorldGenSettings>,
+}
+
+#[derive(Deserialize)]
+struct WorldGenSettings {
+    seed: Option<i64>,
+}
+
+fn read_level_dat(path: &Path) -> Result<LevelDat> {
+    let mut nbt = Vec::new();
+    GzDecoder::new(std::fs::File::open(path)?).read_to_end(&mut nbt)?;
+    Ok(fastnbt::from_bytes(&nbt)?)
+}
+
+impl Instance {
+    /// Every world in `saves/` with the metadata a world list shows, most recently
+    /// played first.
+    ///
+    /// Reads `LevelName`, `GameType`, `LastPlayed`, `hardcore` and the seed from each
+    /// world's gzipped NBT `level.dat`. A world whose `level.dat` is corrupt is still
+    /// listed, without metadata; folders with no `level.dat` at all are not worlds and
+    /// are left out. A missing `saves/` directory lists as empty.
+    pub fn list_worlds(&self) -> Result<Vec<World>> {
+        let entries = match std::fs::read_dir(self.saves_dir()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut worlds = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            if let Some(world) = World::read(entry.path()) {
+                worlds.push(world);
+            }
+        }
+        worlds.sort_by(|a, b| b.last_played.cmp(&a.last_played).then_with(|| a.folder.cmp(&b.folder)));
+        Ok(worlds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    /// The `level.dat` of a 1.20 world, trimmed to the fields read here plus a few
+    /// that are not.
+    #[derive(Serialize)]
+    struct FixtureLevel {
+        #[serde(rename = "Data")]
+        data: FixtureData,
+    }
+
+    #[derive(Serialize)]
+    struct FixtureData {
+        #[serde(rename = "LevelName")]
+        level_name: String,
+        #[serde(rename = "GameType")]
+        game_type: i32,
+        #[serde(rename = "LastPlayed")]
+        last_played: i64,
+        hardcore: i8,
+        #[serde(rename = "WorldGenSettings")]
+        world_gen_settings: FixtureWorldGen,
+        #[serde(rename = "DataVersion")]
+        data_version: i32,
+        #[serde(rename = "SpawnX")]
+        spawn_x: i32,
+    }
+
+    #[derive(Serialize)]
+    struct FixtureWorldGen {
+        seed: i64,
+        bonus_chest: i8,
+    }
+
+    fn write_level_dat(world: &Path, level: &FixtureLevel) {
+        std::fs::create_dir_all(world).unwrap();
+        let mut gz = GzEncoder::new(std::fs::File::create(world.join("level.dat")).unwrap(), Compression::default());
+        gz.write_all(&fastnbt::to_bytes(level).unwrap()).unwrap();
+        gz.finish().unwrap();
+    }
+
+    fn fixture_level(name: &str, game_type: i32, last_played: i64, hardcore: bool) -> FixtureLevel {
+        FixtureLevel {
+            data: FixtureData {
+                level_name: name.to_string(),
+                game_type,
+                last_played,
+                hardcore: hardcore as i8,
+                world_gen_settings: FixtureWorldGen {
+                    seed: -4172144997902289642,
+                    bonus_chest: 0,
+                },
+                data_version: 3465,
+                spawn_x: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn list_worlds_reads_level_dat() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        write_level_dat(
+            &instance.saves_dir().join("New World"),
+            &fixture_level("My Survival World", 0, 1_700_000_000_000, false),
+        );
+        write_level_dat(
+            &instance.saves_dir().join("Hardcore"),
+            &fixture_level("Hardcore!", 0, 1_710_000_000_000, true),
+        );
+        write_level_dat(
+            &instance.saves_dir().join("Build"),
+            &fixture_level("Creative Build", 1, 1_600_000_000_000, false),
+        );
+        std::fs::create_dir_all(instance.saves_dir().join("not a world")).unwrap();
+        std::fs::write(instance.saves_dir().join("stray.txt"), b"").unwrap();
+
+        let worlds = instance.list_worlds().unwrap();
+        let folders: Vec<&str> = worlds.iter().map(|w| w.folder.as_str()).collect();
+        assert_eq!(folders, ["Hardcore", "New World", "Build"]);
+
+        let survival = &worlds[1];
+        assert_eq!(survival.name.as_deref(), Some("My Survival World"));
+        assert_eq!(survival.game_mode, Some(GameMode::Survival));
+        assert_eq!(survival.last_played, DateTime::from_timestamp_millis(1_700_000_000_000));
+        assert_eq!(survival.hardcore, Some(false));
+        assert_eq!(survival.seed, Some(-4172144997902289642));
+        assert_eq!(survival.path, instance.saves_dir().join("New World"));
+        assert_eq!(worlds[0].hardcore, Some(true));
+        assert_eq!(worlds[2].game_mode, Some(GameMode::Creative));
+    }
+
+    #[test]
+    fn list_worlds_tolerates_corrupt_level_dat() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::create(dir.path()).unwrap();
+        write_level_dat(&instance.saves_dir().join("Good"), &fixture_level("Good", 2, 1_700_000_000_000, false));
+        let broken = instance.saves_dir().join("Broken");
+        std::fs::create_dir_all(&broken).unwrap();
+        std::fs::write(broken.join("level.dat"), b"definitely not gzip").unwrap();
+
+        let worlds = instance.list_worlds().unwrap();
+        assert_eq!(worlds.len(), 2);
+        assert_eq!(worlds[0].game_mode, Some(GameMode::Adventure));
+        let broken = worlds.iter().find(|w| w.folder == "Broken").unwrap();
+        assert_eq!(broken.name, None);
+        assert_eq!(broken.last_played, None);
+        assert_eq!(broken.seed, None);
+    }
+
+    #[test]
+    fn list_worlds_without_saves_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let instance = Instance::new(dir.path());
+        assert!(instance.list_worlds().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+//! End-to-end test of `Instance::install` against a local mock of Mojang's services.
+//!
+//! Run with `cargo test -p lodestone-core --features integration-tests`.
+#![cfg(feature = "integration-tests")]
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use lodestone_core::install::InstallOptions;
+use lodestone_core::instance::{Instance, LoaderType};
+use lodestone_core::selector::VersionSelector;
+use sha1::{Digest, Sha1};
+use test_support::{MockServer, Response, serve};
+
+type Routes = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+fn sha1_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha1::digest(data))
+}
+
+struct Fixture {
+    base: String,
+    server: MockServer,
+    client_jar: Vec<u8>,
+    library: Vec<u8>,
+    asset: Vec<u8>,
+}
+
+/// A tiny 1.20.4 with one library and one asset, and a manifest listing only it.
+fn fixture() -> Fixture {
+    // Filled in below, once the bodies that link back to the server know its address.
+    let routes = Routes::default();
+    let served = routes.clone();
+    let server = serve(move |request| match served.lock().unwrap().get(&request.path) {
+        Some(body) => Response::ok(body.clone()),
+        None => Response::not_found(),
+    });
+    let base = server.base_url().to_string();
+
+    let client_jar = b"client jar".to_vec();
+    let library = b"library jar".to_vec();
+    let asset = b"asset bytes".to_vec();
+    let asset_hash = sha1_hex(&asset);
+    let asset_index = format!(
+        r#"{{"objects": {{"minecraft/lang/en_us.json": {{"hash": "{}", "size": {}}}}}}}"#,
+        asset_hash,
+        asset.len()
+    )
+    .into_bytes();
+    let version = format!(
+        r#"{{
+            "id": "1.20.4",
+            "type": "release",
+            "mainClass": "net.minecraft.client.main.Main",
+            "releaseTime": "2023-12-07T12:56:20+00:00",
+            "downloads": {{"client": {{"sha1": "{}", "size": {}, "url": "{base}/client.jar"}}}},
+            "assetIndex": {{"id": "12", "sha1": "{}", "size": {}, "totalSize": {}, "url": "{base}/indexes/12.json"}},
+            "libraries": [{{
+                "name": "com.example:tiny:1.0",
+                "downloads": {{"artifact": {{"path": "com/example/tiny/1.0/tiny-1.0.jar", "sha1": "{}", "size": {}, "url": "{base}/libraries/tiny-1.0.jar"}}}}
+            }}]
+        }}"#,
+        sha1_hex(&client_jar),
+        client_jar.len(),
+        sha1_hex(&asset_index),
+        asset_index.len(),
+        asset.len(),
+        sha1_hex(&library),
+        library.len(),
+    );
+    let manifest = format!(
+        r#"{{
+            "latest": {{"release": "1.20.4", "snapshot": "1.20.4"}},
+            "versions": [{{
+                "id": "1.20.4",
+                "type": "release",
+                "url": "{base}/1.20.4.json",
+                "time": "2023-12-07T12:56:20+00:00",
+                "releaseTime": "2023-12-07T12:56:20+00:00"
+            }}]
+        }}"#
+    );
+
+    let mut routes = routes.lock().unwrap();
+    routes.insert("/manifest.json".to_string(), manifest.into_bytes());
+    routes.insert("/1.20.4.json".to_string(), version.into_bytes());
+    routes.insert("/client.jar".to_string(), client_jar.clone());
+    routes.insert("/libraries/tiny-1.0.jar".to_string(), library.clone());
+    routes.insert("/indexes/12.json".to_string(), asset_index);
+    routes.insert(format!("/resources/{}/{}", &asset_hash[..2], asset_hash), asset.clone());
+    drop(routes);
+
+    Fixture {
+        base,
+        server,
+        client_jar,
+        library,
+        asset,
+    }
+}
+
+#[tokio::test]
+async fn install_provisions_fixture_version_and_is_idempotent() {
+    let fixture = fixture();
+    let dir = tempfile::tempdir().unwrap();
+    let instance = Instance::create(dir.path()).unwrap();
+    let options = InstallOptions::new()
+        .with_manifest_url(format!("{}/manifest.json", fixture.base))
+        .with_resources_url(format!("{}/resources", fixture.base));
+
+    let first = instance
+        .install_with(&VersionSelector::LatestRelease, None, None, &options)
+        .await
+        .unwrap();
+    assert_eq!(first.minecraft_version, "1.20.4");
+    assert_eq!(first.loader, LoaderType::Vanilla);
+    assert_eq!(first.loader_version, None);
+
+    let by_path: HashMap<PathBuf, &str> = first.files.iter().map(|f| (f.path.clone(), f.sha1.as_str())).collect();
+    let client_jar = instance.client_jar("1.20.4").unwrap();
+    let client_jar = client_jar.strip_prefix(instance.root()).unwrap();
+    assert_eq!(by_path.get(client_jar), Some(&sha1_hex(&fixture.client_jar).as_str()));
+    assert!(by_path.contains_key(&client_jar.with_extension("json")));
+    let library = first.files.iter().find(|f| f.path.ends_with("tiny-1.0.jar")).expect("library installed");
+    assert_eq!(library.sha1, sha1_hex(&fixture.library));
+    let asset_hash = sha1_hex(&fixture.asset);
+    assert!(first.files.iter().any(|f| f.path.ends_with(&asset_hash) && f.sha1 == asset_hash));
+    assert!(first.files.iter().all(|f| f.path.is_relative()));
+    assert!(first.files.iter().all(|f| dir.path().join(&f.path).is_file()));
+    assert_eq!(first.written().count(), first.files.len());
+
+    let seen = fixture.server.hits();
+    let second = instance
+        .install_with(&VersionSelector::Exact("1.20.4".to_string()), None, None, &options)
+        .await
+        .unwrap();
+    let strip = |files: &[lodestone_core::install::InstalledFile]| files.iter().map(|f| (f.path.clone(), f.sha1.clone(), f.size)).collect::<Vec<_>>();
+    assert_eq!(strip(&first.files), strip(&second.files));
+    // The asset index is always fetched fresh; everything else was already in place.
+    let rewritten: Vec<_> = second.written().collect();
+    assert!(rewritten.iter().all(|f| f.path.starts_with("assets/indexes")), "{rewritten:?}");
+    assert!(
+        !fixture.server.paths()[seen..].iter().any(|path| path == "/client.jar"),
+        "client jar was downloaded again"
+    );
+}
@@ -8,6 +8,7 @@ use tokio::sync::Mutex;
 use lodestone_core::instance::{CreateInstanceParams, InstanceConfig, LoaderType};
 use lodestone_core::instance_manager::InstanceManager;
 
+use minecraft_modloaders::download::{DownloadJob, Downloader};
 use minecraft_modloaders::fabric::FabricVersions;
 use minecraft_modloaders::forge::ForgeVersions;
 use minecraft_modloaders::neoforge::NeoForgeVersions;
@@ -179,7 +180,7 @@ pub async fn get_loader_versions(
                 .map_err(|e| format!("failed to fetch Fabric versions: {e}"))?;
             let loader_versions: Vec<String> =
                 versions.loader.iter().map(|v| v.version.clone()).collect();
-            let recommended = versions.get_latest_loader().map(|v| v.version.clone());
+            let recommended = versions.latest_stable_loader().map(|v| v.version.clone());
             Ok(LoaderVersionsResponse {
                 versions: loader_versions,
                 recommended,
@@ -1033,6 +1034,8 @@ struct ResolvedModFile {
     version_id: String,
     project_name: String,
     icon_url: Option<String>,
+    sha1: Option<String>,
+    sha512: Option<String>,
 }
 
 /// Collect all files to download for a mod + its required dependencies.
@@ -1079,6 +1082,8 @@ fn resolve_mod_files<'a>(
                     version_id: version.id.clone(),
                     project_name: mod_title.clone(),
                     icon_url: icon_url.clone(),
+                    sha1: file.sha1().map(str::to_string),
+                    sha512: file.sha512().map(str::to_string),
                 });
             }
         }
@@ -1111,22 +1116,29 @@ async fn download_mod_files(
     if files.is_empty() {
         return Ok(vec![]);
     }
-    let client = reqwest::Client::new();
-    let mut installed = Vec::new();
-    for f in files {
-        let dest = mods_dir.join(&f.filename);
-        let response = client.get(&f.url).send().await
-            .map_err(|e| format!("failed to download {}: {e}", f.filename))?;
-        if !response.status().is_success() {
-            return Err(format!("download failed for {}: HTTP {}", f.filename, response.status()));
-        }
-        let bytes = response.bytes().await
-            .map_err(|e| format!("failed to read {}: {e}", f.filename))?;
-        std::fs::write(&dest, &bytes)
-            .map_err(|e| format!("failed to write {}: {e}", f.filename))?;
-        installed.push(f.filename.clone());
+    // The downloader verifies sha512 (or sha1) when the platform provides one and
+    // re-downloads corrupt files.
+    let jobs = files
+        .iter()
+        .map(|f| mod_download_job(&f.url, mods_dir.join(&f.filename), f.sha1.as_deref(), f.sha512.as_deref()))
+        .collect();
+    let summary = Downloader::new().download(jobs).await;
+    if let Some(failure) = summary.failed.first() {
+        return Err(format!("failed to download {}: {:#}", failure.job.url, failure.error));
     }
-    Ok(installed)
+    Ok(files.iter().map(|f| f.filename.clone()).collect())
+}
+
+/// A download job for a mod file, verified against whichever hashes are known.
+fn mod_download_job(url: &str, dest: PathBuf, sha1: Option<&str>, sha512: Option<&str>) -> DownloadJob {
+    let mut job = DownloadJob::new(url, dest);
+    if let Some(sha1) = sha1 {
+        job = job.with_sha1(sha1);
+    }
+    if let Some(sha512) = sha512 {
+        job = job.with_sha512(sha512);
+    }
+    job
 }
 
 #[tauri::command]
@@ -1229,6 +1241,8 @@ pub async fn install_mod_version(
                     version_id: version.id.clone(),
                     project_name: version.name.clone(),
                     icon_url: primary_icon.clone(),
+                    sha1: file.sha1().map(str::to_string),
+                    sha512: file.sha512().map(str::to_string),
                 });
             }
         }
@@ -1330,6 +1344,8 @@ pub async fn replace_mod_version(
                 version_id: version.id.clone(),
                 project_name: version.name.clone(),
                 icon_url: replace_icon.clone(),
+                sha1: file.sha1().map(str::to_string),
+                sha512: file.sha512().map(str::to_string),
             });
         }
     }
@@ -1535,7 +1551,7 @@ async fn install_modpack_from_archive(
     let total = downloadable.len();
 
     if total > 0 {
-        let client = &http_client;
+        let downloader = Downloader::new().with_client(http_client.clone());
         let sem = Arc::new(tokio::sync::Semaphore::new(8));
 
         for (i, pack_file) in downloadable.iter().enumerate() {
@@ -1566,21 +1582,19 @@ async fn install_modpack_from_archive(
 
             let mut downloaded = false;
             for url in &pack_file.download_urls {
-                match client.get(url).send().await {
-                    Ok(resp) if resp.status().is_success() => {
-                        let bytes = resp.bytes().await
-                            .map_err(|e| format!("failed to read {file_name}: {e}"))?;
-                        std::fs::write(&dest, &bytes)
-                            .map_err(|e| format!("failed to write {file_name}: {e}"))?;
-                        downloaded = true;
-                        break;
-                    }
-                    Ok(resp) => {
-                        log::warn!("download failed for {file_name} from {url}: HTTP {}", resp.status());
-                    }
-                    Err(e) => {
-                        log::warn!("download error for {file_name} from {url}: {e}");
-                    }
+                let job = mod_download_job(
+                    url,
+                    dest.clone(),
+                    pack_file.hashes.get("sha1").map(String::as_str),
+                    pack_file.hashes.get("sha512").map(String::as_str),
+                );
+                let summary = downloader.download(vec![job]).await;
+                if summary.is_success() {
+                    downloaded = true;
+                    break;
+                }
+                for failure in &summary.failed {
+                    log::warn!("download failed for {file_name} from {url}: {:#}", failure.error);
                 }
             }
             if !downloaded {
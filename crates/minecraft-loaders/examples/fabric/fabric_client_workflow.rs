@@ -47,12 +47,12 @@ async fn main() -> anyhow::Result<()> {
     let fabric_versions = FabricVersions::fetch().await?;
 
     let mc_version = fabric_versions
-        .get_latest_game_version()
+        .latest_stable_game()
         .expect("No game version found")
         .version
         .clone();
     let loader_version = fabric_versions
-        .get_latest_loader()
+        .latest_stable_loader()
         .expect("No loader version found")
         .version
         .clone();
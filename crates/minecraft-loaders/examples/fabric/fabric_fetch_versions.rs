@@ -8,15 +8,15 @@ async fn main() -> anyhow::Result<()> {
 
     println!("\n=== Latest Stable Versions ===");
 
-    if let Some(game) = versions.get_latest_game_version() {
+    if let Some(game) = versions.latest_stable_game() {
         println!("Latest Game Version: {}", game.version);
     }
 
-    if let Some(loader) = versions.get_latest_loader() {
+    if let Some(loader) = versions.latest_stable_loader() {
         println!("Latest Loader Version: {} (build {})", loader.version, loader.build);
     }
 
-    if let Some(installer) = versions.get_latest_installer() {
+    if let Some(installer) = versions.latest_installer() {
         println!("Latest Installer Version: {}", installer.version);
     }
 
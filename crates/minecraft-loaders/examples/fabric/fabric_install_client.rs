@@ -11,10 +11,10 @@ async fn main() -> anyhow::Result<()> {
 
     // Get the latest stable versions
     let game_version = versions
-        .get_latest_game_version()
+        .latest_stable_game()
         .expect("No stable game version found");
     let loader_version = versions
-        .get_latest_loader()
+        .latest_stable_loader()
         .expect("No stable loader version found");
 
     println!("Minecraft Version: {}", game_version.version);
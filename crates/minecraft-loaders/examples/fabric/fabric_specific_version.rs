@@ -26,7 +26,7 @@ async fn main() -> anyhow::Result<()> {
         .find_loader(loader_version_string)
         .or_else(|| {
             println!("Exact loader version not found, using latest stable...");
-            versions.get_latest_loader()
+            versions.latest_stable_loader()
         })
         .expect("No loader version found");
 
@@ -0,0 +1,273 @@
+//! On-disk caching of metadata responses such as version lists and manifests.
+//!
+//! Cached bodies are stored verbatim, one file per URL, so a cache directory can be
+//! shared between every fetch in this crate. In [offline mode](crate::http::is_offline)
+//! cached copies are used regardless of their age and nothing is requested.
+//!
+//! The `ETag` and `Last-Modified` headers of a cached response are kept next to it in
+//! a `.validators.json` file. Refreshing a stale copy sends them back as
+//! `If-None-Match` and `If-Modified-Since`, and a `304 Not Modified` answer keeps the
+//! cached body and restarts its max age, so unchanged files are not downloaded again.
+
+use anyhow::{Result, anyhow};
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, HeaderMap, HeaderName, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::fs;
+
+use crate::LoaderError;
+use crate::http;
+
+/// The cache file name for `url`, `<prefix>-<sha1 of url>.json`.
+///
+/// SHA-1 rather than `std`'s hasher, whose output may change between Rust releases and
+/// would leave existing caches behind.
+pub(crate) fn file_name(prefix: &str, url: &str) -> String {
+    format!("{}-{}.json", prefix, crate::download::sha1_hex(url.as_bytes()))
+}
+
+/// Fetches the JSON at `url`, keeping a copy of the response body at `path`.
+///
+/// A cached copy younger than `max_age` is returned without touching the network.
+/// If the cache is stale the URL is queried again, conditionally when the copy has
+/// validators, and if that request fails the stale copy is returned instead. When
+/// `offline` is set any cached copy is used and a missing one is a [`LoaderError::Offline`].
+pub(crate) async fn fetch_json<T: DeserializeOwned>(url: &str, path: &Path, max_age: Duration, offline: bool) -> Result<T> {
+    if offline {
+        return match read(path, None).await {
+            Some(cached) => Ok(cached),
+            None => Err(LoaderError::Offline { url: url.to_string() }.into()),
+        };
+    }
+
+    if let Some(cached) = read(path, Some(max_age)).await {
+        return Ok(cached);
+    }
+
+    let stale = read(path, None).await;
+    let validators = match stale {
+        Some(_) => Validators::read(path).await,
+        None => Validators::default(),
+    };
+    match fetch_body(url, &validators).await {
+        Ok(Fetched::NotModified) => {
+            touch(path).await?;
+            stale.ok_or_else(|| anyhow!("{} answered 304 Not Modified without a cached copy", url))
+        }
+        Ok(Fetched::Modified { body, value, validators }) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            fs::write(path, body).await?;
+            validators.write(path).await?;
+            Ok(value)
+        }
+        Err(err) => stale.ok_or(err),
+    }
+}
+
+/// The response headers needed to ask whether a cached body is still current.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: HeaderName| headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string);
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// The conditional request headers these validators translate to.
+    fn request_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let pairs = [(IF_NONE_MATCH, &self.etag), (IF_MODIFIED_SINCE, &self.last_modified)];
+        for (name, value) in pairs {
+            if let Some(value) = value.as_deref().and_then(|value| HeaderValue::from_str(value).ok()) {
+                headers.insert(name, value);
+            }
+        }
+        headers
+    }
+
+    /// The validators stored for the cached body at `path`; none if there are none.
+    async fn read(path: &Path) -> Self {
+        match fs::read_to_string(validators_path(path)).await {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Stores the validators for the cached body at `path`, or removes stale ones
+    /// when the server sent none.
+    async fn write(&self, path: &Path) -> Result<()> {
+        let validators_path = validators_path(path);
+        if self.is_empty() {
+            return match fs::remove_file(&validators_path).await {
+                Ok(()) => Ok(()),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(err) => Err(err.into()),
+            };
+        }
+        fs::write(&validators_path, serde_json::to_vec(self)?).await?;
+        Ok(())
+    }
+}
+
+/// Where the validators of the cached body at `path` are kept.
+fn validators_path(path: &Path) -> PathBuf {
+    path.with_extension("validators.json")
+}
+
+/// Restarts the max age of the cached body at `path`.
+async fn touch(path: &Path) -> Result<()> {
+    let file = fs::OpenOptions::new().write(true).open(path).await?;
+    file.into_std().await.set_modified(SystemTime::now())?;
+    Ok(())
+}
+
+enum Fetched<T> {
+    NotModified,
+    Modified { body: String, value: T, validators: Validators },
+}
+
+/// Reads a cached response, ignoring it if it is older than `max_age`.
+async fn read<T: DeserializeOwned>(path: &Path, max_age: Option<Duration>) -> Option<T> {
+    if let Some(max_age) = max_age {
+        let modified = fs::metadata(path).await.ok()?.modified().ok()?;
+        if !modified.elapsed().is_ok_and(|age| age < max_age) {
+            return None;
+        }
+    }
+    let content = fs::read_to_string(path).await.ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Downloads the raw response body and parses it, so only valid payloads get cached.
+/// Non-empty `validators` make the request conditional.
+async fn fetch_body<T: DeserializeOwned>(url: &str, validators: &Validators) -> Result<Fetched<T>> {
+    let response = http::get_with_headers(url, &validators.request_headers()).await?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::NotModified);
+    }
+    let validators = Validators::from_headers(response.headers());
+    let body = response.text().await?;
+    let value = serde_json::from_str(&body)?;
+    Ok(Fetched::Modified { body, value, validators })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Nothing listens on the discard port, so any request to it fails fast.
+    const UNREACHABLE_URL: &str = "http://127.0.0.1:9/versions.json";
+
+    #[tokio::test]
+    async fn test_offline_uses_stale_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(file_name("test", UNREACHABLE_URL));
+        fs::write(&path, r#"{"id": "cached"}"#).await.unwrap();
+
+        let value: serde_json::Value = fetch_json(UNREACHABLE_URL, &path, Duration::ZERO, true).await.unwrap();
+        assert_eq!(value["id"], "cached");
+    }
+
+    #[tokio::test]
+    async fn test_offline_without_cache_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(file_name("test", UNREACHABLE_URL));
+
+        let err = fetch_json::<serde_json::Value>(UNREACHABLE_URL, &path, Duration::from_secs(3600), true)
+            .await
+            .unwrap_err();
+        match err.downcast_ref::<LoaderError>() {
+            Some(LoaderError::Offline { url }) => assert_eq!(url, UNREACHABLE_URL),
+            other => panic!("expected an offline error, got {:?}", other),
+        }
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_online_falls_back_to_stale_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(file_name("test", UNREACHABLE_URL));
+        assert!(
+            fetch_json::<serde_json::Value>(UNREACHABLE_URL, &path, Duration::ZERO, false)
+                .await
+                .is_err()
+        );
+
+        fs::write(&path, r#"{"id": "stale"}"#).await.unwrap();
+        let value: serde_json::Value = fetch_json(UNREACHABLE_URL, &path, Duration::ZERO, false).await.unwrap();
+        assert_eq!(value["id"], "stale");
+    }
+
+    #[tokio::test]
+    async fn test_not_modified_reuses_cache_and_bumps_ttl() {
+        use test_support::{Response, serve};
+
+        // Answers the first request with a body and an ETag, and later ones that send the
+        // ETag back with 304.
+        let server = serve(|request| {
+            if request.header("if-none-match") == Some("\"v1\"") {
+                Response::status(304).with_header("ETag", "\"v1\"")
+            } else {
+                Response::ok(r#"{"id": "fresh"}"#)
+                    .with_header("ETag", "\"v1\"")
+                    .with_header("Last-Modified", "Wed, 01 May 2024 10:00:00 GMT")
+            }
+        });
+        let url = server.url("/version_manifest.json");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(file_name("test", &url));
+        let max_age = Duration::from_secs(3600);
+        let value: serde_json::Value = fetch_json(&url, &path, max_age, false).await.unwrap();
+        assert_eq!(value["id"], "fresh");
+        assert_eq!(
+            Validators::read(&path).await,
+            Validators {
+                etag: Some("\"v1\"".to_string()),
+                last_modified: Some("Wed, 01 May 2024 10:00:00 GMT".to_string()),
+            }
+        );
+
+        // Age the copy past `max_age`, and mark the body so a re-download would show.
+        fs::write(&path, r#"{"id": "cached"}"#).await.unwrap();
+        let expired = SystemTime::now() - Duration::from_secs(7200);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(expired).unwrap();
+
+        let value: serde_json::Value = fetch_json(&url, &path, max_age, false).await.unwrap();
+        assert_eq!(value["id"], "cached");
+        let age = std::fs::metadata(&path).unwrap().modified().unwrap().elapsed().unwrap();
+        assert!(age < Duration::from_secs(60), "TTL not bumped: {:?}", age);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].header("if-modified-since"), Some("Wed, 01 May 2024 10:00:00 GMT"));
+
+        // Fresh again, so answered from disk.
+        let value: serde_json::Value = fetch_json(&url, &path, max_age, false).await.unwrap();
+        assert_eq!(value["id"], "cached");
+        assert_eq!(server.hits(), 2);
+    }
+
+    #[test]
+    fn test_file_name_includes_prefix_and_url() {
+        assert!(file_name("fabric-versions", UNREACHABLE_URL).starts_with("fabric-versions-"));
+        assert_ne!(file_name("test", UNREACHABLE_URL), file_name("test", "https://example.com/"));
+        assert_eq!(file_name("test", UNREACHABLE_URL), file_name("test", UNREACHABLE_URL));
+        assert_eq!(file_name("test", UNREACHABLE_URL), "test-35317aadc7828c4427362fad0eec24845adf6fe1.json");
+    }
+}
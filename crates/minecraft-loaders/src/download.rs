@@ -0,0 +1,1177 @@
+use anyhow::{Context, Result, anyhow};
+use sha1::{Digest, Sha1};
+use sha2::Sha512;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+pub use tokio_util::sync::CancellationToken;
+
+use reqwest::StatusCode;
+use reqwest::header::{ACCEPT_RANGES, CONTENT_RANGE};
+
+use crate::{LoaderError, http};
+
+/// Default number of files downloaded at the same time.
+pub const DEFAULT_CONCURRENCY: usize = 8;
+/// Default number of times a file is downloaded before a hash mismatch is an error.
+pub const DEFAULT_VERIFY_ATTEMPTS: u32 = 3;
+/// Default number of times a download cut off mid-transfer is resumed or restarted.
+pub const DEFAULT_RESUME_ATTEMPTS: u32 = 3;
+
+/// A single file to download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadJob {
+    pub url: String,
+    pub dest: PathBuf,
+    /// Expected SHA-1 of the file. When the destination already exists with this
+    /// hash the download is skipped, and a downloaded file that does not match it
+    /// is fetched again.
+    pub sha1: Option<String>,
+    /// Expected SHA-512 of the file, as published by Modrinth. Checked instead of
+    /// `sha1` when both are set.
+    pub sha512: Option<String>,
+    /// Expected size in bytes, used for progress totals. A downloaded file of any
+    /// other size is fetched again, like a hash mismatch.
+    pub size: Option<u64>,
+}
+
+impl DownloadJob {
+    pub fn new(url: impl Into<String>, dest: impl Into<PathBuf>) -> Self {
+        Self {
+            url: url.into(),
+            dest: dest.into(),
+            sha1: None,
+            sha512: None,
+            size: None,
+        }
+    }
+
+    pub fn with_sha1(mut self, sha1: impl Into<String>) -> Self {
+        self.sha1 = Some(sha1.into());
+        self
+    }
+
+    pub fn with_sha512(mut self, sha512: impl Into<String>) -> Self {
+        self.sha512 = Some(sha512.into());
+        self
+    }
+
+    /// The strongest hash the job can be verified against.
+    pub fn expected_hash(&self) -> Option<(HashAlgo, &str)> {
+        match (&self.sha512, &self.sha1) {
+            (Some(sha512), _) => Some((HashAlgo::Sha512, sha512)),
+            (None, Some(sha1)) => Some((HashAlgo::Sha1, sha1)),
+            (None, None) => None,
+        }
+    }
+
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+}
+
+/// A snapshot of a running [`Downloader::download`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// Files finished so far, whether downloaded, skipped or failed.
+    pub files_completed: usize,
+    pub files_total: usize,
+    pub bytes_downloaded: u64,
+    /// Sum of the known job sizes; jobs without a size do not contribute.
+    pub bytes_total: u64,
+}
+
+/// Returned when an operation stopped because its [`CancellationToken`] was cancelled,
+/// so callers can tell a user abort apart from a failure with
+/// `err.downcast_ref::<Cancelled>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Cancelled")]
+pub struct Cancelled;
+
+/// A job that could not be downloaded.
+#[derive(Debug)]
+pub struct DownloadFailure {
+    pub job: DownloadJob,
+    pub error: anyhow::Error,
+}
+
+/// The outcome of a [`Downloader::download`] call.
+#[derive(Debug, Default)]
+pub struct DownloadSummary {
+    pub succeeded: Vec<DownloadJob>,
+    /// Jobs whose destination already had the expected SHA-1.
+    pub skipped: Vec<DownloadJob>,
+    pub failed: Vec<DownloadFailure>,
+    /// Jobs that had not finished when the [cancellation token](Downloader::with_cancellation)
+    /// was cancelled.
+    pub cancelled: Vec<DownloadJob>,
+}
+
+impl DownloadSummary {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty() && self.cancelled.is_empty()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        !self.cancelled.is_empty()
+    }
+}
+
+/// How a single job of a [`Downloader::download`] call ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    Downloaded,
+    /// The destination already had the expected hash, or in offline mode exists without one.
+    Skipped,
+    Failed,
+}
+
+type ProgressCallback = Arc<dyn Fn(DownloadProgress) + Send + Sync>;
+type FileCallback = Arc<dyn Fn(&DownloadJob, FileStatus) + Send + Sync>;
+
+/// Downloads batches of files with bounded concurrency.
+///
+/// Files are written to a `.part` file next to the destination and only renamed once
+/// they pass verification. A transfer cut off mid-way continues from the bytes already
+/// received when the server accepts byte ranges (`Accept-Ranges: bytes`), and starts
+/// over otherwise.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use minecraft_modloaders::download::{DownloadJob, Downloader};
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let jobs = vec![DownloadJob::new("https://example.com/a.jar", "libraries/a.jar")];
+/// let summary = Downloader::new()
+///     .with_concurrency(16)
+///     .on_progress(|p| println!("{}/{} files", p.files_completed, p.files_total))
+///     .download(jobs)
+///     .await;
+/// assert!(summary.is_success());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Downloader {
+    client: reqwest::Client,
+    concurrency: usize,
+    verify_attempts: u32,
+    resume_attempts: u32,
+    rate_limit: Option<Arc<RateLimiter>>,
+    offline: bool,
+    cancel: Option<CancellationToken>,
+    on_progress: Option<ProgressCallback>,
+    on_file: Option<FileCallback>,
+}
+
+impl Default for Downloader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Downloader {
+    /// A downloader using the crate's shared HTTP client and its offline setting.
+    pub fn new() -> Self {
+        Self {
+            client: http::client().clone(),
+            concurrency: DEFAULT_CONCURRENCY,
+            verify_attempts: DEFAULT_VERIFY_ATTEMPTS,
+            resume_attempts: DEFAULT_RESUME_ATTEMPTS,
+            rate_limit: None,
+            offline: http::is_offline(),
+            cancel: None,
+            on_progress: None,
+            on_file: None,
+        }
+    }
+
+    /// Use a custom `reqwest::Client` (e.g. with proxy or custom headers).
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// The client downloads go through, for other requests that belong to the same job.
+    pub(crate) fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Set how many files may download at once (default: 8, minimum: 1).
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set how many times a file whose SHA-1 does not match is downloaded before the
+    /// job fails (default: 3, minimum: 1).
+    pub fn with_verify_attempts(mut self, attempts: u32) -> Self {
+        self.verify_attempts = attempts.max(1);
+        self
+    }
+
+    /// Set how many times a download interrupted mid-transfer is resumed, or restarted
+    /// when the server does not support ranges, before the job fails (default: 3).
+    ///
+    /// The `.part` file of a job that still fails is kept, so a later download of a
+    /// job with an expected hash picks up where it stopped.
+    pub fn with_resume_attempts(mut self, attempts: u32) -> Self {
+        self.resume_attempts = attempts;
+        self
+    }
+
+    /// Cap the combined download speed of all files at `bytes_per_second`; `None`
+    /// (the default) or `Some(0)` means unlimited.
+    ///
+    /// The limit is shared by every concurrent transfer and by clones of this
+    /// downloader, and allows a burst of up to one second's worth of bytes.
+    pub fn with_rate_limit(mut self, bytes_per_second: Option<u64>) -> Self {
+        self.rate_limit = bytes_per_second.filter(|limit| *limit > 0).map(|limit| Arc::new(RateLimiter::new(limit)));
+        self
+    }
+
+    /// Only use files already on disk (default: [`http::is_offline`]).
+    ///
+    /// A destination that exists counts as skipped when it matches its expected hash,
+    /// or has none; every other job fails with [`LoaderError::Offline`].
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Stop as soon as `token` is cancelled.
+    ///
+    /// Transfers in flight are dropped mid-body and their `.part` files removed, and
+    /// jobs still waiting for a slot never start; both end up in
+    /// [`DownloadSummary::cancelled`]. A destination is only ever replaced by a
+    /// complete, verified file, so none is left half-written.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Call `callback` whenever bytes are received or a file finishes.
+    ///
+    /// Bytes of attempts discarded for a SHA-1 mismatch are still counted, so
+    /// `bytes_downloaded` can exceed `bytes_total`.
+    pub fn on_progress(mut self, callback: impl Fn(DownloadProgress) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Call `callback` once per job as soon as it finishes, including jobs skipped
+    /// because the file was already up to date. Cancelled jobs do not finish and are
+    /// not reported.
+    pub fn on_file(mut self, callback: impl Fn(&DownloadJob, FileStatus) + Send + Sync + 'static) -> Self {
+        self.on_file = Some(Arc::new(callback));
+        self
+    }
+
+    /// Download every job, returning which ones succeeded, were skipped, failed or
+    /// were cancelled.
+    ///
+    /// A failing job does not stop the others.
+    pub async fn download(&self, jobs: Vec<DownloadJob>) -> DownloadSummary {
+        let tracker = Arc::new(Tracker {
+            files_completed: AtomicUsize::new(0),
+            files_total: jobs.len(),
+            bytes_downloaded: AtomicU64::new(0),
+            bytes_total: jobs.iter().filter_map(|job| job.size).sum(),
+            on_progress: self.on_progress.clone(),
+        });
+        let semaphore = Arc::new(Semaphore::new(self.concurrency));
+        let mut tasks = JoinSet::new();
+
+        for job in jobs {
+            let client = self.client.clone();
+            let semaphore = semaphore.clone();
+            let tracker = tracker.clone();
+            let attempts = Attempts {
+                verify: self.verify_attempts,
+                resume: self.resume_attempts,
+            };
+            let offline = self.offline;
+            let rate_limit = self.rate_limit.clone();
+            let on_file = self.on_file.clone();
+            let cancel = self.cancel.clone();
+            tasks.spawn(async move {
+                let mut started = false;
+                let run = async {
+                    let _permit = semaphore.acquire_owned().await.expect("download semaphore is never closed");
+                    started = true;
+                    download_job(&client, &job, &tracker, rate_limit.as_deref(), attempts, offline).await
+                };
+                let outcome = match &cancel {
+                    Some(token) => token.run_until_cancelled(run).await,
+                    None => Some(run.await),
+                };
+                let Some(outcome) = outcome else {
+                    // Only the transfer that was cut off wrote to the partial file; one
+                    // left by an earlier run for a job that never started is kept.
+                    if started {
+                        let _ = fs::remove_file(partial_path(&job.dest)).await;
+                    }
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(url = %job.url, "download cancelled");
+                    return (job, None);
+                };
+                #[cfg(feature = "tracing")]
+                match &outcome {
+                    Ok(Outcome::Downloaded) => tracing::debug!(url = %job.url, size = ?job.size, "downloaded"),
+                    Ok(Outcome::Skipped) => tracing::trace!(url = %job.url, "up to date, skipped"),
+                    Err(error) => tracing::warn!(url = %job.url, error = %format!("{:#}", error), "download failed"),
+                }
+                if let Some(callback) = &on_file {
+                    let status = match &outcome {
+                        Ok(Outcome::Downloaded) => FileStatus::Downloaded,
+                        Ok(Outcome::Skipped) => FileStatus::Skipped,
+                        Err(_) => FileStatus::Failed,
+                    };
+                    callback(&job, status);
+                }
+                tracker.file_completed();
+                (job, Some(outcome))
+            });
+        }
+
+        let mut summary = DownloadSummary::default();
+        while let Some(result) = tasks.join_next().await {
+            let (job, outcome) = match result {
+                Ok(finished) => finished,
+                Err(err) => std::panic::resume_unwind(err.into_panic()),
+            };
+            match outcome {
+                Some(Ok(Outcome::Downloaded)) => summary.succeeded.push(job),
+                Some(Ok(Outcome::Skipped)) => summary.skipped.push(job),
+                Some(Err(error)) => summary.failed.push(DownloadFailure { job, error }),
+                None => summary.cancelled.push(job),
+            }
+        }
+        summary
+    }
+}
+
+enum Outcome {
+    Downloaded,
+    Skipped,
+}
+
+#[derive(Clone, Copy)]
+struct Attempts {
+    verify: u32,
+    resume: u32,
+}
+
+/// A token bucket shared by every transfer of a [`Downloader`], so the limit caps
+/// the combined throughput rather than each file's.
+///
+/// Transfers take bytes after receiving them and may drive the bucket negative; the
+/// taker then sleeps until the debt is paid, which also holds back everyone after it.
+struct RateLimiter {
+    bytes_per_second: u64,
+    bucket: std::sync::Mutex<Bucket>,
+}
+
+struct Bucket {
+    available: f64,
+    refilled: tokio::time::Instant,
+}
+
+impl RateLimiter {
+    fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second,
+            bucket: std::sync::Mutex::new(Bucket {
+                available: bytes_per_second as f64,
+                refilled: tokio::time::Instant::now(),
+            }),
+        }
+    }
+
+    async fn acquire(&self, bytes: u64) {
+        let rate = self.bytes_per_second as f64;
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let now = tokio::time::Instant::now();
+            let refill = now.duration_since(bucket.refilled).as_secs_f64() * rate;
+            bucket.available = (bucket.available + refill).min(rate) - bytes as f64;
+            bucket.refilled = now;
+            (bucket.available < 0.0).then(|| Duration::from_secs_f64(-bucket.available / rate))
+        };
+        if let Some(wait) = wait {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+struct Tracker {
+    files_completed: AtomicUsize,
+    files_total: usize,
+    bytes_downloaded: AtomicU64,
+    bytes_total: u64,
+    on_progress: Option<ProgressCallback>,
+}
+
+impl Tracker {
+    fn add_bytes(&self, bytes: u64) {
+        self.bytes_downloaded.fetch_add(bytes, Ordering::Relaxed);
+        self.report();
+    }
+
+    fn file_completed(&self) {
+        self.files_completed.fetch_add(1, Ordering::Relaxed);
+        self.report();
+    }
+
+    fn report(&self) {
+        if let Some(callback) = &self.on_progress {
+            callback(DownloadProgress {
+                files_completed: self.files_completed.load(Ordering::Relaxed),
+                files_total: self.files_total,
+                bytes_downloaded: self.bytes_downloaded.load(Ordering::Relaxed),
+                bytes_total: self.bytes_total,
+            });
+        }
+    }
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "download", level = "debug", skip_all, fields(url = %job.url, dest = %job.dest.display(), size = ?job.size))
+)]
+async fn download_job(
+    client: &reqwest::Client,
+    job: &DownloadJob,
+    tracker: &Tracker,
+    rate_limit: Option<&RateLimiter>,
+    attempts: Attempts,
+    offline: bool,
+) -> Result<Outcome> {
+    let expected_hash = job.expected_hash();
+    let up_to_date = match expected_hash {
+        Some((algo, expected)) => verify_hash(&job.dest, algo, expected).await?,
+        None => offline && fs::try_exists(&job.dest).await?,
+    };
+    if up_to_date {
+        return Ok(Outcome::Skipped);
+    }
+    if offline {
+        return Err(LoaderError::Offline { url: job.url.clone() }.into());
+    }
+
+    if let Some(parent) = job.dest.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    // Write next to the destination first so an interrupted or corrupt download
+    // never leaves a bad file under the final name.
+    let partial = partial_path(&job.dest);
+    let algo = expected_hash.map_or(HashAlgo::Sha1, |(algo, _)| algo);
+    // A partial file left by an earlier run is only continued when a hash can vouch
+    // for the result.
+    let mut resume = expected_hash.is_some() && fs::try_exists(&partial).await?;
+    let mut interruptions = 0;
+    let mut attempt = 1;
+    loop {
+        let offset = if resume {
+            fs::metadata(&partial).await.map_or(0, |metadata| metadata.len())
+        } else {
+            0
+        };
+        let (actual, written) = match fetch_to(client, &job.url, &partial, offset, algo, tracker, rate_limit).await? {
+            Fetch::Complete { hash, size } => (hash, size),
+            Fetch::Interrupted { error, resumable } => {
+                interruptions += 1;
+                if interruptions > attempts.resume {
+                    return Err(error.context(format!("Giving up on {} after {} interruptions", job.url, interruptions)));
+                }
+                resume = resumable;
+                continue;
+            }
+        };
+
+        let mismatch = match (expected_hash, job.size) {
+            (Some((_, expected)), _) if !actual.eq_ignore_ascii_case(expected) => Some(format!(
+                "{} mismatch for {} after {} attempts: expected {}, got {}",
+                algo, job.url, attempts.verify, expected, actual
+            )),
+            (_, Some(size)) if written != size => Some(format!(
+                "Size mismatch for {} after {} attempts: expected {} bytes, got {}",
+                job.url, attempts.verify, size, written
+            )),
+            _ => None,
+        };
+        match mismatch {
+            Some(message) => {
+                fs::remove_file(&partial).await?;
+                if attempt >= attempts.verify {
+                    return Err(anyhow!(message));
+                }
+                attempt += 1;
+                resume = false;
+            }
+            None => {
+                fs::rename(&partial, &job.dest).await?;
+                return Ok(Outcome::Downloaded);
+            }
+        }
+    }
+}
+
+/// How a single transfer of [`fetch_to`] ended.
+enum Fetch {
+    /// The whole file is in place, with its `algo` hash and total length.
+    Complete { hash: String, size: u64 },
+    /// The connection dropped mid-body. What arrived is kept, and `resumable` says
+    /// whether the server accepts a `Range` request for the rest.
+    Interrupted { error: anyhow::Error, resumable: bool },
+}
+
+/// Streams `url` into `path`, continuing after the first `offset` bytes already in
+/// `path` when the server answers the range request, and rewriting it otherwise.
+async fn fetch_to(
+    client: &reqwest::Client,
+    url: &str,
+    path: &Path,
+    offset: u64,
+    algo: HashAlgo,
+    tracker: &Tracker,
+    rate_limit: Option<&RateLimiter>,
+) -> Result<Fetch> {
+    let policy = http::RetryPolicy::default();
+    let mut offset = offset;
+    let mut response = loop {
+        let result = match offset {
+            0 => http::get_with_client(client, url, &policy).await,
+            offset => http::get_range_with_client(client, url, offset, &policy).await,
+        };
+        match result {
+            // The partial file is longer than the remote one, so it cannot be a prefix of it
+            Err(err) if offset > 0 && status_of(&err) == Some(StatusCode::RANGE_NOT_SATISFIABLE) => offset = 0,
+            Ok(response) if offset > 0 && response.status() == StatusCode::PARTIAL_CONTENT && content_range_start(&response) != Some(offset) => {
+                offset = 0
+            }
+            result => break result.with_context(|| format!("Failed to download {}", url))?,
+        }
+    };
+
+    let resumed = offset > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+    let resumable = resumed || accepts_ranges(&response);
+    let mut hasher = Hasher::new(algo);
+    let (mut file, mut written) = if resumed {
+        let existing = hash_file_into(path, &mut hasher).await?;
+        (fs::OpenOptions::new().append(true).open(path).await?, existing)
+    } else {
+        (fs::File::create(path).await?, 0)
+    };
+
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                if let Some(limiter) = rate_limit {
+                    limiter.acquire(chunk.len() as u64).await;
+                }
+                file.write_all(&chunk).await?;
+                hasher.update(&chunk);
+                written += chunk.len() as u64;
+                tracker.add_bytes(chunk.len() as u64);
+            }
+            Ok(None) => break,
+            Err(err) => {
+                file.flush().await?;
+                let error = anyhow::Error::new(err).context(format!("Download of {} was interrupted after {} bytes", url, written));
+                return Ok(Fetch::Interrupted { error, resumable });
+            }
+        }
+    }
+    file.flush().await?;
+    Ok(Fetch::Complete {
+        hash: hasher.finish(),
+        size: written,
+    })
+}
+
+fn status_of(err: &anyhow::Error) -> Option<StatusCode> {
+    err.downcast_ref::<reqwest::Error>()?.status()
+}
+
+fn accepts_ranges(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get(ACCEPT_RANGES)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|unit| unit.trim().eq_ignore_ascii_case("bytes")))
+}
+
+/// The first byte of a `Content-Range: bytes <start>-<end>/<total>` response.
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    let value = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    let range = value.trim().strip_prefix("bytes ")?;
+    range.split('-').next()?.trim().parse().ok()
+}
+
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+/// `path` from a manifest, if joining it onto a directory stays inside that
+/// directory. Rejects absolute paths, drive prefixes, `..` components and
+/// backslashes, which Windows would treat as separators.
+pub(crate) fn enclosed_path(path: &str) -> Option<PathBuf> {
+    if path.contains('\\') {
+        return None;
+    }
+    let mut enclosed = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => enclosed.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!enclosed.as_os_str().is_empty()).then_some(enclosed)
+}
+
+/// A hash algorithm a download can be verified with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgo {
+    /// Used by Mojang for libraries, assets and jars, and by CurseForge.
+    Sha1,
+    /// Published by Modrinth alongside SHA-1.
+    Sha512,
+}
+
+impl HashAlgo {
+    /// Lowercase hex digest of a byte slice.
+    pub fn hex(self, bytes: &[u8]) -> String {
+        let mut hasher = Hasher::new(self);
+        hasher.update(bytes);
+        hasher.finish()
+    }
+}
+
+impl fmt::Display for HashAlgo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Sha1 => "SHA-1",
+            Self::Sha512 => "SHA-512",
+        })
+    }
+}
+
+/// An incremental hasher for one of the [`HashAlgo`]s.
+enum Hasher {
+    Sha1(Sha1),
+    Sha512(Sha512),
+}
+
+impl Hasher {
+    fn new(algo: HashAlgo) -> Self {
+        match algo {
+            HashAlgo::Sha1 => Self::Sha1(Sha1::new()),
+            HashAlgo::Sha512 => Self::Sha512(Sha512::new()),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Sha1(hasher) => hasher.update(bytes),
+            Self::Sha512(hasher) => hasher.update(bytes),
+        }
+    }
+
+    fn finish(self) -> String {
+        match self {
+            Self::Sha1(hasher) => format!("{:x}", hasher.finalize()),
+            Self::Sha512(hasher) => format!("{:x}", hasher.finalize()),
+        }
+    }
+}
+
+/// Checks whether the file at `path` has the given SHA-1 (compared case-insensitively).
+///
+/// A missing file is reported as `Ok(false)`; other I/O errors are returned.
+pub async fn verify_sha1(path: impl AsRef<Path>, expected: &str) -> Result<bool> {
+    verify_hash(path, HashAlgo::Sha1, expected).await
+}
+
+/// Like [`verify_sha1`], for any [`HashAlgo`].
+pub async fn verify_hash(path: impl AsRef<Path>, algo: HashAlgo, expected: &str) -> Result<bool> {
+    match file_hash(path.as_ref(), algo).await {
+        Ok(actual) => Ok(actual.eq_ignore_ascii_case(expected)),
+        Err(err)
+            if err
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound) =>
+        {
+            Ok(false)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Lowercase hex SHA-1 of a byte slice.
+pub fn sha1_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha1::digest(bytes))
+}
+
+/// Lowercase hex SHA-1 of a file's contents.
+pub(crate) async fn file_sha1(path: &Path) -> Result<String> {
+    file_hash(path, HashAlgo::Sha1).await
+}
+
+/// Lowercase hex `algo` digest of a file's contents.
+pub async fn file_hash(path: impl AsRef<Path>, algo: HashAlgo) -> Result<String> {
+    let mut hasher = Hasher::new(algo);
+    hash_file_into(path.as_ref(), &mut hasher).await?;
+    Ok(hasher.finish())
+}
+
+/// Like [`file_hash`], reading on the calling thread; for synchronous code and
+/// `spawn_blocking`.
+pub fn file_hash_blocking(path: &Path, algo: HashAlgo) -> Result<String> {
+    use std::io::Read;
+
+    let mut hasher = Hasher::new(algo);
+    let mut file = std::fs::File::open(path)?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finish())
+}
+
+/// Feeds the contents of the file at `path` into `hasher`, returning its length.
+async fn hash_file_into(path: &Path, hasher: &mut Hasher) -> Result<u64> {
+    let mut file = fs::File::open(path).await?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut total = 0;
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        total += read as u64;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use test_support::{MockServer, Response, serve, serve_files, serve_sequence};
+
+    #[tokio::test]
+    async fn test_downloads_files_concurrently() {
+        let files: HashMap<&'static str, &'static [u8]> = HashMap::from([
+            ("/a.jar", b"alpha" as &[u8]),
+            ("/b.jar", b"bravo" as &[u8]),
+            ("/nested/c.jar", b"charlie" as &[u8]),
+        ]);
+        let server = serve_files(files.clone());
+        let base_url = server.base_url();
+        let dir = tempfile::tempdir().unwrap();
+
+        let jobs: Vec<DownloadJob> = files
+            .iter()
+            .map(|(path, body)| {
+                DownloadJob::new(format!("{}{}", base_url, path), dir.path().join(&path[1..]))
+                    .with_sha1(sha1_hex(body))
+                    .with_size(body.len() as u64)
+            })
+            .collect();
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let progress = reports.clone();
+        let summary = Downloader::new()
+            .with_concurrency(2)
+            .on_progress(move |p| progress.lock().unwrap().push(p))
+            .download(jobs)
+            .await;
+
+        assert!(summary.is_success());
+        assert_eq!(summary.succeeded.len(), 3);
+        assert_eq!(server.hits(), 3);
+        assert_eq!(std::fs::read(dir.path().join("nested/c.jar")).unwrap(), b"charlie");
+        assert!(!dir.path().join("a.jar.part").exists());
+
+        // Reports from concurrent tasks may arrive out of order, so look at the maxima.
+        let reports = reports.lock().unwrap();
+        assert!(reports.iter().all(|p| p.files_total == 3 && p.bytes_total == 19));
+        assert_eq!(reports.iter().map(|p| p.files_completed).max(), Some(3));
+        assert_eq!(reports.iter().map(|p| p.bytes_downloaded).max(), Some(19));
+    }
+
+    #[tokio::test]
+    async fn test_skips_files_with_matching_sha1() {
+        let server = serve_files(HashMap::from([("/a.jar", b"alpha" as &[u8]), ("/b.jar", b"bravo" as &[u8])]));
+        let base_url = server.base_url();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.jar"), b"alpha").unwrap();
+        std::fs::write(dir.path().join("b.jar"), b"stale").unwrap();
+
+        let jobs = vec![
+            DownloadJob::new(format!("{}/a.jar", base_url), dir.path().join("a.jar")).with_sha1(sha1_hex(b"alpha")),
+            DownloadJob::new(format!("{}/b.jar", base_url), dir.path().join("b.jar")).with_sha1(sha1_hex(b"bravo")),
+        ];
+        let summary = Downloader::new().download(jobs).await;
+
+        assert_eq!(summary.skipped.len(), 1);
+        assert_eq!(summary.skipped[0].dest, dir.path().join("a.jar"));
+        assert_eq!(summary.succeeded.len(), 1);
+        assert_eq!(server.paths(), ["/b.jar"]);
+        assert_eq!(std::fs::read(dir.path().join("b.jar")).unwrap(), b"bravo");
+    }
+
+    #[tokio::test]
+    async fn test_reports_failures_without_stopping() {
+        let server = serve_files(HashMap::from([("/a.jar", b"alpha" as &[u8])]));
+        let base_url = server.base_url();
+        let dir = tempfile::tempdir().unwrap();
+
+        let jobs = vec![
+            DownloadJob::new(format!("{}/a.jar", base_url), dir.path().join("a.jar")),
+            DownloadJob::new(format!("{}/missing.jar", base_url), dir.path().join("missing.jar")),
+        ];
+        let summary = Downloader::new().download(jobs).await;
+
+        assert_eq!(summary.succeeded.len(), 1);
+        assert_eq!(summary.failed.len(), 1);
+        assert!(summary.failed[0].job.url.ends_with("/missing.jar"));
+        assert!(!summary.is_success());
+    }
+
+    #[tokio::test]
+    async fn test_redownloads_on_sha1_mismatch() {
+        let server = serve_sequence(vec![Response::ok("corrupt"), Response::ok("correct")]);
+        let url = server.url("/file.jar");
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("file.jar");
+
+        let summary = Downloader::new()
+            .download(vec![DownloadJob::new(url, &dest).with_sha1(sha1_hex(b"correct"))])
+            .await;
+
+        assert!(summary.is_success());
+        assert_eq!(server.hits(), 2);
+        assert_eq!(std::fs::read(&dest).unwrap(), b"correct");
+    }
+
+    #[tokio::test]
+    async fn test_fails_after_repeated_sha1_mismatch() {
+        let server = serve_sequence(vec![Response::ok("corrupt"), Response::ok("corrupt"), Response::ok("correct")]);
+        let url = server.url("/file.jar");
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("file.jar");
+
+        let summary = Downloader::new()
+            .with_verify_attempts(2)
+            .download(vec![DownloadJob::new(url.clone(), &dest).with_sha1(sha1_hex(b"correct"))])
+            .await;
+
+        assert_eq!(summary.failed.len(), 1);
+        let message = summary.failed[0].error.to_string();
+        assert!(message.contains("SHA-1 mismatch"));
+        assert!(message.contains(&url));
+        assert_eq!(server.hits(), 2);
+        assert!(!dest.exists());
+        assert!(!dir.path().join("file.jar.part").exists());
+    }
+
+    #[tokio::test]
+    async fn test_redownloads_on_size_mismatch() {
+        let server = serve_sequence(vec![Response::ok("short"), Response::ok("correct")]);
+        let url = server.url("/file.jar");
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("file.jar");
+
+        let summary = Downloader::new().download(vec![DownloadJob::new(url, &dest).with_size(7)]).await;
+
+        assert!(summary.is_success());
+        assert_eq!(server.hits(), 2);
+        assert_eq!(std::fs::read(&dest).unwrap(), b"correct");
+
+        let server = serve_sequence(vec![Response::ok("short")]);
+        let url = server.url("/file.jar");
+        let summary = Downloader::new()
+            .with_verify_attempts(1)
+            .download(vec![DownloadJob::new(url, dir.path().join("other.jar")).with_size(7)])
+            .await;
+        assert!(summary.failed[0].error.to_string().contains("Size mismatch"));
+        assert!(!dir.path().join("other.jar").exists());
+    }
+
+    /// Serves `body` at any path.
+    ///
+    /// With `interrupt_first`, the first response announces the full length but the
+    /// connection is closed after half of the body. Range requests are answered with
+    /// `206 Partial Content` only when `ranges` is set.
+    fn serve_ranges(body: &'static [u8], ranges: bool, interrupt_first: bool) -> MockServer {
+        serve(move |request| {
+            let start = request
+                .header("range")
+                .and_then(|range| range.strip_prefix("bytes="))
+                .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok())
+                .filter(|_| ranges);
+            let mut response = match start {
+                Some(start) => Response::status(206).with_header("Content-Range", format!("bytes {}-{}/{}", start, body.len() - 1, body.len())),
+                None => Response::status(200),
+            };
+            if ranges {
+                response = response.with_header("Accept-Ranges", "bytes");
+            }
+            let rest = &body[start.unwrap_or(0)..];
+            response = response.with_body(rest);
+            if interrupt_first && request.index == 0 {
+                response = response.cut_short(rest.len() / 2);
+            }
+            response
+        })
+    }
+
+    /// The `Range` header of each request `server` received.
+    fn ranges(server: &MockServer) -> Vec<Option<String>> {
+        server
+            .requests()
+            .iter()
+            .map(|request| request.header("range").map(str::to_string))
+            .collect()
+    }
+
+    const LARGE_BODY: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ!?";
+
+    #[tokio::test]
+    async fn test_resumes_interrupted_download() {
+        let server = serve_ranges(LARGE_BODY, true, true);
+        let url = server.url("/file.jar");
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("file.jar");
+
+        let summary = Downloader::new()
+            .download(vec![DownloadJob::new(url, &dest).with_sha1(sha1_hex(LARGE_BODY))])
+            .await;
+
+        assert!(summary.is_success(), "{:?}", summary.failed);
+        assert_eq!(std::fs::read(&dest).unwrap(), LARGE_BODY);
+        assert!(!dir.path().join("file.jar.part").exists());
+        assert_eq!(ranges(&server), vec![None, Some(format!("bytes={}-", LARGE_BODY.len() / 2))]);
+    }
+
+    #[tokio::test]
+    async fn test_restarts_interrupted_download_without_ranges() {
+        let server = serve_ranges(LARGE_BODY, false, true);
+        let url = server.url("/file.jar");
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("file.jar");
+
+        let summary = Downloader::new()
+            .download(vec![DownloadJob::new(url, &dest).with_size(LARGE_BODY.len() as u64)])
+            .await;
+
+        assert!(summary.is_success(), "{:?}", summary.failed);
+        assert_eq!(std::fs::read(&dest).unwrap(), LARGE_BODY);
+        assert_eq!(ranges(&server), vec![None, None]);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_resume_attempts() {
+        let server = serve_ranges(LARGE_BODY, true, true);
+        let url = server.url("/file.jar");
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("file.jar");
+
+        let summary = Downloader::new()
+            .with_resume_attempts(0)
+            .download(vec![DownloadJob::new(url, &dest).with_sha1(sha1_hex(LARGE_BODY))])
+            .await;
+
+        assert_eq!(summary.failed.len(), 1);
+        assert!(summary.failed[0].error.to_string().contains("interruptions"));
+        assert_eq!(server.hits(), 1);
+        assert!(!dest.exists());
+        // What arrived is kept for the next run
+        assert_eq!(
+            std::fs::read(dir.path().join("file.jar.part")).unwrap(),
+            &LARGE_BODY[..LARGE_BODY.len() / 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_continues_partial_file_from_earlier_run() {
+        let server = serve_ranges(LARGE_BODY, true, false);
+        let url = server.url("/file.jar");
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("file.jar");
+        std::fs::write(dir.path().join("file.jar.part"), &LARGE_BODY[..40]).unwrap();
+
+        let summary = Downloader::new()
+            .download(vec![DownloadJob::new(url, &dest).with_sha1(sha1_hex(LARGE_BODY))])
+            .await;
+
+        assert!(summary.is_success(), "{:?}", summary.failed);
+        assert_eq!(std::fs::read(&dest).unwrap(), LARGE_BODY);
+        assert_eq!(ranges(&server), vec![Some("bytes=40-".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_partial_file_is_verified_and_replaced() {
+        let server = serve_ranges(LARGE_BODY, true, false);
+        let url = server.url("/file.jar");
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("file.jar");
+        std::fs::write(dir.path().join("file.jar.part"), [b'x'; 40]).unwrap();
+
+        let summary = Downloader::new()
+            .download(vec![DownloadJob::new(url, &dest).with_sha1(sha1_hex(LARGE_BODY))])
+            .await;
+
+        assert!(summary.is_success(), "{:?}", summary.failed);
+        assert_eq!(std::fs::read(&dest).unwrap(), LARGE_BODY);
+        assert_eq!(ranges(&server), vec![Some("bytes=40-".to_string()), None]);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_caps_combined_throughput() {
+        static BODY: [u8; 10_000] = [7; 10_000];
+        let files: HashMap<&'static str, &'static [u8]> =
+            HashMap::from([("/a.jar", &BODY[..]), ("/b.jar", &BODY[..]), ("/c.jar", &BODY[..]), ("/d.jar", &BODY[..])]);
+        let server = serve_files(files.clone());
+        let base_url = server.base_url();
+        let dir = tempfile::tempdir().unwrap();
+        let jobs = files
+            .keys()
+            .map(|path| DownloadJob::new(format!("{}{}", base_url, path), dir.path().join(&path[1..])))
+            .collect();
+
+        // 40 000 bytes at 20 000 bytes/s: the first second's worth is a burst, the
+        // rest has to wait at least another second.
+        let started = std::time::Instant::now();
+        let summary = Downloader::new().with_concurrency(4).with_rate_limit(Some(20_000)).download(jobs).await;
+
+        assert!(summary.is_success(), "{:?}", summary.failed);
+        assert!(started.elapsed() >= Duration::from_millis(950), "{:?}", started.elapsed());
+        assert_eq!(std::fs::read(dir.path().join("d.jar")).unwrap(), BODY);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_allows_burst_then_waits() {
+        let limiter = RateLimiter::new(1_000);
+        let started = tokio::time::Instant::now();
+        limiter.acquire(1_000).await;
+        assert!(started.elapsed() < Duration::from_millis(100));
+
+        limiter.acquire(200).await;
+        assert!(started.elapsed() >= Duration::from_millis(190));
+    }
+
+    #[tokio::test]
+    async fn test_verify_sha1() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.jar");
+        std::fs::write(&path, b"correct").unwrap();
+
+        assert!(verify_sha1(&path, &sha1_hex(b"correct")).await.unwrap());
+        assert!(verify_sha1(&path, &sha1_hex(b"correct").to_uppercase()).await.unwrap());
+        assert!(!verify_sha1(&path, &sha1_hex(b"corrupt")).await.unwrap());
+        assert!(!verify_sha1(dir.path().join("missing.jar"), "00").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_hash_with_both_algorithms() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mod.jar");
+        std::fs::write(&path, b"abc").unwrap();
+
+        let sha1 = "a9993e364706816aba3e25717850c26c9cd0d89d";
+        let sha512 =
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f";
+        assert_eq!(HashAlgo::Sha1.hex(b"abc"), sha1);
+        assert_eq!(HashAlgo::Sha512.hex(b"abc"), sha512);
+        assert_eq!(file_hash(&path, HashAlgo::Sha512).await.unwrap(), sha512);
+
+        assert!(verify_hash(&path, HashAlgo::Sha1, sha1).await.unwrap());
+        assert!(verify_hash(&path, HashAlgo::Sha512, &sha512.to_uppercase()).await.unwrap());
+        assert!(!verify_hash(&path, HashAlgo::Sha512, &HashAlgo::Sha512.hex(b"abd")).await.unwrap());
+        assert!(!verify_hash(dir.path().join("missing.jar"), HashAlgo::Sha512, sha512).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_redownloads_on_sha512_mismatch() {
+        let server = serve_sequence(vec![Response::ok("corrupt"), Response::ok("correct")]);
+        let url = server.url("/file.jar");
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("mod.jar");
+        let summary = Downloader::new()
+            .download(vec![
+                DownloadJob::new(url, &dest)
+                    .with_sha1(sha1_hex(b"corrupt"))
+                    .with_sha512(HashAlgo::Sha512.hex(b"correct")),
+            ])
+            .await;
+
+        assert!(summary.is_success());
+        assert_eq!(std::fs::read(&dest).unwrap(), b"correct");
+        assert_eq!(server.hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_before_start_downloads_nothing() {
+        let server = serve_files(HashMap::from([("/a.jar", b"alpha" as &[u8])]));
+        let base_url = server.base_url();
+        let dir = tempfile::tempdir().unwrap();
+        // Left by an earlier run; a job that never started must not remove it.
+        std::fs::write(dir.path().join("a.jar.part"), b"al").unwrap();
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let job = DownloadJob::new(format!("{}/a.jar", base_url), dir.path().join("a.jar")).with_sha1(sha1_hex(b"alpha"));
+        let summary = Downloader::new().with_cancellation(token).download(vec![job.clone()]).await;
+
+        assert!(summary.is_cancelled());
+        assert!(!summary.is_success());
+        assert_eq!(summary.cancelled, vec![job]);
+        assert!(summary.failed.is_empty());
+        assert_eq!(server.hits(), 0);
+        assert!(!dir.path().join("a.jar").exists());
+        assert_eq!(std::fs::read(dir.path().join("a.jar.part")).unwrap(), b"al");
+    }
+
+    #[tokio::test]
+    async fn test_offline_keeps_files_on_disk() {
+        let server = serve_files(HashMap::from([("/a.jar", b"aaa" as &[u8])]));
+        let base_url = server.base_url();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.jar"), b"aaa").unwrap();
+        std::fs::write(dir.path().join("b.jar"), b"unverified").unwrap();
+
+        let cached = DownloadJob::new(format!("{}/a.jar", base_url), dir.path().join("a.jar")).with_sha1(sha1_hex(b"aaa"));
+        let unhashed = DownloadJob::new(format!("{}/b.jar", base_url), dir.path().join("b.jar"));
+        let missing = DownloadJob::new(format!("{}/c.jar", base_url), dir.path().join("c.jar"));
+        let summary = Downloader::new().with_offline(true).download(vec![cached, unhashed, missing]).await;
+
+        assert_eq!(summary.skipped.len(), 2);
+        assert_eq!(summary.failed.len(), 1);
+        let failure = &summary.failed[0];
+        assert_eq!(failure.job.dest, dir.path().join("c.jar"));
+        assert!(matches!(failure.error.downcast_ref::<LoaderError>(), Some(LoaderError::Offline { .. })));
+        assert!(!dir.path().join("c.jar").exists());
+        assert_eq!(server.hits(), 0);
+    }
+}
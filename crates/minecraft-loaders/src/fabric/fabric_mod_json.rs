@@ -16,7 +16,8 @@ pub enum FabricModJsonError {
     },
     #[error("Failed to read JAR as ZIP archive: {0}")]
     ZipRead(#[from] zip::result::ZipError),
-    #[error("fabric.mod.json not found in JAR")]
+    /// The JAR has no `fabric.mod.json`, so it is not a Fabric mod.
+    #[error("Not a Fabric mod: fabric.mod.json not found in JAR")]
     JsonNotFound,
     #[error("Failed to read fabric.mod.json contents: {0}")]
     JsonRead(#[source] std::io::Error),
@@ -155,6 +156,16 @@ pub enum License {
     Multiple(Vec<String>),
 }
 
+impl DependencyVersion {
+    /// The version ranges, any one of which satisfies the dependency.
+    pub fn ranges(&self) -> Vec<String> {
+        match self {
+            Self::Single(range) => vec![range.clone()],
+            Self::Multiple(ranges) => ranges.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum EntryPoint {
@@ -228,6 +239,45 @@ impl FabricModJson {
     }
 }
 
+/// The parts of a `fabric.mod.json` needed to display a mod and check it against others.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FabricModMetadata {
+    pub id: String,
+    pub version: String,
+    /// Display name, the id when the mod does not set one
+    pub name: String,
+    /// Required mod ids mapped to their accepted version ranges (any one matches)
+    pub depends: HashMap<String, Vec<String>>,
+    /// Mod id aliases this mod also satisfies
+    pub provides: Vec<String>,
+}
+
+impl From<FabricModJson> for FabricModMetadata {
+    fn from(json: FabricModJson) -> Self {
+        let depends = json
+            .depends
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(id, version)| (id, version.ranges()))
+            .collect();
+        Self {
+            name: json.name.unwrap_or_else(|| json.id.clone()),
+            id: json.id,
+            version: json.version,
+            depends,
+            provides: json.provides.unwrap_or_default(),
+        }
+    }
+}
+
+/// Read the mod metadata from the `fabric.mod.json` inside `jar`.
+///
+/// Jars without a `fabric.mod.json` fail with [`FabricModJsonError::JsonNotFound`],
+/// which callers can use to tell "not a Fabric mod" apart from a broken one.
+pub fn read_mod_metadata(jar: &Path) -> Result<FabricModMetadata, FabricModJsonError> {
+    FabricModJson::from_jar(jar).map(FabricModMetadata::from)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -578,4 +628,109 @@ mod tests {
 
         std::fs::remove_file(&jar_path).ok();
     }
+
+    fn write_test_jar(name: &str, json_content: &str) -> PathBuf {
+        let jar_path = std::env::temp_dir().join(name);
+        std::fs::write(&jar_path, create_test_jar_with_json(json_content)).unwrap();
+        jar_path
+    }
+
+    #[test]
+    fn test_read_mod_metadata_sodium() {
+        // Trimmed from Sodium 0.5.8's fabric.mod.json
+        let json = r#"{
+            "schemaVersion": 1,
+            "id": "sodium",
+            "version": "0.5.8+mc1.20.4",
+            "name": "Sodium",
+            "description": "Sodium is a free and open-source optimization mod for Minecraft which improves frame rates and reduces lag spikes.",
+            "authors": ["JellySquid"],
+            "contact": {
+                "homepage": "https://github.com/CaffeineMC/sodium-fabric",
+                "sources": "https://github.com/CaffeineMC/sodium-fabric"
+            },
+            "license": "LGPL-3.0-only",
+            "icon": "assets/sodium/icon.png",
+            "environment": "client",
+            "entrypoints": {
+                "client": ["me.jellysquid.mods.sodium.client.SodiumClientMod"]
+            },
+            "mixins": ["sodium.mixins.json"],
+            "depends": {
+                "fabricloader": ">=0.12.0"
+            },
+            "breaks": {
+                "optifabric": "*"
+            },
+            "provides": ["indium"]
+        }"#;
+
+        let jar_path = write_test_jar("test_metadata_sodium.jar", json);
+        let metadata = read_mod_metadata(&jar_path).unwrap();
+
+        assert_eq!(metadata.id, "sodium");
+        assert_eq!(metadata.version, "0.5.8+mc1.20.4");
+        assert_eq!(metadata.name, "Sodium");
+        assert_eq!(metadata.depends.len(), 1);
+        assert_eq!(metadata.depends["fabricloader"], vec![">=0.12.0".to_string()]);
+        assert_eq!(metadata.provides, vec!["indium".to_string()]);
+
+        std::fs::remove_file(&jar_path).ok();
+    }
+
+    #[test]
+    fn test_read_mod_metadata_depends_on_fabric_api() {
+        // Trimmed from Mod Menu 9.0.0's fabric.mod.json
+        let json = r#"{
+            "schemaVersion": 1,
+            "id": "modmenu",
+            "version": "9.0.0",
+            "environment": "client",
+            "entrypoints": {
+                "client": ["com.terraformersmc.modmenu.ModMenu"],
+                "modmenu": ["com.terraformersmc.modmenu.ModMenuModMenuCompat"]
+            },
+            "mixins": ["mixins.modmenu.json"],
+            "depends": {
+                "fabricloader": ">=0.15.0",
+                "fabric-api": ["*"],
+                "minecraft": [">=1.20.3-beta.1", "1.20.4"]
+            }
+        }"#;
+
+        let jar_path = write_test_jar("test_metadata_modmenu.jar", json);
+        let metadata = read_mod_metadata(&jar_path).unwrap();
+
+        assert_eq!(metadata.id, "modmenu");
+        // No name in the JSON, so it falls back to the id
+        assert_eq!(metadata.name, "modmenu");
+        assert_eq!(metadata.depends["fabric-api"], vec!["*".to_string()]);
+        assert_eq!(
+            metadata.depends["minecraft"],
+            vec![">=1.20.3-beta.1".to_string(), "1.20.4".to_string()]
+        );
+        assert!(metadata.provides.is_empty());
+
+        std::fs::remove_file(&jar_path).ok();
+    }
+
+    #[test]
+    fn test_read_mod_metadata_not_a_fabric_mod() {
+        let mut buffer = Vec::new();
+        {
+            let mut zip = ZipWriter::new(std::io::Cursor::new(&mut buffer));
+            zip.start_file("META-INF/mods.toml", SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(b"modLoader = \"javafml\"").unwrap();
+            zip.finish().unwrap();
+        }
+        let jar_path = std::env::temp_dir().join("test_metadata_forge.jar");
+        std::fs::write(&jar_path, buffer).unwrap();
+
+        let err = read_mod_metadata(&jar_path).unwrap_err();
+        assert!(matches!(err, FabricModJsonError::JsonNotFound));
+        assert!(err.to_string().contains("Not a Fabric mod"));
+
+        std::fs::remove_file(&jar_path).ok();
+    }
 }
@@ -2,11 +2,17 @@ use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
-use crate::ModLoader;
+use crate::cache;
+use crate::http::{self, HttpClient, RetryPolicy};
+use crate::maven::MavenCoordinate;
+use crate::versions::compare_versions;
+use crate::{LoaderError, LoaderVersionInfo, ModLoader, ModLoaderVersions};
 
+pub(crate) const DEFAULT_META_URL: &str = "https://meta.fabricmc.net/";
 const API_URL: &str = "https://meta.fabricmc.net/v2/versions/";
 const SERVER_LAUNCH_JAR_URL: &str = "https://meta.fabricmc.net/v2/versions/loader";
 
@@ -37,8 +43,12 @@ pub struct LoaderVersion {
 }
 
 /// An intermediary mappings version.
+///
+/// There is one intermediary per game version, and `version` is that game version's
+/// id (e.g. `1.20.4`), not a version of the mappings themselves.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IntermediaryVersion {
+    /// Maven coordinate, e.g. `net.fabricmc:intermediary:1.20.4`.
     pub maven: String,
     pub version: String,
     pub stable: bool,
@@ -47,6 +57,7 @@ pub struct IntermediaryVersion {
 /// A Fabric installer version.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstallerVersion {
+    /// Where the installer jar can be downloaded, see [`InstallerVersion::download`].
     pub url: String,
     pub maven: String,
     pub version: String,
@@ -55,30 +66,117 @@ pub struct InstallerVersion {
 
 impl FabricVersions {
     /// Fetches all available Fabric versions from the meta API.
-    pub async fn fetch() -> Result<Self> {
-        let response = reqwest::get(API_URL).await?;
-        let versions = response.json::<Self>().await?;
-        Ok(versions)
+    pub async fn fetch() -> Result<Self, LoaderError> {
+        Self::fetch_from(DEFAULT_META_URL).await
+    }
+
+    /// Like [`FabricVersions::fetch`], but through a caller-provided HTTP client.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "fetch_fabric_versions", skip_all))]
+    pub async fn fetch_with_client(client: &impl HttpClient) -> Result<Self, LoaderError> {
+        client.get_json(API_URL).await
+    }
+
+    /// Fetches all available Fabric versions from a meta API mirror.
+    ///
+    /// `base_url` is the root of the meta service (e.g. `https://meta.fabricmc.net/`);
+    /// the `v2/versions/` endpoint is resolved relative to it.
+    pub async fn fetch_from(base_url: &str) -> Result<Self, LoaderError> {
+        Self::fetch_from_with_retry(base_url, &RetryPolicy::default()).await
+    }
+
+    /// Like [`FabricVersions::fetch_from`], with a custom [`RetryPolicy`] for transient failures.
+    ///
+    /// An invalid `base_url` is reported as [`LoaderError::Network`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "fetch_fabric_versions", skip_all, fields(base_url = %base_url)))]
+    pub async fn fetch_from_with_retry(base_url: &str, retry: &RetryPolicy) -> Result<Self, LoaderError> {
+        let url = versions_url(base_url).map_err(|err| LoaderError::from_http(base_url, err))?;
+        http::get_json(http::client(), url.as_str(), retry).await
+    }
+
+    /// Fetches all available Fabric versions, caching the response in `cache_dir`.
+    ///
+    /// A cached copy younger than `max_age` is returned without touching the network.
+    /// If the cache is stale the API is queried again, and if that request fails the
+    /// stale copy is returned instead so the version list keeps working offline. In
+    /// [offline mode](http::is_offline) any cached copy is used, whatever its age.
+    pub async fn fetch_cached(cache_dir: &Path, max_age: Duration) -> Result<Self, LoaderError> {
+        Self::fetch_cached_url(API_URL, cache_dir, max_age).await
+    }
+
+    /// Like [`FabricVersions::fetch_cached`], but against a meta API mirror.
+    ///
+    /// An invalid `base_url` is reported as [`LoaderError::Network`].
+    pub async fn fetch_cached_from(base_url: &str, cache_dir: &Path, max_age: Duration) -> Result<Self, LoaderError> {
+        let url = versions_url(base_url).map_err(|err| LoaderError::from_http(base_url, err))?;
+        Self::fetch_cached_url(url.as_str(), cache_dir, max_age).await
+    }
+
+    async fn fetch_cached_url(url: &str, cache_dir: &Path, max_age: Duration) -> Result<Self, LoaderError> {
+        let cache_path = cache_dir.join(cache_file_name(url));
+        cache::fetch_json(url, &cache_path, max_age, http::is_offline())
+            .await
+            .map_err(|err| LoaderError::from_http(url, err))
     }
 
     /// Gets the latest stable installer version.
+    #[deprecated(note = "use `FabricVersions::latest_installer`")]
     pub fn get_latest_installer(&self) -> Option<&InstallerVersion> {
-        self.installer
-            .iter()
-            .filter(|v| v.stable)
-            .max_by(|a, b| a.version.cmp(&b.version))
+        self.latest_installer()
     }
 
     /// Gets the latest stable loader version.
+    #[deprecated(note = "use `FabricVersions::latest_stable_loader`")]
     pub fn get_latest_loader(&self) -> Option<&LoaderVersion> {
+        self.latest_stable_loader()
+    }
+
+    /// All loader versions, newest first by [`compare_versions`].
+    pub fn loaders_sorted(&self) -> Vec<&LoaderVersion> {
+        let mut loaders: Vec<&LoaderVersion> = self.loader.iter().collect();
+        loaders.sort_by(|a, b| compare_versions(&b.version, &a.version));
+        loaders
+    }
+
+    /// All game versions, newest first by [`compare_versions`].
+    ///
+    /// Snapshot ids (`24w14a`) do not share a numbering with releases, so they sort
+    /// among themselves rather than next to the release they lead up to; use the API
+    /// order in [`FabricVersions::game`] for a chronological list.
+    pub fn games_sorted(&self) -> Vec<&GameVersion> {
+        let mut games: Vec<&GameVersion> = self.game.iter().collect();
+        games.sort_by(|a, b| compare_versions(&b.version, &a.version));
+        games
+    }
+
+    /// Gets the latest stable game version.
+    #[deprecated(note = "use `FabricVersions::latest_stable_game`")]
+    pub fn get_latest_game_version(&self) -> Option<&GameVersion> {
+        self.latest_stable_game()
+    }
+
+    /// Gets the stable loader with the highest build number.
+    ///
+    /// The meta API lists loaders globally rather than per game version, so this is a
+    /// global selection; use [`FabricVersions::find_game_version`] to check that a
+    /// game version is supported separately.
+    pub fn latest_stable_loader(&self) -> Option<&LoaderVersion> {
         self.loader
             .iter()
             .filter(|v| v.stable)
-            .max_by(|a, b| a.version.cmp(&b.version))
+            .max_by_key(|v| v.build)
     }
 
-    /// Gets the latest stable game version.
-    pub fn get_latest_game_version(&self) -> Option<&GameVersion> {
+    /// Gets the loader with the highest build number, including unstable builds.
+    ///
+    /// Like [`FabricVersions::latest_stable_loader`], this is a global selection.
+    pub fn latest_loader(&self) -> Option<&LoaderVersion> {
+        self.loader.iter().max_by_key(|v| v.build)
+    }
+
+    /// Gets the newest stable game version.
+    ///
+    /// The meta API lists game versions newest first, so this is the first stable entry.
+    pub fn latest_stable_game(&self) -> Option<&GameVersion> {
         self.game.iter().find(|v| v.stable)
     }
 
@@ -87,6 +185,22 @@ impl FabricVersions {
         self.loader.iter().find(|v| v.version == version)
     }
 
+    /// Gets the newest stable installer.
+    ///
+    /// The meta API lists installers newest first, so this is the first stable entry;
+    /// its `url` points at the jar needed to install a Fabric server.
+    pub fn latest_installer(&self) -> Option<&InstallerVersion> {
+        self.installer.iter().find(|v| v.stable)
+    }
+
+    /// Gets the intermediary mappings for a game version.
+    ///
+    /// Intermediaries are keyed by the game version string, so `game` must match
+    /// exactly, e.g. `1.20.4` or `24w14a`.
+    pub fn intermediary_for(&self, game: &str) -> Option<&IntermediaryVersion> {
+        self.intermediary.iter().find(|v| v.version == game)
+    }
+
     /// Finds a specific installer version by version string.
     pub fn find_installer(&self, version: &str) -> Option<&InstallerVersion> {
         self.installer.iter().find(|v| v.version == version)
@@ -96,12 +210,109 @@ impl FabricVersions {
     pub fn find_game_version(&self, version: &str) -> Option<&GameVersion> {
         self.game.iter().find(|v| v.version == version)
     }
+
+    /// Checks that Fabric offers both `game` and the loader build `loader`.
+    ///
+    /// Meant to run before downloading anything for a pinned combination. The error
+    /// names every unknown version along with the closest known ones, e.g.
+    /// `Unknown Minecraft version "1.20.9" (did you mean 1.20.4, 1.20.1?)`.
+    pub fn validate_combination(&self, game: &str, loader: &str) -> Result<()> {
+        let mut problems = Vec::new();
+        if self.find_game_version(game).is_none() {
+            let known = self.game.iter().map(|v| v.version.as_str());
+            problems.push(unknown_version("Minecraft version", game, known));
+        }
+        if self.find_loader(loader).is_none() {
+            let known = self.loader.iter().map(|v| v.version.as_str());
+            problems.push(unknown_version("Fabric loader version", loader, known));
+        }
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(anyhow!("{}", problems.join("; "))),
+        }
+    }
+}
+
+/// Describes an unknown version, suggesting up to three close matches from `known`.
+fn unknown_version<'a>(what: &str, version: &str, known: impl Iterator<Item = &'a str>) -> String {
+    let mut candidates: Vec<(usize, &str)> = known
+        .map(|candidate| (edit_distance(version, candidate), candidate))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .collect();
+    // Stable sort keeps the API's newest-first order among equally close versions.
+    candidates.sort_by_key(|(distance, _)| *distance);
+    let suggestions: Vec<&str> = candidates.into_iter().take(3).map(|(_, candidate)| candidate).collect();
+    match suggestions.is_empty() {
+        true => format!("Unknown {} {:?}", what, version),
+        false => format!("Unknown {} {:?} (did you mean {}?)", what, version, suggestions.join(", ")),
+    }
+}
+
+/// Versions further than this many edits from the requested one are not suggested.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Levenshtein distance between two strings, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Resolves the versions endpoint against a meta API base URL.
+fn versions_url(base_url: &str) -> Result<reqwest::Url> {
+    let mut base = reqwest::Url::parse(base_url)
+        .with_context(|| format!("Fabric meta URL must be absolute, got {:?}", base_url))?;
+    if !matches!(base.scheme(), "http" | "https") {
+        return Err(anyhow!("Fabric meta URL must use http or https, got {:?}", base_url));
+    }
+    if !base.path().ends_with('/') {
+        let path = format!("{}/", base.path());
+        base.set_path(&path);
+    }
+    Ok(base.join("v2/versions/")?)
+}
+
+/// Name of the cache file for a meta API URL, so different endpoints never share a file.
+fn cache_file_name(url: &str) -> String {
+    cache::file_name("fabric-versions", url)
+}
+
+impl LoaderVersion {
+    /// The parsed [`LoaderVersion::maven`] coordinate.
+    pub fn coordinate(&self) -> Result<MavenCoordinate> {
+        MavenCoordinate::parse(&self.maven)
+    }
+}
+
+impl IntermediaryVersion {
+    /// The parsed [`IntermediaryVersion::maven`] coordinate.
+    pub fn coordinate(&self) -> Result<MavenCoordinate> {
+        MavenCoordinate::parse(&self.maven)
+    }
+}
+
+impl From<&LoaderVersion> for LoaderVersionInfo {
+    fn from(v: &LoaderVersion) -> Self {
+        Self {
+            version: v.version.clone(),
+            game_version: None,
+            stable: v.stable,
+        }
+    }
 }
 
 impl InstallerVersion {
     /// Downloads the installer JAR to a specified path.
     pub async fn download(&self, output_path: impl AsRef<Path>) -> Result<PathBuf> {
-        let response = reqwest::get(&self.url).await?;
+        let response = http::client().get(&self.url).send().await?;
         let bytes = response.bytes().await?;
         let output = output_path.as_ref();
 
@@ -116,6 +327,44 @@ impl InstallerVersion {
     }
 }
 
+/// File name of the server launcher written by [`install_server`].
+pub const SERVER_LAUNCH_JAR: &str = "fabric-server-launch.jar";
+
+/// Installs a Fabric server for `game` with the given loader and installer versions
+/// into `dest`, returning the path of the jar to run.
+///
+/// Instead of running the installer jar, which needs Java, this downloads the server
+/// launcher the meta API builds from the same installer version. On its first start
+/// the launcher fetches the vanilla server jar and libraries into `dest`, so the
+/// directory is ready to run with `java -jar fabric-server-launch.jar nogui`.
+pub async fn install_server(game: &str, loader: &str, installer: &str, dest: &Path) -> Result<PathBuf> {
+    install_server_from(DEFAULT_META_URL, game, loader, installer, dest).await
+}
+
+/// Like [`install_server`], but against a meta API mirror.
+pub async fn install_server_from(base_url: &str, game: &str, loader: &str, installer: &str, dest: &Path) -> Result<PathBuf> {
+    for (what, version) in [("game", game), ("loader", loader), ("installer", installer)] {
+        if !is_path_safe_version(version) {
+            return Err(anyhow!("Invalid Fabric {} version {:?}", what, version));
+        }
+    }
+    let url = versions_url(base_url)?.join(&format!("loader/{}/{}/{}/server/jar", game, loader, installer))?;
+
+    fs::create_dir_all(dest).await?;
+    let jar = dest.join(SERVER_LAUNCH_JAR);
+    FabricModLoader::download_file(url.as_str(), &jar)
+        .await
+        .with_context(|| format!("Failed to install Fabric server {} with loader {} and installer {}", game, loader, installer))
+}
+
+/// Whether `version` can go into a URL path as a single segment: only ASCII letters,
+/// digits and `._+-`, and never `.` or `..`.
+fn is_path_safe_version(version: &str) -> bool {
+    !version.is_empty()
+        && version != "."
+        && !version.contains("..")
+        && version.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '+' | '-'))
+}
 
 /// Fabric mod loader implementation.
 ///
@@ -151,7 +400,9 @@ impl FabricModLoader {
 
     /// Downloads a file from a URL to the specified path.
     async fn download_file(url: &str, output_path: &Path) -> Result<PathBuf> {
-        let response = reqwest::get(url)
+        let response = http::client()
+            .get(url)
+            .send()
             .await
             .context("Failed to download file")?;
 
@@ -335,7 +586,7 @@ impl ModLoader for FabricModLoader {
     ) -> Result<PathBuf> {
         let versions = FabricVersions::fetch().await?;
         let installer = versions
-            .get_latest_installer()
+            .latest_installer()
             .ok_or_else(|| anyhow!("No installer version available"))?;
 
         // Create server directory and download installer
@@ -415,7 +666,7 @@ impl ModLoader for FabricModLoader {
     ) -> Result<PathBuf> {
         let versions = FabricVersions::fetch().await?;
         let installer = versions
-            .get_latest_installer()
+            .latest_installer()
             .ok_or_else(|| anyhow!("No installer version available"))?;
 
         // Create install directory and download installer
@@ -467,7 +718,7 @@ impl ModLoader for FabricModLoader {
         // Fabric client requires the installer - download it to the specified path
         let versions = FabricVersions::fetch().await?;
         let installer = versions
-            .get_latest_installer()
+            .latest_installer()
             .ok_or_else(|| anyhow!("No installer version available"))?;
 
         installer.download(file_path).await
@@ -489,9 +740,106 @@ impl ModLoader for FabricModLoader {
     }
 }
 
+#[async_trait]
+impl ModLoaderVersions for FabricModLoader {
+    fn name(&self) -> &'static str {
+        "Fabric"
+    }
+
+    async fn fetch_versions(&self) -> Result<Vec<LoaderVersionInfo>> {
+        let versions = FabricVersions::fetch().await?;
+        Ok(versions.loader.iter().map(LoaderVersionInfo::from).collect())
+    }
+
+    async fn loader_versions_for_game(&self, game: &str) -> Result<Vec<LoaderVersionInfo>> {
+        // Fabric loader builds are not tied to a game version, but only
+        // game versions with intermediary mappings can actually run them.
+        let versions = FabricVersions::fetch().await?;
+        if versions.find_game_version(game).is_none() {
+            return Ok(Vec::new());
+        }
+        Ok(versions.loader.iter().map(LoaderVersionInfo::from).collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_support::{MockServer, Response, serve};
+    use std::collections::HashMap;
+
+    /// Serves canned bodies by URL; any other URL fails as if the host were unreachable.
+    struct FakeHttp(HashMap<&'static str, &'static str>);
+
+    #[async_trait]
+    impl HttpClient for FakeHttp {
+        async fn get_bytes(&self, url: &str) -> Result<Vec<u8>, LoaderError> {
+            self.0.get(url).map(|body| body.as_bytes().to_vec()).ok_or_else(|| LoaderError::Network {
+                url: url.to_string(),
+                source: "no fixture for this URL".into(),
+            })
+        }
+    }
+
+    const FAKE_HTTP_VERSIONS_FIXTURE: &str = r#"{
+        "game": [{"version": "1.20.4", "stable": true}, {"version": "24w14a", "stable": false}],
+        "loader": [
+            {"separator": ".", "build": 11, "maven": "net.fabricmc:fabric-loader:0.15.11", "version": "0.15.11", "stable": true},
+            {"separator": ".", "build": 1, "maven": "net.fabricmc:fabric-loader:0.16.0-beta.1", "version": "0.16.0-beta.1", "stable": false}
+        ],
+        "intermediary": [{"maven": "net.fabricmc:intermediary:1.20.4", "version": "1.20.4", "stable": true}],
+        "installer": [
+            {"url": "https://maven.fabricmc.net/net/fabricmc/fabric-installer/1.0.1/fabric-installer-1.0.1.jar", "maven": "net.fabricmc:fabric-installer:1.0.1", "version": "1.0.1", "stable": true}
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn test_fetch_with_fake_client() {
+        let client = FakeHttp(HashMap::from([(API_URL, FAKE_HTTP_VERSIONS_FIXTURE)]));
+        let versions = FabricVersions::fetch_with_client(&client).await.unwrap();
+        assert_eq!(versions.game.len(), 2);
+        assert_eq!(versions.latest_stable_loader().map(|l| l.version.as_str()), Some("0.15.11"));
+        assert_eq!(versions.latest_installer().map(|i| i.version.as_str()), Some("1.0.1"));
+        assert_eq!(versions.intermediary[0].version, "1.20.4");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_fake_client_errors() {
+        let err = FabricVersions::fetch_with_client(&FakeHttp(HashMap::new())).await.unwrap_err();
+        assert!(matches!(err, LoaderError::Network { .. }), "{:?}", err);
+        assert_eq!(err.url(), API_URL);
+
+        let client = FakeHttp(HashMap::from([(API_URL, r#"{"game": "not a list"}"#)]));
+        let err = FabricVersions::fetch_with_client(&client).await.unwrap_err();
+        assert!(matches!(err, LoaderError::Deserialize { .. }), "{:?}", err);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn test_fetch_emits_spans() {
+        use std::sync::{Arc, Mutex};
+        use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+        /// Records the name of every span opened while it is the default subscriber.
+        struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+
+        impl<S: tracing::Subscriber> Layer<S> for SpanNames {
+            fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, _: &tracing::span::Id, _: Context<'_, S>) {
+                self.0.lock().unwrap().push(attrs.metadata().name());
+            }
+        }
+
+        let server = serve(|_| Response::ok(FAKE_HTTP_VERSIONS_FIXTURE));
+        let base_url = format!("{}/", server.base_url());
+
+        let names = Arc::new(Mutex::new(Vec::new()));
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(SpanNames(names.clone())));
+        FabricVersions::fetch_from(&base_url).await.unwrap();
+
+        let names = names.lock().unwrap();
+        assert!(names.contains(&"fetch_fabric_versions"), "{:?}", names);
+        assert!(names.contains(&"get_json"), "{:?}", names);
+    }
 
     #[tokio::test]
     async fn test_fetch_versions() {
@@ -506,15 +854,15 @@ mod tests {
     async fn test_get_latest_versions() {
         let versions = FabricVersions::fetch().await.unwrap();
 
-        let latest_installer = versions.get_latest_installer();
+        let latest_installer = versions.latest_installer();
         assert!(latest_installer.is_some());
         assert!(latest_installer.unwrap().stable);
 
-        let latest_loader = versions.get_latest_loader();
+        let latest_loader = versions.latest_stable_loader();
         assert!(latest_loader.is_some());
         assert!(latest_loader.unwrap().stable);
 
-        let latest_game = versions.get_latest_game_version();
+        let latest_game = versions.latest_stable_game();
         assert!(latest_game.is_some());
         assert!(latest_game.unwrap().stable);
     }
@@ -578,7 +926,7 @@ mod tests {
     async fn test_download_installer() {
         let versions = FabricVersions::fetch().await.unwrap();
         let installer = versions
-            .get_latest_installer()
+            .latest_installer()
             .expect("No installer found");
 
         let temp_dir = std::env::temp_dir();
@@ -597,6 +945,353 @@ mod tests {
         let _ = std::fs::remove_file(downloaded_path);
     }
 
+    const VERSIONS_FIXTURE: &str = r#"{
+        "game": [{"version": "1.20.4", "stable": true}],
+        "mappings": [],
+        "intermediary": [{"maven": "net.fabricmc:intermediary:1.20.4", "version": "1.20.4", "stable": true}],
+        "loader": [{"separator": ".", "build": 1, "maven": "net.fabricmc:fabric-loader:0.0.1", "version": "0.0.1", "stable": true}],
+        "installer": [{"url": "https://maven.fabricmc.net/installer.jar", "maven": "net.fabricmc:fabric-installer:0.0.1", "version": "0.0.1", "stable": true}],
+        "yarn": []
+    }"#;
+
+    /// A URL nothing listens on, used to simulate being offline.
+    const OFFLINE_URL: &str = "http://127.0.0.1:9/v2/versions/";
+
+    async fn seed_cache(dir: &Path, url: &str) -> PathBuf {
+        let path = dir.join(cache_file_name(url));
+        fs::write(&path, VERSIONS_FIXTURE).await.unwrap();
+        path
+    }
+
+    /// Serves `body` as JSON at any path on a random local port.
+    fn serve_json(body: &'static str) -> MockServer {
+        serve(move |_| Response::ok(body).with_header("Content-Type", "application/json"))
+    }
+
+    #[tokio::test]
+    async fn test_install_server_from_mirror() {
+        let server = serve_json("fixture launcher jar");
+        let base_url = server.url("/mirror");
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("server");
+
+        let jar = install_server_from(&base_url, "1.20.4", "0.15.11", "1.0.1", &dest).await.unwrap();
+        assert_eq!(jar, dest.join(SERVER_LAUNCH_JAR));
+        assert_eq!(std::fs::read_to_string(&jar).unwrap(), "fixture launcher jar");
+
+        assert_eq!(server.paths(), ["/mirror/v2/versions/loader/1.20.4/0.15.11/1.0.1/server/jar"]);
+    }
+
+    #[tokio::test]
+    async fn test_install_server_rejects_path_in_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = install_server_from(OFFLINE_URL, "1.20.4/../x", "0.15.11", "1.0.1", dir.path()).await;
+        assert!(result.unwrap_err().to_string().contains("Invalid Fabric game version"));
+        assert!(!dir.path().join(SERVER_LAUNCH_JAR).exists());
+
+        for version in ["", ".", "..", "0.15.11%2F..", "0.15 11", "löader", "1.20;4"] {
+            assert!(!is_path_safe_version(version), "{:?}", version);
+        }
+        for version in ["1.20.4", "24w14a", "0.16.0-beta.1", "0.15.11+build.1", "1.20_pre"] {
+            assert!(is_path_safe_version(version), "{:?}", version);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_mirror() {
+        let server = serve_json(VERSIONS_FIXTURE);
+        let base_url = server.url("/mirror");
+
+        let versions = FabricVersions::fetch_from(&base_url).await.unwrap();
+        assert_eq!(versions.loader[0].version, "0.0.1");
+
+        assert_eq!(server.paths(), ["/mirror/v2/versions/"]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_reports_loader_errors() {
+        let base_url = serve_json("<html>maintenance</html>").url("/mirror");
+        let err = FabricVersions::fetch_from(&base_url).await.unwrap_err();
+        assert!(matches!(err, LoaderError::Deserialize { .. }), "{:?}", err);
+
+        let err = FabricVersions::fetch_from("meta.fabricmc.net").await.unwrap_err();
+        assert!(matches!(err, LoaderError::Network { .. }), "{:?}", err);
+    }
+
+    fn combination_fixture() -> FabricVersions {
+        serde_json::from_str(
+            r#"{
+                "game": [
+                    {"version": "1.21", "stable": true},
+                    {"version": "1.20.4", "stable": true},
+                    {"version": "1.20.1", "stable": true},
+                    {"version": "1.19.2", "stable": true}
+                ],
+                "intermediary": [],
+                "loader": [
+                    {"separator": ".", "build": 16, "maven": "net.fabricmc:fabric-loader:0.16.0", "version": "0.16.0", "stable": true},
+                    {"separator": ".", "build": 15, "maven": "net.fabricmc:fabric-loader:0.15.11", "version": "0.15.11", "stable": true}
+                ],
+                "installer": []
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_combination_valid() {
+        let versions = combination_fixture();
+        assert!(versions.validate_combination("1.20.1", "0.15.11").is_ok());
+    }
+
+    #[test]
+    fn test_validate_combination_unknown_loader() {
+        let versions = combination_fixture();
+        let err = versions.validate_combination("1.20.1", "0.15.1").unwrap_err().to_string();
+        assert_eq!(err, r#"Unknown Fabric loader version "0.15.1" (did you mean 0.15.11, 0.16.0?)"#);
+    }
+
+    #[test]
+    fn test_validate_combination_unknown_game() {
+        let versions = combination_fixture();
+        let err = versions.validate_combination("1.20.9", "0.16.0").unwrap_err().to_string();
+        assert_eq!(err, r#"Unknown Minecraft version "1.20.9" (did you mean 1.20.4, 1.20.1?)"#);
+
+        let err = versions.validate_combination("b1.7.3", "0.0.0").unwrap_err().to_string();
+        assert!(err.starts_with(r#"Unknown Minecraft version "b1.7.3"; "#), "{}", err);
+        assert!(err.contains("Fabric loader version"), "{}", err);
+    }
+
+    fn selection_fixture() -> FabricVersions {
+        serde_json::from_str(
+            r#"{
+                "game": [{"version": "1.20.4", "stable": true}, {"version": "23w51b", "stable": false}],
+                "intermediary": [
+                    {"maven": "net.fabricmc:intermediary:23w51b", "version": "23w51b", "stable": false},
+                    {"maven": "net.fabricmc:intermediary:1.20.4", "version": "1.20.4", "stable": true},
+                    {"maven": "net.fabricmc:intermediary:1.20.1", "version": "1.20.1", "stable": true}
+                ],
+                "loader": [],
+                "installer": [
+                    {"url": "https://maven.fabricmc.net/net/fabricmc/fabric-installer/1.1.0/fabric-installer-1.1.0.jar", "maven": "net.fabricmc:fabric-installer:1.1.0", "version": "1.1.0", "stable": false},
+                    {"url": "https://maven.fabricmc.net/net/fabricmc/fabric-installer/1.0.10/fabric-installer-1.0.10.jar", "maven": "net.fabricmc:fabric-installer:1.0.10", "version": "1.0.10", "stable": true},
+                    {"url": "https://maven.fabricmc.net/net/fabricmc/fabric-installer/1.0.9/fabric-installer-1.0.9.jar", "maven": "net.fabricmc:fabric-installer:1.0.9", "version": "1.0.9", "stable": true}
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sorted_versions() {
+        let versions: FabricVersions = serde_json::from_str(
+            r#"{
+                "game": [
+                    {"version": "1.20.2", "stable": true},
+                    {"version": "1.20.10", "stable": true},
+                    {"version": "1.20.2-rc1", "stable": false},
+                    {"version": "1.19.4", "stable": true}
+                ],
+                "intermediary": [],
+                "loader": [
+                    {"separator": ".", "build": 9, "maven": "net.fabricmc:fabric-loader:0.15.9", "version": "0.15.9", "stable": true},
+                    {"separator": ".", "build": 11, "maven": "net.fabricmc:fabric-loader:0.15.11", "version": "0.15.11", "stable": true},
+                    {"separator": ".", "build": 1, "maven": "net.fabricmc:fabric-loader:0.16.0-beta.1", "version": "0.16.0-beta.1", "stable": false}
+                ],
+                "installer": []
+            }"#,
+        )
+        .unwrap();
+
+        let loaders: Vec<&str> = versions.loaders_sorted().iter().map(|v| v.version.as_str()).collect();
+        assert_eq!(loaders, ["0.16.0-beta.1", "0.15.11", "0.15.9"]);
+        let games: Vec<&str> = versions.games_sorted().iter().map(|v| v.version.as_str()).collect();
+        assert_eq!(games, ["1.20.10", "1.20.2", "1.20.2-rc1", "1.19.4"]);
+        assert_eq!(versions.latest_stable_loader().unwrap().version, "0.15.11");
+    }
+
+    #[test]
+    fn test_latest_installer() {
+        let versions = selection_fixture();
+        let installer = versions.latest_installer().unwrap();
+        // Newest stable by API order, not by string comparison of "1.0.10" and "1.0.9"
+        assert_eq!(installer.version, "1.0.10");
+        assert_eq!(
+            installer.url,
+            "https://maven.fabricmc.net/net/fabricmc/fabric-installer/1.0.10/fabric-installer-1.0.10.jar"
+        );
+
+        let none_stable = FabricVersions {
+            installer: versions.installer.iter().filter(|v| !v.stable).cloned().collect(),
+            ..versions.clone()
+        };
+        assert!(none_stable.latest_installer().is_none());
+    }
+
+    #[test]
+    fn test_intermediary_for() {
+        let versions = selection_fixture();
+        assert_eq!(versions.intermediary_for("1.20.4").unwrap().maven, "net.fabricmc:intermediary:1.20.4");
+        assert_eq!(versions.intermediary_for("1.20.1").unwrap().version, "1.20.1");
+        assert!(!versions.intermediary_for("23w51b").unwrap().stable);
+        assert!(versions.intermediary_for("1.20").is_none());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("1.20.1", "1.20.1"), 0);
+        assert_eq!(edit_distance("1.20.9", "1.20.4"), 1);
+        assert_eq!(edit_distance("0.15.1", "0.15.11"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_versions_url() {
+        assert_eq!(versions_url(DEFAULT_META_URL).unwrap().as_str(), API_URL);
+        assert_eq!(
+            versions_url("https://mirror.example.com/fabric").unwrap().as_str(),
+            "https://mirror.example.com/fabric/v2/versions/"
+        );
+        assert!(versions_url("meta.fabricmc.net").is_err());
+        assert!(versions_url("/v2/versions").is_err());
+        assert!(versions_url("file:///tmp/meta").is_err());
+    }
+
+    #[test]
+    fn test_cache_file_name_includes_url() {
+        assert_ne!(cache_file_name(API_URL), cache_file_name(OFFLINE_URL));
+        assert_eq!(cache_file_name(API_URL), cache_file_name(API_URL));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_cached_fresh_read() {
+        let dir = tempfile::tempdir().unwrap();
+        seed_cache(dir.path(), OFFLINE_URL).await;
+
+        let versions = FabricVersions::fetch_cached_url(OFFLINE_URL, dir.path(), Duration::from_secs(3600))
+            .await
+            .unwrap();
+        assert_eq!(versions.loader[0].version, "0.0.1");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_cached_stale_refresh() {
+        let server = serve_json(
+            r#"{
+                "game": [{"version": "1.20.4", "stable": true}],
+                "intermediary": [],
+                "loader": [{"separator": ".", "build": 11, "maven": "net.fabricmc:fabric-loader:0.15.11", "version": "0.15.11", "stable": true}],
+                "installer": []
+            }"#,
+        );
+        let url = server.url("/v2/versions/");
+        let dir = tempfile::tempdir().unwrap();
+        let path = seed_cache(dir.path(), &url).await;
+
+        let versions = FabricVersions::fetch_cached_url(&url, dir.path(), Duration::ZERO).await.unwrap();
+        assert_eq!(server.hits(), 1);
+        assert!(versions.find_loader("0.0.1").is_none());
+        assert!(versions.find_loader("0.15.11").is_some());
+
+        let refreshed: FabricVersions = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(refreshed.loader[0].version, "0.15.11");
+        let cached = FabricVersions::fetch_cached_url(&url, dir.path(), Duration::from_secs(3600))
+            .await
+            .unwrap();
+        assert_eq!(cached.loader[0].version, "0.15.11");
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_cached_offline_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let missing = FabricVersions::fetch_cached_url(OFFLINE_URL, dir.path(), Duration::ZERO).await;
+        assert!(missing.is_err());
+
+        seed_cache(dir.path(), OFFLINE_URL).await;
+        let stale = FabricVersions::fetch_cached_url(OFFLINE_URL, dir.path(), Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(stale.loader[0].version, "0.0.1");
+    }
+
+    #[test]
+    fn test_loader_version_info_from_fabric() {
+        let loader = LoaderVersion {
+            separator: ".".to_string(),
+            build: 11,
+            maven: "net.fabricmc:fabric-loader:0.15.11".to_string(),
+            version: "0.15.11".to_string(),
+            stable: true,
+        };
+
+        let info = LoaderVersionInfo::from(&loader);
+        assert_eq!(info.version, "0.15.11");
+        assert_eq!(info.game_version, None);
+        assert!(info.stable);
+    }
+
+    #[test]
+    fn test_loader_version_coordinate() {
+        let loader = LoaderVersion {
+            separator: ".".to_string(),
+            build: 11,
+            maven: "net.fabricmc:fabric-loader:0.15.11".to_string(),
+            version: "0.15.11".to_string(),
+            stable: true,
+        };
+
+        let coordinate = loader.coordinate().unwrap();
+        assert_eq!(coordinate.artifact, "fabric-loader");
+        assert_eq!(coordinate.version, loader.version);
+        assert_eq!(coordinate.to_path(), "net/fabricmc/fabric-loader/0.15.11/fabric-loader-0.15.11.jar");
+    }
+
+    #[cfg(feature = "network-tests")]
+    #[tokio::test]
+    async fn test_loader_versions_for_unknown_game() {
+        let loader = FabricModLoader::new();
+        let versions = loader.loader_versions_for_game("0.0.1").await.unwrap();
+        assert!(versions.is_empty());
+    }
+
+    const MIXED_FIXTURE: &str = r#"{
+        "game": [
+            {"version": "24w14a", "stable": false},
+            {"version": "1.20.4", "stable": true},
+            {"version": "1.20.3", "stable": true}
+        ],
+        "intermediary": [],
+        "loader": [
+            {"separator": ".", "build": 16, "maven": "net.fabricmc:fabric-loader:0.16.0-beta.1", "version": "0.16.0-beta.1", "stable": false},
+            {"separator": ".", "build": 11, "maven": "net.fabricmc:fabric-loader:0.15.11", "version": "0.15.11", "stable": true},
+            {"separator": ".", "build": 9, "maven": "net.fabricmc:fabric-loader:0.9.3", "version": "0.9.3", "stable": true}
+        ],
+        "installer": []
+    }"#;
+
+    #[test]
+    fn test_latest_selection_over_mixed_fixture() {
+        let versions: FabricVersions = serde_json::from_str(MIXED_FIXTURE).unwrap();
+
+        // "0.9.3" sorts above "0.15.11" as a string, so this must compare builds.
+        assert_eq!(versions.latest_stable_loader().unwrap().version, "0.15.11");
+        assert_eq!(versions.latest_loader().unwrap().version, "0.16.0-beta.1");
+        assert_eq!(versions.latest_stable_game().unwrap().version, "1.20.4");
+    }
+
+    #[test]
+    fn test_latest_selection_without_stable_entries() {
+        let mut versions: FabricVersions = serde_json::from_str(MIXED_FIXTURE).unwrap();
+        versions.loader.retain(|v| !v.stable);
+        versions.game.retain(|v| !v.stable);
+
+        assert!(versions.latest_stable_loader().is_none());
+        assert!(versions.latest_stable_game().is_none());
+        assert_eq!(versions.latest_loader().unwrap().build, 16);
+    }
+
     #[test]
     fn test_version_ordering() {
         let versions = [
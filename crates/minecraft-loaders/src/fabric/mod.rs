@@ -1,10 +1,13 @@
 mod loader;
+mod profile;
 pub mod fabric_mod_json;
 pub mod version_json;
 
 pub use loader::*;
+pub use profile::LaunchProfile;
+pub(crate) use profile::fetch_profile;
 pub use fabric_mod_json::{
     ContactInfo, DependencyVersion, EntryPoint, EntryPointObject, Environment, FabricModJson,
-    FabricModJsonError, Icon, JarInfo, License, MixinConfig, MixinConfigObject, Person,
-    PersonDetails,
+    FabricModJsonError, FabricModMetadata, Icon, JarInfo, License, MixinConfig, MixinConfigObject,
+    Person, PersonDetails, read_mod_metadata,
 };
\ No newline at end of file
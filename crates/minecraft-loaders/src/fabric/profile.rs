@@ -0,0 +1,274 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, anyhow};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use super::FabricVersions;
+use super::version_json::{Arguments, LibraryItem};
+use crate::http;
+use crate::vanilla::library::compare_versions;
+use crate::vanilla::{Library, LibrarySet};
+
+const PROFILE_BASE_URL: &str = "https://meta.fabricmc.net/v2/versions/loader";
+
+/// The combined launch profile for a game + loader pair, as served by
+/// `v2/versions/loader/{game}/{loader}/profile/json`. Quilt's meta API serves the same
+/// format under `v3`.
+///
+/// The profile only lists what Fabric adds on top of vanilla; `inherits_from`
+/// names the vanilla version whose own libraries and arguments it extends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchProfile {
+    pub id: String,
+    #[serde(rename = "inheritsFrom")]
+    pub inherits_from: String,
+    #[serde(rename = "mainClass")]
+    pub main_class: String,
+    pub libraries: Vec<LibraryItem>,
+    #[serde(default)]
+    pub arguments: Arguments,
+}
+
+impl LaunchProfile {
+    /// The profile's libraries as a [`LibrarySet`], ready to merge over the vanilla set
+    /// of [`LaunchProfile::inherits_from`].
+    pub fn library_set(&self) -> LibrarySet {
+        self.libraries.iter().map(Library::from).collect()
+    }
+
+    /// Layers `other` over this profile, the way a version JSON is composed with the
+    /// one it inherits from: first vanilla, then the loader, then any tweaks a mod's
+    /// nested jars ask for.
+    ///
+    /// - `mainClass` and `id` come from `other`, so the later profile wins.
+    /// - JVM and game arguments of `other` are appended after these.
+    /// - Libraries are deduplicated like [`LibrarySet::merge`]: by `group:artifact`
+    ///   (plus classifier), the higher version winning and keeping the first entry's
+    ///   position, with new libraries appended in `other`'s order.
+    ///
+    /// `inheritsFrom` is kept, since the merged profile still sits on this one's base.
+    pub fn merge(&mut self, other: &LaunchProfile) {
+        if !other.main_class.is_empty() {
+            self.main_class = other.main_class.clone();
+        }
+        if !other.id.is_empty() {
+            self.id = other.id.clone();
+        }
+        self.arguments.jvm.extend(other.arguments.jvm.iter().cloned());
+        self.arguments.game.extend(other.arguments.game.iter().cloned());
+
+        let mut index: HashMap<String, usize> = self
+            .libraries
+            .iter()
+            .enumerate()
+            .map(|(i, l)| (Library::from(l).dedup_key(), i))
+            .collect();
+        for item in &other.libraries {
+            let library = Library::from(item);
+            match index.get(&library.dedup_key()) {
+                Some(&i) => {
+                    let current = Library::from(&self.libraries[i]);
+                    if compare_versions(library.version(), current.version()) == Ordering::Greater {
+                        self.libraries[i] = item.clone();
+                    }
+                }
+                None => {
+                    index.insert(library.dedup_key(), self.libraries.len());
+                    self.libraries.push(item.clone());
+                }
+            }
+        }
+    }
+}
+
+impl FabricVersions {
+    /// Fetches the launch profile for a Minecraft version and Fabric loader version.
+    ///
+    /// Fails with a descriptive error when the meta API does not know the combination,
+    /// e.g. because the loader version does not exist or does not support that game version.
+    pub async fn profile_json(game: &str, loader: &str) -> Result<LaunchProfile> {
+        fetch_profile("Fabric", PROFILE_BASE_URL, game, loader).await
+    }
+}
+
+/// Fetches `{base_url}/{game}/{loader}/profile/json` from the meta API of the loader
+/// called `name`, which names it in errors.
+pub(crate) async fn fetch_profile(name: &str, base_url: &str, game: &str, loader: &str) -> Result<LaunchProfile> {
+    let mut url = reqwest::Url::parse(base_url)?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("{} meta URL cannot be a base: {}", name, base_url))?
+        .pop_if_empty()
+        .extend([game, loader, "profile", "json"]);
+
+    let response = match http::get(url).await {
+        Ok(response) => response,
+        Err(err) => {
+            let status = err.downcast_ref::<reqwest::Error>().and_then(|e| e.status());
+            return Err(match status {
+                Some(StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND) => {
+                    anyhow!("{} loader {} is not available for Minecraft {}", name, loader, game)
+                }
+                _ => err.context(format!("Failed to fetch {} launch profile", name)),
+            });
+        }
+    };
+
+    response
+        .json::<LaunchProfile>()
+        .await
+        .with_context(|| format!("Failed to parse {} launch profile", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::{Response, serve};
+
+    const PROFILE_FIXTURE: &str = r#"{
+        "id": "fabric-loader-0.15.11-1.20.4",
+        "inheritsFrom": "1.20.4",
+        "releaseTime": "2024-05-06T19:13:32+0000",
+        "time": "2024-05-06T19:13:32+0000",
+        "type": "release",
+        "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+        "arguments": {
+            "game": [],
+            "jvm": ["-DFabricMcEmu= net.minecraft.client.main.Main "]
+        },
+        "libraries": [
+            {
+                "name": "net.fabricmc:sponge-mixin:0.13.3+mixin.0.8.5",
+                "url": "https://maven.fabricmc.net/",
+                "sha1": "cc2a2b2ec4a7ee6e4a1bd2a0f5c1d8c8b27ea2a4",
+                "size": 1452964
+            },
+            {
+                "name": "net.fabricmc:fabric-loader:0.15.11",
+                "url": "https://maven.fabricmc.net/"
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_deserialize_profile_fixture() {
+        let profile: LaunchProfile = serde_json::from_str(PROFILE_FIXTURE).unwrap();
+        assert_eq!(profile.inherits_from, "1.20.4");
+        assert_eq!(profile.main_class, "net.fabricmc.loader.impl.launch.knot.KnotClient");
+        assert_eq!(profile.libraries.len(), 2);
+        assert_eq!(profile.libraries[0].size, Some(1452964));
+        assert!(profile.libraries[1].sha1.is_none());
+        assert!(profile.arguments.game.is_empty());
+        assert_eq!(profile.arguments.jvm.len(), 1);
+
+        let libraries = profile.library_set();
+        assert_eq!(
+            libraries.libraries[1].artifact().unwrap().url,
+            "https://maven.fabricmc.net/net/fabricmc/fabric-loader/0.15.11/fabric-loader-0.15.11.jar"
+        );
+    }
+
+    #[test]
+    fn test_profile_without_arguments() {
+        let profile: LaunchProfile = serde_json::from_str(r#"{"id": "x", "inheritsFrom": "1.14", "mainClass": "Main", "libraries": []}"#).unwrap();
+        assert!(profile.arguments.jvm.is_empty());
+    }
+
+    #[test]
+    fn test_merge_vanilla_with_fabric() {
+        let mut merged: LaunchProfile = serde_json::from_str(
+            r#"{
+                "id": "1.20.4",
+                "inheritsFrom": "",
+                "mainClass": "net.minecraft.client.main.Main",
+                "arguments": {"game": ["--username", "${auth_player_name}"], "jvm": ["-Djava.library.path=${natives_directory}"]},
+                "libraries": [
+                    {"name": "org.ow2.asm:asm:9.3", "url": "https://libraries.minecraft.net/"},
+                    {"name": "com.mojang:brigadier:1.2.9", "url": "https://libraries.minecraft.net/"},
+                    {"name": "org.lwjgl:lwjgl:3.3.3:natives-linux", "url": "https://libraries.minecraft.net/"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let mut fabric: LaunchProfile = serde_json::from_str(PROFILE_FIXTURE).unwrap();
+        for name in ["org.ow2.asm:asm:9.6", "org.lwjgl:lwjgl:3.3.3"] {
+            fabric.libraries.push(LibraryItem {
+                name: name.to_string(),
+                url: "https://maven.fabricmc.net/".to_string(),
+                sha1: None,
+                sha256: None,
+                sha512: None,
+                md5: None,
+                size: None,
+            });
+        }
+
+        merged.merge(&fabric);
+        assert_eq!(merged.id, "fabric-loader-0.15.11-1.20.4");
+        assert_eq!(merged.main_class, "net.fabricmc.loader.impl.launch.knot.KnotClient");
+        let names: Vec<&str> = merged.libraries.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "org.ow2.asm:asm:9.6",
+                "com.mojang:brigadier:1.2.9",
+                "org.lwjgl:lwjgl:3.3.3:natives-linux",
+                "net.fabricmc:sponge-mixin:0.13.3+mixin.0.8.5",
+                "net.fabricmc:fabric-loader:0.15.11",
+                "org.lwjgl:lwjgl:3.3.3",
+            ]
+        );
+        assert_eq!(merged.libraries[0].url, "https://maven.fabricmc.net/");
+        assert_eq!(
+            merged.arguments.jvm,
+            [
+                "-Djava.library.path=${natives_directory}",
+                "-DFabricMcEmu= net.minecraft.client.main.Main "
+            ]
+        );
+        assert_eq!(merged.arguments.game, ["--username", "${auth_player_name}"]);
+
+        // An older duplicate from a later profile does not downgrade the library.
+        let tweak: LaunchProfile = serde_json::from_str(
+            r#"{"id": "", "inheritsFrom": "", "mainClass": "", "libraries": [{"name": "org.ow2.asm:asm:9.3", "url": "https://example.com/"}]}"#,
+        )
+        .unwrap();
+        merged.merge(&tweak);
+        assert_eq!(merged.libraries.len(), 6);
+        assert_eq!(merged.libraries[0].name, "org.ow2.asm:asm:9.6");
+        assert_eq!(merged.main_class, "net.fabricmc.loader.impl.launch.knot.KnotClient");
+        assert_eq!(merged.inherits_from, "");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_combination_is_a_clear_error() {
+        let server = serve(|_| Response::status(400).with_body("no loader version found"));
+        let base_url = server.url("/v2/versions/loader");
+
+        let err = fetch_profile("Fabric", &base_url, "1.20.4", "0.0.0").await.unwrap_err();
+        assert_eq!(err.to_string(), "Fabric loader 0.0.0 is not available for Minecraft 1.20.4");
+
+        assert_eq!(server.paths(), ["/v2/versions/loader/1.20.4/0.0.0/profile/json"]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_profile_from_fixture() {
+        let server = serve(|_| Response::ok(PROFILE_FIXTURE).with_header("Content-Type", "application/json"));
+        let base_url = server.url("/v2/versions/loader");
+
+        let profile = fetch_profile("Fabric", &base_url, "1.20.4", "0.15.11").await.unwrap();
+        assert_eq!(profile.id, "fabric-loader-0.15.11-1.20.4");
+        assert_eq!(profile.main_class, "net.fabricmc.loader.impl.launch.knot.KnotClient");
+        assert_eq!(profile.libraries[1].name, "net.fabricmc:fabric-loader:0.15.11");
+        assert_eq!(server.paths(), ["/v2/versions/loader/1.20.4/0.15.11/profile/json"]);
+    }
+
+    #[cfg(feature = "network-tests")]
+    #[tokio::test]
+    async fn test_fetch_profile_json() {
+        let profile = FabricVersions::profile_json("1.20.4", "0.15.11").await.unwrap();
+        assert_eq!(profile.inherits_from, "1.20.4");
+        assert!(profile.libraries.iter().any(|l| l.name.starts_with("net.fabricmc:fabric-loader:")));
+    }
+}
@@ -1,8 +1,10 @@
 use anyhow::Result;
 use piston_mc::manifest_v2::ReleaseType;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+use crate::maven::MavenCoordinate;
+
 #[derive(Deserialize, Debug)]
 pub struct VersionJson {
     pub id: String,
@@ -19,7 +21,7 @@ pub struct VersionJson {
     pub arguments: Arguments,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LibraryItem {
     pub name: String,
     pub url: String,
@@ -34,9 +36,11 @@ pub struct LibraryItem {
     #[serde(default)]
     pub size: Option<usize>,
 }
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Arguments {
+    #[serde(default)]
     pub jvm: Vec<String>,
+    #[serde(default)]
     pub game: Vec<String>,
 }
 
@@ -68,18 +72,9 @@ impl VersionJson {
     }
 
     fn path_from_maven(maven: &str) -> String {
-        let parts: Vec<&str> = maven.split(':').collect();
-        if parts.len() != 3 {
-            return maven.to_string();
+        match MavenCoordinate::parse(maven) {
+            Ok(coordinate) => coordinate.to_path(),
+            Err(_) => maven.to_string(),
         }
-
-        let group = parts[0].replace('.', "/");
-        let artifact = parts[1];
-        let version = parts[2];
-
-        format!(
-            "{}/{}/{}/{}-{}.jar",
-            group, artifact, version, artifact, version
-        )
     }
 }
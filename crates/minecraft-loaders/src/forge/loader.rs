@@ -7,9 +7,12 @@ use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
-use crate::ModLoader;
+use crate::http::{self, HttpClient};
+use crate::maven::MavenCoordinate;
+use crate::{LoaderError, LoaderVersionInfo, ModLoader, ModLoaderVersions};
 
-const VERSIONS_URL: &str = "https://files.minecraftforge.net/net/minecraftforge/forge/maven-metadata.json";
+pub(crate) const VERSIONS_URL: &str = "https://files.minecraftforge.net/net/minecraftforge/forge/maven-metadata.json";
+const PROMOTIONS_URL: &str = "https://files.minecraftforge.net/net/minecraftforge/forge/promotions_slim.json";
 const MAVEN_BASE_URL: &str = "https://maven.minecraftforge.net/net/minecraftforge/forge";
 
 /// Forge version era determines how the server/client should be launched.
@@ -57,23 +60,19 @@ pub struct ForgeVersions {
 
 impl ForgeVersions {
     /// Fetch all available Forge versions from the API.
-    pub async fn fetch() -> Result<Self> {
-        let response = reqwest::get(VERSIONS_URL)
-            .await
-            .context("Failed to fetch Forge versions")?;
-
-        if !response.status().is_success() {
-            return Err(anyhow!(
-                "Failed to fetch Forge versions: HTTP {}",
-                response.status()
-            ));
-        }
+    pub async fn fetch() -> Result<Self, LoaderError> {
+        Self::fetch_with_client(http::client()).await
+    }
 
-        let versions: HashMap<String, Vec<String>> = response
-            .json()
-            .await
-            .context("Failed to parse Forge versions")?;
+    /// Like [`ForgeVersions::fetch`], but through a caller-provided HTTP client.
+    pub async fn fetch_with_client(client: &impl HttpClient) -> Result<Self, LoaderError> {
+        Self::fetch_from_url(client, VERSIONS_URL).await
+    }
 
+    /// Fetch the versions from a `maven-metadata.json` served at `url`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "fetch_forge_versions", skip_all, fields(url = %url)))]
+    pub(crate) async fn fetch_from_url(client: &impl HttpClient, url: &str) -> Result<Self, LoaderError> {
+        let versions = client.get_json(url).await?;
         Ok(Self { versions })
     }
 
@@ -98,6 +97,25 @@ impl ForgeVersions {
         })
     }
 
+    /// Get all Forge builds for a Minecraft version as structured versions, newest first.
+    pub fn versions_for_game(&self, minecraft_version: &str) -> Vec<ForgeVersion> {
+        let mut versions: Vec<ForgeVersion> = self
+            .versions
+            .get(minecraft_version)
+            .map(|versions| versions.iter().filter_map(|v| ForgeVersion::parse(v)).collect())
+            .unwrap_or_default();
+        versions.sort_by(|a, b| crate::versions::compare_versions(&b.forge_version, &a.forge_version));
+        versions
+    }
+
+    /// Get the newest Forge build for a Minecraft version.
+    ///
+    /// This is the highest build number, which may not be the one Forge recommends;
+    /// see [`ForgePromotions::recommended`] for that.
+    pub fn latest(&self, minecraft_version: &str) -> Option<ForgeVersion> {
+        self.versions_for_game(minecraft_version).into_iter().next()
+    }
+
     /// Get all supported Minecraft versions.
     pub fn get_supported_minecraft_versions(&self) -> Vec<&str> {
         let mut versions: Vec<&str> = self.versions.keys().map(|s| s.as_str()).collect();
@@ -117,6 +135,83 @@ impl ForgeVersions {
     }
 }
 
+/// A Forge build parsed from a combined `<minecraft>-<forge>[-<branch>]` version key.
+///
+/// Some pre-1.8 builds carry a trailing branch name, e.g. `1.7.10-10.13.4.1614-1.7.10`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForgeVersion {
+    /// The Minecraft version (e.g., "1.20.1")
+    pub minecraft_version: String,
+    /// The Forge version (e.g., "47.2.0")
+    pub forge_version: String,
+    /// The optional branch suffix found on some legacy builds
+    pub branch: Option<String>,
+}
+
+impl ForgeVersion {
+    /// Parse a combined Forge version key like `1.20.1-47.2.0`.
+    pub fn parse(full_version: &str) -> Option<Self> {
+        let (minecraft_version, rest) = full_version.split_once('-')?;
+        let (forge_version, branch) = match rest.split_once('-') {
+            Some((forge, branch)) => (forge, Some(branch.to_string())),
+            None => (rest, None),
+        };
+        if minecraft_version.is_empty() || forge_version.is_empty() {
+            return None;
+        }
+        Some(Self {
+            minecraft_version: minecraft_version.to_string(),
+            forge_version: forge_version.to_string(),
+            branch,
+        })
+    }
+
+    /// The full version key as used in Maven paths (e.g., "1.20.1-47.2.0").
+    pub fn full_version(&self) -> String {
+        match &self.branch {
+            Some(branch) => format!("{}-{}-{}", self.minecraft_version, self.forge_version, branch),
+            None => format!("{}-{}", self.minecraft_version, self.forge_version),
+        }
+    }
+}
+
+/// Recommended and latest Forge builds per Minecraft version, from Forge's promotions feed.
+///
+/// This only tells you *which* build to pick; downloading the installer JAR is a
+/// separate step (see [`ForgeModLoader`]'s `download_client`/`install_client`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgePromotions {
+    /// Map of `<minecraft>-recommended` / `<minecraft>-latest` -> Forge version
+    pub promos: HashMap<String, String>,
+}
+
+impl ForgePromotions {
+    /// Fetch the promotions feed from the Forge website.
+    pub async fn fetch() -> Result<Self, LoaderError> {
+        Self::fetch_with_client(http::client()).await
+    }
+
+    /// Like [`ForgePromotions::fetch`], but through a caller-provided HTTP client.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "fetch_forge_promotions", skip_all))]
+    pub async fn fetch_with_client(client: &impl HttpClient) -> Result<Self, LoaderError> {
+        client.get_json(PROMOTIONS_URL).await
+    }
+
+    /// The Forge version Forge recommends for a Minecraft version, if any.
+    pub fn recommended(&self, minecraft_version: &str) -> Option<&str> {
+        self.promos
+            .get(&format!("{}-recommended", minecraft_version))
+            .map(|s| s.as_str())
+    }
+
+    /// The latest promoted Forge version for a Minecraft version, if any.
+    pub fn latest(&self, minecraft_version: &str) -> Option<&str> {
+        self.promos
+            .get(&format!("{}-latest", minecraft_version))
+            .map(|s| s.as_str())
+    }
+}
+
 /// Forge mod loader implementation.
 ///
 /// Handles three different eras of Forge:
@@ -199,7 +294,9 @@ impl ForgeModLoader {
 
     /// Downloads a file from a URL to the specified path.
     async fn download_file(url: &str, output_path: &Path) -> Result<PathBuf> {
-        let response = reqwest::get(url)
+        let response = http::client()
+            .get(url)
+            .send()
             .await
             .context("Failed to download file")?;
 
@@ -360,87 +457,37 @@ impl ForgeModLoader {
         library_directory: &Path,
     ) -> Result<()> {
         for lib in libraries {
-            // Parse library name (format: "group:artifact:version" or "group:artifact:version:classifier")
-            if let Some(name) = lib.get("name").and_then(|v| v.as_str()) {
-                let parts: Vec<&str> = name.split(':').collect();
-                if parts.len() >= 3 {
-                    let group = parts[0].replace('.', "/");
-                    let artifact = parts[1];
-                    let version = parts[2];
-                    let classifier = parts.get(3).copied();
-
-                    // Get the path from downloads if available, otherwise construct it
-                    let (url, lib_path) = if let Some(downloads) = lib.get("downloads") {
-                        if let Some(artifact_info) = downloads.get("artifact") {
-                            let url = artifact_info
-                                .get("url")
-                                .and_then(|u| u.as_str())
-                                .filter(|s| !s.is_empty())
-                                .map(|s| s.to_string());
-
-                            let path = artifact_info
-                                .get("path")
-                                .and_then(|p| p.as_str())
-                                .map(|p| library_directory.join(p));
-
-                            (url, path)
-                        } else {
-                            (None, None)
-                        }
-                    } else {
-                        (None, None)
-                    };
-
-                    // If no URL from downloads, try Maven repositories
-                    let url = url.or_else(|| {
-                        let jar_name = if let Some(c) = classifier {
-                            format!("{}-{}-{}.jar", artifact, version, c)
-                        } else {
-                            format!("{}-{}.jar", artifact, version)
-                        };
-
-                        // Try Forge Maven first, then Minecraft libraries
-                        Some(format!(
-                            "https://maven.minecraftforge.net/{}/{}/{}/{}",
-                            group, artifact, version, jar_name
-                        ))
-                    });
-
-                    // If no path from downloads, construct it
-                    let lib_path = lib_path.unwrap_or_else(|| {
-                        let jar_name = if let Some(c) = classifier {
-                            format!("{}-{}-{}.jar", artifact, version, c)
-                        } else {
-                            format!("{}-{}.jar", artifact, version)
-                        };
-                        library_directory
-                            .join(&group)
-                            .join(artifact)
-                            .join(version)
-                            .join(jar_name)
-                    });
-
-                    // Download if doesn't exist and we have a URL
-                    if let Some(url_str) = url
-                        && !lib_path.exists()
-                    {
-                        // Try primary URL first
-                        let result = Self::download_file(&url_str, &lib_path).await;
-
-                        // If failed, try Minecraft libraries as fallback
-                        if result.is_err() {
-                            let jar_name = if let Some(c) = classifier {
-                                format!("{}-{}-{}.jar", artifact, version, c)
-                            } else {
-                                format!("{}-{}.jar", artifact, version)
-                            };
-                            let mc_url = format!(
-                                "https://libraries.minecraft.net/{}/{}/{}/{}",
-                                group, artifact, version, jar_name
-                            );
-                            let _ = Self::download_file(&mc_url, &lib_path).await;
-                        }
-                    }
+            // Parse library name (format: "group:artifact:version[:classifier][@extension]")
+            let Some(coordinate) = lib
+                .get("name")
+                .and_then(|v| v.as_str())
+                .and_then(|name| MavenCoordinate::parse(name).ok())
+            else {
+                continue;
+            };
+
+            // Get the path from downloads if available, otherwise construct it
+            let artifact_info = lib.get("downloads").and_then(|d| d.get("artifact"));
+            let url = artifact_info
+                .and_then(|a| a.get("url"))
+                .and_then(|u| u.as_str())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string());
+            let lib_path = artifact_info
+                .and_then(|a| a.get("path"))
+                .and_then(|p| p.as_str())
+                .map(|p| library_directory.join(p))
+                .unwrap_or_else(|| library_directory.join(coordinate.to_path()));
+
+            // If no URL from downloads, try Forge Maven first
+            let url = url.unwrap_or_else(|| coordinate.to_url("https://maven.minecraftforge.net/"));
+
+            // Download if doesn't exist
+            if !lib_path.exists() {
+                // Try primary URL first, then Minecraft libraries as fallback
+                if Self::download_file(&url, &lib_path).await.is_err() {
+                    let mc_url = coordinate.to_url("https://libraries.minecraft.net/");
+                    let _ = Self::download_file(&mc_url, &lib_path).await;
                 }
             }
         }
@@ -710,6 +757,51 @@ impl ModLoader for ForgeModLoader {
     }
 }
 
+#[async_trait]
+impl ModLoaderVersions for ForgeModLoader {
+    fn name(&self) -> &'static str {
+        "Forge"
+    }
+
+    async fn fetch_versions(&self) -> Result<Vec<LoaderVersionInfo>> {
+        let versions = ForgeVersions::fetch().await?;
+        let promotions = ForgePromotions::fetch().await?;
+        Ok(versions
+            .get_supported_minecraft_versions()
+            .into_iter()
+            .flat_map(|mc| versions.versions_for_game(mc))
+            .map(|v| version_info(v, &promotions))
+            .collect())
+    }
+
+    async fn loader_versions_for_game(&self, game: &str) -> Result<Vec<LoaderVersionInfo>> {
+        let versions = ForgeVersions::fetch().await?;
+        let promotions = ForgePromotions::fetch().await?;
+        Ok(versions
+            .versions_for_game(game)
+            .into_iter()
+            .map(|v| version_info(v, &promotions))
+            .collect())
+    }
+
+    async fn latest_stable_loader(&self, game: &str) -> Result<Option<LoaderVersionInfo>> {
+        let versions = self.loader_versions_for_game(game).await?;
+        let recommended = versions.iter().find(|v| v.stable).cloned();
+        Ok(recommended.or_else(|| versions.into_iter().next()))
+    }
+}
+
+/// Forge has no per-build stability flag, so only the build promoted as recommended
+/// for its Minecraft version is marked stable.
+fn version_info(version: ForgeVersion, promotions: &ForgePromotions) -> LoaderVersionInfo {
+    let stable = promotions.recommended(&version.minecraft_version) == Some(version.forge_version.as_str());
+    LoaderVersionInfo {
+        version: version.forge_version,
+        game_version: Some(version.minecraft_version),
+        stable,
+    }
+}
+
 impl ForgeModLoader {
     /// Run a Forge client with proper classpath and arguments.
     /// This is the recommended way to run a Forge client, as it properly sets up
@@ -984,6 +1076,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_forge_version() {
+        let v = ForgeVersion::parse("1.20.1-47.2.0").unwrap();
+        assert_eq!(v.minecraft_version, "1.20.1");
+        assert_eq!(v.forge_version, "47.2.0");
+        assert_eq!(v.branch, None);
+        assert_eq!(v.full_version(), "1.20.1-47.2.0");
+
+        let v = ForgeVersion::parse("1.7.10-10.13.4.1614-1.7.10").unwrap();
+        assert_eq!(v.minecraft_version, "1.7.10");
+        assert_eq!(v.forge_version, "10.13.4.1614");
+        assert_eq!(v.branch.as_deref(), Some("1.7.10"));
+        assert_eq!(v.full_version(), "1.7.10-10.13.4.1614-1.7.10");
+
+        assert!(ForgeVersion::parse("47.2.0").is_none());
+    }
+
+    #[test]
+    fn test_versions_from_fixture() {
+        let json = r#"{
+            "1.20.1": ["1.20.1-47.0.1", "1.20.1-47.2.0", "1.20.1-47.2.10", "1.20.1-47.2.9"],
+            "1.7.10": ["1.7.10-10.13.4.1558-1.7.10", "1.7.10-10.13.4.1614-1.7.10"]
+        }"#;
+        let versions: ForgeVersions = serde_json::from_str(json).unwrap();
+
+        let for_game: Vec<String> = versions
+            .versions_for_game("1.20.1")
+            .into_iter()
+            .map(|v| v.forge_version)
+            .collect();
+        assert_eq!(for_game, vec!["47.2.10", "47.2.9", "47.2.0", "47.0.1"]);
+
+        assert_eq!(versions.latest("1.20.1").unwrap().forge_version, "47.2.10");
+        assert_eq!(versions.latest("1.7.10").unwrap().forge_version, "10.13.4.1614");
+        assert!(versions.latest("1.0").is_none());
+    }
+
+    #[test]
+    fn test_promotions_from_fixture() {
+        let json = r#"{
+            "homepage": "https://files.minecraftforge.net/net/minecraftforge/forge/",
+            "promos": {
+                "1.20.1-latest": "47.2.10",
+                "1.20.1-recommended": "47.2.0",
+                "1.20.4-latest": "49.0.30"
+            }
+        }"#;
+        let promotions: ForgePromotions = serde_json::from_str(json).unwrap();
+
+        assert_eq!(promotions.recommended("1.20.1"), Some("47.2.0"));
+        assert_eq!(promotions.latest("1.20.1"), Some("47.2.10"));
+        assert_eq!(promotions.recommended("1.20.4"), None);
+        assert_eq!(promotions.latest("1.20.4"), Some("49.0.30"));
+    }
+
+    #[test]
+    fn test_only_recommended_builds_are_stable() {
+        let promotions: ForgePromotions =
+            serde_json::from_str(r#"{"promos": {"1.20.1-latest": "47.2.10", "1.20.1-recommended": "47.2.0"}}"#).unwrap();
+        let stable = |key: &str| version_info(ForgeVersion::parse(key).unwrap(), &promotions).stable;
+
+        assert!(stable("1.20.1-47.2.0"));
+        assert!(!stable("1.20.1-47.2.10"));
+        assert!(!stable("1.20.4-49.0.30"));
+    }
+
     #[tokio::test]
     async fn test_fetch_versions() {
         let versions = ForgeVersions::fetch().await;
@@ -1,8 +1,10 @@
 pub mod loader;
 pub mod mod_toml;
+pub mod processors;
 
-pub use loader::{ForgeEra, ForgeModLoader, ForgeVersions};
+pub use loader::{ForgeEra, ForgeModLoader, ForgePromotions, ForgeVersion, ForgeVersions};
 pub use mod_toml::{
     Dependency, DependencyOrdering, DependencySide, ForgeModTomlError, ForgeModsToml,
     ModDefinition,
-};
\ No newline at end of file
+};
+pub use processors::{ForgeInstaller, InstallProfile, Processor, ProcessorReport, ProcessorRunner, SidedData};
@@ -0,0 +1,634 @@
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::download::{self, Cancelled, DownloadJob, Downloader};
+use crate::maven::MavenCoordinate;
+use crate::vanilla::{LibrarySet, RuleContext};
+
+/// Where an installer keeps its [`InstallProfile`].
+pub const INSTALL_PROFILE: &str = "install_profile.json";
+
+/// The `install_profile.json` of a Forge or NeoForge installer for Minecraft 1.13 and
+/// later, listing the processors that turn the vanilla client jar into the patched
+/// one the game launches with.
+///
+/// Installers for older versions store a different profile without processors, and
+/// fail to parse as this one.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct InstallProfile {
+    #[serde(default)]
+    pub spec: u32,
+    /// The version id the installer creates, e.g. `1.20.1-forge-47.2.0`.
+    pub version: String,
+    /// The Minecraft version whose client jar the processors patch.
+    pub minecraft: String,
+    /// Where the launch profile is inside the installer, usually `/version.json`.
+    #[serde(default = "default_version_json")]
+    pub json: String,
+    /// Values processor arguments refer to as `{KEY}`, per side.
+    #[serde(default)]
+    pub data: HashMap<String, SidedData>,
+    /// Run in order; each may read what the ones before it wrote.
+    #[serde(default)]
+    pub processors: Vec<Processor>,
+    /// The processor jars and their classpaths. Entries with an empty download URL
+    /// ship inside the installer under `maven/`.
+    #[serde(default)]
+    pub libraries: LibrarySet,
+}
+
+fn default_version_json() -> String {
+    "/version.json".to_string()
+}
+
+/// An [`InstallProfile::data`] value for each side.
+///
+/// A value is a maven coordinate in brackets (`[group:artifact:version]`) standing
+/// for that library's path, a literal in single quotes, or the path of a file inside
+/// the installer.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SidedData {
+    pub client: String,
+    pub server: String,
+}
+
+/// One step of an [`InstallProfile`]: a jar run with `args` on its classpath.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Processor {
+    /// Maven coordinate of the jar to run; its manifest names the main class.
+    pub jar: String,
+    #[serde(default)]
+    pub classpath: Vec<String>,
+    /// Arguments with `{KEY}` tokens and `[coordinate]` library references.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Files the processor writes, mapped to their expected SHA-1. Both sides may use
+    /// `{KEY}` tokens.
+    #[serde(default)]
+    pub outputs: BTreeMap<String, String>,
+    /// `client` and/or `server`; empty when the processor runs for both.
+    #[serde(default)]
+    pub sides: Vec<String>,
+}
+
+impl Processor {
+    pub fn runs_on_client(&self) -> bool {
+        self.sides.is_empty() || self.sides.iter().any(|side| side == "client")
+    }
+}
+
+/// A downloaded Forge or NeoForge installer jar, read without running it.
+#[derive(Debug, Clone)]
+pub struct ForgeInstaller {
+    path: PathBuf,
+    profile: InstallProfile,
+}
+
+impl ForgeInstaller {
+    /// Reads the [`InstallProfile`] of the installer at `path`.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let profile = read_entry(&path, INSTALL_PROFILE)?;
+        let profile = serde_json::from_slice(&profile).with_context(|| {
+            format!(
+                "Failed to parse {} of {}; installers before 1.13 have no processors",
+                INSTALL_PROFILE,
+                path.display()
+            )
+        })?;
+        Ok(Self { path, profile })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn profile(&self) -> &InstallProfile {
+        &self.profile
+    }
+
+    /// The launch profile the installer adds, read from [`InstallProfile::json`].
+    pub fn version_json(&self) -> Result<serde_json::Value> {
+        let json = read_entry(&self.path, self.profile.json.trim_start_matches('/'))?;
+        serde_json::from_slice(&json).with_context(|| format!("Failed to parse {} of {}", self.profile.json, self.path.display()))
+    }
+
+    /// The processors a client install runs, in order.
+    pub fn client_processors(&self) -> impl Iterator<Item = &Processor> {
+        self.profile.processors.iter().filter(|processor| processor.runs_on_client())
+    }
+}
+
+/// What [`ProcessorRunner::run`] did, as processor jar coordinates in run order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessorReport {
+    pub ran: Vec<String>,
+    /// Processors whose outputs were already in place with the expected SHA-1.
+    pub skipped: Vec<String>,
+}
+
+/// Runs the client processors of a [`ForgeInstaller`], which patch the vanilla client
+/// jar into the one modern Forge launches with. This is the part of the installer's
+/// own client install that has to run Java.
+///
+/// The vanilla client jar must already be in place. Server-only processors are left
+/// out.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use minecraft_modloaders::forge::{ForgeInstaller, ProcessorRunner};
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let installer = ForgeInstaller::open("forge-1.20.1-47.2.0-installer.jar")?;
+/// let report = ProcessorRunner::new(&installer, "minecraft/libraries", "minecraft/versions/1.20.1/1.20.1.jar", "java")
+///     .run()
+///     .await?;
+/// println!("ran {} processors, {} were up to date", report.ran.len(), report.skipped.len());
+/// # Ok(())
+/// # }
+/// ```
+pub struct ProcessorRunner<'a> {
+    installer: &'a ForgeInstaller,
+    libraries_dir: PathBuf,
+    minecraft_jar: PathBuf,
+    java: PathBuf,
+    root: Option<PathBuf>,
+    downloader: Downloader,
+}
+
+impl<'a> ProcessorRunner<'a> {
+    pub fn new(
+        installer: &'a ForgeInstaller,
+        libraries_dir: impl Into<PathBuf>,
+        minecraft_jar: impl Into<PathBuf>,
+        java: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            installer,
+            libraries_dir: libraries_dir.into(),
+            minecraft_jar: minecraft_jar.into(),
+            java: java.into(),
+            root: None,
+            downloader: Downloader::new(),
+        }
+    }
+
+    /// Set the `.minecraft` directory processors see as `{ROOT}` (default: the parent
+    /// of the libraries directory).
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Use a configured [`Downloader`] for the processor libraries.
+    pub fn with_downloader(mut self, downloader: Downloader) -> Self {
+        self.downloader = downloader;
+        self
+    }
+
+    /// Puts the processor libraries in place, then runs every client processor in
+    /// order and checks the SHA-1 of each file it declares as an output.
+    ///
+    /// A processor whose outputs are all present with the expected SHA-1 is skipped,
+    /// so running again after a successful install does not start Java. An output
+    /// with the wrong hash is deleted and fails the run. Files the processors read
+    /// from the installer are extracted to a temporary directory that is removed
+    /// afterwards.
+    pub async fn run(&self) -> Result<ProcessorReport> {
+        if !self.minecraft_jar.is_file() {
+            bail!(
+                "The Minecraft {} client jar must be installed before running processors; not found at {}",
+                self.installer.profile.minecraft,
+                self.minecraft_jar.display()
+            );
+        }
+        self.install_libraries().await?;
+
+        // Unique per run, as several installs may run in one process.
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+        let work_dir = std::env::temp_dir().join(format!(
+            "forge-processors-{}-{}-{}",
+            self.installer.profile.version,
+            std::process::id(),
+            RUNS.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = self.run_processors(&work_dir).await;
+        let _ = std::fs::remove_dir_all(&work_dir);
+        result
+    }
+
+    /// Downloads the profile's libraries, extracting those that ship inside the installer.
+    async fn install_libraries(&self) -> Result<()> {
+        let mut jobs = Vec::new();
+        for library in self.installer.profile.libraries.allowed(&RuleContext::default()) {
+            let Some(artifact) = library.artifact() else {
+                continue;
+            };
+            let Some(relative) = download::enclosed_path(&artifact.path) else {
+                bail!("Refusing to install {} outside of {}", library.name, self.libraries_dir.display());
+            };
+            let dest = self.libraries_dir.join(relative);
+            if !artifact.url.is_empty() {
+                let mut job = DownloadJob::new(&artifact.url, dest);
+                if let Some(sha1) = &artifact.sha1 {
+                    job = job.with_sha1(sha1);
+                }
+                if let Some(size) = artifact.size {
+                    job = job.with_size(size);
+                }
+                jobs.push(job);
+                continue;
+            }
+
+            let up_to_date = match &artifact.sha1 {
+                Some(sha1) => download::verify_sha1(&dest, sha1).await?,
+                None => dest.is_file(),
+            };
+            if up_to_date {
+                continue;
+            }
+            let contents = read_entry(&self.installer.path, &format!("maven/{}", artifact.path))?;
+            if let Some(sha1) = &artifact.sha1 {
+                if !download::sha1_hex(&contents).eq_ignore_ascii_case(sha1) {
+                    bail!("{} in the installer does not have the expected SHA-1 {}", library.name, sha1);
+                }
+            }
+            if let Some(parent) = dest.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&dest, contents).await?;
+        }
+
+        let summary = self.downloader.download(jobs).await;
+        if summary.is_cancelled() {
+            return Err(Cancelled.into());
+        }
+        match summary.failed.into_iter().next() {
+            Some(failure) => Err(failure.error.context(format!("Failed to download processor library {}", failure.job.url))),
+            None => Ok(()),
+        }
+    }
+
+    async fn run_processors(&self, work_dir: &Path) -> Result<ProcessorReport> {
+        let data = self.client_data(work_dir)?;
+        let mut report = ProcessorReport::default();
+        for processor in self.installer.client_processors() {
+            let outputs = processor
+                .outputs
+                .iter()
+                .map(|(path, sha1)| -> Result<(PathBuf, String)> { Ok((PathBuf::from(replace_tokens(path, &data)?), replace_tokens(sha1, &data)?)) })
+                .collect::<Result<Vec<_>>>()?;
+            if !outputs.is_empty() && outputs_intact(&outputs).await? {
+                report.skipped.push(processor.jar.clone());
+                continue;
+            }
+
+            self.run_processor(processor, &data).await?;
+            for (path, sha1) in &outputs {
+                if !download::verify_sha1(path, sha1).await? {
+                    let _ = tokio::fs::remove_file(path).await;
+                    bail!(
+                        "Processor {} did not write {} with the expected SHA-1 {}",
+                        processor.jar,
+                        path.display(),
+                        sha1
+                    );
+                }
+            }
+            report.ran.push(processor.jar.clone());
+        }
+        Ok(report)
+    }
+
+    async fn run_processor(&self, processor: &Processor, data: &HashMap<String, String>) -> Result<()> {
+        let jar = self.library_path(&processor.jar)?;
+        let main_class = main_class(&jar)?;
+        let mut classpath = vec![jar];
+        for entry in &processor.classpath {
+            classpath.push(self.library_path(entry)?);
+        }
+        let args = processor.args.iter().map(|arg| self.map_arg(arg, data)).collect::<Result<Vec<_>>>()?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(jar = %processor.jar, main_class = %main_class, ?args, "running processor");
+        // `java -jar` ignores `-cp`, and processors need their classpath, so run the
+        // manifest's main class directly as the installer does.
+        let output = tokio::process::Command::new(&self.java)
+            .arg("-cp")
+            .arg(std::env::join_paths(&classpath)?)
+            .arg(&main_class)
+            .args(&args)
+            .output()
+            .await
+            .with_context(|| format!("Failed to start {} for processor {}", self.java.display(), processor.jar))?;
+        if !output.status.success() {
+            bail!(
+                "Processor {} exited with {}: {}",
+                processor.jar,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+
+    /// The `{KEY}` values of a client install: the profile's client data with library
+    /// references turned into paths and installer files extracted into `work_dir`,
+    /// plus the keys the installer itself defines.
+    fn client_data(&self, work_dir: &Path) -> Result<HashMap<String, String>> {
+        let profile = &self.installer.profile;
+        let mut data = HashMap::new();
+        for (key, value) in &profile.data {
+            let value = &value.client;
+            let resolved = match artifact_reference(value) {
+                Some(coordinate) => path_string(&self.library_path(coordinate)?),
+                None => match value.strip_prefix('\'').and_then(|literal| literal.strip_suffix('\'')) {
+                    Some(literal) => literal.to_string(),
+                    None => path_string(&extract_entry(&self.installer.path, value.trim_start_matches('/'), work_dir)?),
+                },
+            };
+            data.insert(key.clone(), resolved);
+        }
+
+        let root = match &self.root {
+            Some(root) => root.clone(),
+            None => self.libraries_dir.parent().map(Path::to_path_buf).unwrap_or_default(),
+        };
+        data.insert("SIDE".to_string(), "client".to_string());
+        data.insert("MINECRAFT_JAR".to_string(), path_string(&self.minecraft_jar));
+        data.insert("MINECRAFT_VERSION".to_string(), profile.minecraft.clone());
+        data.insert("ROOT".to_string(), path_string(&root));
+        data.insert("INSTALLER".to_string(), path_string(&self.installer.path));
+        data.insert("LIBRARY_DIR".to_string(), path_string(&self.libraries_dir));
+        Ok(data)
+    }
+
+    fn map_arg(&self, arg: &str, data: &HashMap<String, String>) -> Result<String> {
+        match artifact_reference(arg) {
+            Some(coordinate) => Ok(path_string(&self.library_path(coordinate)?)),
+            None => replace_tokens(arg, data),
+        }
+    }
+
+    fn library_path(&self, coordinate: &str) -> Result<PathBuf> {
+        Ok(self.libraries_dir.join(MavenCoordinate::parse(coordinate)?.to_path()))
+    }
+}
+
+/// The coordinate of a `[group:artifact:version]` library reference.
+fn artifact_reference(value: &str) -> Option<&str> {
+    value.strip_prefix('[')?.strip_suffix(']')
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Expands the `{KEY}` tokens of a processor argument from `data` the way the Forge
+/// installer does: text in single quotes is taken literally, and `\` escapes the
+/// next character.
+fn replace_tokens(value: &str, data: &HashMap<String, String>) -> Result<String> {
+    let unterminated = || anyhow!("Unterminated token in processor argument {:?}", value);
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push(chars.next().ok_or_else(unterminated)?),
+            '{' | '\'' => {
+                let close = if c == '{' { '}' } else { '\'' };
+                let mut token = String::new();
+                loop {
+                    match chars.next().ok_or_else(unterminated)? {
+                        '\\' => token.push(chars.next().ok_or_else(unterminated)?),
+                        d if d == close => break,
+                        d => token.push(d),
+                    }
+                }
+                match c {
+                    '\'' => out.push_str(&token),
+                    _ => match data.get(&token) {
+                        Some(replacement) => out.push_str(replacement),
+                        None => bail!("Processor argument {:?} uses unknown key {}", value, token),
+                    },
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+async fn outputs_intact(outputs: &[(PathBuf, String)]) -> Result<bool> {
+    for (path, sha1) in outputs {
+        if !download::verify_sha1(path, sha1).await? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// The `Main-Class` of a jar's manifest, whose lines wrap at 72 bytes onto
+/// continuation lines starting with a space.
+fn main_class(jar: &Path) -> Result<String> {
+    let manifest = read_entry(jar, "META-INF/MANIFEST.MF")?;
+    let mut lines: Vec<String> = Vec::new();
+    for line in String::from_utf8_lossy(&manifest).lines() {
+        match (line.strip_prefix(' '), lines.last_mut()) {
+            (Some(continued), Some(last)) => last.push_str(continued),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+        .iter()
+        .find_map(|line| line.strip_prefix("Main-Class:"))
+        .map(|class| class.trim().to_string())
+        .ok_or_else(|| anyhow!("{} has no Main-Class in its manifest", jar.display()))
+}
+
+/// Reads the entry `name` of the zip or jar at `path`.
+fn read_entry(path: &Path, name: &str) -> Result<Vec<u8>> {
+    let file = std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file).with_context(|| format!("Failed to read {} as a jar", path.display()))?;
+    let mut entry = archive.by_name(name).with_context(|| format!("No {} in {}", name, path.display()))?;
+    let mut contents = Vec::new();
+    entry.read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+/// Extracts the entry `name` of the jar at `path` into the same place under `dir`,
+/// returning where it was written.
+fn extract_entry(path: &Path, name: &str, dir: &Path) -> Result<PathBuf> {
+    let file = std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file).with_context(|| format!("Failed to read {} as a jar", path.display()))?;
+    let mut entry = archive.by_name(name).with_context(|| format!("No {} in {}", name, path.display()))?;
+    // `enclosed_name` rejects absolute paths and `..` components.
+    let Some(relative) = entry.enclosed_name() else {
+        bail!("Refusing to extract {} from {} outside of {}", name, path.display(), dir.display());
+    };
+    let dest = dir.join(relative);
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::io::copy(&mut entry, &mut std::fs::File::create(&dest)?)?;
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    fn write_jar(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(std::fs::File::create(path).unwrap());
+        for (name, contents) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    const PROFILE: &str = r#"{
+        "spec": 1,
+        "profile": "forge",
+        "version": "1.20.1-forge-47.2.0",
+        "minecraft": "1.20.1",
+        "json": "/version.json",
+        "path": null,
+        "data": {
+            "MOJMAPS": {"client": "[net.minecraft:client:1.20.1-20230612.114412:mappings@txt]", "server": "[net.minecraft:server:1.20.1-20230612.114412:mappings@txt]"},
+            "MC_SLIM_SHA": {"client": "'0e3b0f0a21a2f4e0b7d1c3a9b7d2e5f4a1c6b8d9'", "server": "'1df9a6b0f5c3d1f7a9e3b1c5d7f9a2b4c6d8e0f1'"}
+        },
+        "processors": [
+            {"sides": ["server"], "jar": "net.minecraftforge:installertools:1.3.0", "args": ["--task", "EXTRACT_FILES"]},
+            {"jar": "net.minecraftforge:installertools:1.3.0", "classpath": ["net.sf.jopt-simple:jopt-simple:5.0.4"], "args": ["--task", "MCP_DATA", "--output", "{MOJMAPS}"]},
+            {"sides": ["client"], "jar": "net.minecraftforge:jarsplitter:1.1.4", "args": ["--input", "{MINECRAFT_JAR}"], "outputs": {"{MOJMAPS}": "{MC_SLIM_SHA}"}}
+        ],
+        "libraries": [
+            {"name": "net.minecraftforge:installertools:1.3.0", "downloads": {"artifact": {"path": "net/minecraftforge/installertools/1.3.0/installertools-1.3.0.jar", "url": "https://maven.minecraftforge.net/net/minecraftforge/installertools/1.3.0/installertools-1.3.0.jar", "sha1": "cbd5b5f0e3d2b7c1a3e4f5d6c7b8a9e0f1d2c3b4", "size": 1000}}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_install_profile_lists_client_processors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("installer.jar");
+        write_jar(
+            &path,
+            &[
+                (INSTALL_PROFILE, PROFILE.as_bytes()),
+                ("version.json", br#"{"id": "1.20.1-forge-47.2.0"}"#),
+            ],
+        );
+
+        let installer = ForgeInstaller::open(&path).unwrap();
+        assert_eq!(installer.profile().version, "1.20.1-forge-47.2.0");
+        assert_eq!(installer.profile().libraries.libraries.len(), 1);
+        let jars: Vec<&str> = installer.client_processors().map(|processor| processor.jar.as_str()).collect();
+        assert_eq!(jars, ["net.minecraftforge:installertools:1.3.0", "net.minecraftforge:jarsplitter:1.1.4"]);
+        assert_eq!(installer.version_json().unwrap()["id"], "1.20.1-forge-47.2.0");
+    }
+
+    #[test]
+    fn test_legacy_install_profile_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("installer.jar");
+        write_jar(&path, &[(INSTALL_PROFILE, br#"{"install": {"minecraft": "1.12.2"}, "versionInfo": {}}"#)]);
+
+        let error = ForgeInstaller::open(&path).unwrap_err();
+        assert!(format!("{:#}", error).contains("before 1.13"), "{:#}", error);
+    }
+
+    #[test]
+    fn test_client_data_stays_in_work_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("installer.jar");
+        let profile = PROFILE.replace(
+            r#""data": {"#,
+            r#""data": {"BINPATCH": {"client": "/data/client.lzma", "server": "/data/server.lzma"},"#,
+        );
+        write_jar(&path, &[(INSTALL_PROFILE, profile.as_bytes()), ("data/client.lzma", b"patch")]);
+        let installer = ForgeInstaller::open(&path).unwrap();
+        let runner = ProcessorRunner::new(&installer, dir.path().join("libraries"), dir.path().join("client.jar"), "java");
+
+        let work_dir = dir.path().join("work");
+        let data = runner.client_data(&work_dir).unwrap();
+        assert_eq!(data["BINPATCH"], path_string(&work_dir.join("data/client.lzma")));
+        assert_eq!(std::fs::read(work_dir.join("data/client.lzma")).unwrap(), b"patch");
+
+        let profile = PROFILE.replace(r#""data": {"#, r#""data": {"BINPATCH": {"client": "/../escape.txt", "server": ""},"#);
+        write_jar(&path, &[(INSTALL_PROFILE, profile.as_bytes()), ("../escape.txt", b"escaped")]);
+        let installer = ForgeInstaller::open(&path).unwrap();
+        let runner = ProcessorRunner::new(&installer, dir.path().join("libraries"), dir.path().join("client.jar"), "java");
+
+        let error = runner.client_data(&work_dir).unwrap_err();
+        assert!(error.to_string().contains("Refusing to extract"), "{:#}", error);
+        assert!(!dir.path().join("escape.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_libraries_stay_in_libraries_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("installer.jar");
+        let libraries_dir = dir.path().join("libraries");
+        for (artifact_path, url) in [
+            ("../escape.jar", ""),
+            ("net/../../escape.jar", ""),
+            ("/tmp/escape.jar", "https://maven.minecraftforge.net/escape.jar"),
+        ] {
+            let profile = PROFILE
+                .replace("net/minecraftforge/installertools/1.3.0/installertools-1.3.0.jar", artifact_path)
+                .replace(
+                    "https://maven.minecraftforge.net/net/minecraftforge/installertools/1.3.0/installertools-1.3.0.jar",
+                    url,
+                );
+            write_jar(
+                &path,
+                &[(INSTALL_PROFILE, profile.as_bytes()), (&format!("maven/{}", artifact_path), b"escaped")],
+            );
+            let installer = ForgeInstaller::open(&path).unwrap();
+            let runner = ProcessorRunner::new(&installer, &libraries_dir, dir.path().join("client.jar"), "java");
+
+            let error = runner.install_libraries().await.unwrap_err();
+            assert!(error.to_string().contains("Refusing to install"), "{}: {:#}", artifact_path, error);
+            assert!(!dir.path().join("escape.jar").exists());
+        }
+    }
+
+    #[test]
+    fn test_replace_tokens() {
+        let data = HashMap::from([
+            ("ROOT".to_string(), "/games/.minecraft".to_string()),
+            ("SIDE".to_string(), "client".to_string()),
+        ]);
+        assert_eq!(replace_tokens("{ROOT}/mappings.txt", &data).unwrap(), "/games/.minecraft/mappings.txt");
+        assert_eq!(replace_tokens("--{SIDE}", &data).unwrap(), "--client");
+        assert_eq!(replace_tokens("'{ROOT}' stays", &data).unwrap(), "{ROOT} stays");
+        assert_eq!(replace_tokens(r"\{SIDE\}", &data).unwrap(), "{SIDE}");
+        assert!(replace_tokens("{MISSING}", &data).is_err());
+        assert!(replace_tokens("{ROOT", &data).is_err());
+    }
+
+    #[test]
+    fn test_main_class_follows_continuation_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("processor.jar");
+        write_jar(
+            &path,
+            &[(
+                "META-INF/MANIFEST.MF",
+                b"Manifest-Version: 1.0\r\nMain-Class: net.minecraftforge.installertools.Cons\r\n oleTool\r\nCreated-By: 17\r\n",
+            )],
+        );
+        assert_eq!(main_class(&path).unwrap(), "net.minecraftforge.installertools.ConsoleTool");
+
+        write_jar(&path, &[("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\n")]);
+        assert!(main_class(&path).is_err());
+    }
+}
@@ -0,0 +1,598 @@
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use reqwest::header::{CONTENT_LENGTH, HeaderMap, HeaderValue, RANGE, RETRY_AFTER};
+use reqwest::{Client, IntoUrl, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::LoaderError;
+
+/// The `User-Agent` sent by the shared client unless [`HttpConfig::with_user_agent`]
+/// overrides it. Resolves to e.g. `"minecraft_modloaders/0.1.0"`.
+pub const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Default limit for a whole metadata request, from connecting to reading the body.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Default limit for establishing a connection.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+static SHARED: OnceLock<SharedClient> = OnceLock::new();
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+struct SharedClient {
+    client: Client,
+    timeout: Duration,
+}
+
+/// Settings for the HTTP client used by every network fetch in this crate.
+///
+/// `timeout` bounds each metadata request as a whole. Downloads are only bounded by
+/// `connect_timeout` and by `timeout` between reads, so a slow but steady transfer
+/// of a large jar is not cut off.
+///
+/// With `offline` set nothing touches the network: cached fetches use whatever is on
+/// disk, downloads keep the files already present, and everything else fails with
+/// [`LoaderError::Offline`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use minecraft_modloaders::http::HttpConfig;
+/// use std::time::Duration;
+///
+/// HttpConfig::new()
+///     .with_user_agent("lodestone/1.0.0")
+///     .with_timeout(Duration::from_secs(60))
+///     .install()
+///     .expect("HTTP client was already in use");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpConfig {
+    user_agent: String,
+    timeout: Duration,
+    connect_timeout: Duration,
+    offline: bool,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            offline: false,
+        }
+    }
+}
+
+impl HttpConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Identify requests with the embedding application's own `<name>/<version>`.
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Set the overall request timeout (default: 30 seconds).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the connection timeout (default: 10 seconds).
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Never touch the network, relying on cached data instead (default: false).
+    ///
+    /// Can also be switched at runtime with [`set_offline`].
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Build a `reqwest::Client` with these settings.
+    ///
+    /// Fails if the user agent is not a valid header value, e.g. if it contains a
+    /// newline.
+    pub fn build(&self) -> Result<Client> {
+        Client::builder()
+            .user_agent(&self.user_agent)
+            .connect_timeout(self.connect_timeout)
+            .read_timeout(self.timeout)
+            .build()
+            .with_context(|| format!("Failed to build an HTTP client with user agent {:?}", self.user_agent))
+    }
+
+    /// Make these settings the ones used by every fetch in this crate.
+    ///
+    /// Must be called before the first network request; fails if the shared client
+    /// has already been created or these settings are invalid.
+    pub fn install(self) -> Result<()> {
+        let shared = SharedClient {
+            client: self.build()?,
+            timeout: self.timeout,
+        };
+        SHARED
+            .set(shared)
+            .map_err(|_| anyhow!("The shared HTTP client has already been initialized"))?;
+        set_offline(self.offline);
+        Ok(())
+    }
+}
+
+/// Switch offline mode on or off for every fetch in this crate.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether offline mode is on, see [`HttpConfig::with_offline`].
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+fn shared() -> &'static SharedClient {
+    SHARED.get_or_init(|| {
+        let config = HttpConfig::default();
+        SharedClient {
+            client: config.build().expect("the default HTTP settings are valid"),
+            timeout: config.timeout,
+        }
+    })
+}
+
+/// The HTTP client shared by every fetch in this crate.
+///
+/// Created on first use from [`HttpConfig::default`] unless [`HttpConfig::install`]
+/// was called first. Cloning it is cheap and shares the connection pool.
+pub fn client() -> &'static Client {
+    &shared().client
+}
+
+/// The HTTP requests behind the metadata fetches in this crate.
+///
+/// Every `fetch_with_client` function takes `&impl HttpClient`, so tests can pass a
+/// fake that serves canned fixtures instead of starting a server. The implementation
+/// for [`reqwest::Client`] retries with the default [`RetryPolicy`] and respects
+/// [offline mode](is_offline), like the free functions in this module.
+#[async_trait]
+pub trait HttpClient: Send + Sync {
+    /// The body of a successful GET request to `url`.
+    async fn get_bytes(&self, url: &str) -> Result<Vec<u8>, LoaderError>;
+
+    /// The body of `url` parsed as JSON; a body that does not parse is a
+    /// [`LoaderError::Deserialize`].
+    async fn get_json<T: DeserializeOwned + Send>(&self, url: &str) -> Result<T, LoaderError> {
+        let body = self.get_bytes(url).await?;
+        serde_json::from_slice(&body).map_err(|source| LoaderError::Deserialize {
+            url: url.to_string(),
+            source,
+        })
+    }
+}
+
+#[async_trait]
+impl HttpClient for Client {
+    async fn get_bytes(&self, url: &str) -> Result<Vec<u8>, LoaderError> {
+        let response = get_with_client(self, url, &RetryPolicy::default())
+            .await
+            .map_err(|err| LoaderError::from_http(url, err))?;
+        let body = response.bytes().await.map_err(|err| LoaderError::from_reqwest(url, err))?;
+        Ok(body.to_vec())
+    }
+
+    async fn get_json<T: DeserializeOwned + Send>(&self, url: &str) -> Result<T, LoaderError> {
+        get_json(self, url, &RetryPolicy::default()).await
+    }
+}
+
+/// How network fetches are retried when a request fails transiently.
+///
+/// Only connection errors, timeouts, `429 Too Many Requests` and `5xx` responses are
+/// retried. Delays grow exponentially from `base_delay` with random jitter, capped at
+/// `max_delay`; a `Retry-After` header from the server takes precedence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that makes a single attempt.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Backoff before retry number `attempt` (starting at 0), without jitter.
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay.saturating_mul(2u32.saturating_pow(attempt)).min(self.max_delay)
+    }
+
+    /// Backoff with up to 50% random jitter added, still capped at `max_delay`.
+    fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self.backoff(attempt);
+        let jitter_range = backoff.as_millis() as u64 / 2;
+        let jitter = match jitter_range {
+            0 => 0,
+            range => RandomState::new().hash_one(attempt) % (range + 1),
+        };
+        (backoff + Duration::from_millis(jitter)).min(self.max_delay)
+    }
+}
+
+/// Sends a GET request using the default [`RetryPolicy`].
+pub async fn get(url: impl IntoUrl) -> Result<Response> {
+    get_with_retry(url, &RetryPolicy::default()).await
+}
+
+/// Sends a GET request, retrying transient failures according to `policy`.
+///
+/// The returned response always has a success status; any other status is turned
+/// into an error.
+pub async fn get_with_retry(url: impl IntoUrl, policy: &RetryPolicy) -> Result<Response> {
+    get_with_client(client(), url, policy).await
+}
+
+/// Like [`get_with_retry`], but sent through a caller-provided client.
+pub async fn get_with_client(client: &Client, url: impl IntoUrl, policy: &RetryPolicy) -> Result<Response> {
+    // Only the shared client carries the crate-wide request timeout; an injected
+    // client keeps whatever timeouts it was built with.
+    let shared = shared();
+    let timeout = std::ptr::eq(client, &shared.client).then_some(shared.timeout);
+    send_with_retry(client, timeout, url, &HeaderMap::new(), policy).await
+}
+
+/// Like [`get`], sending extra request headers such as `If-None-Match`.
+///
+/// Statuses that are not errors, such as `304 Not Modified`, are returned as is.
+pub(crate) async fn get_with_headers(url: impl IntoUrl, headers: &HeaderMap) -> Result<Response> {
+    let shared = shared();
+    send_with_retry(&shared.client, Some(shared.timeout), url, headers, &RetryPolicy::default()).await
+}
+
+/// Like [`get_with_client`], asking only for the bytes from `offset` on with a
+/// `Range` header.
+///
+/// Servers that support ranges answer `206 Partial Content`; others send the whole
+/// body with `200 OK`, so callers must check the status before appending.
+pub async fn get_range_with_client(client: &Client, url: impl IntoUrl, offset: u64, policy: &RetryPolicy) -> Result<Response> {
+    let shared = shared();
+    let timeout = std::ptr::eq(client, &shared.client).then_some(shared.timeout);
+    let mut headers = HeaderMap::new();
+    headers.insert(RANGE, HeaderValue::from_str(&format!("bytes={}-", offset))?);
+    send_with_retry(client, timeout, url, &headers, policy).await
+}
+
+/// Fetches `url` like [`get_with_client`] and parses the body as JSON.
+///
+/// Failures are classified into a [`LoaderError`], so callers can react differently
+/// to being offline, a 404 or a malformed response.
+#[cfg_attr(feature = "tracing", tracing::instrument(name = "get_json", skip_all, fields(url = %url)))]
+pub async fn get_json<T: DeserializeOwned>(client: &Client, url: &str, policy: &RetryPolicy) -> Result<T, LoaderError> {
+    let response = get_with_client(client, url, policy)
+        .await
+        .map_err(|err| LoaderError::from_http(url, err))?;
+    let body = response.text().await.map_err(|err| LoaderError::from_reqwest(url, err))?;
+    serde_json::from_str(&body).map_err(|source| LoaderError::Deserialize {
+        url: url.to_string(),
+        source,
+    })
+}
+
+/// Asks for the size of `url` with a `HEAD` request, without downloading it.
+///
+/// Returns `None` when the server does not send a `Content-Length`.
+pub async fn content_length(client: &Client, url: &str) -> Result<Option<u64>> {
+    if is_offline() {
+        return Err(LoaderError::Offline { url: url.to_string() }.into());
+    }
+    let response = client.head(url).send().await?.error_for_status()?;
+    // `Response::content_length` reports the (empty) body of a HEAD response, so the
+    // header has to be read directly.
+    Ok(response
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok()))
+}
+
+async fn send_with_retry(
+    client: &Client,
+    timeout: Option<Duration>,
+    url: impl IntoUrl,
+    headers: &HeaderMap,
+    policy: &RetryPolicy,
+) -> Result<Response> {
+    let url = url.into_url()?;
+    if is_offline() {
+        return Err(LoaderError::Offline { url: url.to_string() }.into());
+    }
+    let max_attempts = policy.max_attempts.max(1);
+    let mut attempt = 0;
+
+    loop {
+        let last_attempt = attempt + 1 >= max_attempts;
+        let mut request = client.get(url.clone());
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+        if !headers.is_empty() {
+            request = request.headers(headers.clone());
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(%url, attempt, headers = ?headers.keys().collect::<Vec<_>>(), "GET");
+        let delay = match request.send().await {
+            Ok(response) if is_retryable_status(response.status()) && !last_attempt => retry_after(&response)
+                .map(|d| d.min(policy.max_delay))
+                .unwrap_or_else(|| policy.delay(attempt)),
+            Ok(response) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(%url, status = response.status().as_u16(), "response");
+                return Ok(response.error_for_status()?);
+            }
+            Err(err) if (err.is_connect() || err.is_timeout()) && !last_attempt => policy.delay(attempt),
+            Err(err) => return Err(err.into()),
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::warn!(%url, attempt, ?delay, "request failed, retrying");
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Parses a `Retry-After` header given either as seconds or as an HTTP date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::{MockServer, serve, serve_sequence};
+
+    /// Serves `responses` in order, one per connection, at `<base>/`.
+    fn serve_in_order(responses: Vec<MockResponse>) -> (String, MockServer) {
+        let server = serve_sequence(responses);
+        (server.url("/"), server)
+    }
+
+    fn unavailable() -> MockResponse {
+        MockResponse::status(503)
+    }
+
+    fn not_found() -> MockResponse {
+        MockResponse::not_found()
+    }
+
+    fn rate_limited() -> MockResponse {
+        MockResponse::status(429).with_header("Retry-After", "0")
+    }
+
+    fn ok() -> MockResponse {
+        MockResponse::ok("ok")
+    }
+
+    /// Accepts a connection and then never answers.
+    fn serve_stalled() -> (String, MockServer) {
+        let server = serve(|_| MockResponse::raw(Vec::new()).held_open(Duration::from_secs(5)));
+        (server.url("/"), server)
+    }
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors_then_succeeds() {
+        let (url, server) = serve_in_order(vec![unavailable(), unavailable(), ok()]);
+        let response = get_with_retry(&url, &fast_policy()).await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "ok");
+        assert_eq!(server.hits(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_not_found() {
+        let (url, server) = serve_in_order(vec![not_found(), ok()]);
+        let result = get_with_retry(&url, &fast_policy()).await;
+        assert!(result.is_err());
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let (url, server) = serve_in_order(vec![unavailable(), unavailable(), unavailable(), ok()]);
+        let result = get_with_retry(&url, &fast_policy()).await;
+        assert!(result.is_err());
+        assert_eq!(server.hits(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_respects_retry_after() {
+        let (url, server) = serve_in_order(vec![rate_limited(), ok()]);
+        let response = get_with_retry(&url, &fast_policy()).await.unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(server.hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_times_out_stalled_request() {
+        let (url, _server) = serve_stalled();
+
+        let timeout = Duration::from_millis(200);
+        let client = HttpConfig::new().with_timeout(timeout).build().unwrap();
+        let started = std::time::Instant::now();
+        let err = send_with_retry(&client, Some(timeout), &url, &HeaderMap::new(), &RetryPolicy::none())
+            .await
+            .unwrap_err();
+
+        assert!(err.downcast_ref::<reqwest::Error>().is_some_and(|e| e.is_timeout()));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_sends_user_agent() {
+        let (url, server) = serve_in_order(vec![ok()]);
+        get_with_retry(&url, &RetryPolicy::none()).await.unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(
+            request.header("user-agent"),
+            Some(DEFAULT_USER_AGENT),
+            "request was missing the user agent:\n{}",
+            request.head
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_with_custom_client() {
+        let (url, server) = serve_in_order(vec![ok()]);
+        let client = HttpConfig::new().with_user_agent("lodestone-test/1.0").build().unwrap();
+        let response = get_with_client(&client, &url, &RetryPolicy::none()).await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "ok");
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[test]
+    fn test_invalid_user_agent_is_an_error() {
+        let err = HttpConfig::new().with_user_agent("lodestone\n1.0").build().unwrap_err();
+        assert!(err.to_string().contains("user agent"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_get_json_parses_body() {
+        let (url, _) = serve_in_order(vec![MockResponse::ok("[1, 2, 3]")]);
+        let parsed: Vec<u32> = get_json(client(), &url, &RetryPolicy::none()).await.unwrap();
+        assert_eq!(parsed, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_get_json_not_found() {
+        let (url, _) = serve_in_order(vec![not_found()]);
+        let err = get_json::<serde_json::Value>(client(), &url, &fast_policy()).await.unwrap_err();
+        assert!(matches!(err, LoaderError::NotFound { .. }), "{:?}", err);
+        assert_eq!(err.url(), url);
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_get_json_rate_limited() {
+        let (url, server) = serve_in_order(vec![rate_limited(), rate_limited(), rate_limited()]);
+        let err = get_json::<serde_json::Value>(client(), &url, &fast_policy()).await.unwrap_err();
+        assert!(matches!(err, LoaderError::RateLimited { .. }), "{:?}", err);
+        assert_eq!(server.hits(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_json_deserialize() {
+        let (url, _) = serve_in_order(vec![ok()]);
+        let err = get_json::<Vec<u32>>(client(), &url, &RetryPolicy::none()).await.unwrap_err();
+        assert!(matches!(err, LoaderError::Deserialize { .. }), "{:?}", err);
+        assert!(std::error::Error::source(&err).is_some_and(|source| source.is::<serde_json::Error>()));
+    }
+
+    #[tokio::test]
+    async fn test_get_json_network() {
+        // Nothing listens on the discard port.
+        let err = get_json::<serde_json::Value>(client(), "http://127.0.0.1:9/", &RetryPolicy::none())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LoaderError::Network { .. }), "{:?}", err);
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_get_json_timeout() {
+        let (url, _server) = serve_stalled();
+
+        let client = Client::builder().timeout(Duration::from_millis(200)).build().unwrap();
+        let err = get_json::<serde_json::Value>(&client, &url, &RetryPolicy::none()).await.unwrap_err();
+        assert!(matches!(err, LoaderError::Timeout { .. }), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_loader_error_converts_to_anyhow() {
+        async fn fetch(url: &str) -> Result<serde_json::Value> {
+            Ok(get_json(client(), url, &RetryPolicy::none()).await?)
+        }
+        let (url, _) = serve_in_order(vec![not_found()]);
+        let err = fetch(&url).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<LoaderError>(), Some(LoaderError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_client_implements_http_client() {
+        let (url, _) = serve_in_order(vec![MockResponse::ok("[1, 2, 3]")]);
+        let parsed: Vec<u32> = HttpClient::get_json(client(), &url).await.unwrap();
+        assert_eq!(parsed, vec![1, 2, 3]);
+
+        let (url, _) = serve_in_order(vec![not_found()]);
+        let err = client().get_bytes(&url).await.unwrap_err();
+        assert!(matches!(err, LoaderError::NotFound { .. }), "{:?}", err);
+    }
+
+    #[test]
+    fn test_from_http_keeps_loader_errors() {
+        let err = anyhow::Error::from(LoaderError::Offline {
+            url: "https://example.com/".to_string(),
+        });
+        let classified = LoaderError::from_http("https://example.com/", err);
+        assert!(matches!(classified, LoaderError::Offline { .. }), "{:?}", classified);
+    }
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(8), Duration::from_secs(1));
+        for attempt in 0..10 {
+            let delay = policy.delay(attempt);
+            assert!(delay >= policy.backoff(attempt));
+            assert!(delay <= policy.max_delay);
+        }
+    }
+}
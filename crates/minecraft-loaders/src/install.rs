@@ -0,0 +1,1177 @@
+use anyhow::{Result, anyhow, bail};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+use crate::download::{self, CancellationToken, Cancelled, DownloadJob, DownloadSummary, Downloader, FileStatus};
+use crate::fabric::LaunchProfile;
+use crate::http;
+use crate::maven::RepositoryList;
+use crate::vanilla::{AssetIndex, LibrarySet, NativesCache, RuleContext, VersionDetail, extract_natives, extract_natives_cached};
+
+const RESOURCES_URL: &str = "https://resources.download.minecraft.net";
+
+/// A step of [`Installer::install`], in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstallStage {
+    /// Downloading the client jar; only runs when a destination is set.
+    ClientJar,
+    Libraries,
+    /// Fetching and storing the asset index document.
+    AssetIndex,
+    Assets,
+    /// Unpacking native libraries; only runs when a natives directory is set.
+    Natives,
+    /// Hashing files already on disk; only run by [`Installer::verify`].
+    Verify,
+    /// Downloading the files a verify found broken; only run by [`Installer::repair`].
+    Repair,
+}
+
+/// Progress reported by [`Installer::install`].
+///
+/// Every stage that runs emits `StageStarted` first and `StageCompleted` last, with
+/// its `FileDownloaded` and `BytesProgress` events in between.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallEvent {
+    StageStarted {
+        stage: InstallStage,
+        /// Files the stage will download or write, 0 when unknown up front.
+        files: usize,
+        /// Bytes the stage will download, 0 when unknown up front.
+        bytes: u64,
+    },
+    /// A file is in place. `skipped` is set when it already existed with the
+    /// expected SHA-1 and was not downloaded again.
+    FileDownloaded {
+        stage: InstallStage,
+        path: PathBuf,
+        bytes: u64,
+        skipped: bool,
+    },
+    /// Bytes received so far in the current stage, for determinate progress bars.
+    /// During [`InstallStage::Verify`] these are the bytes checked so far.
+    BytesProgress {
+        stage: InstallStage,
+        downloaded: u64,
+        total: u64,
+    },
+    StageCompleted {
+        stage: InstallStage,
+    },
+    /// Something went wrong that does not stop the install on its own.
+    Warning {
+        message: String,
+    },
+}
+
+/// Non-blocking emitter used inside the pipeline; events are forwarded to the
+/// caller's channel by a background task so download callbacks never wait on a
+/// slow receiver.
+#[derive(Clone)]
+struct Events(Option<mpsc::UnboundedSender<InstallEvent>>);
+
+impl Events {
+    fn forward_to(out: Option<mpsc::Sender<InstallEvent>>) -> Self {
+        let Some(out) = out else {
+            return Self(None);
+        };
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                if out.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Self(Some(tx))
+    }
+
+    fn emit(&self, event: InstallEvent) {
+        if let Some(tx) = &self.0 {
+            let _ = tx.send(event);
+        }
+    }
+
+    fn warn(&self, message: impl Into<String>) {
+        self.emit(InstallEvent::Warning { message: message.into() });
+    }
+}
+
+/// Everything an install would download, worked out without downloading any of it.
+///
+/// `jobs` can be handed straight to [`Downloader::download`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstallPlan {
+    pub jobs: Vec<DownloadJob>,
+    /// Sum of the known file sizes, in bytes.
+    pub total_bytes: u64,
+    /// Files whose size neither the JSON nor the server reported; they are not
+    /// counted in `total_bytes`.
+    pub unknown_sizes: usize,
+}
+
+impl InstallPlan {
+    fn new(jobs: Vec<DownloadJob>) -> Self {
+        Self {
+            total_bytes: jobs.iter().filter_map(|job| job.size).sum(),
+            unknown_sizes: jobs.iter().filter(|job| job.size.is_none()).count(),
+            jobs,
+        }
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.jobs.len()
+    }
+}
+
+/// Files [`Installer::verify`] found missing or damaged, as the jobs that would fetch
+/// them again. Hand it to [`Installer::repair`] to re-download only those.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairReport {
+    /// Files that do not exist.
+    pub missing: Vec<DownloadJob>,
+    /// Files whose size or hash differs from the one the version declares.
+    pub corrupt: Vec<DownloadJob>,
+    /// How many files were checked, intact ones included.
+    pub checked: usize,
+}
+
+impl RepairReport {
+    /// Whether every checked file is intact.
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.corrupt.is_empty()
+    }
+
+    /// The missing files followed by the corrupt ones.
+    pub fn jobs(&self) -> impl Iterator<Item = &DownloadJob> {
+        self.missing.iter().chain(&self.corrupt)
+    }
+}
+
+/// The state of one file checked by [`Installer::verify`].
+#[derive(Debug, Clone, Copy)]
+enum FileCheck {
+    Missing,
+    Present { bytes: u64, intact: bool },
+}
+
+/// Compare the file `job` would download with its declared size and hash, on the
+/// calling thread.
+fn check_file(job: &DownloadJob) -> Result<FileCheck> {
+    let bytes = match std::fs::metadata(&job.dest) {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(FileCheck::Missing),
+        Err(e) => return Err(e.into()),
+    };
+    let intact = job.size.is_none_or(|expected| expected == bytes)
+        && match job.expected_hash() {
+            Some((algo, expected)) => download::file_hash_blocking(&job.dest, algo)?.eq_ignore_ascii_case(expected),
+            None => true,
+        };
+    Ok(FileCheck::Present { bytes, intact })
+}
+
+/// Where the client jar of `version` goes under `versions_dir`: `<id>/<id>.jar`.
+///
+/// Fails when the id is not a plain directory name, see [`version_jar_path`].
+pub fn client_jar_path(versions_dir: impl AsRef<Path>, version: &VersionDetail) -> Result<PathBuf> {
+    version_jar_path(versions_dir, &version.id)
+}
+
+/// Where the jar of the version called `id` goes under `versions_dir`: `<id>/<id>.jar`.
+///
+/// Version ids come from manifests that may not be Mojang's, so an id containing a
+/// path separator, a drive colon or `..` is refused rather than joined, as it could
+/// point outside of `versions_dir`.
+pub fn version_jar_path(versions_dir: impl AsRef<Path>, id: &str) -> Result<PathBuf> {
+    if id.is_empty() || id == "." || id.contains("..") || id.contains(['/', '\\', ':', '\0']) {
+        bail!("Refusing to use version id {:?} as a directory name", id);
+    }
+    Ok(versions_dir.as_ref().join(id).join(format!("{}.jar", id)))
+}
+
+/// Downloads the client jar from the `downloads.client` block of `version` into
+/// `versions_dir`, checking its SHA-1 and size, and returns its path.
+///
+/// A jar already in place with the expected SHA-1 is not downloaded again. Use
+/// [`Installer::install_client_jar`] for progress events or a configured [`Downloader`].
+pub async fn download_client_jar(version: &VersionDetail, versions_dir: impl AsRef<Path>) -> Result<PathBuf> {
+    Installer::new(version, PathBuf::new(), PathBuf::new())
+        .with_client_jar(client_jar_path(versions_dir, version)?)
+        .install_client_jar()
+        .await
+}
+
+/// Plans installing `version`, plus the libraries of a `loader` profile built on it,
+/// into a standard `.minecraft` layout under `minecraft_dir`.
+///
+/// See [`Installer::plan`] for what gets fetched to build the plan.
+pub async fn plan_install(version: &VersionDetail, loader: Option<&LaunchProfile>, minecraft_dir: impl AsRef<Path>) -> Result<InstallPlan> {
+    let minecraft_dir = minecraft_dir.as_ref();
+    let mut installer = Installer::new(version, minecraft_dir.join("libraries"), minecraft_dir.join("assets"))
+        .with_client_jar(client_jar_path(minecraft_dir.join("versions"), version)?);
+    if let Some(loader) = loader {
+        installer = installer.with_loader_profile(loader);
+    }
+    installer.plan().await
+}
+
+/// Downloads everything a version needs to launch: optionally its client jar, its
+/// libraries and those of a loader profile, the asset index and assets, and
+/// optionally its extracted natives.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use minecraft_modloaders::install::{InstallEvent, Installer};
+/// use minecraft_modloaders::vanilla::VersionManifest;
+/// use tokio::sync::mpsc;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let version = VersionManifest::fetch().await?.fetch_detail("1.20.1").await?;
+/// let (tx, mut rx) = mpsc::channel(256);
+/// tokio::spawn(async move {
+///     while let Some(event) = rx.recv().await {
+///         if let InstallEvent::BytesProgress { downloaded, total, .. } = event {
+///             println!("{downloaded}/{total} bytes");
+///         }
+///     }
+/// });
+/// Installer::new(&version, "minecraft/libraries", "minecraft/assets")
+///     .with_natives_dir("minecraft/natives/1.20.1")
+///     .with_events(tx)
+///     .install()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Installer<'a> {
+    version: &'a VersionDetail,
+    loader: Option<&'a LaunchProfile>,
+    libraries_dir: PathBuf,
+    assets_dir: PathBuf,
+    client_jar: Option<PathBuf>,
+    natives_dir: Option<PathBuf>,
+    natives_cache: Option<NativesCache>,
+    rule_context: RuleContext,
+    downloader: Downloader,
+    resources_url: String,
+    repositories: Option<RepositoryList>,
+    events: Option<mpsc::Sender<InstallEvent>>,
+    cancel: Option<CancellationToken>,
+}
+
+impl<'a> Installer<'a> {
+    pub fn new(version: &'a VersionDetail, libraries_dir: impl Into<PathBuf>, assets_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            version,
+            loader: None,
+            libraries_dir: libraries_dir.into(),
+            assets_dir: assets_dir.into(),
+            client_jar: None,
+            natives_dir: None,
+            natives_cache: None,
+            rule_context: RuleContext::default(),
+            downloader: Downloader::new(),
+            resources_url: RESOURCES_URL.to_string(),
+            repositories: None,
+            events: None,
+            cancel: None,
+        }
+    }
+
+    /// Also install the libraries a loader profile adds on top of the version.
+    pub fn with_loader_profile(mut self, profile: &'a LaunchProfile) -> Self {
+        self.loader = Some(profile);
+        self
+    }
+
+    /// Download the version's client jar to `dest`.
+    pub fn with_client_jar(mut self, dest: impl Into<PathBuf>) -> Self {
+        self.client_jar = Some(dest.into());
+        self
+    }
+
+    /// Extract natives into `dir` after the libraries are downloaded.
+    pub fn with_natives_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.natives_dir = Some(dir.into());
+        self
+    }
+
+    /// Unpack natives once into the shared store at `dir` and link them into the
+    /// natives directory from there. Only used together with [`Self::with_natives_dir`].
+    pub fn with_natives_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.natives_cache = Some(NativesCache::new(dir));
+        self
+    }
+
+    /// Evaluate library rules for another platform than the current one.
+    pub fn with_rule_context(mut self, ctx: RuleContext) -> Self {
+        self.rule_context = ctx;
+        self
+    }
+
+    /// Use a configured [`Downloader`], e.g. with a custom client or concurrency.
+    /// Its progress callbacks are replaced while installing.
+    pub fn with_downloader(mut self, downloader: Downloader) -> Self {
+        self.downloader = downloader;
+        self
+    }
+
+    /// Download assets from a mirror of Mojang's resource server.
+    pub fn with_resources_url(mut self, url: impl Into<String>) -> Self {
+        self.resources_url = url.into();
+        self
+    }
+
+    /// Look up libraries that name only a maven coordinate in `repositories` instead
+    /// of assuming Mojang's; see [`LibrarySet::resolve_repositories`].
+    pub fn with_repositories(mut self, repositories: RepositoryList) -> Self {
+        self.repositories = Some(repositories);
+        self
+    }
+
+    /// Report progress through `events`. Events are never dropped; a slow receiver
+    /// only delays their delivery, not the install.
+    pub fn with_events(mut self, events: mpsc::Sender<InstallEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Stop as soon as `token` is cancelled, failing with [`Cancelled`].
+    ///
+    /// Downloads in flight are aborted and their `.part` files removed; files already
+    /// in place stay, so installing again only fetches what is still missing.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Lists every file [`Installer::install`] would download and their total size.
+    ///
+    /// Sizes come from the version JSON where it declares them. Listing the assets
+    /// requires fetching the asset index document itself, and files without a
+    /// declared size (typically loader libraries) are sized with `HEAD` requests;
+    /// no game file is downloaded.
+    pub async fn plan(&self) -> Result<InstallPlan> {
+        let mut jobs: Vec<DownloadJob> = self.client_job()?.into_iter().collect();
+        jobs.extend(self.libraries(&Events(None)).await.download_jobs(&self.libraries_dir, &self.rule_context));
+        if let Some(index_ref) = &self.version.asset_index {
+            let index = AssetIndex::fetch(index_ref).await?;
+            jobs.extend(index.download_jobs_from(&self.resources_url, &self.assets_dir));
+        }
+
+        let mut lookups = JoinSet::new();
+        for (i, job) in jobs.iter().enumerate().filter(|(_, job)| job.size.is_none()) {
+            let url = job.url.clone();
+            let client = self.downloader.client().clone();
+            lookups.spawn(async move { (i, http::content_length(&client, &url).await) });
+        }
+        while let Some(lookup) = lookups.join_next().await {
+            // A failed lookup leaves the size unknown rather than failing the plan.
+            if let (i, Ok(Some(size))) = lookup? {
+                jobs[i].size = Some(size);
+            }
+        }
+        Ok(InstallPlan::new(jobs))
+    }
+
+    /// Run only the [`InstallStage::ClientJar`] stage, returning the jar's path.
+    ///
+    /// Fails when no destination was set with [`Installer::with_client_jar`].
+    pub async fn install_client_jar(&self) -> Result<PathBuf> {
+        let Some(job) = self.client_job()? else {
+            bail!("No client jar destination set for version {}", self.version.id);
+        };
+        let events = Events::forward_to(self.events.clone());
+        let dest = job.dest.clone();
+        self.download_stage(InstallStage::ClientJar, vec![job], &events).await?;
+        Ok(dest)
+    }
+
+    /// Run every stage in order. Fails after the first stage in which a file could
+    /// not be downloaded; each failed file is also reported as a warning. Fails with
+    /// [`Cancelled`] once the [cancellation token](Installer::with_cancellation) is
+    /// cancelled.
+    pub async fn install(&self) -> Result<()> {
+        let events = Events::forward_to(self.events.clone());
+
+        if let Some(client) = self.client_job()? {
+            self.download_stage(InstallStage::ClientJar, vec![client], &events).await?;
+        }
+
+        let libraries = self.libraries(&events).await;
+        let library_jobs = libraries.download_jobs(&self.libraries_dir, &self.rule_context);
+        self.download_stage(InstallStage::Libraries, library_jobs, &events).await?;
+
+        match &self.version.asset_index {
+            Some(index_ref) => {
+                self.check_cancelled()?;
+                events.emit(InstallEvent::StageStarted {
+                    stage: InstallStage::AssetIndex,
+                    files: 1,
+                    bytes: index_ref.size,
+                });
+                // The index is written in one blocking call that completes even when the
+                // fetch is cancelled, so it is never left half-written.
+                let index = self.until_cancelled(AssetIndex::fetch_into(index_ref, &self.assets_dir)).await?;
+                events.emit(InstallEvent::FileDownloaded {
+                    stage: InstallStage::AssetIndex,
+                    path: self.assets_dir.join("indexes").join(format!("{}.json", index_ref.id)),
+                    bytes: index_ref.size,
+                    skipped: false,
+                });
+                events.emit(InstallEvent::StageCompleted {
+                    stage: InstallStage::AssetIndex,
+                });
+
+                let assets = index.download_jobs_from(&self.resources_url, &self.assets_dir);
+                self.download_stage(InstallStage::Assets, assets, &events).await?;
+            }
+            None => events.warn(format!("Version {} has no asset index; skipping assets", self.version.id)),
+        }
+
+        if let Some(natives_dir) = &self.natives_dir {
+            self.check_cancelled()?;
+            events.emit(InstallEvent::StageStarted {
+                stage: InstallStage::Natives,
+                files: 0,
+                bytes: 0,
+            });
+            let extracted = match &self.natives_cache {
+                Some(cache) => extract_natives_cached(&libraries, &self.libraries_dir, natives_dir, &self.rule_context, cache)?,
+                None => extract_natives(&libraries, &self.libraries_dir, natives_dir, &self.rule_context)?,
+            };
+            for path in extracted {
+                let bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                events.emit(InstallEvent::FileDownloaded {
+                    stage: InstallStage::Natives,
+                    path,
+                    bytes,
+                    skipped: false,
+                });
+            }
+            events.emit(InstallEvent::StageCompleted {
+                stage: InstallStage::Natives,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks the client jar, every library and every asset object already on disk
+    /// against the sizes and hashes the version declares, downloading nothing.
+    ///
+    /// Asset objects are listed from the index stored under `assets/indexes/`. When
+    /// that index is itself missing or damaged only the index is reported, with a
+    /// warning; verify again after repairing it to check the objects.
+    ///
+    /// Files are hashed on blocking threads, one per CPU core, and reported in
+    /// [`InstallEvent::BytesProgress`] as each finishes. The report lists files in the
+    /// same order on every run however the checks interleave: client jar, libraries,
+    /// asset objects by path, then the asset index. Cancelling stops the checks and
+    /// fails with [`Cancelled`].
+    pub async fn verify(&self) -> Result<RepairReport> {
+        let events = Events::forward_to(self.events.clone());
+        let mut jobs: Vec<DownloadJob> = self.client_job()?.into_iter().collect();
+        jobs.extend(self.libraries(&events).await.download_jobs(&self.libraries_dir, &self.rule_context));
+        if let Some(index_ref) = &self.version.asset_index {
+            let index_path = self.assets_dir.join("indexes").join(format!("{}.json", index_ref.id));
+            let index_job = DownloadJob::new(&index_ref.url, &index_path)
+                .with_sha1(&index_ref.sha1)
+                .with_size(index_ref.size);
+            let local_index = if download::verify_sha1(&index_path, &index_ref.sha1).await? {
+                AssetIndex::from_json(&tokio::fs::read_to_string(&index_path).await?).ok()
+            } else {
+                None
+            };
+            match local_index {
+                Some(index) => jobs.extend(index.download_jobs_from(&self.resources_url, &self.assets_dir)),
+                None => events.warn(format!("Asset index {} needs repairing before its objects can be checked", index_ref.id)),
+            }
+            jobs.push(index_job);
+        }
+
+        let total = jobs.iter().filter_map(|job| job.size).sum();
+        events.emit(InstallEvent::StageStarted {
+            stage: InstallStage::Verify,
+            files: jobs.len(),
+            bytes: total,
+        });
+        let mut report = RepairReport {
+            checked: jobs.len(),
+            ..RepairReport::default()
+        };
+
+        // Hashing is disk and CPU bound, so run one blocking check per core rather than
+        // the downloader's network concurrency.
+        let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let mut outcomes: Vec<Option<FileCheck>> = vec![None; jobs.len()];
+        let mut checks = JoinSet::new();
+        let mut pending = jobs.iter().cloned().enumerate();
+        let mut checked_bytes = 0;
+        loop {
+            while checks.len() < workers {
+                let Some((i, job)) = pending.next() else {
+                    break;
+                };
+                checks.spawn_blocking(move || (i, check_file(&job)));
+            }
+            let Some(done) = self.until_cancelled(async { Ok(checks.join_next().await) }).await? else {
+                break;
+            };
+            let (i, outcome) = done?;
+            let outcome = outcome?;
+            if let FileCheck::Present { bytes, .. } = outcome {
+                checked_bytes += jobs[i].size.unwrap_or(bytes);
+                events.emit(InstallEvent::BytesProgress {
+                    stage: InstallStage::Verify,
+                    downloaded: checked_bytes,
+                    total,
+                });
+            }
+            outcomes[i] = Some(outcome);
+        }
+
+        // Report in job order, whichever check finished first.
+        for (job, outcome) in jobs.into_iter().zip(outcomes) {
+            match outcome {
+                Some(FileCheck::Missing) => report.missing.push(job),
+                Some(FileCheck::Present { intact: false, .. }) => report.corrupt.push(job),
+                Some(FileCheck::Present { intact: true, .. }) | None => {}
+            }
+        }
+        events.emit(InstallEvent::StageCompleted { stage: InstallStage::Verify });
+        Ok(report)
+    }
+
+    /// Downloads the files of a [`RepairReport`] again, as the
+    /// [`InstallStage::Repair`] stage. Intact files are not touched.
+    pub async fn repair(&self, report: &RepairReport) -> Result<DownloadSummary> {
+        let events = Events::forward_to(self.events.clone());
+        self.download_stage(InstallStage::Repair, report.jobs().cloned().collect(), &events).await
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        match &self.cancel {
+            Some(token) if token.is_cancelled() => Err(Cancelled.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Runs `future` until it finishes or the install is cancelled, dropping it in the
+    /// latter case.
+    async fn until_cancelled<T>(&self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        match &self.cancel {
+            Some(token) => token.run_until_cancelled(future).await.unwrap_or_else(|| Err(Cancelled.into())),
+            None => future.await,
+        }
+    }
+
+    /// The version's libraries merged with those of the loader profile, if any, with
+    /// coordinate-only entries looked up in the configured repositories.
+    async fn libraries(&self, events: &Events) -> LibrarySet {
+        let mut libraries = match self.loader {
+            Some(profile) => self.version.libraries.clone().merged(profile.library_set()),
+            None => self.version.libraries.clone(),
+        };
+        if let Some(repositories) = &self.repositories {
+            for name in libraries.resolve_repositories(repositories, self.downloader.client()).await {
+                events.warn(format!("Library {} was not found in any repository", name));
+            }
+        }
+        libraries
+    }
+
+    fn client_job(&self) -> Result<Option<DownloadJob>> {
+        let Some(dest) = &self.client_jar else {
+            return Ok(None);
+        };
+        let client = self
+            .version
+            .download("client")
+            .ok_or_else(|| anyhow!("Version {} has no client download", self.version.id))?;
+        Ok(Some(DownloadJob::new(client.url, dest).with_sha1(client.sha1).with_size(client.size)))
+    }
+
+    async fn download_stage(&self, stage: InstallStage, jobs: Vec<DownloadJob>, events: &Events) -> Result<DownloadSummary> {
+        self.check_cancelled()?;
+        events.emit(InstallEvent::StageStarted {
+            stage,
+            files: jobs.len(),
+            bytes: jobs.iter().filter_map(|job| job.size).sum(),
+        });
+
+        let progress_events = events.clone();
+        let file_events = events.clone();
+        let mut downloader = self.downloader.clone();
+        if let Some(token) = &self.cancel {
+            downloader = downloader.with_cancellation(token.clone());
+        }
+        let summary = downloader
+            .on_progress(move |p| {
+                progress_events.emit(InstallEvent::BytesProgress {
+                    stage,
+                    downloaded: p.bytes_downloaded,
+                    total: p.bytes_total,
+                })
+            })
+            .on_file(move |job, status| {
+                if status != FileStatus::Failed {
+                    file_events.emit(InstallEvent::FileDownloaded {
+                        stage,
+                        path: job.dest.clone(),
+                        bytes: job.size.unwrap_or(0),
+                        skipped: status == FileStatus::Skipped,
+                    });
+                }
+            })
+            .download(jobs)
+            .await;
+
+        for failure in &summary.failed {
+            events.warn(format!("Failed to download {}: {:#}", failure.job.url, failure.error));
+        }
+        if summary.is_cancelled() {
+            return Err(Cancelled.into());
+        }
+        if !summary.is_success() {
+            return Err(anyhow!("{} of the {:?} files failed to download", summary.failed.len(), stage));
+        }
+        events.emit(InstallEvent::StageCompleted { stage });
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::download::sha1_hex;
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use test_support::{MockServer, Response, serve, serve_files};
+
+    fn version_json(base_url: &str, index: &[u8]) -> String {
+        format!(
+            r#"{{
+                "id": "test-1",
+                "type": "release",
+                "mainClass": "net.minecraft.client.main.Main",
+                "releaseTime": "2024-01-01T00:00:00+00:00",
+                "assetIndex": {{"id": "test", "sha1": "{index_sha1}", "size": {index_size}, "totalSize": 9, "url": "{base_url}/indexes/test.json"}},
+                "libraries": [
+                    {{
+                        "name": "com.example:fresh:1.0",
+                        "downloads": {{"artifact": {{"path": "com/example/fresh/1.0/fresh-1.0.jar", "sha1": "{fresh_sha1}", "size": 5, "url": "{base_url}/fresh.jar"}}}}
+                    }},
+                    {{
+                        "name": "com.example:cached:1.0",
+                        "downloads": {{"artifact": {{"path": "com/example/cached/1.0/cached-1.0.jar", "sha1": "{cached_sha1}", "size": 6, "url": "{base_url}/cached.jar"}}}}
+                    }}
+                ]
+            }}"#,
+            index_sha1 = sha1_hex(index),
+            index_size = index.len(),
+            fresh_sha1 = sha1_hex(b"fresh"),
+            cached_sha1 = sha1_hex(b"cached"),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_install_emits_events_in_order() {
+        let object = b"sound";
+        let object_hash = sha1_hex(object);
+        let index = format!(r#"{{"objects": {{"minecraft/sounds/a.ogg": {{"hash": "{}", "size": 5}}}}}}"#, object_hash).into_bytes();
+
+        let files = HashMap::from([
+            ("/fresh.jar".to_string(), b"fresh".to_vec()),
+            ("/cached.jar".to_string(), b"cached".to_vec()),
+            ("/indexes/test.json".to_string(), index.clone()),
+            (format!("/resources/{}/{}", &object_hash[..2], object_hash), object.to_vec()),
+        ]);
+        let server = serve_files(files);
+        let base_url = server.base_url();
+        let version: VersionDetail = serde_json::from_str(&version_json(base_url, &index)).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let libraries_dir = dir.path().join("libraries");
+        let assets_dir = dir.path().join("assets");
+        // Already up to date, so it takes the SHA-1 fast path.
+        let cached = libraries_dir.join("com/example/cached/1.0/cached-1.0.jar");
+        std::fs::create_dir_all(cached.parent().unwrap()).unwrap();
+        std::fs::write(&cached, b"cached").unwrap();
+
+        let (tx, mut rx) = mpsc::channel(4);
+        let installer = Installer::new(&version, &libraries_dir, &assets_dir)
+            .with_resources_url(format!("{}/resources", base_url))
+            .with_events(tx);
+        let collector = tokio::spawn(async move {
+            let mut events = Vec::new();
+            while let Some(event) = rx.recv().await {
+                events.push(event);
+            }
+            events
+        });
+        installer.install().await.unwrap();
+        drop(installer);
+        let events = collector.await.unwrap();
+
+        let sequence: Vec<InstallEvent> = events
+            .iter()
+            .filter(|e| !matches!(e, InstallEvent::BytesProgress { .. }))
+            .cloned()
+            .collect();
+        let (libraries, rest) = sequence.split_at(4);
+        assert_eq!(
+            libraries[0],
+            InstallEvent::StageStarted {
+                stage: InstallStage::Libraries,
+                files: 2,
+                bytes: 11
+            }
+        );
+        let mut downloaded: Vec<(&PathBuf, bool)> = libraries[1..3]
+            .iter()
+            .map(|e| match e {
+                InstallEvent::FileDownloaded {
+                    stage: InstallStage::Libraries,
+                    path,
+                    skipped,
+                    ..
+                } => (path, *skipped),
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+        downloaded.sort();
+        assert_eq!(
+            downloaded,
+            vec![(&cached, true), (&libraries_dir.join("com/example/fresh/1.0/fresh-1.0.jar"), false)]
+        );
+        assert_eq!(
+            libraries[3],
+            InstallEvent::StageCompleted {
+                stage: InstallStage::Libraries
+            }
+        );
+
+        let object_path = assets_dir.join("objects").join(&object_hash[..2]).join(&object_hash);
+        assert_eq!(
+            rest,
+            &[
+                InstallEvent::StageStarted {
+                    stage: InstallStage::AssetIndex,
+                    files: 1,
+                    bytes: index.len() as u64
+                },
+                InstallEvent::FileDownloaded {
+                    stage: InstallStage::AssetIndex,
+                    path: assets_dir.join("indexes/test.json"),
+                    bytes: index.len() as u64,
+                    skipped: false
+                },
+                InstallEvent::StageCompleted {
+                    stage: InstallStage::AssetIndex
+                },
+                InstallEvent::StageStarted {
+                    stage: InstallStage::Assets,
+                    files: 1,
+                    bytes: 5
+                },
+                InstallEvent::FileDownloaded {
+                    stage: InstallStage::Assets,
+                    path: object_path.clone(),
+                    bytes: 5,
+                    skipped: false
+                },
+                InstallEvent::StageCompleted { stage: InstallStage::Assets },
+            ]
+        );
+
+        assert!(events.contains(&InstallEvent::BytesProgress {
+            stage: InstallStage::Assets,
+            downloaded: 5,
+            total: 5
+        }));
+        assert_eq!(std::fs::read(object_path).unwrap(), object);
+    }
+
+    #[tokio::test]
+    async fn test_verify_reports_and_repair_refetches() {
+        let object = b"sound";
+        let object_hash = sha1_hex(object);
+        let index = format!(r#"{{"objects": {{"minecraft/sounds/a.ogg": {{"hash": "{}", "size": 5}}}}}}"#, object_hash).into_bytes();
+        let files = HashMap::from([
+            ("/fresh.jar".to_string(), b"fresh".to_vec()),
+            ("/cached.jar".to_string(), b"cached".to_vec()),
+            (format!("/resources/{}/{}", &object_hash[..2], object_hash), object.to_vec()),
+        ]);
+        let server = serve_files(files);
+        let base_url = server.base_url();
+        let version: VersionDetail = serde_json::from_str(&version_json(base_url, &index)).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let libraries_dir = dir.path().join("libraries");
+        let assets_dir = dir.path().join("assets");
+        let truncated = libraries_dir.join("com/example/cached/1.0/cached-1.0.jar");
+        std::fs::create_dir_all(truncated.parent().unwrap()).unwrap();
+        std::fs::write(&truncated, b"cac").unwrap();
+        std::fs::create_dir_all(assets_dir.join("indexes")).unwrap();
+        std::fs::write(assets_dir.join("indexes/test.json"), &index).unwrap();
+
+        let (tx, mut rx) = mpsc::channel(64);
+        let installer = Installer::new(&version, &libraries_dir, &assets_dir)
+            .with_resources_url(format!("{}/resources", base_url))
+            .with_events(tx);
+        let report = installer.verify().await.unwrap();
+        let object_path = assets_dir.join("objects").join(&object_hash[..2]).join(&object_hash);
+        let missing: Vec<&PathBuf> = report.missing.iter().map(|job| &job.dest).collect();
+        assert_eq!(missing, vec![&libraries_dir.join("com/example/fresh/1.0/fresh-1.0.jar"), &object_path]);
+        assert_eq!(report.corrupt.len(), 1);
+        assert_eq!(report.corrupt[0].dest, truncated);
+        assert_eq!(report.checked, 4);
+
+        let summary = installer.repair(&report).await.unwrap();
+        assert_eq!(summary.succeeded.len(), 3);
+        assert_eq!(std::fs::read(&truncated).unwrap(), b"cached");
+        assert!(installer.verify().await.unwrap().is_clean());
+        drop(installer);
+
+        let mut stages = Vec::new();
+        while let Some(event) = rx.recv().await {
+            if let InstallEvent::StageStarted { stage, .. } = event {
+                stages.push(stage);
+            }
+        }
+        assert_eq!(stages, vec![InstallStage::Verify, InstallStage::Repair, InstallStage::Verify]);
+    }
+
+    #[tokio::test]
+    async fn test_verify_hashes_every_file_in_stable_order() {
+        let objects: Vec<Vec<u8>> = (0..64).map(|i| format!("object {i}").into_bytes()).collect();
+        let entries: Vec<String> = objects
+            .iter()
+            .enumerate()
+            .map(|(i, object)| {
+                format!(
+                    r#""minecraft/sounds/{i}.ogg": {{"hash": "{}", "size": {}}}"#,
+                    sha1_hex(object),
+                    object.len()
+                )
+            })
+            .collect();
+        let index = format!(r#"{{"objects": {{{}}}}}"#, entries.join(", ")).into_bytes();
+        let version: VersionDetail = serde_json::from_str(&version_json("http://127.0.0.1:9", &index)).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let libraries_dir = dir.path().join("libraries");
+        let assets_dir = dir.path().join("assets");
+        let libraries = [
+            ("com/example/fresh/1.0/fresh-1.0.jar", &b"fresh"[..]),
+            ("com/example/cached/1.0/cached-1.0.jar", b"cached"),
+        ];
+        for (path, contents) in libraries {
+            let path = libraries_dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        std::fs::create_dir_all(assets_dir.join("indexes")).unwrap();
+        std::fs::write(assets_dir.join("indexes/test.json"), &index).unwrap();
+        for (i, object) in objects.iter().enumerate() {
+            let hash = sha1_hex(object);
+            let path = assets_dir.join("objects").join(&hash[..2]).join(&hash);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            match i % 7 {
+                // Leave some out and damage others, keeping the size so only the hash catches it.
+                0 => {}
+                3 => std::fs::write(&path, vec![b'x'; object.len()]).unwrap(),
+                _ => std::fs::write(&path, object).unwrap(),
+            }
+        }
+
+        let (tx, mut rx) = mpsc::channel(1024);
+        let installer = Installer::new(&version, &libraries_dir, &assets_dir).with_events(tx);
+        let first = installer.verify().await.unwrap();
+        let second = installer.verify().await.unwrap();
+        drop(installer);
+
+        assert_eq!(first, second);
+        assert_eq!(first.checked, 2 + objects.len() + 1);
+        assert_eq!(first.missing.len(), objects.len().div_ceil(7));
+        assert_eq!(first.corrupt.len(), objects.iter().enumerate().filter(|(i, _)| i % 7 == 3).count());
+        let missing: Vec<&PathBuf> = first.missing.iter().map(|job| &job.dest).collect();
+        let mut sorted = missing.clone();
+        sorted.sort();
+        assert_eq!(missing, sorted, "asset objects are reported in path order");
+
+        let mut progress = Vec::new();
+        while let Some(event) = rx.recv().await {
+            if let InstallEvent::BytesProgress { downloaded, total, .. } = event {
+                progress.push((downloaded, total));
+            }
+        }
+        let present_objects: u64 = objects.iter().enumerate().filter(|(i, _)| i % 7 != 0).map(|(_, o)| o.len() as u64).sum();
+        let present_bytes = 5 + 6 + index.len() as u64 + present_objects;
+        // One update per file on disk, per run, ending at the bytes of every file present.
+        assert_eq!(progress.len(), 2 * (first.checked - first.missing.len()));
+        assert_eq!(progress.last().unwrap().0, present_bytes);
+    }
+
+    #[tokio::test]
+    async fn test_failed_download_warns_and_errors() {
+        let server = serve_files(HashMap::<String, Vec<u8>>::new());
+        let base_url = server.base_url();
+        let mut version: VersionDetail = serde_json::from_str(&version_json(base_url, b"{}")).unwrap();
+        version.asset_index = None;
+
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, mut rx) = mpsc::channel(64);
+        let installer = Installer::new(&version, dir.path().join("libraries"), dir.path().join("assets")).with_events(tx);
+        assert!(installer.install().await.is_err());
+        drop(installer);
+
+        let mut warnings = 0;
+        let mut completed = false;
+        while let Some(event) = rx.recv().await {
+            match event {
+                InstallEvent::Warning { .. } => warnings += 1,
+                InstallEvent::StageCompleted { .. } => completed = true,
+                _ => {}
+            }
+        }
+        assert_eq!(warnings, 2);
+        assert!(!completed);
+    }
+
+    /// Answers every request with the first bytes of a body far longer than it sends,
+    /// then holds the connection open, like a stalled mirror.
+    fn serve_slowly() -> MockServer {
+        serve(|_| Response::ok("0123456789".repeat(10_000)).cut_short(10).held_open(Duration::from_secs(30)))
+    }
+
+    /// Every `.part` file under `dir`.
+    fn partial_files(dir: &Path) -> Vec<PathBuf> {
+        let mut found = Vec::new();
+        for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                found.extend(partial_files(&path));
+            } else if path.extension().is_some_and(|ext| ext == "part") {
+                found.push(path);
+            }
+        }
+        found
+    }
+
+    #[tokio::test]
+    async fn test_cancel_mid_install_removes_partial_files() {
+        let server = serve_slowly();
+        let base_url = server.base_url();
+        let version: VersionDetail = serde_json::from_str(&version_json(base_url, b"{}")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let libraries_dir = dir.path().join("libraries");
+        let token = CancellationToken::new();
+
+        let (tx, mut rx) = mpsc::channel(64);
+        let installer = Installer::new(&version, &libraries_dir, dir.path().join("assets"))
+            .with_events(tx)
+            .with_cancellation(token.clone());
+        // Cancel once bytes have arrived, so the transfers are cut off mid-body.
+        let watched = libraries_dir.clone();
+        let canceller = tokio::spawn(async move {
+            let mut events = Vec::new();
+            let mut partial_seen = false;
+            while let Some(event) = rx.recv().await {
+                if matches!(event, InstallEvent::BytesProgress { downloaded, .. } if downloaded > 0) && !token.is_cancelled() {
+                    partial_seen = !partial_files(&watched).is_empty();
+                    token.cancel();
+                }
+                events.push(event);
+            }
+            (events, partial_seen)
+        });
+
+        let started = std::time::Instant::now();
+        let err = installer.install().await.unwrap_err();
+        drop(installer);
+        let (events, partial_seen) = canceller.await.unwrap();
+
+        assert!(err.downcast_ref::<Cancelled>().is_some(), "{:#}", err);
+        assert!(started.elapsed() < std::time::Duration::from_secs(10), "{:?}", started.elapsed());
+        assert!(partial_seen);
+        assert_eq!(partial_files(dir.path()), Vec::<PathBuf>::new());
+        assert!(!libraries_dir.join("com/example/fresh/1.0/fresh-1.0.jar").exists());
+        assert!(!libraries_dir.join("com/example/cached/1.0/cached-1.0.jar").exists());
+        // Being cancelled is not a failure to warn about, and no stage completed.
+        assert!(
+            !events
+                .iter()
+                .any(|e| matches!(e, InstallEvent::Warning { .. } | InstallEvent::StageCompleted { .. })),
+            "{:?}",
+            events
+        );
+    }
+
+    #[tokio::test]
+    async fn test_plan_sums_declared_and_probed_sizes() {
+        let sound = b"sound";
+        let texture = b"texture";
+        let index = format!(
+            r#"{{"objects": {{"a.ogg": {{"hash": "{}", "size": 5}}, "b.png": {{"hash": "{}", "size": 7}}}}}}"#,
+            sha1_hex(sound),
+            sha1_hex(texture)
+        )
+        .into_bytes();
+        let files = HashMap::from([
+            ("/indexes/test.json".to_string(), index.clone()),
+            (
+                "/maven/net/fabricmc/fabric-loader/0.16.0/fabric-loader-0.16.0.jar".to_string(),
+                vec![0; 12],
+            ),
+        ]);
+        let server = serve_files(files);
+        let base_url = server.base_url();
+
+        let mut version: VersionDetail = serde_json::from_str(&version_json(base_url, &index)).unwrap();
+        version.extra.insert(
+            "downloads".to_string(),
+            serde_json::json!({"client": {"sha1": sha1_hex(b"client jar"), "size": 10, "url": format!("{}/client.jar", base_url)}}),
+        );
+        let profile: LaunchProfile = serde_json::from_str(&format!(
+            r#"{{
+                "id": "fabric-loader-0.16.0-test-1",
+                "inheritsFrom": "test-1",
+                "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+                "libraries": [
+                    {{"name": "net.fabricmc:fabric-loader:0.16.0", "url": "{base_url}/maven/"}},
+                    {{"name": "net.fabricmc:missing:1.0", "url": "{base_url}/maven/"}}
+                ]
+            }}"#
+        ))
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let plan = plan_install(&version, Some(&profile), dir.path()).await.unwrap();
+
+        // Client jar, two vanilla libraries, two loader libraries and two assets.
+        assert_eq!(plan.file_count(), 7);
+        assert_eq!(plan.total_bytes, 10 + 5 + 6 + 12 + 5 + 7);
+        assert_eq!(plan.unknown_sizes, 1);
+        assert_eq!(plan.jobs[0].dest, dir.path().join("versions/test-1/test-1.jar"));
+        assert!(!dir.path().join("libraries").exists());
+        assert!(!dir.path().join("assets").exists());
+    }
+
+    #[tokio::test]
+    async fn test_plan_probes_sizes_with_the_downloader_client() {
+        let server = serve(|request| match request.header("x-lodestone-test") {
+            Some("plan") => Response::ok(vec![0; 12]),
+            _ => Response::status(403),
+        });
+        let base_url = server.base_url();
+        let version: VersionDetail = serde_json::from_str(
+            r#"{"id": "test-1", "type": "release", "mainClass": "net.minecraft.client.main.Main", "releaseTime": "2024-01-01T00:00:00+00:00"}"#,
+        )
+        .unwrap();
+        let profile: LaunchProfile = serde_json::from_str(&format!(
+            r#"{{"id": "fabric", "inheritsFrom": "test-1", "mainClass": "Knot", "libraries": [{{"name": "net.fabricmc:fabric-loader:0.16.0", "url": "{base_url}/maven/"}}]}}"#
+        ))
+        .unwrap();
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-lodestone-test", reqwest::header::HeaderValue::from_static("plan"));
+        let client = reqwest::Client::builder().default_headers(headers).build().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let plan = Installer::new(&version, dir.path().join("libraries"), dir.path().join("assets"))
+            .with_loader_profile(&profile)
+            .with_downloader(Downloader::new().with_client(client))
+            .plan()
+            .await
+            .unwrap();
+        assert_eq!(plan.total_bytes, 12);
+        assert_eq!(plan.unknown_sizes, 0);
+        assert_eq!(server.requests()[0].method, "HEAD");
+    }
+
+    fn client_version(base_url: &str, jar: &[u8]) -> VersionDetail {
+        serde_json::from_str(&format!(
+            r#"{{
+                "id": "test-1",
+                "type": "release",
+                "mainClass": "net.minecraft.client.main.Main",
+                "releaseTime": "2024-01-01T00:00:00+00:00",
+                "downloads": {{"client": {{"sha1": "{}", "size": {}, "url": "{}/client.jar"}}}}
+            }}"#,
+            sha1_hex(jar),
+            jar.len(),
+            base_url
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_download_client_jar_verifies_and_skips() {
+        let jar = b"not really a jar";
+        let server = serve_files(HashMap::from([("/client.jar".to_string(), jar.to_vec())]));
+        let base_url = server.base_url();
+        let version = client_version(base_url, jar);
+        let dir = tempfile::tempdir().unwrap();
+
+        let path = download_client_jar(&version, dir.path().join("versions")).await.unwrap();
+        assert_eq!(path, dir.path().join("versions/test-1/test-1.jar"));
+        assert_eq!(std::fs::read(&path).unwrap(), jar);
+
+        // Run again through the installer to see that the valid jar is skipped
+        let (tx, mut rx) = mpsc::channel(4);
+        let installer = Installer::new(&version, dir.path().join("libraries"), dir.path().join("assets"))
+            .with_client_jar(&path)
+            .with_events(tx);
+        let collector = tokio::spawn(async move {
+            let mut events = Vec::new();
+            while let Some(event) = rx.recv().await {
+                events.push(event);
+            }
+            events
+        });
+        assert_eq!(installer.install_client_jar().await.unwrap(), path);
+        drop(installer);
+        let events = collector.await.unwrap();
+        assert!(events.contains(&InstallEvent::FileDownloaded {
+            stage: InstallStage::ClientJar,
+            path: path.clone(),
+            bytes: jar.len() as u64,
+            skipped: true,
+        }));
+        assert_eq!(
+            events.last(),
+            Some(&InstallEvent::StageCompleted {
+                stage: InstallStage::ClientJar
+            })
+        );
+    }
+
+    #[test]
+    fn test_version_jar_path_rejects_unsafe_ids() {
+        assert_eq!(
+            version_jar_path("versions", "1.20.1-forge-47.2.0").unwrap(),
+            Path::new("versions/1.20.1-forge-47.2.0/1.20.1-forge-47.2.0.jar")
+        );
+        assert!(version_jar_path("versions", "1.14 Pre-Release 1").is_ok());
+        for id in ["", ".", "..", "../x", "1.20.1/../x", "..\\x", "C:x", "/etc/x"] {
+            assert!(version_jar_path("versions", id).is_err(), "{:?} was accepted", id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_client_jar_rejects_wrong_sha1() {
+        let server = serve_files(HashMap::from([("/client.jar".to_string(), b"tampered".to_vec())]));
+        let base_url = server.base_url();
+        let version = client_version(base_url, b"original");
+        let dir = tempfile::tempdir().unwrap();
+
+        assert!(download_client_jar(&version, dir.path()).await.is_err());
+        assert!(!client_jar_path(dir.path(), &version).unwrap().exists());
+
+        let without_destination = Installer::new(&version, dir.path(), dir.path());
+        assert!(without_destination.install_client_jar().await.is_err());
+    }
+}
@@ -0,0 +1,378 @@
+use anyhow::{Context, Result, anyhow};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+/// How long `java -version` may take before an installation is skipped.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// CPU architecture a JVM was built for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JavaArch {
+    X86,
+    X86_64,
+    Arm64,
+    /// The output did not say, or named an architecture we do not model.
+    Unknown,
+}
+
+impl JavaArch {
+    /// Map a `os.arch` system property (`amd64`, `x86`, `aarch64`, ...).
+    pub fn from_os_arch(os_arch: &str) -> Self {
+        match os_arch.trim().to_ascii_lowercase().as_str() {
+            "amd64" | "x86_64" | "x64" => Self::X86_64,
+            "x86" | "i386" | "i486" | "i586" | "i686" => Self::X86,
+            "aarch64" | "arm64" => Self::Arm64,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn is_64_bit(&self) -> bool {
+        matches!(self, Self::X86_64 | Self::Arm64)
+    }
+}
+
+/// A Java installation found on this machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaInstall {
+    /// Path to the `java` executable.
+    pub path: PathBuf,
+    /// Full version string, e.g. `17.0.8` or `1.8.0_381`.
+    pub version: String,
+    /// Major version, e.g. `17`, or `8` for `1.8.0_381`.
+    pub major: u8,
+    /// Vendor as reported by the JVM, e.g. `Eclipse Adoptium`. Empty if unknown.
+    pub vendor: String,
+    pub arch: JavaArch,
+}
+
+impl JavaInstall {
+    /// Parse the output of `java -XshowSettings:properties -version` (or plain
+    /// `java -version`) for the executable at `path`.
+    ///
+    /// Returns `None` if no version string can be found.
+    pub fn from_version_output(path: impl Into<PathBuf>, output: &str) -> Option<Self> {
+        let properties = parse_properties(output);
+        let version = properties
+            .iter()
+            .find(|(key, _)| *key == "java.version")
+            .map(|(_, value)| value.to_string())
+            .or_else(|| quoted_version(output))?;
+        let major = parse_major(&version)?;
+
+        let arch = match properties.iter().find(|(key, _)| *key == "os.arch") {
+            Some((_, os_arch)) => JavaArch::from_os_arch(os_arch),
+            // Without `os.arch` only the bitness is known; assume the common x86 builds.
+            None if output.contains("64-Bit") => JavaArch::X86_64,
+            // 32-bit HotSpot builds call themselves "Client VM" or "Server VM" without a bitness.
+            None if output.contains(" VM ") => JavaArch::X86,
+            None => JavaArch::Unknown,
+        };
+
+        let vendor = properties
+            .iter()
+            .find(|(key, _)| *key == "java.vendor")
+            .map(|(_, value)| value.to_string())
+            .unwrap_or_else(|| vendor_from_banner(output).to_string());
+
+        Some(Self {
+            path: path.into(),
+            version,
+            major,
+            vendor,
+            arch,
+        })
+    }
+}
+
+/// Java major version of a version string: `17.0.8` -> 17, `1.8.0_381` -> 8,
+/// `21` -> 21, `22-ea` -> 22.
+pub fn parse_major(version: &str) -> Option<u8> {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let first: u8 = parts.next()?.parse().ok()?;
+    if first == 1 {
+        // Java 8 and older report `1.<major>`.
+        parts.next()?.parse().ok()
+    } else {
+        Some(first)
+    }
+}
+
+/// `key = value` lines printed by `-XshowSettings:properties`. Multi-line values
+/// (indented continuation lines) are ignored.
+fn parse_properties(output: &str) -> Vec<(&str, &str)> {
+    output
+        .lines()
+        .filter(|line| line.starts_with("    ") && !line.starts_with("        "))
+        .filter_map(|line| line.trim().split_once(" = "))
+        .collect()
+}
+
+/// The quoted version from the banner line, e.g. `openjdk version "17.0.8" 2023-07-18`.
+fn quoted_version(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        if !line.contains(" version \"") {
+            return None;
+        }
+        let start = line.find('"')? + 1;
+        let end = start + line[start..].find('"')?;
+        Some(line[start..end].to_string())
+    })
+}
+
+/// Best-effort vendor name from the `Runtime Environment` banner line.
+fn vendor_from_banner(output: &str) -> &'static str {
+    let lower = output.to_ascii_lowercase();
+    const VENDORS: &[(&str, &str)] = &[
+        ("temurin", "Eclipse Adoptium"),
+        ("adoptopenjdk", "AdoptOpenJDK"),
+        ("zulu", "Azul Systems, Inc."),
+        ("corretto", "Amazon.com Inc."),
+        ("microsoft", "Microsoft"),
+        ("graalvm", "GraalVM"),
+        ("semeru", "IBM Corporation"),
+        ("openj9", "Eclipse OpenJ9"),
+        ("liberica", "BellSoft"),
+        ("java(tm)", "Oracle Corporation"),
+    ];
+    VENDORS
+        .iter()
+        .find(|(needle, _)| lower.contains(needle))
+        .map(|(_, vendor)| *vendor)
+        .unwrap_or("")
+}
+
+/// Run `java` and describe the installation it belongs to.
+pub async fn probe(java: impl AsRef<Path>) -> Result<JavaInstall> {
+    let java = java.as_ref();
+    let output = tokio::time::timeout(
+        PROBE_TIMEOUT,
+        tokio::process::Command::new(java)
+            .arg("-XshowSettings:properties")
+            .arg("-version")
+            .output(),
+    )
+    .await
+    .map_err(|_| anyhow!("{} -version timed out", java.display()))?
+    .with_context(|| format!("Failed to run {}", java.display()))?;
+
+    // Both the settings and the version banner go to stderr.
+    let text = String::from_utf8_lossy(&output.stderr);
+    JavaInstall::from_version_output(java, &text).ok_or_else(|| anyhow!("Could not read the Java version of {}", java.display()))
+}
+
+/// Find Java installations on `PATH`, under `JAVA_HOME`, and in the usual install
+/// directories for this OS, newest major first.
+///
+/// Every candidate is run with `-version`; ones that fail to start or report no
+/// version are left out. Symlinked duplicates (e.g. `/usr/bin/java` pointing into
+/// `/usr/lib/jvm`) are reported once.
+pub async fn detect_installations() -> Vec<JavaInstall> {
+    let mut seen = HashSet::new();
+    let mut probes = JoinSet::new();
+    for candidate in candidate_executables() {
+        let Ok(resolved) = dunce::canonicalize(&candidate) else {
+            continue;
+        };
+        if seen.insert(resolved.clone()) {
+            probes.spawn(async move { probe(&resolved).await });
+        }
+    }
+
+    let mut installs = Vec::new();
+    while let Some(result) = probes.join_next().await {
+        if let Ok(Ok(install)) = result {
+            installs.push(install);
+        }
+    }
+    installs.sort_by(|a, b| b.major.cmp(&a.major).then_with(|| a.path.cmp(&b.path)));
+    installs
+}
+
+fn java_exe_name() -> &'static str {
+    if cfg!(windows) { "java.exe" } else { "java" }
+}
+
+/// Existing executables worth probing, possibly with duplicates.
+fn candidate_executables() -> Vec<PathBuf> {
+    let exe = java_exe_name();
+    let mut candidates = Vec::new();
+
+    if let Some(path) = std::env::var_os("PATH") {
+        candidates.extend(std::env::split_paths(&path).map(|dir| dir.join(exe)));
+    }
+    if let Some(home) = std::env::var_os("JAVA_HOME") {
+        candidates.push(PathBuf::from(home).join("bin").join(exe));
+    }
+    for root in install_roots() {
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let home = entry.path();
+            candidates.push(home.join("bin").join(exe));
+            // macOS bundles keep the real home under Contents/Home.
+            candidates.push(home.join("Contents").join("Home").join("bin").join(exe));
+        }
+    }
+
+    candidates.retain(|path| path.is_file());
+    candidates
+}
+
+/// Directories whose children are JDK or JRE homes.
+fn install_roots() -> Vec<PathBuf> {
+    let home = std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).map(PathBuf::from);
+    let mut roots = Vec::new();
+
+    if cfg!(windows) {
+        let program_files = [std::env::var_os("ProgramFiles"), std::env::var_os("ProgramFiles(x86)")];
+        for base in program_files.into_iter().flatten().map(PathBuf::from) {
+            for vendor in ["Java", "Eclipse Adoptium", "Zulu", "Microsoft", "Amazon Corretto", "BellSoft", "Semeru"] {
+                roots.push(base.join(vendor));
+            }
+        }
+    } else if cfg!(target_os = "macos") {
+        roots.push(PathBuf::from("/Library/Java/JavaVirtualMachines"));
+        roots.push(PathBuf::from("/opt/homebrew/opt"));
+        if let Some(home) = &home {
+            roots.push(home.join("Library/Java/JavaVirtualMachines"));
+        }
+    } else {
+        for root in ["/usr/lib/jvm", "/usr/lib64/jvm", "/usr/java", "/opt/java", "/opt/jdk"] {
+            roots.push(PathBuf::from(root));
+        }
+    }
+
+    if let Some(home) = &home {
+        roots.push(home.join(".jdks"));
+        roots.push(home.join(".sdkman").join("candidates").join("java"));
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMURIN_17: &str = "openjdk version \"17.0.8\" 2023-07-18
+OpenJDK Runtime Environment Temurin-17.0.8+7 (build 17.0.8+7)
+OpenJDK 64-Bit Server VM Temurin-17.0.8+7 (build 17.0.8+7, mixed mode, sharing)
+";
+
+    const ORACLE_8_32BIT: &str = "java version \"1.8.0_381\"
+Java(TM) SE Runtime Environment (build 1.8.0_381-b09)
+Java HotSpot(TM) Client VM (build 25.381-b09, mixed mode, sharing)
+";
+
+    const ZULU_21_WITH_OPTIONS: &str = "Picked up _JAVA_OPTIONS: -Dawt.useSystemAAFontSettings=on
+openjdk version \"21.0.1\" 2023-10-17 LTS
+OpenJDK Runtime Environment Zulu21.30+15-CA (build 21.0.1+12-LTS)
+OpenJDK 64-Bit Server VM Zulu21.30+15-CA (build 21.0.1+12-LTS, mixed mode, sharing)
+";
+
+    const OPENJDK_11_NO_MINOR: &str = "openjdk version \"11\" 2018-09-25
+OpenJDK Runtime Environment 18.9 (build 11+28)
+OpenJDK 64-Bit Server VM 18.9 (build 11+28, mixed mode)
+";
+
+    const SETTINGS_ARM64: &str = "Property settings:
+    file.encoding = UTF-8
+    java.class.path =
+    java.library.path = /Users/steve/Library/Java/Extensions
+        /Library/Java/Extensions
+    java.vendor = Microsoft
+    java.version = 17.0.8
+    os.arch = aarch64
+    os.name = Mac OS X
+
+openjdk version \"17.0.8\" 2023-07-18 LTS
+OpenJDK Runtime Environment Microsoft-8035246 (build 17.0.8+7-LTS)
+OpenJDK 64-Bit Server VM Microsoft-8035246 (build 17.0.8+7-LTS, mixed mode)
+";
+
+    #[test]
+    fn test_parse_temurin() {
+        let install = JavaInstall::from_version_output("/usr/bin/java", TEMURIN_17).unwrap();
+        assert_eq!(install.version, "17.0.8");
+        assert_eq!(install.major, 17);
+        assert_eq!(install.vendor, "Eclipse Adoptium");
+        assert_eq!(install.arch, JavaArch::X86_64);
+        assert_eq!(install.path, Path::new("/usr/bin/java"));
+    }
+
+    #[test]
+    fn test_parse_oracle_legacy_32_bit() {
+        let install = JavaInstall::from_version_output("java", ORACLE_8_32BIT).unwrap();
+        assert_eq!(install.version, "1.8.0_381");
+        assert_eq!(install.major, 8);
+        assert_eq!(install.vendor, "Oracle Corporation");
+        assert_eq!(install.arch, JavaArch::X86);
+        assert!(!install.arch.is_64_bit());
+    }
+
+    #[test]
+    fn test_parse_skips_picked_up_lines() {
+        let install = JavaInstall::from_version_output("java", ZULU_21_WITH_OPTIONS).unwrap();
+        assert_eq!(install.version, "21.0.1");
+        assert_eq!(install.major, 21);
+        assert_eq!(install.vendor, "Azul Systems, Inc.");
+    }
+
+    #[test]
+    fn test_parse_version_without_minor() {
+        let install = JavaInstall::from_version_output("java", OPENJDK_11_NO_MINOR).unwrap();
+        assert_eq!(install.version, "11");
+        assert_eq!(install.major, 11);
+        assert_eq!(install.vendor, "");
+    }
+
+    #[test]
+    fn test_parse_settings_properties() {
+        let install = JavaInstall::from_version_output("java", SETTINGS_ARM64).unwrap();
+        assert_eq!(install.version, "17.0.8");
+        assert_eq!(install.vendor, "Microsoft");
+        assert_eq!(install.arch, JavaArch::Arm64);
+    }
+
+    #[test]
+    fn test_parse_garbage() {
+        assert!(JavaInstall::from_version_output("java", "").is_none());
+        assert!(JavaInstall::from_version_output("java", "Error: could not open `jvm.cfg'").is_none());
+    }
+
+    #[test]
+    fn test_parse_major() {
+        assert_eq!(parse_major("1.8.0_381"), Some(8));
+        assert_eq!(parse_major("17.0.8"), Some(17));
+        assert_eq!(parse_major("21"), Some(21));
+        assert_eq!(parse_major("22-ea"), Some(22));
+        assert_eq!(parse_major("1.7.0_80"), Some(7));
+        assert_eq!(parse_major("abc"), None);
+    }
+
+    #[test]
+    fn test_arch_from_os_arch() {
+        assert_eq!(JavaArch::from_os_arch("amd64"), JavaArch::X86_64);
+        assert_eq!(JavaArch::from_os_arch("x86"), JavaArch::X86);
+        assert_eq!(JavaArch::from_os_arch("aarch64"), JavaArch::Arm64);
+        assert_eq!(JavaArch::from_os_arch("ppc64le"), JavaArch::Unknown);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_probe_fake_java() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let java = dir.path().join("java");
+        let script = format!("#!/bin/sh\ncat >&2 <<'EOF'\n{}EOF\n", TEMURIN_17);
+        std::fs::write(&java, script).unwrap();
+        std::fs::set_permissions(&java, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let install = probe(&java).await.unwrap();
+        assert_eq!(install.major, 17);
+        assert_eq!(install.path, java);
+    }
+}
@@ -0,0 +1,88 @@
+//! Java runtimes for running the game.
+//!
+//! Minecraft needs a specific Java major per version: 8 up to 1.16, 16 for 1.17,
+//! 17 from 1.18 and 21 from 1.20.5. [`required_java_major`] reads that requirement
+//! from a version JSON, [`install_runtime`] downloads the matching runtime
+//! Mojang publishes for the current platform, and [`detect_installations`] finds
+//! the JDKs and JREs already installed on the machine.
+
+pub mod detect;
+pub mod runtime;
+
+pub use detect::{JavaArch, JavaInstall, detect_installations, parse_major, probe};
+pub use runtime::{
+    JavaRuntimeManifest, RuntimeAvailability, RuntimeEntry, RuntimeFile, RuntimeFileDownloads, RuntimeFileRef, RuntimeFiles, RuntimeVersion,
+    install_runtime, install_runtime_for_version, java_executable, platform_key,
+};
+
+use crate::vanilla::VersionDetail;
+
+/// The Java major every version without a `javaVersion` block runs on.
+pub const LEGACY_JAVA_MAJOR: u8 = 8;
+
+/// The Java major version `version` requires, from its `javaVersion.majorVersion`.
+///
+/// Version JSONs older than the 1.17 format have no `javaVersion` block and run on
+/// Java 8.
+pub fn required_java_major(version: &VersionDetail) -> u8 {
+    version.java_version.as_ref().map_or(LEGACY_JAVA_MAJOR, |java| java.major_version)
+}
+
+/// The Mojang runtime component for `version`, e.g. `java-runtime-gamma`.
+///
+/// Uses the version's own `javaVersion.component` when present and falls back to
+/// the component Mojang ships for its Java major otherwise.
+pub fn runtime_component(version: &VersionDetail) -> &str {
+    match &version.java_version {
+        Some(java) => &java.component,
+        None => component_for_major(LEGACY_JAVA_MAJOR).unwrap_or("jre-legacy"),
+    }
+}
+
+/// The Mojang runtime component that provides Java `major`, if there is one.
+pub fn component_for_major(major: u8) -> Option<&'static str> {
+    match major {
+        8 => Some("jre-legacy"),
+        16 => Some("java-runtime-alpha"),
+        17 => Some("java-runtime-gamma"),
+        21 => Some("java-runtime-delta"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detail(java_version: &str) -> VersionDetail {
+        let json = format!(
+            r#"{{
+                "id": "1.20.6",
+                "type": "release",
+                "mainClass": "net.minecraft.client.main.Main",
+                "releaseTime": "2024-04-29T12:00:00+00:00"
+                {}
+            }}"#,
+            java_version
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_required_java_major() {
+        let modern = detail(r#", "javaVersion": {"component": "java-runtime-delta", "majorVersion": 21}"#);
+        assert_eq!(required_java_major(&modern), 21);
+        assert_eq!(runtime_component(&modern), "java-runtime-delta");
+
+        let legacy = detail("");
+        assert_eq!(required_java_major(&legacy), 8);
+        assert_eq!(runtime_component(&legacy), "jre-legacy");
+    }
+
+    #[test]
+    fn test_component_for_major() {
+        assert_eq!(component_for_major(17), Some("java-runtime-gamma"));
+        assert_eq!(component_for_major(21), Some("java-runtime-delta"));
+        assert_eq!(component_for_major(11), None);
+    }
+}
@@ -0,0 +1,504 @@
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+use crate::download::{self, DownloadJob, Downloader};
+use crate::http::{self, HttpClient};
+use crate::vanilla::VersionDetail;
+use crate::vanilla::rules::{current_arch, current_os};
+
+const RUNTIME_MANIFEST_URL: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/all.json";
+
+/// Mojang's index of Java runtimes, keyed by platform (e.g. `windows-x64`) and then
+/// by component (e.g. `java-runtime-gamma`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct JavaRuntimeManifest {
+    pub platforms: HashMap<String, HashMap<String, Vec<RuntimeEntry>>>,
+}
+
+/// One published build of a runtime component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeEntry {
+    pub availability: RuntimeAvailability,
+    /// The file list of this runtime, see [`RuntimeFiles`].
+    pub manifest: RuntimeFileRef,
+    pub version: RuntimeVersion,
+}
+
+/// Staged rollout information; the launcher ignores it and treats every entry as available.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeAvailability {
+    pub group: u32,
+    pub progress: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeVersion {
+    /// Full Java version, e.g. `17.0.8`.
+    pub name: String,
+    pub released: chrono::DateTime<chrono::offset::Utc>,
+}
+
+/// A downloadable document or file with its checksum.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeFileRef {
+    pub sha1: String,
+    pub size: u64,
+    pub url: String,
+}
+
+/// The file list of a single runtime, keyed by path relative to the runtime root.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeFiles {
+    pub files: BTreeMap<String, RuntimeFile>,
+}
+
+/// An entry of [`RuntimeFiles`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RuntimeFile {
+    File {
+        #[serde(default)]
+        executable: bool,
+        downloads: RuntimeFileDownloads,
+    },
+    Directory,
+    /// A symbolic link; `target` is relative to the link's own directory.
+    Link {
+        target: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeFileDownloads {
+    pub raw: RuntimeFileRef,
+    /// LZMA-compressed copy, which this crate does not use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lzma: Option<RuntimeFileRef>,
+}
+
+impl JavaRuntimeManifest {
+    /// Fetch the runtime index from Mojang's launcher metadata service.
+    pub async fn fetch() -> Result<Self> {
+        Self::fetch_with_client(http::client()).await
+    }
+
+    /// Like [`JavaRuntimeManifest::fetch`], but through a caller-provided HTTP client.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "fetch_java_runtimes", skip_all))]
+    pub async fn fetch_with_client(client: &impl HttpClient) -> Result<Self> {
+        Ok(client.get_json(RUNTIME_MANIFEST_URL).await?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// The current build of `component` for `platform`, if Mojang publishes one.
+    pub fn runtime(&self, platform: &str, component: &str) -> Option<&RuntimeEntry> {
+        self.platforms.get(platform)?.get(component)?.first()
+    }
+
+    /// Components with at least one build for `platform`, sorted by name.
+    pub fn components(&self, platform: &str) -> Vec<&str> {
+        let mut components: Vec<&str> = self
+            .platforms
+            .get(platform)
+            .map(|c| {
+                c.iter()
+                    .filter(|(_, entries)| !entries.is_empty())
+                    .map(|(name, _)| name.as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+        components.sort_unstable();
+        components
+    }
+}
+
+impl RuntimeFiles {
+    /// Fetch the file list an entry points to, failing if it does not have the SHA-1
+    /// the entry advertises.
+    pub async fn fetch(manifest: &RuntimeFileRef) -> Result<Self> {
+        let bytes = http::get(&manifest.url).await?.bytes().await?;
+        let sha1 = download::sha1_hex(&bytes);
+        if !sha1.eq_ignore_ascii_case(&manifest.sha1) {
+            bail!("Java runtime file list {} has SHA-1 {}, expected {}", manifest.url, sha1, manifest.sha1);
+        }
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Download jobs for every regular file, placed under `dest`. Fails if a path in
+    /// the list would end up outside of `dest`.
+    pub fn download_jobs(&self, dest: impl AsRef<Path>) -> Result<Vec<DownloadJob>> {
+        let dest = dest.as_ref();
+        let mut jobs = Vec::new();
+        for (path, file) in &self.files {
+            if let RuntimeFile::File { downloads, .. } = file {
+                jobs.push(
+                    DownloadJob::new(&downloads.raw.url, runtime_path(dest, path)?)
+                        .with_sha1(&downloads.raw.sha1)
+                        .with_size(downloads.raw.size),
+                );
+            }
+        }
+        Ok(jobs)
+    }
+
+    /// Sum of the raw sizes of every regular file.
+    pub fn total_size(&self) -> u64 {
+        self.files
+            .values()
+            .map(|file| match file {
+                RuntimeFile::File { downloads, .. } => downloads.raw.size,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Download the runtime into `dest`, then mark executables and create links.
+    ///
+    /// Files that already exist with the right SHA-1 are not downloaded again, so
+    /// this also repairs a partially installed runtime. Nothing is written if a path
+    /// or link target in the list would end up outside of `dest`.
+    pub async fn install(&self, dest: impl AsRef<Path>, downloader: &Downloader) -> Result<()> {
+        let dest = dest.as_ref();
+        let jobs = self.download_jobs(dest)?;
+        for (path, file) in &self.files {
+            if let RuntimeFile::Link { target } = file {
+                check_link_target(path, target)?;
+            }
+        }
+
+        for (path, file) in &self.files {
+            if matches!(file, RuntimeFile::Directory) {
+                tokio::fs::create_dir_all(runtime_path(dest, path)?).await?;
+            }
+        }
+
+        let summary = downloader.download(jobs).await;
+        if let Some(failure) = summary.failed.into_iter().next() {
+            return Err(failure.error).with_context(|| format!("Failed to download Java runtime file {}", failure.job.dest.display()));
+        }
+
+        for (path, file) in &self.files {
+            match file {
+                RuntimeFile::File { executable: true, .. } => mark_executable(&runtime_path(dest, path)?).await?,
+                RuntimeFile::Link { target } => create_link(&runtime_path(dest, path)?, target).await?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where `path` from a file list goes under `dest`.
+fn runtime_path(dest: &Path, path: &str) -> Result<PathBuf> {
+    match download::enclosed_path(path) {
+        Some(relative) => Ok(dest.join(relative)),
+        None => bail!("Refusing to install Java runtime file {} outside of {}", path, dest.display()),
+    }
+}
+
+/// Fails unless the link at `path` resolves to `target` inside the runtime. The
+/// target is relative to the link's own directory, so it may go up with `..` as
+/// long as it does not leave the runtime root.
+fn check_link_target(path: &str, target: &str) -> Result<()> {
+    let escapes = || anyhow!("Refusing to link Java runtime file {} to {} outside of the runtime", path, target);
+    let link = download::enclosed_path(path).ok_or_else(escapes)?;
+    if target.contains('\\') {
+        return Err(escapes());
+    }
+    // How many directories deep the link target is at each step.
+    let mut depth = link.components().count() - 1;
+    for component in Path::new(target).components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => depth = depth.checked_sub(1).ok_or_else(escapes)?,
+            Component::RootDir | Component::Prefix(_) => return Err(escapes()),
+        }
+    }
+    Ok(())
+}
+
+/// The `all.json` platform key for a Mojang OS and architecture name (as used by
+/// [`crate::vanilla::RuleContext`]), or `None` if Mojang ships no runtimes for it.
+pub fn platform_key(os: &str, arch: &str) -> Option<&'static str> {
+    match (os, arch) {
+        ("windows", "x86_64") => Some("windows-x64"),
+        ("windows", "x86") => Some("windows-x86"),
+        ("windows", "arm64") => Some("windows-arm64"),
+        ("osx", "x86_64") => Some("mac-os"),
+        ("osx", "arm64") => Some("mac-os-arm64"),
+        ("linux", "x86_64") => Some("linux"),
+        ("linux", "x86") => Some("linux-i386"),
+        _ => None,
+    }
+}
+
+/// Path of the java executable inside a runtime installed at `runtime_dir`.
+pub fn java_executable(runtime_dir: impl AsRef<Path>) -> PathBuf {
+    java_executable_for(runtime_dir.as_ref(), current_os())
+}
+
+fn java_executable_for(runtime_dir: &Path, os: &str) -> PathBuf {
+    match os {
+        "windows" => runtime_dir.join("bin").join("java.exe"),
+        "osx" => runtime_dir.join("jre.bundle/Contents/Home/bin/java"),
+        _ => runtime_dir.join("bin").join("java"),
+    }
+}
+
+/// Install `component` for the current platform into `runtimes_dir/<component>` and
+/// return the path to its java executable.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use minecraft_modloaders::java::{JavaRuntimeManifest, install_runtime};
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let manifest = JavaRuntimeManifest::fetch().await?;
+/// let java = install_runtime(&manifest, "java-runtime-gamma", "runtimes").await?;
+/// println!("Java 17 installed at {}", java.display());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn install_runtime(manifest: &JavaRuntimeManifest, component: &str, runtimes_dir: impl AsRef<Path>) -> Result<PathBuf> {
+    let (os, arch) = (current_os(), current_arch());
+    let platform = platform_key(os, arch).ok_or_else(|| anyhow!("Mojang does not publish Java runtimes for {} {}", os, arch))?;
+    let entry = manifest
+        .runtime(platform, component)
+        .ok_or_else(|| anyhow!("Java runtime {} is not available for {}", component, platform))?;
+
+    let runtime_dir = runtimes_dir.as_ref().join(component);
+    let files = RuntimeFiles::fetch(&entry.manifest)
+        .await
+        .with_context(|| format!("Failed to fetch the file list of Java runtime {}", component))?;
+    files.install(&runtime_dir, &Downloader::new()).await?;
+    Ok(java_executable_for(&runtime_dir, os))
+}
+
+/// Fetch the runtime index and install the runtime `version` needs.
+pub async fn install_runtime_for_version(version: &VersionDetail, runtimes_dir: impl AsRef<Path>) -> Result<PathBuf> {
+    let manifest = JavaRuntimeManifest::fetch().await?;
+    install_runtime(&manifest, super::runtime_component(version), runtimes_dir).await
+}
+
+#[cfg(unix)]
+async fn mark_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .await
+        .with_context(|| format!("Failed to mark {} as executable", path.display()))
+}
+
+#[cfg(not(unix))]
+async fn mark_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn create_link(path: &Path, target: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    match tokio::fs::remove_file(path).await {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+        _ => {}
+    }
+    tokio::fs::symlink(target, path)
+        .await
+        .with_context(|| format!("Failed to link {} to {}", path.display(), target))
+}
+
+/// Runtime links only exist in the macOS and Linux runtimes.
+#[cfg(not(unix))]
+async fn create_link(_path: &Path, _target: &str) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_FIXTURE: &str = r#"{
+        "linux": {
+            "java-runtime-gamma": [
+                {
+                    "availability": {"group": 8135, "progress": 100},
+                    "manifest": {
+                        "sha1": "55db9ea6decdd2ba8d8d2b4cea0d8bdf0e17e8b8",
+                        "size": 127810,
+                        "url": "https://piston-meta.mojang.com/v1/packages/55db/manifest.json"
+                    },
+                    "version": {"name": "17.0.8", "released": "2023-08-09T11:12:49+00:00"}
+                }
+            ],
+            "jre-legacy": [
+                {
+                    "availability": {"group": 6, "progress": 100},
+                    "manifest": {
+                        "sha1": "a1c15cc788f8893fba7e988eb27404772f699a84",
+                        "size": 125744,
+                        "url": "https://piston-meta.mojang.com/v1/packages/a1c1/manifest.json"
+                    },
+                    "version": {"name": "8u51", "released": "2021-01-21T16:08:00+00:00"}
+                }
+            ],
+            "minecraft-java-exe": []
+        },
+        "windows-x64": {}
+    }"#;
+
+    const FILES_FIXTURE: &str = r#"{
+        "files": {
+            "bin": {"type": "directory"},
+            "bin/java": {
+                "type": "file",
+                "executable": true,
+                "downloads": {
+                    "lzma": {"sha1": "1111111111111111111111111111111111111111", "size": 5, "url": "https://example.com/java.lzma"},
+                    "raw": {"sha1": "2222222222222222222222222222222222222222", "size": 12, "url": "https://example.com/java"}
+                }
+            },
+            "lib/modules": {
+                "type": "file",
+                "executable": false,
+                "downloads": {
+                    "raw": {"sha1": "3333333333333333333333333333333333333333", "size": 30, "url": "https://example.com/modules"}
+                }
+            },
+            "legal/java.base/LICENSE": {"type": "link", "target": "../java.desktop/LICENSE"}
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_runtime_manifest() {
+        let manifest = JavaRuntimeManifest::from_json(ALL_FIXTURE).unwrap();
+        let gamma = manifest.runtime("linux", "java-runtime-gamma").unwrap();
+        assert_eq!(gamma.version.name, "17.0.8");
+        assert_eq!(gamma.manifest.size, 127810);
+        assert!(gamma.manifest.url.ends_with("manifest.json"));
+
+        assert_eq!(manifest.components("linux"), vec!["java-runtime-gamma", "jre-legacy"]);
+        assert!(manifest.components("windows-x64").is_empty());
+        assert!(manifest.runtime("linux", "minecraft-java-exe").is_none());
+        assert!(manifest.runtime("mac-os", "java-runtime-gamma").is_none());
+    }
+
+    #[test]
+    fn test_parse_runtime_files() {
+        let files = RuntimeFiles::from_json(FILES_FIXTURE).unwrap();
+        assert_eq!(files.files.len(), 4);
+        assert_eq!(files.files["bin"], RuntimeFile::Directory);
+        assert_eq!(
+            files.files["legal/java.base/LICENSE"],
+            RuntimeFile::Link {
+                target: "../java.desktop/LICENSE".to_string()
+            }
+        );
+        assert_eq!(files.total_size(), 42);
+
+        let jobs = files.download_jobs("/runtimes/java-runtime-gamma").unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].url, "https://example.com/java");
+        assert_eq!(jobs[0].dest, Path::new("/runtimes/java-runtime-gamma/bin/java"));
+        assert_eq!(jobs[0].sha1.as_deref(), Some("2222222222222222222222222222222222222222"));
+        assert_eq!(jobs[1].size, Some(30));
+    }
+
+    #[test]
+    fn test_rejects_paths_outside_the_runtime() {
+        let files = |path: &str| {
+            let json = FILES_FIXTURE.replace(r#""lib/modules""#, &serde_json::to_string(path).unwrap());
+            RuntimeFiles::from_json(&json).unwrap()
+        };
+        for path in ["../escape", "lib/../../escape", "/etc/escape", "lib\\..\\..\\escape", ""] {
+            assert!(files(path).download_jobs("/runtimes/java-runtime-gamma").is_err(), "{}", path);
+        }
+
+        assert!(check_link_target("legal/java.base/LICENSE", "../java.desktop/LICENSE").is_ok());
+        assert!(check_link_target("bin/java", "../lib/java").is_ok());
+        assert!(check_link_target("bin/java", "../../java").is_err());
+        assert!(check_link_target("bin/java", "/usr/bin/java").is_err());
+        assert!(check_link_target("../bin/java", "java").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_install_rejects_link_outside_the_runtime() {
+        let json = FILES_FIXTURE.replace("../java.desktop/LICENSE", "../../../../outside");
+        let files = RuntimeFiles::from_json(&json).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("runtime");
+        let err = files.install(&dest, &Downloader::new()).await.unwrap_err();
+        assert!(err.to_string().contains("outside of the runtime"), "{:#}", err);
+        assert!(!dest.exists());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_checks_file_list_sha1() {
+        use test_support::{Response, serve};
+
+        let server = serve(|_| Response::ok(FILES_FIXTURE));
+        let mut manifest = RuntimeFileRef {
+            sha1: crate::download::sha1_hex(FILES_FIXTURE.as_bytes()),
+            size: FILES_FIXTURE.len() as u64,
+            url: server.url("/manifest.json"),
+        };
+        assert_eq!(RuntimeFiles::fetch(&manifest).await.unwrap().files.len(), 4);
+
+        manifest.sha1 = "0000000000000000000000000000000000000000".to_string();
+        let err = RuntimeFiles::fetch(&manifest).await.unwrap_err();
+        assert!(err.to_string().contains("expected 0000000000000000000000000000000000000000"), "{:#}", err);
+    }
+
+    #[test]
+    fn test_platform_key_and_executable() {
+        assert_eq!(platform_key("windows", "x86_64"), Some("windows-x64"));
+        assert_eq!(platform_key("osx", "arm64"), Some("mac-os-arm64"));
+        assert_eq!(platform_key("linux", "x86_64"), Some("linux"));
+        assert_eq!(platform_key("linux", "arm64"), None);
+
+        let dir = Path::new("runtimes/java-runtime-gamma");
+        assert_eq!(java_executable_for(dir, "windows"), dir.join("bin").join("java.exe"));
+        assert_eq!(java_executable_for(dir, "osx"), dir.join("jre.bundle/Contents/Home/bin/java"));
+        assert_eq!(java_executable_for(dir, "linux"), dir.join("bin").join("java"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_marks_executables_and_links() {
+        use std::os::unix::fs::PermissionsExt;
+        use test_support::{Response, serve};
+
+        let server = serve(|_| Response::ok("#!/bin/"));
+        let base_url = server.base_url();
+
+        let sha1 = crate::download::sha1_hex(b"#!/bin/");
+        let json = FILES_FIXTURE
+            .replace("https://example.com", &base_url)
+            .replace("2222222222222222222222222222222222222222", &sha1)
+            .replace("3333333333333333333333333333333333333333", &sha1)
+            // Every file is served the same 7-byte body, so declare that size
+            .replace(r#""size": 12"#, r#""size": 7"#)
+            .replace(r#""size": 30"#, r#""size": 7"#);
+        let files = RuntimeFiles::from_json(&json).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        files.install(dir.path(), &Downloader::new()).await.unwrap();
+
+        let java = dir.path().join("bin/java");
+        assert_eq!(std::fs::read(&java).unwrap(), b"#!/bin/");
+        assert_eq!(std::fs::metadata(&java).unwrap().permissions().mode() & 0o777, 0o755);
+        let link = dir.path().join("legal/java.base/LICENSE");
+        assert_eq!(std::fs::read_link(link).unwrap(), Path::new("../java.desktop/LICENSE"));
+    }
+}
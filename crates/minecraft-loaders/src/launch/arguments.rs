@@ -0,0 +1,1030 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+
+use super::jvm::{JvmSettings, merge_jvm_args};
+use super::log4j::{self, Log4jMitigation, NO_LOOKUPS_ARG};
+use crate::fabric::LaunchProfile;
+use crate::vanilla::{Argument, RuleContext, VersionDetail};
+
+/// Who the game is launched as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchAuth {
+    pub player_name: String,
+    /// Player UUID without dashes, as the game expects it.
+    pub uuid: String,
+    pub access_token: String,
+    /// `msa` for Microsoft accounts, `legacy` for offline play.
+    pub user_type: String,
+    pub xuid: Option<String>,
+    pub client_id: Option<String>,
+}
+
+impl Default for LaunchAuth {
+    fn default() -> Self {
+        Self {
+            player_name: "Player".to_string(),
+            uuid: "00000000000000000000000000000000".to_string(),
+            access_token: "0".to_string(),
+            user_type: "legacy".to_string(),
+            xuid: None,
+            client_id: None,
+        }
+    }
+}
+
+#[cfg(feature = "auth")]
+impl From<&emerald_auth::Session> for LaunchAuth {
+    fn from(session: &emerald_auth::Session) -> Self {
+        use secrecy::ExposeSecret;
+
+        Self {
+            player_name: session.username.clone(),
+            uuid: session.uuid.clone(),
+            access_token: session.access_token.expose_secret().to_string(),
+            user_type: session.user_type().to_string(),
+            xuid: None,
+            client_id: None,
+        }
+    }
+}
+
+/// Where to go straight after the game starts, skipping the title screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuickPlay {
+    /// A singleplayer world, by its folder name under `saves/`.
+    World(String),
+    /// A multiplayer server as `host` or `host:port`.
+    Server(String),
+    /// A Realm, by its id.
+    Realm(String),
+}
+
+impl QuickPlay {
+    /// The launcher feature the version's argument rules gate this on, and the
+    /// placeholder that carries the target.
+    fn feature(&self) -> (&'static str, &'static str, &str) {
+        match self {
+            Self::World(name) => ("is_quick_play_singleplayer", "quickPlaySingleplayer", name),
+            Self::Server(address) => ("is_quick_play_multiplayer", "quickPlayMultiplayer", address),
+            Self::Realm(id) => ("is_quick_play_realms", "quickPlayRealms", id),
+        }
+    }
+
+    /// `--server`/`--port` for versions from before quick play (pre-1.20). Older
+    /// versions have no way to open a world or Realm directly, so those yield nothing.
+    fn legacy_args(&self) -> Vec<String> {
+        let Self::Server(address) = self else {
+            return Vec::new();
+        };
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) if port.parse::<u16>().is_ok() => (host, port),
+            _ => (address.as_str(), "25565"),
+        };
+        vec!["--server".to_string(), host.to_string(), "--port".to_string(), port.to_string()]
+    }
+}
+
+/// The size and mode of the game window, for [`LaunchArguments::with_window`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowSettings {
+    pub width: u32,
+    pub height: u32,
+    /// Start in fullscreen; the size still applies once the player leaves it.
+    pub fullscreen: bool,
+}
+
+impl WindowSettings {
+    /// A windowed `width` x `height` window.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            fullscreen: false,
+        }
+    }
+
+    pub fn with_fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// Fails unless both dimensions are positive.
+    pub fn validate(&self) -> Result<()> {
+        if self.width == 0 || self.height == 0 {
+            bail!("Window size must be positive, got {}x{}", self.width, self.height);
+        }
+        Ok(())
+    }
+}
+
+/// The fully substituted command line for one launch, minus the `java` executable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchCommand {
+    pub jvm_args: Vec<String>,
+    pub main_class: String,
+    pub game_args: Vec<String>,
+}
+
+impl LaunchCommand {
+    /// All arguments in invocation order: JVM arguments, main class, game arguments.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::with_capacity(self.jvm_args.len() + 1 + self.game_args.len());
+        args.extend(self.jvm_args.iter().cloned());
+        args.push(self.main_class.clone());
+        args.extend(self.game_args.iter().cloned());
+        args
+    }
+
+    /// The full command line for running this with `java`, without starting anything,
+    /// e.g. to show it to the user or attach it to a bug report.
+    pub fn dry_run(&self, java: impl AsRef<OsStr>) -> DryRun {
+        let mut argv = vec![java.as_ref().to_string_lossy().into_owned()];
+        argv.extend(self.to_args());
+        let command_line = redact_args(&argv).iter().map(|arg| shell_quote(arg)).collect::<Vec<_>>().join(" ");
+        DryRun { argv, command_line }
+    }
+}
+
+/// A launch command rendered by [`LaunchCommand::dry_run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRun {
+    /// The executable followed by every argument, exactly as the game would be started,
+    /// access token included.
+    pub argv: Vec<String>,
+    /// `argv` as one line quoted for a POSIX shell, with the access token and legacy
+    /// session replaced by `<redacted>` so it can be shared safely.
+    pub command_line: String,
+}
+
+/// Stands in for secrets in [`redact_args`] output.
+const REDACTED: &str = "<redacted>";
+
+/// `args` with the value after `--accessToken` or `--session`, and legacy
+/// `token:<access token>:<uuid>` session arguments, replaced by a placeholder.
+pub(crate) fn redact_args<S: AsRef<str>>(args: impl IntoIterator<Item = S>) -> Vec<String> {
+    let mut redact_next = false;
+    args.into_iter()
+        .map(|arg| {
+            let arg = arg.as_ref();
+            let secret = redact_next || arg.starts_with("token:");
+            redact_next = matches!(arg, "--accessToken" | "--session");
+            if secret { REDACTED.to_string() } else { arg.to_string() }
+        })
+        .collect()
+}
+
+/// Quotes `arg` for a POSIX shell when it contains anything but safe characters.
+fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | ':' | '=' | ',' | '+' | '@' | '%'));
+    if safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// The rule feature that gates `--demo` in modern version JSONs.
+const DEMO_FEATURE: &str = "is_demo_user";
+
+/// Assembles the launch arguments for a version.
+///
+/// Handles both the modern `arguments.jvm`/`arguments.game` lists, evaluated against
+/// a [`RuleContext`], and the legacy `minecraftArguments` string. `${...}` placeholders
+/// are substituted; unknown placeholders are left untouched.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use minecraft_modloaders::launch::{LaunchArguments, LaunchAuth};
+/// use minecraft_modloaders::vanilla::VersionManifest;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let manifest = VersionManifest::fetch().await?;
+/// let version = manifest.fetch_detail("1.20.1").await?;
+/// let command = LaunchArguments::new(&version)
+///     .with_game_dir("instances/survival")
+///     .with_assets_dir("assets")
+///     .with_natives_dir("instances/survival/natives")
+///     .with_classpath(vec!["libraries/a.jar".into(), "versions/1.20.1/1.20.1.jar".into()])
+///     .with_auth(LaunchAuth::default())
+///     .build();
+/// println!("java {}", command.to_args().join(" "));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LaunchArguments<'a> {
+    version: &'a VersionDetail,
+    profile: Option<&'a LaunchProfile>,
+    classpath: Vec<PathBuf>,
+    natives_dir: PathBuf,
+    game_dir: PathBuf,
+    assets_dir: PathBuf,
+    libraries_dir: PathBuf,
+    auth: LaunchAuth,
+    window: Option<WindowSettings>,
+    quick_play: Option<QuickPlay>,
+    demo: Option<bool>,
+    jvm_settings: Option<JvmSettings>,
+    log4j: Log4jMitigation,
+    authlib_injector: Option<(PathBuf, String)>,
+    rule_context: RuleContext,
+    launcher_name: String,
+    launcher_version: String,
+}
+
+impl<'a> LaunchArguments<'a> {
+    pub fn new(version: &'a VersionDetail) -> Self {
+        Self {
+            version,
+            profile: None,
+            classpath: Vec::new(),
+            natives_dir: PathBuf::from("natives"),
+            game_dir: PathBuf::from("."),
+            assets_dir: PathBuf::from("assets"),
+            libraries_dir: PathBuf::from("libraries"),
+            auth: LaunchAuth::default(),
+            window: None,
+            quick_play: None,
+            demo: None,
+            jvm_settings: None,
+            log4j: Log4jMitigation::default(),
+            authlib_injector: None,
+            rule_context: RuleContext::default(),
+            launcher_name: env!("CARGO_PKG_NAME").to_string(),
+            launcher_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    /// Launch through a loader profile (e.g. Fabric) layered over this version: its main
+    /// class replaces the vanilla one and its arguments are appended.
+    pub fn with_loader_profile(mut self, profile: &'a LaunchProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Classpath entries, in order; the client jar is usually last.
+    pub fn with_classpath(mut self, classpath: Vec<PathBuf>) -> Self {
+        self.classpath = classpath;
+        self
+    }
+
+    pub fn with_natives_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.natives_dir = dir.into();
+        self
+    }
+
+    pub fn with_game_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.game_dir = dir.into();
+        self
+    }
+
+    pub fn with_assets_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.assets_dir = dir.into();
+        self
+    }
+
+    pub fn with_libraries_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.libraries_dir = dir.into();
+        self
+    }
+
+    pub fn with_auth(mut self, auth: LaunchAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Launch as the player in `session`, first refreshing its access token through
+    /// `auth` if it is expired or about to be, so the game does not start with a token
+    /// that lapses mid-session. See [`emerald_auth::Session::ensure_valid`].
+    #[cfg(feature = "auth")]
+    pub async fn with_session(mut self, session: &mut emerald_auth::Session, auth: &emerald_auth::MicrosoftAuth) -> Result<Self> {
+        let session = session.ensure_valid(auth).await?;
+        self.auth = LaunchAuth::from(session);
+        Ok(self)
+    }
+
+    /// Load authlib-injector from `jar` as a Java agent so the game authenticates
+    /// against the Yggdrasil server at `api_url` instead of Mojang's, adding
+    /// `-javaagent:<jar>=<api_url>`. Pair it with [`LaunchArguments::with_auth`] for an
+    /// account from that server.
+    pub fn with_authlib_injector(mut self, jar: impl Into<PathBuf>, api_url: impl Into<String>) -> Self {
+        self.authlib_injector = Some((jar.into(), api_url.into()));
+        self
+    }
+
+    /// Authenticate against `server`: custom servers go through authlib-injector loaded
+    /// from `jar`, see [`LaunchArguments::with_authlib_injector`]; Mojang needs nothing.
+    #[cfg(feature = "auth")]
+    pub fn with_auth_server(mut self, server: &emerald_auth::AuthServer, jar: impl Into<PathBuf>) -> Self {
+        self.authlib_injector = server.api_url().map(|api_url| (jar.into(), api_url.to_string()));
+        self
+    }
+
+    /// Set a custom window size, enabling the `has_custom_resolution` feature.
+    pub fn with_resolution(mut self, width: u32, height: u32) -> Self {
+        let fullscreen = self.window.is_some_and(|window| window.fullscreen);
+        self.window = Some(WindowSettings::new(width, height).with_fullscreen(fullscreen));
+        self
+    }
+
+    /// Set the window size and whether to start fullscreen. A valid size enables the
+    /// `has_custom_resolution` feature, so versions that declare it get `--width` and
+    /// `--height`; a size that fails [`WindowSettings::validate`] is left out and the
+    /// game picks its default. Fullscreen adds `--fullscreen`.
+    pub fn with_window(mut self, window: WindowSettings) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    /// Join a world, server or Realm as soon as the game is up. Versions that declare
+    /// the quick play features get `--quickPlay*`; older ones fall back to
+    /// `--server`/`--port`, which only covers servers.
+    pub fn with_quick_play(mut self, quick_play: QuickPlay) -> Self {
+        self.quick_play = Some(quick_play);
+        self
+    }
+
+    /// Launch in demo mode, for accounts that do not own the game, or explicitly not.
+    /// This turns the `is_demo_user` feature on or off whatever the rule context says,
+    /// so versions that declare it get `--demo` exactly when `demo` is set. Versions
+    /// with legacy arguments get `--demo` appended instead.
+    pub fn with_demo(mut self, demo: bool) -> Self {
+        self.demo = Some(demo);
+        self
+    }
+
+    /// Heap, collector and extra JVM flags, placed ahead of the version's JVM arguments.
+    /// Where both set a flag the JVM only honours once, such as `-Xmx`, these win. See
+    /// [`JvmSettings::validate_for_system`] for warning about an oversized heap.
+    pub fn with_jvm_settings(mut self, settings: JvmSettings) -> Self {
+        self.jvm_settings = Some(settings);
+        self
+    }
+
+    /// How to protect versions with a Log4Shell-vulnerable log4j (1.7 to 1.18.0). By
+    /// default [`NO_LOOKUPS_ARG`] is added to their JVM arguments, unless the version,
+    /// profile or [`JvmSettings`] already set it.
+    pub fn with_log4j_mitigation(mut self, mitigation: Log4jMitigation) -> Self {
+        self.log4j = mitigation;
+        self
+    }
+
+    /// Evaluate argument rules against this context instead of the current platform.
+    pub fn with_rule_context(mut self, ctx: RuleContext) -> Self {
+        self.rule_context = ctx;
+        self
+    }
+
+    /// Name and version reported through `${launcher_name}` and `${launcher_version}`.
+    pub fn with_launcher(mut self, name: impl Into<String>, version: impl Into<String>) -> Self {
+        self.launcher_name = name.into();
+        self.launcher_version = version.into();
+        self
+    }
+
+    /// Produce the substituted JVM arguments, main class and game arguments.
+    pub fn build(&self) -> LaunchCommand {
+        let ctx = self.effective_context();
+        let placeholders = self.placeholders(&ctx);
+        let substitute = |value: &str| substitute(value, &placeholders);
+
+        let arguments = self.version.launch_arguments();
+        let jvm_args = expand(&arguments.jvm, &ctx).map(substitute).collect();
+        let game_args = expand(&arguments.game, &ctx).map(substitute).collect();
+
+        let mut command = LaunchCommand {
+            jvm_args,
+            main_class: self.version.main_class.clone(),
+            game_args,
+        };
+        if let Some(profile) = self.profile {
+            command.main_class = profile.main_class.clone();
+            command.jvm_args.extend(profile.arguments.jvm.iter().map(|arg| substitute(arg)));
+            command.game_args.extend(profile.arguments.game.iter().map(|arg| substitute(arg)));
+        }
+        if let Some(quick_play) = self.quick_play.as_ref().filter(|_| !self.supports_quick_play()) {
+            command.game_args.extend(quick_play.legacy_args());
+        }
+        if self.window.is_some_and(|window| window.fullscreen) {
+            command.game_args.push("--fullscreen".to_string());
+        }
+        if self.demo == Some(true) && !self.declares_feature(DEMO_FEATURE) {
+            command.game_args.push("--demo".to_string());
+        }
+        let front: Vec<String> = match &self.jvm_settings {
+            Some(settings) => settings.args().iter().map(|arg| substitute(arg)).collect(),
+            None => Vec::new(),
+        };
+        let mut agents: Vec<String> = self.javaagent_arg().into_iter().collect();
+        agents.extend(self.log4j_args(front.iter().chain(&command.jvm_args)));
+        command.jvm_args.splice(0..0, agents);
+        if self.jvm_settings.is_some() {
+            command.jvm_args = merge_jvm_args(front, command.jvm_args);
+        }
+        command
+    }
+
+    fn effective_context(&self) -> RuleContext {
+        let mut ctx = self.rule_context.clone();
+        if self.resolution().is_some() {
+            ctx.features.insert("has_custom_resolution".to_string());
+        }
+        if let Some(quick_play) = self.quick_play.as_ref().filter(|_| self.supports_quick_play()) {
+            ctx.features.insert(quick_play.feature().0.to_string());
+        }
+        match self.demo {
+            Some(true) => {
+                ctx.features.insert(DEMO_FEATURE.to_string());
+            }
+            Some(false) => {
+                ctx.features.remove(DEMO_FEATURE);
+            }
+            None => {}
+        }
+        ctx
+    }
+
+    /// `-javaagent:<jar>=<api_url>` when authlib-injector is configured.
+    fn javaagent_arg(&self) -> Option<String> {
+        let (jar, api_url) = self.authlib_injector.as_ref()?;
+        Some(format!("-javaagent:{}={}", display(jar), api_url))
+    }
+
+    /// The Log4Shell mitigation flags for this version that `existing` does not already
+    /// set.
+    fn log4j_args<'s>(&self, existing: impl Iterator<Item = &'s String> + Clone) -> Vec<String> {
+        if self.log4j == Log4jMitigation::Disabled || !log4j::is_vulnerable(self.version) {
+            return Vec::new();
+        }
+        let sets = |key: &str| existing.clone().any(|arg| arg.starts_with(key));
+        let mut args = Vec::new();
+        if !sets("-Dlog4j2.formatMsgNoLookups=") {
+            args.push(NO_LOOKUPS_ARG.to_string());
+        }
+        match &self.log4j {
+            Log4jMitigation::ConfigFile(path) if !sets("-Dlog4j.configurationFile=") => {
+                args.push(format!("-Dlog4j.configurationFile={}", display(path)));
+            }
+            _ => {}
+        }
+        args
+    }
+
+    /// Whether the version's argument rules handle the configured quick play target.
+    fn supports_quick_play(&self) -> bool {
+        match &self.quick_play {
+            Some(quick_play) => self.declares_feature(quick_play.feature().0),
+            None => false,
+        }
+    }
+
+    /// The window settings, if their size is usable.
+    fn resolution(&self) -> Option<WindowSettings> {
+        self.window.filter(|window| window.validate().is_ok())
+    }
+
+    fn declares_feature(&self, feature: &str) -> bool {
+        self.version
+            .arguments
+            .as_ref()
+            .is_some_and(|arguments| arguments.declares_feature(feature))
+    }
+
+    fn placeholders(&self, ctx: &RuleContext) -> HashMap<&'static str, String> {
+        let separator = if ctx.os == "windows" { ";" } else { ":" };
+        let classpath = self.classpath.iter().map(|p| display(p)).collect::<Vec<_>>().join(separator);
+        let version_name = self.profile.map_or(self.version.id.as_str(), |p| p.id.as_str());
+        let assets_index = self.version.asset_index_id().to_string();
+        let version_type = self.version.release_type.to_string();
+
+        let mut values = HashMap::from([
+            ("auth_player_name", self.auth.player_name.clone()),
+            ("auth_uuid", self.auth.uuid.clone()),
+            ("auth_access_token", self.auth.access_token.clone()),
+            ("auth_session", format!("token:{}:{}", self.auth.access_token, self.auth.uuid)),
+            ("auth_xuid", self.auth.xuid.clone().unwrap_or_else(|| "0".to_string())),
+            ("clientid", self.auth.client_id.clone().unwrap_or_default()),
+            ("user_type", self.auth.user_type.clone()),
+            ("user_properties", "{}".to_string()),
+            ("version_name", version_name.to_string()),
+            ("version_type", version_type),
+            ("game_directory", display(&self.game_dir)),
+            ("assets_root", display(&self.assets_dir)),
+            ("game_assets", display(&self.assets_dir.join("virtual").join(&assets_index))),
+            ("assets_index_name", assets_index),
+            ("natives_directory", display(&self.natives_dir)),
+            ("library_directory", display(&self.libraries_dir)),
+            ("classpath_separator", separator.to_string()),
+            ("classpath", classpath),
+            ("launcher_name", self.launcher_name.clone()),
+            ("launcher_version", self.launcher_version.clone()),
+        ]);
+        if let Some(window) = self.resolution() {
+            values.insert("resolution_width", window.width.to_string());
+            values.insert("resolution_height", window.height.to_string());
+        }
+        if let Some(quick_play) = &self.quick_play {
+            let (_, placeholder, target) = quick_play.feature();
+            values.insert(placeholder, target.to_string());
+        }
+        values
+    }
+}
+
+fn expand<'a>(arguments: &'a [Argument], ctx: &'a RuleContext) -> impl Iterator<Item = &'a str> + 'a {
+    arguments.iter().flat_map(move |argument| argument.values(ctx))
+}
+
+fn display(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Replaces every `${name}` in `value` whose name is in `placeholders`.
+fn substitute(value: &str, placeholders: &HashMap<&'static str, String>) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                let name = &after[..end];
+                match placeholders.get(name) {
+                    Some(replacement) => result.push_str(replacement),
+                    None => result.push_str(&rest[start..start + 2 + end + 1]),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODERN_FIXTURE: &str = r#"{
+        "id": "1.20.4",
+        "type": "release",
+        "mainClass": "net.minecraft.client.main.Main",
+        "assets": "12",
+        "assetIndex": {"id": "12", "sha1": "aa", "size": 1, "totalSize": 1, "url": "https://example.com/12.json"},
+        "releaseTime": "2023-12-07T12:56:20+00:00",
+        "libraries": [],
+        "arguments": {
+            "game": [
+                "--username", "${auth_player_name}",
+                "--version", "${version_name}",
+                "--gameDir", "${game_directory}",
+                "--assetsDir", "${assets_root}",
+                "--assetIndex", "${assets_index_name}",
+                "--uuid", "${auth_uuid}",
+                "--accessToken", "${auth_access_token}",
+                "--userType", "${user_type}",
+                "--versionType", "${version_type}",
+                {"rules": [{"action": "allow", "features": {"is_demo_user": true}}], "value": "--demo"},
+                {"rules": [{"action": "allow", "features": {"has_custom_resolution": true}}],
+                 "value": ["--width", "${resolution_width}", "--height", "${resolution_height}"]},
+                {"rules": [{"action": "allow", "features": {"is_quick_play_singleplayer": true}}],
+                 "value": ["--quickPlaySingleplayer", "${quickPlaySingleplayer}"]},
+                {"rules": [{"action": "allow", "features": {"is_quick_play_multiplayer": true}}],
+                 "value": ["--quickPlayMultiplayer", "${quickPlayMultiplayer}"]},
+                {"rules": [{"action": "allow", "features": {"is_quick_play_realms": true}}],
+                 "value": ["--quickPlayRealms", "${quickPlayRealms}"]}
+            ],
+            "jvm": [
+                {"rules": [{"action": "allow", "os": {"name": "osx"}}], "value": ["-XstartOnFirstThread"]},
+                {"rules": [{"action": "allow", "os": {"name": "windows"}}],
+                 "value": "-XX:HeapDumpPath=MojangTricksIntelDriversForPerformance_javaw.exe_minecraft.exe.heapdump"},
+                "-Djava.library.path=${natives_directory}",
+                "-Dminecraft.launcher.brand=${launcher_name}",
+                "-cp", "${classpath}"
+            ]
+        }
+    }"#;
+
+    const LEGACY_FIXTURE: &str = r#"{
+        "id": "1.8.9",
+        "type": "release",
+        "mainClass": "net.minecraft.client.main.Main",
+        "assets": "1.8",
+        "releaseTime": "2015-12-03T09:24:39+00:00",
+        "libraries": [],
+        "minecraftArguments": "--username ${auth_player_name} --version ${version_name} --gameDir ${game_directory} --assetsDir ${assets_root} --assetIndex ${assets_index_name} --uuid ${auth_uuid} --accessToken ${auth_access_token} --userProperties ${user_properties} --userType ${user_type}"
+    }"#;
+
+    fn auth() -> LaunchAuth {
+        LaunchAuth {
+            player_name: "Steve".to_string(),
+            uuid: "069a79f444e94726a5befca90e38aaf5".to_string(),
+            access_token: "token".to_string(),
+            user_type: "msa".to_string(),
+            xuid: None,
+            client_id: None,
+        }
+    }
+
+    fn arg_after<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+        args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(String::as_str)
+    }
+
+    #[test]
+    fn test_modern_arguments_substitution() {
+        let version: VersionDetail = serde_json::from_str(MODERN_FIXTURE).unwrap();
+        let command = LaunchArguments::new(&version)
+            .with_game_dir("/game")
+            .with_assets_dir("/game/assets")
+            .with_natives_dir("/game/natives")
+            .with_classpath(vec!["/libs/a.jar".into(), "/libs/b.jar".into()])
+            .with_auth(auth())
+            .with_launcher("lodestone", "1.0.0")
+            .with_rule_context(RuleContext::new("linux", "x86_64"))
+            .build();
+
+        assert_eq!(command.main_class, "net.minecraft.client.main.Main");
+        assert_eq!(arg_after(&command.game_args, "--username"), Some("Steve"));
+        assert_eq!(arg_after(&command.game_args, "--version"), Some("1.20.4"));
+        assert_eq!(arg_after(&command.game_args, "--gameDir"), Some("/game"));
+        assert_eq!(arg_after(&command.game_args, "--assetIndex"), Some("12"));
+        assert_eq!(arg_after(&command.game_args, "--userType"), Some("msa"));
+        assert_eq!(arg_after(&command.game_args, "--versionType"), Some("release"));
+        assert_eq!(arg_after(&command.jvm_args, "-cp"), Some("/libs/a.jar:/libs/b.jar"));
+        assert!(command.jvm_args.contains(&"-Djava.library.path=/game/natives".to_string()));
+        assert!(command.jvm_args.contains(&"-Dminecraft.launcher.brand=lodestone".to_string()));
+
+        let args = command.to_args();
+        let main = args.iter().position(|a| a == "net.minecraft.client.main.Main").unwrap();
+        assert_eq!(main, command.jvm_args.len());
+    }
+
+    #[test]
+    fn test_modern_arguments_rule_filtering() {
+        let version: VersionDetail = serde_json::from_str(MODERN_FIXTURE).unwrap();
+
+        let linux = LaunchArguments::new(&version)
+            .with_rule_context(RuleContext::new("linux", "x86_64"))
+            .build();
+        assert!(!linux.jvm_args.contains(&"-XstartOnFirstThread".to_string()));
+        assert!(!linux.jvm_args.iter().any(|a| a.starts_with("-XX:HeapDumpPath")));
+        assert!(!linux.game_args.contains(&"--demo".to_string()));
+        assert!(!linux.game_args.contains(&"--width".to_string()));
+
+        let osx = LaunchArguments::new(&version)
+            .with_rule_context(RuleContext::new("osx", "x86_64").with_feature("is_demo_user"))
+            .with_resolution(1280, 720)
+            .build();
+        assert_eq!(osx.jvm_args[0], "-XstartOnFirstThread");
+        assert!(osx.game_args.contains(&"--demo".to_string()));
+        assert_eq!(arg_after(&osx.game_args, "--width"), Some("1280"));
+        assert_eq!(arg_after(&osx.game_args, "--height"), Some("720"));
+
+        let windows = LaunchArguments::new(&version)
+            .with_classpath(vec!["a.jar".into(), "b.jar".into()])
+            .with_rule_context(RuleContext::new("windows", "x86_64"))
+            .build();
+        assert!(windows.jvm_args[0].starts_with("-XX:HeapDumpPath"));
+        assert_eq!(arg_after(&windows.jvm_args, "-cp"), Some("a.jar;b.jar"));
+    }
+
+    #[test]
+    fn test_legacy_minecraft_arguments() {
+        let version: VersionDetail = serde_json::from_str(LEGACY_FIXTURE).unwrap();
+        let command = LaunchArguments::new(&version)
+            .with_natives_dir("/game/natives")
+            .with_classpath(vec!["/libs/a.jar".into()])
+            .with_auth(auth())
+            .with_rule_context(RuleContext::new("linux", "x86_64"))
+            .build();
+
+        assert_eq!(
+            command.jvm_args,
+            vec![NO_LOOKUPS_ARG, "-Djava.library.path=/game/natives", "-cp", "/libs/a.jar"]
+        );
+        assert_eq!(arg_after(&command.game_args, "--username"), Some("Steve"));
+        assert_eq!(arg_after(&command.game_args, "--assetIndex"), Some("1.8"));
+        assert_eq!(arg_after(&command.game_args, "--userProperties"), Some("{}"));
+    }
+
+    #[test]
+    fn test_dry_run_redacts_token_in_command_line() {
+        let version: VersionDetail = serde_json::from_str(MODERN_FIXTURE).unwrap();
+        let auth = LaunchAuth {
+            access_token: "eyJhbGciOi.secret".to_string(),
+            ..auth()
+        };
+        let command = LaunchArguments::new(&version)
+            .with_game_dir("/home/steve/My Pack")
+            .with_auth(auth)
+            .with_rule_context(RuleContext::new("linux", "x86_64"))
+            .build();
+
+        let dry_run = command.dry_run("/opt/java/bin/java");
+        assert_eq!(dry_run.argv[0], "/opt/java/bin/java");
+        assert_eq!(dry_run.argv[1..], command.to_args()[..]);
+        assert_eq!(arg_after(&dry_run.argv, "--accessToken"), Some("eyJhbGciOi.secret"));
+
+        assert!(!dry_run.command_line.contains("eyJhbGciOi.secret"), "{}", dry_run.command_line);
+        assert!(dry_run.command_line.starts_with("/opt/java/bin/java "));
+        assert!(dry_run.command_line.contains("--accessToken '<redacted>'"));
+        assert!(dry_run.command_line.contains("--gameDir '/home/steve/My Pack'"));
+        assert!(dry_run.command_line.contains("--username Steve"));
+    }
+
+    #[test]
+    fn test_redact_args_and_shell_quote() {
+        let args = ["--session", "abc", "token:abc:069a", "--username", "Steve"];
+        assert_eq!(redact_args(args), vec![REDACTED, REDACTED, REDACTED, "--username", "Steve"]);
+
+        assert_eq!(shell_quote("-Xmx2G"), "-Xmx2G");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("${classpath}"), "'${classpath}'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn test_demo_mode() {
+        let version: VersionDetail = serde_json::from_str(MODERN_FIXTURE).unwrap();
+        let linux = RuleContext::new("linux", "x86_64");
+        let demo_args = |arguments: LaunchArguments| arguments.build().game_args.iter().filter(|a| *a == "--demo").count();
+
+        assert_eq!(demo_args(LaunchArguments::new(&version).with_rule_context(linux.clone())), 0);
+        assert_eq!(
+            demo_args(LaunchArguments::new(&version).with_rule_context(linux.clone()).with_demo(false)),
+            0
+        );
+        assert_eq!(
+            demo_args(LaunchArguments::new(&version).with_rule_context(linux.clone()).with_demo(true)),
+            1
+        );
+        assert_eq!(
+            demo_args(
+                LaunchArguments::new(&version)
+                    .with_rule_context(linux.clone().with_feature("is_demo_user"))
+                    .with_demo(false)
+            ),
+            0
+        );
+
+        let legacy: VersionDetail = serde_json::from_str(LEGACY_FIXTURE).unwrap();
+        assert_eq!(demo_args(LaunchArguments::new(&legacy).with_rule_context(linux.clone())), 0);
+        assert_eq!(demo_args(LaunchArguments::new(&legacy).with_rule_context(linux).with_demo(true)), 1);
+    }
+
+    #[test]
+    fn test_window_settings() {
+        let version: VersionDetail = serde_json::from_str(MODERN_FIXTURE).unwrap();
+        let linux = RuleContext::new("linux", "x86_64");
+
+        let windowed = LaunchArguments::new(&version)
+            .with_rule_context(linux.clone())
+            .with_window(WindowSettings::new(1920, 1080))
+            .build();
+        assert_eq!(arg_after(&windowed.game_args, "--width"), Some("1920"));
+        assert_eq!(arg_after(&windowed.game_args, "--height"), Some("1080"));
+        assert!(!windowed.game_args.contains(&"--fullscreen".to_string()));
+
+        let invalid = LaunchArguments::new(&version)
+            .with_rule_context(linux.clone())
+            .with_window(WindowSettings::new(0, 1080).with_fullscreen(true))
+            .build();
+        assert!(!invalid.game_args.contains(&"--width".to_string()));
+        assert!(!invalid.game_args.iter().any(|a| a.contains("${resolution")));
+        assert!(invalid.game_args.contains(&"--fullscreen".to_string()));
+        assert!(WindowSettings::new(0, 1080).validate().is_err());
+        assert!(WindowSettings::new(854, 480).validate().is_ok());
+
+        // Legacy versions do not declare `has_custom_resolution`.
+        let legacy: VersionDetail = serde_json::from_str(LEGACY_FIXTURE).unwrap();
+        let legacy = LaunchArguments::new(&legacy)
+            .with_rule_context(linux)
+            .with_window(WindowSettings::new(1920, 1080))
+            .build();
+        assert!(!legacy.game_args.contains(&"--width".to_string()));
+    }
+
+    #[test]
+    fn test_loader_profile_overrides_main_class() {
+        let version: VersionDetail = serde_json::from_str(MODERN_FIXTURE).unwrap();
+        let profile: LaunchProfile = serde_json::from_str(
+            r#"{
+                "id": "fabric-loader-0.15.11-1.20.4",
+                "inheritsFrom": "1.20.4",
+                "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+                "libraries": [],
+                "arguments": {"game": [], "jvm": ["-DFabricMcEmu= net.minecraft.client.main.Main "]}
+            }"#,
+        )
+        .unwrap();
+
+        let command = LaunchArguments::new(&version).with_loader_profile(&profile).build();
+        assert_eq!(command.main_class, "net.fabricmc.loader.impl.launch.knot.KnotClient");
+        assert_eq!(command.jvm_args.last().unwrap(), "-DFabricMcEmu= net.minecraft.client.main.Main ");
+        assert_eq!(arg_after(&command.game_args, "--version"), Some("fabric-loader-0.15.11-1.20.4"));
+    }
+
+    #[test]
+    fn test_quick_play_modern_version() {
+        let version: VersionDetail = serde_json::from_str(MODERN_FIXTURE).unwrap();
+        let world = LaunchArguments::new(&version)
+            .with_quick_play(QuickPlay::World("New World".into()))
+            .build();
+        assert_eq!(arg_after(&world.game_args, "--quickPlaySingleplayer"), Some("New World"));
+        assert!(!world.game_args.contains(&"--quickPlayMultiplayer".to_string()));
+
+        let server = LaunchArguments::new(&version)
+            .with_quick_play(QuickPlay::Server("play.example.com:25570".into()))
+            .build();
+        assert_eq!(arg_after(&server.game_args, "--quickPlayMultiplayer"), Some("play.example.com:25570"));
+        assert!(!server.game_args.contains(&"--server".to_string()));
+
+        let none = LaunchArguments::new(&version).build();
+        assert!(!none.game_args.iter().any(|a| a.starts_with("--quickPlay")));
+    }
+
+    #[test]
+    fn test_quick_play_legacy_version() {
+        let version: VersionDetail = serde_json::from_str(LEGACY_FIXTURE).unwrap();
+        let server = LaunchArguments::new(&version)
+            .with_quick_play(QuickPlay::Server("play.example.com:25570".into()))
+            .build();
+        assert_eq!(arg_after(&server.game_args, "--server"), Some("play.example.com"));
+        assert_eq!(arg_after(&server.game_args, "--port"), Some("25570"));
+        assert!(!server.game_args.iter().any(|a| a.starts_with("--quickPlay")));
+
+        let default_port = LaunchArguments::new(&version)
+            .with_quick_play(QuickPlay::Server("localhost".into()))
+            .build();
+        assert_eq!(arg_after(&default_port.game_args, "--port"), Some("25565"));
+
+        let world = LaunchArguments::new(&version)
+            .with_quick_play(QuickPlay::World("New World".into()))
+            .build();
+        assert!(!world.game_args.contains(&"New World".to_string()));
+    }
+
+    #[test]
+    fn test_jvm_settings_come_first() {
+        let version: VersionDetail = serde_json::from_str(MODERN_FIXTURE).unwrap();
+        let command = LaunchArguments::new(&version)
+            .with_jvm_settings(JvmSettings::new().with_min_memory(1024).with_max_memory(4096))
+            .with_rule_context(RuleContext::new("linux", "x86_64"))
+            .build();
+
+        assert_eq!(&command.jvm_args[..2], ["-Xms1024M", "-Xmx4096M"]);
+        assert_eq!(command.jvm_args.iter().filter(|a| a.starts_with("-Xmx")).count(), 1);
+        assert!(command.jvm_args.contains(&"-cp".to_string()));
+    }
+
+    #[test]
+    fn test_jvm_settings_user_xmx_wins() {
+        let version: VersionDetail = serde_json::from_str(MODERN_FIXTURE).unwrap();
+        let profile: LaunchProfile = serde_json::from_str(
+            r#"{
+                "id": "fabric-loader-0.15.11-1.20.4",
+                "inheritsFrom": "1.20.4",
+                "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+                "libraries": [],
+                "arguments": {"game": [], "jvm": ["-Xmx1G"]}
+            }"#,
+        )
+        .unwrap();
+
+        let command = LaunchArguments::new(&version)
+            .with_loader_profile(&profile)
+            .with_jvm_settings(JvmSettings::new().with_extra_args(["-Xmx8G"]))
+            .build();
+        let heap: Vec<&String> = command.jvm_args.iter().filter(|a| a.starts_with("-Xmx")).collect();
+        assert_eq!(heap, ["-Xmx8G"]);
+    }
+
+    fn release(id: &str, release_time: &str) -> VersionDetail {
+        serde_json::from_str(&format!(
+            r#"{{"id": "{id}", "type": "release", "mainClass": "net.minecraft.client.main.Main",
+                "releaseTime": "{release_time}", "libraries": [],
+                "arguments": {{"game": [], "jvm": ["-cp", "${{classpath}}"]}}}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_log4j_flag_for_vulnerable_versions_only() {
+        let vulnerable = release("1.16.5", "2021-01-14T16:05:32+00:00");
+        let command = LaunchArguments::new(&vulnerable).build();
+        assert_eq!(command.jvm_args.iter().filter(|a| *a == NO_LOOKUPS_ARG).count(), 1);
+
+        let fixed = release("1.19", "2022-06-07T09:42:18+00:00");
+        let command = LaunchArguments::new(&fixed).build();
+        assert!(!command.jvm_args.iter().any(|a| a.contains("log4j")), "{:?}", command.jvm_args);
+    }
+
+    #[test]
+    fn test_log4j_mitigation_overrides() {
+        let version = release("1.16.5", "2021-01-14T16:05:32+00:00");
+
+        let disabled = LaunchArguments::new(&version).with_log4j_mitigation(Log4jMitigation::Disabled).build();
+        assert!(!disabled.jvm_args.iter().any(|a| a.contains("log4j")));
+
+        let config = LaunchArguments::new(&version)
+            .with_log4j_mitigation(Log4jMitigation::ConfigFile("/game/log4j2_112-116.xml".into()))
+            .with_jvm_settings(JvmSettings::new().with_max_memory(4096))
+            .build();
+        assert_eq!(
+            &config.jvm_args[..3],
+            ["-Xmx4096M", NO_LOOKUPS_ARG, "-Dlog4j.configurationFile=/game/log4j2_112-116.xml"]
+        );
+
+        let user_set = LaunchArguments::new(&version)
+            .with_jvm_settings(JvmSettings::new().with_extra_args(["-Dlog4j2.formatMsgNoLookups=false"]))
+            .build();
+        let flags: Vec<&String> = user_set.jvm_args.iter().filter(|a| a.contains("formatMsgNoLookups")).collect();
+        assert_eq!(flags, ["-Dlog4j2.formatMsgNoLookups=false"]);
+    }
+
+    #[test]
+    fn test_authlib_injector_javaagent() {
+        let version: VersionDetail = serde_json::from_str(MODERN_FIXTURE).unwrap();
+        let command = LaunchArguments::new(&version)
+            .with_authlib_injector("/launcher/authlib-injector-1.2.5.jar", "https://authserver.ely.by/api/authlib-injector")
+            .with_jvm_settings(JvmSettings::new().with_max_memory(4096))
+            .with_rule_context(RuleContext::new("linux", "x86_64"))
+            .build();
+        assert_eq!(
+            &command.jvm_args[..2],
+            [
+                "-Xmx4096M",
+                "-javaagent:/launcher/authlib-injector-1.2.5.jar=https://authserver.ely.by/api/authlib-injector"
+            ]
+        );
+
+        let plain = LaunchArguments::new(&version).build();
+        assert!(!plain.jvm_args.iter().any(|a| a.starts_with("-javaagent:")));
+    }
+
+    #[cfg(feature = "auth")]
+    #[test]
+    fn test_auth_server_mojang_adds_no_agent() {
+        let version: VersionDetail = serde_json::from_str(MODERN_FIXTURE).unwrap();
+        let mojang = LaunchArguments::new(&version)
+            .with_auth_server(&emerald_auth::AuthServer::Mojang, "authlib-injector.jar")
+            .build();
+        assert!(!mojang.jvm_args.iter().any(|a| a.starts_with("-javaagent:")));
+
+        let custom = LaunchArguments::new(&version)
+            .with_auth_server(
+                &emerald_auth::AuthServer::custom("https://example.com/api/yggdrasil/"),
+                "authlib-injector.jar",
+            )
+            .build();
+        assert_eq!(custom.jvm_args[0], "-javaagent:authlib-injector.jar=https://example.com/api/yggdrasil");
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    async fn test_with_session_fills_auth() {
+        let version: VersionDetail = serde_json::from_str(MODERN_FIXTURE).unwrap();
+        let mut session = emerald_auth::Session {
+            username: "Steve".to_string(),
+            uuid: "069a79f444e94726a5befca90e38aaf5".to_string(),
+            access_token: "session-token".to_string().into(),
+            expires_at: Some(std::time::SystemTime::now() + std::time::Duration::from_secs(3600)),
+            refresh_token: None,
+            kind: emerald_auth::AccountKind::Microsoft,
+        };
+        // Fresh, so nothing is sent to the unreachable default endpoints.
+        let auth = emerald_auth::MicrosoftAuth::new("client-id");
+
+        let command = LaunchArguments::new(&version)
+            .with_session(&mut session, &auth)
+            .await
+            .unwrap()
+            .with_rule_context(RuleContext::new("linux", "x86_64"))
+            .build();
+        assert_eq!(arg_after(&command.game_args, "--username"), Some("Steve"));
+        assert_eq!(arg_after(&command.game_args, "--accessToken"), Some("session-token"));
+        assert_eq!(arg_after(&command.game_args, "--userType"), Some("msa"));
+
+        session.expires_at = Some(std::time::SystemTime::now());
+        assert!(LaunchArguments::new(&version).with_session(&mut session, &auth).await.is_err());
+    }
+
+    #[test]
+    fn test_substitute_leaves_unknown_placeholders() {
+        let placeholders = HashMap::from([("known", "value".to_string())]);
+        assert_eq!(substitute("${known}-${unknown}", &placeholders), "value-${unknown}");
+        assert_eq!(substitute("prefix ${unterminated", &placeholders), "prefix ${unterminated");
+        assert_eq!(substitute("plain", &placeholders), "plain");
+    }
+}
@@ -0,0 +1,436 @@
+//! Turning a crashed game's output into something a user can act on.
+//!
+//! [`diagnose`] scans the captured stdout/stderr and the newest report under
+//! `crash-reports/` for the failures players hit most often, such as a missing
+//! dependency or too little memory, and explains how to fix them.
+
+use anyhow::Result;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::LogLine;
+
+/// Directory under the game directory where Minecraft writes crash reports.
+pub const CRASH_REPORTS_DIR: &str = "crash-reports";
+
+/// The kind of failure a crash was attributed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CrashCategory {
+    /// A mod needs another mod, or another version of it, that is not installed.
+    MissingDependency,
+    /// Two installed mods cannot run together, or a mod's mixins failed to apply.
+    IncompatibleMod,
+    /// The game ran out of heap memory.
+    OutOfMemory,
+    /// The game or a mod was compiled for a newer Java than the one running it.
+    WrongJavaVersion,
+    /// The same mod is installed more than once.
+    DuplicateMod,
+    /// None of the known patterns matched.
+    Unknown,
+}
+
+impl fmt::Display for CrashCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::MissingDependency => "Missing dependency",
+            Self::IncompatibleMod => "Incompatible mod",
+            Self::OutOfMemory => "Out of memory",
+            Self::WrongJavaVersion => "Wrong Java version",
+            Self::DuplicateMod => "Duplicate mod",
+            Self::Unknown => "Unknown",
+        })
+    }
+}
+
+/// Why the game most likely crashed and what to do about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashDiagnosis {
+    pub category: CrashCategory,
+    /// The relevant log line, or a summary of it.
+    pub message: String,
+    pub suggested_fix: String,
+    /// The crash report the diagnosis was taken from, or that was written alongside the crash.
+    pub crash_report: Option<PathBuf>,
+}
+
+/// A crash report from `crash-reports/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashReport {
+    pub path: PathBuf,
+    /// The `Description:` line, e.g. `Initializing game`.
+    pub description: Option<String>,
+    /// The first line of the stack trace, e.g. `java.lang.NullPointerException: ...`.
+    pub exception: Option<String>,
+    pub content: String,
+}
+
+impl CrashReport {
+    /// Reads and parses the crash report at `path`.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        Ok(Self::parse(path, content))
+    }
+
+    fn parse(path: &Path, content: String) -> Self {
+        let mut lines = content.lines().map(str::trim);
+        let description = lines
+            .by_ref()
+            .find_map(|line| line.strip_prefix("Description:"))
+            .map(|description| description.trim().to_string());
+        // The stack trace starts after the blank line that follows the description
+        let exception = match description {
+            Some(_) => lines.find(|line| !line.is_empty()).map(str::to_string),
+            None => None,
+        };
+        Self {
+            path: path.to_path_buf(),
+            description,
+            exception,
+            content,
+        }
+    }
+}
+
+/// The most recently written report in `<game_dir>/crash-reports/`, if any.
+pub fn latest_crash_report(game_dir: impl AsRef<Path>) -> Result<Option<CrashReport>> {
+    let entries = match std::fs::read_dir(game_dir.as_ref().join(CRASH_REPORTS_DIR)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut latest: Option<(std::time::SystemTime, PathBuf)> = None;
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().is_none_or(|extension| extension != "txt") {
+            continue;
+        }
+        let modified = entry.metadata()?.modified()?;
+        // Report names embed their timestamp, so they break ties between equal mtimes
+        if latest.as_ref().is_none_or(|(time, newest)| (modified, &path) > (*time, newest)) {
+            latest = Some((modified, path));
+        }
+    }
+    latest.map(|(_, path)| CrashReport::read(path)).transpose()
+}
+
+/// Diagnoses a crash from the game's captured output and the newest crash report
+/// in `game_dir`.
+///
+/// The output is checked first, then the report. When neither matches a known
+/// pattern the diagnosis is [`CrashCategory::Unknown`], carrying the report's
+/// exception or description if there is one.
+pub fn diagnose(output: &str, game_dir: impl AsRef<Path>) -> Result<CrashDiagnosis> {
+    let report = latest_crash_report(game_dir)?;
+    let report_path = report.as_ref().map(|report| report.path.clone());
+
+    let found = diagnose_output(output).or_else(|| report.as_ref().and_then(|report| diagnose_output(&report.content)));
+    if let Some(diagnosis) = found {
+        return Ok(CrashDiagnosis {
+            crash_report: report_path,
+            ..diagnosis
+        });
+    }
+
+    let message = report
+        .as_ref()
+        .and_then(|report| report.exception.clone().or_else(|| report.description.clone()))
+        .unwrap_or_else(|| "The game exited unexpectedly".to_string());
+    let suggested_fix = match &report_path {
+        Some(path) => format!("See the crash report at {} for details", path.display()),
+        None => "Check the game log for the error that preceded the crash".to_string(),
+    };
+    Ok(CrashDiagnosis {
+        category: CrashCategory::Unknown,
+        message,
+        suggested_fix,
+        crash_report: report_path,
+    })
+}
+
+/// Like [`diagnose`], for output collected from [`GameProcess`](super::GameProcess).
+pub fn diagnose_lines(lines: &[LogLine], game_dir: impl AsRef<Path>) -> Result<CrashDiagnosis> {
+    let output = lines.iter().map(|line| line.line.as_str()).collect::<Vec<_>>().join("\n");
+    diagnose(&output, game_dir)
+}
+
+/// Matches `output` against the known crash patterns, most specific first.
+pub fn diagnose_output(output: &str) -> Option<CrashDiagnosis> {
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+    let checks: [fn(&[&str]) -> Option<CrashDiagnosis>; 5] = [wrong_java_version, out_of_memory, duplicate_mod, missing_dependency, incompatible_mod];
+    checks.iter().find_map(|check| check(&lines))
+}
+
+fn diagnosis(category: CrashCategory, message: &str, suggested_fix: impl Into<String>) -> CrashDiagnosis {
+    CrashDiagnosis {
+        category,
+        message: message.trim_start_matches(['-', '\t', ' ']).to_string(),
+        suggested_fix: suggested_fix.into(),
+        crash_report: None,
+    }
+}
+
+fn find_line<'a>(lines: &[&'a str], needles: &[&str]) -> Option<&'a str> {
+    lines.iter().copied().find(|line| needles.iter().any(|needle| line.contains(needle)))
+}
+
+fn wrong_java_version(lines: &[&str]) -> Option<CrashDiagnosis> {
+    let line = find_line(
+        lines,
+        &["UnsupportedClassVersionError", "Unsupported class file major version", "requires Java"],
+    )?;
+    let fix = match required_java(line) {
+        Some(java) => format!("Run the game with Java {} or newer", java),
+        None => "Run the game with the Java version this Minecraft version requires".to_string(),
+    };
+    Some(diagnosis(CrashCategory::WrongJavaVersion, line, fix))
+}
+
+/// The Java release needed for the class file version mentioned in `line`.
+///
+/// Class file version 52 is Java 8, and every Java release since adds one.
+fn required_java(line: &str) -> Option<u32> {
+    let class_version = if let Some((_, rest)) = line.split_once("class file version ") {
+        rest.split('.').next()?.parse::<u32>().ok()?
+    } else if let Some((_, rest)) = line.split_once("major version ") {
+        rest.split(|c: char| !c.is_ascii_digit()).next()?.parse::<u32>().ok()?
+    } else {
+        return None;
+    };
+    class_version.checked_sub(44).filter(|java| *java >= 8)
+}
+
+fn out_of_memory(lines: &[&str]) -> Option<CrashDiagnosis> {
+    let line = find_line(lines, &["java.lang.OutOfMemoryError"])?;
+    Some(diagnosis(
+        CrashCategory::OutOfMemory,
+        line,
+        "Allocate more memory to the instance (raise -Xmx), or remove memory-heavy mods and resource packs",
+    ))
+}
+
+fn duplicate_mod(lines: &[&str]) -> Option<CrashDiagnosis> {
+    let line = lines.iter().copied().find(|line| {
+        let lower = line.to_ascii_lowercase();
+        lower.contains("duplicate mod") || lower.contains("has multiple versions") || lower.contains("found duplicate")
+    })?;
+    Some(diagnosis(
+        CrashCategory::DuplicateMod,
+        line,
+        "Remove all but one copy of the duplicated mod from the mods folder",
+    ))
+}
+
+fn missing_dependency(lines: &[&str]) -> Option<CrashDiagnosis> {
+    // Fabric and Quilt: "- Mod 'Mod Menu' (modmenu) 9.0.0 requires any version of fabric-api, which is missing!"
+    if let Some(line) = find_line(lines, &["which is missing", "but only the wrong version is present"]) {
+        return Some(diagnosis(
+            CrashCategory::MissingDependency,
+            line,
+            "Install the required mod, in the version it asks for, into the mods folder",
+        ));
+    }
+    // Forge and NeoForge list one "Mod ID: ..., Actual version: '[MISSING]'" line per dependency
+    if let Some(line) = find_line(lines, &["[MISSING]", "Missing or unsupported mandatory dependencies"]) {
+        // Point at the first dependency rather than the header above the list
+        let line = if line.contains("Missing or unsupported") {
+            find_line(lines, &["Mod ID:"]).unwrap_or(line)
+        } else {
+            line
+        };
+        return Some(diagnosis(
+            CrashCategory::MissingDependency,
+            line,
+            "Install the mod named in the error, in the expected version range, into the mods folder",
+        ));
+    }
+    None
+}
+
+fn incompatible_mod(lines: &[&str]) -> Option<CrashDiagnosis> {
+    if let Some(line) = find_line(lines, &["is incompatible with", "Incompatible mods found", "Incompatible mod set"]) {
+        // Prefer the line naming the mods over the generic header
+        let line = find_line(lines, &["is incompatible with"]).unwrap_or(line);
+        return Some(diagnosis(
+            CrashCategory::IncompatibleMod,
+            line,
+            "Remove one of the conflicting mods, or update both to versions that support each other",
+        ));
+    }
+    let line = find_line(
+        lines,
+        &[
+            "Mixin apply failed",
+            "Mixin apply for mod",
+            "MixinApplyError",
+            "InvalidInjectionException",
+        ],
+    )?;
+    Some(diagnosis(
+        CrashCategory::IncompatibleMod,
+        line,
+        "A mod failed to patch the game; update or remove the mod named in the error, it may not support this game or loader version",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launch::LogStream;
+
+    #[test]
+    fn test_fabric_missing_dependency() {
+        let output = r#"[12:00:00] [main/INFO]: Loading 42 mods
+[12:00:01] [main/ERROR]: Incompatible mod set!
+net.fabricmc.loader.impl.FormattedException: Mod resolution encountered an incompatible mod set!
+A potential solution has been determined:
+	 - Install fabric-api, any version.
+Unmet dependency listing:
+	 - Mod 'Mod Menu' (modmenu) 9.0.0 requires any version of fabric-api, which is missing!
+"#;
+        let diagnosis = diagnose_output(output).unwrap();
+        assert_eq!(diagnosis.category, CrashCategory::MissingDependency);
+        assert_eq!(
+            diagnosis.message,
+            "Mod 'Mod Menu' (modmenu) 9.0.0 requires any version of fabric-api, which is missing!"
+        );
+    }
+
+    #[test]
+    fn test_forge_missing_dependency() {
+        let output = r#"[main/ERROR] [net.minecraftforge.fml.loading.ModSorter/LOADING]: Missing or unsupported mandatory dependencies:
+	Mod ID: 'architectury', Requested by: 'rei', Expected range: '[9.1.12,)', Actual version: '[MISSING]'
+"#;
+        let diagnosis = diagnose_output(output).unwrap();
+        assert_eq!(diagnosis.category, CrashCategory::MissingDependency);
+        assert!(diagnosis.message.starts_with("Mod ID: 'architectury'"));
+    }
+
+    #[test]
+    fn test_incompatible_mod() {
+        let output = r#"net.fabricmc.loader.impl.FormattedException: Mod resolution encountered an incompatible mod set!
+A potential solution has been determined:
+	 - Remove mod 'OptiFabric' (optifabric).
+Unmet dependency listing:
+	 - Mod 'Sodium' (sodium) 0.5.8 is incompatible with any version of mod 'OptiFabric' (optifabric), but a matching version is present: 1.14.3!
+"#;
+        let diagnosis = diagnose_output(output).unwrap();
+        assert_eq!(diagnosis.category, CrashCategory::IncompatibleMod);
+        assert!(diagnosis.message.starts_with("Mod 'Sodium' (sodium)"));
+
+        let mixin = "org.spongepowered.asm.mixin.transformer.throwables.MixinTransformerError: An unexpected critical error was encountered\nCaused by: org.spongepowered.asm.mixin.throwables.MixinApplyError: Mixin [sodium.mixins.json:core.MixinWindow] from phase [DEFAULT] in config [sodium.mixins.json] FAILED during APPLY";
+        assert_eq!(diagnose_output(mixin).unwrap().category, CrashCategory::IncompatibleMod);
+    }
+
+    #[test]
+    fn test_out_of_memory() {
+        let output = "[Render thread/ERROR]: Reported exception thrown!\njava.lang.OutOfMemoryError: Java heap space\n\tat java.base/java.util.Arrays.copyOf(Arrays.java:3537)";
+        let diagnosis = diagnose_output(output).unwrap();
+        assert_eq!(diagnosis.category, CrashCategory::OutOfMemory);
+        assert_eq!(diagnosis.message, "java.lang.OutOfMemoryError: Java heap space");
+        assert!(diagnosis.suggested_fix.contains("-Xmx"));
+    }
+
+    #[test]
+    fn test_wrong_java_version() {
+        let output = "Error: LinkageError occurred while loading main class net.minecraft.client.main.Main\n\tjava.lang.UnsupportedClassVersionError: net/minecraft/client/main/Main has been compiled by a more recent version of the Java Runtime (class file version 65.0), this version of the Java Runtime only recognizes class file versions up to 52.0";
+        let diagnosis = diagnose_output(output).unwrap();
+        assert_eq!(diagnosis.category, CrashCategory::WrongJavaVersion);
+        assert_eq!(diagnosis.suggested_fix, "Run the game with Java 21 or newer");
+
+        let forge = "java.lang.IllegalArgumentException: Unsupported class file major version 61";
+        assert_eq!(diagnose_output(forge).unwrap().suggested_fix, "Run the game with Java 17 or newer");
+    }
+
+    #[test]
+    fn test_duplicate_mods() {
+        let fabric = "net.fabricmc.loader.impl.FormattedException: Mod resolution encountered an incompatible mod set!\n\t - Mod ID `sodium` has multiple versions: 0.5.3, 0.5.8";
+        assert_eq!(diagnose_output(fabric).unwrap().category, CrashCategory::DuplicateMod);
+
+        let forge = "[main/ERROR] [net.minecraftforge.fml.loading.UniqueModListBuilder/]: Found duplicate mods:\n\tMod ID: 'jei' from mod files: jei-15.2.0.jar, jei-15.3.0.jar";
+        let diagnosis = diagnose_output(forge).unwrap();
+        assert_eq!(diagnosis.category, CrashCategory::DuplicateMod);
+    }
+
+    #[test]
+    fn test_unrecognized_output() {
+        assert!(diagnose_output("[main/INFO]: Stopping!\n").is_none());
+        assert!(diagnose_output("").is_none());
+    }
+
+    const CRASH_REPORT: &str = "---- Minecraft Crash Report ----
+// Who set us up the TNT?
+
+Time: 2024-01-01 12:00:00
+Description: Initializing game
+
+java.lang.RuntimeException: Could not execute entrypoint stage 'client' due to errors, provided by 'brokenmod'!
+\tat net.fabricmc.loader.impl.FabricLoaderImpl.lambda$invokeEntrypoints$2(FabricLoaderImpl.java:388)
+
+-- Head --
+Thread: Render thread
+";
+
+    #[test]
+    fn test_parse_crash_report() {
+        let report = CrashReport::parse(Path::new("crash.txt"), CRASH_REPORT.to_string());
+        assert_eq!(report.description.as_deref(), Some("Initializing game"));
+        assert_eq!(
+            report.exception.as_deref(),
+            Some("java.lang.RuntimeException: Could not execute entrypoint stage 'client' due to errors, provided by 'brokenmod'!")
+        );
+    }
+
+    #[test]
+    fn test_diagnose_uses_latest_crash_report() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(latest_crash_report(dir.path()).unwrap().is_none());
+
+        let reports = dir.path().join(CRASH_REPORTS_DIR);
+        std::fs::create_dir_all(&reports).unwrap();
+        std::fs::write(
+            reports.join("crash-2024-01-01_11.00.00-client.txt"),
+            "Description: Older\n\njava.lang.OutOfMemoryError: Java heap space\n",
+        )
+        .unwrap();
+        std::fs::write(reports.join("crash-2024-01-01_12.00.00-client.txt"), CRASH_REPORT).unwrap();
+        let newest = reports.join("crash-2024-01-01_12.00.00-client.txt");
+        let older = std::fs::File::options()
+            .write(true)
+            .open(reports.join("crash-2024-01-01_11.00.00-client.txt"))
+            .unwrap();
+        older
+            .set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(3600))
+            .unwrap();
+
+        assert_eq!(latest_crash_report(dir.path()).unwrap().unwrap().path, newest);
+
+        // Nothing in the output or the newest report matches, so the report's exception is shown
+        let diagnosis = diagnose("[main/INFO]: Loading", dir.path()).unwrap();
+        assert_eq!(diagnosis.category, CrashCategory::Unknown);
+        assert!(diagnosis.message.starts_with("java.lang.RuntimeException"));
+        assert_eq!(diagnosis.crash_report, Some(newest.clone()));
+
+        // A match in the output wins, and still points at the report
+        let diagnosis = diagnose("java.lang.OutOfMemoryError: Java heap space", dir.path()).unwrap();
+        assert_eq!(diagnosis.category, CrashCategory::OutOfMemory);
+        assert_eq!(diagnosis.crash_report, Some(newest));
+    }
+
+    #[test]
+    fn test_diagnose_without_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let diagnosis = diagnose("", dir.path()).unwrap();
+        assert_eq!(diagnosis.category, CrashCategory::Unknown);
+        assert_eq!(diagnosis.crash_report, None);
+
+        let lines = vec![LogLine {
+            stream: LogStream::Stderr,
+            line: "java.lang.OutOfMemoryError: Java heap space".to_string(),
+        }];
+        assert_eq!(diagnose_lines(&lines, dir.path()).unwrap().category, CrashCategory::OutOfMemory);
+    }
+}
@@ -0,0 +1,268 @@
+//! Heap size, garbage collector and extra flags for the game's JVM.
+//!
+//! [`JvmSettings`] turns these into arguments that [`super::LaunchArguments`] places
+//! ahead of the version's own JVM arguments. Flags that the JVM only honours once, such
+//! as `-Xmx`, are de-duplicated with the first occurrence winning, so a `-Xmx` in
+//! [`JvmSettings::extra_args`] overrides [`JvmSettings::max_memory_mb`] and both
+//! override anything a version or loader profile supplies.
+
+use std::fmt;
+
+/// Maximum heap used when none is configured, matching the official launcher.
+pub const DEFAULT_MAX_MEMORY_MB: u32 = 2048;
+
+/// A garbage collector and the tuning flags that go with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcPreset {
+    /// G1 with the settings the official launcher passes.
+    G1,
+    /// ZGC, low pause times at the cost of some throughput; needs Java 15+.
+    Z,
+    Shenandoah,
+    Parallel,
+}
+
+impl GcPreset {
+    pub fn args(&self) -> &'static [&'static str] {
+        match self {
+            Self::G1 => &[
+                "-XX:+UseG1GC",
+                "-XX:+UnlockExperimentalVMOptions",
+                "-XX:G1NewSizePercent=20",
+                "-XX:G1ReservePercent=20",
+                "-XX:MaxGCPauseMillis=50",
+                "-XX:G1HeapRegionSize=32M",
+            ],
+            Self::Z => &["-XX:+UseZGC"],
+            Self::Shenandoah => &["-XX:+UseShenandoahGC"],
+            Self::Parallel => &["-XX:+UseParallelGC"],
+        }
+    }
+}
+
+impl fmt::Display for GcPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::G1 => "G1",
+            Self::Z => "ZGC",
+            Self::Shenandoah => "Shenandoah",
+            Self::Parallel => "Parallel",
+        })
+    }
+}
+
+/// JVM options for a launch.
+///
+/// # Example
+///
+/// ```rust
+/// use minecraft_modloaders::launch::{GcPreset, JvmSettings};
+///
+/// let settings = JvmSettings::new().with_min_memory(1024).with_max_memory(4096).with_gc(GcPreset::G1);
+/// assert_eq!(&settings.args()[..2], ["-Xms1024M", "-Xmx4096M"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JvmSettings {
+    /// Initial heap (`-Xms`) in MB.
+    pub min_memory_mb: Option<u32>,
+    /// Maximum heap (`-Xmx`) in MB.
+    pub max_memory_mb: Option<u32>,
+    pub gc: Option<GcPreset>,
+    /// Passed through as given; these take precedence over the fields above.
+    pub extra_args: Vec<String>,
+}
+
+impl Default for JvmSettings {
+    fn default() -> Self {
+        Self {
+            min_memory_mb: None,
+            max_memory_mb: Some(DEFAULT_MAX_MEMORY_MB),
+            gc: None,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+impl JvmSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_min_memory(mut self, mb: u32) -> Self {
+        self.min_memory_mb = Some(mb);
+        self
+    }
+
+    pub fn with_max_memory(mut self, mb: u32) -> Self {
+        self.max_memory_mb = Some(mb);
+        self
+    }
+
+    pub fn with_gc(mut self, gc: GcPreset) -> Self {
+        self.gc = Some(gc);
+        self
+    }
+
+    pub fn with_extra_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extra_args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// The arguments for these settings, with duplicates of single-valued flags removed.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        args.extend(self.min_memory_mb.map(|mb| format!("-Xms{mb}M")));
+        args.extend(self.max_memory_mb.map(|mb| format!("-Xmx{mb}M")));
+        // Extra arguments go first for de-duplication so that they win.
+        merge_jvm_args(
+            self.extra_args.clone(),
+            args.into_iter()
+                .chain(self.gc.iter().flat_map(|gc| gc.args().iter().map(|arg| arg.to_string()))),
+        )
+    }
+
+    /// The maximum heap the JVM will actually get, in MB, taking `-Xmx` in
+    /// [`Self::extra_args`] into account.
+    pub fn effective_max_memory_mb(&self) -> Option<u64> {
+        self.extra_args
+            .iter()
+            .find_map(|arg| arg.strip_prefix("-Xmx").and_then(parse_memory_mb))
+            .or(self.max_memory_mb.map(u64::from))
+    }
+
+    /// The initial heap in MB, taking `-Xms` in [`Self::extra_args`] into account.
+    pub fn effective_min_memory_mb(&self) -> Option<u64> {
+        self.extra_args
+            .iter()
+            .find_map(|arg| arg.strip_prefix("-Xms").and_then(parse_memory_mb))
+            .or(self.min_memory_mb.map(u64::from))
+    }
+
+    /// Problems with these settings on a machine with `total_memory_mb` of RAM. An empty
+    /// list means the settings look sane; the game can still be launched either way.
+    pub fn validate(&self, total_memory_mb: u64) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let max = self.effective_max_memory_mb();
+        if let Some(max) = max.filter(|max| *max > total_memory_mb) {
+            warnings.push(format!(
+                "Maximum heap of {max} MB exceeds the {total_memory_mb} MB of memory in this system"
+            ));
+        }
+        if let Some((min, max)) = self.effective_min_memory_mb().zip(max).filter(|(min, max)| min > max) {
+            warnings.push(format!(
+                "Initial heap of {min} MB is larger than the maximum heap of {max} MB; the JVM will refuse to start"
+            ));
+        }
+        warnings
+    }
+
+    /// [`Self::validate`] against the memory of the machine this runs on.
+    pub fn validate_for_system(&self) -> Vec<String> {
+        self.validate(system_memory_mb())
+    }
+}
+
+/// Total physical memory of this machine in MB.
+pub fn system_memory_mb() -> u64 {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    system.total_memory() / (1024 * 1024)
+}
+
+/// Appends `rest` to `front`, dropping any flag the JVM only honours once (heap and
+/// stack sizes, the collector choice) when `front` or an earlier `rest` entry already
+/// set it.
+pub(crate) fn merge_jvm_args(front: Vec<String>, rest: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut seen: Vec<&'static str> = Vec::new();
+    let mut merged = Vec::with_capacity(front.len());
+    for arg in front.into_iter().chain(rest) {
+        if let Some(key) = single_valued_key(&arg) {
+            if seen.contains(&key) {
+                continue;
+            }
+            seen.push(key);
+        }
+        merged.push(arg);
+    }
+    merged
+}
+
+fn single_valued_key(arg: &str) -> Option<&'static str> {
+    ["-Xmx", "-Xms", "-Xss"].into_iter().find(|prefix| arg.starts_with(prefix)).or_else(|| {
+        let collector = arg.strip_prefix("-XX:+Use")?;
+        collector.ends_with("GC").then_some("gc")
+    })
+}
+
+/// Parses a JVM memory size such as `4G`, `2048M` or `524288k` into MB.
+fn parse_memory_mb(value: &str) -> Option<u64> {
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (digits, unit) = value.split_at(split);
+    let amount: u64 = digits.parse().ok()?;
+    match unit {
+        "" => Some(amount / (1024 * 1024)),
+        "k" | "K" => Some(amount / 1024),
+        "m" | "M" => Some(amount),
+        "g" | "G" => Some(amount * 1024),
+        "t" | "T" => Some(amount * 1024 * 1024),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_settings_args() {
+        assert_eq!(JvmSettings::new().args(), vec!["-Xmx2048M"]);
+    }
+
+    #[test]
+    fn test_memory_and_gc_args() {
+        let args = JvmSettings::new().with_min_memory(512).with_max_memory(4096).with_gc(GcPreset::Z).args();
+        assert_eq!(args, vec!["-Xms512M", "-Xmx4096M", "-XX:+UseZGC"]);
+    }
+
+    #[test]
+    fn test_extra_xmx_overrides_default() {
+        let settings = JvmSettings::new().with_extra_args(["-Xmx6G", "-Dfoo=bar"]);
+        let args = settings.args();
+        assert_eq!(args, vec!["-Xmx6G", "-Dfoo=bar"]);
+        assert_eq!(settings.effective_max_memory_mb(), Some(6144));
+    }
+
+    #[test]
+    fn test_merge_keeps_first_single_valued_flag() {
+        let merged = merge_jvm_args(
+            vec!["-Xmx4G".to_string(), "-XX:+UseZGC".to_string()],
+            ["-Xmx1G", "-XX:+UseG1GC", "-XX:+UseStringDeduplication", "-cp", "a.jar"].map(String::from),
+        );
+        assert_eq!(merged, vec!["-Xmx4G", "-XX:+UseZGC", "-XX:+UseStringDeduplication", "-cp", "a.jar"]);
+    }
+
+    #[test]
+    fn test_validate_memory() {
+        assert!(JvmSettings::new().with_max_memory(4096).validate(16384).is_empty());
+
+        let warnings = JvmSettings::new().with_max_memory(32768).validate(16384);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("exceeds"));
+
+        let warnings = JvmSettings::new().with_min_memory(4096).with_extra_args(["-Xmx2G"]).validate(16384);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Initial heap"));
+    }
+
+    #[test]
+    fn test_parse_memory_mb() {
+        assert_eq!(parse_memory_mb("4G"), Some(4096));
+        assert_eq!(parse_memory_mb("512m"), Some(512));
+        assert_eq!(parse_memory_mb("1048576k"), Some(1024));
+        assert_eq!(parse_memory_mb("lots"), None);
+    }
+}
@@ -0,0 +1,162 @@
+//! Log4Shell (CVE-2021-44228) mitigation for the versions that shipped a vulnerable
+//! log4j, 1.7 through 1.18.0.
+//!
+//! Following Mojang's advisory, 1.17 and 1.18.0 only need [`NO_LOOKUPS_ARG`], while
+//! older versions bundle a log4j too old to honour it and need a patched logging
+//! config instead, which [`download_patched_config`] fetches. [`LaunchArguments`]
+//! adds the flag on its own; see [`Log4jMitigation`] for adding the config as well or
+//! for turning this off.
+//!
+//! [`LaunchArguments`]: super::LaunchArguments
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+
+use crate::download::{DownloadJob, Downloader};
+use crate::vanilla::VersionDetail;
+
+/// Turns off message lookups, the feature Log4Shell abuses, in log4j 2.10 and later.
+pub const NO_LOOKUPS_ARG: &str = "-Dlog4j2.formatMsgNoLookups=true";
+
+/// How [`LaunchArguments`](super::LaunchArguments) protects versions with a vulnerable
+/// log4j. Versions outside the affected range get nothing either way.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Log4jMitigation {
+    /// Add [`NO_LOOKUPS_ARG`].
+    #[default]
+    Auto,
+    /// Add [`NO_LOOKUPS_ARG`] and point `-Dlog4j.configurationFile` at this file,
+    /// usually the one [`download_patched_config`] saved.
+    ConfigFile(PathBuf),
+    /// Add nothing, for users who handle it in their own JVM arguments.
+    Disabled,
+}
+
+/// One of Mojang's patched logging configs for pre-1.17 versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatchedConfig {
+    pub file_name: &'static str,
+    pub url: &'static str,
+    pub sha1: &'static str,
+}
+
+/// For 1.7 to 1.11.2.
+pub const CONFIG_1_7: PatchedConfig = PatchedConfig {
+    file_name: "log4j2_17-111.xml",
+    url: "https://launcher.mojang.com/v1/objects/4bb89a97a66f350bc9f73b3ca8509632682aea2e/log4j2_17-111.xml",
+    sha1: "4bb89a97a66f350bc9f73b3ca8509632682aea2e",
+};
+
+/// For 1.12 to 1.16.5.
+pub const CONFIG_1_12: PatchedConfig = PatchedConfig {
+    file_name: "log4j2_112-116.xml",
+    url: "https://launcher.mojang.com/v1/objects/02937d122c86ce73319ef9975b58896fc1b491d1/log4j2_112-116.xml",
+    sha1: "02937d122c86ce73319ef9975b58896fc1b491d1",
+};
+
+/// Snapshots have no release number, so they are placed by release date: the first
+/// 1.7 snapshot, 1.12, 1.17 and the fixed 1.18.1.
+const LOG4J_SINCE: &str = "2013-09-05T00:00:00+00:00";
+const CONFIG_1_12_SINCE: &str = "2017-06-02T00:00:00+00:00";
+const NO_LOOKUPS_SINCE: &str = "2021-06-08T00:00:00+00:00";
+const FIXED_SINCE: &str = "2021-12-10T00:00:00+00:00";
+
+/// Whether `version` shipped a log4j affected by Log4Shell.
+pub fn is_vulnerable(version: &VersionDetail) -> bool {
+    match release_number(&version.id) {
+        Some((minor, patch)) => (7..=17).contains(&minor) || (minor, patch) == (18, 0),
+        None => released_between(&version.release_time, LOG4J_SINCE, FIXED_SINCE),
+    }
+}
+
+/// The patched logging config `version` needs, or `None` if it is not vulnerable or
+/// [`NO_LOOKUPS_ARG`] alone covers it.
+pub fn patched_config(version: &VersionDetail) -> Option<PatchedConfig> {
+    if !is_vulnerable(version) {
+        return None;
+    }
+    match release_number(&version.id) {
+        Some((minor, _)) if minor < 12 => Some(CONFIG_1_7),
+        Some((minor, _)) if minor < 17 => Some(CONFIG_1_12),
+        Some(_) => None,
+        None if released_between(&version.release_time, LOG4J_SINCE, CONFIG_1_12_SINCE) => Some(CONFIG_1_7),
+        None if released_between(&version.release_time, CONFIG_1_12_SINCE, NO_LOOKUPS_SINCE) => Some(CONFIG_1_12),
+        None => None,
+    }
+}
+
+/// Download the patched logging config for `version` into `dir`, returning its path
+/// for [`Log4jMitigation::ConfigFile`]. A copy already there with the right hash is
+/// kept. Returns `None` when the version needs no config.
+pub async fn download_patched_config(version: &VersionDetail, dir: impl AsRef<Path>) -> Result<Option<PathBuf>> {
+    let Some(config) = patched_config(version) else {
+        return Ok(None);
+    };
+    let dest = dir.as_ref().join(config.file_name);
+    let summary = Downloader::new()
+        .download(vec![DownloadJob::new(config.url, &dest).with_sha1(config.sha1)])
+        .await;
+    if let Some(failure) = summary.failed.into_iter().next() {
+        bail!("Failed to download {}: {}", config.file_name, failure.error);
+    }
+    Ok(Some(dest))
+}
+
+/// `(minor, patch)` of a `1.x[.y]` release, pre-release or release candidate id.
+fn release_number(id: &str) -> Option<(u32, u32)> {
+    let number = id.split(['-', ' ']).next()?;
+    let mut parts = number.strip_prefix("1.")?.split('.');
+    let minor = parts.next()?.parse().ok()?;
+    let patch = match parts.next() {
+        Some(patch) => patch.parse().ok()?,
+        None => 0,
+    };
+    Some((minor, patch))
+}
+
+fn released_between(release_time: &DateTime<Utc>, since: &str, until: &str) -> bool {
+    let parse = |date: &str| DateTime::parse_from_rfc3339(date).expect("valid constant date");
+    *release_time >= parse(since) && *release_time < parse(until)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(id: &str, release_time: &str) -> VersionDetail {
+        serde_json::from_str(&format!(
+            r#"{{"id": "{id}", "type": "release", "mainClass": "net.minecraft.client.main.Main",
+                "releaseTime": "{release_time}", "libraries": []}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_vulnerable_range() {
+        let cases = [
+            ("1.6.4", "2013-09-19T15:52:37+00:00", false),
+            ("1.7.10", "2014-05-14T17:29:23+00:00", true),
+            ("1.16.5", "2021-01-14T16:05:32+00:00", true),
+            ("1.18", "2021-11-30T09:16:29+00:00", true),
+            ("1.18-pre1", "2021-11-12T15:18:13+00:00", true),
+            ("1.18.1", "2021-12-10T08:23:00+00:00", false),
+            ("1.19", "2022-06-07T09:42:18+00:00", false),
+            ("21w44a", "2021-11-03T14:38:22+00:00", true),
+            ("22w03a", "2022-01-19T16:24:21+00:00", false),
+        ];
+        for (id, release_time, vulnerable) in cases {
+            assert_eq!(is_vulnerable(&version(id, release_time)), vulnerable, "{id}");
+        }
+    }
+
+    #[test]
+    fn test_patched_config_by_version() {
+        assert_eq!(patched_config(&version("1.8.9", "2015-12-03T09:24:39+00:00")), Some(CONFIG_1_7));
+        assert_eq!(patched_config(&version("1.16.5", "2021-01-14T16:05:32+00:00")), Some(CONFIG_1_12));
+        assert_eq!(patched_config(&version("20w45a", "2020-11-04T14:24:31+00:00")), Some(CONFIG_1_12));
+        assert_eq!(patched_config(&version("1.17.1", "2021-07-06T12:01:34+00:00")), None);
+        assert_eq!(patched_config(&version("1.20.4", "2023-12-07T12:56:20+00:00")), None);
+    }
+}
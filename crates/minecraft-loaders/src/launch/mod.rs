@@ -0,0 +1,11 @@
+pub mod arguments;
+pub mod crash;
+pub mod jvm;
+pub mod log4j;
+pub mod process;
+
+pub use arguments::{DryRun, LaunchArguments, LaunchAuth, LaunchCommand, QuickPlay, WindowSettings};
+pub use crash::{CrashCategory, CrashDiagnosis, CrashReport};
+pub use jvm::{GcPreset, JvmSettings};
+pub use log4j::Log4jMitigation;
+pub use process::{ExitKind, GameExit, GameProcess, LogLine, LogStream};
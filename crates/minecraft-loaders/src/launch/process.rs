@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+
+use super::LaunchCommand;
+#[cfg(feature = "tracing")]
+use super::arguments::redact_args;
+
+/// Which output stream a [`LogLine`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// A single line of game output, without the trailing newline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    pub stream: LogStream,
+    pub line: String,
+}
+
+/// How the game process ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitKind {
+    /// Exit code 0.
+    Clean,
+    /// Non-zero exit code, or terminated by a signal we did not send. Minecraft
+    /// exits with code 1 after writing a crash report.
+    Crashed,
+    /// Stopped through [`GameProcess::kill`].
+    Killed,
+}
+
+/// Exit status of a finished game process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameExit {
+    /// Exit code, or `None` when the process was terminated by a signal.
+    pub code: Option<i32>,
+    pub kind: ExitKind,
+}
+
+impl GameExit {
+    pub fn is_crash(&self) -> bool {
+        self.kind == ExitKind::Crashed
+    }
+}
+
+/// A running Minecraft (or any Java) process.
+///
+/// stdout and stderr are read line by line in background tasks and forwarded through
+/// the channel returned by [`GameProcess::take_lines`]. The channel closes once both
+/// streams reach EOF.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use minecraft_modloaders::launch::GameProcess;
+/// # async fn example(command: minecraft_modloaders::launch::LaunchCommand) -> anyhow::Result<()> {
+/// let mut process = GameProcess::spawn("java", &command, "instances/survival")?;
+/// let mut lines = process.take_lines().unwrap();
+/// tokio::spawn(async move {
+///     while let Some(line) = lines.recv().await {
+///         println!("{}", line.line);
+///     }
+/// });
+/// let exit = process.wait().await?;
+/// if exit.is_crash() {
+///     eprintln!("Minecraft crashed with exit code {:?}", exit.code);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct GameProcess {
+    child: Child,
+    pid: Option<u32>,
+    lines: Option<mpsc::UnboundedReceiver<LogLine>>,
+    killed: bool,
+}
+
+impl GameProcess {
+    /// Runs `java` with the arguments of `command` inside `working_dir`.
+    pub fn spawn(java: impl AsRef<OsStr>, command: &LaunchCommand, working_dir: impl AsRef<Path>) -> Result<Self> {
+        let mut cmd = Command::new(java);
+        cmd.args(command.to_args()).current_dir(working_dir);
+        Self::spawn_command(cmd)
+    }
+
+    /// Spawns an already configured command, capturing its stdout and stderr.
+    pub fn spawn_command(mut cmd: Command) -> Result<Self> {
+        cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            program = ?cmd.as_std().get_program(),
+            args = ?redact_args(cmd.as_std().get_args().map(OsStr::to_string_lossy)),
+            "spawning game process"
+        );
+        let mut child = cmd.spawn().with_context(|| format!("Failed to start {:?}", cmd.as_std().get_program()))?;
+        #[cfg(feature = "tracing")]
+        tracing::info!(pid = child.id(), "game process started");
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        if let Some(stdout) = child.stdout.take() {
+            forward_lines(stdout, LogStream::Stdout, tx.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forward_lines(stderr, LogStream::Stderr, tx);
+        }
+
+        Ok(Self {
+            pid: child.id(),
+            child,
+            lines: Some(rx),
+            killed: false,
+        })
+    }
+
+    /// OS process id, if the process had not already exited when it was spawned.
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// Takes the receiver for captured output lines. Returns `None` after the first call.
+    pub fn take_lines(&mut self) -> Option<mpsc::UnboundedReceiver<LogLine>> {
+        self.lines.take()
+    }
+
+    /// Waits for the process to exit.
+    pub async fn wait(&mut self) -> Result<GameExit> {
+        let status = self.child.wait().await.context("Failed to wait for game process")?;
+        let kind = if self.killed {
+            ExitKind::Killed
+        } else if status.success() {
+            ExitKind::Clean
+        } else {
+            ExitKind::Crashed
+        };
+        Ok(GameExit { code: status.code(), kind })
+    }
+
+    /// Forcefully stops the process and waits for it to exit.
+    pub async fn kill(&mut self) -> Result<()> {
+        self.killed = true;
+        self.child.kill().await.context("Failed to kill game process")
+    }
+}
+
+/// Sends each line `reader` produces until it reaches the end. Invalid UTF-8 is
+/// replaced rather than ending the stream, and the output keeps being read after the
+/// receiver is gone so the game never blocks on a full pipe.
+fn forward_lines<R>(reader: R, stream: LogStream, tx: mpsc::UnboundedSender<LogLine>)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(reader);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf).await {
+                Ok(0) => break,
+                Ok(_) => {
+                    let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+                    let line = line.strip_suffix(b"\r").unwrap_or(line);
+                    let line = String::from_utf8_lossy(line).into_owned();
+                    let _ = tx.send(LogLine { stream, line });
+                }
+                Err(_error) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(?stream, error = %_error, "failed to read game output");
+                    break;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(script);
+        cmd
+    }
+
+    async fn collect(mut lines: mpsc::UnboundedReceiver<LogLine>) -> Vec<LogLine> {
+        let mut collected = Vec::new();
+        while let Some(line) = lines.recv().await {
+            collected.push(line);
+        }
+        collected
+    }
+
+    #[tokio::test]
+    async fn test_captures_output_and_clean_exit() {
+        let mut process = GameProcess::spawn_command(shell("echo hello; echo world; echo oops >&2")).unwrap();
+        assert!(process.pid().is_some());
+        let lines = process.take_lines().unwrap();
+        assert!(process.take_lines().is_none());
+
+        let exit = process.wait().await.unwrap();
+        assert_eq!(
+            exit,
+            GameExit {
+                code: Some(0),
+                kind: ExitKind::Clean
+            }
+        );
+
+        let lines = collect(lines).await;
+        let stdout: Vec<&str> = lines.iter().filter(|l| l.stream == LogStream::Stdout).map(|l| l.line.as_str()).collect();
+        assert_eq!(stdout, vec!["hello", "world"]);
+        assert!(lines.contains(&LogLine {
+            stream: LogStream::Stderr,
+            line: "oops".to_string()
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_utf8_does_not_end_output() {
+        let mut process = GameProcess::spawn_command(shell(r"printf 'caf\351\r\nafter\nno newline'")).unwrap();
+        let lines = process.take_lines().unwrap();
+        process.wait().await.unwrap();
+
+        let lines: Vec<String> = collect(lines).await.into_iter().map(|l| l.line).collect();
+        assert_eq!(lines, vec!["caf\u{FFFD}", "after", "no newline"]);
+    }
+
+    #[tokio::test]
+    async fn test_exit_code_one_is_a_crash() {
+        let mut process = GameProcess::spawn_command(shell("echo '---- Minecraft Crash Report ----'; exit 1")).unwrap();
+        let exit = process.wait().await.unwrap();
+        assert_eq!(exit.code, Some(1));
+        assert!(exit.is_crash());
+    }
+
+    #[tokio::test]
+    async fn test_kill() {
+        let mut process = GameProcess::spawn_command(shell("sleep 30")).unwrap();
+        process.kill().await.unwrap();
+        let exit = process.wait().await.unwrap();
+        assert_eq!(exit.kind, ExitKind::Killed);
+        assert!(!exit.is_crash());
+    }
+
+    #[tokio::test]
+    async fn test_spawn_missing_program_fails() {
+        assert!(GameProcess::spawn_command(Command::new("definitely-not-a-real-java-binary")).is_err());
+    }
+}
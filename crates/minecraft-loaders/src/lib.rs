@@ -1,7 +1,18 @@
 pub mod fabric;
+pub mod cache;
+pub mod download;
 pub mod forge;
+pub mod http;
+pub mod install;
+pub mod java;
+pub mod launch;
+pub mod maven;
 pub mod neoforge;
+pub mod platform;
 pub mod quilt;
+pub mod server;
+pub mod vanilla;
+pub mod versions;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -46,6 +57,101 @@ pub enum ModLoaderError {
     IncorrectJavaVersion(String, String),
 }
 
+/// Why fetching loader metadata failed.
+///
+/// Lets callers tell being offline apart from a missing resource or an API returning
+/// something unexpected. Every variant keeps the underlying error as its
+/// [`source`](std::error::Error::source) for logging, and the type converts into
+/// `anyhow::Error`, so `?` keeps working in `anyhow` code.
+#[derive(Debug, thiserror::Error)]
+pub enum LoaderError {
+    /// The request could not be sent or the connection failed, e.g. when offline.
+    #[error("Network error requesting {url}")]
+    Network {
+        url: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[error("Request to {url} timed out")]
+    Timeout {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// The server answered, but not with the JSON the loader API documents.
+    #[error("Unexpected response from {url}")]
+    Deserialize {
+        url: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Not found: {url}")]
+    NotFound {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// The server still answered `429 Too Many Requests` after every retry.
+    #[error("Rate limited by {url}")]
+    RateLimited {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// [Offline mode](http::is_offline) is on and nothing is cached for the request.
+    #[error("Offline mode: {url} is not cached")]
+    Offline { url: String },
+}
+
+impl LoaderError {
+    /// The URL of the request that failed.
+    pub fn url(&self) -> &str {
+        match self {
+            Self::Network { url, .. }
+            | Self::Timeout { url, .. }
+            | Self::Deserialize { url, .. }
+            | Self::NotFound { url, .. }
+            | Self::RateLimited { url, .. }
+            | Self::Offline { url } => url,
+        }
+    }
+
+    /// Classifies an error returned by the [`http`] helpers for a request to `url`.
+    pub(crate) fn from_http(url: &str, err: anyhow::Error) -> Self {
+        let err = match err.downcast::<Self>() {
+            Ok(loader_error) => return loader_error,
+            Err(other) => other,
+        };
+        match err.downcast::<reqwest::Error>() {
+            Ok(source) => Self::from_reqwest(url, source),
+            Err(other) => Self::Network {
+                url: url.to_string(),
+                source: other.into(),
+            },
+        }
+    }
+
+    pub(crate) fn from_reqwest(url: &str, source: reqwest::Error) -> Self {
+        let url = url.to_string();
+        if source.is_timeout() {
+            return Self::Timeout { url, source };
+        }
+        match source.status() {
+            Some(reqwest::StatusCode::NOT_FOUND) => Self::NotFound { url, source },
+            Some(reqwest::StatusCode::TOO_MANY_REQUESTS) => Self::RateLimited { url, source },
+            _ => Self::Network {
+                url,
+                source: Box::new(source),
+            },
+        }
+    }
+}
+
 /// Trait for mod loader implementations (Fabric, Forge, etc.)
 ///
 /// This trait provides async methods for installing, downloading, and running
@@ -162,3 +268,69 @@ pub trait ModLoader: Send + Sync {
         java_path: &Path,
     ) -> Result<std::process::Command>;
 }
+
+/// A loader version as seen by launcher UI code, independent of the concrete loader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoaderVersionInfo {
+    /// The loader version string (e.g., "0.15.11" or "47.2.0")
+    pub version: String,
+    /// The Minecraft version this build targets, or `None` if it works with every
+    /// game version the loader supports (as with Fabric).
+    pub game_version: Option<String>,
+    /// Whether the loader marks this build as stable
+    pub stable: bool,
+}
+
+/// Trait for listing the versions a mod loader offers.
+///
+/// Unlike [`ModLoader`], which installs and runs a loader, this trait only answers
+/// "which builds exist", so a launcher can populate version pickers for every loader
+/// through one interface.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use minecraft_modloaders::fabric::FabricModLoader;
+/// use minecraft_modloaders::ModLoaderVersions;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let loaders: Vec<Box<dyn ModLoaderVersions>> = vec![Box::new(FabricModLoader::new())];
+///
+///     for loader in &loaders {
+///         let latest = loader.latest_stable_loader("1.20.1").await?;
+///         println!("{}: {:?}", loader.name(), latest.map(|v| v.version));
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[async_trait]
+pub trait ModLoaderVersions: Send + Sync {
+    /// Human-readable loader name (e.g., "Fabric")
+    fn name(&self) -> &'static str;
+
+    /// Fetches every available loader version, newest first.
+    async fn fetch_versions(&self) -> Result<Vec<LoaderVersionInfo>>;
+
+    /// Fetches the loader versions usable with a Minecraft version, newest first.
+    ///
+    /// Returns an empty list if the loader does not support `game` at all.
+    async fn loader_versions_for_game(&self, game: &str) -> Result<Vec<LoaderVersionInfo>> {
+        Ok(self
+            .fetch_versions()
+            .await?
+            .into_iter()
+            .filter(|v| v.game_version.as_deref().is_none_or(|g| g == game))
+            .collect())
+    }
+
+    /// Fetches the newest stable loader version for a Minecraft version.
+    async fn latest_stable_loader(&self, game: &str) -> Result<Option<LoaderVersionInfo>> {
+        Ok(self
+            .loader_versions_for_game(game)
+            .await?
+            .into_iter()
+            .find(|v| v.stable))
+    }
+}
@@ -0,0 +1,354 @@
+use anyhow::{Result, anyhow, bail};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::http;
+
+/// Mojang's library repository, which hosts every vanilla library.
+pub const MOJANG_REPOSITORY: &str = "https://libraries.minecraft.net/";
+pub const FORGE_REPOSITORY: &str = "https://maven.minecraftforge.net/";
+pub const NEOFORGE_REPOSITORY: &str = "https://maven.neoforged.net/releases/";
+pub const FABRIC_REPOSITORY: &str = "https://maven.fabricmc.net/";
+pub const QUILT_REPOSITORY: &str = "https://maven.quiltmc.org/repository/release/";
+/// Hosts Mixin and other SpongePowered libraries some loaders pull in.
+pub const SPONGE_REPOSITORY: &str = "https://repo.spongepowered.org/repository/maven-public/";
+pub const MAVEN_CENTRAL: &str = "https://repo1.maven.org/maven2/";
+
+/// A parsed maven coordinate, `group:artifact:version[:classifier][@extension]`.
+///
+/// Loader metadata and version JSON libraries name their artifacts this way; the
+/// coordinate maps onto the standard repository layout with [`MavenCoordinate::to_path`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MavenCoordinate {
+    pub group: String,
+    pub artifact: String,
+    pub version: String,
+    pub classifier: Option<String>,
+    /// The file extension, `jar` when absent.
+    pub extension: Option<String>,
+}
+
+impl MavenCoordinate {
+    pub fn parse(coordinate: &str) -> Result<Self> {
+        let (name, extension) = match coordinate.split_once('@') {
+            Some((name, extension)) => (name, Some(extension)),
+            None => (coordinate, None),
+        };
+        let parts: Vec<&str> = name.split(':').collect();
+        let (group, artifact, version, classifier) = match parts.as_slice() {
+            [group, artifact, version] => (*group, *artifact, *version, None),
+            [group, artifact, version, classifier] => (*group, *artifact, *version, Some(*classifier)),
+            _ => bail!(
+                "Invalid maven coordinate {:?}: expected group:artifact:version[:classifier][@extension]",
+                coordinate
+            ),
+        };
+        if [group, artifact, version].iter().any(|part| part.is_empty())
+            || classifier.is_some_and(str::is_empty)
+            || extension.is_some_and(str::is_empty)
+        {
+            bail!("Invalid maven coordinate {:?}: empty component", coordinate);
+        }
+
+        Ok(Self {
+            group: group.to_string(),
+            artifact: artifact.to_string(),
+            version: version.to_string(),
+            classifier: classifier.map(str::to_string),
+            extension: extension.map(str::to_string),
+        })
+    }
+
+    /// The file extension, defaulting to `jar`.
+    pub fn extension(&self) -> &str {
+        self.extension.as_deref().unwrap_or("jar")
+    }
+
+    /// The artifact's file name, `artifact-version[-classifier].ext`.
+    pub fn file_name(&self) -> String {
+        match &self.classifier {
+            Some(classifier) => format!("{}-{}-{}.{}", self.artifact, self.version, classifier, self.extension()),
+            None => format!("{}-{}.{}", self.artifact, self.version, self.extension()),
+        }
+    }
+
+    /// The path relative to a repository root or `libraries` directory,
+    /// `group/artifact/version/artifact-version[-classifier].ext` with the group's dots as slashes.
+    pub fn to_path(&self) -> String {
+        format!("{}/{}/{}/{}", self.group.replace('.', "/"), self.artifact, self.version, self.file_name())
+    }
+
+    /// The download URL of the artifact in the repository at `repo_base`.
+    pub fn to_url(&self, repo_base: &str) -> String {
+        format!("{}/{}", repo_base.trim_end_matches('/'), self.to_path())
+    }
+
+    /// The same coordinate with a different classifier, e.g. a `natives-linux` jar.
+    pub fn with_classifier(mut self, classifier: impl Into<String>) -> Self {
+        self.classifier = Some(classifier.into());
+        self
+    }
+}
+
+impl FromStr for MavenCoordinate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for MavenCoordinate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.group, self.artifact, self.version)?;
+        if let Some(classifier) = &self.classifier {
+            write!(f, ":{}", classifier)?;
+        }
+        if let Some(extension) = &self.extension {
+            write!(f, "@{}", extension)?;
+        }
+        Ok(())
+    }
+}
+
+/// An ordered list of maven repositories to look up libraries that name only a
+/// coordinate, with no download URL of their own.
+///
+/// The default list holds the repositories Mojang and the supported loaders publish
+/// to, in that order. Custom repositories, such as a private mirror, can be added
+/// at either end.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use minecraft_modloaders::maven::{MavenCoordinate, RepositoryList};
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let repositories = RepositoryList::default().with_preferred_repository("https://mirror.example.com/maven/");
+/// let coordinate = MavenCoordinate::parse("org.spongepowered:mixin:0.8.5")?;
+/// let url = repositories.find(minecraft_modloaders::http::client(), &coordinate).await;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryList {
+    repositories: Vec<String>,
+}
+
+impl Default for RepositoryList {
+    fn default() -> Self {
+        Self::new()
+            .with_repository(MOJANG_REPOSITORY)
+            .with_repository(FORGE_REPOSITORY)
+            .with_repository(NEOFORGE_REPOSITORY)
+            .with_repository(FABRIC_REPOSITORY)
+            .with_repository(QUILT_REPOSITORY)
+            .with_repository(SPONGE_REPOSITORY)
+            .with_repository(MAVEN_CENTRAL)
+    }
+}
+
+impl RepositoryList {
+    /// An empty list; see [`RepositoryList::default`] for the well-known repositories.
+    pub fn new() -> Self {
+        Self { repositories: Vec::new() }
+    }
+
+    /// Try `base_url` after the repositories already listed.
+    pub fn with_repository(mut self, base_url: impl Into<String>) -> Self {
+        self.repositories.push(base_url.into());
+        self
+    }
+
+    /// Try `base_url` before every repository already listed.
+    pub fn with_preferred_repository(mut self, base_url: impl Into<String>) -> Self {
+        self.repositories.insert(0, base_url.into());
+        self
+    }
+
+    /// The repository base URLs, in the order they are tried.
+    pub fn repositories(&self) -> &[String] {
+        &self.repositories
+    }
+
+    /// Where `coordinate` would be in each repository, in order.
+    pub fn candidate_urls(&self, coordinate: &MavenCoordinate) -> Vec<String> {
+        self.repositories.iter().map(|repo| coordinate.to_url(repo)).collect()
+    }
+
+    /// The URL of `coordinate` in the first repository that has it, asked with `HEAD`
+    /// requests. `None` when no repository answers with a success status.
+    pub async fn find(&self, client: &reqwest::Client, coordinate: &MavenCoordinate) -> Option<String> {
+        let repository = self.find_repository(client, coordinate).await?;
+        Some(coordinate.to_url(repository))
+    }
+
+    /// Like [`RepositoryList::find`], returning the repository's base URL instead.
+    pub async fn find_repository(&self, client: &reqwest::Client, coordinate: &MavenCoordinate) -> Option<&str> {
+        for repository in &self.repositories {
+            if http::content_length(client, &coordinate.to_url(repository)).await.is_ok() {
+                return Some(repository);
+            }
+        }
+        None
+    }
+
+    /// Like [`RepositoryList::find`], failing with every URL tried when no repository
+    /// has the artifact.
+    pub async fn resolve(&self, client: &reqwest::Client, coordinate: &MavenCoordinate) -> Result<String> {
+        match self.find(client, coordinate).await {
+            Some(url) => Ok(url),
+            None => bail!(
+                "{} was not found in any repository, tried {}",
+                coordinate,
+                self.candidate_urls(coordinate).join(", ")
+            ),
+        }
+    }
+}
+
+/// Extracts the `<versioning><versions>` list from a Maven `maven-metadata.xml` document.
+///
+/// Versions are returned in document order, which Maven repositories keep oldest first.
+/// This is a minimal scanner for the well-known metadata layout rather than a general XML parser.
+pub fn metadata_versions(xml: &str) -> Result<Vec<String>> {
+    let open = "<versions>";
+    let start = xml
+        .find(open)
+        .ok_or_else(|| anyhow!("maven-metadata.xml does not contain a <versions> list"))?
+        + open.len();
+    let end = xml[start..]
+        .find("</versions>")
+        .map(|i| start + i)
+        .ok_or_else(|| anyhow!("maven-metadata.xml has an unterminated <versions> list"))?;
+
+    Ok(xml[start..end]
+        .split("<version>")
+        .skip(1)
+        .filter_map(|chunk| chunk.split_once("</version>"))
+        .map(|(version, _)| version.trim().to_string())
+        .filter(|version| !version.is_empty())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::serve_files;
+
+    #[test]
+    fn test_metadata_versions() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata>
+  <groupId>net.neoforged</groupId>
+  <artifactId>neoforge</artifactId>
+  <versioning>
+    <latest>20.4.80-beta</latest>
+    <release>20.4.80-beta</release>
+    <versions>
+      <version>20.2.3-beta</version>
+      <version>20.4.80-beta</version>
+    </versions>
+    <lastUpdated>20240101000000</lastUpdated>
+  </versioning>
+</metadata>"#;
+
+        assert_eq!(
+            metadata_versions(xml).unwrap(),
+            vec!["20.2.3-beta".to_string(), "20.4.80-beta".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_metadata_without_versions() {
+        assert!(metadata_versions("<metadata></metadata>").is_err());
+    }
+
+    #[test]
+    fn test_parse_coordinate() {
+        let coordinate = MavenCoordinate::parse("net.fabricmc:fabric-loader:0.15.11").unwrap();
+        assert_eq!(coordinate.group, "net.fabricmc");
+        assert_eq!(coordinate.artifact, "fabric-loader");
+        assert_eq!(coordinate.version, "0.15.11");
+        assert_eq!(coordinate.classifier, None);
+        assert_eq!(coordinate.extension(), "jar");
+        assert_eq!(coordinate.to_string(), "net.fabricmc:fabric-loader:0.15.11");
+    }
+
+    #[test]
+    fn test_parse_coordinate_with_classifier_and_extension() {
+        let coordinate: MavenCoordinate = "org.lwjgl:lwjgl:3.3.3:natives-linux".parse().unwrap();
+        assert_eq!(coordinate.classifier.as_deref(), Some("natives-linux"));
+        assert_eq!(coordinate.extension, None);
+
+        let coordinate = MavenCoordinate::parse("de.oceanlabs.mcp:mcp_config:1.20.4-20231207.154220@zip").unwrap();
+        assert_eq!(coordinate.version, "1.20.4-20231207.154220");
+        assert_eq!(coordinate.classifier, None);
+        assert_eq!(coordinate.extension(), "zip");
+
+        let coordinate = MavenCoordinate::parse("net.minecraft:client:1.20.4-20231207.154220:mappings@txt").unwrap();
+        assert_eq!(coordinate.classifier.as_deref(), Some("mappings"));
+        assert_eq!(coordinate.extension(), "txt");
+        assert_eq!(coordinate.to_string(), "net.minecraft:client:1.20.4-20231207.154220:mappings@txt");
+    }
+
+    #[test]
+    fn test_parse_invalid_coordinates() {
+        for coordinate in ["", "net.fabricmc", "net.fabricmc:fabric-loader", "a:b:c:d:e", "a::c", "a:b:c@", "a:b:c:"] {
+            assert!(MavenCoordinate::parse(coordinate).is_err(), "{:?}", coordinate);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repository_list_tries_in_order() {
+        let path = "/org/spongepowered/mixin/0.8.5/mixin-0.8.5.jar";
+        let first = format!("{}/", serve_files(Vec::<(&str, &str)>::new()).base_url());
+        let second = format!("{}/", serve_files([(path, "mixin")]).base_url());
+        let coordinate = MavenCoordinate::parse("org.spongepowered:mixin:0.8.5").unwrap();
+
+        let repositories = RepositoryList::new().with_repository(&first).with_repository(&second);
+        assert_eq!(
+            repositories.candidate_urls(&coordinate),
+            vec![coordinate.to_url(&first), coordinate.to_url(&second)]
+        );
+        let client = reqwest::Client::new();
+        assert_eq!(repositories.resolve(&client, &coordinate).await.unwrap(), coordinate.to_url(&second));
+
+        let missing = MavenCoordinate::parse("org.example:missing:1.0").unwrap();
+        let err = repositories.resolve(&client, &missing).await.unwrap_err();
+        assert!(err.to_string().contains(&missing.to_url(&first)), "{}", err);
+    }
+
+    #[test]
+    fn test_repository_list_order() {
+        let repositories = RepositoryList::default()
+            .with_repository("https://last.example.com/")
+            .with_preferred_repository("https://first.example.com/");
+        let repos = repositories.repositories();
+        assert_eq!(repos.first().map(String::as_str), Some("https://first.example.com/"));
+        assert_eq!(repos[1], MOJANG_REPOSITORY);
+        assert_eq!(repos.last().map(String::as_str), Some("https://last.example.com/"));
+    }
+
+    #[test]
+    fn test_coordinate_path_and_url() {
+        let coordinate = MavenCoordinate::parse("net.fabricmc:fabric-loader:0.15.11").unwrap();
+        assert_eq!(coordinate.to_path(), "net/fabricmc/fabric-loader/0.15.11/fabric-loader-0.15.11.jar");
+        assert_eq!(
+            coordinate.to_url("https://maven.fabricmc.net/"),
+            "https://maven.fabricmc.net/net/fabricmc/fabric-loader/0.15.11/fabric-loader-0.15.11.jar"
+        );
+        assert_eq!(
+            coordinate.to_url("https://maven.fabricmc.net"),
+            "https://maven.fabricmc.net/net/fabricmc/fabric-loader/0.15.11/fabric-loader-0.15.11.jar"
+        );
+
+        let natives = MavenCoordinate::parse("org.lwjgl:lwjgl:3.3.3")
+            .unwrap()
+            .with_classifier("natives-windows");
+        assert_eq!(natives.to_path(), "org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3-natives-windows.jar");
+
+        let zip = MavenCoordinate::parse("de.oceanlabs.mcp:mcp_config:1.20.4@zip").unwrap();
+        assert_eq!(zip.to_path(), "de/oceanlabs/mcp/mcp_config/1.20.4/mcp_config-1.20.4.zip");
+    }
+}
@@ -1,8 +1,14 @@
-use anyhow::{anyhow, Result};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-const VERSIONS_URL: &str =
+use crate::http::{self, HttpClient};
+use crate::{LoaderError, LoaderVersionInfo, ModLoaderVersions};
+
+pub(crate) const VERSIONS_URL: &str =
     "https://maven.neoforged.net/api/maven/versions/releases/net/neoforged/neoforge";
+const MAVEN_METADATA_URL: &str =
+    "https://maven.neoforged.net/releases/net/neoforged/neoforge/maven-metadata.xml";
 
 /// Contains all available NeoForge versions.
 ///
@@ -23,20 +29,47 @@ struct MavenResponse {
 
 impl NeoForgeVersions {
     /// Fetch all available NeoForge versions from the Maven API.
-    pub async fn fetch() -> Result<Self> {
-        let response = reqwest::get(VERSIONS_URL).await?;
-        if !response.status().is_success() {
-            return Err(anyhow!(
-                "Failed to fetch NeoForge versions: HTTP {}",
-                response.status()
-            ));
-        }
-        let data: MavenResponse = response.json().await?;
+    pub async fn fetch() -> Result<Self, LoaderError> {
+        Self::fetch_with_client(http::client()).await
+    }
+
+    /// Like [`NeoForgeVersions::fetch`], but through a caller-provided HTTP client.
+    pub async fn fetch_with_client(client: &impl HttpClient) -> Result<Self, LoaderError> {
+        Self::fetch_from_url(client, VERSIONS_URL).await
+    }
+
+    /// Fetch the versions from a Maven API `versions` endpoint at `url`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "fetch_neoforge_versions", skip_all, fields(url = %url)))]
+    pub(crate) async fn fetch_from_url(client: &impl HttpClient, url: &str) -> Result<Self, LoaderError> {
+        let data: MavenResponse = client.get_json(url).await?;
         Ok(Self {
             versions: data.versions,
         })
     }
 
+    /// Fetch all available NeoForge versions from the raw `maven-metadata.xml`.
+    ///
+    /// Useful when the Maven API endpoint used by [`NeoForgeVersions::fetch`] is
+    /// unavailable, e.g. behind a mirror that only serves static repository files.
+    pub async fn fetch_maven_metadata() -> Result<Self> {
+        let response = http::get(MAVEN_METADATA_URL)
+            .await
+            .context("Failed to fetch NeoForge maven metadata")?;
+        Self::from_maven_metadata(&response.text().await?)
+    }
+
+    /// Parse NeoForge versions from the contents of a `maven-metadata.xml` file.
+    pub fn from_maven_metadata(xml: &str) -> Result<Self> {
+        Ok(Self {
+            versions: crate::maven::metadata_versions(xml)?,
+        })
+    }
+
+    /// Alias of [`NeoForgeVersions::get_versions`].
+    pub fn versions_for_game(&self, mc_version: &str) -> Vec<&str> {
+        self.get_versions(mc_version)
+    }
+
     /// Get all NeoForge versions for a specific Minecraft version.
     ///
     /// Returns versions in the order they appear (typically newest last).
@@ -80,6 +113,41 @@ impl NeoForgeVersions {
     }
 }
 
+/// NeoForge version listing.
+///
+/// NeoForge installs reuse [`crate::forge::ForgeModLoader::install_client_from_url`]
+/// with [`NeoForgeVersions::installer_url`], so this type only implements [`ModLoaderVersions`].
+#[derive(Debug, Clone, Default)]
+pub struct NeoForgeModLoader;
+
+impl NeoForgeModLoader {
+    /// Creates a new NeoForgeModLoader instance.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl ModLoaderVersions for NeoForgeModLoader {
+    fn name(&self) -> &'static str {
+        "NeoForge"
+    }
+
+    async fn fetch_versions(&self) -> Result<Vec<LoaderVersionInfo>> {
+        let versions = NeoForgeVersions::fetch().await?;
+        Ok(versions
+            .versions
+            .iter()
+            .rev()
+            .map(|v| LoaderVersionInfo {
+                version: v.clone(),
+                game_version: neoforge_to_mc(v),
+                stable: !v.contains('-'),
+            })
+            .collect())
+    }
+}
+
 /// Convert a Minecraft version like `1.21.1` to a NeoForge prefix like `21.1.`.
 /// MC `1.21` (no patch) maps to prefix `21.0.`.
 fn mc_to_neoforge_prefix(mc_version: &str) -> Option<String> {
@@ -137,6 +205,32 @@ mod tests {
         assert_eq!(neoforge_to_mc("21.1.77-beta"), Some("1.21.1".into()));
     }
 
+    #[test]
+    fn test_from_maven_metadata() {
+        let xml = r#"<metadata>
+  <groupId>net.neoforged</groupId>
+  <artifactId>neoforge</artifactId>
+  <versioning>
+    <versions>
+      <version>20.2.86</version>
+      <version>20.4.80-beta</version>
+      <version>20.4.237</version>
+      <version>21.0.14</version>
+    </versions>
+  </versioning>
+</metadata>"#;
+
+        let versions = NeoForgeVersions::from_maven_metadata(xml).unwrap();
+        assert_eq!(versions.versions.len(), 4);
+        assert_eq!(
+            versions.versions_for_game("1.20.4"),
+            vec!["20.4.80-beta", "20.4.237"]
+        );
+        assert_eq!(versions.get_latest("1.20.4"), Some("20.4.237"));
+        assert_eq!(versions.versions_for_game("1.21"), vec!["21.0.14"]);
+        assert!(versions.versions_for_game("1.19.2").is_empty());
+    }
+
     #[tokio::test]
     async fn test_fetch_versions() {
         let versions = NeoForgeVersions::fetch().await;
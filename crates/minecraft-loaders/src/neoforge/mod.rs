@@ -1,3 +1,3 @@
 pub mod loader;
 
-pub use loader::NeoForgeVersions;
+pub use loader::{NeoForgeModLoader, NeoForgeVersions};
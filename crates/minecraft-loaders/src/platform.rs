@@ -0,0 +1,73 @@
+//! Platform names as Mojang's version JSONs spell them.
+//!
+//! LWJGL 3 ships one native jar per platform, told apart by the maven classifier:
+//! `natives-windows`, `natives-windows-x86`, `natives-windows-arm64`, `natives-linux`,
+//! `natives-linux-arm64`, `natives-macos` and `natives-macos-arm64`. Version JSONs gate
+//! these on the OS alone, so on Apple Silicon or ARM Linux the rules let the x86_64
+//! jar through as well; [`native_classifier`] is what picks the right one.
+
+use crate::vanilla::rules::{current_arch, current_os};
+
+/// The LWJGL 3 native classifier for the machine this binary was built for.
+///
+/// To pick natives for another platform, for example to install Mac natives from a
+/// Linux CI box, evaluate libraries against a [`RuleContext`](crate::vanilla::RuleContext)
+/// for that platform; its [`native_classifier`](crate::vanilla::RuleContext::native_classifier)
+/// follows the context's OS and architecture instead.
+pub fn native_classifier() -> &'static str {
+    native_classifier_for(current_os(), current_arch())
+}
+
+/// The LWJGL 3 native classifier for a Mojang OS name (`windows`, `osx`, `linux`) and
+/// architecture (`x86`, `x86_64`, `arm64`).
+///
+/// ```
+/// use minecraft_modloaders::platform::native_classifier_for;
+///
+/// assert_eq!(native_classifier_for("osx", "arm64"), "natives-macos-arm64");
+/// assert_eq!(native_classifier_for("windows", "x86_64"), "natives-windows");
+/// ```
+pub fn native_classifier_for(os: &str, arch: &str) -> &'static str {
+    match (os, arch) {
+        ("windows", "x86") => "natives-windows-x86",
+        ("windows", "arm64") => "natives-windows-arm64",
+        ("windows", _) => "natives-windows",
+        ("osx", "arm64") => "natives-macos-arm64",
+        ("osx", _) => "natives-macos",
+        (_, "arm64") => "natives-linux-arm64",
+        _ => "natives-linux",
+    }
+}
+
+/// Whether a maven classifier names a platform native jar, e.g. `natives-linux`.
+pub(crate) fn is_native_classifier(classifier: &str) -> bool {
+    classifier.starts_with("natives-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_classifier_mapping() {
+        let cases = [
+            (("windows", "x86_64"), "natives-windows"),
+            (("windows", "x86"), "natives-windows-x86"),
+            (("windows", "arm64"), "natives-windows-arm64"),
+            (("osx", "x86_64"), "natives-macos"),
+            (("osx", "arm64"), "natives-macos-arm64"),
+            (("linux", "x86_64"), "natives-linux"),
+            (("linux", "arm64"), "natives-linux-arm64"),
+        ];
+        for ((os, arch), expected) in cases {
+            assert_eq!(native_classifier_for(os, arch), expected, "{os}/{arch}");
+        }
+    }
+
+    #[test]
+    fn test_native_classifier_matches_current_platform() {
+        assert_eq!(native_classifier(), native_classifier_for(current_os(), current_arch()));
+        assert!(is_native_classifier(native_classifier()));
+        assert!(!is_native_classifier("sources"));
+    }
+}
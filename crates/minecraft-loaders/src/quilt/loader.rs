@@ -1,12 +1,15 @@
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
-const GAME_VERSIONS_URL: &str = "https://meta.quiltmc.org/v3/versions/game";
-const LOADER_VERSIONS_URL: &str = "https://meta.quiltmc.org/v3/versions/loader";
-const INSTALLER_VERSIONS_URL: &str = "https://meta.quiltmc.org/v3/versions/installer";
+use crate::fabric::{LaunchProfile, fetch_profile};
+use crate::http::{self, HttpClient};
+use crate::{LoaderError, LoaderVersionInfo, ModLoaderVersions};
+
+pub(crate) const DEFAULT_META_URL: &str = "https://meta.quiltmc.org/";
 
 /// All available Quilt versions, fetched from the Quilt Meta API.
 /// The API is structurally similar to Fabric's.
@@ -25,6 +28,10 @@ pub struct GameVersion {
 }
 
 /// A Quilt loader version.
+///
+/// Unlike Fabric, Quilt does not report a `stable` flag; pre-releases are marked
+/// in the version string instead (e.g. `0.26.0-beta.1`). The maven coordinate
+/// also lives under a different group: `org.quiltmc:quilt-loader:<version>`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoaderVersion {
     pub version: String,
@@ -34,6 +41,23 @@ pub struct LoaderVersion {
     pub build: u32,
 }
 
+impl LoaderVersion {
+    /// Whether this is a release build rather than a beta/rc/pre-release.
+    pub fn is_stable(&self) -> bool {
+        !self.version.contains('-')
+    }
+}
+
+impl From<&LoaderVersion> for LoaderVersionInfo {
+    fn from(v: &LoaderVersion) -> Self {
+        Self {
+            version: v.version.clone(),
+            game_version: None,
+            stable: v.is_stable(),
+        }
+    }
+}
+
 /// A Quilt installer version.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstallerVersion {
@@ -47,14 +71,27 @@ pub struct InstallerVersion {
 impl QuiltVersions {
     /// Fetch all available Quilt versions from the Meta API.
     /// Makes three parallel requests for game, loader, and installer versions.
-    pub async fn fetch() -> Result<Self> {
-        let game_res = reqwest::get(GAME_VERSIONS_URL).await?;
-        let loader_res = reqwest::get(LOADER_VERSIONS_URL).await?;
-        let installer_res = reqwest::get(INSTALLER_VERSIONS_URL).await?;
+    pub async fn fetch() -> Result<Self, LoaderError> {
+        Self::fetch_with_client(http::client()).await
+    }
+
+    /// Like [`QuiltVersions::fetch`], but through a caller-provided HTTP client.
+    pub async fn fetch_with_client(client: &impl HttpClient) -> Result<Self, LoaderError> {
+        Self::fetch_from_meta(client, DEFAULT_META_URL).await
+    }
 
-        let game = game_res.json::<Vec<GameVersion>>().await?;
-        let loader = loader_res.json::<Vec<LoaderVersion>>().await?;
-        let installer = installer_res.json::<Vec<InstallerVersion>>().await?;
+    /// Fetch the versions from the Meta API rooted at `base_url`, e.g. a mirror.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "fetch_quilt_versions", skip_all, fields(base_url = %base_url)))]
+    pub(crate) async fn fetch_from_meta(client: &impl HttpClient, base_url: &str) -> Result<Self, LoaderError> {
+        let base_url = base_url.trim_end_matches('/');
+        let game_url = format!("{}/v3/versions/game", base_url);
+        let loader_url = format!("{}/v3/versions/loader", base_url);
+        let installer_url = format!("{}/v3/versions/installer", base_url);
+        let (game, loader, installer) = tokio::try_join!(
+            client.get_json(&game_url),
+            client.get_json(&loader_url),
+            client.get_json(&installer_url),
+        )?;
 
         Ok(Self {
             game,
@@ -92,6 +129,14 @@ impl QuiltVersions {
     pub fn supports_game_version(&self, version: &str) -> bool {
         self.game.iter().any(|v| v.version == version)
     }
+
+    /// Fetches the launch profile for a Minecraft version and Quilt loader version.
+    ///
+    /// Quilt serves the same format as [`FabricVersions::profile_json`](crate::fabric::FabricVersions::profile_json),
+    /// and fails the same way for an unknown combination.
+    pub async fn profile_json(game: &str, loader: &str) -> Result<LaunchProfile> {
+        fetch_profile("Quilt", &format!("{}v3/versions/loader", DEFAULT_META_URL), game, loader).await
+    }
 }
 
 impl InstallerVersion {
@@ -100,7 +145,7 @@ impl InstallerVersion {
         if self.url.is_empty() {
             return Err(anyhow!("No download URL available for installer {}", self.version));
         }
-        let response = reqwest::get(&self.url).await?;
+        let response = http::client().get(&self.url).send().await?;
         let bytes = response.bytes().await?;
         let output = output_path.as_ref();
 
@@ -115,10 +160,87 @@ impl InstallerVersion {
     }
 }
 
+/// Quilt version listing.
+///
+/// Quilt installs go through the Fabric-compatible installer flow, so this type
+/// only implements [`ModLoaderVersions`].
+#[derive(Debug, Clone, Default)]
+pub struct QuiltModLoader;
+
+impl QuiltModLoader {
+    /// Creates a new QuiltModLoader instance.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl ModLoaderVersions for QuiltModLoader {
+    fn name(&self) -> &'static str {
+        "Quilt"
+    }
+
+    async fn fetch_versions(&self) -> Result<Vec<LoaderVersionInfo>> {
+        let versions = QuiltVersions::fetch().await?;
+        Ok(versions.loader.iter().map(LoaderVersionInfo::from).collect())
+    }
+
+    async fn loader_versions_for_game(&self, game: &str) -> Result<Vec<LoaderVersionInfo>> {
+        // Like Fabric, Quilt loader builds are global rather than per game version.
+        let versions = QuiltVersions::fetch().await?;
+        if !versions.supports_game_version(game) {
+            return Ok(Vec::new());
+        }
+        Ok(versions.loader.iter().map(LoaderVersionInfo::from).collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const LOADER_FIXTURE: &str = r#"[
+        {"separator": ".", "build": 52, "maven": "org.quiltmc:quilt-loader:0.26.0-beta.1", "version": "0.26.0-beta.1"},
+        {"separator": ".", "build": 51, "maven": "org.quiltmc:quilt-loader:0.25.0", "version": "0.25.0"}
+    ]"#;
+
+    const GAME_FIXTURE: &str = r#"[
+        {"version": "24w14a", "stable": false},
+        {"version": "1.20.4", "stable": true}
+    ]"#;
+
+    const INSTALLER_FIXTURE: &str = r#"[
+        {"url": "https://maven.quiltmc.org/repository/release/org/quiltmc/quilt-installer/0.9.2/quilt-installer-0.9.2.jar",
+         "maven": "org.quiltmc:quilt-installer:0.9.2", "version": "0.9.2"}
+    ]"#;
+
+    #[test]
+    fn test_deserialize_fixture() {
+        let versions = QuiltVersions {
+            game: serde_json::from_str(GAME_FIXTURE).unwrap(),
+            loader: serde_json::from_str(LOADER_FIXTURE).unwrap(),
+            installer: serde_json::from_str(INSTALLER_FIXTURE).unwrap(),
+        };
+
+        let latest = versions.get_latest_loader().unwrap();
+        assert_eq!(latest.version, "0.26.0-beta.1");
+        assert_eq!(latest.maven, "org.quiltmc:quilt-loader:0.26.0-beta.1");
+        assert!(!latest.is_stable());
+        assert!(versions.find_loader("0.25.0").unwrap().is_stable());
+
+        assert_eq!(versions.get_latest_game_version().unwrap().version, "1.20.4");
+        assert!(versions.get_latest_installer().unwrap().url.ends_with(".jar"));
+    }
+
+    #[test]
+    fn test_loader_version_info_from_quilt() {
+        let loader: Vec<LoaderVersion> = serde_json::from_str(LOADER_FIXTURE).unwrap();
+        let infos: Vec<LoaderVersionInfo> = loader.iter().map(LoaderVersionInfo::from).collect();
+        assert!(!infos[0].stable);
+        assert!(infos[1].stable);
+        assert!(infos.iter().all(|v| v.game_version.is_none()));
+    }
+
     #[tokio::test]
     async fn test_fetch_versions() {
         let versions = QuiltVersions::fetch().await;
@@ -145,4 +267,37 @@ mod tests {
         assert!(versions.supports_game_version(&latest.version));
         assert!(!versions.supports_game_version("0.0.1"));
     }
+
+    #[test]
+    fn test_deserialize_profile_fixture() {
+        let profile: LaunchProfile = serde_json::from_str(
+            r#"{
+                "id": "quilt-loader-0.26.0-1.20.4",
+                "inheritsFrom": "1.20.4",
+                "type": "release",
+                "mainClass": "org.quiltmc.loader.impl.launch.knot.KnotClient",
+                "arguments": {"game": []},
+                "libraries": [
+                    {"name": "net.fabricmc:intermediary:1.20.4", "url": "https://maven.fabricmc.net/"},
+                    {"name": "org.quiltmc:quilt-loader:0.26.0", "url": "https://maven.quiltmc.org/repository/release/"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(profile.inherits_from, "1.20.4");
+        assert_eq!(profile.main_class, "org.quiltmc.loader.impl.launch.knot.KnotClient");
+        assert!(profile.arguments.jvm.is_empty());
+        assert_eq!(
+            profile.library_set().libraries[1].artifact().unwrap().url,
+            "https://maven.quiltmc.org/repository/release/org/quiltmc/quilt-loader/0.26.0/quilt-loader-0.26.0.jar"
+        );
+    }
+
+    #[cfg(feature = "network-tests")]
+    #[tokio::test]
+    async fn test_fetch_profile_json() {
+        let profile = QuiltVersions::profile_json("1.20.4", "0.26.0").await.unwrap();
+        assert_eq!(profile.inherits_from, "1.20.4");
+        assert!(profile.libraries.iter().any(|l| l.name.starts_with("org.quiltmc:quilt-loader:")));
+    }
 }
@@ -1,3 +1,3 @@
 pub mod loader;
 
-pub use loader::QuiltVersions;
+pub use loader::{QuiltModLoader, QuiltVersions};
@@ -0,0 +1,364 @@
+//! Files a dedicated server needs next to its jar before the first start.
+//!
+//! The server refuses to run until `eula.txt` says `eula=true`, and reads its settings
+//! from `server.properties`, a Java properties file. [`write_properties`] only touches
+//! the keys [`ServerProperties`] knows about, so comments and every other setting in an
+//! existing file survive.
+
+use anyhow::{Context, Result, anyhow};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const EULA_FILE: &str = "eula.txt";
+pub const PROPERTIES_FILE: &str = "server.properties";
+
+/// The game mode new players join in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameMode {
+    #[default]
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl GameMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Survival => "survival",
+            Self::Creative => "creative",
+            Self::Adventure => "adventure",
+            Self::Spectator => "spectator",
+        }
+    }
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for GameMode {
+    type Err = anyhow::Error;
+
+    /// Accepts the names and the numeric ids older servers wrote.
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "survival" | "0" => Ok(Self::Survival),
+            "creative" | "1" => Ok(Self::Creative),
+            "adventure" | "2" => Ok(Self::Adventure),
+            "spectator" | "3" => Ok(Self::Spectator),
+            _ => Err(anyhow!("Unknown game mode {:?}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Peaceful,
+    #[default]
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Peaceful => "peaceful",
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = anyhow::Error;
+
+    /// Accepts the names and the numeric ids older servers wrote.
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "peaceful" | "0" => Ok(Self::Peaceful),
+            "easy" | "1" => Ok(Self::Easy),
+            "normal" | "2" => Ok(Self::Normal),
+            "hard" | "3" => Ok(Self::Hard),
+            _ => Err(anyhow!("Unknown difficulty {:?}", s)),
+        }
+    }
+}
+
+/// The commonly changed settings of `server.properties`, defaulting to the values a
+/// fresh vanilla server writes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerProperties {
+    /// `server-port`
+    pub port: u16,
+    /// `motd`, the message shown in the multiplayer server list.
+    pub motd: String,
+    /// `gamemode`
+    pub gamemode: GameMode,
+    /// `difficulty`
+    pub difficulty: Difficulty,
+    /// `max-players`
+    pub max_players: u32,
+}
+
+impl Default for ServerProperties {
+    fn default() -> Self {
+        Self {
+            port: 25565,
+            motd: "A Minecraft Server".to_string(),
+            gamemode: GameMode::default(),
+            difficulty: Difficulty::default(),
+            max_players: 20,
+        }
+    }
+}
+
+impl ServerProperties {
+    /// The `(key, value)` pairs in the order a vanilla server writes them.
+    fn entries(&self) -> [(&'static str, String); 5] {
+        [
+            ("difficulty", self.difficulty.to_string()),
+            ("gamemode", self.gamemode.to_string()),
+            ("max-players", self.max_players.to_string()),
+            ("motd", self.motd.clone()),
+            ("server-port", self.port.to_string()),
+        ]
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "server-port" => self.port = value.parse().with_context(|| format!("Invalid server-port {:?}", value))?,
+            "motd" => self.motd = value.to_string(),
+            "gamemode" => self.gamemode = value.parse()?,
+            "difficulty" => self.difficulty = value.parse()?,
+            "max-players" => self.max_players = value.parse().with_context(|| format!("Invalid max-players {:?}", value))?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Accept the Minecraft EULA for the server in `dir` by writing `eula=true` to its
+/// `eula.txt`. Only call this after the user agreed to <https://aka.ms/MinecraftEULA>.
+pub fn accept_eula(dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(EULA_FILE);
+    std::fs::write(
+        &path,
+        "#By changing the setting below to TRUE you are indicating your agreement to our EULA (https://aka.ms/MinecraftEULA).\neula=true\n",
+    )?;
+    Ok(path)
+}
+
+/// Write `props` to the `server.properties` in `dir`, returning its path.
+///
+/// When the file exists, the known keys are updated in place and comments, blank
+/// lines and every other key are kept as they are; known keys it lacks are appended.
+pub fn write_properties(dir: &Path, props: &ServerProperties) -> Result<PathBuf> {
+    let path = dir.join(PROPERTIES_FILE);
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut pending: Vec<(&str, String)> = props.entries().into_iter().collect();
+    let mut lines = Vec::new();
+    for line in existing.lines() {
+        let replaced = parse_line(line).and_then(|(key, _)| {
+            let index = pending.iter().position(|(known, _)| *known == key)?;
+            let (key, value) = pending.remove(index);
+            Some(format!("{}={}", key, escape(&value)))
+        });
+        lines.push(replaced.unwrap_or_else(|| line.to_string()));
+    }
+    lines.extend(pending.into_iter().map(|(key, value)| format!("{}={}", key, escape(&value))));
+
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(&path, lines.join("\n") + "\n")?;
+    Ok(path)
+}
+
+/// Read the known settings from the `server.properties` in `dir`; keys the file
+/// does not set keep their defaults.
+pub fn read_properties(dir: &Path) -> Result<ServerProperties> {
+    let path = dir.join(PROPERTIES_FILE);
+    let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut props = ServerProperties::default();
+    for (key, value) in content.lines().filter_map(parse_line) {
+        props.set(&key, &value)?;
+    }
+    Ok(props)
+}
+
+/// Splits a `key=value` line into its unescaped parts; comments and blank lines are `None`.
+fn parse_line(line: &str) -> Option<(String, String)> {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
+        return None;
+    }
+
+    let mut escaped = false;
+    let mut split = None;
+    for (i, c) in trimmed.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '=' | ':' => {
+                split = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let (key, value) = match split {
+        Some(i) => (&trimmed[..i], &trimmed[i + 1..]),
+        None => (trimmed, ""),
+    };
+    Some((unescape(key.trim_end()), unescape(value.trim_start())))
+}
+
+/// Escapes a value the way `java.util.Properties` reads it back.
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '=' | ':' | '#' | '!' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' ' if i == 0 => out.push_str("\\ "),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(decoded) => out.push(decoded),
+                    None => {
+                        out.push_str("\\u");
+                        out.push_str(&hex);
+                    }
+                }
+            }
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_eula() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = accept_eula(&dir.path().join("server")).unwrap();
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.lines().any(|line| line == "eula=true"));
+    }
+
+    #[test]
+    fn test_write_properties_new_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write_properties(dir.path(), &ServerProperties::default()).unwrap();
+
+        let content = std::fs::read_to_string(dir.path().join(PROPERTIES_FILE)).unwrap();
+        assert!(content.contains("server-port=25565\n"));
+        assert!(content.contains("gamemode=survival\n"));
+        assert_eq!(read_properties(dir.path()).unwrap(), ServerProperties::default());
+    }
+
+    #[test]
+    fn test_write_properties_keeps_comments_and_unknown_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(PROPERTIES_FILE),
+            "#Minecraft server properties\n#Mon Jan 01 00:00:00 UTC 2024\nenable-command-block=false\nmotd=Old message\nmy-plugin-key=custom\\:value\nserver-port=25565\n",
+        )
+        .unwrap();
+
+        let props = ServerProperties {
+            port: 25570,
+            motd: "Welcome: have fun = win".to_string(),
+            gamemode: GameMode::Creative,
+            difficulty: Difficulty::Hard,
+            max_players: 8,
+        };
+        write_properties(dir.path(), &props).unwrap();
+
+        let content = std::fs::read_to_string(dir.path().join(PROPERTIES_FILE)).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "#Minecraft server properties",
+                "#Mon Jan 01 00:00:00 UTC 2024",
+                "enable-command-block=false",
+                "motd=Welcome\\: have fun \\= win",
+                "my-plugin-key=custom\\:value",
+                "server-port=25570",
+                "difficulty=hard",
+                "gamemode=creative",
+                "max-players=8",
+            ]
+        );
+        assert_eq!(read_properties(dir.path()).unwrap(), props);
+    }
+
+    #[test]
+    fn test_read_properties_legacy_values() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(PROPERTIES_FILE),
+            "gamemode=1\ndifficulty=3\nmotd=\\u00A7aGreen \\u00A7rserver\n",
+        )
+        .unwrap();
+
+        let props = read_properties(dir.path()).unwrap();
+        assert_eq!(props.gamemode, GameMode::Creative);
+        assert_eq!(props.difficulty, Difficulty::Hard);
+        assert_eq!(props.motd, "\u{a7}aGreen \u{a7}rserver");
+        assert_eq!(props.port, 25565);
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        for value in ["plain", " leading space", "a=b:c", "back\\slash", "line\nbreak", "#not a comment"] {
+            let line = format!("key={}", escape(value));
+            assert_eq!(parse_line(&line), Some(("key".to_string(), value.to_string())), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use super::rules::{Rule, RuleContext, evaluate};
+
+/// JVM arguments of versions from before 1.13, whose JSON only carries game arguments.
+pub(crate) const LEGACY_JVM_ARGUMENTS: &[&str] = &["-Djava.library.path=${natives_directory}", "-cp", "${classpath}"];
+
+/// The modern (1.13+) `arguments` block of a version JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct VersionArguments {
+    #[serde(default)]
+    pub game: Vec<Argument>,
+    #[serde(default)]
+    pub jvm: Vec<Argument>,
+}
+
+impl VersionArguments {
+    /// The arguments of a pre-1.13 version: its space-separated `minecraftArguments`
+    /// as plain game arguments, and the JVM arguments the launcher always passed them.
+    pub fn from_legacy(minecraft_arguments: &str) -> Self {
+        Self {
+            game: minecraft_arguments.split_whitespace().map(Argument::from).collect(),
+            jvm: LEGACY_JVM_ARGUMENTS.iter().copied().map(Argument::from).collect(),
+        }
+    }
+
+    /// Whether any rule in the game or JVM arguments tests the launcher feature
+    /// `feature`, i.e. whether this version knows about it at all.
+    pub fn declares_feature(&self, feature: &str) -> bool {
+        self.game.iter().chain(&self.jvm).any(|argument| match argument {
+            Argument::Plain(_) => false,
+            Argument::Conditional { rules, .. } => rules.iter().any(|rule| rule.features.contains_key(feature)),
+        })
+    }
+}
+
+/// A single entry of `arguments.game` or `arguments.jvm`: either a plain string or
+/// one or more values gated by rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Argument {
+    Plain(String),
+    Conditional { rules: Vec<Rule>, value: ArgumentValue },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ArgumentValue {
+    Single(String),
+    Many(Vec<String>),
+}
+
+impl Argument {
+    /// The raw values of this argument that apply in `ctx`, before placeholder
+    /// substitution.
+    pub fn values(&self, ctx: &RuleContext) -> Vec<&str> {
+        match self {
+            Argument::Plain(value) => vec![value.as_str()],
+            Argument::Conditional { rules, value } if evaluate(rules, ctx) => match value {
+                ArgumentValue::Single(value) => vec![value.as_str()],
+                ArgumentValue::Many(values) => values.iter().map(String::as_str).collect(),
+            },
+            Argument::Conditional { .. } => Vec::new(),
+        }
+    }
+}
+
+impl From<&str> for Argument {
+    fn from(value: &str) -> Self {
+        Argument::Plain(value.to_string())
+    }
+}
@@ -0,0 +1,277 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use crate::download::{DownloadJob, sha1_hex};
+use crate::http;
+
+const RESOURCES_URL: &str = "https://resources.download.minecraft.net";
+
+/// The `assetIndex` entry of a version JSON, pointing at the index document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetIndexRef {
+    pub id: String,
+    pub sha1: String,
+    pub size: u64,
+    #[serde(rename = "totalSize")]
+    pub total_size: u64,
+    pub url: String,
+}
+
+/// An asset index: every resource a version needs, keyed by its logical name.
+///
+/// Modern versions read assets straight from the hashed `objects/` store. Very old
+/// indexes additionally set `virtual` (assets are looked up by name under
+/// `assets/virtual/<index id>/`) or `map_to_resources` (assets are looked up by name
+/// under `<game dir>/resources/`); see [`AssetIndex::legacy_copies`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetIndex {
+    pub objects: HashMap<String, AssetObject>,
+    #[serde(rename = "virtual", default)]
+    pub is_virtual: bool,
+    #[serde(default)]
+    pub map_to_resources: bool,
+}
+
+/// A single asset, stored by content hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetObject {
+    pub hash: String,
+    pub size: u64,
+}
+
+impl AssetObject {
+    /// Path relative to `assets/objects/`: the first two hash characters, then the hash.
+    pub fn object_path(&self) -> String {
+        format!("{}/{}", &self.hash[..2.min(self.hash.len())], self.hash)
+    }
+
+    /// Download URL on Mojang's resource server.
+    pub fn url(&self) -> String {
+        self.url_from(RESOURCES_URL)
+    }
+
+    /// Download URL on a mirror of the resource server rooted at `base_url`.
+    pub fn url_from(&self, base_url: &str) -> String {
+        format!("{}/{}", base_url.trim_end_matches('/'), self.object_path())
+    }
+}
+
+impl AssetIndex {
+    /// Parses an asset index document.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Failed to parse asset index")
+    }
+
+    /// Fetches the index referenced by a version JSON and verifies its SHA-1.
+    pub async fn fetch(index: &AssetIndexRef) -> Result<Self> {
+        let body = fetch_verified(index).await?;
+        Self::from_json(std::str::from_utf8(&body)?)
+    }
+
+    /// Fetches the index like [`AssetIndex::fetch`] and stores it as
+    /// `assets_dir/indexes/<id>.json`, where the game expects to find it.
+    pub async fn fetch_into(index: &AssetIndexRef, assets_dir: impl AsRef<Path>) -> Result<Self> {
+        let body = fetch_verified(index).await?;
+        let parsed = Self::from_json(std::str::from_utf8(&body)?)?;
+        let indexes = assets_dir.as_ref().join("indexes");
+        fs::create_dir_all(&indexes).await?;
+        fs::write(indexes.join(format!("{}.json", index.id)), &body).await?;
+        Ok(parsed)
+    }
+
+    /// One download job per unique object, targeting `assets_dir/objects/<xx>/<hash>`.
+    ///
+    /// Objects shared by several names are only downloaded once. Jobs are sorted by
+    /// destination so the list is stable between runs.
+    pub fn download_jobs(&self, assets_dir: impl AsRef<Path>) -> Vec<DownloadJob> {
+        self.download_jobs_from(RESOURCES_URL, assets_dir)
+    }
+
+    /// Like [`AssetIndex::download_jobs`], but downloading from a mirror of the
+    /// resource server rooted at `base_url`.
+    pub fn download_jobs_from(&self, base_url: &str, assets_dir: impl AsRef<Path>) -> Vec<DownloadJob> {
+        let objects_dir = assets_dir.as_ref().join("objects");
+        let mut by_hash: HashMap<&str, &AssetObject> = HashMap::new();
+        for object in self.objects.values() {
+            by_hash.entry(object.hash.as_str()).or_insert(object);
+        }
+        let mut jobs: Vec<DownloadJob> = by_hash
+            .into_values()
+            .map(|object| {
+                DownloadJob::new(object.url_from(base_url), objects_dir.join(object.object_path()))
+                    .with_sha1(&object.hash)
+                    .with_size(object.size)
+            })
+            .collect();
+        jobs.sort_by(|a, b| a.dest.cmp(&b.dest));
+        jobs
+    }
+
+    /// Total size of all unique objects, in bytes.
+    pub fn total_size(&self) -> u64 {
+        self.download_jobs(PathBuf::new()).iter().filter_map(|job| job.size).sum()
+    }
+
+    /// For legacy indexes, the `(object, named copy)` pairs that must exist once the
+    /// objects are downloaded. Empty for modern indexes.
+    ///
+    /// `index_id` is the [`AssetIndexRef::id`] (e.g. `legacy` or `pre-1.6`).
+    pub fn legacy_copies(&self, assets_dir: impl AsRef<Path>, game_dir: impl AsRef<Path>, index_id: &str) -> Vec<(PathBuf, PathBuf)> {
+        let assets_dir = assets_dir.as_ref();
+        let target_dir = if self.map_to_resources {
+            game_dir.as_ref().join("resources")
+        } else if self.is_virtual {
+            assets_dir.join("virtual").join(index_id)
+        } else {
+            return Vec::new();
+        };
+
+        let objects_dir = assets_dir.join("objects");
+        let mut copies: Vec<(PathBuf, PathBuf)> = self
+            .objects
+            .iter()
+            .map(|(name, object)| (objects_dir.join(object.object_path()), target_dir.join(name)))
+            .collect();
+        copies.sort_by(|a, b| a.1.cmp(&b.1));
+        copies
+    }
+
+    /// Performs [`AssetIndex::legacy_copies`], skipping copies that already exist with
+    /// the right size.
+    pub async fn install_legacy_layout(&self, assets_dir: impl AsRef<Path>, game_dir: impl AsRef<Path>, index_id: &str) -> Result<()> {
+        for (object, target) in self.legacy_copies(assets_dir, game_dir, index_id) {
+            let source_len = fs::metadata(&object)
+                .await
+                .with_context(|| format!("Missing asset object {}", object.display()))?
+                .len();
+            if fs::metadata(&target).await.is_ok_and(|m| m.len() == source_len) {
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            fs::copy(&object, &target).await?;
+        }
+        Ok(())
+    }
+}
+
+async fn fetch_verified(index: &AssetIndexRef) -> Result<Vec<u8>> {
+    let body = http::get(&index.url)
+        .await
+        .with_context(|| format!("Failed to fetch asset index {}", index.id))?
+        .bytes()
+        .await?;
+    let actual = sha1_hex(&body);
+    if !actual.eq_ignore_ascii_case(&index.sha1) {
+        return Err(anyhow!(
+            "SHA-1 mismatch for asset index {} from {}: expected {}, got {}",
+            index.id,
+            index.url,
+            index.sha1,
+            actual
+        ));
+    }
+    Ok(body.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX_FIXTURE: &str = r#"{
+        "objects": {
+            "icons/icon_16x16.png": {"hash": "bdf48ef6b5d0d23bbb02e17d04865216179f510a", "size": 3665},
+            "minecraft/lang/en_us.json": {"hash": "0a1b2c3d4e5f60718293a4b5c6d7e8f901234567", "size": 421616},
+            "minecraft/sounds/ambient/cave/cave1.ogg": {"hash": "5681cfd5f3ae4dcbbe1a1fa44a5f74b5aece4b90", "size": 34000},
+            "minecraft/sounds/ambient/cave/cave1_copy.ogg": {"hash": "5681cfd5f3ae4dcbbe1a1fa44a5f74b5aece4b90", "size": 34000}
+        }
+    }"#;
+
+    const LEGACY_FIXTURE: &str = r#"{
+        "virtual": true,
+        "objects": {
+            "sound/step/grass1.ogg": {"hash": "227ab99bf7c6cf0b2002e0f7957d0ff7e5cb0c96", "size": 6462}
+        }
+    }"#;
+
+    const RESOURCES_FIXTURE: &str = r#"{
+        "map_to_resources": true,
+        "objects": {
+            "sound/step/grass1.ogg": {"hash": "227ab99bf7c6cf0b2002e0f7957d0ff7e5cb0c96", "size": 6462}
+        }
+    }"#;
+
+    #[test]
+    fn test_download_jobs_from_fixture() {
+        let index = AssetIndex::from_json(INDEX_FIXTURE).unwrap();
+        assert!(!index.is_virtual);
+        assert!(!index.map_to_resources);
+
+        let jobs = index.download_jobs("/game/assets");
+        // The two cave sounds share a hash and are only downloaded once.
+        assert_eq!(jobs.len(), 3);
+
+        let icon = jobs
+            .iter()
+            .find(|j| j.sha1.as_deref() == Some("bdf48ef6b5d0d23bbb02e17d04865216179f510a"))
+            .unwrap();
+        assert_eq!(icon.dest, Path::new("/game/assets/objects/bd/bdf48ef6b5d0d23bbb02e17d04865216179f510a"));
+        assert_eq!(
+            icon.url,
+            "https://resources.download.minecraft.net/bd/bdf48ef6b5d0d23bbb02e17d04865216179f510a"
+        );
+        assert_eq!(icon.size, Some(3665));
+
+        assert_eq!(index.total_size(), 3665 + 421616 + 34000);
+        assert!(index.legacy_copies("/game/assets", "/game", "1.20").is_empty());
+    }
+
+    #[test]
+    fn test_virtual_layout() {
+        let index = AssetIndex::from_json(LEGACY_FIXTURE).unwrap();
+        let copies = index.legacy_copies("/game/assets", "/game", "legacy");
+        assert_eq!(
+            copies,
+            vec![(
+                PathBuf::from("/game/assets/objects/22/227ab99bf7c6cf0b2002e0f7957d0ff7e5cb0c96"),
+                PathBuf::from("/game/assets/virtual/legacy/sound/step/grass1.ogg"),
+            )]
+        );
+    }
+
+    #[test]
+    fn test_map_to_resources_layout() {
+        let index = AssetIndex::from_json(RESOURCES_FIXTURE).unwrap();
+        let copies = index.legacy_copies("/game/assets", "/game", "pre-1.6");
+        assert_eq!(copies[0].1, PathBuf::from("/game/resources/sound/step/grass1.ogg"));
+    }
+
+    #[tokio::test]
+    async fn test_install_legacy_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let assets_dir = dir.path().join("assets");
+        let object = assets_dir.join("objects/22/227ab99bf7c6cf0b2002e0f7957d0ff7e5cb0c96");
+        std::fs::create_dir_all(object.parent().unwrap()).unwrap();
+        std::fs::write(&object, b"ogg").unwrap();
+
+        let index = AssetIndex::from_json(LEGACY_FIXTURE).unwrap();
+        index.install_legacy_layout(&assets_dir, dir.path(), "legacy").await.unwrap();
+
+        let copy = assets_dir.join("virtual/legacy/sound/step/grass1.ogg");
+        assert_eq!(std::fs::read(copy).unwrap(), b"ogg");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_asset_index() {
+        let manifest = crate::vanilla::VersionManifest::fetch().await.unwrap();
+        let detail = manifest.fetch_detail("1.20.1").await.unwrap();
+        let index_ref = detail.asset_index.unwrap();
+
+        let index = AssetIndex::fetch(&index_ref).await.unwrap();
+        assert!(!index.objects.is_empty());
+    }
+}
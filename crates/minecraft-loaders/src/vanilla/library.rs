@@ -0,0 +1,553 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::rules::{Rule, RuleContext, evaluate};
+use crate::download::DownloadJob;
+use crate::fabric::version_json::LibraryItem;
+use crate::maven::{MOJANG_REPOSITORY, MavenCoordinate, RepositoryList};
+
+/// A library entry from a version JSON.
+///
+/// Covers both Mojang entries, which carry explicit `downloads`, and loader entries
+/// (Fabric, Quilt) that only give a maven coordinate plus a repository `url`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Library {
+    /// Maven coordinate, `group:artifact:version[:classifier][@extension]`.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downloads: Option<LibraryDownloads>,
+    /// Maven repository base URL for entries without `downloads`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+    /// Map of OS name to native classifier, e.g. `windows` -> `natives-windows-${arch}`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub natives: HashMap<String, String>,
+    /// How a native jar is unpacked; only present alongside `natives`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extract: Option<ExtractRules>,
+}
+
+/// The `extract` block of a native library.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ExtractRules {
+    /// Entry name prefixes that must not be unpacked, e.g. `META-INF/`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct LibraryDownloads {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<Artifact>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub classifiers: HashMap<String, Artifact>,
+}
+
+/// A downloadable file with its repository-relative path and checksum.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    pub path: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+impl Library {
+    /// Whether this library's rules allow it in `ctx`.
+    ///
+    /// A LWJGL 3 native jar must also carry the [native classifier](RuleContext::native_classifier)
+    /// of `ctx`'s platform: version JSONs gate those on the OS alone, so without this
+    /// check an Apple Silicon Mac would get `natives-macos` next to `natives-macos-arm64`.
+    pub fn is_allowed(&self, ctx: &RuleContext) -> bool {
+        let native_matches = self.native_classifier().is_none_or(|classifier| classifier == ctx.native_classifier());
+        native_matches && evaluate(&self.rules, ctx)
+    }
+
+    /// The classifier of a LWJGL 3 style native jar, e.g. `natives-linux` for
+    /// `org.lwjgl:lwjgl:3.3.1:natives-linux`. `None` for other libraries, including
+    /// pre-1.19 entries that list their natives under [`Library::natives`].
+    pub fn native_classifier(&self) -> Option<String> {
+        self.coordinate()?
+            .classifier
+            .filter(|classifier| crate::platform::is_native_classifier(classifier))
+    }
+
+    /// The maven version of this library, if its coordinate is well formed.
+    pub fn version(&self) -> Option<&str> {
+        // Borrowed from `name` rather than the parsed coordinate, which owns its parts
+        self.coordinate()?;
+        self.name.split('@').next()?.split(':').nth(2)
+    }
+
+    /// The parsed maven coordinate of [`Library::name`].
+    pub fn coordinate(&self) -> Option<MavenCoordinate> {
+        MavenCoordinate::parse(&self.name).ok()
+    }
+
+    /// Key used to deduplicate libraries: `group:artifact`, plus the classifier if any,
+    /// so per-platform native jars are not collapsed into their main artifact.
+    pub fn dedup_key(&self) -> String {
+        match self.coordinate() {
+            Some(MavenCoordinate {
+                group,
+                artifact,
+                classifier: Some(classifier),
+                ..
+            }) => format!("{}:{}:{}", group, artifact, classifier),
+            Some(c) => format!("{}:{}", c.group, c.artifact),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Path of the main artifact relative to the `libraries` directory.
+    pub fn path(&self) -> Option<String> {
+        if let Some(artifact) = self.downloads.as_ref().and_then(|d| d.artifact.as_ref()) {
+            return Some(artifact.path.clone());
+        }
+        self.coordinate().map(|c| c.to_path())
+    }
+
+    /// Local path of the main artifact under `libraries_dir`.
+    pub fn local_path(&self, libraries_dir: impl AsRef<Path>) -> Option<PathBuf> {
+        self.path().map(|path| libraries_dir.as_ref().join(path))
+    }
+
+    /// The main artifact to download, built from the maven coordinate and repository
+    /// URL when the entry has no explicit `downloads.artifact`.
+    pub fn artifact(&self) -> Option<Artifact> {
+        if let Some(downloads) = &self.downloads {
+            return downloads.artifact.clone();
+        }
+        let coordinate = self.coordinate()?;
+        let path = coordinate.to_path();
+        let url = coordinate.to_url(self.url.as_deref().unwrap_or(MOJANG_REPOSITORY));
+        Some(Artifact {
+            path,
+            url,
+            sha1: self.sha1.clone(),
+            size: self.size,
+        })
+    }
+
+    /// The native classifier artifact for an OS, for pre-1.19 entries that list
+    /// natives under `downloads.classifiers`.
+    pub fn native_artifact(&self, ctx: &RuleContext) -> Option<&Artifact> {
+        let bits = if ctx.arch == "x86" { "32" } else { "64" };
+        let classifier = self.natives.get(&ctx.os)?.replace("${arch}", bits);
+        self.downloads.as_ref()?.classifiers.get(&classifier)
+    }
+}
+
+impl From<&LibraryItem> for Library {
+    fn from(item: &LibraryItem) -> Self {
+        Self {
+            name: item.name.clone(),
+            downloads: None,
+            url: Some(item.url.clone()),
+            sha1: item.sha1.clone(),
+            size: item.size.map(|size| size as u64),
+            rules: Vec::new(),
+            natives: HashMap::new(),
+            extract: None,
+        }
+    }
+}
+
+/// An ordered set of libraries, such as the vanilla libraries of a version or the
+/// extra libraries a loader profile adds.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LibrarySet {
+    pub libraries: Vec<Library>,
+}
+
+impl LibrarySet {
+    pub fn new(libraries: Vec<Library>) -> Self {
+        Self { libraries }
+    }
+
+    /// Libraries whose rules allow them in `ctx`.
+    pub fn allowed<'a>(&'a self, ctx: &'a RuleContext) -> impl Iterator<Item = &'a Library> + 'a {
+        self.libraries.iter().filter(move |l| l.is_allowed(ctx))
+    }
+
+    /// Local classpath entries allowed in `ctx`, in set order.
+    pub fn classpath(&self, libraries_dir: impl AsRef<Path>, ctx: &RuleContext) -> Vec<PathBuf> {
+        let libraries_dir = libraries_dir.as_ref();
+        self.allowed(ctx).filter_map(|l| l.local_path(libraries_dir)).collect()
+    }
+
+    /// Download jobs for every artifact allowed in `ctx`: main artifacts plus the
+    /// native classifier jars of pre-1.19 entries, placed under `libraries_dir`.
+    ///
+    /// Libraries without a resolvable artifact (e.g. natives-only entries) only
+    /// contribute their native jar.
+    pub fn download_jobs(&self, libraries_dir: impl AsRef<Path>, ctx: &RuleContext) -> Vec<DownloadJob> {
+        let libraries_dir = libraries_dir.as_ref();
+        let mut jobs = Vec::new();
+        for library in self.allowed(ctx) {
+            let artifacts = library.artifact().into_iter().chain(library.native_artifact(ctx).cloned());
+            for artifact in artifacts {
+                let mut job = DownloadJob::new(&artifact.url, libraries_dir.join(&artifact.path));
+                if let Some(sha1) = &artifact.sha1 {
+                    job = job.with_sha1(sha1);
+                }
+                if let Some(size) = artifact.size {
+                    job = job.with_size(size);
+                }
+                jobs.push(job);
+            }
+        }
+        jobs
+    }
+
+    /// Looks up the libraries that name only a maven coordinate in `repositories`:
+    /// entries with neither `downloads` nor a repository `url`, which would otherwise
+    /// be fetched from Mojang's repository, and artifacts with an empty `url`.
+    ///
+    /// Each is pointed at the first repository that has it. The names of libraries no
+    /// repository has are returned, and those entries are left unchanged.
+    pub async fn resolve_repositories(&mut self, repositories: &RepositoryList, client: &reqwest::Client) -> Vec<String> {
+        let mut unresolved = Vec::new();
+        for library in &mut self.libraries {
+            let needs_lookup = match &library.downloads {
+                Some(downloads) => downloads.artifact.as_ref().is_some_and(|artifact| artifact.url.is_empty()),
+                None => library.url.is_none(),
+            };
+            let Some(coordinate) = library.coordinate().filter(|_| needs_lookup) else {
+                continue;
+            };
+            let Some(repository) = repositories.find_repository(client, &coordinate).await else {
+                unresolved.push(library.name.clone());
+                continue;
+            };
+            match library.downloads.as_mut().and_then(|downloads| downloads.artifact.as_mut()) {
+                Some(artifact) => artifact.url = coordinate.to_url(repository),
+                None => library.url = Some(repository.to_string()),
+            }
+        }
+        unresolved
+    }
+
+    /// Merges `other` into this set.
+    ///
+    /// Libraries are deduplicated by [`Library::dedup_key`]. When both sets contain the
+    /// same library the higher version wins and takes the position of the first
+    /// occurrence; new libraries are appended in `other`'s order.
+    pub fn merge(&mut self, other: LibrarySet) {
+        let mut index: HashMap<String, usize> = self.libraries.iter().enumerate().map(|(i, l)| (l.dedup_key(), i)).collect();
+
+        for library in other.libraries {
+            let key = library.dedup_key();
+            match index.get(&key) {
+                Some(&i) => {
+                    let current = &self.libraries[i];
+                    if compare_versions(library.version(), current.version()) == Ordering::Greater {
+                        self.libraries[i] = library;
+                    }
+                }
+                None => {
+                    index.insert(key, self.libraries.len());
+                    self.libraries.push(library);
+                }
+            }
+        }
+    }
+
+    /// Consuming variant of [`LibrarySet::merge`].
+    pub fn merged(mut self, other: LibrarySet) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+impl FromIterator<Library> for LibrarySet {
+    fn from_iter<T: IntoIterator<Item = Library>>(iter: T) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+/// Compares library versions with [`crate::versions::compare_versions`], so that
+/// `9.7.1` < `9.10`. A missing version sorts lowest.
+pub(crate) fn compare_versions(a: Option<&str>, b: Option<&str>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => crate::versions::compare_versions(a, b),
+        (a, b) => a.is_some().cmp(&b.is_some()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::serve_files;
+
+    const LIBRARIES_FIXTURE: &str = r#"[
+        {
+            "name": "org.ow2.asm:asm:9.3",
+            "downloads": {
+                "artifact": {
+                    "path": "org/ow2/asm/asm/9.3/asm-9.3.jar",
+                    "sha1": "8e6300ef51c1d801a7ed62d07cd221aca3a90640",
+                    "size": 122004,
+                    "url": "https://libraries.minecraft.net/org/ow2/asm/asm/9.3/asm-9.3.jar"
+                }
+            }
+        },
+        {
+            "name": "org.lwjgl:lwjgl:3.3.1:natives-windows",
+            "downloads": {
+                "artifact": {
+                    "path": "org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-windows.jar",
+                    "sha1": "0036c37f16ab611b3aa11f3bcf80b1d509b4ce6b",
+                    "size": 159361,
+                    "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-windows.jar"
+                }
+            },
+            "rules": [{"action": "allow", "os": {"name": "windows"}}]
+        },
+        {
+            "name": "org.lwjgl:lwjgl:3.3.1:natives-linux",
+            "downloads": {
+                "artifact": {
+                    "path": "org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.jar",
+                    "sha1": "1de885aba434f934201b99f2f1afb142036ac189",
+                    "size": 110704,
+                    "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.jar"
+                }
+            },
+            "rules": [{"action": "allow", "os": {"name": "linux"}}]
+        },
+        {
+            "name": "ca.weblite:java-objc-bridge:1.1",
+            "rules": [
+                {"action": "allow"},
+                {"action": "disallow", "os": {"name": "osx"}}
+            ]
+        },
+        {
+            "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.4-nightly-20150209",
+            "natives": {"linux": "natives-linux", "windows": "natives-windows-${arch}"},
+            "downloads": {
+                "classifiers": {
+                    "natives-linux": {
+                        "path": "org/lwjgl/lwjgl/lwjgl-platform/2.9.4-nightly-20150209/lwjgl-platform-2.9.4-nightly-20150209-natives-linux.jar",
+                        "url": "https://libraries.minecraft.net/natives-linux.jar"
+                    },
+                    "natives-windows-64": {
+                        "path": "org/lwjgl/lwjgl/lwjgl-platform/2.9.4-nightly-20150209/lwjgl-platform-2.9.4-nightly-20150209-natives-windows-64.jar",
+                        "url": "https://libraries.minecraft.net/natives-windows-64.jar"
+                    }
+                }
+            }
+        }
+    ]"#;
+
+    fn fixture() -> LibrarySet {
+        serde_json::from_str(LIBRARIES_FIXTURE).unwrap()
+    }
+
+    fn names<'a>(libraries: impl Iterator<Item = &'a Library>) -> Vec<&'a str> {
+        libraries.map(|l| l.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_rules_for_windows_and_linux() {
+        let set = fixture();
+        let windows = names(set.allowed(&RuleContext::new("windows", "x86_64")));
+        let linux = names(set.allowed(&RuleContext::new("linux", "x86_64")));
+        let osx = names(set.allowed(&RuleContext::new("osx", "x86_64")));
+
+        assert!(windows.contains(&"org.lwjgl:lwjgl:3.3.1:natives-windows"));
+        assert!(!windows.contains(&"org.lwjgl:lwjgl:3.3.1:natives-linux"));
+        assert!(linux.contains(&"org.lwjgl:lwjgl:3.3.1:natives-linux"));
+        assert!(!linux.contains(&"org.lwjgl:lwjgl:3.3.1:natives-windows"));
+
+        assert!(windows.contains(&"ca.weblite:java-objc-bridge:1.1"));
+        assert!(!osx.contains(&"ca.weblite:java-objc-bridge:1.1"));
+        assert!(osx.contains(&"org.ow2.asm:asm:9.3"));
+    }
+
+    #[test]
+    fn test_paths_from_downloads_and_coordinates() {
+        let set = fixture();
+        assert_eq!(set.libraries[0].path().unwrap(), "org/ow2/asm/asm/9.3/asm-9.3.jar");
+        assert_eq!(
+            set.libraries[3].path().unwrap(),
+            "ca/weblite/java-objc-bridge/1.1/java-objc-bridge-1.1.jar"
+        );
+
+        let classpath = set.classpath("/game/libraries", &RuleContext::new("linux", "x86_64"));
+        assert_eq!(classpath[0], Path::new("/game/libraries/org/ow2/asm/asm/9.3/asm-9.3.jar"));
+        assert_eq!(classpath.len(), 4);
+
+        let extension = Library::from(&LibraryItem {
+            name: "net.minecraft:launchwrapper:1.12@zip".to_string(),
+            url: "https://maven.example.com/".to_string(),
+            sha1: None,
+            sha256: None,
+            sha512: None,
+            md5: None,
+            size: None,
+        });
+        let artifact = extension.artifact().unwrap();
+        assert_eq!(artifact.path, "net/minecraft/launchwrapper/1.12/launchwrapper-1.12.zip");
+        assert_eq!(
+            artifact.url,
+            "https://maven.example.com/net/minecraft/launchwrapper/1.12/launchwrapper-1.12.zip"
+        );
+    }
+
+    #[test]
+    fn test_download_jobs_include_natives() {
+        let set = fixture();
+        let jobs = set.download_jobs("/game/libraries", &RuleContext::new("linux", "x86_64"));
+        let urls: Vec<&str> = jobs.iter().map(|job| job.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://libraries.minecraft.net/org/ow2/asm/asm/9.3/asm-9.3.jar",
+                "https://libraries.minecraft.net/org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.jar",
+                "https://libraries.minecraft.net/ca/weblite/java-objc-bridge/1.1/java-objc-bridge-1.1.jar",
+                "https://libraries.minecraft.net/natives-linux.jar",
+            ]
+        );
+        assert_eq!(jobs[0].dest, Path::new("/game/libraries/org/ow2/asm/asm/9.3/asm-9.3.jar"));
+        assert_eq!(jobs[0].sha1.as_deref(), Some("8e6300ef51c1d801a7ed62d07cd221aca3a90640"));
+        assert_eq!(jobs[0].size, Some(122004));
+        assert_eq!(jobs[2].sha1, None);
+    }
+
+    #[test]
+    fn test_native_classifiers() {
+        let set = fixture();
+        let platform = &set.libraries[4];
+        assert!(
+            platform
+                .native_artifact(&RuleContext::new("windows", "x86_64"))
+                .unwrap()
+                .path
+                .ends_with("natives-windows-64.jar")
+        );
+        assert!(platform.native_artifact(&RuleContext::new("windows", "x86")).is_none());
+        assert!(platform.native_artifact(&RuleContext::new("linux", "x86_64")).is_some());
+        assert!(platform.native_artifact(&RuleContext::new("osx", "x86_64")).is_none());
+    }
+
+    #[test]
+    fn test_lwjgl3_natives_follow_platform_classifier() {
+        let native = |classifier: &str, os: &str| {
+            serde_json::from_value::<Library>(serde_json::json!({
+                "name": format!("org.lwjgl:lwjgl:3.3.3:{classifier}"),
+                "rules": [{"action": "allow", "os": {"name": os}}]
+            }))
+            .unwrap()
+        };
+        let set = LibrarySet::new(vec![
+            native("natives-macos", "osx"),
+            native("natives-macos-arm64", "osx"),
+            native("natives-windows", "windows"),
+            native("natives-windows-x86", "windows"),
+            native("natives-windows-arm64", "windows"),
+            native("natives-linux", "linux"),
+        ]);
+        let allowed = |os: &str, arch: &str| names(set.allowed(&RuleContext::new(os, arch)));
+
+        assert_eq!(allowed("osx", "arm64"), ["org.lwjgl:lwjgl:3.3.3:natives-macos-arm64"]);
+        assert_eq!(allowed("osx", "x86_64"), ["org.lwjgl:lwjgl:3.3.3:natives-macos"]);
+        assert_eq!(allowed("windows", "x86_64"), ["org.lwjgl:lwjgl:3.3.3:natives-windows"]);
+        assert_eq!(allowed("windows", "arm64"), ["org.lwjgl:lwjgl:3.3.3:natives-windows-arm64"]);
+        assert_eq!(allowed("linux", "x86_64"), ["org.lwjgl:lwjgl:3.3.3:natives-linux"]);
+        assert!(allowed("linux", "arm64").is_empty());
+
+        // Planning a Mac install from another machine only needs a context for the Mac.
+        let mac = RuleContext::default().with_os("osx").with_arch("arm64");
+        let jobs = set.download_jobs("/libraries", &mac);
+        assert_eq!(jobs.len(), 1);
+        assert!(jobs[0].url.ends_with("lwjgl-3.3.3-natives-macos-arm64.jar"), "{}", jobs[0].url);
+        assert_eq!(set.libraries[1].native_classifier().as_deref(), Some("natives-macos-arm64"));
+    }
+
+    fn fabric_library(name: &str) -> Library {
+        Library::from(&LibraryItem {
+            name: name.to_string(),
+            url: "https://maven.fabricmc.net/".to_string(),
+            sha1: None,
+            sha256: None,
+            sha512: None,
+            md5: None,
+            size: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_resolve_repositories_uses_first_repo_with_artifact() {
+        // 404s everything but the mixin jar.
+        let server = serve_files([("/org/spongepowered/mixin/0.8.5/mixin-0.8.5.jar", "")]);
+        let mirror = format!("{}/", server.base_url());
+
+        let mut set: LibrarySet = serde_json::from_str(
+            r#"[
+                {"name": "org.ow2.asm:asm:9.3", "downloads": {"artifact": {"path": "org/ow2/asm/asm/9.3/asm-9.3.jar", "url": "https://libraries.minecraft.net/org/ow2/asm/asm/9.3/asm-9.3.jar"}}},
+                {"name": "org.spongepowered:mixin:0.8.5"},
+                {"name": "net.fabricmc:sponge-mixin:0.13.3", "url": "https://maven.fabricmc.net/"},
+                {"name": "org.example:nowhere:1.0"}
+            ]"#,
+        )
+        .unwrap();
+        let repositories = RepositoryList::new().with_repository("http://127.0.0.1:9/").with_repository(&mirror);
+        let unresolved = set.resolve_repositories(&repositories, &reqwest::Client::new()).await;
+
+        assert_eq!(unresolved, vec!["org.example:nowhere:1.0".to_string()]);
+        assert_eq!(set.libraries[1].url.as_deref(), Some(mirror.as_str()));
+        assert_eq!(
+            set.libraries[1].artifact().unwrap().url,
+            format!("{}org/spongepowered/mixin/0.8.5/mixin-0.8.5.jar", mirror)
+        );
+        assert_eq!(set.libraries[2].url.as_deref(), Some("https://maven.fabricmc.net/"));
+        assert_eq!(set.libraries[3].url, None);
+    }
+
+    #[test]
+    fn test_merge_prefers_higher_version() {
+        let fabric = LibrarySet::new(vec![
+            fabric_library("org.ow2.asm:asm:9.10"),
+            fabric_library("net.fabricmc:fabric-loader:0.15.11"),
+        ]);
+        let merged = fixture().merged(fabric);
+
+        let asm: Vec<&Library> = merged.libraries.iter().filter(|l| l.dedup_key() == "org.ow2.asm:asm").collect();
+        assert_eq!(asm.len(), 1);
+        assert_eq!(asm[0].version(), Some("9.10"));
+        assert_eq!(merged.libraries[0].name, "org.ow2.asm:asm:9.10");
+        assert_eq!(merged.libraries.last().unwrap().name, "net.fabricmc:fabric-loader:0.15.11");
+
+        // Native jars share group:artifact but are distinct entries.
+        assert_eq!(merged.libraries.iter().filter(|l| l.name.starts_with("org.lwjgl:lwjgl:")).count(), 2);
+    }
+
+    #[test]
+    fn test_merge_keeps_newer_existing_version() {
+        let mut set = fixture();
+        set.merge(LibrarySet::new(vec![fabric_library("org.ow2.asm:asm:9.2")]));
+        assert_eq!(set.libraries[0].name, "org.ow2.asm:asm:9.3");
+        assert_eq!(set.libraries.len(), 5);
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions(Some("9.7.1"), Some("9.10")), Ordering::Less);
+        assert_eq!(compare_versions(Some("1.2"), Some("1.2.0")), Ordering::Less);
+        assert_eq!(compare_versions(Some("0.13.3+mixin.0.8.5"), Some("0.13.3+mixin.0.8.5")), Ordering::Equal);
+        assert_eq!(compare_versions(None, Some("1.0")), Ordering::Less);
+    }
+}
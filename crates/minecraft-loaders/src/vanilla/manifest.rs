@@ -0,0 +1,572 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use super::arguments::VersionArguments;
+use super::assets::AssetIndexRef;
+use super::library::LibrarySet;
+use crate::LoaderError;
+use crate::cache;
+use crate::http::{self, HttpClient};
+
+/// The Mojang runtime component for versions that predate `javaVersion`.
+const LEGACY_JAVA_COMPONENT: &str = "jre-legacy";
+/// The asset index assumed for versions that name none.
+const LEGACY_ASSET_INDEX: &str = "legacy";
+
+pub(crate) const MANIFEST_URL: &str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+
+/// The vanilla Minecraft version manifest published by Mojang.
+/// This is the source of truth for every game version and its launch JSON.
+///
+/// Forks and custom servers can publish their own manifest and load it with
+/// [`VersionManifest::fetch_from`]. It must follow Mojang's `version_manifest_v2.json`
+/// schema: a `latest` object with `release` and `snapshot` ids, and a `versions` array
+/// whose entries have `id`, `type`, `url`, `time` and `releaseTime` (`sha1` is optional).
+/// Each `url` must serve a version JSON that parses as a [`VersionDetail`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionManifest {
+    pub latest: LatestVersions,
+    pub versions: Vec<VersionEntry>,
+}
+
+/// The ids of the newest release and snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatestVersions {
+    pub release: String,
+    pub snapshot: String,
+}
+
+/// A single game version listed in the manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionEntry {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub release_type: VersionType,
+    /// Location of the per-version detail JSON.
+    pub url: String,
+    /// SHA-1 of the document at `url`; empty when the manifest leaves it out, as
+    /// `version_manifest.json` (v1) and some third-party manifests do.
+    #[serde(default)]
+    pub sha1: String,
+    pub time: chrono::DateTime<chrono::offset::Utc>,
+    #[serde(rename = "releaseTime")]
+    pub release_time: chrono::DateTime<chrono::offset::Utc>,
+    #[serde(rename = "complianceLevel", default)]
+    pub compliance_level: u8,
+}
+
+/// The per-version detail JSON referenced by [`VersionEntry::url`].
+///
+/// Only the fields needed to identify and launch a version are modelled; everything
+/// else (downloads, logging, ...) is kept verbatim in `extra`.
+///
+/// Both the modern schema and the legacy one of versions before 1.13 (and the even
+/// older pre-1.6 files without `assetIndex` or `downloads`) parse into this type.
+/// Use [`VersionDetail::launch_arguments`], [`VersionDetail::java`] and
+/// [`VersionDetail::asset_index_id`] to read them the same way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionDetail {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub release_type: VersionType,
+    #[serde(rename = "mainClass")]
+    pub main_class: String,
+    #[serde(default)]
+    pub assets: Option<String>,
+    #[serde(rename = "assetIndex", default)]
+    pub asset_index: Option<AssetIndexRef>,
+    #[serde(rename = "releaseTime")]
+    pub release_time: chrono::DateTime<chrono::offset::Utc>,
+    #[serde(default)]
+    pub libraries: LibrarySet,
+    /// Modern argument lists, present from 1.13 on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<VersionArguments>,
+    /// Legacy space-separated game arguments, used before 1.13.
+    #[serde(rename = "minecraftArguments", default, skip_serializing_if = "Option::is_none")]
+    pub minecraft_arguments: Option<String>,
+    /// The Java runtime the version was built for. Missing from versions older
+    /// than 1.17's launcher format, which all run on Java 8; see [`VersionDetail::java`].
+    #[serde(rename = "javaVersion", default, skip_serializing_if = "Option::is_none")]
+    pub java_version: Option<JavaVersion>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// An entry of a version JSON's `downloads` block, such as the client jar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionDownload {
+    pub sha1: String,
+    pub size: u64,
+    pub url: String,
+}
+
+/// The `type` of a version in the manifest and in version JSONs.
+///
+/// Types Mojang may introduce later deserialize as [`VersionType::Unknown`] instead
+/// of failing, and serialize back to the same string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum VersionType {
+    Release,
+    Snapshot,
+    OldBeta,
+    OldAlpha,
+    Unknown(String),
+}
+
+impl VersionType {
+    /// The string used for this type in Mojang's JSON, e.g. `old_beta`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Release => "release",
+            Self::Snapshot => "snapshot",
+            Self::OldBeta => "old_beta",
+            Self::OldAlpha => "old_alpha",
+            Self::Unknown(other) => other,
+        }
+    }
+}
+
+impl From<String> for VersionType {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "release" => Self::Release,
+            "snapshot" => Self::Snapshot,
+            "old_beta" => Self::OldBeta,
+            "old_alpha" => Self::OldAlpha,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<VersionType> for String {
+    fn from(value: VersionType) -> Self {
+        match value {
+            VersionType::Unknown(other) => other,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for VersionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The `javaVersion` block of a version JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JavaVersion {
+    /// Mojang runtime component, e.g. `java-runtime-gamma`.
+    pub component: String,
+    #[serde(rename = "majorVersion")]
+    pub major_version: u8,
+}
+
+impl VersionManifest {
+    /// Fetch the version manifest from Mojang's piston-meta service.
+    pub async fn fetch() -> Result<Self> {
+        Self::fetch_with_client(http::client()).await
+    }
+
+    /// Like [`VersionManifest::fetch`], but through a caller-provided HTTP client.
+    pub async fn fetch_with_client(client: &impl HttpClient) -> Result<Self> {
+        Ok(Self::fetch_from_url(client, MANIFEST_URL).await?)
+    }
+
+    /// Fetch a third-party manifest from `url` instead of Mojang's, e.g. one published
+    /// for a fork. See [`VersionManifest`] for the schema it must follow; version
+    /// details are then fetched from the URLs it lists.
+    pub async fn fetch_from(url: &str) -> Result<Self> {
+        Ok(Self::fetch_from_url(http::client(), url).await?)
+    }
+
+    /// Fetch a manifest with the same schema as Mojang's from `url`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "fetch_version_manifest", skip_all, fields(url = %url)))]
+    pub(crate) async fn fetch_from_url(client: &impl HttpClient, url: &str) -> Result<Self, LoaderError> {
+        client.get_json(url).await
+    }
+
+    /// Fetch the version manifest, caching it in `cache_dir`.
+    ///
+    /// A cached copy younger than `max_age` is used as is, and a stale one when Mojang
+    /// cannot be reached. In [offline mode](http::is_offline) any cached copy is used and
+    /// a missing one is a [`LoaderError::Offline`].
+    pub async fn fetch_cached(cache_dir: &Path, max_age: Duration) -> Result<Self> {
+        Self::fetch_cached_from(MANIFEST_URL, cache_dir, max_age).await
+    }
+
+    /// Like [`VersionManifest::fetch_cached`], but for the manifest at `url`. Each URL
+    /// is cached separately.
+    pub async fn fetch_cached_from(url: &str, cache_dir: &Path, max_age: Duration) -> Result<Self> {
+        let cache_path = cache_dir.join(cache::file_name("version-manifest", url));
+        cache::fetch_json(url, &cache_path, max_age, http::is_offline()).await
+    }
+
+    /// Look up a version by its id, e.g. `1.20.1` or `24w14a`.
+    pub fn version(&self, id: &str) -> Option<&VersionEntry> {
+        self.versions.iter().find(|v| v.id == id)
+    }
+
+    /// The entry for the newest release.
+    pub fn latest_release(&self) -> Option<&VersionEntry> {
+        self.version(&self.latest.release)
+    }
+
+    /// The entry for the newest snapshot.
+    pub fn latest_snapshot(&self) -> Option<&VersionEntry> {
+        self.version(&self.latest.snapshot)
+    }
+
+    /// All full releases, newest first.
+    pub fn releases(&self) -> impl Iterator<Item = &VersionEntry> {
+        self.filter_by_type(VersionType::Release)
+    }
+
+    /// All snapshots, newest first.
+    pub fn snapshots(&self) -> impl Iterator<Item = &VersionEntry> {
+        self.filter_by_type(VersionType::Snapshot)
+    }
+
+    /// All versions of the given type, newest first.
+    pub fn filter_by_type(&self, version_type: VersionType) -> impl Iterator<Item = &VersionEntry> {
+        self.versions.iter().filter(move |v| v.release_type == version_type)
+    }
+
+    /// All versions released after `date`, newest first, so version pickers can hide
+    /// ancient versions. Combine with a type filter via [`Iterator::filter`].
+    pub fn newer_than(&self, date: DateTime<Utc>) -> impl Iterator<Item = &VersionEntry> {
+        self.versions.iter().filter(move |v| v.release_time > date)
+    }
+
+    /// Fetch the detail JSON for the version with the given id.
+    pub async fn fetch_detail(&self, id: &str) -> Result<VersionDetail> {
+        let entry = self
+            .version(id)
+            .ok_or_else(|| anyhow!("Minecraft version {} not found in the manifest", id))?;
+        entry.fetch_detail().await
+    }
+}
+
+impl VersionDetail {
+    /// The game and JVM arguments in the modern form, converting the legacy
+    /// `minecraftArguments` string of versions before 1.13.
+    pub fn launch_arguments(&self) -> VersionArguments {
+        match &self.arguments {
+            Some(arguments) => arguments.clone(),
+            None => VersionArguments::from_legacy(self.minecraft_arguments.as_deref().unwrap_or_default()),
+        }
+    }
+
+    /// The Java runtime to launch the version with, Java 8 (`jre-legacy`) when the
+    /// JSON does not say.
+    pub fn java(&self) -> JavaVersion {
+        self.java_version.clone().unwrap_or_else(|| JavaVersion {
+            component: LEGACY_JAVA_COMPONENT.to_string(),
+            major_version: 8,
+        })
+    }
+
+    /// The id of the version's asset index: the `assetIndex` entry, else the
+    /// `assets` field, else `legacy` as the launcher assumed for pre-1.6 files.
+    pub fn asset_index_id(&self) -> &str {
+        self.asset_index
+            .as_ref()
+            .map(|index| index.id.as_str())
+            .or(self.assets.as_deref())
+            .unwrap_or(LEGACY_ASSET_INDEX)
+    }
+
+    /// The `downloads.<kind>` entry, where `kind` is e.g. `client` or `server`.
+    pub fn download(&self, kind: &str) -> Option<VersionDownload> {
+        let download = self.extra.get("downloads")?.get(kind)?;
+        serde_json::from_value(download.clone()).ok()
+    }
+}
+
+impl VersionEntry {
+    /// Fetch the detail JSON this entry points to.
+    pub async fn fetch_detail(&self) -> Result<VersionDetail> {
+        self.fetch_detail_with_client(http::client()).await
+    }
+
+    /// Like [`VersionEntry::fetch_detail`], but through a caller-provided HTTP client.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "fetch_version_detail", skip_all, fields(version = %self.id)))]
+    pub async fn fetch_detail_with_client(&self, client: &impl HttpClient) -> Result<VersionDetail> {
+        Ok(client.get_json(&self.url).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vanilla::Argument;
+    use test_support::{Response, serve};
+
+    const MANIFEST_FIXTURE: &str = r#"{
+        "latest": {"release": "1.20.4", "snapshot": "24w14a"},
+        "versions": [
+            {
+                "id": "24w14a",
+                "type": "snapshot",
+                "url": "https://piston-meta.mojang.com/v1/packages/aaaa/24w14a.json",
+                "time": "2024-04-03T12:29:32+00:00",
+                "releaseTime": "2024-04-03T12:19:54+00:00",
+                "sha1": "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "complianceLevel": 1
+            },
+            {
+                "id": "1.20.4",
+                "type": "release",
+                "url": "https://piston-meta.mojang.com/v1/packages/bbbb/1.20.4.json",
+                "time": "2024-03-26T12:15:03+00:00",
+                "releaseTime": "2023-12-07T12:56:20+00:00",
+                "sha1": "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+                "complianceLevel": 1
+            },
+            {
+                "id": "rd-132211",
+                "type": "old_alpha",
+                "url": "https://piston-meta.mojang.com/v1/packages/cccc/rd-132211.json",
+                "time": "2022-03-10T09:51:38+00:00",
+                "releaseTime": "2009-05-13T20:11:00+00:00",
+                "sha1": "cccccccccccccccccccccccccccccccccccccccc",
+                "complianceLevel": 0
+            }
+        ]
+    }"#;
+
+    const DETAIL_FIXTURE: &str = r#"{
+        "id": "1.20.4",
+        "type": "release",
+        "mainClass": "net.minecraft.client.main.Main",
+        "assets": "12",
+        "releaseTime": "2023-12-07T12:56:20+00:00",
+        "time": "2023-12-07T12:56:20+00:00",
+        "minimumLauncherVersion": 21,
+        "javaVersion": {"component": "java-runtime-gamma", "majorVersion": 17},
+        "libraries": []
+    }"#;
+
+    #[test]
+    fn test_deserialize_manifest_fixture() {
+        let manifest: VersionManifest = serde_json::from_str(MANIFEST_FIXTURE).unwrap();
+        assert_eq!(manifest.latest.release, "1.20.4");
+        assert_eq!(manifest.latest.snapshot, "24w14a");
+        assert_eq!(manifest.versions.len(), 3);
+
+        let release = manifest.version("1.20.4").unwrap();
+        assert!(release.url.ends_with("1.20.4.json"));
+        assert_eq!(release.sha1.len(), 40);
+        assert_eq!(release.release_time.to_rfc3339(), "2023-12-07T12:56:20+00:00");
+
+        assert_eq!(manifest.latest_release().unwrap().id, "1.20.4");
+        assert_eq!(manifest.latest_snapshot().unwrap().id, "24w14a");
+        assert!(manifest.version("0.0.1").is_none());
+    }
+
+    #[test]
+    fn test_deserialize_detail_fixture() {
+        let detail: VersionDetail = serde_json::from_str(DETAIL_FIXTURE).unwrap();
+        assert_eq!(detail.id, "1.20.4");
+        assert_eq!(detail.main_class, "net.minecraft.client.main.Main");
+        assert_eq!(detail.assets.as_deref(), Some("12"));
+        assert!(detail.libraries.libraries.is_empty());
+        assert!(!detail.extra.contains_key("libraries"));
+        assert!(detail.extra.contains_key("minimumLauncherVersion"));
+        assert_eq!(
+            detail.java_version,
+            Some(JavaVersion {
+                component: "java-runtime-gamma".to_string(),
+                major_version: 17
+            })
+        );
+        assert!(!detail.extra.contains_key("javaVersion"));
+    }
+
+    /// A 1.5.2 JSON as the 2013 launcher shipped it: no `assetIndex`, `downloads` or
+    /// `javaVersion`, and libraries given only by coordinate.
+    const LEGACY_DETAIL_FIXTURE: &str = r#"{
+        "id": "1.5.2",
+        "time": "2013-04-25T17:45:00+02:00",
+        "releaseTime": "2013-04-25T17:45:00+02:00",
+        "type": "release",
+        "minecraftArguments": "${auth_player_name} ${auth_session} --workDir ${game_directory}",
+        "minimumLauncherVersion": 4,
+        "libraries": [
+            {"name": "net.sf.jopt-simple:jopt-simple:4.5"},
+            {
+                "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.0",
+                "natives": {"linux": "natives-linux", "windows": "natives-windows", "osx": "natives-osx"},
+                "extract": {"exclude": ["META-INF/"]}
+            }
+        ],
+        "mainClass": "net.minecraft.launchwrapper.Launch"
+    }"#;
+
+    const MODERN_DETAIL_FIXTURE: &str = r#"{
+        "id": "1.20.1",
+        "type": "release",
+        "mainClass": "net.minecraft.client.main.Main",
+        "assets": "5",
+        "assetIndex": {"id": "5", "sha1": "c37be5a3b5d1e4a5b2a9f1d7f9e8c5b3f5b4a1d2", "size": 409252, "totalSize": 622384458, "url": "https://piston-meta.mojang.com/v1/packages/c37b/5.json"},
+        "releaseTime": "2023-06-12T13:25:51+00:00",
+        "javaVersion": {"component": "java-runtime-gamma", "majorVersion": 17},
+        "arguments": {
+            "game": ["--username", "${auth_player_name}", {"rules": [{"action": "allow", "features": {"is_demo_user": true}}], "value": "--demo"}],
+            "jvm": ["-cp", "${classpath}"]
+        },
+        "libraries": [{"name": "com.mojang:logging:1.1.1", "downloads": {"artifact": {"path": "com/mojang/logging/1.1.1/logging-1.1.1.jar", "sha1": "832b8e6674a9b325a5175a3a6267dfaf34c85139", "size": 15343, "url": "https://libraries.minecraft.net/com/mojang/logging/1.1.1/logging-1.1.1.jar"}}}]
+    }"#;
+
+    #[test]
+    fn test_legacy_and_modern_details_parse_alike() {
+        let legacy: VersionDetail = serde_json::from_str(LEGACY_DETAIL_FIXTURE).unwrap();
+        let modern: VersionDetail = serde_json::from_str(MODERN_DETAIL_FIXTURE).unwrap();
+
+        assert_eq!(legacy.libraries.libraries.len(), 2);
+        assert!(legacy.download("client").is_none());
+        assert_eq!(legacy.asset_index_id(), "legacy");
+        assert_eq!(modern.asset_index_id(), "5");
+
+        assert_eq!(legacy.java().major_version, 8);
+        assert_eq!(legacy.java().component, "jre-legacy");
+        assert_eq!(modern.java().major_version, 17);
+
+        let legacy_args = legacy.launch_arguments();
+        assert_eq!(
+            legacy_args.game,
+            vec![
+                Argument::from("${auth_player_name}"),
+                Argument::from("${auth_session}"),
+                Argument::from("--workDir"),
+                Argument::from("${game_directory}")
+            ]
+        );
+        assert!(legacy_args.jvm.contains(&Argument::from("${classpath}")));
+
+        let modern_args = modern.launch_arguments();
+        assert_eq!(Some(&modern_args), modern.arguments.as_ref());
+        assert_eq!(modern_args.game.len(), 3);
+        assert!(modern_args.declares_feature("is_demo_user"));
+    }
+
+    const ALL_TYPES_FIXTURE: &str = r#"{
+        "latest": {"release": "1.20.4", "snapshot": "24w14a"},
+        "versions": [
+            {"id": "24w14a", "type": "snapshot", "url": "", "sha1": "", "time": "2024-04-03T12:29:32+00:00", "releaseTime": "2024-04-03T12:19:54+00:00"},
+            {"id": "1.20.4", "type": "release", "url": "", "sha1": "", "time": "2024-03-26T12:15:03+00:00", "releaseTime": "2023-12-07T12:56:20+00:00"},
+            {"id": "1.0", "type": "release", "url": "", "sha1": "", "time": "2019-06-28T07:05:57+00:00", "releaseTime": "2011-11-17T22:00:00+00:00"},
+            {"id": "b1.8.1", "type": "old_beta", "url": "", "sha1": "", "time": "2019-06-28T07:05:57+00:00", "releaseTime": "2011-09-18T22:00:00+00:00"},
+            {"id": "rd-132211", "type": "old_alpha", "url": "", "sha1": "", "time": "2022-03-10T09:51:38+00:00", "releaseTime": "2009-05-13T20:11:00+00:00"},
+            {"id": "25x01a", "type": "experiment", "url": "", "sha1": "", "time": "2025-01-01T00:00:00+00:00", "releaseTime": "2025-01-01T00:00:00+00:00"}
+        ]
+    }"#;
+
+    fn ids<'a>(entries: impl Iterator<Item = &'a VersionEntry>) -> Vec<&'a str> {
+        entries.map(|v| v.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_filter_by_type() {
+        let manifest: VersionManifest = serde_json::from_str(ALL_TYPES_FIXTURE).unwrap();
+        assert_eq!(ids(manifest.releases()), vec!["1.20.4", "1.0"]);
+        assert_eq!(ids(manifest.snapshots()), vec!["24w14a"]);
+        assert_eq!(ids(manifest.filter_by_type(VersionType::OldBeta)), vec!["b1.8.1"]);
+        assert_eq!(ids(manifest.filter_by_type(VersionType::OldAlpha)), vec!["rd-132211"]);
+        assert_eq!(
+            ids(manifest.filter_by_type(VersionType::Unknown("experiment".to_string()))),
+            vec!["25x01a"]
+        );
+    }
+
+    #[test]
+    fn test_newer_than() {
+        let manifest: VersionManifest = serde_json::from_str(ALL_TYPES_FIXTURE).unwrap();
+        let cutoff = "2011-10-01T00:00:00+00:00".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(ids(manifest.newer_than(cutoff)), vec!["24w14a", "1.20.4", "1.0", "25x01a"]);
+        assert_eq!(
+            ids(manifest.newer_than(cutoff).filter(|v| v.release_type == VersionType::Release)),
+            vec!["1.20.4", "1.0"]
+        );
+    }
+
+    #[test]
+    fn test_version_type_round_trip() {
+        let types: Vec<VersionType> = serde_json::from_str(r#"["release", "snapshot", "old_beta", "old_alpha", "pending"]"#).unwrap();
+        assert_eq!(
+            types,
+            vec![
+                VersionType::Release,
+                VersionType::Snapshot,
+                VersionType::OldBeta,
+                VersionType::OldAlpha,
+                VersionType::Unknown("pending".to_string())
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&types).unwrap(),
+            r#"["release","snapshot","old_beta","old_alpha","pending"]"#
+        );
+        assert_eq!(VersionType::OldBeta.to_string(), "old_beta");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_custom_manifest() {
+        let detail = r#"{
+            "id": "b1.7.3-bta",
+            "type": "release",
+            "mainClass": "net.minecraft.client.Minecraft",
+            "releaseTime": "2024-06-01T00:00:00+00:00",
+            "libraries": [],
+            "minecraftArguments": "${auth_player_name} ${auth_session}"
+        }"#;
+        let server = serve(move |request| match request.path.as_str() {
+            // The manifest links to the detail on this same server, which the request is addressed to.
+            "/manifest.json" => Response::ok(format!(
+                r#"{{
+                    "latest": {{"release": "b1.7.3-bta", "snapshot": "b1.7.3-bta"}},
+                    "versions": [{{
+                        "id": "b1.7.3-bta", "type": "release", "url": "http://{}/b1.7.3-bta.json",
+                        "time": "2024-06-01T00:00:00+00:00", "releaseTime": "2024-06-01T00:00:00+00:00"
+                    }}]
+                }}"#,
+                request.header("host").unwrap_or_default()
+            )),
+            "/b1.7.3-bta.json" => Response::ok(detail),
+            _ => Response::not_found(),
+        });
+        let base = server.base_url();
+
+        let manifest = VersionManifest::fetch_from(&format!("{}/manifest.json", base)).await.unwrap();
+        assert_eq!(manifest.latest_release().unwrap().id, "b1.7.3-bta");
+        assert_eq!(manifest.versions[0].sha1, "");
+
+        let detail = manifest.fetch_detail("b1.7.3-bta").await.unwrap();
+        assert_eq!(detail.main_class, "net.minecraft.client.Minecraft");
+        assert_eq!(detail.java().major_version, 8);
+
+        assert!(VersionManifest::fetch_from(&format!("{}/missing.json", base)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_manifest() {
+        let manifest = VersionManifest::fetch().await.unwrap();
+        assert!(manifest.latest_release().is_some());
+        assert!(manifest.latest_snapshot().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_detail() {
+        let manifest = VersionManifest::fetch().await.unwrap();
+        let detail = manifest.fetch_detail("1.20.1").await.unwrap();
+        assert_eq!(detail.id, "1.20.1");
+        assert!(!detail.main_class.is_empty());
+    }
+}
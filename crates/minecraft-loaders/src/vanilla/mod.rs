@@ -0,0 +1,13 @@
+pub mod arguments;
+pub mod assets;
+pub mod library;
+pub mod manifest;
+pub mod natives;
+pub mod rules;
+
+pub use arguments::{Argument, ArgumentValue, VersionArguments};
+pub use assets::{AssetIndex, AssetIndexRef, AssetObject};
+pub use library::{Artifact, ExtractRules, Library, LibraryDownloads, LibrarySet};
+pub use manifest::{JavaVersion, LatestVersions, VersionDetail, VersionDownload, VersionEntry, VersionManifest, VersionType};
+pub use natives::{NativesCache, extract_natives, extract_natives_cached};
+pub use rules::{Rule, RuleAction, RuleContext, evaluate};
@@ -0,0 +1,369 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+use super::library::LibrarySet;
+use super::rules::RuleContext;
+use crate::download::{HashAlgo, file_hash_blocking};
+
+/// Name of the file listing an entry's contents, written once the entry is complete.
+const ENTRIES_FILE: &str = ".entries";
+
+/// Unpacks the platform natives of `libraries` into `target`, for `-Djava.library.path`.
+///
+/// Only libraries with a `natives` classifier for `ctx` are considered; their native
+/// jars are read from `libraries_dir`, where they were downloaded. `target` is wiped
+/// first so natives from a previous version cannot linger, and entries matching the
+/// library's `extract.exclude` prefixes (typically `META-INF/`) are skipped.
+///
+/// LWJGL 3 native jars (1.19+) are not unpacked here: the game loads them from the
+/// classpath, where [`LibrarySet::allowed`] has already narrowed them to the jar for
+/// [`RuleContext::native_classifier`].
+///
+/// Returns the paths of the extracted files.
+pub fn extract_natives(libraries: &LibrarySet, libraries_dir: impl AsRef<Path>, target: impl AsRef<Path>, ctx: &RuleContext) -> Result<Vec<PathBuf>> {
+    let libraries_dir = libraries_dir.as_ref();
+    let target = target.as_ref();
+    clean_dir(target)?;
+
+    let mut extracted = Vec::new();
+    for library in libraries.allowed(ctx) {
+        let Some(artifact) = library.native_artifact(ctx) else {
+            continue;
+        };
+        let jar = libraries_dir.join(&artifact.path);
+        let excludes = library.extract.as_ref().map(|e| e.exclude.as_slice()).unwrap_or_default();
+        extract_jar(&jar, target, excludes, &mut extracted).with_context(|| format!("Failed to extract natives from {}", jar.display()))?;
+    }
+    Ok(extracted)
+}
+
+/// Like [`extract_natives`], but unpacks each native jar once into a store shared by
+/// every instance and links the files into `target` from there.
+///
+/// See [`NativesCache`] for how entries are keyed, linked and validated.
+pub fn extract_natives_cached(
+    libraries: &LibrarySet,
+    libraries_dir: impl AsRef<Path>,
+    target: impl AsRef<Path>,
+    ctx: &RuleContext,
+    cache: &NativesCache,
+) -> Result<Vec<PathBuf>> {
+    let libraries_dir = libraries_dir.as_ref();
+    let target = target.as_ref();
+    clean_dir(target)?;
+
+    let mut extracted = Vec::new();
+    for library in libraries.allowed(ctx) {
+        let Some(artifact) = library.native_artifact(ctx) else {
+            continue;
+        };
+        let jar = libraries_dir.join(&artifact.path);
+        let excludes = library.extract.as_ref().map(|e| e.exclude.as_slice()).unwrap_or_default();
+        let entry = cache.entry_dir(&artifact.path);
+        let files = match cache.read_entry(&entry) {
+            Some(files) => files,
+            None => cache
+                .populate(&jar, excludes, &entry)
+                .with_context(|| format!("Failed to extract natives from {}", jar.display()))?,
+        };
+        for relative in files {
+            let dest = target.join(&relative);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            link_or_copy(&entry.join(&relative), &dest)?;
+            extracted.push(dest);
+        }
+    }
+    Ok(extracted)
+}
+
+/// A directory of unpacked native jars shared across instances, so many instances on
+/// the same LWJGL version do not each extract their own copy.
+///
+/// Entries are keyed by the native jar's library path, which already carries the
+/// library version and the platform classifier (e.g.
+/// `org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux`). An entry is only used when
+/// its `.entries` listing is present and every listed file still has the recorded
+/// size and SHA-1; otherwise the jar is extracted again. Files are hardlinked into
+/// the instance, falling back to a symlink and then a copy where links are not
+/// possible (e.g. across filesystems).
+#[derive(Debug, Clone)]
+pub struct NativesCache {
+    root: PathBuf,
+}
+
+impl NativesCache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The directory holding the unpacked contents of the native jar at `artifact_path`.
+    pub fn entry_dir(&self, artifact_path: &str) -> PathBuf {
+        let key = artifact_path.strip_suffix(".jar").unwrap_or(artifact_path);
+        self.root.join(key)
+    }
+
+    /// The relative paths of a complete, intact entry, or `None` if it must be rebuilt.
+    fn read_entry(&self, entry: &Path) -> Option<Vec<PathBuf>> {
+        let listing = fs::read_to_string(entry.join(ENTRIES_FILE)).ok()?;
+        let mut files = Vec::new();
+        for line in listing.lines().filter(|line| !line.is_empty()) {
+            let mut parts = line.splitn(3, ' ');
+            let (sha1, size, relative) = (parts.next()?, parts.next()?.parse::<u64>().ok()?, parts.next()?);
+            let path = entry.join(relative);
+            if fs::metadata(&path).ok()?.len() != size || file_hash_blocking(&path, HashAlgo::Sha1).ok()? != sha1 {
+                return None;
+            }
+            files.push(PathBuf::from(relative));
+        }
+        Some(files)
+    }
+
+    /// Extracts `jar` into a fresh entry, replacing whatever was there.
+    fn populate(&self, jar: &Path, excludes: &[String], entry: &Path) -> Result<Vec<PathBuf>> {
+        // Build next to the entry and swap it in, so a crash part way leaves no listing.
+        let name = entry.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let staging = entry.with_file_name(format!("{}.partial-{}", name, std::process::id()));
+        clean_dir(&staging)?;
+
+        let mut extracted = Vec::new();
+        extract_jar(jar, &staging, excludes, &mut extracted)?;
+        extracted.sort();
+        extracted.dedup();
+
+        let mut files = Vec::new();
+        let mut listing = String::new();
+        for path in extracted {
+            let relative = path.strip_prefix(&staging)?.to_path_buf();
+            let line = format!(
+                "{} {} {}\n",
+                file_hash_blocking(&path, HashAlgo::Sha1)?,
+                fs::metadata(&path)?.len(),
+                relative.to_string_lossy().replace('\\', "/")
+            );
+            listing.push_str(&line);
+            files.push(relative);
+        }
+        fs::write(staging.join(ENTRIES_FILE), listing)?;
+
+        match fs::remove_dir_all(entry) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+        fs::rename(&staging, entry).with_context(|| format!("Failed to store natives cache entry {}", entry.display()))?;
+        Ok(files)
+    }
+}
+
+/// Removes `dir` if it exists and creates it empty.
+fn clean_dir(dir: &Path) -> Result<()> {
+    match fs::remove_dir_all(dir) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => {
+            return Err(err).with_context(|| format!("Failed to clean natives directory {}", dir.display()));
+        }
+        _ => {}
+    }
+    fs::create_dir_all(dir)?;
+    Ok(())
+}
+
+fn link_or_copy(source: &Path, dest: &Path) -> Result<()> {
+    if fs::hard_link(source, dest).is_ok() {
+        return Ok(());
+    }
+    #[cfg(unix)]
+    if std::os::unix::fs::symlink(source, dest).is_ok() {
+        return Ok(());
+    }
+    #[cfg(windows)]
+    if std::os::windows::fs::symlink_file(source, dest).is_ok() {
+        return Ok(());
+    }
+    fs::copy(source, dest).with_context(|| format!("Failed to copy {} to {}", source.display(), dest.display()))?;
+    Ok(())
+}
+
+fn extract_jar(jar: &Path, target: &Path, excludes: &[String], extracted: &mut Vec<PathBuf>) -> Result<()> {
+    let mut archive = ZipArchive::new(fs::File::open(jar)?)?;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() || excludes.iter().any(|prefix| entry.name().starts_with(prefix.as_str())) {
+            continue;
+        }
+        // `enclosed_name` rejects absolute paths and `..` components.
+        let Some(relative) = entry.enclosed_name() else {
+            continue;
+        };
+        let dest = target.join(relative);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut entry, &mut fs::File::create(&dest)?)?;
+        extracted.push(dest);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    const LIBRARY_FIXTURE: &str = r#"[{
+        "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.4",
+        "natives": {"linux": "natives-linux", "windows": "natives-windows"},
+        "extract": {"exclude": ["META-INF/"]},
+        "downloads": {
+            "classifiers": {
+                "natives-linux": {
+                    "path": "org/lwjgl/lwjgl/lwjgl-platform/2.9.4/lwjgl-platform-2.9.4-natives-linux.jar",
+                    "url": "https://libraries.minecraft.net/natives-linux.jar"
+                },
+                "natives-windows": {
+                    "path": "org/lwjgl/lwjgl/lwjgl-platform/2.9.4/lwjgl-platform-2.9.4-natives-windows.jar",
+                    "url": "https://libraries.minecraft.net/natives-windows.jar"
+                }
+            }
+        }
+    }]"#;
+
+    fn write_jar(path: &Path, entries: &[(&str, &[u8])]) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut zip = ZipWriter::new(fs::File::create(path).unwrap());
+        for (name, contents) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_extract_natives_skips_excluded_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let libraries_dir = dir.path().join("libraries");
+        write_jar(
+            &libraries_dir.join("org/lwjgl/lwjgl/lwjgl-platform/2.9.4/lwjgl-platform-2.9.4-natives-linux.jar"),
+            &[
+                ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0" as &[u8]),
+                ("liblwjgl.so", b"elf" as &[u8]),
+                ("libopenal.so", b"elf" as &[u8]),
+            ],
+        );
+
+        let target = dir.path().join("natives");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("stale.so"), b"old").unwrap();
+
+        let libraries: LibrarySet = serde_json::from_str(LIBRARY_FIXTURE).unwrap();
+        let extracted = extract_natives(&libraries, &libraries_dir, &target, &RuleContext::new("linux", "x86_64")).unwrap();
+
+        assert_eq!(extracted.len(), 2);
+        assert_eq!(fs::read(target.join("liblwjgl.so")).unwrap(), b"elf");
+        assert!(target.join("libopenal.so").exists());
+        assert!(!target.join("META-INF").exists());
+        assert!(!target.join("stale.so").exists());
+    }
+
+    #[test]
+    fn test_extract_natives_cached_reuses_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let libraries_dir = dir.path().join("libraries");
+        let jar = libraries_dir.join("org/lwjgl/lwjgl/lwjgl-platform/2.9.4/lwjgl-platform-2.9.4-natives-linux.jar");
+        write_jar(
+            &jar,
+            &[
+                ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0" as &[u8]),
+                ("liblwjgl.so", b"elf" as &[u8]),
+            ],
+        );
+
+        let libraries: LibrarySet = serde_json::from_str(LIBRARY_FIXTURE).unwrap();
+        let ctx = RuleContext::new("linux", "x86_64");
+        let cache = NativesCache::new(dir.path().join("cache"));
+        let first = dir.path().join("first/natives");
+        extract_natives_cached(&libraries, &libraries_dir, &first, &ctx, &cache).unwrap();
+
+        let entry = cache.entry_dir("org/lwjgl/lwjgl/lwjgl-platform/2.9.4/lwjgl-platform-2.9.4-natives-linux.jar");
+        assert_eq!(fs::read(entry.join("liblwjgl.so")).unwrap(), b"elf");
+        assert!(!entry.join("META-INF").exists());
+
+        // With the jar gone, the second instance can only be served from the cache.
+        fs::remove_file(&jar).unwrap();
+        let second = dir.path().join("second/natives");
+        let extracted = extract_natives_cached(&libraries, &libraries_dir, &second, &ctx, &cache).unwrap();
+        assert_eq!(extracted, vec![second.join("liblwjgl.so")]);
+        assert_eq!(fs::read(second.join("liblwjgl.so")).unwrap(), b"elf");
+        assert_eq!(fs::read(first.join("liblwjgl.so")).unwrap(), b"elf");
+    }
+
+    #[test]
+    fn test_extract_natives_cached_rebuilds_corrupt_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let libraries_dir = dir.path().join("libraries");
+        write_jar(
+            &libraries_dir.join("org/lwjgl/lwjgl/lwjgl-platform/2.9.4/lwjgl-platform-2.9.4-natives-linux.jar"),
+            &[("liblwjgl.so", b"elf" as &[u8]), ("libopenal.so", b"elf" as &[u8])],
+        );
+
+        let libraries: LibrarySet = serde_json::from_str(LIBRARY_FIXTURE).unwrap();
+        let ctx = RuleContext::new("linux", "x86_64");
+        let cache = NativesCache::new(dir.path().join("cache"));
+        let target = dir.path().join("natives");
+        extract_natives_cached(&libraries, &libraries_dir, &target, &ctx, &cache).unwrap();
+
+        // Same size, different bytes, plus a missing file.
+        let entry = cache.entry_dir("org/lwjgl/lwjgl/lwjgl-platform/2.9.4/lwjgl-platform-2.9.4-natives-linux.jar");
+        fs::remove_file(entry.join("liblwjgl.so")).unwrap();
+        fs::write(entry.join("liblwjgl.so"), b"bad").unwrap();
+        fs::remove_file(entry.join("libopenal.so")).unwrap();
+
+        let extracted = extract_natives_cached(&libraries, &libraries_dir, &target, &ctx, &cache).unwrap();
+        assert_eq!(extracted.len(), 2);
+        assert_eq!(fs::read(target.join("liblwjgl.so")).unwrap(), b"elf");
+        assert_eq!(fs::read(target.join("libopenal.so")).unwrap(), b"elf");
+        assert_eq!(fs::read(entry.join("liblwjgl.so")).unwrap(), b"elf");
+        assert!(entry.join("libopenal.so").exists());
+    }
+
+    #[test]
+    fn test_extract_natives_cached_ignores_incomplete_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let libraries_dir = dir.path().join("libraries");
+        write_jar(
+            &libraries_dir.join("org/lwjgl/lwjgl/lwjgl-platform/2.9.4/lwjgl-platform-2.9.4-natives-linux.jar"),
+            &[("liblwjgl.so", b"elf" as &[u8])],
+        );
+        let cache = NativesCache::new(dir.path().join("cache"));
+        let entry = cache.entry_dir("org/lwjgl/lwjgl/lwjgl-platform/2.9.4/lwjgl-platform-2.9.4-natives-linux.jar");
+        fs::create_dir_all(&entry).unwrap();
+        fs::write(entry.join("liblwjgl.so"), b"half").unwrap();
+
+        let libraries: LibrarySet = serde_json::from_str(LIBRARY_FIXTURE).unwrap();
+        let target = dir.path().join("natives");
+        extract_natives_cached(&libraries, &libraries_dir, &target, &RuleContext::new("linux", "x86_64"), &cache).unwrap();
+        assert_eq!(fs::read(target.join("liblwjgl.so")).unwrap(), b"elf");
+        assert!(entry.join(ENTRIES_FILE).exists());
+    }
+
+    #[test]
+    fn test_extract_natives_ignores_other_platforms() {
+        let dir = tempfile::tempdir().unwrap();
+        let libraries: LibrarySet = serde_json::from_str(LIBRARY_FIXTURE).unwrap();
+        let target = dir.path().join("natives");
+
+        // No osx classifier, so nothing is read and the missing jars do not matter.
+        let extracted = extract_natives(&libraries, dir.path(), &target, &RuleContext::new("osx", "x86_64")).unwrap();
+        assert!(extracted.is_empty());
+        assert!(target.is_dir());
+    }
+}
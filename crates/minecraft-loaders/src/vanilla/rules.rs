@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A Mojang-style rule gating a library or argument on the running platform and
+/// launcher features.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rule {
+    pub action: RuleAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<OsRule>,
+    /// Launcher features the rule requires to be on (`true`) or off (`false`),
+    /// e.g. `is_demo_user` or `has_custom_resolution`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub features: HashMap<String, bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Allow,
+    Disallow,
+}
+
+/// The `os` condition of a [`Rule`]. Every field that is present must match.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct OsRule {
+    /// `windows`, `osx` or `linux`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Only ever `x86` in practice, meaning a 32-bit JVM.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    /// A regex over the OS version. Not evaluated; treated as matching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// The platform and enabled launcher features rules are evaluated against.
+///
+/// [`RuleContext::default`] describes the machine this binary was built for with no
+/// features enabled; override the fields to evaluate rules for another platform.
+///
+/// ```
+/// use minecraft_modloaders::vanilla::rules::RuleContext;
+///
+/// let ctx = RuleContext::default().with_os("osx").with_feature("is_demo_user");
+/// assert_eq!(ctx.os, "osx");
+/// assert!(ctx.has_feature("is_demo_user"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleContext {
+    /// Mojang OS name: `windows`, `osx` or `linux`.
+    pub os: String,
+    /// Mojang architecture name, e.g. `x86` or `x86_64`.
+    pub arch: String,
+    pub features: HashSet<String>,
+}
+
+impl Default for RuleContext {
+    fn default() -> Self {
+        Self {
+            os: current_os().to_string(),
+            arch: current_arch().to_string(),
+            features: HashSet::new(),
+        }
+    }
+}
+
+impl RuleContext {
+    /// A context for the given OS and architecture with no features enabled.
+    pub fn new(os: impl Into<String>, arch: impl Into<String>) -> Self {
+        Self {
+            os: os.into(),
+            arch: arch.into(),
+            features: HashSet::new(),
+        }
+    }
+
+    pub fn with_os(mut self, os: impl Into<String>) -> Self {
+        self.os = os.into();
+        self
+    }
+
+    pub fn with_arch(mut self, arch: impl Into<String>) -> Self {
+        self.arch = arch.into();
+        self
+    }
+
+    /// Enable a launcher feature such as `is_demo_user`.
+    pub fn with_feature(mut self, feature: impl Into<String>) -> Self {
+        self.features.insert(feature.into());
+        self
+    }
+
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.contains(feature)
+    }
+
+    /// The LWJGL 3 native classifier for this context's OS and architecture, such as
+    /// `natives-macos-arm64`; see [`crate::platform::native_classifier_for`].
+    pub fn native_classifier(&self) -> &'static str {
+        crate::platform::native_classifier_for(&self.os, &self.arch)
+    }
+}
+
+impl Rule {
+    /// Whether every condition of this rule holds in `ctx`.
+    pub fn matches(&self, ctx: &RuleContext) -> bool {
+        let os_matches = self
+            .os
+            .as_ref()
+            .is_none_or(|os| os.name.as_deref().is_none_or(|name| name == ctx.os) && os.arch.as_deref().is_none_or(|arch| arch == ctx.arch));
+        os_matches && self.features.iter().all(|(feature, required)| ctx.has_feature(feature) == *required)
+    }
+}
+
+/// Evaluates a rule list the way the vanilla launcher does.
+///
+/// An empty list allows everything. Otherwise the result starts as disallowed and
+/// every matching rule overrides it with its own action, so the last match wins.
+pub fn evaluate(rules: &[Rule], ctx: &RuleContext) -> bool {
+    if rules.is_empty() {
+        return true;
+    }
+    rules
+        .iter()
+        .rev()
+        .find(|rule| rule.matches(ctx))
+        .is_some_and(|rule| rule.action == RuleAction::Allow)
+}
+
+/// The Mojang name for the OS this binary was built for.
+pub fn current_os() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "osx",
+        "windows" => "windows",
+        _ => "linux",
+    }
+}
+
+/// The Mojang name for the architecture this binary was built for.
+pub fn current_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86" => "x86",
+        "aarch64" => "arm64",
+        _ => "x86_64",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_rules(json: &str) -> Vec<Rule> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_allow_only_osx() {
+        let rules = parse_rules(r#"[{"action": "allow", "os": {"name": "osx"}}]"#);
+        assert!(evaluate(&rules, &RuleContext::new("osx", "x86_64")));
+        assert!(!evaluate(&rules, &RuleContext::new("windows", "x86_64")));
+        assert!(!evaluate(&rules, &RuleContext::new("linux", "x86_64")));
+    }
+
+    #[test]
+    fn test_disallow_overrides_earlier_allow() {
+        let rules = parse_rules(r#"[{"action": "allow"}, {"action": "disallow", "os": {"name": "osx"}}]"#);
+        assert!(evaluate(&rules, &RuleContext::new("linux", "x86_64")));
+        assert!(!evaluate(&rules, &RuleContext::new("osx", "x86_64")));
+    }
+
+    #[test]
+    fn test_arch_rule() {
+        let rules = parse_rules(r#"[{"action": "allow", "os": {"arch": "x86"}}]"#);
+        assert!(evaluate(&rules, &RuleContext::new("windows", "x86")));
+        assert!(!evaluate(&rules, &RuleContext::new("windows", "x86_64")));
+    }
+
+    #[test]
+    fn test_feature_gated_demo_arguments() {
+        let rules = parse_rules(r#"[{"action": "allow", "features": {"is_demo_user": true}}]"#);
+        let ctx = RuleContext::new("linux", "x86_64");
+        assert!(!evaluate(&rules, &ctx));
+        assert!(evaluate(&rules, &ctx.with_feature("is_demo_user")));
+
+        let rules = parse_rules(r#"[{"action": "allow", "features": {"has_custom_resolution": false}}]"#);
+        assert!(evaluate(&rules, &RuleContext::new("linux", "x86_64")));
+    }
+
+    #[test]
+    fn test_empty_rules_allow() {
+        assert!(evaluate(&[], &RuleContext::default()));
+    }
+}
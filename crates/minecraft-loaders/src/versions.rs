@@ -0,0 +1,293 @@
+//! Version lists of every supported loader at once, for version pickers, and the
+//! comparison used to sort them.
+
+use std::cmp::Ordering;
+
+use crate::LoaderError;
+use crate::fabric::FabricVersions;
+use crate::forge::ForgeVersions;
+use crate::http;
+use crate::neoforge::NeoForgeVersions;
+use crate::quilt::QuiltVersions;
+use crate::vanilla::VersionManifest;
+
+/// Where [`fetch_all_loaders_from`] looks up each loader's versions.
+///
+/// The defaults are the official services; override single fields to use mirrors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoaderEndpoints {
+    /// Root of the Fabric meta API, e.g. `https://meta.fabricmc.net/`.
+    pub fabric_meta: String,
+    /// Root of the Quilt meta API, e.g. `https://meta.quiltmc.org/`.
+    pub quilt_meta: String,
+    /// Forge's `maven-metadata.json`.
+    pub forge_versions: String,
+    /// NeoForge's Maven API `versions` endpoint.
+    pub neoforge_versions: String,
+    /// The vanilla version manifest.
+    pub version_manifest: String,
+}
+
+impl Default for LoaderEndpoints {
+    fn default() -> Self {
+        Self {
+            fabric_meta: crate::fabric::DEFAULT_META_URL.to_string(),
+            quilt_meta: crate::quilt::loader::DEFAULT_META_URL.to_string(),
+            forge_versions: crate::forge::loader::VERSIONS_URL.to_string(),
+            neoforge_versions: crate::neoforge::loader::VERSIONS_URL.to_string(),
+            version_manifest: crate::vanilla::manifest::MANIFEST_URL.to_string(),
+        }
+    }
+}
+
+/// The outcome of fetching every loader's versions, one result per loader.
+///
+/// One service being down does not affect the others, so a version screen can show
+/// what loaded and report the rest.
+#[derive(Debug)]
+pub struct AllLoaderVersions {
+    pub vanilla: Result<VersionManifest, LoaderError>,
+    pub fabric: Result<FabricVersions, LoaderError>,
+    pub quilt: Result<QuiltVersions, LoaderError>,
+    pub forge: Result<ForgeVersions, LoaderError>,
+    pub neoforge: Result<NeoForgeVersions, LoaderError>,
+}
+
+impl AllLoaderVersions {
+    /// The loaders that failed to load, by name, with their errors.
+    pub fn failures(&self) -> Vec<(&'static str, &LoaderError)> {
+        [
+            ("vanilla", self.vanilla.as_ref().err()),
+            ("fabric", self.fabric.as_ref().err()),
+            ("quilt", self.quilt.as_ref().err()),
+            ("forge", self.forge.as_ref().err()),
+            ("neoforge", self.neoforge.as_ref().err()),
+        ]
+        .into_iter()
+        .filter_map(|(name, err)| err.map(|err| (name, err)))
+        .collect()
+    }
+
+    /// Whether every loader's versions were fetched.
+    pub fn is_complete(&self) -> bool {
+        self.failures().is_empty()
+    }
+}
+
+/// Fetch the versions of vanilla and every supported loader concurrently.
+pub async fn fetch_all_loaders() -> AllLoaderVersions {
+    fetch_all_loaders_from(&LoaderEndpoints::default()).await
+}
+
+/// Like [`fetch_all_loaders`], against the given endpoints.
+pub async fn fetch_all_loaders_from(endpoints: &LoaderEndpoints) -> AllLoaderVersions {
+    let client = http::client();
+    let (vanilla, fabric, quilt, forge, neoforge) = tokio::join!(
+        VersionManifest::fetch_from_url(client, &endpoints.version_manifest),
+        FabricVersions::fetch_from(&endpoints.fabric_meta),
+        QuiltVersions::fetch_from_meta(client, &endpoints.quilt_meta),
+        ForgeVersions::fetch_from_url(client, &endpoints.forge_versions),
+        NeoForgeVersions::fetch_from_url(client, &endpoints.neoforge_versions),
+    );
+    AllLoaderVersions {
+        vanilla,
+        fabric,
+        quilt,
+        forge,
+        neoforge,
+    }
+}
+
+/// Compares game or loader version strings so that `1.20.10` sorts after `1.20.2`.
+///
+/// Versions are split on `.`, `-` and other separators, and each number is compared
+/// numerically. A pre-release suffix after the first `-` (`-beta.1`, `-rc1`, `-pre2`)
+/// sorts before the release it precedes, and `+` build metadata only breaks ties.
+/// Snapshot ids such as `24w14a` order among themselves by year, week and letter.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a, a_build) = a.split_once('+').unwrap_or((a, ""));
+    let (b, b_build) = b.split_once('+').unwrap_or((b, ""));
+    let (a_release, a_pre) = split_pre_release(a);
+    let (b_release, b_pre) = split_pre_release(b);
+
+    compare_parts(a_release, b_release)
+        .then_with(|| match (a_pre, b_pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => compare_parts(a, b),
+        })
+        .then_with(|| compare_parts(a_build, b_build))
+}
+
+fn split_pre_release(version: &str) -> (&str, Option<&str>) {
+    match version.split_once('-') {
+        Some((release, pre)) => (release, Some(pre)),
+        None => (version, None),
+    }
+}
+
+/// One run of digits or letters of a version string.
+#[derive(Debug)]
+enum Token<'a> {
+    Number(&'a str),
+    Text(&'a str),
+}
+
+impl Ord for Token<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            // Compared by digit count first so arbitrarily long numbers never overflow
+            (Token::Number(a), Token::Number(b)) => {
+                let (a, b) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+                a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+            }
+            (Token::Text(a), Token::Text(b)) => a.to_ascii_lowercase().cmp(&b.to_ascii_lowercase()),
+            (Token::Number(_), Token::Text(_)) => Ordering::Greater,
+            (Token::Text(_), Token::Number(_)) => Ordering::Less,
+        }
+    }
+}
+
+impl PartialEq for Token<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Token<'_> {}
+
+impl PartialOrd for Token<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn tokens(part: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in part.char_indices().chain(std::iter::once((part.len(), '.'))) {
+        if let Some(s) = start {
+            let run = &part[s..i];
+            let same_kind = c.is_ascii_alphanumeric() && c.is_ascii_digit() == run.as_bytes()[0].is_ascii_digit();
+            if same_kind {
+                continue;
+            }
+            tokens.push(if run.as_bytes()[0].is_ascii_digit() {
+                Token::Number(run)
+            } else {
+                Token::Text(run)
+            });
+            start = None;
+        }
+        if c.is_ascii_alphanumeric() {
+            start = Some(i);
+        }
+    }
+    tokens
+}
+
+/// Compares token by token; when one side runs out first, the longer one is greater.
+fn compare_parts(a: &str, b: &str) -> Ordering {
+    tokens(a).cmp(&tokens(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::serve_files;
+    #[tokio::test]
+    async fn test_fetch_all_loaders_tolerates_one_failure() {
+        let server = serve_files([
+            (
+                "/mc/manifest.json",
+                r#"{"latest": {"release": "1.20.4", "snapshot": "1.20.4"}, "versions": []}"#,
+            ),
+            (
+                "/fabric/v2/versions/",
+                r#"{"game": [{"version": "1.20.4", "stable": true}], "intermediary": [], "loader": [], "installer": []}"#,
+            ),
+            ("/quilt/v3/versions/game", r#"[{"version": "1.20.4", "stable": true}]"#),
+            (
+                "/quilt/v3/versions/loader",
+                r#"[{"version": "0.25.0", "maven": "org.quiltmc:quilt-loader:0.25.0"}]"#,
+            ),
+            ("/quilt/v3/versions/installer", r#"[]"#),
+            ("/neoforge/versions", r#"{"isSnapshot": false, "versions": ["20.4.237"]}"#),
+        ]);
+        let base_url = server.base_url();
+        let endpoints = LoaderEndpoints {
+            fabric_meta: format!("{}/fabric/", base_url),
+            quilt_meta: format!("{}/quilt", base_url),
+            // Not served, so Forge fails with a 404
+            forge_versions: format!("{}/forge/maven-metadata.json", base_url),
+            neoforge_versions: format!("{}/neoforge/versions", base_url),
+            version_manifest: format!("{}/mc/manifest.json", base_url),
+        };
+
+        let all = fetch_all_loaders_from(&endpoints).await;
+
+        assert_eq!(all.vanilla.as_ref().unwrap().latest.release, "1.20.4");
+        assert_eq!(all.fabric.as_ref().unwrap().game[0].version, "1.20.4");
+        assert_eq!(all.quilt.as_ref().unwrap().loader[0].version, "0.25.0");
+        assert_eq!(all.neoforge.as_ref().unwrap().versions, vec!["20.4.237".to_string()]);
+        assert!(matches!(all.forge, Err(LoaderError::NotFound { .. })), "{:?}", all.forge);
+
+        let failures = all.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, "forge");
+        assert!(!all.is_complete());
+    }
+
+    #[test]
+    fn test_compare_versions_numeric_segments() {
+        assert_eq!(compare_versions("1.20.2", "1.20.10"), Ordering::Less);
+        assert_eq!(compare_versions("0.15.11", "0.15.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.20", "1.20.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.20.4", "1.20.4"), Ordering::Equal);
+        assert_eq!(compare_versions("1.9", "1.10"), Ordering::Less);
+        assert_eq!(compare_versions("20.4.237", "20.4.80"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_compare_versions_pre_releases() {
+        assert_eq!(compare_versions("0.16.0-beta.1", "0.16.0"), Ordering::Less);
+        assert_eq!(compare_versions("0.16.0-beta.2", "0.16.0-beta.10"), Ordering::Less);
+        assert_eq!(compare_versions("0.16.0-beta.1", "0.16.0-rc.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.20.5-pre1", "1.20.5-rc1"), Ordering::Less);
+        assert_eq!(compare_versions("1.20.5-rc1", "1.20.5"), Ordering::Less);
+        assert_eq!(compare_versions("1.20.5-rc1", "1.20.4"), Ordering::Greater);
+        assert_eq!(compare_versions("20.4.80-beta", "20.4.237-beta"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_compare_versions_snapshots() {
+        assert_eq!(compare_versions("24w14a", "24w14b"), Ordering::Less);
+        assert_eq!(compare_versions("23w51b", "24w03a"), Ordering::Less);
+        assert_eq!(compare_versions("24w9a", "24w10a"), Ordering::Less);
+        assert_eq!(compare_versions("24w14potato", "24w14potato"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_compare_versions_build_metadata() {
+        assert_eq!(compare_versions("0.92.0+1.20.1", "0.92.0+1.20.1"), Ordering::Equal);
+        assert_eq!(compare_versions("0.92.0+1.20.1", "0.92.1+1.20.1"), Ordering::Less);
+        assert_eq!(compare_versions("0.92.0+1.20.1", "0.92.0+1.20.4"), Ordering::Less);
+        assert_eq!(compare_versions("0.92.0", "0.92.0+1.20.4"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_sorting_with_compare_versions() {
+        let mut versions = vec!["1.20.10", "1.20.2", "1.20.2-rc1", "1.20", "1.19.4"];
+        versions.sort_by(|a, b| compare_versions(a, b));
+        assert_eq!(versions, ["1.19.4", "1.20", "1.20.2-rc1", "1.20.2", "1.20.10"]);
+    }
+
+    #[test]
+    fn test_default_endpoints_are_official() {
+        let endpoints = LoaderEndpoints::default();
+        assert_eq!(endpoints.fabric_meta, "https://meta.fabricmc.net/");
+        assert_eq!(endpoints.quilt_meta, "https://meta.quiltmc.org/");
+        assert!(endpoints.version_manifest.starts_with("https://piston-meta.mojang.com/"));
+    }
+}
@@ -0,0 +1,68 @@
+//! Installs a Fabric server through the meta API: from a local mock serving a fixture
+//! launcher jar, and with the `network-tests` feature from the real service:
+//! ```
+//! cargo test --features network-tests --test fabric_server_install_test
+//! ```
+
+use minecraft_modloaders::fabric::{SERVER_LAUNCH_JAR, install_server_from};
+use std::io::{Read, Write};
+use std::path::Path;
+use test_support::serve_files;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+/// Checks that `jar` is a runnable jar: a zip whose manifest names a main class.
+fn assert_launch_jar(jar: &Path) {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(jar).unwrap()).expect("Launch jar is not a zip");
+    let mut manifest = String::new();
+    archive
+        .by_name("META-INF/MANIFEST.MF")
+        .expect("Launch jar has no manifest")
+        .read_to_string(&mut manifest)
+        .unwrap();
+    assert!(manifest.contains("Main-Class"), "{}", manifest);
+}
+
+fn fixture_launch_jar() -> Vec<u8> {
+    let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file("META-INF/MANIFEST.MF", SimpleFileOptions::default()).unwrap();
+    zip.write_all(b"Manifest-Version: 1.0\r\nMain-Class: net.fabricmc.installer.ServerLauncher\r\n")
+        .unwrap();
+    zip.start_file("fabric-server-launch.properties", SimpleFileOptions::default()).unwrap();
+    zip.write_all(b"launch.mainClass=net.fabricmc.loader.impl.launch.knot.KnotServer\n")
+        .unwrap();
+    zip.finish().unwrap().into_inner()
+}
+
+#[tokio::test]
+async fn test_install_server_from_fixture_mirror() {
+    let server = serve_files([("/v2/versions/loader/1.20.4/0.15.11/1.0.1/server/jar", fixture_launch_jar())]);
+
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("server");
+    let jar = install_server_from(&server.url("/"), "1.20.4", "0.15.11", "1.0.1", &dest)
+        .await
+        .expect("Failed to install Fabric server");
+    assert_eq!(jar, dest.join(SERVER_LAUNCH_JAR));
+    assert_launch_jar(&jar);
+
+    let missing = install_server_from(&server.url("/"), "1.20.4", "0.15.11", "9.9.9", &dest).await;
+    assert!(missing.is_err());
+}
+
+#[cfg(feature = "network-tests")]
+#[tokio::test]
+async fn test_install_server_produces_launch_jar() {
+    use minecraft_modloaders::fabric::{FabricVersions, install_server};
+
+    let versions = FabricVersions::fetch().await.expect("Failed to fetch Fabric versions");
+    let loader = versions.latest_stable_loader().expect("No stable loader");
+    let installer = versions.latest_installer().expect("No stable installer");
+
+    let dir = tempfile::tempdir().unwrap();
+    let jar = install_server("1.20.4", &loader.version, &installer.version, dir.path())
+        .await
+        .expect("Failed to install Fabric server");
+    assert_eq!(jar, dir.path().join(SERVER_LAUNCH_JAR));
+    assert_launch_jar(&jar);
+}
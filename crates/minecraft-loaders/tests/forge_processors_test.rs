@@ -0,0 +1,236 @@
+//! Runs the processors of a miniature Forge installer with a real JVM.
+//!
+//! The installer mirrors the layout of a 1.20.1 one (an `install_profile.json` with
+//! sided data, a server-only and a client processor, a library downloaded from a
+//! maven and one embedded under `maven/`), but its processor is a few lines of Java
+//! compiled at test time, so the test needs neither the network nor a real patch.
+//! Needs a JDK on PATH, so it only builds with the `java-tests` feature:
+//! ```
+//! cargo test --features java-tests --test forge_processors_test
+//! ```
+#![cfg(feature = "java-tests")]
+
+use minecraft_modloaders::download::sha1_hex;
+use minecraft_modloaders::forge::{ForgeInstaller, ProcessorRunner};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use test_support::{MockServer, serve_files};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+const SUFFIX_JAVA: &str = r#"
+package fixture;
+
+public class Suffix {
+    public static String value() {
+        return "+patched";
+    }
+}
+"#;
+
+/// Writes `--input`, `--patch` and `--side` into `--output`, so the output shows both
+/// that the arguments were mapped and that the classpath (for `Suffix`) was set.
+const PATCH_TASK_JAVA: &str = r#"
+package fixture;
+
+import java.nio.file.*;
+import java.util.*;
+
+public class PatchTask {
+    public static void main(String[] args) throws Exception {
+        Map<String, String> options = new HashMap<>();
+        for (int i = 0; i + 1 < args.length; i += 2) {
+            options.put(args[i], args[i + 1]);
+        }
+        String input = Files.readString(Path.of(options.get("--input")));
+        String patch = Files.readString(Path.of(options.get("--patch")));
+        Path output = Path.of(options.get("--output"));
+        Files.createDirectories(output.getParent());
+        Files.writeString(output, input + patch + options.get("--side") + Suffix.value());
+    }
+}
+"#;
+
+const VANILLA_JAR: &[u8] = b"vanilla";
+const BINPATCH: &[u8] = b"+binpatch";
+const EXPECTED_OUTPUT: &str = "vanilla+binpatchclient+patched";
+const PATCH_TASK_PATH: &str = "fixture/patch-task/1.0/patch-task-1.0.jar";
+const SUFFIX_PATH: &str = "fixture/suffix/1.0/suffix-1.0.jar";
+const PATCHED_PATH: &str = "net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-client.jar";
+
+fn zip_bytes(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, contents) in entries {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(contents).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+/// Compiles the fixture classes and returns the processor jar and the jar it needs
+/// on its classpath.
+fn compile_jars(dir: &Path) -> (Vec<u8>, Vec<u8>) {
+    let sources = dir.join("src");
+    let classes = dir.join("classes");
+    std::fs::create_dir_all(&sources).unwrap();
+    std::fs::write(sources.join("Suffix.java"), SUFFIX_JAVA).unwrap();
+    std::fs::write(sources.join("PatchTask.java"), PATCH_TASK_JAVA).unwrap();
+    let status = Command::new("javac")
+        .arg("-d")
+        .arg(&classes)
+        .arg(sources.join("Suffix.java"))
+        .arg(sources.join("PatchTask.java"))
+        .status()
+        .expect("javac not found on PATH");
+    assert!(status.success(), "javac failed");
+
+    let class = |name: &str| std::fs::read(classes.join("fixture").join(name)).unwrap();
+    let patch_task = zip_bytes(&[
+        ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nMain-Class: fixture.PatchTask\r\n"),
+        ("fixture/PatchTask.class", &class("PatchTask.class")),
+    ]);
+    let suffix = zip_bytes(&[("fixture/Suffix.class", &class("Suffix.class"))]);
+    (patch_task, suffix)
+}
+
+struct Fixture {
+    dir: tempfile::TempDir,
+    server: MockServer,
+    installer: PathBuf,
+    libraries: PathBuf,
+    minecraft_jar: PathBuf,
+}
+
+/// An installer whose client processor is expected to write output with `patched_sha`.
+fn fixture(patched_sha: &str) -> Fixture {
+    let dir = tempfile::tempdir().unwrap();
+    let (patch_task, suffix) = compile_jars(dir.path());
+    let server = serve_files([(format!("/{}", PATCH_TASK_PATH), patch_task.clone())]);
+    let base = server.base_url();
+
+    let patch_args = |output: &str| {
+        serde_json::json!([
+            "--input",
+            "{MINECRAFT_JAR}",
+            "--patch",
+            "{BINPATCH}",
+            "--side",
+            "{SIDE}",
+            "--output",
+            output
+        ])
+    };
+    let profile = serde_json::json!({
+        "spec": 1,
+        "profile": "forge",
+        "version": "1.20.1-forge-47.2.0",
+        "minecraft": "1.20.1",
+        "json": "/version.json",
+        "data": {
+            "PATCHED": {"client": "[net.minecraftforge:forge:1.20.1-47.2.0:client]", "server": "[net.minecraftforge:forge:1.20.1-47.2.0:server]"},
+            "PATCHED_SHA": {"client": format!("'{}'", patched_sha), "server": "'0000000000000000000000000000000000000000'"},
+            "BINPATCH": {"client": "/data/client.txt", "server": "/data/server.txt"}
+        },
+        "processors": [
+            {
+                "sides": ["server"],
+                "jar": "fixture:patch-task:1.0",
+                "classpath": ["fixture:suffix:1.0"],
+                "args": patch_args("{ROOT}/server-only.txt")
+            },
+            {
+                "sides": ["client"],
+                "jar": "fixture:patch-task:1.0",
+                "classpath": ["fixture:suffix:1.0"],
+                "args": patch_args("{PATCHED}"),
+                "outputs": {"{PATCHED}": "{PATCHED_SHA}"}
+            }
+        ],
+        "libraries": [
+            {
+                "name": "fixture:patch-task:1.0",
+                "downloads": {"artifact": {
+                    "path": PATCH_TASK_PATH,
+                    "url": format!("{}/{}", base, PATCH_TASK_PATH),
+                    "sha1": sha1_hex(&patch_task),
+                    "size": patch_task.len()
+                }}
+            },
+            {
+                "name": "fixture:suffix:1.0",
+                "downloads": {"artifact": {"path": SUFFIX_PATH, "url": "", "sha1": sha1_hex(&suffix), "size": suffix.len()}}
+            }
+        ]
+    });
+    let installer = dir.path().join("forge-1.20.1-47.2.0-installer.jar");
+    std::fs::write(
+        &installer,
+        zip_bytes(&[
+            ("install_profile.json", profile.to_string().as_bytes()),
+            ("version.json", br#"{"id": "1.20.1-forge-47.2.0", "inheritsFrom": "1.20.1"}"#),
+            ("data/client.txt", BINPATCH),
+            (&format!("maven/{}", SUFFIX_PATH), &suffix),
+        ]),
+    )
+    .unwrap();
+
+    let minecraft_jar = dir.path().join("minecraft/versions/1.20.1/1.20.1.jar");
+    std::fs::create_dir_all(minecraft_jar.parent().unwrap()).unwrap();
+    std::fs::write(&minecraft_jar, VANILLA_JAR).unwrap();
+    Fixture {
+        libraries: dir.path().join("minecraft/libraries"),
+        dir,
+        server,
+        installer,
+        minecraft_jar,
+    }
+}
+
+#[tokio::test]
+async fn test_runs_client_processors_once() {
+    let fixture = fixture(&sha1_hex(EXPECTED_OUTPUT.as_bytes()));
+    let installer = ForgeInstaller::open(&fixture.installer).unwrap();
+    assert_eq!(installer.version_json().unwrap()["inheritsFrom"], "1.20.1");
+    let runner = ProcessorRunner::new(&installer, &fixture.libraries, &fixture.minecraft_jar, "java");
+
+    let report = runner.run().await.expect("Failed to run processors");
+    assert_eq!(report.ran, ["fixture:patch-task:1.0"]);
+    assert!(report.skipped.is_empty());
+    assert_eq!(std::fs::read_to_string(fixture.libraries.join(PATCHED_PATH)).unwrap(), EXPECTED_OUTPUT);
+    assert!(fixture.libraries.join(SUFFIX_PATH).is_file());
+    assert!(!fixture.dir.path().join("minecraft/server-only.txt").exists());
+    assert_eq!(fixture.server.hits(), 1);
+
+    let report = runner.run().await.expect("Failed to rerun processors");
+    assert!(report.ran.is_empty());
+    assert_eq!(report.skipped, ["fixture:patch-task:1.0"]);
+    assert_eq!(fixture.server.hits(), 1, "the processor jar was downloaded again");
+}
+
+#[tokio::test]
+async fn test_output_hash_mismatch_fails_and_removes_output() {
+    let fixture = fixture(&sha1_hex(b"something else"));
+    let installer = ForgeInstaller::open(&fixture.installer).unwrap();
+
+    let error = ProcessorRunner::new(&installer, &fixture.libraries, &fixture.minecraft_jar, "java")
+        .run()
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", error).contains("expected SHA-1"), "{:#}", error);
+    assert!(!fixture.libraries.join(PATCHED_PATH).exists());
+}
+
+#[tokio::test]
+async fn test_missing_minecraft_jar_fails_before_downloading() {
+    let fixture = fixture(&sha1_hex(EXPECTED_OUTPUT.as_bytes()));
+    std::fs::remove_file(&fixture.minecraft_jar).unwrap();
+    let installer = ForgeInstaller::open(&fixture.installer).unwrap();
+
+    let error = ProcessorRunner::new(&installer, &fixture.libraries, &fixture.minecraft_jar, "java")
+        .run()
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("client jar"), "{}", error);
+    assert_eq!(fixture.server.hits(), 0);
+}
@@ -108,12 +108,12 @@ async fn test_fabric_server_full_workflow() {
     // 1. Get latest Fabric versions
     let versions = FabricVersions::fetch().await.expect("Failed to fetch Fabric versions");
     let mc_version = versions
-        .get_latest_game_version()
+        .latest_stable_game()
         .expect("No game version found")
         .version
         .clone();
     let loader_version = versions
-        .get_latest_loader()
+        .latest_stable_loader()
         .expect("No loader version found")
         .version
         .clone();
@@ -174,12 +174,12 @@ async fn test_fabric_server_install_workflow() {
     // Get versions
     let versions = FabricVersions::fetch().await.expect("Failed to fetch versions");
     let mc_version = versions
-        .get_latest_game_version()
+        .latest_stable_game()
         .expect("No game version")
         .version
         .clone();
     let loader_version = versions
-        .get_latest_loader()
+        .latest_stable_loader()
         .expect("No loader")
         .version
         .clone();
@@ -223,12 +223,12 @@ async fn test_fabric_client_full_workflow() {
     // Get versions
     let versions = FabricVersions::fetch().await.expect("Failed to fetch versions");
     let mc_version = versions
-        .get_latest_game_version()
+        .latest_stable_game()
         .expect("No game version")
         .version
         .clone();
     let loader_version = versions
-        .get_latest_loader()
+        .latest_stable_loader()
         .expect("No loader")
         .version
         .clone();
@@ -463,8 +463,8 @@ async fn test_fabric_version_fetch() {
     assert!(!versions.loader.is_empty(), "No loader versions");
     assert!(!versions.installer.is_empty(), "No installer versions");
 
-    let latest_game = versions.get_latest_game_version().expect("No latest game");
-    let latest_loader = versions.get_latest_loader().expect("No latest loader");
+    let latest_game = versions.latest_stable_game().expect("No latest game");
+    let latest_loader = versions.latest_stable_loader().expect("No latest loader");
 
     println!("Fabric - Latest MC: {}, Loader: {}", latest_game.version, latest_loader.version);
 }
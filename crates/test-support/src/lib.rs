@@ -0,0 +1,242 @@
+//! A local HTTP server for the workspace's tests that go through real requests.
+//!
+//! Each connection is served on its own thread and closed after one response, so it
+//! works the same under blocking and async clients.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A request as the mock server received it.
+#[derive(Debug, Clone)]
+pub struct Request {
+    /// How many connections the server accepted before this one.
+    pub index: usize,
+    pub method: String,
+    pub path: String,
+    /// The request line and headers, as sent.
+    pub head: String,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.head.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    }
+
+    pub fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// What the mock server answers a request with. `Content-Length` and
+/// `Connection: close` are added, and the body is left out for `HEAD` requests.
+#[derive(Debug, Clone)]
+pub struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    raw: Option<Vec<u8>>,
+    cut_short: Option<usize>,
+    held_open: Option<Duration>,
+}
+
+impl Response {
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            raw: None,
+            cut_short: None,
+            held_open: None,
+        }
+    }
+
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self::status(200).with_body(body)
+    }
+
+    pub fn not_found() -> Self {
+        Self::status(404)
+    }
+
+    /// Bytes sent as they are, for cut-off or otherwise hand-made responses.
+    pub fn raw(bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            raw: Some(bytes.into()),
+            ..Self::status(200)
+        }
+    }
+
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    /// Close the connection after `len` bytes of the body, though `Content-Length`
+    /// announces all of it.
+    pub fn cut_short(mut self, len: usize) -> Self {
+        self.cut_short = Some(len);
+        self
+    }
+
+    /// Keep the connection open for `duration` after responding, like a stalled server.
+    pub fn held_open(mut self, duration: Duration) -> Self {
+        self.held_open = Some(duration);
+        self
+    }
+
+    fn to_bytes(&self, head_only: bool) -> Vec<u8> {
+        if let Some(raw) = &self.raw {
+            return raw.clone();
+        }
+        let mut bytes = format!("HTTP/1.1 {} {}\r\n", self.status, reason(self.status)).into_bytes();
+        for (name, value) in &self.headers {
+            bytes.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+        }
+        bytes.extend_from_slice(format!("Content-Length: {}\r\nConnection: close\r\n\r\n", self.body.len()).as_bytes());
+        if !head_only {
+            let len = self.cut_short.unwrap_or(self.body.len()).min(self.body.len());
+            bytes.extend_from_slice(&self.body[..len]);
+        }
+        bytes
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        206 => "Partial Content",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Mock",
+    }
+}
+
+/// A server started by [`serve`], running until the test ends.
+pub struct MockServer {
+    base_url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// `http://127.0.0.1:<port>`, without a trailing slash.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// The URL of `path`, which starts with `/`.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Every request so far, in the order they arrived.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn paths(&self) -> Vec<String> {
+        self.requests.lock().unwrap().iter().map(|request| request.path.clone()).collect()
+    }
+
+    pub fn hits(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+}
+
+/// Answers every request with `handler` on a local port, one thread per connection.
+pub fn serve(handler: impl Fn(&Request) -> Response + Send + Sync + 'static) -> MockServer {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    let handler = Arc::new(handler);
+    std::thread::spawn(move || {
+        for (index, stream) in listener.incoming().enumerate() {
+            let Ok(mut stream) = stream else { break };
+            let recorded = recorded.clone();
+            let handler = handler.clone();
+            std::thread::spawn(move || {
+                let Some(request) = read_request(&mut stream, index) else { return };
+                recorded.lock().unwrap().push(request.clone());
+                let response = handler(&request);
+                let _ = stream.write_all(&response.to_bytes(request.method == "HEAD"));
+                let _ = stream.flush();
+                if let Some(duration) = response.held_open {
+                    std::thread::sleep(duration);
+                }
+            });
+        }
+    });
+    MockServer { base_url, requests }
+}
+
+/// Serves `files` by path and answers 404 for anything else.
+pub fn serve_files<P: Into<String>, B: Into<Vec<u8>>>(files: impl IntoIterator<Item = (P, B)>) -> MockServer {
+    let files: HashMap<String, Vec<u8>> = files.into_iter().map(|(path, body)| (path.into(), body.into())).collect();
+    serve(move |request| match files.get(&request.path) {
+        Some(body) => Response::ok(body.clone()),
+        None => Response::not_found(),
+    })
+}
+
+/// Answers the n-th connection with the n-th response, whatever it asks for, and
+/// repeats the last one once they run out.
+pub fn serve_sequence(responses: Vec<Response>) -> MockServer {
+    serve(move |request| responses[request.index.min(responses.len() - 1)].clone())
+}
+
+fn read_request(stream: &mut TcpStream, index: usize) -> Option<Request> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut chunk).ok()?;
+        buf.extend_from_slice(&chunk[..read]);
+        if let Some(pos) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if read == 0 {
+            return None;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..header_end]).into_owned();
+    let mut request = Request {
+        index,
+        method: head.split_whitespace().next().unwrap_or("").to_string(),
+        path: head.split_whitespace().nth(1).unwrap_or("/").to_string(),
+        head,
+        body: Vec::new(),
+    };
+    // Read the body too, so that closing the connection does not reset it.
+    let length = request
+        .header("content-length")
+        .and_then(|length| length.parse::<usize>().ok())
+        .unwrap_or(0);
+    while buf.len() < header_end + length {
+        let read = stream.read(&mut chunk).ok()?;
+        if read == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    }
+    request.body = buf.split_off(header_end);
+    Some(request)
+}